@@ -11,6 +11,9 @@ fn main() {
         flop: flop_from_str("Td9d6h").unwrap(),
         turn: card_from_str("Qc").unwrap(),
         river: NOT_DEALT,
+        dead_cards: 0,
+        short_deck: false,
+        positions: [None; 2],
     };
 
     // bet sizes -> 60% of the pot, geometric size, and all-in
@@ -33,6 +36,9 @@ fn main() {
         force_allin_threshold: 0.15, // force all-in if (SPR after the opponent's call) <= 0.15
         merging_threshold: 0.1,
         icm_config: None,
+        rake_config: None,
+        sizing_rules: Vec::new(),
+        bomb_pot: None,
     };
 
     // build the game tree
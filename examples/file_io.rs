@@ -11,6 +11,9 @@ fn main() {
         flop: flop_from_str("Td9d6h").unwrap(),
         turn: card_from_str("Qc").unwrap(),
         river: NOT_DEALT,
+        dead_cards: 0,
+        short_deck: false,
+        positions: [None; 2],
     };
 
     let bet_sizes = BetSizeOptions::try_from(("60%, e, a", "2.5x")).unwrap();
@@ -30,6 +33,9 @@ fn main() {
         force_allin_threshold: 0.15,
         merging_threshold: 0.1,
         icm_config: None,
+        rake_config: None,
+        sizing_rules: Vec::new(),
+        bomb_pot: None,
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
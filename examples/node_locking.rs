@@ -11,6 +11,9 @@ fn normal_node_locking() {
         flop: flop_from_str("2s3h4d").unwrap(),
         turn: card_from_str("6c").unwrap(),
         river: card_from_str("7c").unwrap(),
+        dead_cards: 0,
+        short_deck: false,
+        positions: [None; 2],
     };
 
     let tree_config = TreeConfig {
@@ -62,6 +65,9 @@ fn partial_node_locking() {
         flop: flop_from_str("2s3h4d").unwrap(),
         turn: card_from_str("6c").unwrap(),
         river: card_from_str("7c").unwrap(),
+        dead_cards: 0,
+        short_deck: false,
+        positions: [None; 2],
     };
 
     let tree_config = TreeConfig {
@@ -0,0 +1,64 @@
+use postflop_solver::*;
+use std::time::{Duration, Instant};
+
+// Measures the time spent evaluating the river terminal nodes of a turn spot with wide ranges.
+// Run with `cargo run --release --example showdown_benchmark --features profiling`.
+//
+// The iterations are timed in rounds, and the fastest round is reported to filter out the noise.
+fn main() {
+    let num_rounds = 10;
+    let num_iterations = 10;
+
+    for (rake_rate, rake_cap) in [(0.0, 0.0), (0.05, 30.0)] {
+        let game = wide_turn_game(rake_rate, rake_cap);
+        let mut best_iteration = Duration::MAX;
+        let mut best_evaluation = Duration::MAX;
+
+        for round in 0..num_rounds {
+            reset_profiling();
+            let start = Instant::now();
+            for t in 0..num_iterations {
+                solve_step(&game, round * num_iterations + t);
+            }
+            best_iteration = best_iteration.min(start.elapsed() / num_iterations);
+            best_evaluation =
+                best_evaluation.min(profiling_report().evaluation[2] / num_iterations);
+        }
+
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        println!("rake_rate = {rake_rate}, rake_cap = {rake_cap}:");
+        println!("  iteration:        {:8.3} ms", ms(best_iteration));
+        println!("  river evaluation: {:8.3} ms", ms(best_evaluation));
+    }
+}
+
+fn wide_turn_game(rake_rate: f64, rake_cap: f64) -> PostFlopGame {
+    let card_config = CardConfig {
+        range: ["22+,A2+,K2+,Q2+,J2+,T2+,92+,82+,72+,62+,52+,42+,32"
+            .parse()
+            .unwrap(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: NOT_DEALT,
+        dead_cards: 0,
+        short_deck: false,
+        positions: [None; 2],
+    };
+
+    let bet_sizes = BetSizeOptions::try_from(("50%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 400,
+        rake_rate,
+        rake_cap,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    game
+}
@@ -0,0 +1,93 @@
+/*
+ * C API of postflop-solver (built with the `ffi` feature).
+ *
+ * The crate builds an rlib only; build the shared library with
+ *   cargo rustc --release --lib --crate-type cdylib --features ffi
+ *
+ * This header must be kept in sync with `src/ffi.rs`, which documents each function in detail.
+ */
+
+#ifndef POSTFLOP_SOLVER_H
+#define POSTFLOP_SOLVER_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum PfsStatus {
+  PFS_OK = 0,
+  PFS_NULL_POINTER = 1,
+  PFS_INVALID_ARGUMENT = 2,
+  PFS_INVALID_STATE = 3,
+  PFS_BUFFER_TOO_SMALL = 4,
+  PFS_PANIC = 5,
+  PFS_ABORTED = 6,
+} PfsStatus;
+
+typedef struct PfsTreeConfig PfsTreeConfig;
+typedef struct PfsGame PfsGame;
+
+/* Returning false aborts the solving. */
+typedef bool (*PfsProgressCallback)(void *user_data, uint32_t iteration, float exploitability);
+
+/* Errors */
+size_t pfs_last_error_len(void);
+PfsStatus pfs_last_error(char *buf, size_t len);
+
+/* Tree configuration (street: 0 = flop, 1 = turn, 2 = river; player: 0 = OOP, 1 = IP) */
+PfsTreeConfig *pfs_tree_config_new(int32_t starting_pot, int32_t effective_stack);
+void pfs_tree_config_free(PfsTreeConfig *config);
+PfsStatus pfs_tree_config_set_rake(PfsTreeConfig *config, double rate, double cap);
+PfsStatus pfs_tree_config_set_bet_sizes(PfsTreeConfig *config, uint32_t street, uint32_t player,
+                                        const char *bet, const char *raise);
+PfsStatus pfs_tree_config_set_donk_sizes(PfsTreeConfig *config, uint32_t street,
+                                         const char *donk);
+
+/* Game construction and solving */
+PfsStatus pfs_game_new(const char *oop_range, const char *ip_range, const char *board,
+                       const PfsTreeConfig *config, PfsGame **out);
+void pfs_game_free(PfsGame *game);
+PfsStatus pfs_game_memory_usage(const PfsGame *game, uint64_t *uncompressed,
+                                uint64_t *compressed);
+PfsStatus pfs_game_allocate_memory(PfsGame *game, bool enable_compression);
+PfsStatus pfs_game_solve(PfsGame *game, uint32_t max_num_iterations, float target_exploitability,
+                         PfsProgressCallback progress, void *user_data, float *exploitability);
+PfsStatus pfs_game_solve_step(PfsGame *game, uint32_t current_iteration);
+PfsStatus pfs_game_exploitability(const PfsGame *game, float *exploitability);
+PfsStatus pfs_game_finalize(PfsGame *game);
+
+/* Navigation */
+PfsStatus pfs_game_back_to_root(PfsGame *game);
+PfsStatus pfs_game_apply_history(PfsGame *game, const uint32_t *history, size_t len);
+PfsStatus pfs_game_play(PfsGame *game, uint32_t action);
+bool pfs_game_is_terminal_node(const PfsGame *game);
+bool pfs_game_is_chance_node(const PfsGame *game);
+int32_t pfs_game_current_player(const PfsGame *game);
+size_t pfs_game_num_actions(const PfsGame *game);
+PfsStatus pfs_game_action_name(const PfsGame *game, size_t index, char *buf, size_t len);
+PfsStatus pfs_game_possible_cards(const PfsGame *game, uint64_t *mask);
+PfsStatus pfs_game_current_board(const PfsGame *game, uint8_t *buf, size_t len,
+                                 size_t *num_cards);
+
+/* Queries (buffers are flat arrays; see `pfs_game_strategy` for the layout of strategies) */
+size_t pfs_game_num_private_hands(const PfsGame *game, uint32_t player);
+PfsStatus pfs_game_private_cards(const PfsGame *game, uint32_t player, uint8_t *buf, size_t len);
+PfsStatus pfs_game_cache_normalized_weights(PfsGame *game);
+PfsStatus pfs_game_weights(const PfsGame *game, uint32_t player, float *buf, size_t len);
+PfsStatus pfs_game_normalized_weights(const PfsGame *game, uint32_t player, float *buf,
+                                      size_t len);
+PfsStatus pfs_game_equity(const PfsGame *game, uint32_t player, float *buf, size_t len);
+PfsStatus pfs_game_expected_values(const PfsGame *game, uint32_t player, float *buf, size_t len);
+PfsStatus pfs_game_strategy(const PfsGame *game, float *buf, size_t len);
+PfsStatus pfs_game_lock_current_strategy(PfsGame *game, const float *strategy, size_t len);
+PfsStatus pfs_game_unlock_current_strategy(PfsGame *game);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* POSTFLOP_SOLVER_H */
@@ -1,9 +1,23 @@
 use crate::bet_size::*;
+use crate::bomb_pot::*;
 use crate::card::*;
-use crate::mutex_like::*;
+use crate::error::*;
+use crate::profiling::*;
+use crate::range::*;
+use std::fmt;
+use std::mem;
+use std::ops::Range as IndexRange;
 
 #[cfg(feature = "bincode")]
-use bincode::{Decode, Encode};
+use bincode::{
+    Decode, Encode,
+    de::Decoder,
+    enc::Encoder,
+    error::{DecodeError, EncodeError},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 pub(crate) const PLAYER_OOP: u8 = 0;
 pub(crate) const PLAYER_IP: u8 = 1;
@@ -14,8 +28,16 @@ pub(crate) const PLAYER_TERMINAL_FLAG: u8 = 8;
 pub(crate) const PLAYER_FOLD_FLAG: u8 = 24;
 
 /// Available actions of the postflop game.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+//
+// `repr(u8)` is required by `PostFlopNode::zeroed_arena`: for a data-carrying enum, only a
+// primitive representation defines the layout (a `u8` tag followed by the payload, with the
+// discriminants numbered from zero in declaration order), so the all-zero bit pattern is
+// guaranteed to be `None`. The size (8 bytes) and the API are the same as with the default
+// representation, and none of the encodings depends on it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[repr(u8)]
 pub enum Action {
     /// (Default value)
     #[default]
@@ -43,10 +65,111 @@ pub enum Action {
     Chance(Card),
 }
 
+impl fmt::Display for Action {
+    /// Formats the action in a human-readable form.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// assert_eq!(Action::Check.to_string(), "Check");
+    /// assert_eq!(Action::Bet(120).to_string(), "Bet 120");
+    /// assert_eq!(Action::AllIn(900).to_string(), "All-in 900");
+    /// assert_eq!(Action::Chance(51).to_string(), "As");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::None => write!(f, "None"),
+            Self::Fold => write!(f, "Fold"),
+            Self::Check => write!(f, "Check"),
+            Self::Call => write!(f, "Call"),
+            Self::Bet(amount) => write!(f, "Bet {amount}"),
+            Self::Raise(amount) => write!(f, "Raise {amount}"),
+            Self::AllIn(amount) => write!(f, "All-in {amount}"),
+            Self::Chance(card) => match card_to_string(card) {
+                Ok(s) => write!(f, "{s}"),
+                Err(_) => write!(f, "Chance({card})"),
+            },
+        }
+    }
+}
+
+/// Converts a line (sequence of actions) into a compact, stable identifier.
+///
+/// Each action is encoded as `F` (fold), `X` (check), `C` (call), `B<amount>` (bet),
+/// `R<amount>` (raise), `A<amount>` (all-in), or the card string (chance), and the codes are
+/// joined with `:`. The empty line (i.e., the root node) is encoded as an empty string.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let line = [Action::Check, Action::Bet(50), Action::Call, Action::Chance(51)];
+/// assert_eq!(line_to_string(&line), "X:B50:C:As");
+/// ```
+pub fn line_to_string(line: &[Action]) -> String {
+    line.iter()
+        .map(|&action| match action {
+            Action::None => "-".to_owned(),
+            Action::Fold => "F".to_owned(),
+            Action::Check => "X".to_owned(),
+            Action::Call => "C".to_owned(),
+            Action::Bet(amount) => format!("B{amount}"),
+            Action::Raise(amount) => format!("R{amount}"),
+            Action::AllIn(amount) => format!("A{amount}"),
+            Action::Chance(_) => action.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Converts a line (sequence of actions) into a PioSOLVER node ID.
+///
+/// The node ID starts with `r:0` (the root node), followed by `c` (check or call), `f` (fold),
+/// `b<amount>` (bet, raise, or all-in), or the card string (chance), joined with `:`. Unlike
+/// [`line_to_string`], the amount of `b<amount>` is the total amount the player has put in the pot
+/// since the root node (i.e., including the previous streets), as in PioSOLVER.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let line = [Action::Bet(50), Action::Call, Action::Chance(51), Action::Check, Action::Bet(100)];
+/// assert_eq!(line_to_pio_node_id(&line), "r:0:b50:c:As:c:b150");
+/// ```
+pub fn line_to_pio_node_id(line: &[Action]) -> String {
+    let mut node_id = "r:0".to_owned();
+    let mut prev_streets = 0;
+    let mut street_amount = 0;
+
+    for &action in line {
+        let code = match action {
+            Action::None => "-".to_owned(),
+            Action::Fold => "f".to_owned(),
+            Action::Check | Action::Call => "c".to_owned(),
+            Action::Bet(amount) | Action::Raise(amount) | Action::AllIn(amount) => {
+                street_amount = amount;
+                format!("b{}", prev_streets + amount)
+            }
+            Action::Chance(_) => {
+                prev_streets += street_amount;
+                street_amount = 0;
+                action.to_string()
+            }
+        };
+        node_id.push(':');
+        node_id.push_str(&code);
+    }
+
+    node_id
+}
+
 /// An enum representing the board state.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum BoardState {
     #[default]
     Flop = 0,
@@ -68,6 +191,131 @@ pub struct ICMConfig {
     pub payout_structure: Vec<i32>,
 }
 
+/// Rake configuration that depends on the street on which the hand ends.
+///
+/// If set in [`TreeConfig::rake_config`], it overrides [`TreeConfig::rake_rate`] and
+/// [`TreeConfig::rake_cap`]. A hand ends on the street of the fold, or on the river if it goes to
+/// showdown (including when the players are all-in before the river). Since every hand of a
+/// postflop game sees the flop, "no flop, no drop" is always satisfied.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// // 5% rake capped at 1bb on the flop and 3bb after the flop, where 1bb = 20 chips
+/// let rake_config = RakeConfig::with_caps_in_big_blinds([0.05; 3], [1.0, 3.0, 3.0], 20.0);
+/// assert_eq!(rake_config.caps, [20.0, 60.0, 60.0]);
+/// assert_eq!(rake_config.rake(1000.0, BoardState::Flop), 20.0);
+/// assert_eq!(rake_config.rake(1000.0, BoardState::River), 50.0);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct RakeConfig {
+    /// Rake rates of the hands ending on the flop, turn, and river, respectively. Each must be
+    /// between `0.0` and `1.0`, inclusive.
+    pub rates: [f64; 3],
+
+    /// Rake caps of the hands ending on the flop, turn, and river, respectively. Each must be
+    /// non-negative.
+    pub caps: [f64; 3],
+}
+
+impl RakeConfig {
+    /// Creates a configuration with the caps given in big blinds of `big_blind` chips.
+    #[inline]
+    pub fn with_caps_in_big_blinds(rates: [f64; 3], caps: [f64; 3], big_blind: f64) -> Self {
+        Self {
+            rates,
+            caps: caps.map(|cap| cap * big_blind),
+        }
+    }
+
+    /// Returns the rake taken from `pot` when the hand ends on `street`.
+    #[inline]
+    pub fn rake(&self, pot: f64, street: BoardState) -> f64 {
+        let street = street as usize;
+        (pot * self.rates[street]).min(self.caps[street])
+    }
+
+    /// Returns whether any hand is raked.
+    #[inline]
+    pub fn is_raked(&self) -> bool {
+        (0..3).any(|street| self.rates[street] > 0.0 && self.caps[street] > 0.0)
+    }
+}
+
+/// Bet sizing rule that replaces the bet size options when the pot is large relative to the
+/// remaining stack.
+///
+/// A rule matches a decision node on `street` (any street if `None`) when the pot exceeds
+/// `pot_to_stack_ratio` times the remaining effective stack, both measured after the player calls
+/// the previous bet. The first matching rule in [`TreeConfig::sizing_rules`] replaces the bet and
+/// raise sizes of the acting player, as well as the donk sizes, with `bet_sizes`. The all-in
+/// thresholds and the merging of close bet sizes still apply.
+///
+/// Since the condition is evaluated for each node during the tree construction, the rules keep
+/// deep trees tractable while preserving realistic sizes in bloated pots, which cannot be expressed
+/// with the static options of each street.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// // if the pot exceeds 50% of the remaining effective stack, offer only 60% pot and all-in
+/// let rule = SizingRule {
+///     street: None,
+///     pot_to_stack_ratio: 0.5,
+///     bet_sizes: ("60%, a", "a").try_into().unwrap(),
+/// };
+///
+/// let tree_config = TreeConfig {
+///     initial_state: BoardState::Turn,
+///     starting_pot: 100,
+///     effective_stack: 300,
+///     turn_bet_sizes: [("33%, 75%, 150%", "").try_into().unwrap(), Default::default()],
+///     river_bet_sizes: [("33%, 75%, 150%", "").try_into().unwrap(), Default::default()],
+///     sizing_rules: vec![rule],
+///     ..Default::default()
+/// };
+///
+/// let mut action_tree = ActionTree::new(tree_config).unwrap();
+/// assert_eq!(
+///     action_tree.available_actions(),
+///     &[Action::Check, Action::Bet(33), Action::Bet(75), Action::Bet(150)]
+/// );
+///
+/// // the pot is 250 and the remaining stack is 225 after a 75 bet is called
+/// action_tree.play(Action::Bet(75)).unwrap();
+/// action_tree.play(Action::Call).unwrap();
+/// assert_eq!(
+///     action_tree.available_actions(),
+///     &[Action::Check, Action::Bet(150), Action::AllIn(225)]
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct SizingRule {
+    /// Street to which the rule applies (set `None` to apply to all streets).
+    pub street: Option<BoardState>,
+
+    /// The rule matches when the pot exceeds this ratio of the remaining effective stack. Must be
+    /// non-negative.
+    pub pot_to_stack_ratio: f64,
+
+    /// Bet size options that replace the options of the street when the rule matches.
+    pub bet_sizes: BetSizeOptions,
+}
+
+impl SizingRule {
+    /// Returns whether the rule matches a node on `street` with `pot` and the remaining effective
+    /// `stack`.
+    #[inline]
+    pub fn matches(&self, street: BoardState, pot: i32, stack: i32) -> bool {
+        self.street.is_none_or(|s| s == street)
+            && pot as f64 > self.pot_to_stack_ratio * stack as f64
+    }
+}
+
 /// A struct containing the game tree configuration.
 ///
 /// # Examples
@@ -92,6 +340,9 @@ pub struct ICMConfig {
 ///     force_allin_threshold: 0.15,
 ///     merging_threshold: 0.1,
 ///     icm_config: None,
+///     rake_config: None,
+///     sizing_rules: Vec::new(),
+///     bomb_pot: None,
 /// };
 /// ```
 #[derive(Debug, Clone, Default)]
@@ -149,7 +400,45 @@ pub struct TreeConfig {
 
     /// ICM configuration
     /// If set, enables ICM calculation instead of ChipEV
+    ///
+    /// It can be used together with the bunching effect (see
+    /// [`PostFlopGame::set_bunching_effect`]).
+    ///
+    /// [`PostFlopGame::set_bunching_effect`]: crate::PostFlopGame::set_bunching_effect
     pub icm_config: Option<ICMConfig>,
+
+    /// Rake configuration by street (set `None` to use `rake_rate` and `rake_cap` for all
+    /// streets).
+    pub rake_config: Option<RakeConfig>,
+
+    /// Bet sizing rules evaluated for each node, in order (see [`SizingRule`]). The first matching
+    /// rule overrides the bet size options above.
+    pub sizing_rules: Vec<SizingRule>,
+
+    /// Bomb pot configuration (set `None` for a pot built by preflop action). If set, the game
+    /// must start on the flop, and `starting_pot` and `effective_stack` are derived from it (see
+    /// [`BombPot`]).
+    pub bomb_pot: Option<BombPot>,
+}
+
+impl TreeConfig {
+    /// Returns the rake taken from `pot` when the hand ends on `street`.
+    #[inline]
+    pub fn rake(&self, pot: f64, street: BoardState) -> f64 {
+        match &self.rake_config {
+            Some(rake_config) => rake_config.rake(pot, street),
+            None => (pot * self.rake_rate).min(self.rake_cap),
+        }
+    }
+
+    /// Returns whether any hand is raked.
+    #[inline]
+    pub fn is_raked(&self) -> bool {
+        match &self.rake_config {
+            Some(rake_config) => rake_config.is_raked(),
+            None => self.rake_rate > 0.0 && self.rake_cap > 0.0,
+        }
+    }
 }
 
 /// A struct representing an abstract game tree.
@@ -161,18 +450,30 @@ pub struct ActionTree {
     config: TreeConfig,
     added_lines: Vec<Vec<Action>>,
     removed_lines: Vec<Vec<Action>>,
-    root: Box<MutexLike<ActionTreeNode>>,
+    nodes: ActionTreeArena,
     history: Vec<Action>,
 }
 
-#[derive(Default)]
-#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+/// Flat storage of the nodes of an action tree.
+///
+/// The root is stored at index 0, and the children of a node are stored contiguously. `actions[i]`
+/// is the action leading to `nodes[i]`, so the actions of the children of a node form a slice as
+/// well. A chance node has a single child led by `Action::Chance(0)`.
+///
+/// It is encoded as nested nodes (each node followed by its actions and children), which is the
+/// format of the action tree in the save files.
+pub(crate) struct ActionTreeArena {
+    pub(crate) nodes: Vec<ActionTreeNode>,
+    pub(crate) actions: Vec<Action>,
+}
+
+#[derive(Clone, Copy, Default)]
 pub(crate) struct ActionTreeNode {
     pub(crate) player: u8,
     pub(crate) board_state: BoardState,
     pub(crate) amount: i32,
-    pub(crate) actions: Vec<Action>,
-    pub(crate) children: Vec<MutexLike<ActionTreeNode>>,
+    children_offset: u32,
+    num_children: u32,
 }
 
 struct BuildTreeInfo {
@@ -188,14 +489,33 @@ type EjectedActionTree = (
     TreeConfig,
     Vec<Vec<Action>>,
     Vec<Vec<Action>>,
-    Box<MutexLike<ActionTreeNode>>,
+    ActionTreeArena,
 );
 
+/// Error of a line: the length of the unprocessed suffix of the line, and the message.
+type LineError = (usize, String);
+
+/// Creates an error of `line`, whose suffix of length `remaining` is not processed.
+#[inline]
+fn invalid_line(line: &[Action], remaining: usize, message: String) -> SolverError {
+    let len = (line.len() + 1 - remaining).min(line.len());
+    SolverError::InvalidLine {
+        line: line[..len].to_vec(),
+        message,
+    }
+}
+
 impl ActionTree {
     /// Creates a new [`ActionTree`] with the specified configuration.
     #[inline]
-    pub fn new(config: TreeConfig) -> Result<Self, String> {
-        Self::check_config(&config)?;
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, name = "build_action_tree")
+    )]
+    pub fn new(mut config: TreeConfig) -> Result<Self, SolverError> {
+        Self::apply_bomb_pot(&mut config).map_err(SolverError::InvalidTreeConfig)?;
+        Self::check_config(&config).map_err(SolverError::InvalidTreeConfig)?;
+        let _timer = PhaseTimer::start(Phase::TreeBuild);
         let mut ret = Self {
             config,
             ..Default::default()
@@ -227,7 +547,7 @@ impl ActionTree {
     pub fn invalid_terminals(&self) -> Vec<Vec<Action>> {
         let mut ret = Vec::new();
         let mut line = Vec::new();
-        Self::invalid_terminals_recursive(&self.root.lock(), &mut ret, &mut line);
+        Self::invalid_terminals_recursive(&self.nodes, 0, &mut ret, &mut line);
         ret
     }
 
@@ -239,22 +559,27 @@ impl ActionTree {
     ///   must be a bet action (including raise and all-in action).
     /// - Chance actions (i.e., dealing turn and river cards) must be omitted from the `line`.
     #[inline]
-    pub fn add_line(&mut self, line: &[Action]) -> Result<(), String> {
+    pub fn add_line(&mut self, line: &[Action]) -> Result<(), SolverError> {
         let removed_index = self.removed_lines.iter().position(|x| x == line);
-        let is_replaced = self.add_line_recursive(
-            &mut self.root.lock(),
+        let mut nodes = mem::take(&mut self.nodes);
+        let result = self.add_line_recursive(
+            &mut nodes,
+            0,
             line,
             removed_index.is_some(),
             BuildTreeInfo::new(self.config.effective_stack),
-        )?;
+        );
+        nodes.compact();
+        self.nodes = nodes;
+        let is_replaced =
+            result.map_err(|(remaining, message)| invalid_line(line, remaining, message))?;
         if let Some(index) = removed_index {
             self.removed_lines.remove(index);
         } else {
             let mut line = line.to_vec();
-            if is_replaced {
-                if let Some(&Action::Bet(amount) | &Action::Raise(amount)) = line.last() {
-                    *line.last_mut().unwrap() = Action::AllIn(amount);
-                }
+            if is_replaced && let Some(&Action::Bet(amount) | &Action::Raise(amount)) = line.last()
+            {
+                *line.last_mut().unwrap() = Action::AllIn(amount);
             }
             self.added_lines.push(line);
         }
@@ -268,8 +593,10 @@ impl ActionTree {
     /// - If the current node is removed by this method, the current node is moved to the nearest
     ///   ancestor node that is not removed.
     #[inline]
-    pub fn remove_line(&mut self, line: &[Action]) -> Result<(), String> {
-        Self::remove_line_recursive(&mut self.root.lock(), line)?;
+    pub fn remove_line(&mut self, line: &[Action]) -> Result<(), SolverError> {
+        Self::remove_line_recursive(&mut self.nodes, 0, line)
+            .map_err(|(remaining, message)| invalid_line(line, remaining, message))?;
+        self.nodes.compact();
         let was_added = self.added_lines.iter().any(|l| l == line);
         self.added_lines.retain(|l| !l.starts_with(line));
         self.removed_lines.retain(|l| !l.starts_with(line));
@@ -296,7 +623,7 @@ impl ActionTree {
 
     /// Applies the given action history from the root node.
     #[inline]
-    pub fn apply_history(&mut self, history: &[Action]) -> Result<(), String> {
+    pub fn apply_history(&mut self, history: &[Action]) -> Result<(), SolverError> {
         self.back_to_root();
         for &action in history {
             self.play(action)?;
@@ -307,13 +634,15 @@ impl ActionTree {
     /// Returns whether the current node is a terminal node.
     #[inline]
     pub fn is_terminal_node(&self) -> bool {
-        self.current_node_skip_chance().is_terminal()
+        self.nodes
+            .node(self.current_node_skip_chance())
+            .is_terminal()
     }
 
     /// Returns whether the current node is a chance node.
     #[inline]
     pub fn is_chance_node(&self) -> bool {
-        self.current_node().is_chance() && !self.is_terminal_node()
+        self.nodes.node(self.current_node()).is_chance() && !self.is_terminal_node()
     }
 
     /// Returns the available actions for the current node.
@@ -321,7 +650,7 @@ impl ActionTree {
     /// If the current node is a chance node, returns possible actions after the chance event.
     #[inline]
     pub fn available_actions(&self) -> &[Action] {
-        &self.current_node_skip_chance().actions
+        self.nodes.actions(self.current_node_skip_chance())
     }
 
     /// Plays the given action. Returns `Ok(())` if the action is valid.
@@ -330,10 +659,15 @@ impl ActionTree {
     /// If the current node is a chance node, the chance action is automatically played before
     /// playing the given action.
     #[inline]
-    pub fn play(&mut self, action: Action) -> Result<(), String> {
-        let node = self.current_node_skip_chance();
-        if !node.actions.contains(&action) {
-            return Err(format!("Action `{action:?}` is not available"));
+    pub fn play(&mut self, action: Action) -> Result<(), SolverError> {
+        let index = self.current_node_skip_chance();
+        if !self.nodes.actions(index).contains(&action) {
+            let mut line = self.history.clone();
+            line.push(action);
+            return Err(SolverError::InvalidLine {
+                line,
+                message: format!("Action `{action:?}` is not available"),
+            });
         }
 
         self.history.push(action);
@@ -342,9 +676,9 @@ impl ActionTree {
 
     /// Undoes the last action. Returns `Ok(())` if the action is successfully undone.
     #[inline]
-    pub fn undo(&mut self) -> Result<(), String> {
+    pub fn undo(&mut self) -> Result<(), SolverError> {
         if self.history.is_empty() {
-            return Err("No action to undo".to_owned());
+            return Err(SolverError::InvalidState("No action to undo".to_owned()));
         }
 
         self.history.pop();
@@ -358,7 +692,7 @@ impl ActionTree {
     ///
     /// [`add_line`]: #method.add_line
     #[inline]
-    pub fn add_action(&mut self, action: Action) -> Result<(), String> {
+    pub fn add_action(&mut self, action: Action) -> Result<(), SolverError> {
         let mut action_line = self.history.clone();
         action_line.push(action);
         self.add_line(&action_line)
@@ -371,7 +705,7 @@ impl ActionTree {
     ///
     /// [`remove_line`]: #method.remove_line
     #[inline]
-    pub fn remove_action(&mut self, action: Action) -> Result<(), String> {
+    pub fn remove_action(&mut self, action: Action) -> Result<(), SolverError> {
         let mut action_line = self.history.clone();
         action_line.push(action);
         self.remove_line(&action_line)
@@ -384,7 +718,7 @@ impl ActionTree {
     ///
     /// [`remove_line`]: #method.remove_line
     #[inline]
-    pub fn remove_current_node(&mut self) -> Result<(), String> {
+    pub fn remove_current_node(&mut self) -> Result<(), SolverError> {
         let history = self.history.clone();
         self.remove_line(&history)
     }
@@ -393,41 +727,95 @@ impl ActionTree {
     #[inline]
     pub fn total_bet_amount(&self) -> [i32; 2] {
         let info = BuildTreeInfo::new(self.config.effective_stack);
-        self.total_bet_amount_recursive(&self.root.lock(), &self.history, info)
+        self.total_bet_amount_recursive(0, &self.history, info)
     }
 
     /// Ejects the fields.
     #[inline]
     pub(crate) fn eject(self) -> EjectedActionTree {
-        (self.config, self.added_lines, self.removed_lines, self.root)
+        (
+            self.config,
+            self.added_lines,
+            self.removed_lines,
+            self.nodes,
+        )
     }
 
-    /// Returns the reference to the current node.
+    /// Returns the index of the current node.
     #[inline]
-    fn current_node(&self) -> &ActionTreeNode {
-        unsafe {
-            let mut node = &raw const *self.root.lock();
-            for action in &self.history {
-                while (*node).is_chance() {
-                    node = &raw const *(&(*node).children)[0].lock();
-                }
-                let index = (*node).actions.iter().position(|x| x == action).unwrap();
-                node = &raw const *(&(*node).children)[index].lock();
-            }
-            &*node
+    fn current_node(&self) -> usize {
+        let mut index = 0;
+        for action in &self.history {
+            index = self.skip_chance(index);
+            let position = self.nodes.actions(index).iter().position(|x| x == action);
+            index = self.nodes.children(index).start + position.unwrap();
         }
+        index
     }
 
-    /// Returns the reference to the current node skipping chance nodes.
+    /// Returns the index of the current node skipping chance nodes.
     #[inline]
-    fn current_node_skip_chance(&self) -> &ActionTreeNode {
-        unsafe {
-            let mut node = self.current_node() as *const ActionTreeNode;
-            while (*node).is_chance() {
-                node = &raw const *(&(*node).children)[0].lock();
+    fn current_node_skip_chance(&self) -> usize {
+        self.skip_chance(self.current_node())
+    }
+
+    /// Returns the index of the first node that is not a chance node, starting from `index`.
+    #[inline]
+    fn skip_chance(&self, mut index: usize) -> usize {
+        while self.nodes.node(index).is_chance() {
+            index = self.nodes.children(index).start;
+        }
+        index
+    }
+
+    /// Checks the bomb pot configuration and derives the starting pot and the effective stack.
+    #[inline]
+    fn apply_bomb_pot(config: &mut TreeConfig) -> Result<(), String> {
+        let Some(bomb_pot) = config.bomb_pot else {
+            return Ok(());
+        };
+
+        if bomb_pot.ante <= 0 {
+            return Err(format!("Bomb pot ante must be positive: {}", bomb_pot.ante));
+        }
+
+        if bomb_pot.stack <= bomb_pot.ante {
+            return Err(format!(
+                "Bomb pot stack must be greater than the ante: stack = {}, ante = {}",
+                bomb_pot.stack, bomb_pot.ante
+            ));
+        }
+
+        if config.initial_state != BoardState::Flop {
+            return Err(format!(
+                "Bomb pot must start on the flop: initial_state = {:?}",
+                config.initial_state
+            ));
+        }
+
+        let derived = [
+            (
+                "Starting pot",
+                &mut config.starting_pot,
+                bomb_pot.starting_pot(),
+            ),
+            (
+                "Effective stack",
+                &mut config.effective_stack,
+                bomb_pot.effective_stack(),
+            ),
+        ];
+
+        for (name, value, expected) in derived {
+            if *value != 0 && *value != expected {
+                return Err(format!(
+                    "{name} does not match the bomb pot: expected = {expected}, actual = {value}"
+                ));
             }
-            &*node
+            *value = expected;
         }
+
+        Ok(())
     }
 
     /// Checks the configuration.
@@ -468,6 +856,26 @@ impl ActionTree {
             ));
         }
 
+        if let Some(rake_config) = &config.rake_config {
+            for (rate, cap) in rake_config.rates.iter().zip(&rake_config.caps) {
+                if !(0.0..=1.0).contains(rate) {
+                    return Err(format!("Rake rate must be between 0.0 and 1.0: {rate}"));
+                }
+                if *cap < 0.0 {
+                    return Err(format!("Rake cap must be non-negative: {cap}"));
+                }
+            }
+        }
+
+        for rule in &config.sizing_rules {
+            if rule.pot_to_stack_ratio < 0.0 {
+                return Err(format!(
+                    "Pot-to-stack ratio of sizing rule must be non-negative: {}",
+                    rule.pot_to_stack_ratio
+                ));
+            }
+        }
+
         if config.add_allin_threshold < 0.0 {
             return Err(format!(
                 "Add all-in threshold must be non-negative: {}",
@@ -495,14 +903,19 @@ impl ActionTree {
     /// Builds the action tree.
     #[inline]
     fn build_tree(&mut self) {
-        let mut root = self.root.lock();
-        *root = ActionTreeNode::default();
-        root.board_state = self.config.initial_state;
-        self.build_tree_recursive(&mut root, BuildTreeInfo::new(self.config.effective_stack));
+        let mut nodes = ActionTreeArena::with_root(ActionTreeNode {
+            board_state: self.config.initial_state,
+            ..Default::default()
+        });
+        let info = BuildTreeInfo::new(self.config.effective_stack);
+        self.build_tree_recursive(&mut nodes, 0, info);
+        nodes.shrink_to_fit();
+        self.nodes = nodes;
     }
 
     /// Recursively builds the action tree.
-    fn build_tree_recursive(&self, node: &mut ActionTreeNode, info: BuildTreeInfo) {
+    fn build_tree_recursive(&self, nodes: &mut ActionTreeArena, index: usize, info: BuildTreeInfo) {
+        let node = *nodes.node(index);
         if node.is_terminal() {
             // do nothing
         } else if node.is_chance() {
@@ -518,31 +931,28 @@ impl ActionTree {
                 (true, _) => PLAYER_TERMINAL_FLAG,
             };
 
-            node.actions.push(Action::Chance(0));
-            node.children.push(MutexLike::new(ActionTreeNode {
+            let child = ActionTreeNode {
                 player: next_player,
                 board_state: next_state,
                 amount: node.amount,
                 ..Default::default()
-            }));
+            };
+            nodes.push_children(index, [(Action::Chance(0), child)]);
 
-            self.build_tree_recursive(
-                &mut node.children[0].lock(),
-                info.create_next(0, Action::Chance(0)),
-            );
+            let child_index = nodes.children(index).start;
+            self.build_tree_recursive(nodes, child_index, info.create_next(0, Action::Chance(0)));
         } else {
-            self.push_actions(node, &info);
-            for (action, child) in node.actions.iter().zip(node.children.iter()) {
-                self.build_tree_recursive(
-                    &mut child.lock(),
-                    info.create_next(node.player, *action),
-                );
+            self.push_actions(nodes, index, &info);
+            for child_index in nodes.children(index) {
+                let next_info = info.create_next(node.player, nodes.actions[child_index]);
+                self.build_tree_recursive(nodes, child_index, next_info);
             }
         }
     }
 
-    /// Pushes all possible actions to the given node.
-    fn push_actions(&self, node: &mut ActionTreeNode, info: &BuildTreeInfo) {
+    /// Pushes all possible actions to the node at `index`.
+    fn push_actions(&self, nodes: &mut ActionTreeArena, index: usize, info: &BuildTreeInfo) {
+        let node = *nodes.node(index);
         let player = node.player;
         let opponent = node.player ^ 1;
 
@@ -571,6 +981,17 @@ impl ActionTree {
             ),
         };
 
+        // the first matching sizing rule overrides the options (including the donk sizes)
+        let sizing_rule = self
+            .config
+            .sizing_rules
+            .iter()
+            .find(|rule| rule.matches(node.board_state, pot, opponent_stack));
+        let (bet_options, donk_options) = match sizing_rule {
+            Some(rule) => (&rule.bet_sizes, &None),
+            None => (&bet_options[player as usize], donk_options),
+        };
+
         let mut actions = Vec::new();
 
         if let Some(donk_options) = &donk_options
@@ -613,7 +1034,7 @@ impl ActionTree {
             actions.push(Action::Check);
 
             // bet
-            for &bet_size in &bet_options[player as usize].bet {
+            for &bet_size in &bet_options.bet {
                 match bet_size {
                     BetSize::PotRelative(ratio) => {
                         let amount = (pot as f64 * ratio).round() as i32;
@@ -646,7 +1067,7 @@ impl ActionTree {
 
             if !info.allin_flag {
                 // raise
-                for &bet_size in &bet_options[player as usize].raise {
+                for &bet_size in &bet_options.raise {
                     match bet_size {
                         BetSize::PotRelative(ratio) => {
                             let amount = prev_amount + (pot as f64 * ratio).round() as i32;
@@ -729,7 +1150,7 @@ impl ActionTree {
         };
 
         // push actions
-        for action in actions {
+        let children = actions.into_iter().map(|action| {
             let mut amount = node.amount;
             let next_player = match action {
                 Action::Fold => PLAYER_FOLD_FLAG | player,
@@ -745,35 +1166,37 @@ impl ActionTree {
                 _ => panic!("Unexpected action: {action:?}"),
             };
 
-            node.actions.push(action);
-            node.children.push(MutexLike::new(ActionTreeNode {
+            let child = ActionTreeNode {
                 player: next_player,
                 board_state: node.board_state,
                 amount,
                 ..Default::default()
-            }));
-        }
+            };
+            (action, child)
+        });
 
-        node.actions.shrink_to_fit();
-        node.children.shrink_to_fit();
+        nodes.push_children(index, children);
     }
 
     /// Recursive function to enumerate all invalid terminal nodes.
     fn invalid_terminals_recursive(
-        node: &ActionTreeNode,
+        nodes: &ActionTreeArena,
+        index: usize,
         result: &mut Vec<Vec<Action>>,
         line: &mut Vec<Action>,
     ) {
+        let node = nodes.node(index);
         if node.is_terminal() {
             // do nothing
-        } else if node.children.is_empty() {
+        } else if node.num_children == 0 {
             result.push(line.clone());
         } else if node.is_chance() {
-            Self::invalid_terminals_recursive(&node.children[0].lock(), result, line);
+            let child_index = nodes.children(index).start;
+            Self::invalid_terminals_recursive(nodes, child_index, result, line);
         } else {
-            for (&action, child) in node.actions.iter().zip(node.children.iter()) {
-                line.push(action);
-                Self::invalid_terminals_recursive(&child.lock(), result, line);
+            for child_index in nodes.children(index) {
+                line.push(nodes.actions[child_index]);
+                Self::invalid_terminals_recursive(nodes, child_index, result, line);
                 line.pop();
             }
         }
@@ -782,22 +1205,25 @@ impl ActionTree {
     /// Recursive function to add a given line to the tree.
     fn add_line_recursive(
         &self,
-        node: &mut ActionTreeNode,
+        nodes: &mut ActionTreeArena,
+        index: usize,
         line: &[Action],
         was_removed: bool,
         info: BuildTreeInfo,
-    ) -> Result<bool, String> {
+    ) -> Result<bool, LineError> {
+        let node = *nodes.node(index);
         if line.is_empty() {
-            return Err("Empty line".to_owned());
+            return Err((0, "Empty line".to_owned()));
         }
 
         if node.is_terminal() {
-            return Err("Unexpected terminal node".to_owned());
+            return Err((line.len(), "Unexpected terminal node".to_owned()));
         }
 
         if node.is_chance() {
             return self.add_line_recursive(
-                &mut node.children[0].lock(),
+                nodes,
+                nodes.children(index).start,
                 line,
                 was_removed,
                 info.create_next(0, Action::Chance(0)),
@@ -805,18 +1231,19 @@ impl ActionTree {
         }
 
         let action = line[0];
-        let search_result = node.actions.binary_search(&action);
+        let search_result = nodes.actions(index).binary_search(&action);
 
         let player = node.player;
         let opponent = node.player ^ 1;
 
         if line.len() > 1 {
             if search_result.is_err() {
-                return Err(format!("Action does not exist: {action:?}"));
+                return Err((line.len(), format!("Action does not exist: {action:?}")));
             }
 
             return self.add_line_recursive(
-                &mut node.children[search_result.unwrap()].lock(),
+                nodes,
+                nodes.children(index).start + search_result.unwrap(),
                 &line[1..],
                 was_removed,
                 info.create_next(player, action),
@@ -824,12 +1251,12 @@ impl ActionTree {
         }
 
         if search_result.is_ok() {
-            return Err(format!("Action already exists: {action:?}"));
+            return Err((line.len(), format!("Action already exists: {action:?}")));
         }
 
         let is_bet_action = matches!(action, Action::Bet(_) | Action::Raise(_) | Action::AllIn(_));
         if info.allin_flag && is_bet_action {
-            return Err(format!("Bet action after all-in: {action:?}"));
+            return Err((line.len(), format!("Bet action after all-in: {action:?}")));
         }
 
         let player_stack = info.stack[player as usize];
@@ -866,17 +1293,21 @@ impl ActionTree {
         if !was_removed && !is_valid_bet {
             match action {
                 Action::Bet(amount) | Action::Raise(amount) => {
-                    return Err(format!(
-                        "Invalid bet amount: {amount} (min: {min_amount}, max: {max_amount})"
+                    return Err((
+                        line.len(),
+                        format!(
+                            "Invalid bet amount: {amount} (min: {min_amount}, max: {max_amount})"
+                        ),
                     ));
                 }
                 Action::AllIn(amount) => {
-                    return Err(format!(
-                        "Invalid all-in amount: {amount} (expected: {max_amount})"
+                    return Err((
+                        line.len(),
+                        format!("Invalid all-in amount: {amount} (expected: {max_amount})"),
                     ));
                 }
                 _ => {
-                    return Err(format!("Invalid action: {action:?}"));
+                    return Err((line.len(), format!("Invalid action: {action:?}")));
                 }
             };
         }
@@ -906,88 +1337,80 @@ impl ActionTree {
             _ => panic!("Unexpected action: {action:?}"),
         };
 
-        let index = search_result.unwrap_err();
-        node.actions.insert(index, action);
-        node.children.insert(
-            index,
-            MutexLike::new(ActionTreeNode {
-                player: next_player,
-                board_state: node.board_state,
-                amount,
-                ..Default::default()
-            }),
-        );
-
-        node.actions.shrink_to_fit();
-        node.children.shrink_to_fit();
+        let child = ActionTreeNode {
+            player: next_player,
+            board_state: node.board_state,
+            amount,
+            ..Default::default()
+        };
+        let position = search_result.unwrap_err();
+        let child_index = nodes.insert_child(index, position, action, child);
 
-        self.build_tree_recursive(
-            &mut node.children[index].lock(),
-            info.create_next(player, action),
-        );
+        self.build_tree_recursive(nodes, child_index, info.create_next(player, action));
 
         Ok(is_replaced)
     }
 
     /// Recursive function to remove a given line from the tree.
-    fn remove_line_recursive(node: &mut ActionTreeNode, line: &[Action]) -> Result<(), String> {
+    fn remove_line_recursive(
+        nodes: &mut ActionTreeArena,
+        index: usize,
+        line: &[Action],
+    ) -> Result<(), LineError> {
+        let node = nodes.node(index);
         if line.is_empty() {
-            return Err("Empty line".to_owned());
+            return Err((0, "Empty line".to_owned()));
         }
 
         if node.is_terminal() {
-            return Err("Unexpected terminal node".to_owned());
+            return Err((line.len(), "Unexpected terminal node".to_owned()));
         }
 
         if node.is_chance() {
-            return Self::remove_line_recursive(&mut node.children[0].lock(), line);
+            return Self::remove_line_recursive(nodes, nodes.children(index).start, line);
         }
 
         let action = line[0];
-        let search_result = node.actions.binary_search(&action);
+        let search_result = nodes.actions(index).binary_search(&action);
         if search_result.is_err() {
-            return Err(format!("Action does not exist: {action:?}"));
+            return Err((line.len(), format!("Action does not exist: {action:?}")));
         }
 
         if line.len() > 1 {
-            return Self::remove_line_recursive(
-                &mut node.children[search_result.unwrap()].lock(),
-                &line[1..],
-            );
+            let child_index = nodes.children(index).start + search_result.unwrap();
+            return Self::remove_line_recursive(nodes, child_index, &line[1..]);
         }
 
-        let index = search_result.unwrap();
-        node.actions.remove(index);
-        node.children.remove(index);
-
-        node.actions.shrink_to_fit();
-        node.children.shrink_to_fit();
-
+        nodes.remove_child(index, search_result.unwrap());
         Ok(())
     }
 
     /// Recursive function to compute total bet amount for each player.
     fn total_bet_amount_recursive(
         &self,
-        node: &ActionTreeNode,
+        index: usize,
         line: &[Action],
         info: BuildTreeInfo,
     ) -> [i32; 2] {
+        let node = self.nodes.node(index);
         if line.is_empty() || node.is_terminal() {
             let stack = self.config.effective_stack;
             return [stack - info.stack[0], stack - info.stack[1]];
         }
 
         if node.is_chance() {
-            return self.total_bet_amount_recursive(&node.children[0].lock(), line, info);
+            let child_index = self.nodes.children(index).start;
+            return self.total_bet_amount_recursive(child_index, line, info);
         }
 
         let action = line[0];
-        let search_result = node.actions.binary_search(&action);
+        let search_result = self.nodes.actions(index).binary_search(&action);
 
-        let index = search_result.unwrap_or_else(|_| panic!("Action does not exist: {action:?}"));
+        let position =
+            search_result.unwrap_or_else(|_| panic!("Action does not exist: {action:?}"));
+        let child_index = self.nodes.children(index).start + position;
         let next_info = info.create_next(node.player, action);
-        self.total_bet_amount_recursive(&node.children[index].lock(), &line[1..], next_info)
+        self.total_bet_amount_recursive(child_index, &line[1..], next_info)
     }
 }
 
@@ -1003,6 +1426,191 @@ impl ActionTreeNode {
     }
 }
 
+impl Default for ActionTreeArena {
+    #[inline]
+    fn default() -> Self {
+        Self::with_root(ActionTreeNode::default())
+    }
+}
+
+impl ActionTreeArena {
+    /// Creates an arena consisting only of `root`.
+    #[inline]
+    fn with_root(root: ActionTreeNode) -> Self {
+        Self {
+            nodes: vec![root],
+            actions: vec![Action::None],
+        }
+    }
+
+    /// Returns the node at `index`.
+    #[inline]
+    pub(crate) fn node(&self, index: usize) -> &ActionTreeNode {
+        &self.nodes[index]
+    }
+
+    /// Returns the index range of the children of the node at `index`.
+    #[inline]
+    pub(crate) fn children(&self, index: usize) -> IndexRange<usize> {
+        let node = &self.nodes[index];
+        let start = node.children_offset as usize;
+        start..start + node.num_children as usize
+    }
+
+    /// Returns the actions leading to the children of the node at `index`.
+    #[inline]
+    pub(crate) fn actions(&self, index: usize) -> &[Action] {
+        &self.actions[self.children(index)]
+    }
+
+    /// Appends `children` to the arena as the children of the node at `index`, which must not have
+    /// any children yet.
+    fn push_children(
+        &mut self,
+        index: usize,
+        children: impl IntoIterator<Item = (Action, ActionTreeNode)>,
+    ) {
+        let offset = self.nodes.len();
+        for (action, child) in children {
+            self.actions.push(action);
+            self.nodes.push(child);
+        }
+        let num_children = self.nodes.len() - offset;
+        let node = &mut self.nodes[index];
+        node.children_offset = offset as u32;
+        node.num_children = num_children as u32;
+    }
+
+    /// Inserts `child` at `position` of the children of the node at `index` and returns its index.
+    ///
+    /// The children are moved to the end of the arena to keep them contiguous. The old slots are
+    /// left unreachable until [`compact`] is called.
+    ///
+    /// [`compact`]: #method.compact
+    fn insert_child(
+        &mut self,
+        index: usize,
+        position: usize,
+        action: Action,
+        child: ActionTreeNode,
+    ) -> usize {
+        let children = self.children(index);
+        let offset = self.nodes.len();
+        self.nodes.extend_from_within(children.clone());
+        self.actions.extend_from_within(children);
+        self.nodes.insert(offset + position, child);
+        self.actions.insert(offset + position, action);
+        let node = &mut self.nodes[index];
+        node.children_offset = offset as u32;
+        node.num_children += 1;
+        offset + position
+    }
+
+    /// Removes the child at `position` of the children of the node at `index`.
+    ///
+    /// The subtree of the removed child is left unreachable until [`compact`] is called.
+    ///
+    /// [`compact`]: #method.compact
+    fn remove_child(&mut self, index: usize, position: usize) {
+        let children = self.children(index);
+        let start = children.start + position;
+        self.nodes.copy_within(start + 1..children.end, start);
+        self.actions.copy_within(start + 1..children.end, start);
+        self.nodes[index].num_children -= 1;
+    }
+
+    /// Rebuilds the arena, dropping the nodes that are not reachable from the root.
+    fn compact(&mut self) {
+        let mut ret = Self::with_root(self.nodes[0]);
+        self.compact_recursive(0, &mut ret, 0);
+        ret.shrink_to_fit();
+        *self = ret;
+    }
+
+    fn compact_recursive(&self, index: usize, ret: &mut Self, ret_index: usize) {
+        let children = self.children(index);
+        ret.push_children(
+            ret_index,
+            children.clone().map(|i| (self.actions[i], self.nodes[i])),
+        );
+        let offset = ret.nodes[ret_index].children_offset as usize;
+        for (i, child_index) in children.enumerate() {
+            self.compact_recursive(child_index, ret, offset + i);
+        }
+    }
+
+    #[inline]
+    fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+        self.actions.shrink_to_fit();
+    }
+
+    #[cfg(feature = "bincode")]
+    fn encode_recursive<E: Encoder>(
+        &self,
+        index: usize,
+        encoder: &mut E,
+    ) -> Result<(), EncodeError> {
+        let node = &self.nodes[index];
+        node.player.encode(encoder)?;
+        node.board_state.encode(encoder)?;
+        node.amount.encode(encoder)?;
+        self.actions(index).encode(encoder)?;
+        (node.num_children as u64).encode(encoder)?;
+        for child_index in self.children(index) {
+            self.encode_recursive(child_index, encoder)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "bincode")]
+    fn decode_recursive<D: Decoder>(
+        &mut self,
+        index: usize,
+        decoder: &mut D,
+    ) -> Result<(), DecodeError> {
+        let node = ActionTreeNode {
+            player: Decode::decode(decoder)?,
+            board_state: Decode::decode(decoder)?,
+            amount: Decode::decode(decoder)?,
+            ..Default::default()
+        };
+        let actions: Vec<Action> = Decode::decode(decoder)?;
+        let num_children = u64::decode(decoder)? as usize;
+        if num_children != actions.len() {
+            return Err(DecodeError::Other(
+                "Number of children does not match number of actions",
+            ));
+        }
+        decoder.claim_container_read::<ActionTreeNode>(num_children)?;
+
+        self.nodes[index] = node;
+        self.push_children(index, actions.into_iter().map(|a| (a, Default::default())));
+        for child_index in self.children(index) {
+            self.decode_recursive(child_index, decoder)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl Encode for ActionTreeArena {
+    #[inline]
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        self.encode_recursive(0, encoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<Context> Decode<Context> for ActionTreeArena {
+    fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        let mut ret = Self::default();
+        ret.decode_recursive(0, decoder)?;
+        ret.shrink_to_fit();
+        Ok(ret)
+    }
+}
+
 impl BuildTreeInfo {
     #[inline]
     fn new(stack: i32) -> Self {
@@ -1056,9 +1664,9 @@ impl BuildTreeInfo {
 }
 
 /// Returns the number of action nodes of [flop, turn, river].
-pub(crate) fn count_num_action_nodes(node: &ActionTreeNode) -> [u64; 3] {
+pub(crate) fn count_num_action_nodes(nodes: &ActionTreeArena) -> [u64; 3] {
     let mut ret = [0, 0, 0];
-    count_num_action_nodes_recursive(node, 0, &mut ret);
+    count_num_action_nodes_recursive(nodes, 0, 0, &mut ret);
     if ret[1] == 0 {
         ret = [0, 0, ret[0]];
     } else if ret[2] == 0 {
@@ -1067,15 +1675,22 @@ pub(crate) fn count_num_action_nodes(node: &ActionTreeNode) -> [u64; 3] {
     ret
 }
 
-fn count_num_action_nodes_recursive(node: &ActionTreeNode, street: usize, count: &mut [u64; 3]) {
+fn count_num_action_nodes_recursive(
+    nodes: &ActionTreeArena,
+    index: usize,
+    street: usize,
+    count: &mut [u64; 3],
+) {
+    let node = nodes.node(index);
     count[street] += 1;
     if node.is_terminal() {
         // do nothing
     } else if node.is_chance() {
-        count_num_action_nodes_recursive(&node.children[0].lock(), street + 1, count);
+        let child_index = nodes.children(index).start;
+        count_num_action_nodes_recursive(nodes, child_index, street + 1, count);
     } else {
-        for child in &node.children {
-            count_num_action_nodes_recursive(&child.lock(), street, count);
+        for child_index in nodes.children(index) {
+            count_num_action_nodes_recursive(nodes, child_index, street, count);
         }
     }
 }
@@ -0,0 +1,343 @@
+use crate::action_tree::*;
+use crate::card::*;
+use crate::error::SolverError;
+use crate::game::*;
+use crate::interface::*;
+use crate::range::*;
+use crate::texture::*;
+use crate::utility::*;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+#[cfg(feature = "bincode")]
+use crate::file::*;
+#[cfg(feature = "bincode")]
+use std::path::Path;
+
+/// Aggregated results of a single board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregationRow {
+    /// Board cards at the aggregated node.
+    pub board: Vec<Card>,
+
+    /// Player to act at the aggregated node (0 = OOP, 1 = IP).
+    pub player: usize,
+
+    /// Pot size at the aggregated node.
+    pub pot: i32,
+
+    /// Average equity of each player (OOP, IP).
+    pub equity: [f32; 2],
+
+    /// Average expected value of each player (OOP, IP).
+    pub ev: [f32; 2],
+
+    /// Overall frequency of each action.
+    pub frequencies: Vec<f32>,
+
+    /// Average expected value of each action for the acting player. If an action is never taken,
+    /// the value is `NaN`.
+    pub action_ev: Vec<f32>,
+}
+
+/// Aggregated results of a group of boards.
+///
+/// Each board is weighted equally.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregationGroup {
+    /// Label of the group.
+    pub label: String,
+
+    /// Number of boards in the group.
+    pub num_boards: usize,
+
+    /// Average equity of each player (OOP, IP).
+    pub equity: [f32; 2],
+
+    /// Average expected value of each player (OOP, IP).
+    pub ev: [f32; 2],
+
+    /// Average frequency of each action.
+    pub frequencies: Vec<f32>,
+}
+
+/// A report that aggregates the results of many solved games across different boards.
+///
+/// All games must share the same tree configuration so that the same action history leads to
+/// the node with the same actions in every game.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let mut report = AggregationReport::new();
+///
+/// for flop in ["Td9d6h", "AhKh2h"] {
+///     let card_config = CardConfig {
+///         range: ["AA,KK,QQ,AK".parse().unwrap(), "JJ-88,AQ,KQ".parse().unwrap()],
+///         flop: flop_from_str(flop).unwrap(),
+///         turn: NOT_DEALT,
+///         river: NOT_DEALT,
+///         dead_cards: 0,
+///         short_deck: false,
+///         positions: [None; 2],
+///     };
+///
+///     let bet_sizes = BetSizeOptions::try_from(("50%", "")).unwrap();
+///     let tree_config = TreeConfig {
+///         starting_pot: 100,
+///         effective_stack: 100,
+///         flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+///         turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+///         river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+///         ..Default::default()
+///     };
+///
+///     let action_tree = ActionTree::new(tree_config).unwrap();
+///     let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+///     game.allocate_memory(false);
+///     solve(&mut game, 100, 1.0, false);
+///
+///     // aggregate the root node
+///     report.add_game(&mut game, &[]).unwrap();
+/// }
+///
+/// assert_eq!(report.rows().len(), 2);
+/// let groups = report.group_by(|texture| texture.summary());
+/// assert_eq!(groups.len(), 2);
+///
+/// let csv = report.to_csv();
+/// assert!(csv.starts_with("board,texture,player,pot,"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AggregationReport {
+    actions: Vec<Action>,
+    rows: Vec<AggregationRow>,
+}
+
+impl AggregationReport {
+    /// Creates a new empty report.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the actions of the aggregated node.
+    #[inline]
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    /// Returns the aggregated results of each board.
+    #[inline]
+    pub fn rows(&self) -> &[AggregationRow] {
+        &self.rows
+    }
+
+    /// Aggregates the node reached by `history` of the given solved game.
+    ///
+    /// The `history` is applied from the root node (see [`PostFlopGame::apply_history`]), so the
+    /// current node of the `game` is moved. The node must be a player node, and its actions must
+    /// match those of the previously added games.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the game is not solved, and
+    /// [`SolverError::InvalidArgument`] if the node is not a player node or its actions do not
+    /// match.
+    pub fn add_game(
+        &mut self,
+        game: &mut PostFlopGame,
+        history: &[usize],
+    ) -> Result<(), SolverError> {
+        if !game.is_solved() {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+
+        game.apply_history(history);
+
+        if game.is_terminal_node() || game.is_chance_node() {
+            return Err(SolverError::InvalidArgument(
+                "Aggregated node must be a player node".to_owned(),
+            ));
+        }
+
+        let actions = game.available_actions();
+        if self.rows.is_empty() {
+            self.actions = actions;
+        } else if self.actions != actions {
+            return Err(SolverError::InvalidArgument(format!(
+                "Actions do not match: expected {:?}, but got {:?}",
+                self.actions, actions
+            )));
+        }
+
+        game.cache_normalized_weights();
+
+        let player = game.current_player();
+        let num_actions = self.actions.len();
+        let num_hands = game.private_cards(player).len();
+
+        let mut equity = [0.0; 2];
+        let mut ev = [0.0; 2];
+        for p in 0..2 {
+            let weights = game.normalized_weights(p);
+            equity[p] = compute_average(&game.equity(p), weights);
+            ev[p] = compute_average(&game.expected_values(p), weights);
+        }
+
+        let weights = game.normalized_weights(player);
+        let strategy = game.strategy();
+        let ev_detail = game.expected_values_detail(player);
+        let weight_sum = weights.iter().fold(0.0, |acc, &w| acc + w as f64);
+
+        let mut frequencies = Vec::with_capacity(num_actions);
+        let mut action_ev = Vec::with_capacity(num_actions);
+
+        for action in 0..num_actions {
+            let range = action * num_hands..(action + 1) * num_hands;
+            let mut freq_sum = 0.0;
+            let mut ev_sum = 0.0;
+            for ((&w, &s), &v) in weights
+                .iter()
+                .zip(&strategy[range.clone()])
+                .zip(&ev_detail[range])
+            {
+                freq_sum += w as f64 * s as f64;
+                ev_sum += w as f64 * s as f64 * v as f64;
+            }
+            frequencies.push((freq_sum / weight_sum) as f32);
+            action_ev.push((ev_sum / freq_sum) as f32);
+        }
+
+        let total_bet_amount = game.total_bet_amount();
+        let pot = game.tree_config().starting_pot + total_bet_amount[0] + total_bet_amount[1];
+
+        self.rows.push(AggregationRow {
+            board: game.current_board(),
+            player,
+            pot,
+            equity,
+            ev,
+            frequencies,
+            action_ev,
+        });
+
+        Ok(())
+    }
+
+    /// Loads a solved game from the file and aggregates the node reached by `history`.
+    ///
+    /// See [`add_game`] for the details.
+    ///
+    /// [`add_game`]: #method.add_game
+    #[cfg(feature = "bincode")]
+    pub fn add_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        history: &[usize],
+    ) -> Result<(), SolverError> {
+        let (mut game, _memo): (PostFlopGame, _) = load_data_from_file(path, None)?;
+        self.add_game(&mut game, history)
+    }
+
+    /// Groups the boards by the label computed from the texture of each board.
+    ///
+    /// The groups are sorted by the label. Use [`BoardTexture::summary`] as the `key` to group the
+    /// boards by suits, pairing, and the highest rank.
+    pub fn group_by<F: Fn(&BoardTexture) -> String>(&self, key: F) -> Vec<AggregationGroup> {
+        let mut groups = BTreeMap::<String, Vec<&AggregationRow>>::new();
+        for row in &self.rows {
+            let label = key(&BoardTexture::new(&row.board));
+            groups.entry(label).or_default().push(row);
+        }
+
+        groups
+            .into_iter()
+            .map(|(label, rows)| {
+                let n = rows.len() as f32;
+                let average = |f: &dyn Fn(&AggregationRow) -> f32| {
+                    rows.iter().map(|row| f(row)).sum::<f32>() / n
+                };
+                AggregationGroup {
+                    label,
+                    num_boards: rows.len(),
+                    equity: [average(&|r| r.equity[0]), average(&|r| r.equity[1])],
+                    ev: [average(&|r| r.ev[0]), average(&|r| r.ev[1])],
+                    frequencies: (0..self.actions.len())
+                        .map(|i| average(&|r| r.frequencies[i]))
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Exports the aggregated results of each board in CSV format.
+    ///
+    /// The columns are `board`, `texture`, `player`, `pot`, `equity_oop`, `equity_ip`, `ev_oop`,
+    /// `ev_ip`, followed by the frequency and the EV of each action.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        csv.push_str("board,texture,player,pot,equity_oop,equity_ip,ev_oop,ev_ip");
+        for action in &self.actions {
+            let action = csv_escape(&action.to_string());
+            write!(csv, ",freq:{action},ev:{action}").unwrap();
+        }
+        csv.push('\n');
+
+        for row in &self.rows {
+            let board = row.board.iter().map(|&c| card_to_string(c).unwrap());
+            let texture = BoardTexture::new(&row.board).summary();
+            write!(
+                csv,
+                "{},{},{},{},{},{},{},{}",
+                board.collect::<String>(),
+                csv_escape(&texture),
+                row.player,
+                row.pot,
+                row.equity[0],
+                row.equity[1],
+                row.ev[0],
+                row.ev[1]
+            )
+            .unwrap();
+            for (freq, ev) in row.frequencies.iter().zip(&row.action_ev) {
+                write!(csv, ",{freq},{ev}").unwrap();
+            }
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    /// Exports the given groups in CSV format.
+    ///
+    /// The columns are `group`, `num_boards`, `equity_oop`, `equity_ip`, `ev_oop`, `ev_ip`,
+    /// followed by the average frequency of each action.
+    pub fn groups_to_csv(&self, groups: &[AggregationGroup]) -> String {
+        let mut csv = String::new();
+        csv.push_str("group,num_boards,equity_oop,equity_ip,ev_oop,ev_ip");
+        for action in &self.actions {
+            write!(csv, ",freq:{}", csv_escape(&action.to_string())).unwrap();
+        }
+        csv.push('\n');
+
+        for group in groups {
+            write!(
+                csv,
+                "{},{},{},{},{},{}",
+                csv_escape(&group.label),
+                group.num_boards,
+                group.equity[0],
+                group.equity[1],
+                group.ev[0],
+                group.ev[1]
+            )
+            .unwrap();
+            for freq in &group.frequencies {
+                write!(csv, ",{freq}").unwrap();
+            }
+            csv.push('\n');
+        }
+
+        csv
+    }
+}
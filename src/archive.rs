@@ -0,0 +1,390 @@
+// [Archive format]
+// An archive stores many games sharing one tree configuration, each solved for a different flop.
+//  - Magic number (4 bytes): 91 57 f1 09
+//  - Version number (1 byte): 1
+//  - Tree configuration (`TreeConfig`)
+//  - Entries: plain save files of the games (see `file.rs`), one after another
+//  - Index (`Vec<IndexEntry>`, in the order of appending)
+//  - Offset of the index (8 bytes, little endian)
+//
+// Appending a game overwrites the index with the new entry and writes the updated index after it.
+
+use crate::action_tree::*;
+use crate::card::*;
+use crate::error::*;
+use crate::file::*;
+use crate::game::*;
+use bincode::{Decode, Encode};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const ARCHIVE_MAGIC: u32 = 0x09f15791;
+const ARCHIVE_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Decode, Encode)]
+struct IndexEntry {
+    flop: [Card; 3],
+    offset: u64,
+    length: u64,
+}
+
+/// An archive file storing many solved games for one tree configuration in a single file.
+///
+/// Each game is stored as a save file (see [`save_data_into_std_write`]) and looked up by its flop
+/// through an index, which is read into memory when the archive is opened. Loading a game only
+/// reads its own entry, so any board can be accessed in constant time regardless of the number of
+/// games in the archive.
+///
+/// # Examples
+/// ```no_run
+/// use postflop_solver::*;
+///
+/// # fn solve_flop(flop: [Card; 3]) -> PostFlopGame { unimplemented!() }
+/// let tree_config = TreeConfig::default();
+/// let mut archive = GameArchive::create("flops.bin", &tree_config).unwrap();
+///
+/// for flop in ["Td9d6h", "QsJh2h"] {
+///     let game = solve_flop(flop_from_str(flop).unwrap());
+///     archive.append(&game, "", None).unwrap();
+/// }
+///
+/// let archive = GameArchive::open("flops.bin").unwrap();
+/// let (game, _memo) = archive.load(flop_from_str("Jh2hQs").unwrap(), None).unwrap();
+/// ```
+pub struct GameArchive {
+    path: PathBuf,
+    tree_config: TreeConfig,
+    tree_config_bytes: Vec<u8>,
+    entries: Vec<IndexEntry>,
+    index: HashMap<[Card; 3], usize>,
+    index_offset: u64,
+}
+
+fn encode_to_vec<E: Encode>(val: E, err_msg: &str) -> Result<Vec<u8>, SolverError> {
+    bincode::encode_to_vec(val, bincode::config::standard())
+        .map_err(|e| SolverError::Other(format!("{err_msg}: {e}")))
+}
+
+#[inline]
+fn sorted_flop(mut flop: [Card; 3]) -> [Card; 3] {
+    flop.sort_unstable();
+    flop
+}
+
+impl GameArchive {
+    /// Creates a new empty archive for games with the tree configuration `tree_config`.
+    ///
+    /// If the file already exists, it will be overwritten.
+    pub fn create<P: AsRef<Path>>(path: P, tree_config: &TreeConfig) -> Result<Self, SolverError> {
+        let path = path.as_ref();
+        let tree_config_bytes = encode_to_vec(tree_config, "Failed to encode tree config")?;
+
+        let mut header = Vec::new();
+        header.extend(encode_to_vec(
+            ARCHIVE_MAGIC,
+            "Failed to encode magic number",
+        )?);
+        header.extend(encode_to_vec(ARCHIVE_VERSION, "Failed to encode version")?);
+        header.extend_from_slice(&tree_config_bytes);
+
+        let ret = Self {
+            path: path.to_owned(),
+            tree_config: tree_config.clone(),
+            tree_config_bytes,
+            entries: Vec::new(),
+            index: HashMap::new(),
+            index_offset: header.len() as u64,
+        };
+
+        let file = File::create(path).map_err(|e| SolverError::io("Failed to create file", &e))?;
+        let mut writer = BufWriter::new(file);
+        writer
+            .write_all(&header)
+            .map_err(|e| SolverError::io("Failed to write header", &e))?;
+        ret.write_index(&mut writer)?;
+
+        Ok(ret)
+    }
+
+    /// Opens an existing archive and reads its index.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SolverError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| SolverError::io("Failed to open file", &e))?;
+        let mut reader = BufReader::new(file);
+
+        let magic: u32 = decode_from_std_read(&mut reader, "Failed to read magic number")?;
+        if magic != ARCHIVE_MAGIC {
+            return Err(SolverError::InvalidData(
+                "Magic number is invalid".to_owned(),
+            ));
+        }
+
+        let version: u8 = decode_from_std_read(&mut reader, "Failed to read version number")?;
+        if version != ARCHIVE_VERSION {
+            return Err(SolverError::InvalidData(format!(
+                "Version is unsupported: {version}"
+            )));
+        }
+
+        let tree_config: TreeConfig =
+            decode_from_std_read(&mut reader, "Failed to read tree config")?;
+        let tree_config_bytes = encode_to_vec(&tree_config, "Failed to encode tree config")?;
+
+        let mut offset_bytes = [0; 8];
+        reader
+            .seek(SeekFrom::End(-8))
+            .and_then(|_| reader.read_exact(&mut offset_bytes))
+            .map_err(|e| SolverError::io("Failed to read index offset", &e))?;
+        let index_offset = u64::from_le_bytes(offset_bytes);
+
+        reader
+            .seek(SeekFrom::Start(index_offset))
+            .map_err(|e| SolverError::io("Failed to seek to index", &e))?;
+        let entries: Vec<IndexEntry> = decode_from_std_read(&mut reader, "Failed to read index")?;
+
+        let mut index = HashMap::with_capacity(entries.len());
+        for (i, entry) in entries.iter().enumerate() {
+            if entry.offset + entry.length > index_offset {
+                return Err(SolverError::InvalidData("Index is corrupted".to_owned()));
+            }
+            index.insert(entry.flop, i);
+        }
+
+        Ok(Self {
+            path: path.to_owned(),
+            tree_config,
+            tree_config_bytes,
+            entries,
+            index,
+            index_offset,
+        })
+    }
+
+    /// Returns the tree configuration shared by the games in the archive.
+    #[inline]
+    pub fn tree_config(&self) -> &TreeConfig {
+        &self.tree_config
+    }
+
+    /// Returns the number of games in the archive.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the archive contains no games.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns whether the archive contains a game for the flop (in any order of the cards).
+    #[inline]
+    pub fn contains(&self, flop: [Card; 3]) -> bool {
+        self.index.contains_key(&sorted_flop(flop))
+    }
+
+    /// Returns an iterator over the flops of the games in the order of appending.
+    ///
+    /// The cards of each flop are sorted in ascending order.
+    #[inline]
+    pub fn flops(&self) -> impl Iterator<Item = [Card; 3]> + '_ {
+        self.entries.iter().map(|entry| entry.flop)
+    }
+
+    /// Appends a game to the archive.
+    ///
+    /// The arguments `memo` and `compression_level` are the same as in [`save_data_to_file`].
+    /// Returns an error if the tree configuration of the game differs from that of the archive or
+    /// the archive already contains a game for the same flop.
+    pub fn append(
+        &mut self,
+        game: &PostFlopGame,
+        memo: &str,
+        compression_level: Option<i32>,
+    ) -> Result<(), SolverError> {
+        let tree_config_bytes = encode_to_vec(game.tree_config(), "Failed to encode tree config")?;
+        if tree_config_bytes != self.tree_config_bytes {
+            return Err(SolverError::InvalidTreeConfig(
+                "Tree configuration differs from that of the archive".to_owned(),
+            ));
+        }
+
+        let flop = sorted_flop(game.card_config().flop);
+        if self.index.contains_key(&flop) {
+            return Err(SolverError::InvalidCardConfig(format!(
+                "Archive already contains the flop: {flop:?}"
+            )));
+        }
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .open(&self.path)
+            .map_err(|e| SolverError::io("Failed to open file", &e))?;
+        file.seek(SeekFrom::Start(self.index_offset))
+            .map_err(|e| SolverError::io("Failed to seek to index", &e))?;
+
+        let mut writer = BufWriter::new(file);
+        save_data_into_std_write(game, memo, &mut writer, compression_level)?;
+        let end = writer
+            .stream_position()
+            .map_err(|e| SolverError::io("Failed to get file position", &e))?;
+
+        self.entries.push(IndexEntry {
+            flop,
+            offset: self.index_offset,
+            length: end - self.index_offset,
+        });
+        self.index.insert(flop, self.entries.len() - 1);
+        self.index_offset = end;
+
+        self.write_index(&mut writer)
+    }
+
+    /// Loads the game for the flop (in any order of the cards) from the archive.
+    ///
+    /// The argument `max_memory_usage` is the same as in [`load_data_from_file`].
+    pub fn load(
+        &self,
+        flop: [Card; 3],
+        max_memory_usage: Option<u64>,
+    ) -> Result<(PostFlopGame, String), SolverError> {
+        let flop = sorted_flop(flop);
+        let Some(&i) = self.index.get(&flop) else {
+            return Err(SolverError::InvalidCardConfig(format!(
+                "Archive does not contain the flop: {flop:?}"
+            )));
+        };
+
+        let entry = &self.entries[i];
+        let mut file =
+            File::open(&self.path).map_err(|e| SolverError::io("Failed to open file", &e))?;
+        file.seek(SeekFrom::Start(entry.offset))
+            .map_err(|e| SolverError::io("Failed to seek to entry", &e))?;
+
+        let mut reader = BufReader::new(file).take(entry.length);
+        load_data_from_std_read(&mut reader, max_memory_usage)
+    }
+
+    /// Writes the index and its offset at the current position and truncates the file there.
+    fn write_index(&self, writer: &mut BufWriter<File>) -> Result<(), SolverError> {
+        let index = encode_to_vec(&self.entries, "Failed to encode index")?;
+        writer
+            .write_all(&index)
+            .and_then(|_| writer.write_all(&self.index_offset.to_le_bytes()))
+            .and_then(|_| writer.flush())
+            .map_err(|e| SolverError::io("Failed to write index", &e))?;
+
+        let end = self.index_offset + index.len() as u64 + 8;
+        writer
+            .get_ref()
+            .set_len(end)
+            .map_err(|e| SolverError::io("Failed to truncate file", &e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range::*;
+    use crate::utility::*;
+
+    #[test]
+    fn game_archive() {
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 100,
+            effective_stack: 100,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let path = std::env::temp_dir().join("postflop-solver-test-archive.bin");
+        let mut archive = GameArchive::create(&path, &tree_config).unwrap();
+        assert!(archive.is_empty());
+
+        let mut evs = Vec::new();
+        for flop in ["Td9d6h", "QsJh2h", "8c7c5s"] {
+            let card_config = CardConfig {
+                range: ["AA,KK,QQ".parse().unwrap(), "JJ,AK,AQ".parse().unwrap()],
+                flop: flop_from_str(flop).unwrap(),
+                turn: card_from_str("3d").unwrap(),
+                ..Default::default()
+            };
+
+            let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+            let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+            game.allocate_memory(false);
+            finalize(&mut game);
+            game.cache_normalized_weights();
+            evs.push(game.expected_values(0));
+
+            archive.append(&game, flop, None).unwrap();
+            assert!(archive.append(&game, flop, None).is_err());
+        }
+
+        // different tree configuration
+        let other_config = TreeConfig {
+            effective_stack: 200,
+            ..tree_config.clone()
+        };
+        let card_config = CardConfig {
+            range: ["AA".parse().unwrap(), "KK".parse().unwrap()],
+            flop: flop_from_str("2c3c4c").unwrap(),
+            turn: card_from_str("5c").unwrap(),
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(other_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        finalize(&mut game);
+        assert!(archive.append(&game, "", None).is_err());
+
+        let mut archive = GameArchive::open(&path).unwrap();
+        assert_eq!(archive.len(), 3);
+        assert_eq!(archive.tree_config().effective_stack, 100);
+        let flops = archive.flops().collect::<Vec<_>>();
+        assert_eq!(flops[0], sorted_flop(flop_from_str("Td9d6h").unwrap()));
+        assert!(archive.contains(flop_from_str("Jh2hQs").unwrap()));
+        assert!(!archive.contains(flop_from_str("2c3c4c").unwrap()));
+
+        for (i, flop) in ["6hTd9d", "2hQsJh", "5s8c7c"].into_iter().enumerate() {
+            let (mut game, memo) = archive.load(flop_from_str(flop).unwrap(), None).unwrap();
+            assert_eq!(memo, ["Td9d6h", "QsJh2h", "8c7c5s"][i]);
+            game.cache_normalized_weights();
+            assert_eq!(game.expected_values(0), evs[i]);
+        }
+        assert!(
+            archive
+                .load(flop_from_str("2c3c4c").unwrap(), None)
+                .is_err()
+        );
+
+        // append to the reopened archive
+        let card_config = CardConfig {
+            range: ["AA,KK,QQ".parse().unwrap(), "JJ,AK,AQ".parse().unwrap()],
+            flop: flop_from_str("2c3c4c").unwrap(),
+            turn: card_from_str("5c").unwrap(),
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        finalize(&mut game);
+        archive.append(&game, "", None).unwrap();
+
+        let archive = GameArchive::open(&path).unwrap();
+        assert_eq!(archive.len(), 4);
+        archive
+            .load(flop_from_str("4c3c2c").unwrap(), None)
+            .unwrap();
+        archive
+            .load(flop_from_str("Td9d6h").unwrap(), None)
+            .unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,3 +1,6 @@
+use crate::error::*;
+use std::fmt;
+
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
 
@@ -80,12 +83,46 @@ pub enum BetSize {
     AllIn,
 }
 
+impl fmt::Display for BetSize {
+    /// Formats the bet size in the syntax accepted by the `try_from()` method of
+    /// [`BetSizeOptions`].
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::BetSize::*;
+    ///
+    /// assert_eq!(PotRelative(0.75).to_string(), "75%");
+    /// assert_eq!(Additive(20, 3).to_string(), "20c3r");
+    /// assert_eq!(Geometric(0, f64::INFINITY).to_string(), "e");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::PotRelative(ratio) => write!(f, "{}%", ratio * 100.0),
+            Self::PrevBetRelative(ratio) => write!(f, "{ratio}x"),
+            Self::Additive(add, 0) => write!(f, "{add}c"),
+            Self::Additive(add, cap) => write!(f, "{add}c{cap}r"),
+            Self::Geometric(num_streets, max_pot_rel) => {
+                if num_streets > 0 {
+                    write!(f, "{num_streets}")?;
+                }
+                write!(f, "e")?;
+                if max_pot_rel.is_finite() {
+                    write!(f, "{}%", max_pot_rel * 100.0)?;
+                }
+                Ok(())
+            }
+            Self::AllIn => write!(f, "a"),
+        }
+    }
+}
+
 impl TryFrom<(&str, &str)> for BetSizeOptions {
-    type Error = String;
+    type Error = SolverError;
 
     /// Attempts to convert comma-separated strings into bet sizes.
     ///
-    /// See the [`BetSizeOptions`] struct for the description and examples.
+    /// See the [`BetSizeOptions`] struct for the description and examples. Returns
+    /// [`SolverError::InvalidTreeConfig`] if a size is invalid.
     fn try_from((bet_str, raise_str): (&str, &str)) -> Result<Self, Self::Error> {
         let mut bet_sizes = bet_str.split(',').map(str::trim).collect::<Vec<_>>();
         let mut raise_sizes = raise_str.split(',').map(str::trim).collect::<Vec<_>>();
@@ -102,11 +139,12 @@ impl TryFrom<(&str, &str)> for BetSizeOptions {
         let mut raise = Vec::new();
 
         for bet_size in bet_sizes {
-            bet.push(bet_size_from_str(bet_size, false)?);
+            bet.push(bet_size_from_str(bet_size, false).map_err(SolverError::InvalidTreeConfig)?);
         }
 
         for raise_size in raise_sizes {
-            raise.push(bet_size_from_str(raise_size, true)?);
+            raise
+                .push(bet_size_from_str(raise_size, true).map_err(SolverError::InvalidTreeConfig)?);
         }
 
         bet.sort_unstable_by(|l, r| l.partial_cmp(r).unwrap());
@@ -117,11 +155,12 @@ impl TryFrom<(&str, &str)> for BetSizeOptions {
 }
 
 impl TryFrom<&str> for DonkSizeOptions {
-    type Error = String;
+    type Error = SolverError;
 
     /// Attempts to convert comma-separated strings into bet sizes.
     ///
-    /// See the [`BetSizeOptions`] struct for the description and examples.
+    /// See the [`BetSizeOptions`] struct for the description and examples. Returns
+    /// [`SolverError::InvalidTreeConfig`] if a size is invalid.
     fn try_from(donk_str: &str) -> Result<Self, Self::Error> {
         let mut donk_sizes = donk_str.split(',').map(str::trim).collect::<Vec<_>>();
 
@@ -132,7 +171,7 @@ impl TryFrom<&str> for DonkSizeOptions {
         let mut donk = Vec::new();
 
         for donk_size in donk_sizes {
-            donk.push(bet_size_from_str(donk_size, false)?);
+            donk.push(bet_size_from_str(donk_size, false).map_err(SolverError::InvalidTreeConfig)?);
         }
 
         donk.sort_unstable_by(|l, r| l.partial_cmp(r).unwrap());
@@ -291,6 +330,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_bet_size_to_string() {
+        let tests = [
+            PotRelative(0.75),
+            PotRelative(1.125),
+            PrevBetRelative(2.5),
+            Additive(100, 0),
+            Additive(20, 3),
+            Geometric(0, f64::INFINITY),
+            Geometric(2, 1.5),
+            AllIn,
+        ];
+
+        for bet_size in tests {
+            assert_eq!(bet_size_from_str(&bet_size.to_string(), true), Ok(bet_size));
+        }
+    }
+
     #[test]
     fn test_bet_sizes_from_str() {
         let tests = [
@@ -319,7 +376,10 @@ mod tests {
         let error_tests = [("2.5x", ""), (",", "")];
 
         for (bet, raise) in error_tests {
-            BetSizeOptions::try_from((bet, raise)).unwrap_err();
+            assert!(matches!(
+                BetSizeOptions::try_from((bet, raise)),
+                Err(SolverError::InvalidTreeConfig(_))
+            ));
         }
     }
 
@@ -347,7 +407,10 @@ mod tests {
         let error_tests = ["2.5x", ","];
 
         for donk in error_tests {
-            DonkSizeOptions::try_from(donk).unwrap_err();
+            assert!(matches!(
+                DonkSizeOptions::try_from(donk),
+                Err(SolverError::InvalidTreeConfig(_))
+            ));
         }
     }
 }
@@ -0,0 +1,307 @@
+//! Command-line interface of postflop-solver (built with the `cli` feature).
+//!
+//! ```text
+//! postflop-solver solve <SPEC> [--quiet]
+//! postflop-solver inspect <FILE>
+//! postflop-solver export <FILE> [--format csv|json|pio] [--street flop|turn|river]
+//!                               [--history 0,1,...] [--output PATH]
+//! ```
+//!
+//! `solve` executes a TOML or YAML solve specification (see `SolveSpec`); use `save` outputs in the
+//! specification to keep the solved games for `inspect` and `export`.
+
+use postflop_solver::*;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+const USAGE: &str = "\
+Usage:
+  postflop-solver solve <SPEC> [--quiet]
+  postflop-solver inspect <FILE>
+  postflop-solver export <FILE> [--format csv|json|pio] [--street flop|turn|river]
+                                [--history 0,1,...] [--output PATH]";
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let stdout = io::stdout();
+    if let Err(message) = run(&args, &mut stdout.lock()) {
+        eprintln!("error: {message}");
+        std::process::exit(1);
+    }
+}
+
+fn run<W: Write>(args: &[String], out: &mut W) -> Result<(), String> {
+    let (command, rest) = args.split_first().ok_or(USAGE)?;
+    match command.as_str() {
+        "solve" => solve_command(rest, out),
+        "inspect" => inspect_command(rest, out),
+        "export" => export_command(rest),
+        "help" | "--help" | "-h" => writeln!(out, "{USAGE}").map_err(write_error),
+        _ => Err(format!("Unknown command: {command}\n\n{USAGE}")),
+    }
+}
+
+#[inline]
+fn write_error(e: io::Error) -> String {
+    format!("Failed to write: {e}")
+}
+
+/// Positional arguments and `(name, value)` pairs of options.
+type ParsedArgs<'a> = (Vec<&'a str>, Vec<(&'a str, &'a str)>);
+
+/// Splits the arguments into positional arguments and `--name value` options. `flags` are the
+/// options without a value.
+fn parse_args<'a>(
+    args: &'a [String],
+    options: &[&str],
+    flags: &[&str],
+) -> Result<ParsedArgs<'a>, String> {
+    let mut positional = Vec::new();
+    let mut named = Vec::new();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if flags.contains(&arg.as_str()) {
+            named.push((arg.as_str(), ""));
+        } else if options.contains(&arg.as_str()) {
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("Missing value of {arg}"))?;
+            named.push((arg.as_str(), value.as_str()));
+        } else if arg.starts_with("--") {
+            return Err(format!("Unknown option: {arg}"));
+        } else {
+            positional.push(arg.as_str());
+        }
+    }
+
+    Ok((positional, named))
+}
+
+fn single_path<'a>(positional: &[&'a str]) -> Result<&'a str, String> {
+    match positional {
+        [path] => Ok(path),
+        _ => Err(format!("Expected exactly one path\n\n{USAGE}")),
+    }
+}
+
+fn solve_command<W: Write>(args: &[String], out: &mut W) -> Result<(), String> {
+    let (positional, named) = parse_args(args, &[], &["--quiet"])?;
+    let spec = load_solve_spec(single_path(&positional)?).map_err(|e| e.to_string())?;
+    let results = spec.run(named.is_empty()).map_err(|e| e.to_string())?;
+
+    writeln!(out, "board,iterations,exploitability,outputs,isomorphic_to").map_err(write_error)?;
+    for result in results {
+        let outputs = result
+            .outputs
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>();
+        writeln!(
+            out,
+            "{},{},{},{},{}",
+            result.board,
+            result.num_iterations,
+            result.exploitability,
+            outputs.join(";"),
+            result.isomorphic_to.unwrap_or_default()
+        )
+        .map_err(write_error)?;
+    }
+
+    Ok(())
+}
+
+fn inspect_command<W: Write>(args: &[String], out: &mut W) -> Result<(), String> {
+    let (positional, _) = parse_args(args, &[], &[])?;
+    let path = single_path(&positional)?;
+    let (mut game, memo): (PostFlopGame, _) =
+        load_data_from_file(path, None).map_err(|e| e.to_string())?;
+
+    let card_config = game.card_config().clone();
+    let tree_config = game.tree_config().clone();
+    let board = [
+        card_config.flop[0],
+        card_config.flop[1],
+        card_config.flop[2],
+    ]
+    .into_iter()
+    .chain([card_config.turn, card_config.river])
+    .filter(|&card| card != NOT_DEALT)
+    .map(|card| card_to_string(card).unwrap())
+    .collect::<String>();
+
+    let mut lines = vec![
+        format!("board: {board}"),
+        format!("oop_range: {}", card_config.range[0]),
+        format!("ip_range: {}", card_config.range[1]),
+    ];
+    for (name, position) in ["oop", "ip"].iter().zip(card_config.positions) {
+        if let Some(position) = position {
+            lines.push(format!("{name}_position: {position}"));
+        }
+    }
+    lines.extend([
+        format!("starting_pot: {}", tree_config.starting_pot),
+        format!("effective_stack: {}", tree_config.effective_stack),
+        format!("memory_usage: {}", game.memory_usage().0),
+    ]);
+    if !memo.is_empty() {
+        lines.push(format!("memo: {memo}"));
+    }
+    if let Some(summary) = game.solve_summary() {
+        let pot = tree_config.starting_pot as f32;
+        lines.push(format!("iterations: {}", summary.num_iterations));
+        lines.push(format!(
+            "exploitability: {} ({:.3}% of pot)",
+            summary.exploitability,
+            summary.exploitability / pot * 100.0
+        ));
+    }
+
+    if game.is_memory_allocated().is_some() && !game.is_chance_node() {
+        game.cache_normalized_weights();
+        let player = ["oop", "ip"][game.current_player()];
+        let actions = game
+            .available_actions()
+            .iter()
+            .zip(game.action_frequencies())
+            .map(|(action, frequency)| format!("{action} {:.1}%", frequency * 100.0))
+            .collect::<Vec<_>>();
+        lines.push(format!("root ({player}): {}", actions.join(", ")));
+    }
+
+    for line in lines {
+        writeln!(out, "{line}").map_err(write_error)?;
+    }
+    Ok(())
+}
+
+fn export_command(args: &[String]) -> Result<(), String> {
+    let options = ["--format", "--street", "--history", "--output"];
+    let (positional, named) = parse_args(args, &options, &[])?;
+    let path = single_path(&positional)?;
+
+    let mut export_options = ExportOptions::default();
+    let mut output = None;
+    for (name, value) in named {
+        match name {
+            "--format" => {
+                export_options.format = match value {
+                    "csv" => ExportFormat::Csv,
+                    "json" => ExportFormat::Json,
+                    "pio" => ExportFormat::Pio,
+                    _ => return Err(format!("Invalid format: {value}")),
+                }
+            }
+            "--street" => {
+                export_options.street = Some(match value {
+                    "flop" => BoardState::Flop,
+                    "turn" => BoardState::Turn,
+                    "river" => BoardState::River,
+                    _ => return Err(format!("Invalid street: {value}")),
+                })
+            }
+            "--history" => {
+                export_options.history = value
+                    .split(',')
+                    .filter(|s| !s.is_empty())
+                    .map(|s| {
+                        s.trim()
+                            .parse()
+                            .map_err(|_| format!("Invalid history: {value}"))
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            _ => output = Some(value),
+        }
+    }
+
+    let (mut game, _): (PostFlopGame, _) =
+        load_data_from_file(path, None).map_err(|e| e.to_string())?;
+    let result = match output {
+        Some(output) => {
+            let file = File::create(output).map_err(|e| format!("Failed to create file: {e}"))?;
+            game.export_strategy(&mut BufWriter::new(file), &export_options)
+        }
+        None => {
+            let stdout = io::stdout();
+            game.export_strategy(&mut BufWriter::new(stdout.lock()), &export_options)
+        }
+    };
+    result.map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli() {
+        let dir =
+            std::env::temp_dir().join(format!("postflop-solver-test-cli-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir_str = dir.display().to_string();
+
+        let spec = format!(
+            r#"
+ranges = ["AA,KK,QQ", "QQ-JJ,AQs"]
+positions = ["BB", "BTN"]
+boards = ["Td9d6h2c"]
+
+[tree_config]
+starting_pot = 100
+effective_stack = 100
+turn_bet_sizes = [{{ bet = ["50%"] }}, {{ bet = ["50%"] }}]
+river_bet_sizes = [{{ bet = ["50%"] }}, {{ bet = ["50%"] }}]
+
+[solver]
+max_iterations = 30
+
+[[outputs]]
+format = "save"
+path = "{dir_str}/{{board}}.bin"
+"#
+        );
+        let spec_path = dir.join("spec.toml");
+        std::fs::write(&spec_path, spec).unwrap();
+
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let spec_path = spec_path.display().to_string();
+        let mut out = Vec::new();
+        run(&args(&["solve", &spec_path, "--quiet"]), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(
+            out.starts_with("board,iterations,exploitability,outputs,isomorphic_to\nTd9d6h2c,")
+        );
+
+        let save_path = format!("{dir_str}/Td9d6h2c.bin");
+        let mut out = Vec::new();
+        run(&args(&["inspect", &save_path]), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("board: Td9d6h2c\n"));
+        assert!(out.contains("\noop_position: BB\nip_position: BTN\n"));
+        assert!(out.contains("\nstarting_pot: 100\n"));
+        assert!(out.contains("\nroot (oop): Check "));
+
+        let csv_path = format!("{dir_str}/out.csv");
+        let export = [
+            "export", &save_path, "--street", "river", "--output", &csv_path,
+        ];
+        run(&args(&export), &mut Vec::new()).unwrap();
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(csv.starts_with("line,street,board,player,hand,"));
+        assert!(csv.lines().skip(1).all(|line| line.contains(",river,")));
+
+        run(&args(&[]), &mut Vec::new()).unwrap_err();
+        run(&args(&["solve"]), &mut Vec::new()).unwrap_err();
+        run(
+            &args(&["export", &save_path, "--format", "xml"]),
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+        run(&args(&["inspect", &save_path, "--foo"]), &mut Vec::new()).unwrap_err();
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
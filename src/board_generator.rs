@@ -0,0 +1,486 @@
+use crate::card::*;
+use crate::error::SolverError;
+use crate::random::*;
+use crate::range::*;
+use fastrand::Rng;
+use std::collections::HashMap;
+
+/// Maximum number of consecutive rejected samples in [`sample_boards`].
+const MAX_REJECTIONS: usize = 1_000_000;
+
+#[inline]
+fn check_num_cards(num_cards: usize) -> Result<(), SolverError> {
+    if (3..=5).contains(&num_cards) {
+        Ok(())
+    } else {
+        Err(SolverError::InvalidArgument(format!(
+            "Number of board cards must be 3, 4, or 5: {num_cards}"
+        )))
+    }
+}
+
+#[inline]
+fn check_dead_cards(num_cards: usize, dead_cards: u64) -> Result<(), SolverError> {
+    if dead_cards >> 52 != 0 {
+        return Err(SolverError::InvalidArgument(format!(
+            "Dead cards must be in [0, 52): {dead_cards:#x}"
+        )));
+    }
+    if 52 - (dead_cards.count_ones() as usize) < num_cards {
+        return Err(SolverError::InvalidArgument(
+            "Too many dead cards".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// Enumerates all boards satisfying the given predicate.
+///
+/// The flop cards of each board are sorted in ascending order of card IDs, and the turn and river
+/// cards (if any) follow in the dealing order. For example, "paired turns" can be distinguished
+/// from "paired flops" by looking at the last card. Cards in the `dead_cards` bitmask are never
+/// used. The predicate is typically written in terms of [`BoardTexture`].
+///
+/// **Time complexity:** *O*(#(boards) * (time complexity of the predicate)), where #(boards) is
+/// 22,100 for flops, 1,082,900 for turns, and 51,979,200 for rivers.
+///
+/// [`BoardTexture`]: crate::BoardTexture
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// // monotone flops with an ace
+/// let boards = enumerate_boards(3, 0, |board| {
+///     let texture = BoardTexture::new(board);
+///     texture.is_monotone() && texture.contains_rank(12)
+/// })
+/// .unwrap();
+/// assert_eq!(boards.len(), 4 * 66);
+///
+/// // unpaired flops followed by a pairing turn
+/// let boards = enumerate_boards(4, 0, |board| {
+///     !BoardTexture::new(&board[..3]).is_paired() && BoardTexture::new(board).is_paired()
+/// })
+/// .unwrap();
+/// assert_eq!(boards.len(), 18304 * 9);
+/// ```
+pub fn enumerate_boards<F: FnMut(&[Card]) -> bool>(
+    num_cards: usize,
+    dead_cards: u64,
+    mut predicate: F,
+) -> Result<Vec<Vec<Card>>, SolverError> {
+    check_num_cards(num_cards)?;
+    check_dead_cards(num_cards, dead_cards)?;
+
+    let mut result = Vec::new();
+    let mut board = Vec::with_capacity(num_cards);
+    let mut used_mask = dead_cards;
+
+    for flop1 in 0..52 {
+        for flop2 in flop1 + 1..52 {
+            for flop3 in flop2 + 1..52 {
+                let flop_mask: u64 = (1 << flop1) | (1 << flop2) | (1 << flop3);
+                if flop_mask & dead_cards != 0 {
+                    continue;
+                }
+                board.clear();
+                board.extend_from_slice(&[flop1, flop2, flop3]);
+                used_mask |= flop_mask;
+                enumerate_boards_recursive(
+                    num_cards,
+                    &mut board,
+                    &mut used_mask,
+                    &mut predicate,
+                    &mut result,
+                );
+                used_mask &= !flop_mask;
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+fn enumerate_boards_recursive<F: FnMut(&[Card]) -> bool>(
+    num_cards: usize,
+    board: &mut Vec<Card>,
+    used_mask: &mut u64,
+    predicate: &mut F,
+    result: &mut Vec<Vec<Card>>,
+) {
+    if board.len() == num_cards {
+        if predicate(board) {
+            result.push(board.clone());
+        }
+        return;
+    }
+
+    for card in 0..52 {
+        let bit_card: u64 = 1 << card;
+        if *used_mask & bit_card == 0 {
+            board.push(card);
+            *used_mask |= bit_card;
+            enumerate_boards_recursive(num_cards, board, used_mask, predicate, result);
+            *used_mask &= !bit_card;
+            board.pop();
+        }
+    }
+}
+
+/// Randomly samples `count` boards satisfying the given predicate.
+///
+/// Boards are drawn uniformly at random (with replacement) from the boards enumerated by
+/// [`enumerate_boards`] by rejection sampling, so the same `seed` always produces the same
+/// result. Returns an error if the predicate rejects too many consecutive samples.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let boards = sample_boards(5, 10, 0, 42, |board| BoardTexture::new(board).is_flush_possible())
+///     .unwrap();
+/// assert_eq!(boards.len(), 10);
+/// assert!(boards.iter().all(|board| board.len() == 5));
+/// ```
+#[inline]
+pub fn sample_boards<F: FnMut(&[Card]) -> bool>(
+    num_cards: usize,
+    count: usize,
+    dead_cards: u64,
+    seed: u64,
+    predicate: F,
+) -> Result<Vec<Vec<Card>>, SolverError> {
+    let mut rng = Rng::with_seed(seed);
+    sample_boards_with_rng(num_cards, count, dead_cards, &mut rng, predicate)
+}
+
+/// Same as [`sample_boards`], but draws the boards from `rng`.
+pub fn sample_boards_with_rng<F: FnMut(&[Card]) -> bool, R: RandomSource + ?Sized>(
+    num_cards: usize,
+    count: usize,
+    dead_cards: u64,
+    rng: &mut R,
+    mut predicate: F,
+) -> Result<Vec<Vec<Card>>, SolverError> {
+    check_num_cards(num_cards)?;
+    check_dead_cards(num_cards, dead_cards)?;
+
+    let deck = (0..52)
+        .filter(|&card| dead_cards & (1 << card) == 0)
+        .collect::<Vec<Card>>();
+
+    let mut result = Vec::with_capacity(count);
+    let mut num_rejections = 0;
+
+    while result.len() < count {
+        let mut deck = deck.clone();
+        for i in 0..num_cards {
+            let j = i + rng.next_below(deck.len() - i);
+            deck.swap(i, j);
+        }
+
+        let mut board = deck[..num_cards].to_vec();
+        board[..3].sort_unstable();
+
+        if predicate(&board) {
+            result.push(board);
+            num_rejections = 0;
+        } else {
+            num_rejections += 1;
+            if num_rejections >= MAX_REJECTIONS {
+                return Err(SolverError::InvalidArgument(
+                    "Failed to sample boards satisfying the predicate".to_owned(),
+                ));
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// A permutation of the four suits, which maps a suit `s` to `suits()[s]`.
+///
+/// Two boards related by a suit permutation are strategically identical when the ranges and the
+/// dead cards are invariant under the permutation (see [`group_isomorphic_boards`]). The solution
+/// of one board then answers the queries of the other: a hand `hand` of the other board
+/// corresponds to `permutation.hand(hand)`, and a dealt card `card` in a history corresponds to
+/// `permutation.card(card)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SuitPermutation([u8; 4]);
+
+impl Default for SuitPermutation {
+    #[inline]
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl SuitPermutation {
+    /// The identity permutation.
+    pub const IDENTITY: Self = Self([0, 1, 2, 3]);
+
+    /// Creates a permutation that maps a suit `s` to `suits[s]`.
+    ///
+    /// Returns an error if `suits` is not a permutation of `0..4`.
+    pub fn new(suits: [u8; 4]) -> Result<Self, SolverError> {
+        let mask = suits.iter().fold(0u8, |mask, &suit| mask | 1 << (suit & 7));
+        if mask != 0b1111 {
+            return Err(SolverError::InvalidArgument(format!(
+                "Not a permutation of suits: {suits:?}"
+            )));
+        }
+        Ok(Self(suits))
+    }
+
+    /// Returns the image of each suit.
+    #[inline]
+    pub fn suits(&self) -> [u8; 4] {
+        self.0
+    }
+
+    /// Returns the inverse permutation.
+    #[inline]
+    pub fn inverse(&self) -> Self {
+        let mut suits = [0; 4];
+        for (suit, &image) in self.0.iter().enumerate() {
+            suits[image as usize] = suit as u8;
+        }
+        Self(suits)
+    }
+
+    /// Returns the permutation that applies `self` and then `other`.
+    #[inline]
+    pub fn then(&self, other: &Self) -> Self {
+        Self(self.0.map(|suit| other.0[suit as usize]))
+    }
+
+    /// Maps a card (cards not less than `52`, e.g., [`NOT_DEALT`], are returned as is).
+    #[inline]
+    pub fn card(&self, card: Card) -> Card {
+        if card < 52 {
+            (card & !3) | self.0[(card & 3) as usize]
+        } else {
+            card
+        }
+    }
+
+    /// Maps a hand.
+    #[inline]
+    pub fn hand(&self, hand: (Card, Card)) -> (Card, Card) {
+        (self.card(hand.0), self.card(hand.1))
+    }
+
+    /// Maps a board, keeping the order of the cards.
+    #[inline]
+    pub fn board(&self, board: &[Card]) -> Vec<Card> {
+        board.iter().map(|&card| self.card(card)).collect()
+    }
+
+    /// Maps a card bitmask.
+    #[inline]
+    pub fn mask(&self, mask: u64) -> u64 {
+        (0..52)
+            .filter(|&card| mask & (1 << card) != 0)
+            .fold(0, |acc, card| acc | (1 << self.card(card)))
+    }
+
+    /// Maps a range, i.e., the weight of each hand `hand` is moved to `self.hand(hand)`.
+    pub fn range(&self, range: &Range) -> Range {
+        let mut ret = Range::new();
+        for card1 in 0..52 {
+            for card2 in card1 + 1..52 {
+                let weight = range.get_weight_by_cards(card1, card2);
+                ret.set_weight_by_cards(self.card(card1), self.card(card2), weight);
+            }
+        }
+        ret
+    }
+
+    /// Returns all 24 permutations, starting with the identity.
+    fn all() -> impl Iterator<Item = Self> {
+        (0..4u8).flat_map(|a| {
+            (0..4u8).flat_map(move |b| {
+                (0..4u8).filter_map(move |c| {
+                    let d = 6u8.checked_sub(a + b + c)?;
+                    Self::new([a, b, c, d]).ok()
+                })
+            })
+        })
+    }
+}
+
+/// Groups of suit-isomorphic boards, returned by [`group_isomorphic_boards`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IsomorphicBoards {
+    /// Index of the representative of each board, i.e., the first board of its group in the input.
+    /// A board is its own representative if and only if it is the first of its group.
+    pub representatives: Vec<usize>,
+
+    /// Permutation that maps each board to its representative (the identity for the
+    /// representatives themselves).
+    pub permutations: Vec<SuitPermutation>,
+}
+
+impl IsomorphicBoards {
+    /// Returns the indices of the representatives, i.e., the boards that need to be solved.
+    #[inline]
+    pub fn unique_indices(&self) -> Vec<usize> {
+        (0..self.representatives.len())
+            .filter(|&i| self.representatives[i] == i)
+            .collect()
+    }
+
+    /// Returns the number of boards that can be skipped.
+    #[inline]
+    pub fn num_duplicates(&self) -> usize {
+        self.representatives.len() - self.unique_indices().len()
+    }
+}
+
+/// Groups the boards that are strategically identical because of the suit isomorphism.
+///
+/// Two boards are grouped when a suit permutation maps one to the other (the flop as a set, and
+/// the turn and river cards as they are) and leaves both ranges and `dead_cards` unchanged. Only
+/// the first board of each group needs to be solved: the queries of the other boards are
+/// translated by the permutation (see [`SuitPermutation`]). With suit-symmetric ranges, about a
+/// quarter of the flops are distinct (1,755 of 22,100).
+///
+/// Returns an error if a board does not consist of 3 to 5 distinct valid cards or contains a dead
+/// card.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let ranges = ["AA,KK,AK".parse().unwrap(), "QQ-TT,AQs".parse().unwrap()];
+/// let boards = ["Td9d6h", "Th9h6c", "Td9d6h"]
+///     .map(|board| board_from_str(board).unwrap())
+///     .to_vec();
+///
+/// let groups = group_isomorphic_boards(&boards, &ranges, 0).unwrap();
+/// assert_eq!(groups.representatives, [0, 0, 0]);
+/// assert_eq!(groups.unique_indices(), [0]);
+///
+/// // "Th9h6c" is translated to "Td9d6h" (the flop cards may be reordered in general)
+/// let permutation = groups.permutations[1];
+/// assert_eq!(permutation.board(&boards[1]), boards[0]);
+/// ```
+pub fn group_isomorphic_boards(
+    boards: &[Vec<Card>],
+    ranges: &[Range; 2],
+    dead_cards: u64,
+) -> Result<IsomorphicBoards, SolverError> {
+    let symmetries = SuitPermutation::all()
+        .filter(|permutation| {
+            permutation.mask(dead_cards) == dead_cards
+                && ranges
+                    .iter()
+                    .all(|range| permutation.range(range).raw_data() == range.raw_data())
+        })
+        .collect::<Vec<_>>();
+
+    // canonical form -> (index of the representative, permutation to the canonical form)
+    let mut canonical_boards = HashMap::new();
+    let mut ret = IsomorphicBoards::default();
+
+    for (index, board) in boards.iter().enumerate() {
+        check_num_cards(board.len())?;
+        let mask = board.iter().fold(0u64, |mask, &card| {
+            if card < 52 {
+                mask | (1 << card)
+            } else {
+                u64::MAX
+            }
+        });
+        if mask == u64::MAX || mask.count_ones() as usize != board.len() {
+            return Err(SolverError::InvalidCardConfig(format!(
+                "Invalid board: {board:?}"
+            )));
+        }
+        if mask & dead_cards != 0 {
+            return Err(SolverError::InvalidCardConfig(format!(
+                "Board contains a dead card: {board:?}"
+            )));
+        }
+
+        let (canonical, to_canonical) = symmetries
+            .iter()
+            .map(|permutation| {
+                let mut image = permutation.board(board);
+                image[..3].sort_unstable();
+                (image, *permutation)
+            })
+            .min_by(|a, b| a.0.cmp(&b.0))
+            .unwrap();
+
+        let &mut (representative, representative_to_canonical) = canonical_boards
+            .entry(canonical)
+            .or_insert((index, to_canonical));
+        ret.representatives.push(representative);
+        ret.permutations
+            .push(to_canonical.then(&representative_to_canonical.inverse()));
+    }
+
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::*;
+
+    #[test]
+    fn enumerate_boards_count() {
+        assert_eq!(enumerate_boards(3, 0, |_| true).unwrap().len(), 22100);
+
+        let paired_turns = enumerate_boards(4, 0, |board| {
+            let texture = BoardTexture::new(board);
+            texture.pairing() == Pairing::Paired && texture.rank_counts[board[3] as usize >> 2] == 2
+        })
+        .unwrap();
+        assert_eq!(paired_turns.len(), 18304 * 9);
+
+        let dead_cards = (0..4).fold(0, |acc, suit| acc | (1 << (48 + suit)));
+        let boards = enumerate_boards(3, dead_cards, |_| true).unwrap();
+        assert_eq!(boards.len(), 17296);
+        enumerate_boards(6, 0, |_| true).unwrap_err();
+    }
+
+    #[test]
+    fn sample_boards_seed() {
+        let predicate = |board: &[Card]| BoardTexture::new(board).is_monotone();
+        let boards1 = sample_boards(3, 20, 0, 1, predicate).unwrap();
+        let boards2 = sample_boards(3, 20, 0, 1, predicate).unwrap();
+        assert_eq!(boards1, boards2);
+        assert!(boards1.iter().all(|board| predicate(board)));
+        sample_boards(3, 1, 0, 1, |_| false).unwrap_err();
+    }
+
+    #[test]
+    fn isomorphic_boards() {
+        let flops = enumerate_boards(3, 0, |_| true).unwrap();
+        let ranges = [Range::ones(), Range::ones()];
+        let groups = group_isomorphic_boards(&flops, &ranges, 0).unwrap();
+        assert_eq!(groups.unique_indices().len(), 1755);
+        assert_eq!(groups.num_duplicates(), 22100 - 1755);
+        for (i, board) in flops.iter().enumerate() {
+            let mut image = groups.permutations[i].board(board);
+            image.sort_unstable();
+            assert_eq!(image, flops[groups.representatives[i]]);
+        }
+
+        // spades are distinguished by the range
+        let ranges = ["AsKs".parse().unwrap(), Range::ones()];
+        let groups = group_isomorphic_boards(&flops, &ranges, 0).unwrap();
+        assert!(groups.unique_indices().len() > 1755);
+
+        let permutation = SuitPermutation::new([1, 0, 3, 2]).unwrap();
+        assert_eq!(
+            permutation.then(&permutation.inverse()),
+            SuitPermutation::IDENTITY
+        );
+        SuitPermutation::new([0, 0, 1, 2]).unwrap_err();
+        group_isomorphic_boards(&[vec![0, 0, 1]], &ranges, 0).unwrap_err();
+    }
+}
@@ -0,0 +1,261 @@
+// [Bomb Pot]
+// A bomb pot has no preflop action: every player puts the same ante into the pot and sees the
+// flop with any two cards. A heads-up bomb pot is solved as an ordinary `PostFlopGame` from the
+// flop: `TreeConfig::bomb_pot` makes the action tree start on the flop with the antes as the pot
+// (see `BombPot`).
+//
+// Double-board bomb pots are not supported by `PostFlopGame`, which deals a single board. The
+// `double_board_*` functions only evaluate a showdown between two fixed hands; they do not solve
+// a double-board game.
+
+use crate::action_tree::*;
+use crate::card::*;
+use crate::error::*;
+use crate::evaluator::*;
+use crate::range::*;
+use std::cmp::Ordering;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+
+/// A heads-up bomb pot: both players put `ante` into the pot before the flop.
+///
+/// Set in [`TreeConfig::bomb_pot`], it makes [`ActionTree::new`] start the tree on the flop with
+/// the [`starting_pot`] and the [`effective_stack`] of the bomb pot. The corresponding fields of
+/// the tree configuration may be left `0`; otherwise, they must match the bomb pot.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let bomb_pot = BombPot { ante: 20, stack: 500 };
+/// let card_config = bomb_pot.card_config(flop_from_str("Td9d6h").unwrap());
+/// let tree_config = bomb_pot.tree_config(&TreeConfig::default());
+/// assert_eq!(card_config.range[0], Range::ones());
+/// assert_eq!(tree_config.starting_pot, 40);
+/// assert_eq!(tree_config.effective_stack, 480);
+///
+/// let action_tree = ActionTree::new(tree_config).unwrap();
+/// let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+/// assert_eq!(game.private_cards(0).len(), 49 * 48 / 2);
+/// assert_eq!(game.tree_config().bomb_pot, Some(bomb_pot));
+///
+/// // the pot and the stack are derived from the bomb pot
+/// let tree_config = TreeConfig {
+///     bomb_pot: Some(bomb_pot),
+///     ..Default::default()
+/// };
+/// let action_tree = ActionTree::new(tree_config).unwrap();
+/// assert_eq!(action_tree.config().starting_pot, 40);
+/// ```
+///
+/// [`starting_pot`]: BombPot::starting_pot
+/// [`effective_stack`]: BombPot::effective_stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct BombPot {
+    /// Ante of each player.
+    pub ante: i32,
+
+    /// Effective stack before the ante.
+    pub stack: i32,
+}
+
+impl BombPot {
+    /// Returns the pot on the flop: the antes of both players.
+    #[inline]
+    pub fn starting_pot(&self) -> i32 {
+        2 * self.ante
+    }
+
+    /// Returns the effective stack behind the ante.
+    #[inline]
+    pub fn effective_stack(&self) -> i32 {
+        self.stack - self.ante
+    }
+
+    /// Returns the card configuration of the bomb pot on `flop`: both players hold any two cards.
+    pub fn card_config(&self, flop: [Card; 3]) -> CardConfig {
+        CardConfig {
+            range: [Range::ones(), Range::ones()],
+            flop,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the tree configuration of the bomb pot from the flop: the antes as the pot and the
+    /// stack behind as the effective stack, with the other settings (e.g., bet sizes) of
+    /// `template`.
+    pub fn tree_config(&self, template: &TreeConfig) -> TreeConfig {
+        TreeConfig {
+            initial_state: BoardState::Flop,
+            starting_pot: self.starting_pot(),
+            effective_stack: self.effective_stack(),
+            bomb_pot: Some(*self),
+            ..template.clone()
+        }
+    }
+}
+
+/// Returns the share of the pot won by `hands[0]` at a double-board showdown.
+///
+/// Each board awards half of the pot, and a tie on a board splits the half. This is a standalone
+/// calculation: [`PostFlopGame`] deals a single board and cannot solve double-board games.
+///
+/// [`PostFlopGame`]: crate::PostFlopGame
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let boards = [
+///     board_from_str("Ac7d2h8s3c").unwrap().try_into().unwrap(),
+///     board_from_str("Kc7h2d8c3s").unwrap().try_into().unwrap(),
+/// ];
+/// let aces = (card_from_str("As").unwrap(), card_from_str("Ah").unwrap());
+/// let kings = (card_from_str("Ks").unwrap(), card_from_str("Kh").unwrap());
+/// let share = double_board_share(&HoldemEvaluator, [aces, kings], [&boards[0], &boards[1]]);
+/// assert_eq!(share, 0.5);
+/// ```
+pub fn double_board_share(
+    evaluator: &dyn HandEvaluator,
+    hands: [(Card, Card); 2],
+    boards: [&[Card; 5]; 2],
+) -> f64 {
+    boards
+        .iter()
+        .map(|board| {
+            let strength = hands.map(|hand| evaluator.evaluate(board, hand));
+            match strength[0].cmp(&strength[1]) {
+                Ordering::Greater => 0.5,
+                Ordering::Equal => 0.25,
+                Ordering::Less => 0.0,
+            }
+        })
+        .sum()
+}
+
+/// Computes the double-board equity of `hands[0]` against `hands[1]` by enumerating all runouts
+/// of both boards (see [`double_board_share`]).
+///
+/// The boards must have the same number of cards (three to five), and the cards of each street
+/// are dealt to both boards from the same deck. Returns [`SolverError::InvalidArgument`] if the
+/// boards have different or invalid lengths, or if the cards are not valid and distinct.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let boards = [
+///     board_from_str("Ac7d2h8s").unwrap(),
+///     board_from_str("Kc7h2d8c").unwrap(),
+/// ];
+/// let aces = (card_from_str("As").unwrap(), card_from_str("Ah").unwrap());
+/// let kings = (card_from_str("Ks").unwrap(), card_from_str("Kh").unwrap());
+/// let equity = double_board_equity([aces, kings], [&boards[0], &boards[1]]).unwrap();
+/// assert!(0.5 < equity && equity < 0.6);
+/// ```
+pub fn double_board_equity(
+    hands: [(Card, Card); 2],
+    boards: [&[Card]; 2],
+) -> Result<f64, SolverError> {
+    let board_len = boards[0].len();
+    if !(3..=5).contains(&board_len) || boards[1].len() != board_len {
+        return Err(SolverError::InvalidArgument(
+            "Boards must consist of the same number of cards (three to five)".to_owned(),
+        ));
+    }
+
+    let mut mask = 0u64;
+    let hand_cards = hands.iter().flat_map(|&(c1, c2)| [c1, c2]);
+    for card in hand_cards.chain(boards.iter().flat_map(|board| board.iter().copied())) {
+        if card >= 52 || mask & (1 << card) != 0 {
+            return Err(SolverError::InvalidArgument(
+                "Cards must be valid and distinct".to_owned(),
+            ));
+        }
+        mask |= 1 << card;
+    }
+
+    let mut full_boards = [[NOT_DEALT; 5]; 2];
+    for (full_board, board) in full_boards.iter_mut().zip(boards) {
+        full_board[..board_len].copy_from_slice(board);
+    }
+
+    let mut total = 0.0;
+    let mut count = 0;
+    deal_runouts(board_len, mask, &mut full_boards, &mut |full_boards| {
+        total += double_board_share(&HoldemEvaluator, hands, [&full_boards[0], &full_boards[1]]);
+        count += 1;
+    });
+
+    Ok(total / count as f64)
+}
+
+/// Deals the cards from the position `len` of both boards (the first board first) and calls `f`
+/// with each complete pair of boards.
+fn deal_runouts(
+    len: usize,
+    mask: u64,
+    boards: &mut [[Card; 5]; 2],
+    f: &mut impl FnMut(&[[Card; 5]; 2]),
+) {
+    if len == 5 {
+        f(boards);
+        return;
+    }
+
+    for card1 in 0..52 {
+        if mask & (1 << card1) != 0 {
+            continue;
+        }
+        for card2 in 0..52 {
+            if card2 == card1 || mask & (1 << card2) != 0 {
+                continue;
+            }
+            boards[0][len] = card1;
+            boards[1][len] = card2;
+            deal_runouts(len + 1, mask | (1 << card1) | (1 << card2), boards, f);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn double_board() {
+        let hand = |s: &str| {
+            let cards = cards_from_str(s).unwrap();
+            (cards[0], cards[1])
+        };
+        let boards = [
+            board_from_str("Td9d6h2c").unwrap(),
+            board_from_str("AhKs3c4s").unwrap(),
+        ];
+        let boards = [boards[0].as_slice(), boards[1].as_slice()];
+        let nuts = hand("8c7c");
+        let aces = hand("AsAd");
+
+        let equity = double_board_equity([nuts, aces], boards).unwrap();
+        let reverse = double_board_equity([aces, nuts], boards).unwrap();
+        assert!((equity + reverse - 1.0).abs() < 1e-9);
+
+        // each board is won by a different hand most of the time
+        assert!(0.3 < equity && equity < 0.7);
+
+        // hands of the same ranks
+        let share = double_board_equity([nuts, hand("8d7d")], boards).unwrap();
+        assert!((share - 0.5).abs() < 0.1);
+
+        assert!(matches!(
+            double_board_equity([nuts, nuts], boards),
+            Err(SolverError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            double_board_equity([nuts, aces], [boards[0], &boards[1][..3]]),
+            Err(SolverError::InvalidArgument(_))
+        ));
+    }
+}
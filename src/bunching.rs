@@ -1,5 +1,7 @@
 use crate::atomic_float::*;
 use crate::card::*;
+use crate::error::*;
+use crate::random::*;
 use crate::range::*;
 use crate::utility::*;
 use std::io::{self, Write};
@@ -19,6 +21,14 @@ const COMB_49_5: usize = 1906884;
 const COMB_49_6: usize = 13983816;
 const COMB_49_8: usize = 450978066;
 
+/// `COMB_49[k]` = 49 choose `k` for `k` <= 6.
+const COMB_49: [usize; 7] = [
+    1, COMB_49_1, COMB_49_2, COMB_49_3, COMB_49_4, COMB_49_5, COMB_49_6,
+];
+
+/// Number of samples processed by each task of [`BunchingData::process_approximate`].
+const SAMPLE_CHUNK_SIZE: usize = 4096;
+
 const COMB_TABLE: [[usize; 49]; 8] = [
     [
         0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
@@ -78,6 +88,11 @@ const COMB_TABLE: [[usize; 49]; 8] = [
 /// In this case, the query is defined by 6 cards (2 for the hero, 2 for the opponent, 2 for the
 /// board), and this struct can answer the query instantly after precomputation.
 ///
+/// Each folded seat has its own fold range, so the card removal is specific to the positions of
+/// the folded players (e.g., the folds of UTG and CO remove different cards in a blind-vs-blind
+/// spot). The weight of each set of dead cards is the sum of the products of the weights of the
+/// seats over all assignments of disjoint hands to the seats.
+///
 /// # Examples
 ///
 /// ```no_run
@@ -182,35 +197,72 @@ fn compress_mask(mut mask: u64, flop: [Card; 3]) -> u64 {
     mask
 }
 
+/// Allocates a zero-initialized table in parallel.
+#[inline]
+fn zeros_f64(len: usize) -> Vec<AtomicF64> {
+    into_par_iter(0..len).map(|_| AtomicF64::new(0.0)).collect()
+}
+
+/// Allocates a zero-initialized table in parallel.
+#[inline]
+fn zeros_f32(len: usize) -> Vec<AtomicF32> {
+    into_par_iter(0..len).map(|_| AtomicF32::new(0.0)).collect()
+}
+
+/// Returns the number of samples of [`BunchingData::process_approximate`] for the target standard
+/// error `max_std_error`, i.e., `ceil(1 / (4 * max_std_error^2))`.
+///
+/// The estimated fractions are at most 1, so their standard errors are at most
+/// `0.5 / sqrt(#(samples))`.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// assert_eq!(approximate_bunching_num_samples(0.001), 250000);
+/// ```
+#[inline]
+pub fn approximate_bunching_num_samples(max_std_error: f64) -> usize {
+    (0.25 / (max_std_error * max_std_error)).ceil() as usize
+}
+
 impl BunchingData {
     /// Creates a new `BunchingConfig` instance.
     ///
-    /// `fold_ranges` can contain at most 4 ranges (6-max).
+    /// `fold_ranges` contains the fold range of each folded seat, in any order. Empty ranges are
+    /// ignored, and at most 4 nonempty ranges can be given (6-max). The same range may be given
+    /// for multiple seats.
     #[inline]
-    pub fn new(fold_ranges: &[Range], mut flop: [Card; 3]) -> Result<Self, String> {
+    pub fn new(fold_ranges: &[Range], mut flop: [Card; 3]) -> Result<Self, SolverError> {
         let mut fold_ranges_vec = Vec::new();
 
         for range in fold_ranges {
             if !range.is_empty() {
                 if !range.is_suit_symmetric() {
-                    return Err("Fold ranges must be suit-symmetric".to_owned());
+                    return Err(SolverError::InvalidCardConfig(
+                        "Fold ranges must be suit-symmetric".to_owned(),
+                    ));
                 }
                 fold_ranges_vec.push(*range);
             }
         }
 
         if fold_ranges_vec.is_empty() {
-            return Err("Fold ranges is empty".to_owned());
+            return Err(SolverError::InvalidCardConfig(
+                "Fold ranges is empty".to_owned(),
+            ));
         }
 
         if fold_ranges_vec.len() > 4 {
-            return Err("The number of folded players must be at most 4".to_owned());
+            return Err(SolverError::InvalidCardConfig(
+                "The number of folded players must be at most 4".to_owned(),
+            ));
         }
 
         flop.sort_unstable();
 
         if flop[0] == flop[1] || flop[1] == flop[2] || flop[2] >= 52 {
-            return Err("Invalid flop".to_owned());
+            return Err(SolverError::InvalidCardConfig("Invalid flop".to_owned()));
         }
 
         Ok(Self {
@@ -289,6 +341,134 @@ impl BunchingData {
         self.phase3(print_progress);
     }
 
+    /// Computes the tables approximately by Monte Carlo sampling, instead of [`process`].
+    ///
+    /// The hands of the folded seats are sampled independently from their fold ranges, and the
+    /// weight of each set of dead cards is estimated from the fraction of the samples whose hands
+    /// are disjoint from each other and from the dead cards. `max_std_error` is the target
+    /// standard error of the estimated fractions, which determines the number of samples
+    /// ([`approximate_bunching_num_samples`]). The samples are drawn from the random number
+    /// generator seeded with `seed`, so the result is reproducible.
+    ///
+    /// Unlike the exact computation, the time is proportional to the number of samples and the
+    /// 8-card table of 4 folded players (3.42GB) is not needed, so this method is much cheaper on
+    /// wide fold ranges. The additional memory usage is at most 127MB.
+    ///
+    /// Returns an error if the instance has already been processed, `max_std_error` is not in
+    /// the range (0, 0.5), or a fold range has no hands on the flop.
+    ///
+    /// [`process`]: BunchingData::process
+    pub fn process_approximate(
+        &mut self,
+        max_std_error: f64,
+        seed: u64,
+    ) -> Result<(), SolverError> {
+        if self.phase != 0 {
+            return Err(SolverError::InvalidState(
+                "Bunching data is already processed".to_owned(),
+            ));
+        }
+
+        if !(max_std_error > 0.0 && max_std_error < 0.5) {
+            return Err(SolverError::InvalidArgument(format!(
+                "Standard error must be in the range (0, 0.5): {max_std_error}"
+            )));
+        }
+
+        // (compressed masks, cumulative weights) of the hands of each seat
+        let flop_mask: u64 = self.flop.iter().map(|&c| 1 << c).sum();
+        let mut seats = Vec::with_capacity(self.fold_ranges.len());
+        for range in &self.fold_ranges {
+            let mut masks = Vec::new();
+            let mut cumulative = Vec::new();
+            let mut total = 0.0;
+            for card1 in 0..52 {
+                for card2 in card1 + 1..52 {
+                    let mask: u64 = (1 << card1) | (1 << card2);
+                    let weight = range.get_weight_by_cards(card1, card2) as f64;
+                    if weight > 0.0 && mask & flop_mask == 0 {
+                        total += weight;
+                        masks.push(compress_mask(mask, self.flop));
+                        cumulative.push(total);
+                    }
+                }
+            }
+            if total == 0.0 {
+                return Err(SolverError::InvalidCardConfig(
+                    "Fold range has no hands on the flop".to_owned(),
+                ));
+            }
+            seats.push((masks, cumulative));
+        }
+
+        let num_samples = approximate_bunching_num_samples(max_std_error);
+        let num_cards = 2 * seats.len();
+        let total_weight = seats.iter().map(|(_, c)| c[c.len() - 1]).product::<f64>();
+        let scale = total_weight / num_samples as f64;
+
+        self.sum = Default::default();
+        for (k, sum) in self.sum.iter_mut().enumerate().take(num_cards.min(6) + 1) {
+            *sum = zeros_f64(COMB_49[k]);
+        }
+
+        let sum = &self.sum;
+        let num_chunks = num_samples.div_ceil(SAMPLE_CHUNK_SIZE);
+        into_par_iter(0..num_chunks).for_each(|chunk| {
+            let mut rng = fastrand::Rng::with_seed(seed.wrapping_add(chunk as u64));
+            let len = SAMPLE_CHUNK_SIZE.min(num_samples - chunk * SAMPLE_CHUNK_SIZE);
+
+            'sample: for _ in 0..len {
+                let mut mask = 0;
+                for (masks, cumulative) in &seats {
+                    let x = rng.f64() * cumulative[cumulative.len() - 1];
+                    let index = cumulative.partition_point(|&c| c <= x);
+                    let hand = masks[index.min(masks.len() - 1)];
+                    if mask & hand != 0 {
+                        continue 'sample;
+                    }
+                    mask |= hand;
+                }
+
+                // same as the phase 2: adds to every subset of at most 6 cards
+                let mut bits = [0; 8];
+                for bit in bits.iter_mut().take(num_cards) {
+                    *bit = mask & mask.wrapping_neg();
+                    mask ^= *bit;
+                }
+                for subset in 0u32..1 << num_cards {
+                    let k = subset.count_ones() as usize;
+                    if k > 6 {
+                        continue;
+                    }
+                    let subset_mask = (0..num_cards)
+                        .filter(|&j| subset & (1 << j) != 0)
+                        .fold(0, |acc, j| acc | bits[j]);
+                    sum[k][mask_to_index(subset_mask, k)].add(scale);
+                }
+            }
+        });
+
+        self.phase = 2;
+        self.progress_percent = 100;
+        self.phase3(false);
+        Ok(())
+    }
+
+    /// Same as [`process_approximate`], but draws the seed from `rng`.
+    ///
+    /// The samples are drawn in parallel with generators derived from the seed, so only the seed
+    /// is drawn from `rng`.
+    ///
+    /// [`process_approximate`]: BunchingData::process_approximate
+    #[inline]
+    pub fn process_approximate_with_rng<R: RandomSource + ?Sized>(
+        &mut self,
+        max_std_error: f64,
+        rng: &mut R,
+    ) -> Result<(), SolverError> {
+        self.process_approximate(max_std_error, rng.next_u64())
+    }
+
     /// Processes the phase 1.
     #[inline]
     pub fn phase1(&mut self, print_progress: bool) {
@@ -386,20 +566,20 @@ impl BunchingData {
         );
 
         self.sum[0] = vec![AtomicF64::new(0.0)];
-        self.sum[1] = (0..COMB_49_1).map(|_| AtomicF64::new(0.0)).collect();
+        self.sum[1] = zeros_f64(COMB_49_1);
 
         if self.fold_ranges.len() >= 2 {
-            self.sum[2] = (0..COMB_49_2).map(|_| AtomicF64::new(0.0)).collect();
-            self.sum[3] = (0..COMB_49_3).map(|_| AtomicF64::new(0.0)).collect();
+            self.sum[2] = zeros_f64(COMB_49_2);
+            self.sum[3] = zeros_f64(COMB_49_3);
         }
 
         if self.fold_ranges.len() >= 3 {
-            self.sum[4] = (0..COMB_49_4).map(|_| AtomicF64::new(0.0)).collect();
-            self.sum[5] = (0..COMB_49_5).map(|_| AtomicF64::new(0.0)).collect();
+            self.sum[4] = zeros_f64(COMB_49_4);
+            self.sum[5] = zeros_f64(COMB_49_5);
         }
 
         if self.fold_ranges.len() == 4 {
-            self.sum[6] = (0..COMB_49_6).map(|_| AtomicF64::new(0.0)).collect();
+            self.sum[6] = zeros_f64(COMB_49_6);
         }
 
         self.phase = 2;
@@ -414,9 +594,9 @@ impl BunchingData {
             "Invalid state"
         );
 
-        self.result4 = (0..COMB_49_4).map(|_| AtomicF32::new(0.0)).collect();
-        self.result5 = (0..COMB_49_5).map(|_| AtomicF32::new(0.0)).collect();
-        self.result6 = (0..COMB_49_6).map(|_| AtomicF32::new(0.0)).collect();
+        self.result4 = zeros_f32(COMB_49_4);
+        self.result5 = zeros_f32(COMB_49_5);
+        self.result6 = zeros_f32(COMB_49_6);
 
         self.phase = 3;
         self.progress_percent = 0;
@@ -516,7 +696,7 @@ impl BunchingData {
     fn phase1_prepare1(&mut self) {
         self.temp_table1 = vec![0.0; COMB_49_2];
         Self::phase1_compress(&mut self.temp_table1, &self.fold_ranges[0], self.flop);
-        self.sum[2] = (0..COMB_49_2).map(|_| AtomicF64::new(0.0)).collect();
+        self.sum[2] = zeros_f64(COMB_49_2);
     }
 
     fn phase1_prepare2(&mut self) {
@@ -526,44 +706,26 @@ impl BunchingData {
         Self::phase1_compress(&mut self.temp_table1, &self.fold_ranges[0], self.flop);
         Self::phase1_compress(&mut self.temp_table2, &self.fold_ranges[1], self.flop);
 
-        self.sum[4] = (0..COMB_49_4).map(|_| AtomicF64::new(0.0)).collect();
+        self.sum[4] = zeros_f64(COMB_49_4);
     }
 
     fn phase1_prepare3(&mut self) {
-        self.temp_table1 = vec![0.0; COMB_49_4];
+        self.temp_table1 =
+            Self::phase1_combine(&self.fold_ranges[0], &self.fold_ranges[1], self.flop);
         self.temp_table2 = vec![0.0; COMB_49_2];
 
-        Self::phase1_combine(
-            &mut self.temp_table1,
-            &self.fold_ranges[0],
-            &self.fold_ranges[1],
-            self.flop,
-        );
-
         Self::phase1_compress(&mut self.temp_table2, &self.fold_ranges[2], self.flop);
 
-        self.sum[6] = (0..COMB_49_6).map(|_| AtomicF64::new(0.0)).collect();
+        self.sum[6] = zeros_f64(COMB_49_6);
     }
 
     fn phase1_prepare4(&mut self) {
-        self.temp_table1 = vec![0.0; COMB_49_4];
-        self.temp_table2 = vec![0.0; COMB_49_4];
-
-        Self::phase1_combine(
-            &mut self.temp_table1,
-            &self.fold_ranges[0],
-            &self.fold_ranges[1],
-            self.flop,
-        );
+        self.temp_table1 =
+            Self::phase1_combine(&self.fold_ranges[0], &self.fold_ranges[1], self.flop);
+        self.temp_table2 =
+            Self::phase1_combine(&self.fold_ranges[2], &self.fold_ranges[3], self.flop);
 
-        Self::phase1_combine(
-            &mut self.temp_table2,
-            &self.fold_ranges[2],
-            &self.fold_ranges[3],
-            self.flop,
-        );
-
-        self.temp_table3 = (0..COMB_49_8).map(|_| AtomicF64::new(0.0)).collect();
+        self.temp_table3 = zeros_f64(COMB_49_8);
     }
 
     fn phase1_compress(table: &mut [f64], range: &Range, flop: [Card; 3]) {
@@ -594,56 +756,29 @@ impl BunchingData {
         }
     }
 
-    fn phase1_combine(table: &mut [f64], range1: &Range, range2: &Range, flop: [Card; 3]) {
-        let range1 = range1.raw_data();
-        let range2 = range2.raw_data();
-        let flop_mask: u64 = flop.iter().map(|&c| 1 << c).sum();
-
-        let mut src_index1 = 0;
-
-        for card11 in 0..52 {
-            let mask11 = 1 << card11;
-            if flop_mask & mask11 != 0 {
-                src_index1 += 51 - card11;
-                continue;
-            }
-
-            for card12 in card11 + 1..52 {
-                let freq1 = range1[src_index1] as f64;
-                src_index1 += 1;
-
-                let mask12 = 1 << card12;
-                if flop_mask & mask12 != 0 || freq1 == 0.0 {
-                    continue;
+    fn phase1_combine(range1: &Range, range2: &Range, flop: [Card; 3]) -> Vec<f64> {
+        let mut table1 = vec![0.0; COMB_49_2];
+        let mut table2 = vec![0.0; COMB_49_2];
+        Self::phase1_compress(&mut table1, range1, flop);
+        Self::phase1_compress(&mut table2, range2, flop);
+
+        // each set of 4 cards is split into the hands of the two players in 6 ways
+        into_par_iter(0..COMB_49_4)
+            .map(|index| {
+                let mask = index_to_mask(index, 4);
+                let lowest = mask & mask.wrapping_neg();
+                let mut rest = mask ^ lowest;
+                let mut sum = 0.0;
+                while rest != 0 {
+                    let partner = rest & rest.wrapping_neg();
+                    rest ^= partner;
+                    let hand1 = mask_to_index(lowest | partner, 2);
+                    let hand2 = mask_to_index(mask ^ (lowest | partner), 2);
+                    sum += table1[hand1] * table2[hand2] + table1[hand2] * table2[hand1];
                 }
-
-                let mask1 = mask11 | mask12;
-                let mut src_index2 = 0;
-
-                for card21 in 0..52 {
-                    let mask21 = 1 << card21;
-                    if (flop_mask | mask1) & mask21 != 0 {
-                        src_index2 += 51 - card21;
-                        continue;
-                    }
-
-                    let mask2 = mask1 | mask21;
-                    for card22 in card21 + 1..52 {
-                        let freq2 = range2[src_index2] as f64;
-                        src_index2 += 1;
-
-                        let mask22 = 1 << card22;
-                        if (flop_mask | mask1) & mask22 != 0 || freq2 == 0.0 {
-                            continue;
-                        }
-
-                        let mask = mask2 | mask22;
-                        let index = mask_to_index(compress_mask(mask, flop), 4);
-                        table[index] += freq1 * freq2;
-                    }
-                }
-            }
-        }
+                sum
+            })
+            .collect()
     }
 
     /* Phase 1: Main process */
@@ -1021,6 +1156,104 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bunching_per_seat() {
+        // overlapping fold ranges of two seats, verified by brute force
+        let ranges: [Range; 2] = ["AA,KK,AKs".parse().unwrap(), "AKo,KQ,QQ".parse().unwrap()];
+        let flop = flop_from_str("2s3h4d").unwrap();
+        let mut bunching = BunchingData::new(&ranges, flop).unwrap();
+        bunching.process(false);
+
+        let flop_mask: u64 = flop.iter().map(|&c| 1 << c).sum();
+        let brute_force = |dead: u64| {
+            let mut ret = 0.0;
+            for c1 in 0..52 {
+                for c2 in c1 + 1..52 {
+                    let mask1 = (1 << c1) | (1 << c2);
+                    let w1 = ranges[0].get_weight_by_cards(c1, c2) as f64;
+                    if w1 == 0.0 || mask1 & (flop_mask | dead) != 0 {
+                        continue;
+                    }
+                    for c3 in 0..52 {
+                        for c4 in c3 + 1..52 {
+                            let mask2 = (1 << c3) | (1 << c4);
+                            let w2 = ranges[1].get_weight_by_cards(c3, c4) as f64;
+                            if w2 != 0.0 && mask2 & (flop_mask | dead | mask1) == 0 {
+                                ret += w1 * w2;
+                            }
+                        }
+                    }
+                }
+            }
+            ret as f32
+        };
+
+        let mask = |s: &str| cards_from_str(s).unwrap().iter().map(|&c| 1 << c).sum();
+        for hands in ["AsKsQsQh", "AhAdKcKd", "5c6c7c8c", "AcKhQdJs"] {
+            assert_eq!(
+                bunching.result_4cards(mask(hands)),
+                brute_force(mask(hands))
+            );
+        }
+        for dead in ["AsKsQsQhJc", "AhAdKcKd9s"] {
+            assert_eq!(bunching.result_5cards(mask(dead)), brute_force(mask(dead)));
+        }
+        for dead in ["AsKsQsQhJcTc", "AhKdKcQd9s8s"] {
+            assert_eq!(bunching.result_6cards(mask(dead)), brute_force(mask(dead)));
+        }
+
+        // the seats are distinguished: the result differs from the uniform treatment
+        let uniform = [ranges[0], ranges[0]];
+        let mut uniform = BunchingData::new(&uniform, flop).unwrap();
+        uniform.process(false);
+        let dead = mask("AsKsQsQh");
+        assert_ne!(uniform.result_4cards(dead), bunching.result_4cards(dead));
+
+        // empty ranges are ignored
+        let with_empty = BunchingData::new(&[ranges[0], Range::new(), ranges[1]], flop).unwrap();
+        assert_eq!(with_empty.fold_ranges(), &ranges);
+    }
+
+    #[test]
+    fn test_bunching_approximate() {
+        let range1 = "77,76,75,74,73,72,66,65,64,63,62,55,54,53,52,44,43,42,33,32,22";
+        let range2 = "AA,AK,AQ,AJ,AT,A9,KK,KQ,KJ,KT,K9,QQ,QJ,QT,Q9,JJ,JT,J9,TT,T9,99";
+        let ranges: [Range; 2] = [range1.parse().unwrap(), range2.parse().unwrap()];
+        let flop = flop_from_str("8s8h8d").unwrap();
+
+        let mut exact = BunchingData::new(&ranges, flop).unwrap();
+        exact.process(false);
+
+        let max_std_error = 0.002;
+        let mut approximate = BunchingData::new(&ranges, flop).unwrap();
+        approximate.process_approximate(max_std_error, 42).unwrap();
+        assert!(approximate.is_ready());
+        approximate
+            .process_approximate(max_std_error, 42)
+            .unwrap_err();
+
+        // the weights are compared as fractions of all combinations of the fold hands
+        let total = f64::powi(24.0 * 23.0 / 2.0, 2) as f32;
+        let tolerance = 5.0 * max_std_error as f32;
+        let mask = |s: &str| cards_from_str(s).unwrap().iter().map(|&c| 1 << c).sum();
+        for hands in ["AsKsQsQh", "7c7d2c2d", "AcKh6c5d"] {
+            let expected = exact.result_4cards(mask(hands)) / total;
+            let actual = approximate.result_4cards(mask(hands)) / total;
+            assert!((actual - expected).abs() < tolerance);
+        }
+        for dead in ["AsKsQsQh7c7d", "9s9hTdTcJcJd"] {
+            let expected = exact.result_6cards(mask(dead)) / total;
+            let actual = approximate.result_6cards(mask(dead)) / total;
+            assert!((actual - expected).abs() < tolerance);
+        }
+
+        let mut invalid = BunchingData::new(&ranges, flop).unwrap();
+        invalid.process_approximate(0.0, 42).unwrap_err();
+        invalid.process_approximate(0.5, 42).unwrap_err();
+        let mut on_flop = BunchingData::new(&["88".parse().unwrap()], flop).unwrap();
+        on_flop.process_approximate(0.01, 42).unwrap_err();
+    }
+
     #[test]
     #[ignore]
     fn test_bunching_independent_4() {
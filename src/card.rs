@@ -1,4 +1,6 @@
-use crate::hand::*;
+use crate::evaluator::*;
+use crate::hi_lo::*;
+use crate::position::*;
 use crate::range::*;
 use std::mem;
 
@@ -16,6 +18,14 @@ pub type Card = u8;
 /// Constant representing that the card is not yet dealt.
 pub const NOT_DEALT: Card = Card::MAX;
 
+/// Bitmask of the cards removed from the deck in short-deck (6+) hold'em: the cards of ranks 2-5.
+///
+/// Pass this mask to [`enumerate_boards`] or [`sample_boards`] to generate short-deck boards.
+///
+/// [`enumerate_boards`]: crate::enumerate_boards
+/// [`sample_boards`]: crate::sample_boards
+pub const SHORT_DECK_DEAD_CARDS: u64 = 0xffff;
+
 /// A struct containing the card configuration.
 ///
 /// # Examples
@@ -30,6 +40,9 @@ pub const NOT_DEALT: Card = Card::MAX;
 ///     flop: flop_from_str("Td9d6h").unwrap(),
 ///     turn: card_from_str("Qc").unwrap(),
 ///     river: NOT_DEALT,
+///     dead_cards: 0,
+///     short_deck: false,
+///     positions: [Some(Position::BigBlind), Some(Position::Button)],
 /// };
 /// ```
 #[derive(Debug, Clone)]
@@ -46,6 +59,61 @@ pub struct CardConfig {
 
     /// River card: must be in range [`0`, `52`) or `NOT_DEALT`.
     pub river: Card,
+
+    /// Bitmask of dead cards (`1 << card` for each card): must not overlap with board cards.
+    ///
+    /// Dead cards (e.g., exposed or mucked cards) are excluded from both ranges and runouts.
+    pub dead_cards: u64,
+
+    /// Whether to play short-deck (6+) hold'em: the cards of ranks 2-5 are removed from the deck,
+    /// a flush beats a full house, and A-6-7-8-9 is the lowest straight.
+    ///
+    /// In short-deck mode, board cards and ranges must not contain the removed cards.
+    pub short_deck: bool,
+
+    /// Position of each player (OOP, IP), or `None` if unspecified (default).
+    ///
+    /// The positions are metadata carried through the reports, the exports, and the saved files;
+    /// they do not affect the solving. If both are given, OOP must act first postflop.
+    pub positions: [Option<Position>; 2],
+}
+
+/// Available modes of the suit isomorphism applied to the turn and river deals.
+///
+/// By default, isomorphic chance events (e.g., the three non-dealt suits on a monotone flop) are
+/// combined into one branch, and the results of the eliminated branches are obtained by swapping
+/// suits. Restricting the isomorphism makes every eliminated card a distinct chance branch of its
+/// own, which is useful when the results must be indexed by literal cards. Note that disabling
+/// the isomorphism increases the size of the game tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub enum IsomorphismMode {
+    /// Combines isomorphic turn and river deals (default).
+    #[default]
+    Full,
+
+    /// Combines isomorphic turn deals only.
+    TurnOnly,
+
+    /// Combines isomorphic river deals only.
+    RiverOnly,
+
+    /// Does not combine any chance events.
+    Disabled,
+}
+
+impl IsomorphismMode {
+    /// Returns whether the isomorphic turn deals are combined.
+    #[inline]
+    pub fn is_turn_enabled(self) -> bool {
+        matches!(self, Self::Full | Self::TurnOnly)
+    }
+
+    /// Returns whether the isomorphic river deals are combined.
+    #[inline]
+    pub fn is_river_enabled(self) -> bool {
+        matches!(self, Self::Full | Self::RiverOnly)
+    }
 }
 
 impl Default for CardConfig {
@@ -56,6 +124,9 @@ impl Default for CardConfig {
             flop: [NOT_DEALT; 3],
             turn: NOT_DEALT,
             river: NOT_DEALT,
+            dead_cards: 0,
+            short_deck: false,
+            positions: [None; 2],
         }
     }
 }
@@ -70,6 +141,20 @@ pub(crate) struct StrengthItem {
     pub(crate) index: u16,
 }
 
+/// Showdown strengths of a split-pot game on a runout (see [`SplitPotEvaluator`]). The strengths
+/// are ranks starting from 1, and each list is sorted in ascending order of strength.
+#[derive(Clone, Default)]
+pub(crate) struct SplitPotStrength {
+    /// High strengths of all the hands.
+    pub(crate) high: Vec<StrengthItem>,
+
+    /// High strengths of the hands that do not qualify for low.
+    pub(crate) high_no_low: Vec<StrengthItem>,
+
+    /// Low strengths of the hands that qualify for low.
+    pub(crate) low: Vec<StrengthItem>,
+}
+
 pub(crate) type SwapList = [Vec<(u16, u16)>; 2];
 
 type IsomorphismData = (
@@ -103,6 +188,32 @@ pub(crate) fn index_to_card_pair(index: usize) -> (Card, Card) {
 }
 
 impl CardConfig {
+    /// Returns whether the given card is specified as a dead card.
+    #[inline]
+    pub(crate) fn is_dead(&self, card: Card) -> bool {
+        self.dead_mask() & (1 << card) != 0
+    }
+
+    /// Returns the bitmask of the dead cards, including the removed cards in short-deck mode.
+    #[inline]
+    pub(crate) fn dead_mask(&self) -> u64 {
+        if self.short_deck {
+            self.dead_cards | SHORT_DECK_DEAD_CARDS
+        } else {
+            self.dead_cards
+        }
+    }
+
+    /// Returns the default hand evaluator of the configured variant.
+    #[inline]
+    pub(crate) fn default_evaluator(&self) -> &'static dyn HandEvaluator {
+        if self.short_deck {
+            &ShortDeckEvaluator
+        } else {
+            &HoldemEvaluator
+        }
+    }
+
     pub(crate) fn valid_indices(
         &self,
         private_cards: &PrivateCards,
@@ -119,6 +230,7 @@ impl CardConfig {
         let mut ret_turn = vec![Indices::default(); 52];
         for board in 0..52 {
             if !self.flop.contains(&board)
+                && !self.is_dead(board)
                 && (self.turn == NOT_DEALT || self.turn == board)
                 && self.river == NOT_DEALT
             {
@@ -132,6 +244,8 @@ impl CardConfig {
             for board2 in board1 + 1..52 {
                 if !self.flop.contains(&board1)
                     && !self.flop.contains(&board2)
+                    && !self.is_dead(board1)
+                    && !self.is_dead(board2)
                     && (self.turn == NOT_DEALT || board1 == self.turn || board2 == self.turn)
                     && (self.river == NOT_DEALT || board1 == self.river || board2 == self.river)
                 {
@@ -183,22 +297,59 @@ impl CardConfig {
     pub(crate) fn hand_strength(
         &self,
         private_cards: &PrivateCards,
+        evaluator: &dyn HandEvaluator,
     ) -> Vec<[Vec<StrengthItem>; 2]> {
         let mut ret = vec![Default::default(); 52 * 51 / 2];
+        let mut strength_table = vec![0; 52 * 51 / 2];
+        let mut hands = Vec::with_capacity(52 * 51 / 2);
+        let mut indices = Vec::with_capacity(52 * 51 / 2);
+        let mut values = Vec::with_capacity(52 * 51 / 2);
+        let mut sorted = Vec::with_capacity(52 * 51 / 2);
 
-        let mut board = Hand::new();
-        for &card in &self.flop {
-            board = board.add_card(card as usize);
-        }
+        let flop_mask: u64 = (1 << self.flop[0]) | (1 << self.flop[1]) | (1 << self.flop[2]);
 
         for board1 in 0..52 {
             for board2 in board1 + 1..52 {
-                if !board.contains(board1 as usize)
-                    && !board.contains(board2 as usize)
+                if !self.flop.contains(&board1)
+                    && !self.flop.contains(&board2)
+                    && !self.is_dead(board1)
+                    && !self.is_dead(board2)
                     && (self.turn == NOT_DEALT || board1 == self.turn || board2 == self.turn)
                     && (self.river == NOT_DEALT || board1 == self.river || board2 == self.river)
                 {
-                    let board = board.add_card(board1 as usize).add_card(board2 as usize);
+                    let board = [self.flop[0], self.flop[1], self.flop[2], board1, board2];
+                    let board_mask = flop_mask | (1 << board1) | (1 << board2);
+
+                    // evaluate each hole card combination only once for both players, in a batch
+                    strength_table.fill(0);
+                    hands.clear();
+                    indices.clear();
+                    for &(c1, c2) in private_cards.iter().flatten() {
+                        let index = card_pair_to_index(c1, c2);
+                        let hand_mask: u64 = (1 << c1) | (1 << c2);
+                        if strength_table[index] == 0 && hand_mask & board_mask == 0 {
+                            strength_table[index] = 1;
+                            hands.push((c1, c2));
+                            indices.push(index);
+                        }
+                    }
+
+                    values.clear();
+                    values.resize(hands.len(), 0);
+                    evaluator.evaluate_batch(&board, &hands, &mut values);
+
+                    // the values are converted into ranks starting from 1 (0 is for the sentinel)
+                    sorted.clear();
+                    sorted.extend(values.iter().copied().zip(indices.iter().copied()));
+                    sorted.sort_unstable();
+                    let mut rank = 0;
+                    for (i, &(value, index)) in sorted.iter().enumerate() {
+                        if i == 0 || value != sorted[i - 1].0 {
+                            rank += 1;
+                        }
+                        strength_table[index] = rank;
+                    }
+
                     let mut strength = [
                         Vec::with_capacity(private_cards[0].len() + 2),
                         Vec::with_capacity(private_cards[1].len() + 2),
@@ -218,16 +369,11 @@ impl CardConfig {
                         strength[player].extend(
                             private_cards[player].iter().enumerate().filter_map(
                                 |(index, &(c1, c2))| {
-                                    let (c1, c2) = (c1 as usize, c2 as usize);
-                                    if board.contains(c1) || board.contains(c2) {
-                                        None
-                                    } else {
-                                        let hand = board.add_card(c1).add_card(c2);
-                                        Some(StrengthItem {
-                                            strength: hand.evaluate() + 1, // +1 to avoid 0
-                                            index: index as u16,
-                                        })
-                                    }
+                                    let strength = strength_table[card_pair_to_index(c1, c2)];
+                                    (strength != 0).then_some(StrengthItem {
+                                        strength,
+                                        index: index as u16,
+                                    })
                                 },
                             ),
                         );
@@ -244,13 +390,103 @@ impl CardConfig {
         ret
     }
 
-    pub(crate) fn isomorphism(&self, private_cards: &[Vec<(Card, Card)>; 2]) -> IsomorphismData {
+    pub(crate) fn split_pot_strength(
+        &self,
+        private_cards: &PrivateCards,
+        evaluator: &dyn SplitPotEvaluator,
+    ) -> Vec<[SplitPotStrength; 2]> {
+        let mut ret = vec![Default::default(); 52 * 51 / 2];
+        let flop_mask: u64 = (1 << self.flop[0]) | (1 << self.flop[1]) | (1 << self.flop[2]);
+
+        for board1 in 0..52 {
+            for board2 in board1 + 1..52 {
+                if !self.flop.contains(&board1)
+                    && !self.flop.contains(&board2)
+                    && !self.is_dead(board1)
+                    && !self.is_dead(board2)
+                    && (self.turn == NOT_DEALT || board1 == self.turn || board2 == self.turn)
+                    && (self.river == NOT_DEALT || board1 == self.river || board2 == self.river)
+                {
+                    let board = [self.flop[0], self.flop[1], self.flop[2], board1, board2];
+                    let board_mask = flop_mask | (1 << board1) | (1 << board2);
+
+                    // (index, high value, low value) of the hands not conflicting with the board
+                    let values = private_cards.each_ref().map(|cards| {
+                        cards
+                            .iter()
+                            .enumerate()
+                            .filter(|&(_, &(c1, c2))| ((1 << c1) | (1 << c2)) & board_mask == 0)
+                            .map(|(index, &(c1, c2))| {
+                                let hand = [c1, c2];
+                                let high = evaluator.evaluate_high(&board, &hand);
+                                let low = evaluator.evaluate_low(&board, &hand);
+                                (index as u16, high, low)
+                            })
+                            .collect::<Vec<_>>()
+                    });
+
+                    // the values are converted into ranks starting from 1, shared by both players
+                    let mut high_values = values.iter().flatten().map(|v| v.1).collect::<Vec<_>>();
+                    let mut low_values = values
+                        .iter()
+                        .flatten()
+                        .filter_map(|v| v.2)
+                        .collect::<Vec<_>>();
+                    for values in [&mut high_values, &mut low_values] {
+                        values.sort_unstable();
+                        values.dedup();
+                    }
+                    let rank =
+                        |values: &[u32], value| values.binary_search(&value).unwrap() as u16 + 1;
+
+                    ret[card_pair_to_index(board1, board2)] = values.map(|values| {
+                        let mut strength = SplitPotStrength::default();
+                        for (index, high, low) in values {
+                            let high = StrengthItem {
+                                strength: rank(&high_values, high),
+                                index,
+                            };
+                            strength.high.push(high);
+                            match low {
+                                Some(low) => strength.low.push(StrengthItem {
+                                    strength: rank(&low_values, low),
+                                    index,
+                                }),
+                                None => strength.high_no_low.push(high),
+                            }
+                        }
+                        strength.high.sort_unstable();
+                        strength.high_no_low.sort_unstable();
+                        strength.low.sort_unstable();
+                        strength
+                    });
+                }
+            }
+        }
+
+        ret
+    }
+
+    pub(crate) fn isomorphism(
+        &self,
+        private_cards: &[Vec<(Card, Card)>; 2],
+        mode: IsomorphismMode,
+    ) -> IsomorphismData {
+        // dead cards must also be symmetric with respect to the suits
+        let mut dead_rankset: [u16; 4] = [0; 4];
+        for card in 0..52 {
+            if self.is_dead(card) {
+                dead_rankset[card as usize & 3] |= 1 << (card >> 2);
+            }
+        }
+
         let mut suit_isomorphism = [0; 4];
         let mut next_index = 1;
         'outer: for suit2 in 1..4 {
             for suit1 in 0..suit2 {
                 if self.range[0].is_suit_isomorphic(suit1, suit2)
                     && self.range[1].is_suit_isomorphic(suit1, suit2)
+                    && dead_rankset[suit1 as usize] == dead_rankset[suit2 as usize]
                 {
                     suit_isomorphism[suit2 as usize] = suit_isomorphism[suit1 as usize];
                     continue 'outer;
@@ -260,7 +496,9 @@ impl CardConfig {
             next_index += 1;
         }
 
-        let flop_mask: u64 = (1 << self.flop[0]) | (1 << self.flop[1]) | (1 << self.flop[2]);
+        // dead cards are never dealt, so they are masked out in the same way as the flop cards
+        let flop_mask: u64 =
+            (1 << self.flop[0]) | (1 << self.flop[1]) | (1 << self.flop[2]) | self.dead_mask();
         let mut flop_rankset = [0; 4];
 
         for &card in &self.flop {
@@ -277,7 +515,7 @@ impl CardConfig {
         let mut isomorphism_swap_turn = Default::default();
 
         // turn isomorphism
-        if self.turn == NOT_DEALT {
+        if self.turn == NOT_DEALT && mode.is_turn_enabled() {
             for suit1 in 1..4 {
                 for suit2 in 0..suit1 {
                     if flop_rankset[suit1 as usize] == flop_rankset[suit2 as usize]
@@ -323,8 +561,9 @@ impl CardConfig {
 
                 for suit1 in 1..4 {
                     for suit2 in 0..suit1 {
-                        if (flop_rankset[suit1 as usize] == flop_rankset[suit2 as usize]
-                            || self.turn != NOT_DEALT)
+                        if mode.is_river_enabled()
+                            && (flop_rankset[suit1 as usize] == flop_rankset[suit2 as usize]
+                                || self.turn != NOT_DEALT)
                             && turn_rankset[suit1 as usize] == turn_rankset[suit2 as usize]
                             && suit_isomorphism[suit1 as usize] == suit_isomorphism[suit2 as usize]
                         {
@@ -448,4 +687,36 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn hand_strength_batch() {
+        // evaluates the hands one by one with the default `evaluate_batch`
+        struct ScalarEvaluator(LookupEvaluator);
+
+        impl HandEvaluator for ScalarEvaluator {
+            fn evaluate(&self, board: &[Card; 5], hand: (Card, Card)) -> u32 {
+                self.0.evaluate(board, hand)
+            }
+        }
+
+        let card_config = CardConfig {
+            range: ["22+,A2s+,K9s+,ATo+".parse().unwrap(), Range::ones()],
+            flop: flop_from_str("Td9d6d").unwrap(),
+            ..Default::default()
+        };
+        let flop_mask = card_config.flop.iter().map(|&card| 1 << card).sum();
+        let private_cards = card_config
+            .range
+            .map(|range| range.get_hands_weights(flop_mask).0);
+
+        let scalar = ScalarEvaluator(LookupEvaluator::new());
+        let expected = card_config.hand_strength(&private_cards, &scalar);
+        for evaluator in [
+            &LookupEvaluator::new() as &dyn HandEvaluator,
+            &HoldemEvaluator,
+        ] {
+            let strength = card_config.hand_strength(&private_cards, evaluator);
+            assert!(strength == expected);
+        }
+    }
 }
@@ -0,0 +1,184 @@
+use crate::action_tree::*;
+use crate::game::*;
+use crate::interface::*;
+use std::collections::VecDeque;
+
+/// Progress of the solving, passed to [`ConvergenceCriterion::is_converged`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolveProgress {
+    /// Number of completed iterations.
+    pub num_iterations: u32,
+
+    /// Latest exploitability. The exploitability is computed before the first iteration and after
+    /// every 10 iterations, so it may be a few iterations old.
+    pub exploitability: f32,
+
+    /// Whether `exploitability` was computed after the last iteration (or before the first
+    /// iteration).
+    pub is_exploitability_updated: bool,
+}
+
+/// Stop condition of [`solve_until`].
+///
+/// The criterion is checked before each iteration, and the solving stops as soon as it returns
+/// `true` (or the maximum number of iterations is reached). Closures of the form
+/// `FnMut(&T, &SolveProgress) -> bool` implement this trait, so custom conditions can be passed
+/// directly.
+///
+/// [`solve_until`]: crate::solve_until
+pub trait ConvergenceCriterion<T: Game>: Send {
+    /// Returns whether the solving is converged and should stop.
+    fn is_converged(&mut self, game: &T, progress: &SolveProgress) -> bool;
+}
+
+impl<T: Game, F: FnMut(&T, &SolveProgress) -> bool + Send> ConvergenceCriterion<T> for F {
+    #[inline]
+    fn is_converged(&mut self, game: &T, progress: &SolveProgress) -> bool {
+        self(game, progress)
+    }
+}
+
+/// Converged when the exploitability is at most the given value, which is the stop condition of
+/// [`solve`].
+///
+/// [`solve`]: crate::solve
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TargetExploitability(pub f32);
+
+impl<T: Game> ConvergenceCriterion<T> for TargetExploitability {
+    #[inline]
+    fn is_converged(&mut self, _game: &T, progress: &SolveProgress) -> bool {
+        progress.exploitability <= self.0
+    }
+}
+
+/// Converged when the exploitability stops improving: the relative improvement over the last
+/// `window` computations of the exploitability (i.e., `10 * window` iterations) is less than
+/// `min_relative_improvement`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImprovementStall {
+    window: usize,
+    min_relative_improvement: f32,
+    history: VecDeque<f32>,
+}
+
+impl ImprovementStall {
+    /// Creates a new criterion.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is zero.
+    #[inline]
+    pub fn new(window: usize, min_relative_improvement: f32) -> Self {
+        assert!(window > 0, "Window must be positive");
+        Self {
+            window,
+            min_relative_improvement,
+            history: VecDeque::with_capacity(window + 1),
+        }
+    }
+}
+
+impl<T: Game> ConvergenceCriterion<T> for ImprovementStall {
+    fn is_converged(&mut self, _game: &T, progress: &SolveProgress) -> bool {
+        if !progress.is_exploitability_updated {
+            return false;
+        }
+
+        self.history.push_back(progress.exploitability);
+        if self.history.len() <= self.window {
+            return false;
+        }
+
+        let oldest = self.history.pop_front().unwrap();
+        let improvement = oldest - progress.exploitability;
+        improvement <= self.min_relative_improvement * oldest.abs()
+    }
+}
+
+/// Converged when any of the criteria is converged.
+pub struct AnyOf<T: Game>(pub Vec<Box<dyn ConvergenceCriterion<T>>>);
+
+impl<T: Game> ConvergenceCriterion<T> for AnyOf<T> {
+    #[inline]
+    fn is_converged(&mut self, game: &T, progress: &SolveProgress) -> bool {
+        // evaluates all the criteria to keep their states up to date
+        self.0.iter_mut().fold(false, |acc, criterion| {
+            criterion.is_converged(game, progress) | acc
+        })
+    }
+}
+
+/// Converged when the strategy of each street with a threshold is stable: no action frequency of
+/// any node of the street, aggregated over the range of the acting player (see [`diff_games`]),
+/// changes by more than the threshold of the street between two consecutive checks.
+///
+/// The check takes a snapshot of the game (see [`PostFlopGame::snapshot`]) every `interval`
+/// iterations and keeps the previous one, so it costs as much memory as the game and about as
+/// much time as an iteration per check. Streets without a threshold are not checked, and the
+/// criterion is never converged if no threshold is set.
+pub struct StreetStability {
+    thresholds: [Option<f32>; 3],
+    interval: u32,
+    previous: Option<PostFlopGame>,
+}
+
+impl StreetStability {
+    /// Creates a new criterion that checks the stability every `interval` iterations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    #[inline]
+    pub fn new(interval: u32) -> Self {
+        assert!(interval > 0, "Interval must be positive");
+        Self {
+            thresholds: [None; 3],
+            interval,
+            previous: None,
+        }
+    }
+
+    /// Sets the frequency threshold of `street`.
+    #[inline]
+    pub fn with_threshold(mut self, street: BoardState, threshold: f32) -> Self {
+        self.thresholds[street as usize] = Some(threshold);
+        self
+    }
+}
+
+impl ConvergenceCriterion<PostFlopGame> for StreetStability {
+    fn is_converged(&mut self, game: &PostFlopGame, progress: &SolveProgress) -> bool {
+        if self.thresholds.iter().all(Option::is_none)
+            || progress.num_iterations == 0
+            || !progress.num_iterations.is_multiple_of(self.interval)
+        {
+            return false;
+        }
+
+        let Ok(mut current) = game.snapshot() else {
+            return false;
+        };
+
+        let is_stable = self.previous.as_mut().is_some_and(|previous| {
+            [BoardState::Flop, BoardState::Turn, BoardState::River]
+                .into_iter()
+                .zip(self.thresholds)
+                .all(|(street, threshold)| {
+                    let Some(threshold) = threshold else {
+                        return true;
+                    };
+                    let options = DiffOptions {
+                        street: Some(street),
+                        frequency_threshold: threshold,
+                        ev_threshold: f32::INFINITY,
+                        ..Default::default()
+                    };
+                    diff_games(previous, &mut current, &options).is_ok_and(|diff| diff.is_empty())
+                })
+        });
+
+        self.previous = Some(current);
+        is_stable
+    }
+}
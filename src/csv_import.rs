@@ -0,0 +1,357 @@
+use crate::action_tree::*;
+use crate::bet_size::*;
+use crate::error::SolverError;
+use crate::range::*;
+use crate::utility::*;
+
+/// Attempts to read a range from a CSV text, as maintained in a spreadsheet.
+///
+/// Two layouts are accepted, and tab-separated text (as copied from a spreadsheet) is accepted
+/// as well as comma-separated text:
+///
+/// - **Grid**: 13 rows of 13 weights in the usual hand matrix. The rows and the columns are ordered
+///   from A to 2; the diagonal cells are the pairs, the cells above the diagonal are the suited
+///   hands, and the cells below the diagonal are the offsuit hands. A header row and a label column
+///   (e.g., the rank characters) may be added.
+/// - **List**: one hand per row followed by its weight (default: `1`). A hand is either a hand
+///   class (e.g., `AA`, `AKs`, `AKo`, or `AK`) or a specific combination (e.g., `AsKh` or `KhAs`),
+///   so that lists of all 1326 combinations are accepted. Later rows overwrite the weights set by
+///   earlier rows, and the first row is skipped if it is a header (e.g., `hand,weight`).
+///
+/// A weight is a number between `0` and `1`, or a percentage with `%` (e.g., `50%`). Empty
+/// weights of a grid are treated as `0`.
+///
+/// Returns [`SolverError::InvalidRange`] with the offending cell if a hand or a weight is invalid,
+/// and [`SolverError::InvalidData`] if the text is not a valid CSV.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let range = range_from_csv("hand,weight\nAA,1\nAKs,50%\nAhKd,0.25\n").unwrap();
+/// assert_eq!(range.to_string(), "AA,AKs:0.5,AhKd:0.25");
+/// ```
+pub fn range_from_csv(text: &str) -> Result<Range, SolverError> {
+    let mut records = csv_records(text)?;
+    for record in &mut records {
+        while record.last().is_some_and(|field| field.trim().is_empty()) {
+            record.pop();
+        }
+    }
+
+    let is_grid = matches!(records.len(), 13 | 14)
+        && records
+            .iter()
+            .skip(records.len() - 13)
+            .all(|record| matches!(record.len(), 13 | 14));
+
+    if is_grid {
+        range_from_grid(&records[records.len() - 13..])
+    } else {
+        range_from_list(&records)
+    }
+}
+
+fn range_from_grid(rows: &[Vec<String>]) -> Result<Range, SolverError> {
+    let mut range = Range::new();
+    for (i, row) in rows.iter().enumerate() {
+        // skips the label column
+        let cells = &row[row.len() - 13..];
+        for (j, cell) in cells.iter().enumerate() {
+            let weight = parse_weight(cell).map_err(|message| SolverError::InvalidRange {
+                token: cell.trim().to_owned(),
+                message: format!("{message} (row {}, cell {})", i + 1, j + 1),
+            })?;
+            let (rank1, rank2) = (12 - i as u8, 12 - j as u8);
+            match i.cmp(&j) {
+                std::cmp::Ordering::Equal => range.set_weight_pair(rank1, weight),
+                std::cmp::Ordering::Less => range.set_weight_suited(rank1, rank2, weight),
+                std::cmp::Ordering::Greater => range.set_weight_offsuit(rank2, rank1, weight),
+            }
+        }
+    }
+    Ok(range)
+}
+
+fn range_from_list(records: &[Vec<String>]) -> Result<Range, SolverError> {
+    let mut range = Range::new();
+    for (i, record) in records.iter().enumerate() {
+        let hand = record[0].trim();
+        let cell = record.get(1).map_or("", |cell| cell.trim());
+        let weight = match cell {
+            "" => Ok(1.0),
+            _ => parse_weight(cell).map_err(|message| (cell, message)),
+        };
+
+        let result = weight
+            .and_then(|weight| set_hand_weight(&mut range, hand, weight).map_err(|e| (hand, e)));
+        match result {
+            Err(_) if i == 0 => {} // header
+            Err((token, message)) => {
+                return Err(SolverError::InvalidRange {
+                    token: token.to_owned(),
+                    message: format!("{message} (row {})", i + 1),
+                });
+            }
+            Ok(()) => {}
+        }
+    }
+    Ok(range)
+}
+
+fn set_hand_weight(range: &mut Range, hand: &str, weight: f32) -> Result<(), String> {
+    if let Ok(cards) = cards_from_str(hand)
+        && let [card1, card2] = cards[..]
+    {
+        if card1 == card2 {
+            return Err(format!("Duplicate cards are not allowed: {hand}"));
+        }
+        range.set_weight_by_cards(card1, card2, weight);
+        return Ok(());
+    }
+    range.update_with_singleton(hand, weight)
+}
+
+fn parse_weight(cell: &str) -> Result<f32, String> {
+    let cell = cell.trim();
+    if cell.is_empty() {
+        return Ok(0.0);
+    }
+
+    let weight = match cell.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f32>().map(|x| x / 100.0),
+        None => cell.parse::<f32>(),
+    }
+    .map_err(|_| format!("Invalid weight: {cell}"))?;
+
+    if !(0.0..=1.0).contains(&weight) {
+        return Err(format!("Weight must be between 0 and 1: {cell}"));
+    }
+    Ok(weight)
+}
+
+/// Attempts to read the bet sizes of `tree_config` from a CSV text, as maintained in a spreadsheet.
+///
+/// The first row is a header naming the columns (in any order; other columns such as notes are
+/// ignored):
+///
+/// - `street`: `flop`, `turn`, or `river`.
+/// - `player`: `oop`, `ip`, or `donk` (the donk sizes of the turn or the river).
+/// - `bet`: bet sizes in the format of [`BetSizeOptions`] (e.g., `33%, 75%`). The sizes may also
+///   be separated by semicolons, so that the field does not have to be quoted.
+/// - `raise` (optional): raise sizes in the same format (ignored for `donk`).
+///
+/// The bet sizes of the listed streets and players are replaced, and the others are kept. The
+/// text is validated entirely before `tree_config` is modified.
+///
+/// Returns [`SolverError::InvalidTreeConfig`] if a row is invalid, and
+/// [`SolverError::InvalidData`] if the text is not a valid CSV or a column is missing.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let csv = "\
+/// street,player,bet,raise
+/// flop,oop,33%;75%,2.5x
+/// flop,ip,33%;75%,2.5x
+/// turn,donk,50%,
+/// ";
+///
+/// let mut tree_config = TreeConfig::default();
+/// bet_sizes_from_csv(csv, &mut tree_config).unwrap();
+/// assert_eq!(tree_config.flop_bet_sizes[1].bet.len(), 2);
+/// assert!(tree_config.turn_donk_sizes.is_some());
+/// ```
+pub fn bet_sizes_from_csv(text: &str, tree_config: &mut TreeConfig) -> Result<(), SolverError> {
+    let records = csv_records(text)?;
+    let (header, rows) = records
+        .split_first()
+        .ok_or_else(|| SolverError::InvalidData("Header row is missing".to_owned()))?;
+
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|field| field.trim().eq_ignore_ascii_case(name))
+    };
+    let required_column = |name: &str| {
+        column(name).ok_or_else(|| SolverError::InvalidData(format!("Column is missing: {name}")))
+    };
+    let street_column = required_column("street")?;
+    let player_column = required_column("player")?;
+    let bet_column = required_column("bet")?;
+    let raise_column = column("raise");
+
+    let mut config = tree_config.clone();
+    for (i, row) in rows.iter().enumerate() {
+        let cell = |index: usize| {
+            row.get(index)
+                .map_or(String::new(), |field| field.trim().replace(';', ","))
+        };
+        let err = |e: String| SolverError::InvalidTreeConfig(format!("{e} (row {})", i + 2));
+        let size_err = |e: SolverError| err(e.to_string());
+
+        let street = cell(street_column).to_ascii_lowercase();
+        let player = cell(player_column).to_ascii_lowercase();
+        let bet = cell(bet_column);
+        let raise = raise_column.map(cell).unwrap_or_default();
+
+        let (bet_sizes, donk_sizes) = match street.as_str() {
+            "flop" => (&mut config.flop_bet_sizes, None),
+            "turn" => (
+                &mut config.turn_bet_sizes,
+                Some(&mut config.turn_donk_sizes),
+            ),
+            "river" => (
+                &mut config.river_bet_sizes,
+                Some(&mut config.river_donk_sizes),
+            ),
+            _ => return Err(err(format!("Invalid street: {street}"))),
+        };
+
+        match (player.as_str(), donk_sizes) {
+            ("oop", _) => {
+                bet_sizes[0] =
+                    BetSizeOptions::try_from((bet.as_str(), raise.as_str())).map_err(size_err)?;
+            }
+            ("ip", _) => {
+                bet_sizes[1] =
+                    BetSizeOptions::try_from((bet.as_str(), raise.as_str())).map_err(size_err)?;
+            }
+            ("donk", Some(donk_sizes)) => {
+                *donk_sizes = Some(DonkSizeOptions::try_from(bet.as_str()).map_err(size_err)?);
+            }
+            ("donk", None) => return Err(err("Donk sizes are not allowed on the flop".to_owned())),
+            _ => return Err(err(format!("Invalid player: {player}"))),
+        }
+    }
+
+    *tree_config = config;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write;
+
+    #[test]
+    fn range_grid() {
+        let ranks = "AKQJT98765432".chars().collect::<Vec<_>>();
+        let mut csv = String::new();
+        csv.push_str(",A,K,Q,J,T,9,8,7,6,5,4,3,2\n");
+        for (i, &rank) in ranks.iter().enumerate() {
+            csv.push(rank);
+            for j in 0..13 {
+                // pairs: 1, suited: 50%, offsuit: empty (0) except AKo
+                let cell = match (i, j) {
+                    _ if i == j => "1",
+                    _ if i < j => "50%",
+                    (1, 0) => "0.25",
+                    _ => "",
+                };
+                csv.push(',');
+                csv.push_str(cell);
+            }
+            csv.push('\n');
+        }
+
+        let range = range_from_csv(&csv).unwrap();
+        assert_eq!(range.get_weight_pair(12), 1.0);
+        assert_eq!(range.get_weight_pair(0), 1.0);
+        assert_eq!(range.get_weight_suited(12, 11), 0.5);
+        assert_eq!(range.get_weight_suited(1, 0), 0.5);
+        assert_eq!(range.get_weight_offsuit(12, 11), 0.25);
+        assert_eq!(range.get_weight_offsuit(12, 10), 0.0);
+
+        // tab-separated grid without labels
+        let tsv = csv
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').skip(1).collect::<Vec<_>>().join("\t"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(range_from_csv(&tsv).unwrap(), range);
+
+        let invalid = csv.replacen("50%", "150%", 1);
+        range_from_csv(&invalid).unwrap_err();
+    }
+
+    #[test]
+    fn range_list() {
+        // range notations are not hands
+        let csv = "\u{feff}hand,weight\r\nAK,1\r\nAKo,0.5\r\nKhAs,0\r\n\"QQ+\",1\r\n";
+        range_from_csv(csv).unwrap_err();
+
+        let csv = "\u{feff}hand,weight\r\nAK,1\r\nAKo,0.5\r\nKhAs,0\r\n\r\nQQ\r\n";
+        let range = range_from_csv(csv).unwrap();
+        assert_eq!(range.get_weight_suited(12, 11), 1.0);
+        assert_eq!(range.get_weight_pair(10), 1.0);
+        let ace_spades = card_from_str("As").unwrap();
+        let king_hearts = card_from_str("Kh").unwrap();
+        let king_diamonds = card_from_str("Kd").unwrap();
+        assert_eq!(range.get_weight_by_cards(ace_spades, king_hearts), 0.0);
+        assert_eq!(range.get_weight_by_cards(ace_spades, king_diamonds), 0.5);
+
+        // all 1326 combinations
+        let mut full = String::new();
+        for card1 in 0..52 {
+            for card2 in card1 + 1..52 {
+                let hand = hole_to_string((card1, card2)).unwrap();
+                let weight = u8::from(card2 < 4);
+                writeln!(full, "{hand},{weight}").unwrap();
+            }
+        }
+        assert_eq!(range_from_csv(&full).unwrap(), "22".parse().unwrap());
+
+        range_from_csv("hand,weight\nAKs,2\n").unwrap_err();
+        range_from_csv("hand,weight\nAsAs,1\n").unwrap_err();
+    }
+
+    #[test]
+    fn bet_sizes() {
+        let csv = "\
+Street,Player,Bet,Raise,Note
+flop,oop,\"33%, 75%\",2.5x,small and big
+flop,ip,33%;75%,2.5x,
+turn,oop,66%,3x,
+river,donk,50%,,
+";
+        let mut tree_config = TreeConfig::default();
+        bet_sizes_from_csv(csv, &mut tree_config).unwrap();
+        let expected = BetSizeOptions::try_from(("33%, 75%", "2.5x")).unwrap();
+        assert_eq!(tree_config.flop_bet_sizes, [expected.clone(), expected]);
+        assert_eq!(
+            tree_config.turn_bet_sizes[0],
+            BetSizeOptions::try_from(("66%", "3x")).unwrap()
+        );
+        assert_eq!(tree_config.turn_bet_sizes[1], BetSizeOptions::default());
+        assert_eq!(
+            tree_config.river_donk_sizes,
+            Some(DonkSizeOptions::try_from("50%").unwrap())
+        );
+
+        // invalid plans leave the configuration unchanged
+        let sizes = |config: &TreeConfig| {
+            (
+                config.flop_bet_sizes.clone(),
+                config.turn_bet_sizes.clone(),
+                config.river_bet_sizes.clone(),
+                config.turn_donk_sizes.clone(),
+                config.river_donk_sizes.clone(),
+            )
+        };
+        let original = sizes(&tree_config);
+        for invalid in [
+            "",
+            "street,bet\nflop,50%\n",
+            "street,player,bet\npreflop,oop,50%\n",
+            "street,player,bet\nflop,btn,50%\n",
+            "street,player,bet\nflop,donk,50%\n",
+            "street,player,bet\nturn,oop,50%\nriver,oop,50\n",
+        ] {
+            bet_sizes_from_csv(invalid, &mut tree_config).unwrap_err();
+            assert_eq!(sizes(&tree_config), original);
+        }
+    }
+}
@@ -0,0 +1,320 @@
+// [Encrypted file format]
+//  - Magic number (4 bytes): 0e 57 f1 09
+//  - Nonce (7 bytes)
+//  - Ciphertext of a plain save file (see `file.rs`), split into chunks of `CHUNK_SIZE` bytes
+//    (the last chunk may be shorter), each followed by a 16-byte authentication tag
+//
+// The chunks are encrypted with ChaCha20-Poly1305 in the STREAM construction, which detects
+// reordered, dropped, and truncated chunks as well as modified bytes.
+
+use crate::error::*;
+use crate::file::*;
+use chacha20poly1305::ChaCha20Poly1305;
+use chacha20poly1305::aead::OsRng;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::stream::{DecryptorBE32, EncryptorBE32};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const ENCRYPTED_MAGIC: [u8; 4] = [0x0e, 0x57, 0xf1, 0x09];
+const NONCE_SIZE: usize = 7;
+const CHUNK_SIZE: usize = 1 << 16;
+const TAG_SIZE: usize = 16;
+
+/// A writer that encrypts the written data chunk by chunk.
+struct EncryptWriter<W: Write> {
+    writer: W,
+    encryptor: Option<EncryptorBE32<ChaCha20Poly1305>>,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> EncryptWriter<W> {
+    fn new(mut writer: W, key: &[u8; 32]) -> io::Result<Self> {
+        let mut nonce = [0; NONCE_SIZE];
+        OsRng.fill_bytes(&mut nonce);
+        writer.write_all(&ENCRYPTED_MAGIC)?;
+        writer.write_all(&nonce)?;
+        Ok(Self {
+            writer,
+            encryptor: Some(EncryptorBE32::new(key.into(), (&nonce).into())),
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+        })
+    }
+
+    /// Encrypts the remaining data as the last chunk.
+    fn finish(mut self) -> io::Result<()> {
+        let encryptor = self.encryptor.take().unwrap();
+        let chunk = encryptor
+            .encrypt_last(self.buffer.as_slice())
+            .map_err(|_| io::Error::other("Failed to encrypt data"))?;
+        self.writer.write_all(&chunk)?;
+        self.writer.flush()
+    }
+}
+
+impl<W: Write> Write for EncryptWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // a full chunk is encrypted only when more data follows, so that the last chunk is never
+        // empty unless the whole data is empty
+        if self.buffer.len() == CHUNK_SIZE && !buf.is_empty() {
+            let chunk = self
+                .encryptor
+                .as_mut()
+                .unwrap()
+                .encrypt_next(self.buffer.as_slice())
+                .map_err(|_| io::Error::other("Failed to encrypt data"))?;
+            self.writer.write_all(&chunk)?;
+            self.buffer.clear();
+        }
+
+        let len = buf.len().min(CHUNK_SIZE - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // the buffered data is written by `finish`
+        Ok(())
+    }
+}
+
+/// A reader that decrypts the data chunk by chunk.
+struct DecryptReader<R: Read> {
+    reader: R,
+    decryptor: Option<DecryptorBE32<ChaCha20Poly1305>>,
+    next_chunk: Vec<u8>,
+    plaintext: Vec<u8>,
+    position: usize,
+}
+
+impl<R: Read> DecryptReader<R> {
+    fn new(mut reader: R, key: &[u8; 32]) -> Result<Self, SolverError> {
+        let mut magic = [0; 4];
+        let mut nonce = [0; NONCE_SIZE];
+        reader
+            .read_exact(&mut magic)
+            .map_err(|e| SolverError::io("Failed to read magic number", &e))?;
+        if magic != ENCRYPTED_MAGIC {
+            return Err(SolverError::InvalidData("Data is not encrypted".to_owned()));
+        }
+        reader
+            .read_exact(&mut nonce)
+            .map_err(|e| SolverError::io("Failed to read nonce", &e))?;
+
+        let mut ret = Self {
+            reader,
+            decryptor: Some(DecryptorBE32::new(key.into(), (&nonce).into())),
+            next_chunk: Vec::new(),
+            plaintext: Vec::new(),
+            position: 0,
+        };
+
+        ret.next_chunk = ret
+            .read_chunk()
+            .map_err(|e| SolverError::io("Failed to read data", &e))?;
+        Ok(ret)
+    }
+
+    /// Reads the next encrypted chunk (shorter than a full chunk only at the end of the stream).
+    fn read_chunk(&mut self) -> io::Result<Vec<u8>> {
+        let mut chunk = Vec::with_capacity(CHUNK_SIZE + TAG_SIZE);
+        (&mut self.reader)
+            .take((CHUNK_SIZE + TAG_SIZE) as u64)
+            .read_to_end(&mut chunk)?;
+        Ok(chunk)
+    }
+
+    /// Decrypts the next chunk into `plaintext`. Returns `false` at the end of the stream.
+    fn decrypt_chunk(&mut self) -> io::Result<bool> {
+        if self.decryptor.is_none() {
+            return Ok(false);
+        }
+
+        let chunk = self.read_chunk()?;
+        let current = std::mem::replace(&mut self.next_chunk, chunk);
+        let decrypt_err = |_| io::Error::other("Failed to decrypt data (wrong key or corrupted)");
+
+        self.plaintext = if self.next_chunk.is_empty() {
+            let decryptor = self.decryptor.take().unwrap();
+            decryptor
+                .decrypt_last(current.as_slice())
+                .map_err(decrypt_err)?
+        } else {
+            let decryptor = self.decryptor.as_mut().unwrap();
+            decryptor
+                .decrypt_next(current.as_slice())
+                .map_err(decrypt_err)?
+        };
+
+        self.position = 0;
+        Ok(true)
+    }
+}
+
+impl<R: Read> Read for DecryptReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.plaintext.len() {
+            if !self.decrypt_chunk()? {
+                return Ok(0);
+            }
+        }
+
+        let len = buf.len().min(self.plaintext.len() - self.position);
+        buf[..len].copy_from_slice(&self.plaintext[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// Saves data into a standard writer with authenticated encryption.
+///
+/// The data is serialized as in [`save_data_into_std_write`] and encrypted with ChaCha20-Poly1305
+/// under the 256-bit `key` supplied by the caller, using a random nonce. The encrypted data can be
+/// loaded only with the same key, and any modification of it is detected on load. The key is not
+/// stored anywhere; keeping it secret is up to the caller.
+///
+/// The data is encrypted in chunks while being written, so no copy of the whole data is made.
+pub fn save_data_into_std_write_encrypted<T: FileData, W: Write>(
+    data: &T,
+    memo: &str,
+    writer: &mut W,
+    compression_level: Option<i32>,
+    key: &[u8; 32],
+) -> Result<(), SolverError> {
+    let write_err = |e: io::Error| SolverError::io("Failed to write data", &e);
+    let mut writer = EncryptWriter::new(writer, key).map_err(write_err)?;
+    save_data_into_std_write(data, memo, &mut writer, compression_level)?;
+    writer.finish().map_err(write_err)
+}
+
+/// Saves data into a file with authenticated encryption (see
+/// [`save_data_into_std_write_encrypted`]).
+///
+/// If the file already exists, it will be overwritten.
+pub fn save_data_to_file_encrypted<T: FileData, P: AsRef<Path>>(
+    data: &T,
+    memo: &str,
+    path: P,
+    compression_level: Option<i32>,
+    key: &[u8; 32],
+) -> Result<(), SolverError> {
+    let file = File::create(path).map_err(|e| SolverError::io("Failed to create file", &e))?;
+    let mut writer = BufWriter::new(file);
+    save_data_into_std_write_encrypted(data, memo, &mut writer, compression_level, key)
+}
+
+/// Loads data saved by [`save_data_into_std_write_encrypted`] from a standard reader.
+///
+/// Returns an error if the `key` is wrong or the data is corrupted or tampered with. The whole
+/// data is authenticated before returning, including the bytes after the serialized data.
+///
+/// # Returns
+///
+/// A tuple of the deserialized data (either a [`PostFlopGame`] or a [`BunchingData`]) and the memo
+/// string.
+///
+/// [`PostFlopGame`]: crate::PostFlopGame
+/// [`BunchingData`]: crate::BunchingData
+pub fn load_data_from_std_read_encrypted<T: FileData, R: Read>(
+    reader: &mut R,
+    max_memory_usage: Option<u64>,
+    key: &[u8; 32],
+) -> Result<(T, String), SolverError> {
+    let mut reader = DecryptReader::new(reader, key)?;
+    let result = load_data_from_std_read(&mut reader, max_memory_usage)?;
+
+    // authenticate the rest of the stream, which also detects truncation
+    io::copy(&mut reader, &mut io::sink())
+        .map_err(|e| SolverError::io("Failed to read data", &e))?;
+
+    Ok(result)
+}
+
+/// Loads data saved by [`save_data_to_file_encrypted`] from a file (see
+/// [`load_data_from_std_read_encrypted`]).
+pub fn load_data_from_file_encrypted<T: FileData, P: AsRef<Path>>(
+    path: P,
+    max_memory_usage: Option<u64>,
+    key: &[u8; 32],
+) -> Result<(T, String), SolverError> {
+    let file = File::open(path).map_err(|e| SolverError::io("Failed to open file", &e))?;
+    let mut reader = BufReader::new(file);
+    load_data_from_std_read_encrypted(&mut reader, max_memory_usage, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action_tree::*;
+    use crate::card::*;
+    use crate::game::*;
+    use crate::range::*;
+    use crate::utility::*;
+
+    #[test]
+    fn save_and_load_encrypted() {
+        let card_config = CardConfig {
+            range: [
+                "66+,A8s+,AJo+".parse().unwrap(),
+                "22+,A2s+,AJo+".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Flop,
+            starting_pot: 100,
+            effective_stack: 100,
+            flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        finalize(&mut game);
+        game.cache_normalized_weights();
+        let ev = game.expected_values(0);
+
+        let key = [42; 32];
+        let mut data = Vec::new();
+        save_data_into_std_write_encrypted(&game, "memo", &mut data, None, &key).unwrap();
+        assert!(data.len() > 2 * CHUNK_SIZE);
+
+        let (mut loaded, memo): (PostFlopGame, _) =
+            load_data_from_std_read_encrypted(&mut data.as_slice(), None, &key).unwrap();
+        assert_eq!(memo, "memo");
+        loaded.cache_normalized_weights();
+        assert_eq!(loaded.expected_values(0), ev);
+
+        // wrong key
+        let wrong_key = [43; 32];
+        let result: Result<(PostFlopGame, _), _> =
+            load_data_from_std_read_encrypted(&mut data.as_slice(), None, &wrong_key);
+        assert!(result.is_err());
+
+        // tampered data
+        let mut tampered = data.clone();
+        tampered[100] ^= 1;
+        let result: Result<(PostFlopGame, _), _> =
+            load_data_from_std_read_encrypted(&mut tampered.as_slice(), None, &key);
+        assert!(result.is_err());
+
+        // truncated data (whole chunks dropped)
+        let truncated = &data[..ENCRYPTED_MAGIC.len() + NONCE_SIZE + 2 * (CHUNK_SIZE + TAG_SIZE)];
+        let result: Result<(PostFlopGame, _), _> =
+            load_data_from_std_read_encrypted(&mut &*truncated, None, &key);
+        assert!(result.is_err());
+
+        // plain files are not accepted
+        let mut plain = Vec::new();
+        save_data_into_std_write(&game, "", &mut plain, None).unwrap();
+        let result: Result<(PostFlopGame, _), _> =
+            load_data_from_std_read_encrypted(&mut plain.as_slice(), None, &key);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::action_tree::Action;
+use crate::range::CardParseError;
+use std::io;
+use thiserror::Error;
+
+/// The error type of game construction, tree building, range parsing, save/load, and analysis.
+///
+/// The variants carry structured context so that callers can branch on the kind of the error, and
+/// the `Display` representation is a human-readable message. Every fallible public API of the
+/// crate reports errors of this type, except for the following:
+///
+/// - [`card_from_chars`], [`card_from_str`], and [`flop_from_str`] return [`CardParseError`],
+///   which converts into [`SolverError::CardParse`].
+/// - The language bindings report errors in the form of their languages (exceptions in Python
+///   and JavaScript, and status codes in the C API), with the message of the underlying
+///   `SolverError`.
+///
+/// [`card_from_chars`]: crate::card_from_chars
+/// [`card_from_str`]: crate::card_from_str
+/// [`flop_from_str`]: crate::flop_from_str
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let error = "AK,QQ-JJ,T9x".parse::<Range>().unwrap_err();
+/// match error {
+///     SolverError::InvalidRange { token, .. } => assert_eq!(token, "T9x"),
+///     _ => unreachable!(),
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Error)]
+#[non_exhaustive]
+pub enum SolverError {
+    /// The range string is invalid.
+    #[error("{message}")]
+    InvalidRange {
+        /// The offending element of the range string.
+        token: String,
+
+        /// Description of the error.
+        message: String,
+    },
+
+    /// The cards cannot be parsed.
+    #[error(transparent)]
+    CardParse(#[from] CardParseError),
+
+    /// The card configuration is invalid.
+    #[error("{0}")]
+    InvalidCardConfig(String),
+
+    /// The tree configuration is invalid.
+    #[error("{0}")]
+    InvalidTreeConfig(String),
+
+    /// The line of actions is invalid.
+    #[error("{message}")]
+    InvalidLine {
+        /// The path of actions from the root node to the offending action.
+        line: Vec<Action>,
+
+        /// Description of the error.
+        message: String,
+    },
+
+    /// The operation is not allowed in the current state (e.g., the game is not initialized).
+    #[error("{0}")]
+    InvalidState(String),
+
+    /// An argument is invalid (e.g., the compression parameters or the update schedule).
+    #[error("{0}")]
+    InvalidArgument(String),
+
+    /// The required memory exceeds the limit.
+    #[error(
+        "Memory usage exceeds the limit by {} bytes ({required} bytes required for {context}, \
+         limit is {available} bytes)",
+        .required - .available
+    )]
+    MemoryLimitExceeded {
+        /// Required memory in bytes.
+        required: u64,
+
+        /// Available memory (i.e., the limit) in bytes.
+        available: u64,
+
+        /// What the memory is required for.
+        context: String,
+    },
+
+    /// The memory cannot be allocated.
+    #[error("{message} ({required} bytes required for {context})")]
+    AllocationFailed {
+        /// Required memory in bytes.
+        required: u64,
+
+        /// What the memory is required for.
+        context: String,
+
+        /// Description of the error.
+        message: String,
+    },
+
+    /// An I/O error occurred.
+    #[error("{message}")]
+    Io {
+        /// Kind of the underlying I/O error.
+        kind: io::ErrorKind,
+
+        /// Description of the error.
+        message: String,
+    },
+
+    /// The data is invalid or corrupted.
+    #[error("{0}")]
+    InvalidData(String),
+
+    /// Any other error.
+    #[error("{0}")]
+    Other(String),
+}
+
+impl SolverError {
+    /// Creates an [`Io`] error from `error` with the description of the failed operation.
+    ///
+    /// [`Io`]: SolverError::Io
+    #[inline]
+    pub(crate) fn io(context: &str, error: &io::Error) -> Self {
+        Self::Io {
+            kind: error.kind(),
+            message: format!("{context}: {error}"),
+        }
+    }
+}
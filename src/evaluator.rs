@@ -0,0 +1,453 @@
+use crate::card::*;
+use crate::hand::*;
+use std::fmt;
+use std::sync::LazyLock;
+
+/// A trait for evaluating hands at showdown.
+///
+/// The solver compares the values returned by [`evaluate`] to decide the winner of each showdown:
+/// the larger value wins, and the pot is split if the values are equal. Only the order of the
+/// values matters. The evaluator of a game is set by [`PostFlopGame::set_hand_evaluator`]; by
+/// default, [`HoldemEvaluator`] (or [`ShortDeckEvaluator`] in short-deck mode) is used.
+///
+/// Since a showdown has exactly one winner (or a split), variants in which the pot is divided by
+/// multiple rankings (e.g., hi-lo) cannot be expressed by this trait; see [`SplitPotEvaluator`]
+/// instead.
+///
+/// [`SplitPotEvaluator`]: crate::SplitPotEvaluator
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// // a house rule in which the lowest hand wins
+/// struct LowballEvaluator;
+///
+/// impl HandEvaluator for LowballEvaluator {
+///     fn evaluate(&self, board: &[Card; 5], hand: (Card, Card)) -> u32 {
+///         u32::MAX - HoldemEvaluator.evaluate(board, hand)
+///     }
+/// }
+///
+/// let board = board_from_str("Td9d6h2c3s").unwrap().try_into().unwrap();
+/// let aces = (card_from_str("Ac").unwrap(), card_from_str("Ad").unwrap());
+/// let kings = (card_from_str("Kc").unwrap(), card_from_str("Kd").unwrap());
+/// assert!(LowballEvaluator.evaluate(&board, aces) < LowballEvaluator.evaluate(&board, kings));
+/// ```
+///
+/// [`evaluate`]: HandEvaluator::evaluate
+/// [`PostFlopGame::set_hand_evaluator`]: crate::PostFlopGame::set_hand_evaluator
+pub trait HandEvaluator: Send + Sync {
+    /// Returns the strength of `hand` on the complete `board` (in no particular order). A larger
+    /// value represents a stronger hand.
+    fn evaluate(&self, board: &[Card; 5], hand: (Card, Card)) -> u32;
+
+    /// Evaluates each of `hands` on the complete `board`, writing the values to `out`.
+    ///
+    /// `out` must have the same length as `hands`, and the values must be the same as those of
+    /// [`evaluate`]. The default implementation calls [`evaluate`] for each hand; an evaluator can
+    /// override it to share the work depending only on the board between the hands.
+    ///
+    /// [`evaluate`]: HandEvaluator::evaluate
+    fn evaluate_batch(&self, board: &[Card; 5], hands: &[(Card, Card)], out: &mut [u32]) {
+        debug_assert_eq!(hands.len(), out.len());
+        for (out, &hand) in out.iter_mut().zip(hands) {
+            *out = self.evaluate(board, hand);
+        }
+    }
+}
+
+/// The evaluator of the regular hold'em rankings (default).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HoldemEvaluator;
+
+/// The evaluator of the short-deck (6+) hold'em rankings, where a flush beats a full house and
+/// A-6-7-8-9 is the lowest straight.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShortDeckEvaluator;
+
+#[inline]
+fn board_hand(board: [Card; 5]) -> Hand {
+    board
+        .into_iter()
+        .fold(Hand::new(), |hand, card| hand.add_card(card as usize))
+}
+
+#[inline]
+fn seven_card_hand(board: [Card; 5], hand: (Card, Card)) -> Hand {
+    add_hole_cards(&board_hand(board), hand)
+}
+
+#[inline]
+fn add_hole_cards(board: &Hand, hand: (Card, Card)) -> Hand {
+    board.add_card(hand.0 as usize).add_card(hand.1 as usize)
+}
+
+impl HandEvaluator for HoldemEvaluator {
+    #[inline]
+    fn evaluate(&self, board: &[Card; 5], hand: (Card, Card)) -> u32 {
+        seven_card_hand(*board, hand).evaluate() as u32
+    }
+
+    fn evaluate_batch(&self, board: &[Card; 5], hands: &[(Card, Card)], out: &mut [u32]) {
+        let board = board_hand(*board);
+        for (out, &hand) in out.iter_mut().zip(hands) {
+            *out = add_hole_cards(&board, hand).evaluate() as u32;
+        }
+    }
+}
+
+impl HandEvaluator for ShortDeckEvaluator {
+    #[inline]
+    fn evaluate(&self, board: &[Card; 5], hand: (Card, Card)) -> u32 {
+        seven_card_hand(*board, hand).evaluate_short_deck() as u32
+    }
+
+    fn evaluate_batch(&self, board: &[Card; 5], hands: &[(Card, Card)], out: &mut [u32]) {
+        let board = board_hand(*board);
+        for (out, &hand) in out.iter_mut().zip(hands) {
+            *out = add_hole_cards(&board, hand).evaluate_short_deck() as u32;
+        }
+    }
+}
+
+/// The evaluator based on precomputed lookup tables.
+///
+/// Non-flush hands are looked up by a perfect hash of the rank multiset (49,205 entries), and
+/// flushes by the rank set of the flush suit (8,192 entries), so each 7-card hand is evaluated in
+/// constant time without searching. The tables (about 115 KB for each variant) are built on the
+/// first use and shared by all instances. The returned values are the same as those of
+/// [`HoldemEvaluator`] (or [`ShortDeckEvaluator`] for [`new_short_deck`]).
+///
+/// Since the showdown tables of a game are computed with the evaluator, setting this evaluator by
+/// [`PostFlopGame::set_hand_evaluator`] speeds up the initialization of games with many runouts.
+/// [`evaluate_batch`] builds a flat table of the non-flush values indexed by the ranks of the hole
+/// cards for the board, so that each hand is evaluated by two table lookups.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let board = board_from_str("Td9d6h2c3s").unwrap().try_into().unwrap();
+/// let hand = (card_from_str("Ac").unwrap(), card_from_str("Ad").unwrap());
+/// let evaluator = LookupEvaluator::new();
+/// assert_eq!(
+///     evaluator.evaluate(&board, hand),
+///     HoldemEvaluator.evaluate(&board, hand)
+/// );
+/// ```
+///
+/// [`new_short_deck`]: LookupEvaluator::new_short_deck
+/// [`evaluate_batch`]: HandEvaluator::evaluate_batch
+/// [`PostFlopGame::set_hand_evaluator`]: crate::PostFlopGame::set_hand_evaluator
+#[derive(Clone, Copy)]
+pub struct LookupEvaluator {
+    tables: &'static LookupTables,
+}
+
+struct LookupTables {
+    short_deck: bool,
+    ranks: Vec<u16>,
+    flush: Vec<u16>,
+}
+
+/// Number of the rank multisets of 7 cards, where each rank appears at most 4 times.
+const NUM_RANK_MULTISETS: usize = 49205;
+
+/// `RANK_OFFSET[rank][k][count]`: offset of the perfect hash when `count` cards of `rank` are
+/// taken with `k` cards remaining (the multisets are numbered in the lexicographic order).
+const RANK_OFFSET: [[[u16; 5]; 8]; 13] = {
+    // `num_multisets[n][k]`: number of the multisets of `k` cards over `n` ranks
+    let mut num_multisets = [[0u32; 8]; 14];
+    num_multisets[0][0] = 1;
+    let mut n = 1;
+    while n <= 13 {
+        let mut k = 0;
+        while k < 8 {
+            let mut count = 0;
+            while count <= 4 && count <= k {
+                num_multisets[n][k] += num_multisets[n - 1][k - count];
+                count += 1;
+            }
+            k += 1;
+        }
+        n += 1;
+    }
+
+    let mut ret = [[[0; 5]; 8]; 13];
+    let mut rank = 0;
+    while rank < 13 {
+        let mut k = 0;
+        while k < 8 {
+            let mut count = 1;
+            while count <= 4 && count <= k {
+                let prev = num_multisets[12 - rank][k - (count - 1)];
+                ret[rank][k][count] = ret[rank][k][count - 1] + prev as u16;
+                count += 1;
+            }
+            k += 1;
+        }
+        rank += 1;
+    }
+
+    ret
+};
+
+static HOLDEM_TABLES: LazyLock<LookupTables> = LazyLock::new(|| LookupTables::new(false));
+static SHORT_DECK_TABLES: LazyLock<LookupTables> = LazyLock::new(|| LookupTables::new(true));
+
+#[inline]
+fn rank_multiset_index(rank_count: &[u8; 13]) -> usize {
+    let mut remaining = 7;
+    let mut index = 0;
+    for (rank, &count) in rank_count.iter().enumerate() {
+        index += RANK_OFFSET[rank][remaining][count as usize] as usize;
+        remaining -= count as usize;
+    }
+    index
+}
+
+/// Calls `f` for each multiset of `remaining` cards over the ranks in [`rank`, `13`).
+fn for_each_rank_multiset(
+    rank: usize,
+    remaining: u8,
+    rank_count: &mut [u8; 13],
+    f: &mut impl FnMut(&[u8; 13]),
+) {
+    if rank == 13 {
+        if remaining == 0 {
+            f(rank_count);
+        }
+        return;
+    }
+
+    for count in 0..=remaining.min(4) {
+        rank_count[rank] = count;
+        for_each_rank_multiset(rank + 1, remaining - count, rank_count, f);
+    }
+    rank_count[rank] = 0;
+}
+
+impl LookupTables {
+    fn new(short_deck: bool) -> Self {
+        let min_rank = if short_deck { 4 } else { 0 };
+        let evaluate = |hand: &Hand| {
+            if short_deck {
+                hand.evaluate_short_deck()
+            } else {
+                hand.evaluate()
+            }
+        };
+
+        // the suits are assigned cyclically, so that the representative hand has no flush
+        let mut ranks = vec![0; NUM_RANK_MULTISETS];
+        for_each_rank_multiset(min_rank, 7, &mut [0; 13], &mut |rank_count| {
+            let mut hand = Hand::new();
+            let mut num_cards = 0;
+            for (rank, &count) in rank_count.iter().enumerate() {
+                for _ in 0..count {
+                    hand = hand.add_card(4 * rank + num_cards % 4);
+                    num_cards += 1;
+                }
+            }
+            ranks[rank_multiset_index(rank_count)] = evaluate(&hand);
+        });
+
+        // a flush and a full house cannot coexist within 7 cards
+        let mut flush = vec![0; 1 << 13];
+        for (rankset, entry) in flush.iter_mut().enumerate() {
+            if (5..=7).contains(&rankset.count_ones()) && rankset & ((1 << min_rank) - 1) == 0 {
+                let hand = (0..13)
+                    .filter(|rank| rankset & (1 << rank) != 0)
+                    .fold(Hand::new(), |hand, rank| hand.add_card(4 * rank));
+                *entry = evaluate(&hand);
+            }
+        }
+
+        Self {
+            short_deck,
+            ranks,
+            flush,
+        }
+    }
+}
+
+impl LookupEvaluator {
+    /// Creates an evaluator of the regular hold'em rankings, building the tables if necessary.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            tables: &HOLDEM_TABLES,
+        }
+    }
+
+    /// Creates an evaluator of the short-deck (6+) hold'em rankings, building the tables if
+    /// necessary.
+    #[inline]
+    pub fn new_short_deck() -> Self {
+        Self {
+            tables: &SHORT_DECK_TABLES,
+        }
+    }
+}
+
+impl Default for LookupEvaluator {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for LookupEvaluator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LookupEvaluator")
+            .field("short_deck", &self.tables.short_deck)
+            .finish()
+    }
+}
+
+impl HandEvaluator for LookupEvaluator {
+    #[inline]
+    fn evaluate(&self, board: &[Card; 5], hand: (Card, Card)) -> u32 {
+        let mut rank_count = [0u8; 13];
+        let mut rankset_suit = [0u16; 4];
+        for &card in board.iter().chain([&hand.0, &hand.1]) {
+            rank_count[card as usize >> 2] += 1;
+            rankset_suit[card as usize & 3] |= 1 << (card >> 2);
+        }
+
+        let tables = self.tables;
+        match rankset_suit
+            .iter()
+            .find(|rankset| rankset.count_ones() >= 5)
+        {
+            Some(&rankset) => tables.flush[rankset as usize] as u32,
+            None => tables.ranks[rank_multiset_index(&rank_count)] as u32,
+        }
+    }
+
+    fn evaluate_batch(&self, board: &[Card; 5], hands: &[(Card, Card)], out: &mut [u32]) {
+        let mut rank_count = [0u8; 13];
+        let mut rankset_suit = [0u16; 4];
+        for &card in board {
+            rank_count[card as usize >> 2] += 1;
+            rankset_suit[card as usize & 3] |= 1 << (card >> 2);
+        }
+
+        // non-flush values indexed by the ranks of the hole cards (`0` for impossible pairs)
+        let tables = self.tables;
+        let mut rank_table = [0u16; 13 * 13];
+        for rank1 in 0..13 {
+            for rank2 in rank1..13 {
+                rank_count[rank1] += 1;
+                rank_count[rank2] += 1;
+                if rank_count[rank1] <= 4 && rank_count[rank2] <= 4 {
+                    let value = tables.ranks[rank_multiset_index(&rank_count)];
+                    rank_table[rank1 * 13 + rank2] = value;
+                    rank_table[rank2 * 13 + rank1] = value;
+                }
+                rank_count[rank1] -= 1;
+                rank_count[rank2] -= 1;
+            }
+        }
+
+        // at most one suit can make a flush; otherwise, no hole card matches the suit `4`
+        let (flush_suit, board_rankset) = match rankset_suit
+            .iter()
+            .position(|rankset| rankset.count_ones() >= 3)
+        {
+            Some(suit) => (suit as Card, rankset_suit[suit]),
+            None => (4, 0),
+        };
+
+        for (out, &(c1, c2)) in out.iter_mut().zip(hands) {
+            let (rank1, rank2) = (c1 as usize >> 2, c2 as usize >> 2);
+            let rankset = board_rankset
+                | (((c1 & 3 == flush_suit) as u16) << rank1)
+                | (((c2 & 3 == flush_suit) as u16) << rank2);
+            *out = if rankset.count_ones() >= 5 {
+                tables.flush[rankset as usize] as u32
+            } else {
+                rank_table[rank1 * 13 + rank2] as u32
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastrand::Rng;
+
+    #[test]
+    fn lookup_evaluator() {
+        let mut rng = Rng::with_seed(42);
+        let evaluators: [(&dyn HandEvaluator, LookupEvaluator, u64); 2] = [
+            (&HoldemEvaluator, LookupEvaluator::new(), 0),
+            (
+                &ShortDeckEvaluator,
+                LookupEvaluator::new_short_deck(),
+                0xffff,
+            ),
+        ];
+
+        for (reference, evaluator, removed) in evaluators {
+            for _ in 0..100000 {
+                let mut cards = [0; 7];
+                let mut mask = removed;
+                for card in &mut cards {
+                    *card = loop {
+                        let card = rng.u8(0..52);
+                        if mask & (1 << card) == 0 {
+                            break card;
+                        }
+                    };
+                    mask |= 1 << *card;
+                }
+                let board = cards[..5].try_into().unwrap();
+                let hand = (cards[5], cards[6]);
+                assert_eq!(
+                    evaluator.evaluate(&board, hand),
+                    reference.evaluate(&board, hand)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn evaluate_batch() {
+        let mut rng = Rng::with_seed(42);
+        let evaluators: [(&dyn HandEvaluator, u64); 4] = [
+            (&HoldemEvaluator, 0),
+            (&ShortDeckEvaluator, 0xffff),
+            (&LookupEvaluator::new(), 0),
+            (&LookupEvaluator::new_short_deck(), 0xffff),
+        ];
+
+        for (evaluator, removed) in evaluators {
+            for _ in 0..200 {
+                let mut board = [0; 5];
+                let mut mask = removed;
+                for card in &mut board {
+                    *card = loop {
+                        let card = rng.u8(0..52);
+                        if mask & (1 << card) == 0 {
+                            break card;
+                        }
+                    };
+                    mask |= 1 << *card;
+                }
+
+                let hands = (0..52)
+                    .flat_map(|c1| (c1 + 1..52).map(move |c2| (c1, c2)))
+                    .filter(|&(c1, c2)| mask & ((1 << c1) | (1 << c2)) == 0)
+                    .collect::<Vec<_>>();
+                let mut out = vec![0; hands.len()];
+                evaluator.evaluate_batch(&board, &hands, &mut out);
+                for (&hand, &value) in hands.iter().zip(&out) {
+                    assert_eq!(value, evaluator.evaluate(&board, hand));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,976 @@
+// [C API]
+//  - Objects are passed as opaque handles (`PfsTreeConfig`, `PfsGame`) created by `*_new` and
+//    released by `*_free`.
+//  - Every fallible function returns a `PfsStatus`; the message of the last error on the calling
+//    thread is obtained by `pfs_last_error`.
+//  - Arrays are passed as a pointer to a flat buffer and its length in elements. Functions writing
+//    into a buffer return `PfsStatus::BufferTooSmall` if it is too short; the required length is
+//    computed from `pfs_game_num_actions` and `pfs_game_num_private_hands` beforehand.
+//  - Strings are NUL-terminated UTF-8.
+//
+// The declarations for C and C++ are in `include/postflop_solver.h`, which must be kept in sync
+// with this file.
+
+use crate::action_tree::*;
+use crate::bet_size::*;
+use crate::card::*;
+use crate::game::*;
+use crate::interface::*;
+use crate::range::*;
+use crate::solver::*;
+use crate::utility::*;
+use std::cell::RefCell;
+use std::ffi::{CStr, c_char, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// Status code returned by the functions of the C API.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PfsStatus {
+    /// The function succeeded.
+    Ok = 0,
+    /// A required pointer argument is null.
+    NullPointer = 1,
+    /// An argument is invalid (e.g., a malformed range string or an out-of-range player).
+    InvalidArgument = 2,
+    /// The game is not in a state the function can be called in (e.g., solving a solved game).
+    InvalidState = 3,
+    /// An output buffer is shorter than required.
+    BufferTooSmall = 4,
+    /// The solver panicked; the game handle should not be used except for `pfs_game_free`.
+    Panic = 5,
+    /// The solving was aborted by the progress callback.
+    Aborted = 6,
+}
+
+/// Opaque handle of a [`TreeConfig`] for the C API.
+pub struct PfsTreeConfig(TreeConfig);
+
+/// Opaque handle of a [`PostFlopGame`] for the C API.
+pub struct PfsGame(PostFlopGame);
+
+/// Progress callback of [`pfs_game_solve`], called with the user data, the number of finished
+/// iterations, and the current exploitability. Returning `false` aborts the solving.
+pub type PfsProgressCallback = extern "C" fn(*mut c_void, u32, f32) -> bool;
+
+thread_local! {
+    static LAST_ERROR: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+struct FfiError(PfsStatus, String);
+
+type FfiResult<T> = Result<T, FfiError>;
+
+#[inline]
+fn invalid_argument(message: impl ToString) -> FfiError {
+    FfiError(PfsStatus::InvalidArgument, message.to_string())
+}
+
+#[inline]
+fn invalid_state(message: impl ToString) -> FfiError {
+    FfiError(PfsStatus::InvalidState, message.to_string())
+}
+
+/// Runs `f`, records its error message, and converts the result (or a panic) into a status code.
+fn run(f: impl FnOnce() -> FfiResult<()>) -> PfsStatus {
+    let (status, message) = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => (PfsStatus::Ok, String::new()),
+        Ok(Err(FfiError(status, message))) => (status, message),
+        Err(payload) => {
+            let message = if let Some(s) = payload.downcast_ref::<&str>() {
+                (*s).to_owned()
+            } else if let Some(s) = payload.downcast_ref::<String>() {
+                s.clone()
+            } else {
+                "Unknown panic".to_owned()
+            };
+            (PfsStatus::Panic, message)
+        }
+    };
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+    status
+}
+
+#[inline]
+unsafe fn deref<'a, T>(ptr: *const T, name: &str) -> FfiResult<&'a T> {
+    unsafe { ptr.as_ref() }
+        .ok_or_else(|| FfiError(PfsStatus::NullPointer, format!("{name} is null")))
+}
+
+#[inline]
+unsafe fn deref_mut<'a, T>(ptr: *mut T, name: &str) -> FfiResult<&'a mut T> {
+    unsafe { ptr.as_mut() }
+        .ok_or_else(|| FfiError(PfsStatus::NullPointer, format!("{name} is null")))
+}
+
+#[inline]
+unsafe fn to_str<'a>(ptr: *const c_char, name: &str) -> FfiResult<&'a str> {
+    if ptr.is_null() {
+        return Err(FfiError(PfsStatus::NullPointer, format!("{name} is null")));
+    }
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|_| invalid_argument(format!("{name} is not valid UTF-8")))
+}
+
+/// Copies `src` into the output buffer `dst` of length `len`.
+#[inline]
+unsafe fn write_buffer<T: Copy>(src: &[T], dst: *mut T, len: usize) -> FfiResult<()> {
+    if src.len() > len {
+        return Err(FfiError(
+            PfsStatus::BufferTooSmall,
+            format!("Buffer too small: {len} < {}", src.len()),
+        ));
+    }
+    if !src.is_empty() {
+        if dst.is_null() {
+            return Err(FfiError(
+                PfsStatus::NullPointer,
+                "Buffer is null".to_owned(),
+            ));
+        }
+        unsafe { ptr::copy_nonoverlapping(src.as_ptr(), dst, src.len()) };
+    }
+    Ok(())
+}
+
+/// Copies `s` with a terminating NUL into the output buffer `dst` of length `len`.
+#[inline]
+unsafe fn write_string(s: &str, dst: *mut c_char, len: usize) -> FfiResult<()> {
+    let mut bytes = s
+        .as_bytes()
+        .iter()
+        .map(|&b| b as c_char)
+        .collect::<Vec<_>>();
+    bytes.push(0);
+    unsafe { write_buffer(&bytes, dst, len) }
+}
+
+#[inline]
+fn check_player(player: u32) -> FfiResult<usize> {
+    match player {
+        0 | 1 => Ok(player as usize),
+        _ => Err(invalid_argument(format!("Invalid player: {player}"))),
+    }
+}
+
+#[inline]
+fn check_solvable(game: &PostFlopGame) -> FfiResult<()> {
+    if !game.is_ready() || game.is_solved() {
+        return Err(invalid_state("Game is not ready to be solved"));
+    }
+    Ok(())
+}
+
+/// Returns the length of the buffer required by `pfs_last_error` (including the terminating NUL).
+#[unsafe(no_mangle)]
+pub extern "C" fn pfs_last_error_len() -> usize {
+    LAST_ERROR.with(|e| e.borrow().len() + 1)
+}
+
+/// Writes the message of the last error on the calling thread into `buf`. The message is empty if
+/// the last call succeeded.
+///
+/// # Safety
+///
+/// `buf` must be valid for writes of `len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_last_error(buf: *mut c_char, len: usize) -> PfsStatus {
+    let message = LAST_ERROR.with(|e| e.borrow().clone());
+    match unsafe { write_string(&message, buf, len) } {
+        Ok(()) => PfsStatus::Ok,
+        Err(FfiError(status, _)) => status,
+    }
+}
+
+/// Creates a tree configuration without bet sizes. Release it with `pfs_tree_config_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn pfs_tree_config_new(
+    starting_pot: i32,
+    effective_stack: i32,
+) -> *mut PfsTreeConfig {
+    Box::into_raw(Box::new(PfsTreeConfig(TreeConfig {
+        starting_pot,
+        effective_stack,
+        ..Default::default()
+    })))
+}
+
+/// Releases a tree configuration. Does nothing if `config` is null.
+///
+/// # Safety
+///
+/// `config` must be null or a handle returned by `pfs_tree_config_new` that is not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_tree_config_free(config: *mut PfsTreeConfig) {
+    if !config.is_null() {
+        drop(unsafe { Box::from_raw(config) });
+    }
+}
+
+/// Sets the rake rate and the rake cap.
+///
+/// # Safety
+///
+/// `config` must be a valid tree configuration handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_tree_config_set_rake(
+    config: *mut PfsTreeConfig,
+    rate: f64,
+    cap: f64,
+) -> PfsStatus {
+    run(|| {
+        let config = unsafe { deref_mut(config, "config") }?;
+        config.0.rake_rate = rate;
+        config.0.rake_cap = cap;
+        Ok(())
+    })
+}
+
+/// Sets the bet and raise sizes (in the format of [`BetSizeOptions`]) of `player` (`0` for OOP,
+/// `1` for IP) on `street` (`0` for the flop, `1` for the turn, and `2` for the river).
+///
+/// # Safety
+///
+/// `config` must be a valid tree configuration handle, and `bet` and `raise` must be
+/// NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_tree_config_set_bet_sizes(
+    config: *mut PfsTreeConfig,
+    street: u32,
+    player: u32,
+    bet: *const c_char,
+    raise: *const c_char,
+) -> PfsStatus {
+    run(|| {
+        let config = unsafe { deref_mut(config, "config") }?;
+        let player = check_player(player)?;
+        let bet = unsafe { to_str(bet, "bet") }?;
+        let raise = unsafe { to_str(raise, "raise") }?;
+        let sizes = BetSizeOptions::try_from((bet, raise)).map_err(invalid_argument)?;
+        match street {
+            0 => config.0.flop_bet_sizes[player] = sizes,
+            1 => config.0.turn_bet_sizes[player] = sizes,
+            2 => config.0.river_bet_sizes[player] = sizes,
+            _ => return Err(invalid_argument(format!("Invalid street: {street}"))),
+        }
+        Ok(())
+    })
+}
+
+/// Sets the donk bet sizes on `street` (`1` for the turn and `2` for the river). An empty string
+/// disables them.
+///
+/// # Safety
+///
+/// `config` must be a valid tree configuration handle, and `donk` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_tree_config_set_donk_sizes(
+    config: *mut PfsTreeConfig,
+    street: u32,
+    donk: *const c_char,
+) -> PfsStatus {
+    run(|| {
+        let config = unsafe { deref_mut(config, "config") }?;
+        let donk = unsafe { to_str(donk, "donk") }?;
+        let sizes = if donk.is_empty() {
+            None
+        } else {
+            Some(DonkSizeOptions::try_from(donk).map_err(invalid_argument)?)
+        };
+        match street {
+            1 => config.0.turn_donk_sizes = sizes,
+            2 => config.0.river_donk_sizes = sizes,
+            _ => return Err(invalid_argument(format!("Invalid street: {street}"))),
+        }
+        Ok(())
+    })
+}
+
+/// Creates a game from the ranges, the board (e.g., `"Td9d6h"`), and the tree configuration, and
+/// stores its handle in `*out`. The initial street is determined by the board. Release the game
+/// with `pfs_game_free`.
+///
+/// # Safety
+///
+/// `oop_range`, `ip_range`, and `board` must be NUL-terminated strings, `config` must be a valid
+/// tree configuration handle, and `out` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_new(
+    oop_range: *const c_char,
+    ip_range: *const c_char,
+    board: *const c_char,
+    config: *const PfsTreeConfig,
+    out: *mut *mut PfsGame,
+) -> PfsStatus {
+    run(|| {
+        let oop_range = unsafe { to_str(oop_range, "oop_range") }?;
+        let ip_range = unsafe { to_str(ip_range, "ip_range") }?;
+        let board = unsafe { to_str(board, "board") }?;
+        let config = unsafe { deref(config, "config") }?;
+        let out = unsafe { deref_mut(out, "out") }?;
+
+        let board = board_from_str(board).map_err(|e| invalid_argument(e.to_string()))?;
+        let card_config = CardConfig {
+            range: [
+                oop_range.parse().map_err(invalid_argument)?,
+                ip_range.parse().map_err(invalid_argument)?,
+            ],
+            flop: [board[0], board[1], board[2]],
+            turn: board.get(3).copied().unwrap_or(NOT_DEALT),
+            river: board.get(4).copied().unwrap_or(NOT_DEALT),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: match board.len() {
+                3 => BoardState::Flop,
+                4 => BoardState::Turn,
+                _ => BoardState::River,
+            },
+            ..config.0.clone()
+        };
+
+        let action_tree = ActionTree::new(tree_config).map_err(invalid_argument)?;
+        let game = PostFlopGame::with_config(card_config, action_tree).map_err(invalid_argument)?;
+        *out = Box::into_raw(Box::new(PfsGame(game)));
+        Ok(())
+    })
+}
+
+/// Releases a game. Does nothing if `game` is null.
+///
+/// # Safety
+///
+/// `game` must be null or a handle returned by `pfs_game_new` that is not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_free(game: *mut PfsGame) {
+    if !game.is_null() {
+        drop(unsafe { Box::from_raw(game) });
+    }
+}
+
+/// Stores the estimated memory usage in bytes without and with compression.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle, and `uncompressed` and `compressed` must be valid for
+/// writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_memory_usage(
+    game: *const PfsGame,
+    uncompressed: *mut u64,
+    compressed: *mut u64,
+) -> PfsStatus {
+    run(|| {
+        let game = unsafe { deref(game, "game") }?;
+        let uncompressed = unsafe { deref_mut(uncompressed, "uncompressed") }?;
+        let compressed = unsafe { deref_mut(compressed, "compressed") }?;
+        (*uncompressed, *compressed) = game.0.memory_usage();
+        Ok(())
+    })
+}
+
+/// Allocates the memory for solving.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_allocate_memory(
+    game: *mut PfsGame,
+    enable_compression: bool,
+) -> PfsStatus {
+    run(|| {
+        let game = unsafe { deref_mut(game, "game") }?;
+        if game.0.is_memory_allocated().is_some() {
+            return Err(invalid_state("Memory is already allocated"));
+        }
+        game.0.allocate_memory(enable_compression);
+        Ok(())
+    })
+}
+
+/// Solves the game until `max_num_iterations` or `target_exploitability` is reached, and stores
+/// the exploitability in `*exploitability`.
+///
+/// If `progress` is not null, it is called with `user_data` after each iteration; the
+/// exploitability is updated every 10 iterations. If it returns `false`, the solving is aborted
+/// without finalizing the game and `PfsStatus::Aborted` is returned.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle, and `exploitability` must be null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_solve(
+    game: *mut PfsGame,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    progress: Option<PfsProgressCallback>,
+    user_data: *mut c_void,
+    exploitability: *mut f32,
+) -> PfsStatus {
+    run(|| {
+        let game = &mut unsafe { deref_mut(game, "game") }?.0;
+        check_solvable(game)?;
+
+        let mut current = compute_exploitability(game);
+        let mut num_iterations = 0;
+
+        for t in 0..max_num_iterations {
+            if current <= target_exploitability {
+                break;
+            }
+
+            solve_step(game, t);
+            if (t + 1) % 10 == 0 || t + 1 == max_num_iterations {
+                current = compute_exploitability(game);
+            }
+
+            if let Some(progress) = progress
+                && !progress(user_data, t + 1, current)
+            {
+                return Err(FfiError(PfsStatus::Aborted, "Solving aborted".to_owned()));
+            }
+
+            num_iterations = t + 1;
+        }
+
+        game.set_solve_result(num_iterations, current);
+        finalize(game);
+        if let Some(exploitability) = unsafe { exploitability.as_mut() } {
+            *exploitability = current;
+        }
+        Ok(())
+    })
+}
+
+/// Performs one iteration of the solving (see [`solve_step`]). Call `pfs_game_finalize` after the
+/// last iteration.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_solve_step(
+    game: *mut PfsGame,
+    current_iteration: u32,
+) -> PfsStatus {
+    run(|| {
+        let game = &unsafe { deref_mut(game, "game") }?.0;
+        check_solvable(game)?;
+        solve_step(game, current_iteration);
+        Ok(())
+    })
+}
+
+/// Computes the exploitability of the current strategy and stores it in `*exploitability`.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle, and `exploitability` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_exploitability(
+    game: *const PfsGame,
+    exploitability: *mut f32,
+) -> PfsStatus {
+    run(|| {
+        let game = &unsafe { deref(game, "game") }?.0;
+        let exploitability = unsafe { deref_mut(exploitability, "exploitability") }?;
+        if !game.is_ready() && !game.is_solved() {
+            return Err(invalid_state("Game is not ready"));
+        }
+        *exploitability = compute_exploitability(game);
+        Ok(())
+    })
+}
+
+/// Finalizes the solving (see [`finalize`]).
+///
+/// # Safety
+///
+/// `game` must be a valid game handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_finalize(game: *mut PfsGame) -> PfsStatus {
+    run(|| {
+        let game = &mut unsafe { deref_mut(game, "game") }?.0;
+        check_solvable(game)?;
+        finalize(game);
+        Ok(())
+    })
+}
+
+/// Moves back to the root node.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_back_to_root(game: *mut PfsGame) -> PfsStatus {
+    run(|| {
+        unsafe { deref_mut(game, "game") }?.0.back_to_root();
+        Ok(())
+    })
+}
+
+/// Moves to the node reached by the `len` actions of `history` from the root node.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle, and `history` must be valid for reads of `len` elements.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_apply_history(
+    game: *mut PfsGame,
+    history: *const u32,
+    len: usize,
+) -> PfsStatus {
+    run(|| {
+        let game = &mut unsafe { deref_mut(game, "game") }?.0;
+        let history = if len == 0 {
+            Vec::new()
+        } else {
+            let history = unsafe { deref(history, "history") }?;
+            let history = unsafe { std::slice::from_raw_parts(history, len) };
+            history.iter().map(|&a| a as usize).collect()
+        };
+        game.apply_history(&history);
+        Ok(())
+    })
+}
+
+/// Plays `action` (an index of the available actions, or a card at a chance node).
+///
+/// # Safety
+///
+/// `game` must be a valid game handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_play(game: *mut PfsGame, action: u32) -> PfsStatus {
+    run(|| {
+        unsafe { deref_mut(game, "game") }?.0.play(action as usize);
+        Ok(())
+    })
+}
+
+/// Returns whether the current node is a terminal node (`false` if `game` is null).
+///
+/// # Safety
+///
+/// `game` must be null or a valid game handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_is_terminal_node(game: *const PfsGame) -> bool {
+    unsafe { game.as_ref() }.is_some_and(|game| game.0.is_terminal_node())
+}
+
+/// Returns whether the current node is a chance node (`false` if `game` is null).
+///
+/// # Safety
+///
+/// `game` must be null or a valid game handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_is_chance_node(game: *const PfsGame) -> bool {
+    unsafe { game.as_ref() }.is_some_and(|game| game.0.is_chance_node())
+}
+
+/// Returns the player to act at the current node (`0` for OOP and `1` for IP; `-1` if `game` is
+/// null or the current node is a terminal or chance node).
+///
+/// # Safety
+///
+/// `game` must be null or a valid game handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_current_player(game: *const PfsGame) -> i32 {
+    match unsafe { game.as_ref() } {
+        Some(game) if !game.0.is_terminal_node() && !game.0.is_chance_node() => {
+            game.0.current_player() as i32
+        }
+        _ => -1,
+    }
+}
+
+/// Returns the number of actions available at the current node (`0` if `game` is null).
+///
+/// # Safety
+///
+/// `game` must be null or a valid game handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_num_actions(game: *const PfsGame) -> usize {
+    unsafe { game.as_ref() }.map_or(0, |game| game.0.available_actions().len())
+}
+
+/// Writes the name of the `index`-th available action (e.g., `"Bet 50"`) into `buf` as a
+/// NUL-terminated string.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle, and `buf` must be valid for writes of `len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_action_name(
+    game: *const PfsGame,
+    index: usize,
+    buf: *mut c_char,
+    len: usize,
+) -> PfsStatus {
+    run(|| {
+        let game = &unsafe { deref(game, "game") }?.0;
+        let actions = game.available_actions();
+        let action = actions
+            .get(index)
+            .ok_or_else(|| invalid_argument(format!("Invalid action index: {index}")))?;
+        unsafe { write_string(&action.to_string(), buf, len) }
+    })
+}
+
+/// Stores the bit mask of the cards that can be dealt at the current chance node (the bit `c` is
+/// set if the card `c` can be dealt).
+///
+/// # Safety
+///
+/// `game` must be a valid game handle, and `mask` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_possible_cards(
+    game: *const PfsGame,
+    mask: *mut u64,
+) -> PfsStatus {
+    run(|| {
+        let game = &unsafe { deref(game, "game") }?.0;
+        *unsafe { deref_mut(mask, "mask") }? = game.possible_cards();
+        Ok(())
+    })
+}
+
+/// Writes the board cards at the current node into `buf` and stores their number in `*num_cards`.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle, `buf` must be valid for writes of `len` elements (5
+/// suffices), and `num_cards` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_current_board(
+    game: *const PfsGame,
+    buf: *mut Card,
+    len: usize,
+    num_cards: *mut usize,
+) -> PfsStatus {
+    run(|| {
+        let game = &unsafe { deref(game, "game") }?.0;
+        let num_cards = unsafe { deref_mut(num_cards, "num_cards") }?;
+        let board = game.current_board();
+        unsafe { write_buffer(&board, buf, len) }?;
+        *num_cards = board.len();
+        Ok(())
+    })
+}
+
+/// Returns the number of private hands of `player` (`0` if `game` is null or `player` is
+/// invalid).
+///
+/// # Safety
+///
+/// `game` must be null or a valid game handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_num_private_hands(game: *const PfsGame, player: u32) -> usize {
+    match (unsafe { game.as_ref() }, check_player(player)) {
+        (Some(game), Ok(player)) => game.0.num_private_hands(player),
+        _ => 0,
+    }
+}
+
+/// Writes the private hands of `player` into `buf` as pairs of cards, so that `buf` must hold
+/// `2 * pfs_game_num_private_hands(game, player)` elements.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle, and `buf` must be valid for writes of `len` elements.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_private_cards(
+    game: *const PfsGame,
+    player: u32,
+    buf: *mut Card,
+    len: usize,
+) -> PfsStatus {
+    run(|| {
+        let game = &unsafe { deref(game, "game") }?.0;
+        let player = check_player(player)?;
+        let cards = game
+            .private_cards(player)
+            .iter()
+            .flat_map(|&(c1, c2)| [c1, c2])
+            .collect::<Vec<_>>();
+        unsafe { write_buffer(&cards, buf, len) }
+    })
+}
+
+/// Computes the normalized weights at the current node, which must be called before the queries
+/// of the weights, equity, and expected values.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_cache_normalized_weights(game: *mut PfsGame) -> PfsStatus {
+    run(|| {
+        unsafe { deref_mut(game, "game") }?
+            .0
+            .cache_normalized_weights();
+        Ok(())
+    })
+}
+
+/// Common implementation of the per-hand queries of `player`.
+unsafe fn query_hands(
+    game: *const PfsGame,
+    player: u32,
+    buf: *mut f32,
+    len: usize,
+    f: impl FnOnce(&PostFlopGame, usize) -> Vec<f32>,
+) -> PfsStatus {
+    run(|| {
+        let game = &unsafe { deref(game, "game") }?.0;
+        let player = check_player(player)?;
+        if !game.is_solved() {
+            return Err(invalid_state("Game is not solved"));
+        }
+        unsafe { write_buffer(&f(game, player), buf, len) }
+    })
+}
+
+/// Writes the weights of the private hands of `player` at the current node into `buf`, which must
+/// hold `pfs_game_num_private_hands(game, player)` elements.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle, and `buf` must be valid for writes of `len` elements.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_weights(
+    game: *const PfsGame,
+    player: u32,
+    buf: *mut f32,
+    len: usize,
+) -> PfsStatus {
+    unsafe { query_hands(game, player, buf, len, |g, p| g.weights(p).to_vec()) }
+}
+
+/// Writes the normalized weights of the private hands of `player` at the current node into `buf`
+/// (see `pfs_game_weights`).
+///
+/// # Safety
+///
+/// `game` must be a valid game handle, and `buf` must be valid for writes of `len` elements.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_normalized_weights(
+    game: *const PfsGame,
+    player: u32,
+    buf: *mut f32,
+    len: usize,
+) -> PfsStatus {
+    unsafe {
+        query_hands(game, player, buf, len, |g, p| {
+            g.normalized_weights(p).to_vec()
+        })
+    }
+}
+
+/// Writes the equity of the private hands of `player` at the current node into `buf` (see
+/// `pfs_game_weights`).
+///
+/// # Safety
+///
+/// `game` must be a valid game handle, and `buf` must be valid for writes of `len` elements.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_equity(
+    game: *const PfsGame,
+    player: u32,
+    buf: *mut f32,
+    len: usize,
+) -> PfsStatus {
+    unsafe { query_hands(game, player, buf, len, |g, p| g.equity(p)) }
+}
+
+/// Writes the expected values of the private hands of `player` at the current node into `buf`
+/// (see `pfs_game_weights`).
+///
+/// # Safety
+///
+/// `game` must be a valid game handle, and `buf` must be valid for writes of `len` elements.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_expected_values(
+    game: *const PfsGame,
+    player: u32,
+    buf: *mut f32,
+    len: usize,
+) -> PfsStatus {
+    unsafe { query_hands(game, player, buf, len, |g, p| g.expected_values(p)) }
+}
+
+/// Writes the strategy at the current node into `buf`, which must hold `pfs_game_num_actions(game)
+/// * pfs_game_num_private_hands(game, player)` elements for the player to act. The strategy of
+/// the `i`-th action for the `j`-th hand is at `buf[i * num_hands + j]`.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle, and `buf` must be valid for writes of `len` elements.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_strategy(
+    game: *const PfsGame,
+    buf: *mut f32,
+    len: usize,
+) -> PfsStatus {
+    run(|| {
+        let game = &unsafe { deref(game, "game") }?.0;
+        if game.is_memory_allocated().is_none() {
+            return Err(invalid_state("Memory is not allocated"));
+        }
+        if game.is_terminal_node() || game.is_chance_node() {
+            return Err(invalid_state("Current node is a terminal or chance node"));
+        }
+        unsafe { write_buffer(&game.strategy(), buf, len) }
+    })
+}
+
+/// Locks the strategy at the current node to `strategy` of `len` elements, laid out as in
+/// `pfs_game_strategy`.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle, and `strategy` must be valid for reads of `len` elements.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_lock_current_strategy(
+    game: *mut PfsGame,
+    strategy: *const f32,
+    len: usize,
+) -> PfsStatus {
+    run(|| {
+        let game = &mut unsafe { deref_mut(game, "game") }?.0;
+        let strategy = unsafe { deref(strategy, "strategy") }?;
+        let strategy = unsafe { std::slice::from_raw_parts(strategy, len) };
+        game.lock_current_strategy(strategy);
+        Ok(())
+    })
+}
+
+/// Unlocks the strategy at the current node.
+///
+/// # Safety
+///
+/// `game` must be a valid game handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pfs_game_unlock_current_strategy(game: *mut PfsGame) -> PfsStatus {
+    run(|| {
+        unsafe { deref_mut(game, "game") }?
+            .0
+            .unlock_current_strategy();
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern "C" fn count_iterations(user_data: *mut c_void, _: u32, _: f32) -> bool {
+        unsafe { *user_data.cast::<u32>() += 1 };
+        true
+    }
+
+    #[test]
+    fn c_api_workflow() {
+        unsafe {
+            let config = pfs_tree_config_new(100, 100);
+            let bet = c"50%".as_ptr();
+            let empty = c"".as_ptr();
+            for street in 0..3 {
+                let status = pfs_tree_config_set_bet_sizes(config, street, 0, bet, empty);
+                assert_eq!(status, PfsStatus::Ok);
+            }
+
+            let mut game = ptr::null_mut();
+            let status = pfs_game_new(
+                c"66+,A8s+".as_ptr(),
+                c"22+".as_ptr(),
+                c"Td9d6h".as_ptr(),
+                config,
+                &raw mut game,
+            );
+            assert_eq!(status, PfsStatus::Ok);
+
+            // error reporting
+            let mut invalid = ptr::null_mut();
+            let status = pfs_game_new(
+                c"invalid".as_ptr(),
+                c"22+".as_ptr(),
+                c"Td9d6h".as_ptr(),
+                config,
+                &raw mut invalid,
+            );
+            assert_eq!(status, PfsStatus::InvalidArgument);
+            assert!(invalid.is_null());
+            let mut message = vec![0; pfs_last_error_len()];
+            assert_eq!(
+                pfs_last_error(message.as_mut_ptr(), message.len()),
+                PfsStatus::Ok
+            );
+            assert!(
+                CStr::from_ptr(message.as_ptr())
+                    .to_str()
+                    .unwrap()
+                    .contains("range")
+            );
+            assert_eq!(pfs_game_play(ptr::null_mut(), 0), PfsStatus::NullPointer);
+            pfs_tree_config_free(config);
+
+            // solving
+            assert_eq!(pfs_game_allocate_memory(game, false), PfsStatus::Ok);
+            let mut count = 0u32;
+            let mut exploitability = 0.0;
+            let status = pfs_game_solve(
+                game,
+                20,
+                0.0,
+                Some(count_iterations),
+                (&raw mut count).cast(),
+                &raw mut exploitability,
+            );
+            assert_eq!(status, PfsStatus::Ok);
+            assert_eq!(count, 20);
+            assert!(exploitability > 0.0);
+            assert_eq!(pfs_game_finalize(game), PfsStatus::InvalidState);
+
+            // queries
+            assert_eq!(pfs_game_current_player(game), 0);
+            let num_actions = pfs_game_num_actions(game);
+            assert_eq!(num_actions, 2);
+            let mut name = [0; 16];
+            let status = pfs_game_action_name(game, 1, name.as_mut_ptr(), name.len());
+            assert_eq!(status, PfsStatus::Ok);
+            assert_eq!(CStr::from_ptr(name.as_ptr()).to_str().unwrap(), "Bet 50");
+
+            let num_hands = pfs_game_num_private_hands(game, 0);
+            let mut strategy = vec![0.0; num_actions * num_hands];
+            let status = pfs_game_strategy(game, strategy.as_mut_ptr(), strategy.len() - 1);
+            assert_eq!(status, PfsStatus::BufferTooSmall);
+            let status = pfs_game_strategy(game, strategy.as_mut_ptr(), strategy.len());
+            assert_eq!(status, PfsStatus::Ok);
+            assert_eq!(strategy, (*game).0.strategy());
+
+            assert_eq!(pfs_game_cache_normalized_weights(game), PfsStatus::Ok);
+            let mut ev = vec![0.0; num_hands];
+            let status = pfs_game_expected_values(game, 0, ev.as_mut_ptr(), ev.len());
+            assert_eq!(status, PfsStatus::Ok);
+            assert_eq!(ev, (*game).0.expected_values(0));
+
+            // navigation
+            let history = [0, 0];
+            let status = pfs_game_apply_history(game, history.as_ptr(), history.len());
+            assert_eq!(status, PfsStatus::Ok);
+            assert!(pfs_game_is_chance_node(game));
+            assert_eq!(pfs_game_current_player(game), -1);
+            let mut mask = 0;
+            assert_eq!(pfs_game_possible_cards(game, &raw mut mask), PfsStatus::Ok);
+            assert_eq!(mask, (*game).0.possible_cards());
+            assert_eq!(pfs_game_play(game, 100), PfsStatus::Panic);
+
+            pfs_game_free(game);
+        }
+    }
+}
@@ -1,29 +1,52 @@
 // [File format]
 // The file consists of a header and a body. The header is as follows:
 //  - Magic number (4 bytes): 90 57 f1 09
-//  - Version number (1 byte): 1
+//  - Version number (1 byte): 2 (version 1 can still be loaded)
 //  - Compression type (1 byte): 0 (none), 1 (zstd)
-//  - Data type (1 byte): 0 (game), 1 (bunching)
+//  - Data type (1 byte): 0 (game), 1 (bunching), 2 (tree structure), 3 (equity cache)
 //  - Estimated memory usage (`VarIntEncoding`)
 //  - Memo string
+//  - Metadata (`SaveMetadata`; since version 2)
+//
+// The body of a game starts with its save format version string (`SAVE_FORMAT_VERSION`); the
+// older versions listed in `SUPPORTED_SAVE_FORMAT_VERSIONS` can still be loaded. Since the version
+// "2026-10-27", each section of the body (`SaveSection`) is followed by its CRC-32 checksum, which is
+// verified on load.
 //
 // `VarIntEncoding`: https://github.com/bincode-org/bincode/blob/trunk/docs/spec.md#varintencoding
 
+use crate::action_tree::*;
 use crate::bunching::*;
+use crate::card::*;
+use crate::convergence::*;
+use crate::error::*;
 use crate::game::*;
 use crate::interface::*;
+use crate::range::*;
+use crate::solver::*;
+use crate::utility::*;
 use bincode::{Decode, Encode};
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::Path;
 
+#[cfg(all(feature = "mmap", unix))]
+use crate::storage::FileMapping;
+#[cfg(all(feature = "mmap", unix))]
+use std::sync::Arc;
+
 const MAGIC: u32 = 0x09f15790;
-const VERSION: u8 = 1;
+const VERSION: u8 = 2;
+
+/// Oldest header version that can be loaded (version 1 has no metadata).
+const MIN_VERSION: u8 = 1;
 
 #[doc(hidden)]
 pub enum DataType {
     Game = 0,
     Bunching = 1,
+    TreeStructure = 2,
+    EquityCache = 3,
 }
 
 /// A trait for data that can be saved into a file.
@@ -34,15 +57,74 @@ pub trait FileData: Decode<()> + Encode {
     fn is_ready_to_save(&self) -> bool;
     #[doc(hidden)]
     fn estimated_memory_usage(&self) -> u64;
+    #[doc(hidden)]
+    fn game_metadata(&self) -> Option<GameMetadata>;
+    #[doc(hidden)]
+    fn restore_metadata(&mut self, metadata: &SaveMetadata);
+}
+
+/// Metadata stored in the header of a save file, which can be read by [`read_metadata`] without
+/// loading the data.
+#[derive(Debug, Clone, Decode, Encode)]
+pub struct SaveMetadata {
+    /// Version of this crate that saved the file.
+    pub crate_version: String,
+
+    /// Time when the file was saved (seconds since the Unix epoch; `0` if unavailable).
+    pub saved_at: u64,
+
+    /// Metadata of the game. `None` if the file contains a [`BunchingData`], a [`TreeStructure`],
+    /// or an [`EquityCache`].
+    pub game: Option<GameMetadata>,
+}
+
+/// Metadata of a saved [`PostFlopGame`] (see [`SaveMetadata`]).
+#[derive(Debug, Clone, Decode, Encode)]
+pub struct GameMetadata {
+    /// Card configuration, including the ranges.
+    pub card_config: CardConfig,
+
+    /// Tree configuration.
+    pub tree_config: TreeConfig,
+
+    /// Isomorphism mode of the turn and river deals.
+    pub isomorphism_mode: IsomorphismMode,
+
+    /// Whether the compression is enabled.
+    pub is_compression_enabled: bool,
+
+    /// Whether the cumulative strategy is stored in 8-bit.
+    pub is_strategy_quantized: bool,
+
+    /// Storage mode of the saved data.
+    pub storage_mode: BoardState,
+
+    /// Whether only the strategy is saved.
+    pub is_strategy_only: bool,
+
+    /// Number of iterations, exploitability, and time of the solving (see
+    /// `PostFlopGame::solve_summary`).
+    pub solve_summary: Option<SolveSummary>,
+
+    /// Inputs of the solving (see `PostFlopGame::solve_manifest`).
+    pub solve_manifest: Option<SolveManifest>,
 }
 
 fn encode_into_std_write<E: Encode, W: Write>(
     val: E,
     writer: &mut W,
     err_msg: &str,
-) -> Result<usize, String> {
-    bincode::encode_into_std_write(val, writer, bincode::config::standard())
-        .map_err(|e| format!("{err_msg}: {e}"))
+) -> Result<usize, SolverError> {
+    bincode::encode_into_std_write(val, writer, bincode::config::standard()).map_err(|e| {
+        let message = format!("{err_msg}: {e}");
+        match e {
+            bincode::error::EncodeError::Io { inner, .. } => SolverError::Io {
+                kind: inner.kind(),
+                message,
+            },
+            _ => SolverError::Other(message),
+        }
+    })
 }
 
 /// Saves data into a standard writer.
@@ -57,60 +139,99 @@ fn encode_into_std_write<E: Encode, W: Write>(
 /// - `memo`: A memo string to be saved with the data.
 /// - `writer`: The writer to write the data into.
 /// - `compression_level`: The zstd compression level to use. If `None`, no compression is used.
-///   `Some(level)` can only be specified if the `zstd` feature is enabled.
+///   `Some(level)` can only be specified if the `zstd` feature is enabled, and `level` must be in
+///   the range of `zstd_compression_level_range` (`0` selects the default level of zstd).
 pub fn save_data_into_std_write<T: FileData, W: Write>(
     data: &T,
     memo: &str,
     writer: &mut W,
     compression_level: Option<i32>,
-) -> Result<(), String> {
+) -> Result<(), SolverError> {
     if !data.is_ready_to_save() {
-        return Err("Data is not ready to save".to_owned());
+        return Err(SolverError::InvalidState(
+            "Data is not ready to save".to_owned(),
+        ));
     }
+    write_data(data, memo, writer, compression_level)
+}
 
+/// Writes the header and the body of `data` without checking whether it is ready to save.
+fn write_data<T: FileData, W: Write>(
+    data: &T,
+    memo: &str,
+    writer: &mut W,
+    compression_level: Option<i32>,
+) -> Result<(), SolverError> {
     #[cfg(not(feature = "zstd"))]
     if compression_level.is_some() {
-        return Err("Compression is not supported".to_owned());
+        return Err(SolverError::Other(
+            "Compression is not supported".to_owned(),
+        ));
+    }
+
+    #[cfg(feature = "zstd")]
+    if let Some(level) = compression_level {
+        let range = zstd_compression_level_range();
+        if !range.contains(&level) {
+            return Err(SolverError::InvalidArgument(format!(
+                "Compression level must be in the range [{}, {}]: {level}",
+                range.start(),
+                range.end()
+            )));
+        }
     }
 
-    encode_into_std_write(MAGIC, writer, "Failed to write magic number")?;
-    encode_into_std_write(VERSION, writer, "Failed to write version number")?;
+    let mut header_len = 0;
+    header_len += encode_into_std_write(MAGIC, writer, "Failed to write magic number")?;
+    header_len += encode_into_std_write(VERSION, writer, "Failed to write version number")?;
 
     let compression_type = compression_level.is_some() as u8;
-    encode_into_std_write(compression_type, writer, "Failed to write compression type")?;
+    header_len +=
+        encode_into_std_write(compression_type, writer, "Failed to write compression type")?;
 
-    encode_into_std_write(T::data_type() as u8, writer, "Failed to write data type")?;
-    encode_into_std_write(
+    header_len += encode_into_std_write(T::data_type() as u8, writer, "Failed to write data type")?;
+    header_len += encode_into_std_write(
         data.estimated_memory_usage(),
         writer,
         "Failed to write memory usage",
     )?;
 
-    encode_into_std_write(memo, writer, "Failed to write memo")?;
+    header_len += encode_into_std_write(memo, writer, "Failed to write memo")?;
+
+    let metadata = SaveMetadata {
+        crate_version: env!("CARGO_PKG_VERSION").to_owned(),
+        saved_at: unix_time(),
+        game: data.game_metadata(),
+    };
+    header_len += encode_into_std_write(metadata, writer, "Failed to write metadata")?;
 
     if compression_level.is_none() {
-        encode_into_std_write(data, writer, "Failed to write data")?;
+        // align the storage buffers in the file so that they can be memory-mapped
+        set_stream_offset(header_len);
+        let result = encode_into_std_write(data, writer, "Failed to write data");
+        set_stream_offset(0);
+        result?;
         writer
             .flush()
-            .map_err(|e| format!("Failed to flush writer: {e}"))?;
+            .map_err(|e| SolverError::io("Failed to flush writer", &e))?;
     }
 
     #[cfg(feature = "zstd")]
     if let Some(compression_level) = compression_level {
         let mut zstd_encoder = zstd::stream::Encoder::new(writer, compression_level)
-            .map_err(|e| format!("Failed to create zstd encoder: {e}"))?;
+            .map_err(|e| SolverError::io("Failed to create zstd encoder", &e))?;
 
         #[cfg(feature = "rayon")]
         zstd_encoder
             .multithread(rayon::current_num_threads() as u32)
-            .map_err(|e| format!("Failed to enable multithreaded zstd encoder: {e}"))?;
+            .map_err(|e| SolverError::io("Failed to enable multithreaded zstd encoder", &e))?;
 
         encode_into_std_write(data, &mut zstd_encoder, "Failed to write data")?;
         zstd_encoder
             .finish()
-            .map_err(|e| format!("Failed to finish zstd encoder: {e}"))?
+            .map_err(|e| SolverError::io("Failed to finish zstd encoder", &e))?
             .flush()
-            .map_err(|e| format!("Failed to flush writer: {e}"))?;
+            .map_err(|e| SolverError::io("Failed to flush writer", &e))?;
     }
 
     Ok(())
@@ -127,24 +248,229 @@ pub fn save_data_into_std_write<T: FileData, W: Write>(
 /// - `memo`: A memo string to be saved with the data.
 /// - `path`: The path to the file to save.
 /// - `compression_level`: The zstd compression level to use. If `None`, no compression is used.
-///   `Some(level)` can only be specified if the `zstd` feature is enabled.
+///   `Some(level)` can only be specified if the `zstd` feature is enabled, and `level` must be in
+///   the range of `zstd_compression_level_range` (`0` selects the default level of zstd).
 pub fn save_data_to_file<T: FileData, P: AsRef<Path>>(
     data: &T,
     memo: &str,
     path: P,
     compression_level: Option<i32>,
-) -> Result<(), String> {
-    let file = File::create(path).map_err(|e| format!("Failed to create file: {e}"))?;
+) -> Result<(), SolverError> {
+    let file = File::create(path).map_err(|e| SolverError::io("Failed to create file", &e))?;
     let mut writer = BufWriter::new(file);
     save_data_into_std_write(data, memo, &mut writer, compression_level)
 }
 
-fn decode_from_std_read<D: Decode<()>, R: Read>(
+/// Saves a game into a standard writer without its tree structure, referring to `structure`
+/// instead.
+///
+/// The tree structure (see [`TreeStructure`]) is identical for all boards solved with the same
+/// tree configuration, so it can be saved once with [`save_data_into_std_write`] and omitted from
+/// the game data. The saved game can only be loaded with the same structure by
+/// [`load_game_from_std_read_with_structure`]. Returns an error if the tree structure of `game`
+/// differs from `structure`.
+///
+/// The arguments `memo` and `compression_level` are the same as in [`save_data_into_std_write`].
+pub fn save_game_into_std_write_with_structure<W: Write>(
+    game: &PostFlopGame,
+    structure: &TreeStructure,
+    memo: &str,
+    writer: &mut W,
+    compression_level: Option<i32>,
+) -> Result<(), SolverError> {
+    let game_structure = TreeStructure::from_game(game)?;
+    if game_structure.id() != structure.id() {
+        return Err(SolverError::InvalidData(
+            "Tree structure of the game differs".to_owned(),
+        ));
+    }
+
+    with_encode_structure(structure.id(), || {
+        save_data_into_std_write(game, memo, writer, compression_level)
+    })
+}
+
+/// Saves a game into a file without its tree structure (see
+/// [`save_game_into_std_write_with_structure`]).
+///
+/// If the file already exists, it will be overwritten.
+pub fn save_game_to_file_with_structure<P: AsRef<Path>>(
+    game: &PostFlopGame,
+    structure: &TreeStructure,
+    memo: &str,
+    path: P,
+    compression_level: Option<i32>,
+) -> Result<(), SolverError> {
+    let file = File::create(path).map_err(|e| SolverError::io("Failed to create file", &e))?;
+    let mut writer = BufWriter::new(file);
+    save_game_into_std_write_with_structure(game, structure, memo, &mut writer, compression_level)
+}
+
+/// Saves a snapshot of a game in the middle of solving into a file.
+///
+/// The snapshot is saved as a strategy-only file (see `PostFlopGame::set_target_strategy_only`)
+/// containing the average strategy up to the last iteration, which can be loaded with
+/// [`load_data_from_file`] like a solved game. The solver state is left untouched, so the solving
+/// can be continued after the call. The data is first written to a temporary file next to `path`
+/// and then renamed, so `path` always contains a complete snapshot even if the process is
+/// interrupted while saving.
+///
+/// Returns an error if the game is not in the middle of solving (i.e., not ready to be solved).
+pub fn save_checkpoint<P: AsRef<Path>>(
+    game: &mut PostFlopGame,
+    memo: &str,
+    path: P,
+    compression_level: Option<i32>,
+) -> Result<(), SolverError> {
+    if !game.is_ready() {
+        return Err(SolverError::InvalidState(
+            "Game is not in the middle of solving".to_owned(),
+        ));
+    }
+
+    let path = path.as_ref();
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+
+    game.with_checkpoint_state(|game| {
+        save_data_to_file(game, memo, &temp_path, compression_level)
+    })?;
+    std::fs::rename(&temp_path, path).map_err(|e| SolverError::io("Failed to rename file", &e))
+}
+
+/// Same as [`solve`], but saves a snapshot of the game into `path` every `interval` iterations
+/// with [`save_checkpoint`].
+///
+/// The memo of each snapshot is the number of completed iterations. The final result is not saved
+/// by this function; use [`save_data_to_file`] after solving. Returns an error without finalizing
+/// the game if saving a snapshot fails.
+pub fn solve_with_checkpoints<P: AsRef<Path>>(
+    game: &mut PostFlopGame,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    print_progress: bool,
+    path: P,
+    interval: u32,
+) -> Result<f32, SolverError> {
+    if interval == 0 {
+        return Err(SolverError::InvalidArgument(
+            "Checkpoint interval must be positive".to_owned(),
+        ));
+    }
+
+    let path = path.as_ref();
+    solve_with_callback(
+        game,
+        max_num_iterations,
+        &mut TargetExploitability(target_exploitability),
+        print_progress,
+        None,
+        &mut |game, num_iterations| {
+            if num_iterations % interval != 0 {
+                return Ok(());
+            }
+            save_checkpoint(game, &num_iterations.to_string(), path, None)
+        },
+    )
+}
+
+/// Saves the complete solver state of a game in the middle of solving into a file.
+///
+/// Unlike [`save_checkpoint`], the file also contains the cumulative regrets, so the game loaded by
+/// [`load_resumable_checkpoint`] can be solved further as if the solving had never been
+/// interrupted (pass the number of completed iterations to [`solve_step`]). The file is about three
+/// times as large as a strategy-only snapshot. The data is first written to a temporary file next
+/// to `path` and then renamed, as in [`save_checkpoint`].
+///
+/// Returns an error if the game is not in the middle of solving (i.e., not ready to be solved).
+pub fn save_resumable_checkpoint<P: AsRef<Path>>(
+    game: &mut PostFlopGame,
+    memo: &str,
+    path: P,
+) -> Result<(), SolverError> {
+    if !game.is_ready() {
+        return Err(SolverError::InvalidState(
+            "Game is not in the middle of solving".to_owned(),
+        ));
+    }
+
+    let path = path.as_ref();
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+
+    game.with_resumable_state(|game| {
+        let file =
+            File::create(&temp_path).map_err(|e| SolverError::io("Failed to create file", &e))?;
+        let mut writer = BufWriter::new(file);
+        write_data(game, memo, &mut writer, None)?;
+        for buffer in game.solver_buffers() {
+            encode_into_std_write(buffer.len() as u64, &mut writer, "Failed to write length")?;
+            writer
+                .write_all(buffer)
+                .map_err(|e| SolverError::io("Failed to write data", &e))?;
+        }
+        writer
+            .flush()
+            .map_err(|e| SolverError::io("Failed to flush writer", &e))
+    })?;
+    std::fs::rename(&temp_path, path).map_err(|e| SolverError::io("Failed to rename file", &e))
+}
+
+/// Loads a game saved by [`save_resumable_checkpoint`].
+///
+/// The returned game is in the middle of solving; it is not finalized.
+pub fn load_resumable_checkpoint<P: AsRef<Path>>(
+    path: P,
+    max_memory_usage: Option<u64>,
+) -> Result<(PostFlopGame, String), SolverError> {
+    let file = File::open(path).map_err(|e| SolverError::io("Failed to open file", &e))?;
+    let mut reader = BufReader::new(file);
+    let (mut game, memo): (PostFlopGame, _) =
+        load_data_from_std_read(&mut reader, max_memory_usage)?;
+    let not_resumable =
+        || SolverError::InvalidData("File is not a resumable checkpoint".to_owned());
+    if !game.is_ready() {
+        return Err(not_resumable());
+    }
+
+    for buffer in game.solver_buffers_mut() {
+        let len: u64 = decode_from_std_read(&mut reader, "Failed to read length")?;
+        if len != buffer.len() as u64 {
+            return Err(not_resumable());
+        }
+        reader
+            .read_exact(buffer)
+            .map_err(|e| SolverError::io("Failed to read data", &e))?;
+    }
+
+    Ok((game, memo))
+}
+
+/// Returns the range of the zstd compression levels accepted by [`save_data_into_std_write`] and
+/// [`save_data_to_file`].
+///
+/// Higher levels produce smaller files at the cost of slower saving; loading is fast regardless
+/// of the level. Negative levels are faster than the default level (`3`) with worse compression.
+#[cfg(feature = "zstd")]
+#[inline]
+pub fn zstd_compression_level_range() -> std::ops::RangeInclusive<i32> {
+    zstd::compression_level_range()
+}
+
+pub(crate) fn decode_from_std_read<D: Decode<()>, R: Read>(
     reader: &mut R,
     err_msg: &str,
-) -> Result<D, String> {
-    bincode::decode_from_std_read(reader, bincode::config::standard())
-        .map_err(|e| format!("{err_msg}: {e}"))
+) -> Result<D, SolverError> {
+    bincode::decode_from_std_read(reader, bincode::config::standard()).map_err(|e| {
+        let message = format!("{err_msg}: {e}");
+        match e {
+            bincode::error::DecodeError::Io { inner, .. } => SolverError::Io {
+                kind: inner.kind(),
+                message,
+            },
+            _ => SolverError::InvalidData(message),
+        }
+    })
 }
 
 /// Loads data from a standard reader.
@@ -166,53 +492,118 @@ fn decode_from_std_read<D: Decode<()>, R: Read>(
 pub fn load_data_from_std_read<T: FileData, R: Read>(
     reader: &mut R,
     max_memory_usage: Option<u64>,
-) -> Result<(T, String), String> {
-    let magic: u32 = decode_from_std_read(reader, "Failed to read magic number")?;
-    if magic != MAGIC {
-        return Err("Magic number is invalid".to_owned());
-    }
+) -> Result<(T, String), SolverError> {
+    #[cfg(not(feature = "zstd"))]
+    let (_, memo, metadata) = read_header::<T, R>(reader, max_memory_usage)?;
+    #[cfg(feature = "zstd")]
+    let (compression_type, memo, metadata) = read_header::<T, R>(reader, max_memory_usage)?;
 
-    let version: u8 = decode_from_std_read(reader, "Failed to read version number")?;
-    if version != VERSION {
-        return Err("Version number is invalid".to_owned());
+    #[cfg(not(feature = "zstd"))]
+    let mut data: T = decode_from_std_read(reader, "Failed to read data")?;
+    #[cfg(feature = "zstd")]
+    let mut data: T = if compression_type == 0 {
+        decode_from_std_read(reader, "Failed to read data")?
+    } else {
+        let mut zstd_decoder = zstd::stream::Decoder::new(reader)
+            .map_err(|e| SolverError::io("Failed to create zstd decoder", &e))?;
+        decode_from_std_read(&mut zstd_decoder, "Failed to read data")?
+    };
+
+    if let Some(metadata) = metadata {
+        data.restore_metadata(&metadata);
     }
 
+    Ok((data, memo))
+}
+
+/// Reads the header and returns the compression type, the memo string, and the metadata (`None`
+/// for files with the header version 1).
+fn read_header<T: FileData, R: Read>(
+    reader: &mut R,
+    max_memory_usage: Option<u64>,
+) -> Result<(u8, String, Option<SaveMetadata>), SolverError> {
+    let version = read_magic_and_version(reader)?;
+
     let compression_type: u8 = decode_from_std_read(reader, "Failed to read compression type")?;
     if compression_type > 1 {
-        return Err("Compression type is invalid".to_owned());
+        return Err(SolverError::InvalidData(
+            "Compression type is invalid".to_owned(),
+        ));
     }
 
     #[cfg(not(feature = "zstd"))]
     if compression_type == 1 {
-        return Err("Compression is not supported".to_owned());
+        return Err(SolverError::Other(
+            "Compression is not supported".to_owned(),
+        ));
     }
 
     let data_type: u8 = decode_from_std_read(reader, "Failed to read data type")?;
     if data_type != T::data_type() as u8 {
-        return Err("Data type is invalid".to_owned());
+        return Err(SolverError::InvalidData("Data type is invalid".to_owned()));
     }
 
     let estimated_memory_usage: u64 = decode_from_std_read(reader, "Failed to read memory usage")?;
-    if let Some(max_memory_usage) = max_memory_usage {
-        if estimated_memory_usage > max_memory_usage {
-            return Err("Estimated memory usage is too large".to_owned());
-        }
+    if let Some(max_memory_usage) = max_memory_usage
+        && estimated_memory_usage > max_memory_usage
+    {
+        return Err(SolverError::MemoryLimitExceeded {
+            required: estimated_memory_usage,
+            available: max_memory_usage,
+            context: "loading the data".to_owned(),
+        });
     }
 
     let memo: String = decode_from_std_read(reader, "Failed to read memo")?;
-
-    #[cfg(not(feature = "zstd"))]
-    let data: T = decode_from_std_read(reader, "Failed to read data")?;
-    #[cfg(feature = "zstd")]
-    let data: T = if compression_type == 0 {
-        decode_from_std_read(reader, "Failed to read data")?
+    let metadata = if version >= 2 {
+        Some(decode_from_std_read(reader, "Failed to read metadata")?)
     } else {
-        let mut zstd_decoder = zstd::stream::Decoder::new(reader)
-            .map_err(|e| format!("Failed to create zstd decoder: {e}"))?;
-        decode_from_std_read(&mut zstd_decoder, "Failed to read data")?
+        None
     };
 
-    Ok((data, memo))
+    Ok((compression_type, memo, metadata))
+}
+
+/// Reads the magic number and returns the header version.
+fn read_magic_and_version<R: Read>(reader: &mut R) -> Result<u8, SolverError> {
+    let magic: u32 = decode_from_std_read(reader, "Failed to read magic number")?;
+    if magic != MAGIC {
+        return Err(SolverError::InvalidData(
+            "Magic number is invalid".to_owned(),
+        ));
+    }
+
+    let version: u8 = decode_from_std_read(reader, "Failed to read version number")?;
+    if !(MIN_VERSION..=VERSION).contains(&version) {
+        return Err(SolverError::InvalidData(
+            "Version number is invalid".to_owned(),
+        ));
+    }
+
+    Ok(version)
+}
+
+/// Reads the metadata of a save file without loading the data.
+///
+/// Only the header at the beginning of the file is read, so this is cheap even for large files.
+/// Returns an error if the file was saved by an older version of this crate that did not store
+/// the metadata.
+pub fn read_metadata<P: AsRef<Path>>(path: P) -> Result<SaveMetadata, SolverError> {
+    let file = File::open(path).map_err(|e| SolverError::io("Failed to open file", &e))?;
+    let mut reader = BufReader::new(file);
+
+    let version = read_magic_and_version(&mut reader)?;
+    if version < 2 {
+        return Err(SolverError::InvalidData(
+            "File does not contain metadata".to_owned(),
+        ));
+    }
+
+    let _: u8 = decode_from_std_read(&mut reader, "Failed to read compression type")?;
+    let _: u8 = decode_from_std_read(&mut reader, "Failed to read data type")?;
+    let _: u64 = decode_from_std_read(&mut reader, "Failed to read memory usage")?;
+    let _memo: String = decode_from_std_read(&mut reader, "Failed to read memo")?;
+    decode_from_std_read(&mut reader, "Failed to read metadata")
 }
 
 /// Loads data from a file.
@@ -232,24 +623,265 @@ pub fn load_data_from_std_read<T: FileData, R: Read>(
 pub fn load_data_from_file<T: FileData, P: AsRef<Path>>(
     path: P,
     max_memory_usage: Option<u64>,
-) -> Result<(T, String), String> {
-    let file = File::open(path).map_err(|e| format!("Failed to open file: {e}"))?;
+) -> Result<(T, String), SolverError> {
+    let file = File::open(path).map_err(|e| SolverError::io("Failed to open file", &e))?;
     let mut reader = BufReader::new(file);
     load_data_from_std_read(&mut reader, max_memory_usage)
 }
 
+/// Loads the bunching data of `fold_ranges` on `flop` from the cache directory `cache_dir`, or
+/// computes it and saves it into the directory if it is not cached.
+///
+/// The file in the directory is named after a hash of the fold ranges and the flop, so repeated
+/// solves with the same fold ranges (e.g., over a flop set in multiple runs) compute the bunching
+/// data only once for each flop. The loaded data is verified against the inputs, and a corrupt or
+/// mismatching file is replaced by the recomputed data. The directory is created if it does not
+/// exist.
+///
+/// Returns an error if the inputs are invalid (see [`BunchingData::new`]) or the data cannot be
+/// saved.
+pub fn load_or_process_bunching<P: AsRef<Path>>(
+    fold_ranges: &[Range],
+    flop: [Card; 3],
+    cache_dir: P,
+    print_progress: bool,
+) -> Result<BunchingData, SolverError> {
+    let mut data = BunchingData::new(fold_ranges, flop)?;
+
+    let inputs = (data.fold_ranges(), data.flop());
+    let bytes = bincode::encode_to_vec(inputs, bincode::config::standard())
+        .map_err(|e| SolverError::Other(format!("Failed to encode fold ranges: {e}")))?;
+    let cache_dir = cache_dir.as_ref();
+    let path = cache_dir.join(format!("bunching-{:08x}.bin", crc32fast::hash(&bytes)));
+
+    if let Ok((loaded, _)) = load_data_from_file::<BunchingData, _>(&path, None)
+        && loaded.is_ready()
+        && loaded.fold_ranges() == data.fold_ranges()
+        && loaded.flop() == data.flop()
+    {
+        return Ok(loaded);
+    }
+
+    data.process(print_progress);
+
+    // write into a temporary file first, so that a concurrent reader never sees a partial file
+    std::fs::create_dir_all(cache_dir)
+        .map_err(|e| SolverError::io("Failed to create cache directory", &e))?;
+    let temp_path = path.with_extension("tmp");
+    save_data_to_file(&data, "", &temp_path, None)?;
+    std::fs::rename(&temp_path, &path).map_err(|e| SolverError::io("Failed to rename file", &e))?;
+
+    Ok(data)
+}
+
+/// Loads a game saved by [`save_game_into_std_write_with_structure`] from a standard reader.
+///
+/// The loaded game shares the action tree of `structure`. Returns an error if the game refers to a
+/// different tree structure. Games containing their own tree structure can also be loaded by this
+/// function, in which case `structure` is ignored.
+///
+/// # Returns
+///
+/// A tuple of the loaded game and the memo string.
+pub fn load_game_from_std_read_with_structure<R: Read>(
+    reader: &mut R,
+    structure: &TreeStructure,
+    max_memory_usage: Option<u64>,
+) -> Result<(PostFlopGame, String), SolverError> {
+    with_decode_structure(structure, || {
+        load_data_from_std_read(reader, max_memory_usage)
+    })
+}
+
+/// Loads a game saved by [`save_game_to_file_with_structure`] from a file (see
+/// [`load_game_from_std_read_with_structure`]).
+///
+/// # Returns
+///
+/// A tuple of the loaded game and the memo string.
+pub fn load_game_from_file_with_structure<P: AsRef<Path>>(
+    path: P,
+    structure: &TreeStructure,
+    max_memory_usage: Option<u64>,
+) -> Result<(PostFlopGame, String), SolverError> {
+    let file = File::open(path).map_err(|e| SolverError::io("Failed to open file", &e))?;
+    let mut reader = BufReader::new(file);
+    load_game_from_std_read_with_structure(&mut reader, structure, max_memory_usage)
+}
+
+/// Loads a game from a file, reading only the nodes up to the given street.
+///
+/// The storage of the later streets is skipped, so browsing the early streets of a large save file
+/// does not need the river data resident in memory. The returned game has the storage mode
+/// `street` (or the storage mode of the file if it is lower), and the later streets can be loaded
+/// from the same file on demand with [`load_later_streets`].
+///
+/// If the file was saved with the river storage mode, the counterfactual values of the earlier
+/// streets are not in the file, so the game is loaded as with a strategy-only file (see
+/// `PostFlopGame::set_target_strategy_only`): the strategies can be queried, but the expected
+/// values are only available after loading the river. Files saved in older format versions cannot
+/// be loaded partially.
+///
+/// # Returns
+///
+/// A tuple of the loaded game and the memo string.
+pub fn load_game_from_file_up_to<P: AsRef<Path>>(
+    path: P,
+    street: BoardState,
+) -> Result<(PostFlopGame, String), SolverError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| SolverError::io("Failed to open file", &e))?;
+    let mut reader = BufReader::new(file);
+    let (mut game, memo): (PostFlopGame, _) =
+        with_decode_street(street, || load_data_from_std_read(&mut reader, None))?;
+    game.set_source_path(path.to_path_buf());
+    Ok((game, memo))
+}
+
+/// Loads the nodes up to `street` of a game loaded by [`load_game_from_file_up_to`] from the same
+/// file.
+///
+/// The current navigation state is kept, but the bookmarks taken before are invalidated. Does
+/// nothing if the game already contains the nodes of `street`.
+pub fn load_later_streets(game: &mut PostFlopGame, street: BoardState) -> Result<(), SolverError> {
+    if street <= game.storage_mode() {
+        return Ok(());
+    }
+
+    let Some(path) = game.source_path() else {
+        return Err(SolverError::InvalidState(
+            "Game was not loaded by `load_game_from_file_up_to`".to_owned(),
+        ));
+    };
+
+    let (loaded, _) = load_game_from_file_up_to(path, street)?;
+    if loaded.storage_mode() <= game.storage_mode() {
+        return Err(SolverError::InvalidData(
+            "File does not contain the later streets".to_owned(),
+        ));
+    }
+
+    game.replace_with_later_streets(loaded);
+    Ok(())
+}
+
+/// Loads a game from a standard reader, loading the intact part of a corrupt game.
+///
+/// The save format protects each section of a game (see [`SaveSection`]) with a checksum, and the
+/// other loading functions return an error naming the section if a checksum does not match. This
+/// function instead reports the corrupt sections and loads what remains intact: if a section of a
+/// street is corrupt, the game is truncated to the preceding streets as with
+/// [`load_game_from_file_up_to`], and a corrupt locking strategy is dropped. An error is still
+/// returned if the configuration or the initial street is corrupt, or if the corruption prevents
+/// the data from being parsed. Files saved in older format versions have no checksums, so nothing
+/// is reported for them.
+///
+/// # Returns
+///
+/// A tuple of the loaded game, the memo string, and the corrupt sections (empty if the game is
+/// intact).
+pub fn load_game_from_std_read_recovering<R: Read>(
+    reader: &mut R,
+    max_memory_usage: Option<u64>,
+) -> Result<(PostFlopGame, String, Vec<SaveSection>), SolverError> {
+    let (result, sections) =
+        with_corruption_report(|| load_data_from_std_read(reader, max_memory_usage));
+    let (game, memo) = result?;
+    Ok((game, memo, sections))
+}
+
+/// Loads a game from a file, loading the intact part of a corrupt game (see
+/// [`load_game_from_std_read_recovering`]).
+///
+/// # Returns
+///
+/// A tuple of the loaded game, the memo string, and the corrupt sections (empty if the game is
+/// intact).
+pub fn load_game_from_file_recovering<P: AsRef<Path>>(
+    path: P,
+    max_memory_usage: Option<u64>,
+) -> Result<(PostFlopGame, String, Vec<SaveSection>), SolverError> {
+    let file = File::open(path).map_err(|e| SolverError::io("Failed to open file", &e))?;
+    let mut reader = BufReader::new(file);
+    load_game_from_std_read_recovering(&mut reader, max_memory_usage)
+}
+
+/// Loads a game from a file by memory-mapping it.
+///
+/// Unlike [`load_data_from_file`], the storage buffers are not read into memory: the game refers
+/// to the mapped file, and the operating system reads the pages on demand when the strategies and
+/// the expected values are queried. This makes opening a large save file fast and keeps the
+/// resident memory small. The game can be modified (e.g., solved further after allocating the
+/// memory again), but the changes are never written back to the file.
+///
+/// The checksums of the mapped buffers are not verified, since that would read the whole file.
+/// The file must not be compressed. Only the buffers saved in the file are mapped; if the game was
+/// saved with the river storage mode, the counterfactual values are recomputed on load as in
+/// [`load_data_from_file`], which allocates them on the heap. Only available on Unix with the
+/// `mmap` feature.
+///
+/// # Returns
+///
+/// A tuple of the loaded game and the memo string.
+#[cfg(all(feature = "mmap", unix))]
+pub fn load_game_from_file_mmap<P: AsRef<Path>>(
+    path: P,
+) -> Result<(PostFlopGame, String), SolverError> {
+    let mapping = Arc::new(FileMapping::open(path.as_ref())?);
+
+    let mut reader = mapping.as_slice();
+    let (compression_type, memo, metadata) = read_header::<PostFlopGame, _>(&mut reader, None)?;
+    if compression_type != 0 {
+        return Err(SolverError::InvalidData(
+            "Compressed file cannot be memory-mapped".to_owned(),
+        ));
+    }
+
+    let mut game =
+        PostFlopGame::decode_from_mapping(&mapping, reader).map_err(SolverError::InvalidData)?;
+    if let Some(metadata) = metadata {
+        game.restore_metadata(&metadata);
+    }
+
+    Ok((game, memo))
+}
+
 impl FileData for PostFlopGame {
     fn data_type() -> DataType {
         DataType::Game
     }
 
     fn is_ready_to_save(&self) -> bool {
-        self.is_solved()
+        self.is_solved() && !self.has_strategy_overrides()
     }
 
     fn estimated_memory_usage(&self) -> u64 {
         self.target_memory_usage()
     }
+
+    fn game_metadata(&self) -> Option<GameMetadata> {
+        Some(GameMetadata {
+            card_config: self.card_config().clone(),
+            tree_config: self.tree_config().clone(),
+            isomorphism_mode: self.isomorphism_mode(),
+            is_compression_enabled: self.is_compression_enabled(),
+            is_strategy_quantized: self.is_strategy_quantized(),
+            storage_mode: self.target_storage_mode(),
+            is_strategy_only: self.target_strategy_only(),
+            solve_summary: self.solve_summary(),
+            solve_manifest: self.solve_manifest().cloned(),
+        })
+    }
+
+    fn restore_metadata(&mut self, metadata: &SaveMetadata) {
+        let summary = metadata.game.as_ref().and_then(|game| game.solve_summary);
+        let manifest = metadata
+            .game
+            .as_ref()
+            .and_then(|game| game.solve_manifest.clone());
+        self.set_solve_summary(summary);
+        self.set_solve_manifest(manifest);
+    }
 }
 
 impl FileData for BunchingData {
@@ -264,15 +896,57 @@ impl FileData for BunchingData {
     fn estimated_memory_usage(&self) -> u64 {
         self.memory_usage()
     }
+
+    fn game_metadata(&self) -> Option<GameMetadata> {
+        None
+    }
+
+    fn restore_metadata(&mut self, _metadata: &SaveMetadata) {}
+}
+
+impl FileData for TreeStructure {
+    fn data_type() -> DataType {
+        DataType::TreeStructure
+    }
+
+    fn is_ready_to_save(&self) -> bool {
+        true
+    }
+
+    fn estimated_memory_usage(&self) -> u64 {
+        self.estimated_memory_usage()
+    }
+
+    fn game_metadata(&self) -> Option<GameMetadata> {
+        None
+    }
+
+    fn restore_metadata(&mut self, _metadata: &SaveMetadata) {}
+}
+
+impl FileData for EquityCache {
+    fn data_type() -> DataType {
+        DataType::EquityCache
+    }
+
+    fn is_ready_to_save(&self) -> bool {
+        true
+    }
+
+    fn estimated_memory_usage(&self) -> u64 {
+        self.memory_usage()
+    }
+
+    fn game_metadata(&self) -> Option<GameMetadata> {
+        None
+    }
+
+    fn restore_metadata(&mut self, _metadata: &SaveMetadata) {}
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::action_tree::*;
-    use crate::card::*;
-    use crate::range::*;
-    use crate::utility::*;
 
     #[test]
     fn save_and_load_file() {
@@ -333,6 +1007,259 @@ mod tests {
         assert!((root_ev_ip - 15.0).abs() < 1e-4);
     }
 
+    #[test]
+    fn read_file_metadata() {
+        let card_config = CardConfig {
+            range: ["AA,KK,QQ,87s".parse().unwrap(), "JJ,AK,AQ".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("2c").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 100,
+            effective_stack: 100,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(true);
+        let exploitability = solve(&mut game, 20, 0.0, false);
+
+        let summary = game.solve_summary().unwrap();
+        assert_eq!(summary.num_iterations, 20);
+        assert_eq!(summary.exploitability, exploitability);
+
+        save_data_to_file(&game, "memo", "tmpfile-metadata.flop", None).unwrap();
+        let metadata = read_metadata("tmpfile-metadata.flop").unwrap();
+        assert_eq!(metadata.crate_version, env!("CARGO_PKG_VERSION"));
+        assert!(metadata.saved_at > 0);
+
+        let game_metadata = metadata.game.unwrap();
+        assert_eq!(game_metadata.card_config.range, game.card_config().range);
+        assert_eq!(game_metadata.tree_config.starting_pot, 100);
+        assert!(game_metadata.is_compression_enabled);
+        assert_eq!(game_metadata.storage_mode, BoardState::River);
+        assert_eq!(game_metadata.solve_summary, Some(summary));
+        let manifest = game.solve_manifest().unwrap();
+        assert_eq!(game_metadata.solve_manifest.as_ref(), Some(manifest));
+        assert_eq!(manifest.get("compression"), Some("true"));
+
+        let (loaded, _): (PostFlopGame, _) =
+            load_data_from_file("tmpfile-metadata.flop", None).unwrap();
+        assert_eq!(loaded.solve_summary(), Some(summary));
+        assert_eq!(loaded.solve_manifest(), Some(manifest));
+        std::fs::remove_file("tmpfile-metadata.flop").unwrap();
+
+        // header version 1 has no metadata
+        let mut data = Vec::new();
+        save_data_into_std_write(&game, "memo", &mut data, None).unwrap();
+        let config = bincode::config::standard();
+        let mut pos = 0;
+        pos += bincode::decode_from_slice::<u32, _>(&data[pos..], config)
+            .unwrap()
+            .1;
+        let version_pos = pos;
+        pos += 3;
+        pos += bincode::decode_from_slice::<u64, _>(&data[pos..], config)
+            .unwrap()
+            .1;
+        pos += bincode::decode_from_slice::<String, _>(&data[pos..], config)
+            .unwrap()
+            .1;
+        let metadata_len = bincode::decode_from_slice::<SaveMetadata, _>(&data[pos..], config)
+            .unwrap()
+            .1;
+        data.drain(pos..pos + metadata_len);
+        data[version_pos] = 1;
+
+        let (loaded, memo): (PostFlopGame, _) =
+            load_data_from_std_read(&mut data.as_slice(), None).unwrap();
+        assert_eq!(memo, "memo");
+        assert_eq!(loaded.solve_summary(), None);
+        assert_eq!(loaded.solve_manifest(), None);
+    }
+
+    #[test]
+    fn load_file_up_to_street() {
+        let card_config = CardConfig {
+            range: [
+                "AA,KK,QQ,87s,A5s".parse().unwrap(),
+                "JJ,AK,AQ,KQs".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            starting_pot: 100,
+            effective_stack: 100,
+            flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 10, 0.0, false);
+
+        let history = [0, 0, 40, 1];
+        game.apply_history(&history);
+        game.cache_normalized_weights();
+        let strategy = game.strategy();
+        let expected_values = game.expected_values(1);
+
+        save_data_to_file(&game, "", "tmpfile-street.flop", None).unwrap();
+
+        // flop only
+        let (mut loaded, _) =
+            load_game_from_file_up_to("tmpfile-street.flop", BoardState::Flop).unwrap();
+        assert_eq!(loaded.storage_mode(), BoardState::Flop);
+        assert!(loaded.is_strategy_only());
+        assert!(loaded.target_memory_usage() < game.target_memory_usage());
+        loaded.apply_history(&history[..2]);
+        assert!(loaded.is_chance_node());
+
+        // load the rest on demand, keeping the navigation state
+        load_later_streets(&mut loaded, BoardState::River).unwrap();
+        assert_eq!(loaded.storage_mode(), BoardState::River);
+        assert!(loaded.is_solved());
+        assert_eq!(loaded.history(), &history[..2]);
+        loaded.apply_history(&history);
+        loaded.cache_normalized_weights();
+        assert_eq!(loaded.strategy(), strategy);
+        assert_eq!(loaded.expected_values(1), expected_values);
+
+        // files saved with the turn storage mode keep the expected values of the flop
+        game.back_to_root();
+        game.set_target_storage_mode(BoardState::Turn).unwrap();
+        save_data_to_file(&game, "", "tmpfile-street.flop", None).unwrap();
+        let (mut loaded, _) =
+            load_game_from_file_up_to("tmpfile-street.flop", BoardState::Flop).unwrap();
+        assert!(loaded.is_solved());
+        game.cache_normalized_weights();
+        loaded.cache_normalized_weights();
+        assert_eq!(loaded.strategy(), game.strategy());
+        assert_eq!(loaded.expected_values(0), game.expected_values(0));
+        load_later_streets(&mut loaded, BoardState::River).unwrap();
+        assert_eq!(loaded.storage_mode(), BoardState::Turn);
+
+        std::fs::remove_file("tmpfile-street.flop").unwrap();
+    }
+
+    #[test]
+    fn checkpoint_while_solving() {
+        let card_config = CardConfig {
+            range: ["AA,KK,QQ,87s".parse().unwrap(), "JJ,AK,AQ".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("2c").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 100,
+            effective_stack: 100,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+
+        // the game must be in the middle of solving
+        let mut solved = PostFlopGame::new();
+        save_checkpoint(&mut solved, "", "tmpfile-checkpoint.flop", None).unwrap_err();
+
+        for t in 0..5 {
+            solve_step(&game, t);
+        }
+        game.cache_normalized_weights();
+        let strategy = game.strategy();
+
+        save_checkpoint(&mut game, "5", "tmpfile-checkpoint.flop", None).unwrap();
+        assert!(game.is_ready());
+        assert!(!game.target_strategy_only());
+
+        let (mut loaded, memo): (PostFlopGame, _) =
+            load_data_from_file("tmpfile-checkpoint.flop", None).unwrap();
+        assert_eq!(memo, "5");
+        assert!(loaded.is_strategy_only());
+        loaded.cache_normalized_weights();
+        assert_eq!(loaded.strategy(), strategy);
+
+        // the solving can be continued after saving a snapshot
+        game.back_to_root();
+        solve_with_checkpoints(&mut game, 20, 0.0, false, "tmpfile-checkpoint.flop", 8).unwrap();
+        assert!(game.is_solved());
+        let (_, memo): (PostFlopGame, _) =
+            load_data_from_file("tmpfile-checkpoint.flop", None).unwrap();
+        assert_eq!(memo, "16");
+
+        std::fs::remove_file("tmpfile-checkpoint.flop").unwrap();
+    }
+
+    #[test]
+    fn resumable_checkpoint() {
+        let card_config = CardConfig {
+            range: ["AA,KK,QQ,87s".parse().unwrap(), "JJ,AK,AQ".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("2c").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 100,
+            effective_stack: 100,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+
+        let mut unready = PostFlopGame::new();
+        save_resumable_checkpoint(&mut unready, "", "tmpfile-resumable.flop").unwrap_err();
+
+        for t in 0..5 {
+            solve_step(&game, t);
+        }
+        save_resumable_checkpoint(&mut game, "5", "tmpfile-resumable.flop").unwrap();
+        let (mut resumed, memo) =
+            load_resumable_checkpoint("tmpfile-resumable.flop", None).unwrap();
+        assert_eq!(memo, "5");
+        assert!(resumed.is_ready());
+
+        // the resumed solving produces the same result as the uninterrupted one
+        for t in 5..10 {
+            solve_step(&game, t);
+            solve_step(&resumed, t);
+        }
+
+        // strategy-only snapshots cannot be resumed
+        save_checkpoint(&mut game, "", "tmpfile-resumable.flop", None).unwrap();
+        assert!(load_resumable_checkpoint("tmpfile-resumable.flop", None).is_err());
+
+        finalize(&mut game);
+        finalize(&mut resumed);
+        game.cache_normalized_weights();
+        resumed.cache_normalized_weights();
+        assert_eq!(resumed.strategy(), game.strategy());
+        assert_eq!(resumed.expected_values(0), game.expected_values(0));
+
+        std::fs::remove_file("tmpfile-resumable.flop").unwrap();
+    }
+
     #[test]
     #[cfg(feature = "zstd")]
     fn save_and_load_file_compressed() {
@@ -356,6 +1283,12 @@ mod tests {
         game.allocate_memory(false);
         finalize(&mut game);
 
+        // invalid levels are rejected
+        let range = zstd_compression_level_range();
+        let mut buffer = Vec::new();
+        assert!(save_data_into_std_write(&game, "", &mut buffer, Some(range.end() + 1)).is_err());
+        assert!(buffer.is_empty());
+
         // save
         save_data_to_file(&game, "", "tmpfile-zstd.flop", Some(3)).unwrap();
 
@@ -378,4 +1311,123 @@ mod tests {
         assert!((root_ev_oop - 45.0).abs() < 1e-4);
         assert!((root_ev_ip - 15.0).abs() < 1e-4);
     }
+
+    #[test]
+    fn save_and_load_with_structure() {
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 100,
+            effective_stack: 100,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let mut games = Vec::new();
+        for flop in ["Td9d6h", "QsJh2h"] {
+            let card_config = CardConfig {
+                range: ["AA,KK,QQ".parse().unwrap(), "JJ,AK,AQ".parse().unwrap()],
+                flop: flop_from_str(flop).unwrap(),
+                turn: card_from_str("3d").unwrap(),
+                ..Default::default()
+            };
+            let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+            let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+            game.allocate_memory(false);
+            finalize(&mut game);
+            games.push(game);
+        }
+
+        let structure = TreeStructure::from_game(&games[0]).unwrap();
+        assert_eq!(
+            TreeStructure::from_game(&games[1]).unwrap().id(),
+            structure.id()
+        );
+
+        let mut structure_buffer = Vec::new();
+        save_data_into_std_write(&structure, "", &mut structure_buffer, None).unwrap();
+        let (structure, _): (TreeStructure, _) =
+            load_data_from_std_read(&mut structure_buffer.as_slice(), None).unwrap();
+
+        for game in &mut games {
+            let mut full = Vec::new();
+            save_data_into_std_write(game, "", &mut full, None).unwrap();
+            let mut buffer = Vec::new();
+            save_game_into_std_write_with_structure(game, &structure, "memo", &mut buffer, None)
+                .unwrap();
+            assert!(buffer.len() < full.len());
+
+            // the structure is required
+            let result: Result<(PostFlopGame, _), _> =
+                load_data_from_std_read(&mut buffer.as_slice(), None);
+            assert!(result.is_err());
+
+            let (mut loaded, memo) =
+                load_game_from_std_read_with_structure(&mut buffer.as_slice(), &structure, None)
+                    .unwrap();
+            assert_eq!(memo, "memo");
+            game.cache_normalized_weights();
+            loaded.cache_normalized_weights();
+            assert_eq!(loaded.expected_values(0), game.expected_values(0));
+        }
+
+        // different structure
+        let other_config = TreeConfig {
+            effective_stack: 200,
+            ..tree_config
+        };
+        let card_config = games[0].card_config().clone();
+        let action_tree = ActionTree::new(other_config).unwrap();
+        let mut other = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        other.allocate_memory(false);
+        finalize(&mut other);
+        let other_structure = TreeStructure::from_game(&other).unwrap();
+        assert_ne!(other_structure.id(), structure.id());
+
+        let mut buffer = Vec::new();
+        let result =
+            save_game_into_std_write_with_structure(&other, &structure, "", &mut buffer, None);
+        assert!(result.is_err());
+
+        let mut buffer = Vec::new();
+        save_game_into_std_write_with_structure(&other, &other_structure, "", &mut buffer, None)
+            .unwrap();
+        let result =
+            load_game_from_std_read_with_structure(&mut buffer.as_slice(), &structure, None);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn bunching_cache_dir() {
+        let dir = "tmpdir-bunching";
+        let fold_ranges = ["22+,A2s+,K9s+,ATo+".parse().unwrap(), Range::new()];
+        let flop = flop_from_str("Td9d6h").unwrap();
+
+        let computed = load_or_process_bunching(&fold_ranges, flop, dir, false).unwrap();
+        assert!(computed.is_ready());
+        let files = std::fs::read_dir(dir).unwrap().collect::<Vec<_>>();
+        assert_eq!(files.len(), 1);
+        let path = files[0].as_ref().unwrap().path();
+
+        // the cached data is loaded, and the order of the flop cards does not matter
+        let reversed = [flop[2], flop[1], flop[0]];
+        let loaded = load_or_process_bunching(&fold_ranges, reversed, dir, false).unwrap();
+        let mask = cards_from_str("AsKsQsJs")
+            .unwrap()
+            .iter()
+            .map(|&c| 1 << c)
+            .sum();
+        assert_eq!(loaded.result_4cards(mask), computed.result_4cards(mask));
+        assert_eq!(std::fs::read_dir(dir).unwrap().count(), 1);
+
+        // a corrupt file is replaced
+        std::fs::write(&path, b"corrupt").unwrap();
+        let recomputed = load_or_process_bunching(&fold_ranges, flop, dir, false).unwrap();
+        assert_eq!(recomputed.result_4cards(mask), computed.result_4cards(mask));
+        let (saved, _): (BunchingData, _) = load_data_from_file(&path, None).unwrap();
+        assert!(saved.is_ready());
+
+        assert!(load_or_process_bunching(&[Range::new()], flop, dir, false).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
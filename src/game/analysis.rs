@@ -0,0 +1,1225 @@
+use super::walk::*;
+use super::*;
+use crate::hand_class::*;
+use crate::interface::*;
+use crate::texture::*;
+use crate::utility::*;
+use std::collections::HashMap;
+
+/// Strategy and expected values of the hands in a [`HandClass`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandClassStats {
+    /// Hand class.
+    pub class: HandClass,
+
+    /// Number of private hands in the class.
+    pub num_hands: usize,
+
+    /// Sum of the normalized weights of the hands in the class.
+    pub weight: f32,
+
+    /// Proportion of the class in the range of the current player (weighted by normalized
+    /// weights).
+    pub share: f32,
+
+    /// Frequency of each action.
+    pub frequencies: Vec<f32>,
+
+    /// Average expected value of the class.
+    pub ev: f32,
+
+    /// Average expected value of each action. If an action is never taken by the class, the value
+    /// is `NaN`.
+    pub action_ev: Vec<f32>,
+}
+
+/// Strategy and expected values of the hands in an equity bucket.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquityBucketStats {
+    /// Index of the bucket (0 = lowest equity).
+    pub bucket: usize,
+
+    /// Lowest equity of the hands in the bucket.
+    pub min_equity: f32,
+
+    /// Highest equity of the hands in the bucket.
+    pub max_equity: f32,
+
+    /// Number of private hands in the bucket.
+    pub num_hands: usize,
+
+    /// Sum of the normalized weights of the hands in the bucket.
+    pub weight: f32,
+
+    /// Proportion of the bucket in the range of the current player (weighted by normalized
+    /// weights).
+    pub share: f32,
+
+    /// Frequency of each action.
+    pub frequencies: Vec<f32>,
+
+    /// Average expected value of the bucket.
+    pub ev: f32,
+
+    /// Average expected value of each action. If an action is never taken by the bucket, the value
+    /// is `NaN`.
+    pub action_ev: Vec<f32>,
+}
+
+/// Expected value of each action of a private hand and the gap to the best action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComboEvGap {
+    /// Private hand.
+    pub hand: (Card, Card),
+
+    /// Index of the private hand in [`PostFlopGame::private_cards`].
+    pub hand_index: usize,
+
+    /// Probability of each action.
+    pub strategy: Vec<f32>,
+
+    /// Expected value of each action.
+    pub action_ev: Vec<f32>,
+
+    /// Index of the action with the highest expected value.
+    pub best_action: usize,
+
+    /// Difference between the expected value of the best action and that of each action (i.e.,
+    /// always non-negative, and zero for the best action).
+    pub gaps: Vec<f32>,
+
+    /// Whether two or more actions are within the tolerance of the best action.
+    pub is_indifferent: bool,
+}
+
+/// Strategy and expected values after the turn or river cards of a [`RunoutClass`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunoutClassStats {
+    /// Runout class.
+    pub class: RunoutClass,
+
+    /// Cards in the class.
+    pub cards: Vec<Card>,
+
+    /// Probability that a card in the class is dealt.
+    pub probability: f32,
+
+    /// Overall frequency of each action at the node after the card is dealt.
+    pub frequencies: Vec<f32>,
+
+    /// Average expected value of each player (OOP, IP) after the card is dealt.
+    pub ev: [f32; 2],
+
+    /// Frequency shift of each action compared to the average over all cards.
+    pub frequency_shift: Vec<f32>,
+
+    /// Expected value shift of each player compared to the average over all cards.
+    pub ev_shift: [f32; 2],
+}
+
+/// How mixed the strategy of the current player is, returned by [`PostFlopGame::mixing_stats`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixingStats {
+    /// Normalized entropy of the strategy of each private hand, in the range of \[0, 1\] (0 =
+    /// pure, 1 = uniform over all actions). Hands with zero normalized weight have zero entropy.
+    pub entropy: Vec<f32>,
+
+    /// Average normalized entropy weighted by the normalized weights.
+    pub average_entropy: f32,
+
+    /// Share of the range (by normalized weights) whose most frequent action has a frequency less
+    /// than the given threshold.
+    pub mixed_share: f32,
+
+    /// Average expected value lost if every hand plays its most frequent action purely, weighted
+    /// by the normalized weights. `None` if the game is not solved.
+    pub purification_ev_loss: Option<f32>,
+}
+
+/// Result after a single card used in [`PostFlopGame::runout_class_report`].
+struct RunoutItem {
+    class: RunoutClass,
+    card: Card,
+    reach: f64,
+    frequencies: Vec<f32>,
+    ev: [f32; 2],
+}
+
+/// Range advantage and nut advantage between the two players, returned by
+/// [`PostFlopGame::range_advantage`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RangeAdvantage {
+    /// Average equity of each player (OOP, IP).
+    pub equity: [f32; 2],
+
+    /// Equity advantage of OOP over IP, i.e., `equity[0] - equity[1]`.
+    pub equity_advantage: f32,
+
+    /// Number of nut combos of each player (OOP, IP), weighted by the normalized weights.
+    pub nut_combos: [f32; 2],
+
+    /// Share of nut combos in the range of each player (OOP, IP).
+    pub nut_share: [f32; 2],
+
+    /// Nut advantage of OOP over IP, i.e., `nut_share[0] - nut_share[1]`.
+    pub nut_advantage: f32,
+}
+
+/// Break-even numbers of a bet or raise action, returned as a part of [`PotOddsInfo`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BetOdds {
+    /// Index of the action.
+    pub action_index: usize,
+
+    /// The action.
+    pub action: Action,
+
+    /// Chips put into the pot by the action (including the amount to call).
+    pub amount: i32,
+
+    /// Folding frequency of the opponent required for a pure bluff to break even, i.e.,
+    /// `amount / (pot + amount)`.
+    pub break_even_fold: f32,
+
+    /// Share of bluffs in the betting range that makes the opponent indifferent to calling, i.e.,
+    /// the pot odds offered to the opponent. The bluff-to-value ratio is
+    /// `bluff_share / (1 - bluff_share)`.
+    pub bluff_share: f32,
+}
+
+/// Pot odds and defense frequencies at a player node, returned by [`PostFlopGame::pot_odds`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PotOddsInfo {
+    /// Current pot size, including the bets of both players.
+    pub pot: i32,
+
+    /// Amount the current player has to call. Zero if the player is not facing a bet.
+    pub to_call: i32,
+
+    /// Pot odds of calling, i.e., the equity required to break even: `to_call / (pot +
+    /// to_call)`. `None` if the player is not facing a bet.
+    pub pot_odds: Option<f32>,
+
+    /// Minimum defense frequency, i.e., `1 - to_call / pot`: the frequency at which the player
+    /// must continue so that a pure bluff of the opponent does not immediately profit. `None` if
+    /// the player is not facing a bet.
+    pub minimum_defense_frequency: Option<f32>,
+
+    /// Defense frequency of the solved strategy (one minus the fold frequency). `None` if the
+    /// player is not facing a bet.
+    pub defense_frequency: Option<f32>,
+
+    /// Overall frequency of each action (see [`PostFlopGame::action_frequencies`]).
+    pub frequencies: Vec<f32>,
+
+    /// Break-even numbers of each bet and raise action.
+    pub bets: Vec<BetOdds>,
+}
+
+/// Aggregate action frequencies of a player on a street.
+///
+/// All-in actions are counted as bets if the player can check and as raises otherwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreetSummary {
+    /// Player (0 = OOP, 1 = IP).
+    pub player: usize,
+
+    /// Street.
+    pub street: BoardState,
+
+    /// Expected number of decisions of the player on the street (i.e., sum of the reach
+    /// probabilities of the decision nodes).
+    pub decisions: f32,
+
+    /// Fold frequency.
+    pub fold: f32,
+
+    /// Check frequency.
+    pub check: f32,
+
+    /// Call frequency.
+    pub call: f32,
+
+    /// Bet frequency.
+    pub bet: f32,
+
+    /// Raise frequency.
+    pub raise: f32,
+}
+
+/// Contribution of a line to the expected value of a private hand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineContribution {
+    /// Actions from the current node to the terminal node. Dealt cards are represented as
+    /// [`Action::Chance`] unless runouts are merged.
+    pub line: Vec<Action>,
+
+    /// Probability that the line is taken given the private hand.
+    pub probability: f32,
+
+    /// Expected value of the private hand at the end of the line, measured from the current node
+    /// (i.e., the amount bet along the line is subtracted so that it is comparable to
+    /// [`PostFlopGame::expected_values`] at the current node).
+    pub ev: f32,
+
+    /// Contribution to the expected value (i.e., `probability * ev`).
+    pub contribution: f32,
+}
+
+struct BucketSummary {
+    num_hands: usize,
+    weight: f32,
+    share: f32,
+    frequencies: Vec<f32>,
+    ev: f32,
+    action_ev: Vec<f32>,
+}
+
+impl PostFlopGame {
+    /// Returns the pot size at the current node.
+    #[inline]
+    pub(super) fn current_pot(&self) -> i32 {
+        let total_bet_amount = self.total_bet_amount();
+        self.tree_config.starting_pot + total_bet_amount[0] + total_bet_amount[1]
+    }
+
+    /// Returns the index of the given hole cards (in any order) in the private hands of `player`.
+    #[inline]
+    pub(super) fn private_hand_index(&self, player: usize, hand: (Card, Card)) -> Option<usize> {
+        let (c1, c2) = (hand.0.min(hand.1), hand.0.max(hand.1));
+        self.private_cards[player]
+            .iter()
+            .position(|&(h1, h2)| (h1.min(h2), h1.max(h2)) == (c1, c2))
+    }
+
+    /// Returns the equity realization (EQR) of each private hand of the given player.
+    ///
+    /// The equity realization is the expected value divided by the share of the current pot
+    /// implied by the raw equity, i.e., `EV / (equity * pot)`. A value greater than 1 means that
+    /// the hand realizes more than its equity. If the normalized weight or the equity of a hand is
+    /// zero, `NaN` is returned for the hand.
+    ///
+    /// Panics if the game is not solved.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// **Time complexity:** see [`equity`] and [`expected_values`].
+    ///
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    /// [`equity`]: #method.equity
+    /// [`expected_values`]: #method.expected_values
+    pub fn equity_realization(&self, player: usize) -> Vec<f32> {
+        let pot = self.current_pot() as f32;
+        let equity = self.equity(player);
+        let expected_values = self.expected_values(player);
+        let weights = self.normalized_weights(player);
+
+        equity
+            .iter()
+            .zip(&expected_values)
+            .zip(weights)
+            .map(|((&eq, &ev), &w)| {
+                if w > 0.0 && eq > 0.0 {
+                    ev / (eq * pot)
+                } else {
+                    f32::NAN
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the equity realization (EQR) of the whole range of the given player.
+    ///
+    /// The range EQR is the average expected value divided by the average equity times the
+    /// current pot, where both averages are weighted by the normalized weights. If the player has
+    /// no reaching hand or the average equity is zero, `NaN` is returned.
+    ///
+    /// Panics if the game is not solved.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// **Time complexity:** see [`equity`] and [`expected_values`].
+    ///
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    /// [`equity`]: #method.equity
+    /// [`expected_values`]: #method.expected_values
+    pub fn range_equity_realization(&self, player: usize) -> f32 {
+        let weights = self.normalized_weights(player);
+        let equity = compute_average(&self.equity(player), weights);
+        let ev = compute_average(&self.expected_values(player), weights);
+        if equity > 0.0 {
+            ev / (equity * self.current_pot() as f32)
+        } else {
+            f32::NAN
+        }
+    }
+
+    /// Returns the overall frequency of each action of the current player.
+    ///
+    /// The frequencies are weighted by the normalized weights. If no hand of the current player
+    /// reaches the current node, `NaN` is returned for all actions.
+    ///
+    /// Panics if the memory is not yet allocated or the current node is a terminal node or a chance
+    /// node.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// **Time complexity:** *O*(#(actions) * #(private hands)).
+    ///
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    pub fn action_frequencies(&self) -> Vec<f32> {
+        assert!(
+            self.state >= State::MemoryAllocated,
+            "Memory is not allocated"
+        );
+        assert!(!self.is_terminal_node(), "Terminal node is not allowed");
+        assert!(!self.is_chance_node(), "Chance node is not allowed");
+        assert!(
+            self.is_normalized_weight_cached,
+            "Normalized weights are not cached"
+        );
+
+        let weights = self.normalized_weights(self.current_player());
+        let strategy = self.strategy();
+        let weight_sum = weights.iter().fold(0.0, |acc, &w| acc + w as f64);
+
+        strategy
+            .chunks_exact(weights.len())
+            .map(|row| {
+                let freq_sum = row
+                    .iter()
+                    .zip(weights)
+                    .fold(0.0, |acc, (&s, &w)| acc + s as f64 * w as f64);
+                (freq_sum / weight_sum) as f32
+            })
+            .collect()
+    }
+
+    /// Returns the equity advantage and the nut advantage between the reach ranges of the two
+    /// players at the current node.
+    ///
+    /// A private hand is counted as a nut combo if its equity is at least `nut_threshold` (e.g.,
+    /// `0.9`). Positive advantages favor OOP and negative ones favor IP.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// **Time complexity:** see [`equity`].
+    ///
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    /// [`equity`]: #method.equity
+    pub fn range_advantage(&self, nut_threshold: f32) -> RangeAdvantage {
+        let mut equity = [0.0; 2];
+        let mut nut_combos = [0.0; 2];
+        let mut nut_share = [0.0; 2];
+
+        for player in 0..2 {
+            let weights = self.normalized_weights(player);
+            let player_equity = self.equity(player);
+            let weight_sum = weights.iter().fold(0.0, |acc, &w| acc + w as f64);
+            let nut_sum = player_equity
+                .iter()
+                .zip(weights)
+                .filter(|&(&eq, _)| eq >= nut_threshold)
+                .fold(0.0, |acc, (_, &w)| acc + w as f64);
+
+            equity[player] = compute_average(&player_equity, weights);
+            nut_combos[player] = nut_sum as f32;
+            nut_share[player] = (nut_sum / weight_sum) as f32;
+        }
+
+        RangeAdvantage {
+            equity,
+            equity_advantage: equity[0] - equity[1],
+            nut_combos,
+            nut_share,
+            nut_advantage: nut_share[0] - nut_share[1],
+        }
+    }
+
+    /// Returns the pot odds, the minimum defense frequency, and the break-even numbers of each bet
+    /// size at the current node, alongside the overall action frequencies.
+    ///
+    /// Panics if the memory is not yet allocated or the current node is a terminal node or a chance
+    /// node.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// **Time complexity:** *O*(#(actions) * #(private hands)).
+    ///
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    pub fn pot_odds(&self) -> PotOddsInfo {
+        let frequencies = self.action_frequencies();
+        let actions = self.available_actions();
+
+        let player = self.current_player();
+        let total_bet_amount = self.total_bet_amount();
+        let pot = self.current_pot();
+        let to_call = total_bet_amount[player ^ 1] - total_bet_amount[player];
+
+        let prev_amount = match self.node().prev_action {
+            Action::Bet(amount) | Action::Raise(amount) | Action::AllIn(amount) => amount,
+            _ => 0,
+        };
+
+        let is_facing_bet = to_call > 0;
+        let fold_frequency = actions
+            .iter()
+            .position(|&action| action == Action::Fold)
+            .map_or(0.0, |index| frequencies[index]);
+
+        let bets = actions
+            .iter()
+            .enumerate()
+            .filter_map(|(action_index, &action)| match action {
+                Action::Bet(to) | Action::Raise(to) | Action::AllIn(to) => {
+                    let opponent_to_call = to - prev_amount;
+                    let amount = to_call + opponent_to_call;
+                    let pot_after = (pot + amount) as f32;
+                    Some(BetOdds {
+                        action_index,
+                        action,
+                        amount,
+                        break_even_fold: amount as f32 / pot_after,
+                        bluff_share: opponent_to_call as f32
+                            / (pot_after + opponent_to_call as f32),
+                    })
+                }
+                _ => None,
+            })
+            .collect();
+
+        PotOddsInfo {
+            pot,
+            to_call,
+            pot_odds: is_facing_bet.then(|| to_call as f32 / (pot + to_call) as f32),
+            minimum_defense_frequency: is_facing_bet.then(|| 1.0 - to_call as f32 / pot as f32),
+            defense_frequency: is_facing_bet.then_some(1.0 - fold_frequency),
+            frequencies,
+            bets,
+        }
+    }
+
+    /// Returns the entropy and mixing metrics of the strategy of the current player.
+    ///
+    /// The entropy of each hand is normalized by `ln(#(actions))`. A hand is counted as mixed if
+    /// the frequency of its most frequent action is less than `pure_threshold` (e.g., `0.9`). The
+    /// purification EV loss tells how much the player loses if the strategy is simplified to pure
+    /// actions at this node only; a nearly zero value means the simplification is nearly free.
+    ///
+    /// Panics if the memory is not yet allocated or the current node is a terminal node or a chance
+    /// node.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// **Time complexity:** *O*(#(actions) * #(private hands)).
+    ///
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    pub fn mixing_stats(&self, pure_threshold: f32) -> MixingStats {
+        assert!(
+            self.state >= State::MemoryAllocated,
+            "Memory is not allocated"
+        );
+        assert!(!self.is_terminal_node(), "Terminal node is not allowed");
+        assert!(!self.is_chance_node(), "Chance node is not allowed");
+
+        let player = self.current_player();
+        let weights = self.normalized_weights(player);
+        let strategy = self.strategy();
+        let num_hands = weights.len();
+        let num_actions = strategy.len() / num_hands;
+        let ev_detail = self
+            .is_solved()
+            .then(|| self.expected_values_detail(player));
+
+        let log_num_actions = (num_actions as f64).ln();
+        let mut entropy = vec![0.0; num_hands];
+        let mut weight_sum = 0.0;
+        let mut entropy_sum = 0.0;
+        let mut mixed_sum = 0.0;
+        let mut loss_sum = 0.0;
+
+        for hand in 0..num_hands {
+            let w = weights[hand] as f64;
+            if w <= 0.0 {
+                continue;
+            }
+
+            let column = (0..num_actions).map(|action| strategy[action * num_hands + hand]);
+            let h = column
+                .clone()
+                .filter(|&p| p > 0.0)
+                .fold(0.0, |acc, p| acc - p as f64 * (p as f64).ln());
+            let h = if num_actions > 1 {
+                h / log_num_actions
+            } else {
+                0.0
+            };
+            entropy[hand] = h as f32;
+
+            let (top_action, top_frequency) = column
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+
+            weight_sum += w;
+            entropy_sum += w * h;
+            if top_frequency < pure_threshold {
+                mixed_sum += w;
+            }
+
+            if let Some(ev_detail) = &ev_detail {
+                let mixed_ev = (0..num_actions).fold(0.0, |acc, action| {
+                    let index = action * num_hands + hand;
+                    acc + strategy[index] as f64 * ev_detail[index] as f64
+                });
+                let pure_ev = ev_detail[top_action * num_hands + hand] as f64;
+                loss_sum += w * (mixed_ev - pure_ev);
+            }
+        }
+
+        MixingStats {
+            entropy,
+            average_entropy: (entropy_sum / weight_sum) as f32,
+            mixed_share: (mixed_sum / weight_sum) as f32,
+            purification_ev_loss: ev_detail.map(|_| (loss_sum / weight_sum) as f32),
+        }
+    }
+
+    /// Returns the expected value of each action and the gap to the best action for each private
+    /// hand of the current player.
+    ///
+    /// A hand is flagged as (near-)indifferent if two or more actions have expected values within
+    /// `tolerance` of the best action. Since mixed strategies only arise between indifferent
+    /// actions at equilibrium, a hand that mixes without being flagged indicates an insufficiently
+    /// converged solution (or a locked node). Hands with zero normalized weight are omitted.
+    ///
+    /// Panics if the game is not solved or the current node is a terminal node or a chance node.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// **Time complexity:** *O*(#(actions) * #(private hands)).
+    ///
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    pub fn ev_gaps(&self, tolerance: f32) -> Vec<ComboEvGap> {
+        assert!(self.state == State::Solved, "Game is not solved");
+        assert!(!self.is_terminal_node(), "Terminal node is not allowed");
+        assert!(!self.is_chance_node(), "Chance node is not allowed");
+        assert!(
+            self.is_normalized_weight_cached,
+            "Normalized weights are not cached"
+        );
+
+        let player = self.current_player();
+        let num_actions = self.available_actions().len();
+        let num_hands = self.num_private_hands(player);
+
+        let weights = self.normalized_weights(player);
+        let strategy = self.strategy();
+        let ev_detail = self.expected_values_detail(player);
+
+        (0..num_hands)
+            .filter(|&hand| weights[hand] > 0.0)
+            .map(|hand| {
+                let column = |values: &[f32]| {
+                    (0..num_actions)
+                        .map(|action| values[action * num_hands + hand])
+                        .collect::<Vec<_>>()
+                };
+
+                let action_ev = column(&ev_detail);
+                let best_action = (0..num_actions)
+                    .max_by(|&i, &j| action_ev[i].total_cmp(&action_ev[j]))
+                    .unwrap();
+                let best_ev = action_ev[best_action];
+                let gaps = action_ev.iter().map(|&ev| best_ev - ev).collect::<Vec<_>>();
+                let num_close = gaps.iter().filter(|&&gap| gap <= tolerance).count();
+
+                ComboEvGap {
+                    hand: self.private_cards[player][hand],
+                    hand_index: hand,
+                    strategy: column(&strategy),
+                    action_ev,
+                    best_action,
+                    gaps,
+                    is_indifferent: num_close >= 2,
+                }
+            })
+            .collect()
+    }
+
+    /// Groups the cards that can be dealt at the current chance node by [`classify_runout`] and
+    /// returns the strategy and the expected values after each class of cards.
+    ///
+    /// For each possible card, the overall action frequencies of the player to act next and the
+    /// average expected values of both players are computed, and they are averaged in each class
+    /// (weighted by the probability of each card). Empty classes are omitted. If all the cards in
+    /// a class are unreachable, the frequencies and the expected values of the class are `NaN`.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the game is not solved, the current node is not a
+    /// chance node, or the current node is unreachable. The current node is restored after the
+    /// call.
+    ///
+    /// **Time complexity:** *O*(#(possible cards) * #(depth) * (#(OOP private hands) + #(IP
+    /// private hands))).
+    pub fn runout_class_report(&mut self) -> Result<Vec<RunoutClassStats>, SolverError> {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+        if !self.is_chance_node() {
+            return Err(SolverError::InvalidState(
+                "Current node is not a chance node".to_owned(),
+            ));
+        }
+
+        let board = self.current_board();
+        let possible_cards = self.possible_cards();
+        let mut history = self.history().to_vec();
+        let num_actions = {
+            history.push(possible_cards.trailing_zeros() as usize);
+            self.apply_history(&history);
+            history.pop();
+            self.available_actions().len()
+        };
+
+        let mut items = Vec::new();
+        for card in 0..52 {
+            if possible_cards & (1 << card) == 0 {
+                continue;
+            }
+
+            history.push(card);
+            self.apply_history(&history);
+            history.pop();
+            self.cache_normalized_weights();
+
+            let player = self.current_player();
+            let weights = self.normalized_weights(player);
+            let reach = weights.iter().fold(0.0, |acc, &w| acc + w as f64);
+            let ev = [0, 1]
+                .map(|p| compute_average(&self.expected_values(p), self.normalized_weights(p)));
+            let class = classify_runout(&board, card as Card);
+            items.push(RunoutItem {
+                class,
+                card: card as Card,
+                reach,
+                frequencies: self.action_frequencies(),
+                ev,
+            });
+        }
+
+        self.apply_history(&history);
+
+        let summarize = |items: &[&RunoutItem]| {
+            let total_reach = items.iter().map(|item| item.reach).sum::<f64>();
+            let init = if total_reach > 0.0 { 0.0 } else { f64::NAN };
+            let mut frequencies = vec![init; num_actions];
+            let mut ev = [init; 2];
+            for item in items.iter().filter(|item| item.reach > 0.0) {
+                let w = item.reach / total_reach;
+                frequencies
+                    .iter_mut()
+                    .zip(&item.frequencies)
+                    .for_each(|(dst, &f)| *dst += w * f as f64);
+                ev[0] += w * item.ev[0] as f64;
+                ev[1] += w * item.ev[1] as f64;
+            }
+            (
+                total_reach,
+                frequencies.iter().map(|&f| f as f32).collect::<Vec<_>>(),
+                ev.map(|v| v as f32),
+            )
+        };
+
+        let all_items = items.iter().collect::<Vec<_>>();
+        let (total_reach, overall_frequencies, overall_ev) = summarize(&all_items);
+        if total_reach == 0.0 {
+            return Err(SolverError::InvalidState(
+                "Current node is unreachable".to_owned(),
+            ));
+        }
+
+        let result = RunoutClass::ALL
+            .iter()
+            .filter_map(|&class| {
+                let class_items = items
+                    .iter()
+                    .filter(|item| item.class == class)
+                    .collect::<Vec<_>>();
+                if class_items.is_empty() {
+                    return None;
+                }
+                let (reach, frequencies, ev) = summarize(&class_items);
+                Some(RunoutClassStats {
+                    class,
+                    cards: class_items.iter().map(|item| item.card).collect(),
+                    probability: (reach / total_reach) as f32,
+                    frequency_shift: frequencies
+                        .iter()
+                        .zip(&overall_frequencies)
+                        .map(|(&f, &o)| f - o)
+                        .collect(),
+                    frequencies,
+                    ev_shift: [ev[0] - overall_ev[0], ev[1] - overall_ev[1]],
+                    ev,
+                })
+            })
+            .collect();
+
+        Ok(result)
+    }
+
+    /// Returns the aggregate fold/check/call/bet/raise frequencies of each player by street.
+    ///
+    /// Every player node of the tree is visited, and its action frequencies are weighted by the
+    /// probability of reaching the node (taking the strategies of both players and the card removal
+    /// into account). The frequencies are then normalized by the total reach of the decision nodes
+    /// of each player and street. Pairs of a player and a street without decisions are omitted; the
+    /// rest are sorted by street and then by player.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the memory is not allocated. The current node is
+    /// restored after the call.
+    ///
+    /// **Time complexity:** *O*(#(nodes) * #(depth) * (#(OOP private hands) + #(IP private
+    /// hands))). This method can be slow for a whole flop tree.
+    pub fn street_frequency_summary(&mut self) -> Result<Vec<StreetSummary>, SolverError> {
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+
+        let root_reach = self.root_reach();
+
+        // [street][player][fold, check, call, bet, raise, total]
+        let mut sums = [[[0.0f64; 6]; 2]; 3];
+
+        self.walk_player_nodes(&[], None, &mut |game, _, _| {
+            game.cache_normalized_weights();
+            let player = game.current_player();
+            let reach = game.current_reach(root_reach);
+            if reach <= 0.0 {
+                return Ok(());
+            }
+
+            let street = street_of_board(&game.current_board()) as usize;
+            let actions = game.available_actions();
+            let can_check = actions.contains(&Action::Check);
+            let sums = &mut sums[street][player];
+
+            for (action, freq) in actions.iter().zip(game.action_frequencies()) {
+                let kind = match action {
+                    Action::Fold => 0,
+                    Action::Check => 1,
+                    Action::Call => 2,
+                    Action::Bet(_) => 3,
+                    Action::AllIn(_) if can_check => 3,
+                    _ => 4,
+                };
+                sums[kind] += reach * freq as f64;
+            }
+            sums[5] += reach;
+
+            Ok(())
+        })?;
+
+        let mut result = Vec::new();
+        for (street, sums) in sums.iter().enumerate() {
+            for (player, sums) in sums.iter().enumerate() {
+                let total = sums[5];
+                if total <= 0.0 {
+                    continue;
+                }
+                let freq = |kind: usize| (sums[kind] / total) as f32;
+                result.push(StreetSummary {
+                    player,
+                    street: [BoardState::Flop, BoardState::Turn, BoardState::River][street],
+                    decisions: total as f32,
+                    fold: freq(0),
+                    check: freq(1),
+                    call: freq(2),
+                    bet: freq(3),
+                    raise: freq(4),
+                });
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Buckets the range of the current player into hand classes and returns the strategy and the
+    /// expected values of each class.
+    ///
+    /// Hands are classified by [`classify_hand`] on the current board, and the statistics are
+    /// weighted by the normalized weights. Classes containing no hands (with positive weight) are
+    /// omitted, and the remaining ones are sorted from the strongest to the weakest.
+    ///
+    /// Panics if the game is not solved or the current node is a terminal node or a chance node.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// **Time complexity:** *O*(#(actions) * #(private hands)).
+    ///
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    pub fn hand_class_breakdown(&self) -> Vec<HandClassStats> {
+        assert!(self.state == State::Solved, "Game is not solved");
+        assert!(!self.is_terminal_node(), "Terminal node is not allowed");
+        assert!(!self.is_chance_node(), "Chance node is not allowed");
+        assert!(
+            self.is_normalized_weight_cached,
+            "Normalized weights are not cached"
+        );
+
+        let board = self.current_board();
+        let player = self.current_player();
+        let private_cards = &self.private_cards[player];
+
+        let summaries = self.summarize_buckets(HandClass::ALL.len(), |hand| {
+            classify_hand(private_cards[hand], &board) as usize
+        });
+
+        HandClass::ALL
+            .iter()
+            .zip(summaries)
+            .filter_map(|(&class, summary)| {
+                summary.map(|s| HandClassStats {
+                    class,
+                    num_hands: s.num_hands,
+                    weight: s.weight,
+                    share: s.share,
+                    frequencies: s.frequencies,
+                    ev: s.ev,
+                    action_ev: s.action_ev,
+                })
+            })
+            .collect()
+    }
+
+    /// Partitions the range of the current player into `num_buckets` equity quantiles and returns
+    /// the strategy and the expected values of each bucket.
+    ///
+    /// Hands are sorted by their equity against the range of the opponent reaching the current
+    /// node, and each bucket holds (approximately) the same amount of normalized weights. The
+    /// buckets are ordered from the lowest equity to the highest. Empty buckets are omitted, which
+    /// can happen when the range has only a few hands.
+    ///
+    /// Panics if the game is not solved, the current node is a terminal node or a chance node, or
+    /// `num_buckets` is zero.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// **Time complexity:** see [`equity`].
+    ///
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    /// [`equity`]: #method.equity
+    pub fn equity_bucket_breakdown(&self, num_buckets: usize) -> Vec<EquityBucketStats> {
+        assert!(self.state == State::Solved, "Game is not solved");
+        assert!(!self.is_terminal_node(), "Terminal node is not allowed");
+        assert!(!self.is_chance_node(), "Chance node is not allowed");
+        assert!(
+            self.is_normalized_weight_cached,
+            "Normalized weights are not cached"
+        );
+        assert!(num_buckets > 0, "Number of buckets must be positive");
+
+        let player = self.current_player();
+        let weights = self.normalized_weights(player);
+        let equity = self.equity(player);
+
+        let mut order = (0..weights.len())
+            .filter(|&hand| weights[hand] > 0.0)
+            .collect::<Vec<_>>();
+        order.sort_by(|&i, &j| equity[i].total_cmp(&equity[j]));
+
+        // assigns each hand to the quantile containing the midpoint of its cumulative weight
+        let total_weight = order.iter().map(|&hand| weights[hand] as f64).sum::<f64>();
+        let mut buckets = vec![0; weights.len()];
+        let mut cumulative_weight = 0.0;
+        for &hand in &order {
+            let weight = weights[hand] as f64;
+            let midpoint = (cumulative_weight + 0.5 * weight) / total_weight;
+            buckets[hand] = ((midpoint * num_buckets as f64) as usize).min(num_buckets - 1);
+            cumulative_weight += weight;
+        }
+
+        let summaries = self.summarize_buckets(num_buckets, |hand| buckets[hand]);
+
+        summaries
+            .into_iter()
+            .enumerate()
+            .filter_map(|(bucket, summary)| {
+                summary.map(|s| {
+                    let equities = order
+                        .iter()
+                        .filter(|&&hand| buckets[hand] == bucket)
+                        .map(|&hand| equity[hand]);
+                    let (min_equity, max_equity) = equities
+                        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), e| {
+                            (min.min(e), max.max(e))
+                        });
+                    EquityBucketStats {
+                        bucket,
+                        min_equity,
+                        max_equity,
+                        num_hands: s.num_hands,
+                        weight: s.weight,
+                        share: s.share,
+                        frequencies: s.frequencies,
+                        ev: s.ev,
+                        action_ev: s.action_ev,
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Aggregates the strategy and the expected values of the current player for each bucket.
+    ///
+    /// Hands with zero normalized weight are skipped, and empty buckets are returned as `None`.
+    fn summarize_buckets(
+        &self,
+        num_buckets: usize,
+        mut bucket_of: impl FnMut(usize) -> usize,
+    ) -> Vec<Option<BucketSummary>> {
+        let player = self.current_player();
+        let num_actions = self.available_actions().len();
+        let num_hands = self.num_private_hands(player);
+
+        let weights = self.normalized_weights(player);
+        let strategy = self.strategy();
+        let ev_detail = self.expected_values_detail(player);
+
+        let mut counts = vec![0; num_buckets];
+        let mut weight_sums = vec![0.0f64; num_buckets];
+        let mut freq_sums = vec![0.0f64; num_buckets * num_actions];
+        let mut ev_sums = vec![0.0f64; num_buckets * num_actions];
+
+        for (hand, &weight) in weights.iter().enumerate() {
+            let weight = weight as f64;
+            if weight <= 0.0 {
+                continue;
+            }
+
+            let bucket = bucket_of(hand);
+            counts[bucket] += 1;
+            weight_sums[bucket] += weight;
+
+            for action in 0..num_actions {
+                let index = action * num_hands + hand;
+                let prob = weight * strategy[index] as f64;
+                freq_sums[bucket * num_actions + action] += prob;
+                ev_sums[bucket * num_actions + action] += prob * ev_detail[index] as f64;
+            }
+        }
+
+        let total_weight = weight_sums.iter().sum::<f64>();
+
+        (0..num_buckets)
+            .map(|bucket| {
+                if counts[bucket] == 0 {
+                    return None;
+                }
+                let range = bucket * num_actions..(bucket + 1) * num_actions;
+                let weight = weight_sums[bucket];
+                let freq_sums = &freq_sums[range.clone()];
+                let ev_sums = &ev_sums[range];
+                Some(BucketSummary {
+                    num_hands: counts[bucket],
+                    weight: weight as f32,
+                    share: (weight / total_weight) as f32,
+                    frequencies: freq_sums.iter().map(|&f| (f / weight) as f32).collect(),
+                    ev: (ev_sums.iter().sum::<f64>() / weight) as f32,
+                    action_ev: freq_sums
+                        .iter()
+                        .zip(ev_sums)
+                        .map(|(&f, &v)| (v / f) as f32)
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// Decomposes the expected value of the given private hand of the `player` into the lines
+    /// following the current node.
+    ///
+    /// All continuations from the current node are walked, and each terminal node yields the
+    /// probability of reaching it given the private hand and the expected value there. The sum of
+    /// the `contribution` fields equals the expected value of the hand at the current node. If
+    /// `merge_runouts` is `true`, dealt cards are removed from the lines, and lines consisting of
+    /// the same actions are merged (e.g., "bet - call - bet - fold" across all turn cards). Lines
+    /// with zero probability are omitted, and the remaining ones are listed in depth-first order.
+    ///
+    /// The current node is restored after the call. Returns [`SolverError::InvalidState`] if the
+    /// game is not solved or the current node is a terminal node, and
+    /// [`SolverError::InvalidArgument`] if `player` is invalid or the hand is not in the range at
+    /// the current node.
+    ///
+    /// **Time complexity:** *O*(#(reachable nodes) * #(depth) * (#(OOP private hands) + #(IP
+    /// private hands))). This method can be very slow for flop nodes with many runouts.
+    pub fn ev_decomposition(
+        &mut self,
+        player: usize,
+        hand: (Card, Card),
+        merge_runouts: bool,
+    ) -> Result<Vec<LineContribution>, SolverError> {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+        if player > 1 {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid player: {player}"
+            )));
+        }
+        if self.is_terminal_node() {
+            return Err(SolverError::InvalidState(
+                "Terminal node is not allowed".to_owned(),
+            ));
+        }
+
+        let hand_index = self
+            .private_hand_index(player, hand)
+            .ok_or_else(|| SolverError::InvalidArgument("Hand is not in the range".to_owned()))?;
+
+        self.cache_normalized_weights();
+        if self.normalized_weights(player)[hand_index] <= 0.0 {
+            return Err(SolverError::InvalidArgument(
+                "Hand is not in the range at the current node".to_owned(),
+            ));
+        }
+
+        let mut history = self.history().to_vec();
+        let base_len = history.len();
+        let base_bet = self.total_bet_amount()[player];
+        let mut line = Vec::new();
+        let mut result = Vec::new();
+
+        self.ev_decomposition_recursive(
+            player,
+            hand_index,
+            &mut history,
+            &mut line,
+            1.0,
+            &mut result,
+        );
+
+        history.truncate(base_len);
+        self.apply_history(&history);
+
+        for item in &mut result {
+            item.ev += base_bet as f32;
+            item.contribution = item.probability * item.ev;
+        }
+
+        if !merge_runouts {
+            return Ok(result);
+        }
+
+        let mut merged = Vec::<LineContribution>::new();
+        let mut index_map = HashMap::new();
+        for mut item in result {
+            item.line
+                .retain(|action| !matches!(action, Action::Chance(_)));
+            match index_map.get(&item.line) {
+                Some(&i) => {
+                    let entry: &mut LineContribution = &mut merged[i];
+                    entry.probability += item.probability;
+                    entry.contribution += item.contribution;
+                }
+                None => {
+                    index_map.insert(item.line.clone(), merged.len());
+                    merged.push(item);
+                }
+            }
+        }
+
+        for item in &mut merged {
+            item.ev = item.contribution / item.probability;
+        }
+
+        Ok(merged)
+    }
+
+    fn ev_decomposition_recursive(
+        &mut self,
+        player: usize,
+        hand_index: usize,
+        history: &mut Vec<usize>,
+        line: &mut Vec<Action>,
+        probability: f64,
+        result: &mut Vec<LineContribution>,
+    ) {
+        self.apply_history(history);
+        self.cache_normalized_weights();
+
+        if self.is_terminal_node() {
+            // subtracts the bet amount for now; the bet amount at the base node is added later
+            let bet = self.total_bet_amount()[player];
+            let ev = self.expected_values(player)[hand_index] - bet as f32;
+            result.push(LineContribution {
+                line: line.clone(),
+                probability: probability as f32,
+                ev,
+                contribution: 0.0,
+            });
+            return;
+        }
+
+        let weight = self.normalized_weights(player)[hand_index] as f64;
+
+        // (action index, action, normalized weight of the child node)
+        let is_chance = self.is_chance_node();
+        let mut children = Vec::new();
+        if is_chance {
+            let possible_cards = self.possible_cards();
+            for card in 0..52 {
+                if possible_cards & (1 << card) != 0 {
+                    children.push((card, Action::Chance(card as Card), 0.0));
+                }
+            }
+        } else {
+            let actions = self.available_actions();
+            children.extend(actions.into_iter().enumerate().map(|(i, a)| (i, a, 0.0)));
+        }
+
+        for child in &mut children {
+            history.push(child.0);
+            self.apply_history(history);
+            self.cache_normalized_weights();
+            child.2 = self.normalized_weights(player)[hand_index] as f64;
+            history.pop();
+        }
+
+        // the transition probability of a chance node is normalized over the dealt cards
+        let denom = if is_chance {
+            children.iter().map(|child| child.2).sum::<f64>()
+        } else {
+            weight
+        };
+
+        for (action, prev_action, child_weight) in children {
+            if child_weight <= 0.0 {
+                continue;
+            }
+            history.push(action);
+            line.push(prev_action);
+            self.ev_decomposition_recursive(
+                player,
+                hand_index,
+                history,
+                line,
+                probability * child_weight / denom,
+                result,
+            );
+            line.pop();
+            history.pop();
+        }
+    }
+}
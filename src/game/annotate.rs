@@ -0,0 +1,198 @@
+use super::*;
+use crate::interface::*;
+
+/// A decision point of a hand history annotated with the solver strategy, returned by
+/// [`PostFlopGame::annotate_hand_history`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotatedDecision {
+    /// History from the root node (see [`PostFlopGame::apply_history`]) of the decision point.
+    pub history: Vec<usize>,
+
+    /// Player who made the decision (0 = OOP, 1 = IP).
+    pub player: usize,
+
+    /// Action actually taken.
+    pub action: Action,
+
+    /// Index of the tree action the taken action was snapped to.
+    pub action_index: usize,
+
+    /// Available actions in the tree.
+    pub actions: Vec<Action>,
+
+    /// Solver frequency of each action: the strategy of the hand if the hole cards of the player
+    /// are known, or the overall frequencies of the range otherwise.
+    pub frequencies: Vec<f32>,
+
+    /// Expected value of the snapped action for the hand. `None` if the hole cards are unknown or
+    /// the game is not solved.
+    pub action_ev: Option<f32>,
+
+    /// Expected value of the best action for the hand. `None` if the hole cards are unknown or the
+    /// game is not solved.
+    pub best_ev: Option<f32>,
+
+    /// Expected value lost by the snapped action compared to the solver strategy of the hand.
+    /// `None` if the hole cards are unknown or the game is not solved.
+    pub ev_loss: Option<f32>,
+
+    /// Sum of `ev_loss` of the player up to and including this decision.
+    pub cumulative_ev_loss: f32,
+}
+
+impl PostFlopGame {
+    /// Replays a hand history from the root node and annotates each decision with the solver
+    /// strategy.
+    ///
+    /// The `line` is the sequence of the actions taken and the turn and river cards dealt (as
+    /// [`Action::Chance`]). Fold, check, and call actions must exist in the tree. Bets and raises
+    /// are snapped to the tree action with the nearest amount (an all-in is snapped to the all-in
+    /// action if available). `hands` optionally gives the hole cards of each player (OOP, IP);
+    /// the expected values are reported only for the players whose hole cards are known.
+    ///
+    /// The EV loss of a decision is the expected value of the solver strategy of the hand minus
+    /// that of the snapped action. The current node is restored after the call.
+    ///
+    /// Returns [`SolverError::InvalidLine`] with the line up to the offending action if the line
+    /// cannot be replayed in the tree.
+    ///
+    /// **Time complexity:** *O*(#(line) * #(depth) * (#(OOP private hands) + #(IP private
+    /// hands))).
+    pub fn annotate_hand_history(
+        &mut self,
+        line: &[Action],
+        hands: [Option<(Card, Card)>; 2],
+    ) -> Result<Vec<AnnotatedDecision>, SolverError> {
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+
+        let mut hand_indices = [None; 2];
+        for (player, hand) in hands.iter().enumerate() {
+            if let Some(hand) = *hand {
+                let index = self.private_hand_index(player, hand).ok_or_else(|| {
+                    SolverError::InvalidArgument(format!(
+                        "Hand of player {player} is not in the range"
+                    ))
+                })?;
+                hand_indices[player] = Some(index);
+            }
+        }
+
+        let original_history = self.history().to_vec();
+        self.back_to_root();
+
+        let result = self.annotate_internal(line, hand_indices);
+
+        self.apply_history(&original_history);
+        result
+    }
+
+    fn annotate_internal(
+        &mut self,
+        line: &[Action],
+        hand_indices: [Option<usize>; 2],
+    ) -> Result<Vec<AnnotatedDecision>, SolverError> {
+        let mut decisions = Vec::new();
+        let mut cumulative_ev_loss = [0.0; 2];
+
+        for (i, &action) in line.iter().enumerate() {
+            let invalid = |message| SolverError::InvalidLine {
+                line: line[..=i].to_vec(),
+                message,
+            };
+
+            if self.is_terminal_node() {
+                return Err(invalid(format!("Terminal node reached before {action}")));
+            }
+
+            if self.is_chance_node() {
+                let Action::Chance(card) = action else {
+                    return Err(invalid(format!("Expected a dealt card, but got {action}")));
+                };
+                if card >= 52 || self.possible_cards() & (1 << card) == 0 {
+                    return Err(invalid(format!("Invalid card: {action}")));
+                }
+                self.play(card as usize);
+                continue;
+            }
+
+            let actions = self.available_actions();
+            let action_index = snap_action(&actions, action)
+                .ok_or_else(|| invalid(format!("Action {action} is not available: {actions:?}")))?;
+
+            self.cache_normalized_weights();
+            let player = self.current_player();
+            let num_hands = self.num_private_hands(player);
+
+            let frequencies = match hand_indices[player] {
+                Some(hand) => {
+                    let strategy = self.strategy();
+                    (0..actions.len())
+                        .map(|a| strategy[a * num_hands + hand])
+                        .collect()
+                }
+                None => self.action_frequencies(),
+            };
+
+            let (action_ev, best_ev, ev_loss) = match hand_indices[player] {
+                Some(hand) if self.is_solved() => {
+                    let ev_detail = self.expected_values_detail(player);
+                    let hand_ev = self.expected_values(player)[hand];
+                    let action_ev = ev_detail[action_index * num_hands + hand];
+                    let best_ev = (0..actions.len())
+                        .map(|a| ev_detail[a * num_hands + hand])
+                        .fold(f32::MIN, f32::max);
+                    (Some(action_ev), Some(best_ev), Some(hand_ev - action_ev))
+                }
+                _ => (None, None, None),
+            };
+
+            cumulative_ev_loss[player] += ev_loss.unwrap_or(0.0);
+
+            decisions.push(AnnotatedDecision {
+                history: self.history().to_vec(),
+                player,
+                action,
+                action_index,
+                actions,
+                frequencies,
+                action_ev,
+                best_ev,
+                ev_loss,
+                cumulative_ev_loss: cumulative_ev_loss[player],
+            });
+
+            self.play(action_index);
+        }
+
+        Ok(decisions)
+    }
+}
+
+/// Returns the index of the tree action nearest to the given action.
+fn snap_action(actions: &[Action], action: Action) -> Option<usize> {
+    let bet_amount = |a: &Action| match *a {
+        Action::Bet(amount) | Action::Raise(amount) | Action::AllIn(amount) => Some(amount),
+        _ => None,
+    };
+
+    match action {
+        Action::Bet(amount) | Action::Raise(amount) | Action::AllIn(amount) => {
+            if let Action::AllIn(_) = action
+                && let Some(index) = actions.iter().position(|a| matches!(a, Action::AllIn(_)))
+            {
+                return Some(index);
+            }
+            actions
+                .iter()
+                .enumerate()
+                .filter_map(|(index, a)| bet_amount(a).map(|a| (index, (a - amount).abs())))
+                .min_by_key(|&(_, diff)| diff)
+                .map(|(index, _)| index)
+        }
+        _ => actions.iter().position(|&a| a == action),
+    }
+}
@@ -1,8 +1,13 @@
 use super::*;
 use crate::bunching::*;
 use crate::interface::*;
+use crate::profiling::*;
+use crate::random::*;
 use crate::utility::*;
+use std::convert::Infallible;
 use std::mem::{self, MaybeUninit};
+use std::ptr;
+use std::sync::Arc;
 
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
@@ -53,9 +58,9 @@ impl Game for PostFlopGame {
     #[inline]
     fn chance_factor(&self, node: &Self::Node) -> usize {
         if node.turn == NOT_DEALT {
-            45 - self.bunching_num_dead_cards
+            45 - self.bunching_num_dead_cards - self.num_dead_cards()
         } else {
-            44 - self.bunching_num_dead_cards
+            44 - self.bunching_num_dead_cards - self.num_dead_cards()
         }
     }
 
@@ -71,14 +76,26 @@ impl Game for PostFlopGame {
         self.apply_history(&history);
     }
 
+    #[inline]
+    fn set_solve_result(&mut self, num_iterations: u32, exploitability: f32) {
+        self.solve_summary = Some(SolveSummary {
+            num_iterations,
+            exploitability,
+            solved_at: unix_time(),
+        });
+        self.solve_manifest = self.manifest().ok();
+    }
+
     #[inline]
     fn is_ready(&self) -> bool {
-        self.state == State::MemoryAllocated && self.storage_mode == BoardState::River
+        self.state == State::MemoryAllocated
+            && self.storage_mode == BoardState::River
+            && !self.is_strategy_only
     }
 
     #[inline]
     fn is_raked(&self) -> bool {
-        self.tree_config.rake_rate > 0.0 && self.tree_config.rake_cap > 0.0
+        self.tree_config.is_raked()
     }
 
     #[inline]
@@ -115,10 +132,46 @@ impl Game for PostFlopGame {
         self.is_compression_enabled
     }
 
+    #[inline]
+    fn is_strategy_quantized(&self) -> bool {
+        self.is_strategy_quantized
+    }
+
     #[inline]
     fn is_zero_sum(&self) -> bool {
         self.tree_config.icm_config.is_none()
     }
+
+    #[inline]
+    fn node_compression_params(&self, node: &Self::Node) -> CompressionParams {
+        self.compression_params[self.node_street(node) as usize]
+    }
+
+    #[inline]
+    fn update_schedule(&self, player: usize) -> UpdateSchedule {
+        self.update_schedule[player]
+    }
+
+    #[inline]
+    fn node_street(&self, node: &Self::Node) -> BoardState {
+        match (node.turn, node.river) {
+            (NOT_DEALT, _) => BoardState::Flop,
+            (_, NOT_DEALT) => BoardState::Turn,
+            _ => BoardState::River,
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[inline]
+    fn thread_pool(&self) -> Option<Arc<rayon::ThreadPool>> {
+        self.thread_pool.clone()
+    }
+
+    #[cfg(all(feature = "numa", target_os = "linux"))]
+    #[inline]
+    fn subtree_thread_pool(&self, action: usize) -> Option<Arc<rayon::ThreadPool>> {
+        self.numa_thread_pool(action)
+    }
 }
 
 impl PostFlopGame {
@@ -137,7 +190,10 @@ impl PostFlopGame {
 
     /// Creates a new [`PostFlopGame`] with the specified configuration.
     #[inline]
-    pub fn with_config(card_config: CardConfig, action_tree: ActionTree) -> Result<Self, String> {
+    pub fn with_config(
+        card_config: CardConfig,
+        action_tree: ActionTree,
+    ) -> Result<Self, SolverError> {
         let mut game = Self::new();
         game.update_config(card_config, action_tree)?;
         Ok(game)
@@ -149,30 +205,225 @@ impl PostFlopGame {
         &mut self,
         card_config: CardConfig,
         action_tree: ActionTree,
-    ) -> Result<(), String> {
+    ) -> Result<(), SolverError> {
+        #[cfg(feature = "rayon")]
+        if let Some(pool) = outer_thread_pool(self) {
+            return pool.install(|| self.update_config(card_config, action_tree));
+        }
+
         self.state = State::ConfigError;
 
-        if !action_tree.invalid_terminals().is_empty() {
-            return Err("Invalid terminal is found in action tree".to_owned());
+        if let Some(line) = action_tree.invalid_terminals().into_iter().next() {
+            return Err(SolverError::InvalidLine {
+                line,
+                message: "Invalid terminal is found in action tree".to_owned(),
+            });
         }
 
-        self.card_config = card_config;
+        let action_nodes;
         (
             self.tree_config,
             self.added_lines,
             self.removed_lines,
-            self.action_root,
+            action_nodes,
         ) = action_tree.eject();
+        self.action_nodes = action_nodes.into();
 
-        self.check_card_config()?;
+        self.init_with_action_tree(card_config)
+    }
+
+    /// Updates the tree configuration, keeping the card configuration. The solved result and the
+    /// locked strategies will be lost.
+    ///
+    /// Unlike [`update_config`], the tables computed from the card configuration (the private
+    /// hands, the hand strengths, the suit isomorphism, and the bunching effect) are reused, and
+    /// only the game tree is rebuilt. This makes it cheap to iterate on the bet sizes, the rake,
+    /// and the added and removed lines of the tree. The memory must be allocated again before
+    /// solving, and the current node is reset to the root.
+    ///
+    /// Returns an error if the game is not successfully initialized or `action_tree` does not fit
+    /// the game (it has an invalid terminal, or its initial state differs from the current one), in
+    /// which case the game is left unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["QQ+,AK".parse().unwrap(), "JJ-99,AQs".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("Qc").unwrap(),
+    ///     ..Default::default()
+    /// };
+    /// let mut tree_config = TreeConfig {
+    ///     initial_state: BoardState::Turn,
+    ///     starting_pot: 100,
+    ///     effective_stack: 200,
+    ///     turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+    ///     ..Default::default()
+    /// };
+    /// let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// let small = game.memory_usage();
+    ///
+    /// tree_config.turn_bet_sizes[0] = ("50%, 100%", "").try_into().unwrap();
+    /// game.update_tree_config(ActionTree::new(tree_config).unwrap()).unwrap();
+    /// assert!(game.memory_usage().0 > small.0);
+    /// ```
+    ///
+    /// [`update_config`]: #method.update_config
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, name = "rebuild_game_tree")
+    )]
+    pub fn update_tree_config(&mut self, action_tree: ActionTree) -> Result<(), SolverError> {
+        #[cfg(feature = "rayon")]
+        if let Some(pool) = outer_thread_pool(self) {
+            return pool.install(|| self.update_tree_config(action_tree));
+        }
+
+        if self.state <= State::Uninitialized {
+            return Err(SolverError::InvalidState(
+                "Game is not successfully initialized".to_owned(),
+            ));
+        }
+
+        if let Some(line) = action_tree.invalid_terminals().into_iter().next() {
+            return Err(SolverError::InvalidLine {
+                line,
+                message: "Invalid terminal is found in action tree".to_owned(),
+            });
+        }
+
+        let initial_state = action_tree.config().initial_state;
+        if initial_state != self.tree_config.initial_state {
+            return Err(SolverError::InvalidTreeConfig(format!(
+                "Initial state of the tree cannot be changed: expected = {:?}, actual = {:?}",
+                self.tree_config.initial_state, initial_state
+            )));
+        }
+
+        let _timer = PhaseTimer::start(Phase::TreeBuild);
+        self.state = State::ConfigError;
+
+        let action_nodes;
+        (
+            self.tree_config,
+            self.added_lines,
+            self.removed_lines,
+            action_nodes,
+        ) = action_tree.eject();
+        self.action_nodes = action_nodes.into();
+
+        self.init_tree()?;
+        self.back_to_root();
+
+        Ok(())
+    }
+
+    /// Creates a new [`PostFlopGame`] with the specified card configuration, sharing the action
+    /// tree of `game` instead of building it again.
+    ///
+    /// This is intended for solving many boards with the same tree configuration (e.g., a flop
+    /// subset): the action tree structure, including the added and removed lines of the
+    /// [`ActionTree`], is shared between the instances behind `Arc`. The tables depending on the
+    /// board, such as the private hands and their strengths, are built for each instance because
+    /// the hands conflicting with the board are excluded from them. Lines removed from `game` by
+    /// [`remove_lines`] are not inherited, while the dedicated thread pool, the table cache, and
+    /// the compression parameters are.
+    ///
+    /// Returns an error if `game` is not successfully initialized or the card configuration is
+    /// invalid.
+    ///
+    /// [`remove_lines`]: #method.remove_lines
+    pub fn with_shared_tree(card_config: CardConfig, game: &Self) -> Result<Self, SolverError> {
+        if game.state <= State::Uninitialized {
+            return Err(SolverError::InvalidState(
+                "Game is not successfully initialized".to_owned(),
+            ));
+        }
+
+        let mut ret = Self {
+            tree_config: game.tree_config.clone(),
+            added_lines: game.added_lines.clone(),
+            removed_lines: game.removed_lines.clone(),
+            action_nodes: Arc::clone(&game.action_nodes),
+            compression_params: game.compression_params,
+            update_schedule: game.update_schedule,
+            table_cache: game.table_cache.clone(),
+            equity_cache: game.equity_cache.clone(),
+            hand_evaluator: game.hand_evaluator.clone(),
+            split_pot_evaluator: game.split_pot_evaluator.clone(),
+            icm_seed: game.icm_seed,
+            #[cfg(feature = "rayon")]
+            thread_pool: game.thread_pool.clone(),
+            ..Default::default()
+        };
+
+        #[cfg(feature = "rayon")]
+        if let Some(pool) = outer_thread_pool(&ret) {
+            pool.install(|| ret.init_with_action_tree(card_config))?;
+            return Ok(ret);
+        }
+
+        ret.init_with_action_tree(card_config)?;
+        Ok(ret)
+    }
+
+    /// Returns whether the action tree is shared with `other` (see [`with_shared_tree`]).
+    ///
+    /// [`with_shared_tree`]: #method.with_shared_tree
+    #[inline]
+    pub fn shares_action_tree_with(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.action_nodes, &other.action_nodes)
+    }
+
+    /// Builds the game tree from `self.action_nodes` with the given card configuration.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, name = "build_game_tree")
+    )]
+    pub(super) fn init_with_action_tree(
+        &mut self,
+        card_config: CardConfig,
+    ) -> Result<(), SolverError> {
+        let _timer = PhaseTimer::start(Phase::TreeBuild);
+        self.state = State::ConfigError;
+        self.card_config = card_config;
+
+        self.check_card_config()
+            .map_err(SolverError::InvalidCardConfig)?;
         self.init_card_fields();
+        self.init_tree()?;
+
+        self.init_interpreter();
+        self.reset_bunching_effect();
+
+        Ok(())
+    }
+
+    /// Builds the game tree from `self.action_nodes`, assuming that the fields related to cards are
+    /// initialized.
+    fn init_tree(&mut self) -> Result<(), SolverError> {
         self.init_root()?;
         self.init_icm_calculator();
 
-        self.state = State::TreeBuilt;
+        #[cfg(all(feature = "numa", target_os = "linux"))]
+        {
+            self.numa_partitions = None;
+        }
 
-        self.init_interpreter();
-        self.reset_bunching_effect();
+        self.state = State::TreeBuilt;
+        self.solve_summary = None;
+        self.solve_manifest = None;
+        self.redo_history.clear();
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            num_nodes = self.node_arena.len(),
+            num_hands = ?[self.num_private_hands(0), self.num_private_hands(1)],
+            "game tree built"
+        );
 
         Ok(())
     }
@@ -183,20 +434,52 @@ impl PostFlopGame {
     /// Specifically, the computational complexity of the terminal evaluation will increase from
     /// *O*(#(OOP private hands) + #(IP private hands)) to *O*(#(OOP private hands) * #(IP private
     /// hands)).
+    ///
+    /// `bunching_data` must be processed for the flop of the board, and the game may start on any
+    /// street: when the turn or river is given, the card removal by the board is applied to the
+    /// folded players as well, so the weights are the same as those reached from the flop.
+    ///
+    /// The bunching effect can be combined with the ICM terminal utilities of
+    /// [`TreeConfig::icm_config`]: the payoffs are the ICM equity differences as usual, and the
+    /// card removal of the folded players adjusts how likely each pair of private hands is. The
+    /// stacks of the folded players are given by [`ICMConfig::other_players_stacks`].
     #[inline]
-    pub fn set_bunching_effect(&mut self, bunching_data: &BunchingData) -> Result<(), String> {
+    pub fn set_bunching_effect(&mut self, bunching_data: &BunchingData) -> Result<(), SolverError> {
         if self.state <= State::Uninitialized {
-            return Err("Game is not successfully initialized".to_owned());
+            return Err(SolverError::InvalidState(
+                "Game is not successfully initialized".to_owned(),
+            ));
+        }
+
+        if self.card_config.dead_mask() != 0 {
+            return Err(SolverError::InvalidCardConfig(
+                "Bunching effect is not supported with dead cards".to_owned(),
+            ));
+        }
+
+        if self.split_pot_evaluator.is_some() {
+            return Err(SolverError::InvalidState(
+                "Bunching effect is not supported in split-pot games".to_owned(),
+            ));
+        }
+
+        #[cfg(feature = "rayon")]
+        if let Some(pool) = outer_thread_pool(self) {
+            return pool.install(|| self.set_bunching_effect(bunching_data));
         }
 
         if !bunching_data.is_ready() {
-            return Err("Bunching configuration is not ready".to_owned());
+            return Err(SolverError::InvalidState(
+                "Bunching configuration is not ready".to_owned(),
+            ));
         }
 
         let mut flop_sorted = self.card_config.flop;
         flop_sorted.sort_unstable();
         if flop_sorted != bunching_data.flop() {
-            return Err("Flop cards do not match".to_owned());
+            return Err(SolverError::InvalidCardConfig(
+                "Flop cards do not match".to_owned(),
+            ));
         }
 
         self.reset_bunching_effect();
@@ -220,6 +503,318 @@ impl PostFlopGame {
         self.back_to_root();
     }
 
+    /// Sets the suit isomorphism mode and rebuilds the game tree.
+    ///
+    /// By default, isomorphic turn and river deals are combined into one chance branch (see
+    /// [`IsomorphismMode`]). Restricting the isomorphism is useful when the results need to be
+    /// indexed by literal cards, at the cost of a larger game tree.
+    ///
+    /// This method must be called before allocating memory. Since the game tree is rebuilt, lines
+    /// removed by [`remove_lines`] are restored, and the bunching effect configuration is reset.
+    ///
+    /// [`remove_lines`]: #method.remove_lines
+    pub fn set_isomorphism_mode(&mut self, mode: IsomorphismMode) -> Result<(), SolverError> {
+        if self.state <= State::Uninitialized {
+            return Err(SolverError::InvalidState(
+                "Game is not successfully initialized".to_owned(),
+            ));
+        } else if self.state >= State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Game has already been allocated".to_owned(),
+            ));
+        }
+
+        self.state = State::ConfigError;
+        self.isomorphism_mode = mode;
+
+        self.init_card_fields();
+        self.init_root()?;
+
+        self.state = State::TreeBuilt;
+
+        self.init_interpreter();
+        self.reset_bunching_effect();
+
+        Ok(())
+    }
+
+    /// Obtains the suit isomorphism mode.
+    #[inline]
+    pub fn isomorphism_mode(&self) -> IsomorphismMode {
+        self.isomorphism_mode
+    }
+
+    /// Sets the hand evaluator used at showdowns (see [`HandEvaluator`]). If `None`, the default
+    /// evaluator of the card configuration is used ([`HoldemEvaluator`], or [`ShortDeckEvaluator`]
+    /// in short-deck mode).
+    ///
+    /// The showdown tables are recomputed immediately, but the tree and the storage are kept, so
+    /// the evaluator can also be set to a loaded game. Set it before solving, since the results
+    /// already computed are not updated. The evaluator is not saved when the game is serialized,
+    /// and the tables of a game with a custom evaluator are not shared by [`set_table_cache`].
+    ///
+    /// [`set_table_cache`]: #method.set_table_cache
+    pub fn set_hand_evaluator(
+        &mut self,
+        evaluator: Option<Arc<dyn HandEvaluator>>,
+    ) -> Result<(), SolverError> {
+        if self.state <= State::Uninitialized {
+            return Err(SolverError::InvalidState(
+                "Game is not successfully initialized".to_owned(),
+            ));
+        }
+
+        self.hand_evaluator = evaluator;
+        self.hand_strength = self
+            .card_config
+            .hand_strength(&self.private_cards, self.hand_evaluator());
+        Ok(())
+    }
+
+    /// Returns the hand evaluator used at showdowns.
+    #[inline]
+    pub fn hand_evaluator(&self) -> &dyn HandEvaluator {
+        match &self.hand_evaluator {
+            Some(evaluator) => evaluator.as_ref(),
+            None => self.card_config.default_evaluator(),
+        }
+    }
+
+    /// Sets the evaluator of a split-pot game (e.g., [`HoldemHiLoEvaluator`]), which divides the
+    /// pot at showdowns between its high and low halves (see [`hi_lo_share`]). If `None`, the
+    /// whole pot is awarded by the hand evaluator (default).
+    ///
+    /// The showdowns of the solver, [`equity`], and [`rollout`] follow the split-pot rules, while
+    /// the other analyses that evaluate the hands by themselves (e.g., [`run_it_n`]) return an
+    /// error. As with [`set_hand_evaluator`], the tables are recomputed immediately, the results
+    /// already computed are not updated, and the evaluator is not saved.
+    ///
+    /// Returns an error if the game is not successfully initialized, or if the bunching effect or
+    /// ICM is enabled: their payoffs are computed for a single winner.
+    ///
+    /// [`equity`]: #method.equity
+    /// [`rollout`]: #method.rollout
+    /// [`run_it_n`]: #method.run_it_n
+    /// [`set_hand_evaluator`]: #method.set_hand_evaluator
+    pub fn set_split_pot_evaluator(
+        &mut self,
+        evaluator: Option<Arc<dyn SplitPotEvaluator>>,
+    ) -> Result<(), SolverError> {
+        if self.state <= State::Uninitialized {
+            return Err(SolverError::InvalidState(
+                "Game is not successfully initialized".to_owned(),
+            ));
+        }
+
+        if evaluator.is_some() {
+            if self.bunching_num_dead_cards != 0 {
+                return Err(SolverError::InvalidState(
+                    "Split pots are not supported with the bunching effect".to_owned(),
+                ));
+            }
+            if self.tree_config.icm_config.is_some() {
+                return Err(SolverError::InvalidTreeConfig(
+                    "Split pots are not supported with ICM".to_owned(),
+                ));
+            }
+        }
+
+        self.split_pot_evaluator = evaluator;
+        self.init_split_pot_strength();
+        Ok(())
+    }
+
+    /// Returns the split-pot evaluator, if any.
+    #[inline]
+    pub fn split_pot_evaluator(&self) -> Option<&dyn SplitPotEvaluator> {
+        self.split_pot_evaluator.as_deref()
+    }
+
+    /// Sets a dedicated thread pool on which the game tree is built and solved. If `None`, the
+    /// global thread pool of rayon is used (default).
+    ///
+    /// The pool is used by [`update_config`], [`set_bunching_effect`], and the solver functions
+    /// such as [`solve`] and [`compute_exploitability`]. To build the tree on the pool as well,
+    /// create the instance with [`new`], set the pool, and then call [`update_config`].
+    ///
+    /// The pool is not saved when the game is serialized.
+    ///
+    /// [`update_config`]: #method.update_config
+    /// [`set_bunching_effect`]: #method.set_bunching_effect
+    /// [`new`]: #method.new
+    /// [`solve`]: crate::solve
+    /// [`compute_exploitability`]: crate::compute_exploitability
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn set_thread_pool(&mut self, pool: Option<Arc<rayon::ThreadPool>>) {
+        self.thread_pool = pool;
+    }
+
+    /// Creates a dedicated thread pool with `num_threads` threads and sets it (see
+    /// [`set_thread_pool`]).
+    ///
+    /// Returns an error if `num_threads` is zero or the pool cannot be created.
+    ///
+    /// [`set_thread_pool`]: #method.set_thread_pool
+    #[cfg(feature = "rayon")]
+    pub fn set_num_threads(&mut self, num_threads: usize) -> Result<(), SolverError> {
+        if num_threads == 0 {
+            return Err(SolverError::InvalidArgument(
+                "Number of threads must be positive".to_owned(),
+            ));
+        }
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .map_err(|e| SolverError::Other(format!("Failed to create thread pool: {e}")))?;
+
+        self.thread_pool = Some(Arc::new(pool));
+        Ok(())
+    }
+
+    /// Returns the number of threads used for building and solving the game.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn num_threads(&self) -> usize {
+        self.thread_pool
+            .as_ref()
+            .map_or_else(rayon::current_num_threads, |pool| {
+                pool.current_num_threads()
+            })
+    }
+
+    /// Sets a cache of the card tables shared between instances (see [`TableCache`]). If `None`,
+    /// the tables are computed for each instance (default).
+    ///
+    /// The cache is used when the game tree is built by [`update_config`] or
+    /// [`set_isomorphism_mode`], so set it to an instance created with [`new`] before calling
+    /// [`update_config`]. The cache is not saved when the game is serialized.
+    ///
+    /// [`update_config`]: #method.update_config
+    /// [`set_isomorphism_mode`]: #method.set_isomorphism_mode
+    /// [`new`]: #method.new
+    #[inline]
+    pub fn set_table_cache(&mut self, cache: Option<TableCache>) {
+        self.table_cache = cache;
+    }
+
+    /// Returns the cache of the card tables, if any.
+    #[inline]
+    pub fn table_cache(&self) -> Option<&TableCache> {
+        self.table_cache.as_ref()
+    }
+
+    /// Sets a cache of the all-in equities shared between instances (see [`EquityCache`]). If
+    /// `None`, the equities are computed on each call (default).
+    ///
+    /// The cache is used by [`run_it_n`] unless a custom hand evaluator is set. The cache is not
+    /// saved when the game is serialized.
+    ///
+    /// [`run_it_n`]: #method.run_it_n
+    #[inline]
+    pub fn set_equity_cache(&mut self, cache: Option<EquityCache>) {
+        self.equity_cache = cache;
+    }
+
+    /// Returns the cache of the all-in equities, if any.
+    #[inline]
+    pub fn equity_cache(&self) -> Option<&EquityCache> {
+        self.equity_cache.as_ref()
+    }
+
+    /// Sets the seed of the Monte Carlo estimation of the ICM equities.
+    ///
+    /// With more than 16 payouts or 64 players in [`TreeConfig::icm_config`], the ICM equities are
+    /// estimated by sampling instead of being computed exactly. The samples are drawn with the
+    /// random number generator seeded with `seed`, so the payoffs, and thus the solution, are
+    /// reproducible regardless of the number of threads. The default seed is 0. Set it before
+    /// solving, since the equities already computed are discarded. The seed is saved with the
+    /// game.
+    ///
+    /// [`TreeConfig::icm_config`]: crate::TreeConfig::icm_config
+    pub fn set_icm_seed(&mut self, seed: u64) {
+        self.icm_seed = seed;
+        self.init_icm_calculator();
+    }
+
+    /// Same as [`set_icm_seed`], but draws the seed from `rng`.
+    ///
+    /// The estimation runs in parallel with generators derived from the seed, so only the seed is
+    /// drawn from `rng`, and [`icm_seed`] returns it for reproducing the solution.
+    ///
+    /// [`set_icm_seed`]: #method.set_icm_seed
+    /// [`icm_seed`]: #method.icm_seed
+    #[inline]
+    pub fn set_icm_rng<R: RandomSource + ?Sized>(&mut self, rng: &mut R) {
+        self.set_icm_seed(rng.next_u64());
+    }
+
+    /// Returns the seed of the Monte Carlo estimation of the ICM equities.
+    #[inline]
+    pub fn icm_seed(&self) -> u64 {
+        self.icm_seed
+    }
+
+    /// Sets the scale-management policy of the 16-bit compressed storage for the nodes of
+    /// `street`.
+    ///
+    /// The policy is used while solving in the compressed mode (see [`allocate_memory`]) and
+    /// applies to the cumulative regrets and the 16-bit cumulative strategies. The 8-bit
+    /// strategies of [`allocate_memory_quantized`] always recompute the scale. Since most nodes are
+    /// on the river in a typical tree, the policy of the river has the largest effect on both the
+    /// speed and the precision. The policy is not saved to a file.
+    ///
+    /// Returns an error if the parameters are invalid.
+    ///
+    /// [`allocate_memory`]: #method.allocate_memory
+    /// [`allocate_memory_quantized`]: #method.allocate_memory_quantized
+    #[inline]
+    pub fn set_compression_params(
+        &mut self,
+        street: BoardState,
+        params: CompressionParams,
+    ) -> Result<(), SolverError> {
+        params.check()?;
+        self.compression_params[street as usize] = params;
+        Ok(())
+    }
+
+    /// Returns the scale-management policy of the compressed storage for the nodes of `street`.
+    #[inline]
+    pub fn compression_params(&self, street: BoardState) -> CompressionParams {
+        self.compression_params[street as usize]
+    }
+
+    /// Sets the update schedule of the strategy of `player` while solving (see
+    /// [`UpdateSchedule`]).
+    ///
+    /// For example, when only the strategy of OOP is studied, updating IP every few iterations
+    /// spends most of the computation on OOP. The schedule is not saved to a file.
+    ///
+    /// Returns an error if `player` is not 0 or 1, or the schedule is invalid.
+    #[inline]
+    pub fn set_update_schedule(
+        &mut self,
+        player: usize,
+        schedule: UpdateSchedule,
+    ) -> Result<(), SolverError> {
+        if player > 1 {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid player: {player}"
+            )));
+        }
+        schedule.check()?;
+        self.update_schedule[player] = schedule;
+        Ok(())
+    }
+
+    /// Returns the update schedule of the strategy of `player`.
+    #[inline]
+    pub fn update_schedule(&self, player: usize) -> UpdateSchedule {
+        self.update_schedule[player]
+    }
+
     /// Obtains the card configuration.
     #[inline]
     pub fn card_config(&self) -> &CardConfig {
@@ -276,6 +871,15 @@ impl PostFlopGame {
         (uncompressed, compressed)
     }
 
+    /// Returns the estimated memory usage in bytes when the memory is allocated with
+    /// [`allocate_memory_quantized`].
+    ///
+    /// [`allocate_memory_quantized`]: #method.allocate_memory_quantized
+    #[inline]
+    pub fn memory_usage_quantized(&self) -> u64 {
+        self.memory_usage().1 - self.num_storage
+    }
+
     /// Returns the estimated additional memory usage in bytes when the bunching effect is enabled.
     #[inline]
     pub fn memory_usage_bunching(&self) -> u64 {
@@ -287,20 +891,59 @@ impl PostFlopGame {
         self.memory_usage_bunching_internal()
     }
 
+    /// Returns the storage mode of this instance.
+    ///
+    /// The storage mode represents the deepest accessible node in the game tree.
+    /// For example, if the storage mode is `BoardState::Turn`, then the game tree
+    /// contains no information after the river deal.
+    #[inline]
+    pub fn storage_mode(&self) -> BoardState {
+        self.storage_mode
+    }
+
+    /// Returns the number of iterations and the exploitability of the last [`solve`] call, which
+    /// are also kept in the save files.
+    ///
+    /// Returns `None` if the game was not solved by [`solve`] (e.g., solved by [`solve_step`] and
+    /// [`finalize`]) or the memory was reallocated after solving.
+    ///
+    /// [`solve`]: crate::solve
+    /// [`solve_step`]: crate::solve_step
+    /// [`finalize`]: crate::finalize
+    #[inline]
+    pub fn solve_summary(&self) -> Option<SolveSummary> {
+        self.solve_summary
+    }
+
+    #[cfg(feature = "bincode")]
+    #[inline]
+    pub(crate) fn set_solve_summary(&mut self, summary: Option<SolveSummary>) {
+        self.solve_summary = summary;
+    }
+
     /// Remove lines after building the `PostFlopGame` but before allocating memory.
     ///
     /// This allows the removal of chance-specific lines (e.g., remove overbets on board-pairing
     /// turns) which we cannot do while building an action tree.
-    pub fn remove_lines(&mut self, lines: &[Vec<Action>]) -> Result<(), String> {
+    pub fn remove_lines(&mut self, lines: &[Vec<Action>]) -> Result<(), SolverError> {
         if self.state <= State::Uninitialized {
-            return Err("Game is not successfully initialized".to_owned());
+            return Err(SolverError::InvalidState(
+                "Game is not successfully initialized".to_owned(),
+            ));
         } else if self.state >= State::MemoryAllocated {
-            return Err("Game has already been allocated".to_owned());
+            return Err(SolverError::InvalidState(
+                "Game has already been allocated".to_owned(),
+            ));
         }
 
         for line in lines {
             let mut root = self.root();
-            let info = self.remove_line_recursive(&mut root, line)?;
+            let info = self
+                .remove_line_recursive(&mut root, line)
+                .map_err(|message| SolverError::InvalidLine {
+                    line: line.clone(),
+                    message,
+                })?;
             self.num_storage -= info.num_storage;
             self.num_storage_ip -= info.num_storage_ip;
             self.num_storage_chance -= info.num_storage_chance;
@@ -324,18 +967,276 @@ impl PostFlopGame {
 
     /// Allocates the memory.
     pub fn allocate_memory(&mut self, enable_compression: bool) {
+        if self.state == State::MemoryAllocated
+            && self.storage_mode == BoardState::River
+            && self.is_compression_enabled == enable_compression
+            && !self.is_strategy_quantized
+            && !self.is_memory_on_disk()
+        {
+            return;
+        }
+
+        let Ok(()) =
+            self.allocate_memory_with(enable_compression, false, BoardState::River, &mut |len| {
+                Ok::<_, Infallible>(Storage::zeroed(len))
+            });
+    }
+
+    /// Allocates the memory only for the nodes up to the given street.
+    ///
+    /// The storage of the later streets is allocated when [`play`] deals a card of such a street
+    /// for the first time, or explicitly with [`extend_memory`]. This allows inspecting the tree
+    /// and setting up node locks on the earlier streets without paying the full memory cost up
+    /// front. Solving requires the whole storage, so [`extend_memory`] must be called with
+    /// `BoardState::River` before solving.
+    ///
+    /// Panics if `street` is earlier than the initial state of the tree.
+    ///
+    /// [`play`]: #method.play
+    /// [`extend_memory`]: #method.extend_memory
+    pub fn allocate_memory_up_to(&mut self, enable_compression: bool, street: BoardState) {
         assert!(
-            self.state > State::Uninitialized,
-            "Game is not successfully initialized"
+            street >= self.tree_config.initial_state,
+            "Street must not be earlier than the initial state"
         );
 
+        let Ok(()) = self.allocate_memory_with(enable_compression, false, street, &mut |len| {
+            Ok::<_, Infallible>(Storage::zeroed(len))
+        });
+    }
+
+    /// Extends the storage allocated by [`allocate_memory_up_to`] to the nodes up to the given
+    /// street, preserving the current contents.
+    ///
+    /// Does nothing if the storage already covers `street`. Returns an error if the memory is not
+    /// allocated, the game is already solved, or the game tree was loaded with a lower storage mode
+    /// (in which case the nodes of the later streets do not exist).
+    ///
+    /// [`allocate_memory_up_to`]: #method.allocate_memory_up_to
+    pub fn extend_memory(&mut self, street: BoardState) -> Result<(), SolverError> {
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+        if street <= self.storage_mode {
+            return Ok(());
+        }
+        if self.state == State::Solved {
+            return Err(SolverError::InvalidState(
+                "Cannot extend the memory of a solved game".to_owned(),
+            ));
+        }
+        if !self.is_tree_complete() {
+            return Err(SolverError::InvalidState(
+                "Game tree is truncated by a lower storage mode".to_owned(),
+            ));
+        }
+
+        let sizes = self.storage_bytes_up_to(street);
+        let old = [
+            mem::take(&mut self.storage1),
+            mem::take(&mut self.storage2),
+            mem::take(&mut self.storage_ip),
+            mem::take(&mut self.storage_chance),
+        ];
+        let [storage1, storage2, storage_ip, storage_chance] = [0, 1, 2, 3].map(|i| {
+            let mut storage = Storage::zeroed(sizes[i]);
+            storage[..old[i].len()].copy_from_slice(&old[i]);
+            storage
+        });
+
+        self.storage1 = storage1;
+        self.storage2 = storage2;
+        self.storage_ip = storage_ip;
+        self.storage_chance = storage_chance;
+
+        self.allocate_memory_nodes(street);
+
+        self.storage_mode = street;
+        self.target_storage_mode = street;
+
+        Ok(())
+    }
+
+    /// Allocates the memory with the compression enabled, additionally storing the cumulative
+    /// strategy in 8-bit integers.
+    ///
+    /// The cumulative regrets and the counterfactual values are stored in 16-bit integers as with
+    /// `allocate_memory(true)`, but the cumulative strategy is quantized to 8 bits with a scale
+    /// per node. Since the strategy buffer is one of the two dominant buffers, this reduces the
+    /// memory usage by about 25% compared to the 16-bit compression (see
+    /// [`memory_usage_quantized`]), at the cost of a coarser average strategy.
+    ///
+    /// [`memory_usage_quantized`]: #method.memory_usage_quantized
+    pub fn allocate_memory_quantized(&mut self) {
         if self.state == State::MemoryAllocated
             && self.storage_mode == BoardState::River
-            && self.is_compression_enabled == enable_compression
+            && self.is_strategy_quantized
+            && !self.is_memory_on_disk()
         {
             return;
         }
 
+        let Ok(()) = self.allocate_memory_with(true, true, BoardState::River, &mut |len| {
+            Ok::<_, Infallible>(Storage::zeroed(len))
+        });
+    }
+
+    /// Allocates the memory without aborting the process if it cannot be allocated.
+    ///
+    /// The storage is first allocated in the format specified by `enable_compression`. If the
+    /// estimated memory usage of the game exceeds `max_memory_usage` (in bytes) or the allocation
+    /// fails, the smaller formats allowed by `fallback` are tried in order. The previously
+    /// allocated memory is released before the allocation.
+    ///
+    /// Returns the storage format and the street up to which the storage is allocated. If every
+    /// attempt fails, returns an error describing the shortfall of the smallest attempt; the game is
+    /// left without memory allocated in that case.
+    pub fn try_allocate_memory(
+        &mut self,
+        enable_compression: bool,
+        max_memory_usage: Option<u64>,
+        fallback: AllocationFallback,
+    ) -> Result<(StorageFormat, BoardState), SolverError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!(
+            "try_allocate_memory",
+            enable_compression,
+            max_memory_usage,
+            ?fallback
+        )
+        .entered();
+
+        if self.state <= State::Uninitialized {
+            return Err(SolverError::InvalidState(
+                "Game is not successfully initialized".to_owned(),
+            ));
+        }
+
+        let mut formats = vec![if enable_compression {
+            StorageFormat::Compressed
+        } else {
+            StorageFormat::Uncompressed
+        }];
+        if fallback != AllocationFallback::None {
+            if !enable_compression {
+                formats.push(StorageFormat::Compressed);
+            }
+            formats.push(StorageFormat::Quantized);
+        }
+
+        let mut attempts = formats
+            .iter()
+            .map(|&format| (format, BoardState::River))
+            .collect::<Vec<_>>();
+        if fallback == AllocationFallback::CompressAndDefer {
+            for street in [BoardState::Turn, BoardState::Flop] {
+                if street >= self.tree_config.initial_state {
+                    attempts.push((StorageFormat::Quantized, street));
+                }
+            }
+        }
+
+        let mut error = None;
+        for (format, street) in attempts {
+            let breakdown = self.estimate_memory_breakdown(format);
+            let storage = breakdown.streets[..=street as usize]
+                .iter()
+                .map(StreetMemoryUsage::storage)
+                .sum::<u64>();
+            let required = storage + breakdown.misc + breakdown.bunching;
+            let context = format!("{format:?} storage up to {street:?}");
+
+            if let Some(limit) = max_memory_usage
+                && required > limit
+            {
+                error = Some(SolverError::MemoryLimitExceeded {
+                    required,
+                    available: limit,
+                    context,
+                });
+                #[cfg(feature = "tracing")]
+                tracing::debug!(?format, ?street, required, "memory limit exceeded");
+                continue;
+            }
+
+            if storage > isize::MAX as u64 {
+                error = Some(SolverError::AllocationFailed {
+                    required: storage,
+                    context,
+                    message: "Memory usage exceeds maximum size".to_owned(),
+                });
+                continue;
+            }
+
+            let (compress, quantize) = match format {
+                StorageFormat::Uncompressed => (false, false),
+                StorageFormat::Compressed => (true, false),
+                StorageFormat::Quantized => (true, true),
+            };
+
+            match self.allocate_memory_with(compress, quantize, street, &mut Storage::try_zeroed) {
+                Ok(()) => return Ok((format, street)),
+                Err(e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(?format, ?street, required = storage, error = %e, "allocation failed");
+                    error = Some(e);
+                }
+            }
+        }
+
+        Err(error.unwrap())
+    }
+
+    /// Allocates the memory in memory-mapped files created in `dir` instead of the heap.
+    ///
+    /// The regrets, strategies, and counterfactual values are stored in the files, and the
+    /// operating system keeps only the recently used pages in RAM as a working set. This allows
+    /// solving trees that do not fit in the physical memory, at the cost of a much slower solving
+    /// speed when the working set exceeds the available memory. The files are removed from `dir`
+    /// right after they are created, so no file is left behind; however, the disk space is
+    /// occupied until the game is dropped or the memory is reallocated. Only available on Unix
+    /// with the `mmap` feature.
+    ///
+    /// Returns an error if the files cannot be created or mapped.
+    #[cfg(all(feature = "mmap", unix))]
+    pub fn allocate_memory_on_disk(
+        &mut self,
+        enable_compression: bool,
+        dir: impl AsRef<std::path::Path>,
+    ) -> Result<(), SolverError> {
+        let dir = dir.as_ref();
+        self.allocate_memory_with(enable_compression, false, BoardState::River, &mut |len| {
+            Storage::zeroed_on_disk(len, dir)
+        })
+    }
+
+    /// Returns whether the memory is allocated in memory-mapped files (see
+    /// [`allocate_memory_on_disk`]).
+    ///
+    /// [`allocate_memory_on_disk`]: #method.allocate_memory_on_disk
+    #[inline]
+    pub fn is_memory_on_disk(&self) -> bool {
+        self.storage1.is_on_disk()
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "info", skip(self, allocate), name = "allocate_memory")
+    )]
+    fn allocate_memory_with<E>(
+        &mut self,
+        enable_compression: bool,
+        quantize_strategy: bool,
+        street: BoardState,
+        allocate: &mut dyn FnMut(usize) -> Result<Storage, E>,
+    ) -> Result<(), E> {
+        assert!(
+            self.state > State::Uninitialized,
+            "Game is not successfully initialized"
+        );
+
         let num_bytes = if enable_compression { 2 } else { 4 };
         if num_bytes * self.num_storage > isize::MAX as u64
             || num_bytes * self.num_storage_chance > isize::MAX as u64
@@ -343,24 +1244,50 @@ impl PostFlopGame {
             panic!("Memory usage exceeds maximum size");
         }
 
-        self.state = State::MemoryAllocated;
+        // the nodes must not refer to the old storage if the allocation fails
+        self.clear_storage();
+        self.state = State::TreeBuilt;
+        self.solve_summary = None;
+        self.solve_manifest = None;
+
         self.is_compression_enabled = enable_compression;
+        self.is_strategy_quantized = quantize_strategy;
+
+        let sizes = if street == BoardState::River {
+            let strategy_num_bytes = if quantize_strategy { 1 } else { num_bytes };
+            [
+                (strategy_num_bytes * self.num_storage) as usize,
+                (num_bytes * self.num_storage) as usize,
+                (num_bytes * self.num_storage_ip) as usize,
+                (num_bytes * self.num_storage_chance) as usize,
+            ]
+        } else {
+            self.storage_bytes_up_to(street)
+        };
 
-        self.clear_storage();
+        let storage1 = allocate(sizes[0])?;
+        let storage2 = allocate(sizes[1])?;
+        let storage_ip = allocate(sizes[2])?;
+        let storage_chance = allocate(sizes[3])?;
 
-        let storage_bytes = (num_bytes * self.num_storage) as usize;
-        let storage_ip_bytes = (num_bytes * self.num_storage_ip) as usize;
-        let storage_chance_bytes = (num_bytes * self.num_storage_chance) as usize;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(bytes = sizes.iter().sum::<usize>(), "storage allocated");
 
-        self.storage1 = vec![0; storage_bytes];
-        self.storage2 = vec![0; storage_bytes];
-        self.storage_ip = vec![0; storage_ip_bytes];
-        self.storage_chance = vec![0; storage_chance_bytes];
+        self.state = State::MemoryAllocated;
+
+        self.storage1 = storage1;
+        self.storage2 = storage2;
+        self.storage_ip = storage_ip;
+        self.storage_chance = storage_chance;
 
-        self.allocate_memory_nodes();
+        self.allocate_memory_nodes(street);
 
-        self.storage_mode = BoardState::River;
-        self.target_storage_mode = BoardState::River;
+        self.storage_mode = street;
+        self.target_storage_mode = street;
+        self.is_strategy_only = false;
+        self.target_strategy_only = false;
+
+        Ok(())
     }
 
     /// Checks the card configuration.
@@ -417,6 +1344,34 @@ impl PostFlopGame {
             }
         }
 
+        if config.dead_cards >> 52 != 0 {
+            return Err(format!(
+                "Dead cards must be in [0, 52): dead_cards = {:#x}",
+                config.dead_cards
+            ));
+        }
+
+        let board = [flop[0], flop[1], flop[2], turn, river];
+        if config.short_deck {
+            let is_removed = |card: Card| SHORT_DECK_DEAD_CARDS & (1 << card) != 0;
+            if let Some(&card) = board.iter().find(|&&c| c != NOT_DEALT && is_removed(c)) {
+                return Err(format!(
+                    "Board cards must not be of ranks 2-5 in short-deck mode: card = {card}"
+                ));
+            }
+            if let Some(player) = (0..2).find(|&p| !range[p].is_short_deck()) {
+                return Err(format!(
+                    "Range must not contain cards of ranks 2-5 in short-deck mode: player = {player}"
+                ));
+            }
+        }
+
+        if let Some(&card) = board.iter().find(|&&c| c != NOT_DEALT && config.is_dead(c)) {
+            return Err(format!(
+                "Dead cards must be different from board cards: card = {card}"
+            ));
+        }
+
         let expected_state = match (turn != NOT_DEALT, river != NOT_DEALT) {
             (false, _) => BoardState::Flop,
             (true, false) => BoardState::Turn,
@@ -446,6 +1401,14 @@ impl PostFlopGame {
             return Err("IP range is invalid (loaded broken data?)".to_owned());
         }
 
+        if let [Some(oop), Some(ip)] = config.positions
+            && oop >= ip
+        {
+            return Err(format!(
+                "OOP position must act before IP position: oop = {oop}, ip = {ip}"
+            ));
+        }
+
         self.init_hands();
         self.num_combinations = 0.0;
 
@@ -480,6 +1443,7 @@ impl PostFlopGame {
         let range = &config.range;
 
         let mut board_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+        board_mask |= config.dead_mask();
         if turn != NOT_DEALT {
             board_mask |= 1 << turn;
         }
@@ -511,39 +1475,93 @@ impl PostFlopGame {
             }
         }
 
-        (
-            self.valid_indices_flop,
-            self.valid_indices_turn,
-            self.valid_indices_river,
-        ) = self.card_config.valid_indices(&self.private_cards);
+        let tables = match &self.table_cache {
+            Some(cache) if self.hand_evaluator.is_none() => {
+                cache.get_or_compute(self, || self.compute_card_tables())
+            }
+            _ => Arc::new(self.compute_card_tables()),
+        };
 
-        self.hand_strength = self.card_config.hand_strength(&self.private_cards);
+        // moves the tables out if they are not cached
+        let tables = Arc::try_unwrap(tables).unwrap_or_else(|tables| (*tables).clone());
+        self.valid_indices_flop = tables.valid_indices_flop;
+        self.valid_indices_turn = tables.valid_indices_turn;
+        self.valid_indices_river = tables.valid_indices_river;
+        self.hand_strength = tables.hand_strength;
+        self.isomorphism_ref_turn = tables.isomorphism_ref_turn;
+        self.isomorphism_card_turn = tables.isomorphism_card_turn;
+        self.isomorphism_swap_turn = tables.isomorphism_swap_turn;
+        self.isomorphism_ref_river = tables.isomorphism_ref_river;
+        self.isomorphism_card_river = tables.isomorphism_card_river;
+        self.isomorphism_swap_river = tables.isomorphism_swap_river;
+        self.init_split_pot_strength();
+    }
 
-        (
-            self.isomorphism_ref_turn,
-            self.isomorphism_card_turn,
-            self.isomorphism_swap_turn,
-            self.isomorphism_ref_river,
-            self.isomorphism_card_river,
-            self.isomorphism_swap_river,
-        ) = self.card_config.isomorphism(&self.private_cards);
+    /// Computes the showdown strengths of the split-pot game, if any.
+    fn init_split_pot_strength(&mut self) {
+        self.split_pot_strength = match &self.split_pot_evaluator {
+            Some(evaluator) => self
+                .card_config
+                .split_pot_strength(&self.private_cards, evaluator.as_ref()),
+            None => Vec::new(),
+        };
+    }
+
+    /// Computes the card tables depending on the board and the private hands.
+    fn compute_card_tables(&self) -> CardTables {
+        let (valid_indices_flop, valid_indices_turn, valid_indices_river) =
+            self.card_config.valid_indices(&self.private_cards);
+
+        let hand_strength = self
+            .card_config
+            .hand_strength(&self.private_cards, self.hand_evaluator());
+
+        let (
+            isomorphism_ref_turn,
+            isomorphism_card_turn,
+            isomorphism_swap_turn,
+            isomorphism_ref_river,
+            isomorphism_card_river,
+            isomorphism_swap_river,
+        ) = self
+            .card_config
+            .isomorphism(&self.private_cards, self.isomorphism_mode);
+
+        CardTables {
+            valid_indices_flop,
+            valid_indices_turn,
+            valid_indices_river,
+            hand_strength,
+            isomorphism_ref_turn,
+            isomorphism_card_turn,
+            isomorphism_swap_turn,
+            isomorphism_ref_river,
+            isomorphism_card_river,
+            isomorphism_swap_river,
+        }
     }
 
     /// Initializes the root node of game tree.
-    fn init_root(&mut self) -> Result<(), String> {
+    fn init_root(&mut self) -> Result<(), SolverError> {
         let num_nodes = self.count_num_nodes();
         let total_num_nodes = num_nodes[0] + num_nodes[1] + num_nodes[2];
 
         if total_num_nodes > u32::MAX as u64
             || mem::size_of::<PostFlopNode>() as u64 * total_num_nodes > isize::MAX as u64
         {
-            return Err("Too many nodes".to_owned());
+            return Err(SolverError::InvalidTreeConfig("Too many nodes".to_owned()));
         }
 
         self.num_nodes = num_nodes;
-        self.node_arena = (0..total_num_nodes)
-            .map(|_| MutexLike::new(PostFlopNode::default()))
-            .collect::<Vec<_>>();
+        self.node_arena = Vec::new(); // release the old arena first
+        self.node_arena =
+            PostFlopNode::zeroed_arena(total_num_nodes as usize).ok_or_else(|| {
+                SolverError::AllocationFailed {
+                    required: mem::size_of::<PostFlopNode>() as u64 * total_num_nodes,
+                    context: "the node arena".to_owned(),
+                    message: "Failed to allocate memory".to_owned(),
+                }
+            })?;
         self.clear_storage();
 
         let mut info = BuildTreeInfo {
@@ -558,11 +1576,12 @@ impl PostFlopGame {
             BoardState::River => info.river_index += 1,
         }
 
+        // the arena is zeroed, so `turn` and `river` are set for every node while building
         let mut root = self.node_arena[0].lock();
         root.turn = self.card_config.turn;
         root.river = self.card_config.river;
 
-        self.build_tree_recursive(0, &self.action_root.lock(), &mut info);
+        self.build_tree_recursive(0, 0, &mut info);
 
         self.num_storage = info.num_storage;
         self.num_storage_ip = info.num_storage_ip;
@@ -574,11 +1593,12 @@ impl PostFlopGame {
 
     /// Initializes the ICM calculator.
     #[inline]
-    fn init_icm_calculator(&mut self) {
+    pub(super) fn init_icm_calculator(&mut self) {
         if let Some(ref icm_config) = self.tree_config.icm_config {
             self.icm_calculator = Some(icm::ICMCalculator::new(
                 icm_config.other_players_stacks.clone(),
                 icm_config.payout_structure.clone(),
+                self.icm_seed,
             ));
             self.tree_config.effective_stack =
                 icm_config.player_stacks[0].min(icm_config.player_stacks[1]);
@@ -603,10 +1623,68 @@ impl PostFlopGame {
     /// Clears the storage.
     #[inline]
     fn clear_storage(&mut self) {
-        self.storage1 = Vec::new();
-        self.storage2 = Vec::new();
-        self.storage_ip = Vec::new();
-        self.storage_chance = Vec::new();
+        self.storage1 = Storage::default();
+        self.storage2 = Storage::default();
+        self.storage_ip = Storage::default();
+        self.storage_chance = Storage::default();
+    }
+
+    /// Returns the number of nodes of the streets up to `street`. Nodes are arranged in the arena
+    /// in the order of flop, turn, and river.
+    #[inline]
+    pub(super) fn num_nodes_up_to(&self, street: BoardState) -> usize {
+        match street {
+            BoardState::Flop => self.num_nodes[0] as usize,
+            BoardState::Turn => (self.num_nodes[0] + self.num_nodes[1]) as usize,
+            BoardState::River => self.node_arena.len(),
+        }
+    }
+
+    /// Returns whether the node arena contains the nodes of all streets.
+    #[inline]
+    fn is_tree_complete(&self) -> bool {
+        self.node_arena.len() as u64 == self.num_nodes.iter().sum::<u64>()
+    }
+
+    /// Returns the sizes in bytes of `storage1`, `storage2`, `storage_ip`, and `storage_chance`
+    /// needed for the nodes up to `street`.
+    pub(super) fn storage_bytes_up_to(&self, street: BoardState) -> [usize; 4] {
+        let num_bytes = if self.is_compression_enabled { 2 } else { 4 };
+        let strategy_num_bytes = self.strategy_num_bytes();
+        let mut sizes = [0; 4];
+
+        for node in &self.node_arena[..self.num_nodes_up_to(street)] {
+            let node = node.lock();
+            if node.is_terminal() {
+                // do nothing
+            } else if node.is_chance() {
+                sizes[3] += num_bytes * node.num_elements as usize;
+            } else {
+                sizes[0] += strategy_num_bytes * node.num_elements as usize;
+                sizes[1] += num_bytes * node.num_elements as usize;
+                sizes[2] += num_bytes * node.num_elements_ip as usize;
+            }
+        }
+
+        sizes
+    }
+
+    /// Returns the number of bytes per element of the strategy storage.
+    #[inline]
+    pub(super) fn strategy_num_bytes(&self) -> usize {
+        if self.is_strategy_quantized {
+            1
+        } else if self.is_compression_enabled {
+            2
+        } else {
+            4
+        }
+    }
+
+    /// Returns the number of dead cards specified in the card configuration.
+    #[inline]
+    pub(super) fn num_dead_cards(&self) -> usize {
+        self.card_config.dead_mask().count_ones() as usize
     }
 
     /// Counts the number of nodes in the game tree.
@@ -618,19 +1696,26 @@ impl PostFlopGame {
                 let flop = self.card_config.flop;
                 let skip_cards = &self.isomorphism_card_turn;
                 let flop_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+                let dead_mask = self.card_config.dead_mask();
                 let skip_mask: u64 = skip_cards.iter().map(|&card| 1 << card).sum();
                 for turn in 0..52 {
-                    if (1 << turn) & (flop_mask | skip_mask) == 0 {
-                        river_coef += 48 - self.isomorphism_card_river[turn & 3].len();
+                    if (1 << turn) & (flop_mask | dead_mask | skip_mask) == 0 {
+                        river_coef += 48
+                            - self.num_dead_cards()
+                            - self.isomorphism_card_river[turn & 3].len();
                     }
                 }
-                (49 - self.isomorphism_card_turn.len(), river_coef)
+                let turn_coef = 49 - self.num_dead_cards() - self.isomorphism_card_turn.len();
+                (turn_coef, river_coef)
+            }
+            (turn, NOT_DEALT) => {
+                let skip_cards = &self.isomorphism_card_river[turn as usize & 3];
+                (1, 48 - self.num_dead_cards() - skip_cards.len())
             }
-            (turn, NOT_DEALT) => (1, 48 - self.isomorphism_card_river[turn as usize & 3].len()),
             _ => (0, 1),
         };
 
-        let num_action_nodes = count_num_action_nodes(&self.action_root.lock());
+        let num_action_nodes = count_num_action_nodes(&self.action_nodes);
 
         [
             num_action_nodes[0],
@@ -641,8 +1726,8 @@ impl PostFlopGame {
 
     /// Computes the memory usage of this struct.
     #[inline]
-    fn memory_usage_internal(&self) -> u64 {
-        // untracked: tree_config, action_root
+    pub(super) fn memory_usage_internal(&self) -> u64 {
+        // untracked: tree_config, action_nodes
 
         let mut memory_usage = mem::size_of::<Self>() as u64;
 
@@ -658,6 +1743,7 @@ impl PostFlopGame {
         memory_usage += vec_memory_usage(&self.valid_indices_turn);
         memory_usage += vec_memory_usage(&self.valid_indices_river);
         memory_usage += vec_memory_usage(&self.hand_strength);
+        memory_usage += vec_memory_usage(&self.split_pot_strength);
         memory_usage += vec_memory_usage(&self.isomorphism_ref_turn);
         memory_usage += vec_memory_usage(&self.isomorphism_card_turn);
         memory_usage += vec_memory_usage(&self.isomorphism_ref_river);
@@ -684,6 +1770,11 @@ impl PostFlopGame {
             for strength in &self.hand_strength {
                 memory_usage += vec_memory_usage(&strength[player]);
             }
+            for strength in &self.split_pot_strength {
+                memory_usage += vec_memory_usage(&strength[player].high);
+                memory_usage += vec_memory_usage(&strength[player].high_no_low);
+                memory_usage += vec_memory_usage(&strength[player].low);
+            }
             for swap in &self.isomorphism_swap_turn {
                 memory_usage += vec_memory_usage(&swap[player]);
             }
@@ -703,12 +1794,14 @@ impl PostFlopGame {
     fn build_tree_recursive(
         &self,
         node_index: usize,
-        action_node: &ActionTreeNode,
+        action_index: usize,
         info: &mut BuildTreeInfo,
     ) {
         let mut node = self.node_arena[node_index].lock();
+        let action_node = self.action_nodes.node(action_index);
         node.player = action_node.player;
         node.amount = action_node.amount;
+        let action_children = self.action_nodes.children(action_index);
 
         if node.is_terminal() {
             return;
@@ -718,17 +1811,13 @@ impl PostFlopGame {
             self.push_chances(node_index, info);
             for action_index in 0..node.num_actions() {
                 let child_index = node_index + node.children_offset as usize + action_index;
-                self.build_tree_recursive(child_index, &action_node.children[0].lock(), info);
+                self.build_tree_recursive(child_index, action_children.start, info);
             }
         } else {
-            self.push_actions(node_index, action_node, info);
-            for action_index in 0..node.num_actions() {
-                let child_index = node_index + node.children_offset as usize + action_index;
-                self.build_tree_recursive(
-                    child_index,
-                    &action_node.children[action_index].lock(),
-                    info,
-                );
+            self.push_actions(node_index, action_index, info);
+            for (i, action_child) in action_children.enumerate() {
+                let child_index = node_index + node.children_offset as usize + i;
+                self.build_tree_recursive(child_index, action_child, info);
             }
         }
     }
@@ -737,7 +1826,8 @@ impl PostFlopGame {
     fn push_chances(&self, node_index: usize, info: &mut BuildTreeInfo) {
         let mut node = self.node_arena[node_index].lock();
         let flop = self.card_config.flop;
-        let flop_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+        let mut flop_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+        flop_mask |= self.card_config.dead_mask();
 
         // deal turn
         if node.turn == NOT_DEALT {
@@ -751,6 +1841,7 @@ impl PostFlopGame {
                     let mut child = node.children().last().unwrap().lock();
                     child.prev_action = Action::Chance(card);
                     child.turn = card;
+                    child.river = NOT_DEALT;
                 }
             }
 
@@ -784,13 +1875,9 @@ impl PostFlopGame {
     }
 
     /// Pushes the actions to the `node`.
-    fn push_actions(
-        &self,
-        node_index: usize,
-        action_node: &ActionTreeNode,
-        info: &mut BuildTreeInfo,
-    ) {
+    fn push_actions(&self, node_index: usize, action_index: usize, info: &mut BuildTreeInfo) {
         let mut node = self.node_arena[node_index].lock();
+        let actions = self.action_nodes.actions(action_index);
 
         let street = match (node.turn, node.river) {
             (NOT_DEALT, _) => BoardState::Flop,
@@ -805,10 +1892,10 @@ impl PostFlopGame {
         };
 
         node.children_offset = (*base - node_index) as u32;
-        node.num_children = action_node.children.len() as u16;
+        node.num_children = actions.len() as u16;
         *base += node.num_children as usize;
 
-        for (child, action) in node.children().iter().zip(action_node.actions.iter()) {
+        for (child, action) in node.children().iter().zip(actions) {
             let mut child = child.lock();
             child.prev_action = *action;
             child.turn = node.turn;
@@ -827,7 +1914,10 @@ impl PostFlopGame {
     }
 
     /// Sets the bunching effect.
-    fn set_bunching_effect_internal(&mut self, bunching_data: &BunchingData) -> Result<(), String> {
+    fn set_bunching_effect_internal(
+        &mut self,
+        bunching_data: &BunchingData,
+    ) -> Result<(), SolverError> {
         self.bunching_num_dead_cards = bunching_data.fold_ranges().len() * 2;
         let mut arena = vec![0.0]; // store dummy element
 
@@ -882,7 +1972,9 @@ impl PostFlopGame {
                     self.bunching_num_combinations = arena.iter().fold(0.0, |a, &x| a + x as f64);
                     if self.bunching_num_combinations == 0.0 {
                         self.reset_bunching_effect();
-                        return Err("Valid combination not found".to_owned());
+                        return Err(SolverError::InvalidCardConfig(
+                            "Valid combination not found".to_owned(),
+                        ));
                     }
                 }
 
@@ -942,7 +2034,9 @@ impl PostFlopGame {
                     self.bunching_num_combinations = arena.iter().fold(0.0, |a, &x| a + x as f64);
                     if self.bunching_num_combinations == 0.0 {
                         self.reset_bunching_effect();
-                        return Err("Valid combination not found".to_owned());
+                        return Err(SolverError::InvalidCardConfig(
+                            "Valid combination not found".to_owned(),
+                        ));
                     }
                 }
             }
@@ -1006,7 +2100,9 @@ impl PostFlopGame {
                 self.bunching_num_combinations = arena.iter().fold(0.0, |a, &x| a + x as f64);
                 if self.bunching_num_combinations == 0.0 {
                     self.reset_bunching_effect();
-                    return Err("Valid combination not found".to_owned());
+                    return Err(SolverError::InvalidCardConfig(
+                        "Valid combination not found".to_owned(),
+                    ));
                 }
             }
         }
@@ -1209,7 +2305,7 @@ impl PostFlopGame {
     }
 
     /// Sets the bunching effect.
-    fn memory_usage_bunching_internal(&self) -> u64 {
+    pub(super) fn memory_usage_bunching_internal(&self) -> u64 {
         let mut ret = 4;
 
         let oop_len = self.num_private_hands(0);
@@ -1440,15 +2536,22 @@ impl PostFlopGame {
     }
 
     /// Allocates memory recursively.
-    fn allocate_memory_nodes(&mut self) {
+    fn allocate_memory_nodes(&mut self, street: BoardState) {
         let num_bytes = if self.is_compression_enabled { 2 } else { 4 };
+        let strategy_num_bytes = self.strategy_num_bytes();
+        let mut strategy_counter = 0;
         let mut action_counter = 0;
         let mut ip_counter = 0;
         let mut chance_counter = 0;
+        let num_nodes = self.num_nodes_up_to(street);
 
-        for node in &self.node_arena {
+        for (index, node) in self.node_arena.iter().enumerate() {
             let mut node = node.lock();
-            if node.is_terminal() {
+            if index >= num_nodes {
+                node.storage1 = ptr::null_mut();
+                node.storage2 = ptr::null_mut();
+                node.storage3 = ptr::null_mut();
+            } else if node.is_terminal() {
                 // do nothing
             } else if node.is_chance() {
                 unsafe {
@@ -1461,13 +2564,17 @@ impl PostFlopGame {
                     let ptr1 = self.storage1.as_mut_ptr();
                     let ptr2 = self.storage2.as_mut_ptr();
                     let ptr3 = self.storage_ip.as_mut_ptr();
-                    node.storage1 = ptr1.add(action_counter);
+                    node.storage1 = ptr1.add(strategy_counter);
                     node.storage2 = ptr2.add(action_counter);
                     node.storage3 = ptr3.add(ip_counter);
                 }
+                strategy_counter += strategy_num_bytes * node.num_elements as usize;
                 action_counter += num_bytes * node.num_elements as usize;
                 ip_counter += num_bytes * node.num_elements_ip as usize;
             }
         }
+
+        #[cfg(all(feature = "numa", target_os = "linux"))]
+        self.place_numa_partitions();
     }
 }
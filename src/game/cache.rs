@@ -0,0 +1,171 @@
+use super::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+/// Default capacity of the cache returned by [`TableCache::global`].
+const GLOBAL_CAPACITY: usize = 256;
+
+/// A cache of the precomputed card tables of [`PostFlopGame`], shared between instances.
+///
+/// Building a game computes tables depending on the board and the private hands, such as the hand
+/// strengths and the suit isomorphism. When the same board and ranges are built repeatedly (e.g.,
+/// re-solving a flop subset with different tree configurations), the tables can be reused by
+/// setting the same cache to the games with [`PostFlopGame::set_table_cache`].
+///
+/// The cache is a handle: cloning it yields another handle to the same cache. When the number of
+/// entries exceeds the capacity, the oldest entry is evicted.
+#[derive(Clone)]
+pub struct TableCache {
+    inner: Arc<Mutex<CacheInner>>,
+}
+
+struct CacheInner {
+    capacity: usize,
+    entries: HashMap<TableKey, Arc<CardTables>>,
+    order: VecDeque<TableKey>,
+    num_hits: u64,
+    num_misses: u64,
+}
+
+/// The inputs of the card tables.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct TableKey {
+    flop: [Card; 3],
+    turn: Card,
+    river: Card,
+    dead_cards: u64,
+    short_deck: bool,
+    isomorphism_mode: IsomorphismMode,
+    private_cards: [Vec<(Card, Card)>; 2],
+}
+
+/// The card tables computed by [`PostFlopGame::init_card_fields`].
+#[derive(Clone)]
+pub(super) struct CardTables {
+    pub(super) valid_indices_flop: [Vec<u16>; 2],
+    pub(super) valid_indices_turn: Vec<[Vec<u16>; 2]>,
+    pub(super) valid_indices_river: Vec<[Vec<u16>; 2]>,
+    pub(super) hand_strength: Vec<[Vec<StrengthItem>; 2]>,
+    pub(super) isomorphism_ref_turn: Vec<u8>,
+    pub(super) isomorphism_card_turn: Vec<Card>,
+    pub(super) isomorphism_swap_turn: [SwapList; 4],
+    pub(super) isomorphism_ref_river: Vec<Vec<u8>>,
+    pub(super) isomorphism_card_river: [Vec<Card>; 4],
+    pub(super) isomorphism_swap_river: [[SwapList; 4]; 4],
+}
+
+impl TableCache {
+    /// Creates a new cache holding the tables of up to `capacity` card configurations.
+    ///
+    /// Returns [`SolverError::InvalidArgument`] if `capacity` is zero.
+    pub fn new(capacity: usize) -> Result<Self, SolverError> {
+        if capacity == 0 {
+            return Err(SolverError::InvalidArgument(
+                "Capacity must be positive".to_owned(),
+            ));
+        }
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(CacheInner {
+                capacity,
+                entries: HashMap::new(),
+                order: VecDeque::new(),
+                num_hits: 0,
+                num_misses: 0,
+            })),
+        })
+    }
+
+    /// Returns a handle to the process-wide cache, which holds up to 256 card configurations.
+    pub fn global() -> Self {
+        static GLOBAL: OnceLock<TableCache> = OnceLock::new();
+        GLOBAL
+            .get_or_init(|| Self::new(GLOBAL_CAPACITY).unwrap())
+            .clone()
+    }
+
+    /// Returns the number of cached card configurations.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Returns whether the cache is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the numbers of hits and misses so far.
+    #[inline]
+    pub fn stats(&self) -> (u64, u64) {
+        let inner = self.lock();
+        (inner.num_hits, inner.num_misses)
+    }
+
+    /// Removes all entries. The statistics are kept.
+    #[inline]
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        inner.entries.clear();
+        inner.order.clear();
+    }
+
+    /// Returns whether `self` and `other` are handles to the same cache.
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Returns the tables of `game`, computing them with `compute` if they are not cached.
+    ///
+    /// The lock is not held while computing, so that games with different configurations can be
+    /// built concurrently; the same tables may then be computed more than once.
+    pub(super) fn get_or_compute(
+        &self,
+        game: &PostFlopGame,
+        compute: impl FnOnce() -> CardTables,
+    ) -> Arc<CardTables> {
+        let config = &game.card_config;
+        let key = TableKey {
+            flop: config.flop,
+            turn: config.turn,
+            river: config.river,
+            dead_cards: config.dead_cards,
+            short_deck: config.short_deck,
+            isomorphism_mode: game.isomorphism_mode,
+            private_cards: game.private_cards.clone(),
+        };
+
+        {
+            let mut inner = self.lock();
+            if let Some(tables) = inner.entries.get(&key) {
+                let tables = Arc::clone(tables);
+                inner.num_hits += 1;
+                return tables;
+            }
+            inner.num_misses += 1;
+        }
+
+        let tables = Arc::new(compute());
+
+        let mut inner = self.lock();
+        if !inner.entries.contains_key(&key) {
+            if inner.entries.len() == inner.capacity
+                && let Some(oldest) = inner.order.pop_front()
+            {
+                inner.entries.remove(&oldest);
+            }
+            inner.order.push_back(key.clone());
+            inner.entries.insert(key, Arc::clone(&tables));
+        }
+
+        tables
+    }
+
+    #[inline]
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheInner> {
+        // the cache is always consistent, so a poisoned lock can be recovered
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
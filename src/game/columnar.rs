@@ -0,0 +1,177 @@
+use super::export::*;
+use super::*;
+use crate::range::*;
+use arrow_array::builder::{Float32Builder, StringBuilder, UInt8Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Default number of rows of each record batch written by [`PostFlopGame::export_parquet`].
+const PARQUET_BATCH_ROWS: usize = 1 << 16;
+
+/// Returns the schema of the strategy tables produced by [`PostFlopGame::export_arrow`] and
+/// [`PostFlopGame::export_parquet`].
+///
+/// The columns are the same as the CSV format of [`PostFlopGame::export_strategy`]: `line`,
+/// `street`, `board`, `hand`, and `action` (strings), `player` (`u8`), and `weight`, `frequency`,
+/// and `ev` (`f32`; `ev` is null if the game is not solved).
+pub fn strategy_table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("line", DataType::Utf8, false),
+        Field::new("street", DataType::Utf8, false),
+        Field::new("board", DataType::Utf8, false),
+        Field::new("player", DataType::UInt8, false),
+        Field::new("hand", DataType::Utf8, false),
+        Field::new("weight", DataType::Float32, false),
+        Field::new("action", DataType::Utf8, false),
+        Field::new("frequency", DataType::Float32, false),
+        Field::new("ev", DataType::Float32, true),
+    ]))
+}
+
+/// Accumulates the rows of exported nodes into a record batch.
+struct StrategyBatchBuilder {
+    line: StringBuilder,
+    street: StringBuilder,
+    board: StringBuilder,
+    player: UInt8Builder,
+    hand: StringBuilder,
+    weight: Float32Builder,
+    action: StringBuilder,
+    frequency: Float32Builder,
+    ev: Float32Builder,
+    num_rows: usize,
+}
+
+impl StrategyBatchBuilder {
+    fn new() -> Self {
+        Self {
+            line: StringBuilder::new(),
+            street: StringBuilder::new(),
+            board: StringBuilder::new(),
+            player: UInt8Builder::new(),
+            hand: StringBuilder::new(),
+            weight: Float32Builder::new(),
+            action: StringBuilder::new(),
+            frequency: Float32Builder::new(),
+            ev: Float32Builder::new(),
+            num_rows: 0,
+        }
+    }
+
+    fn push_node(&mut self, private_cards: &[(Card, Card)], node: &ExportNode) {
+        let num_hands = private_cards.len();
+        let street = street_to_str(node.street);
+
+        for (i, &hand) in node.hands.iter().enumerate() {
+            let hand_str = hole_to_string(private_cards[hand]).unwrap();
+            for (action_index, action) in node.actions.iter().enumerate() {
+                let index = action_index * num_hands + hand;
+                self.line.append_value(&node.line);
+                self.street.append_value(street);
+                self.board.append_value(&node.board);
+                self.player.append_value(node.player as u8);
+                self.hand.append_value(&hand_str);
+                self.weight.append_value(node.weights[i]);
+                self.action.append_value(action.to_string());
+                self.frequency.append_value(node.strategy[index]);
+                self.ev
+                    .append_option(node.ev_detail.as_ref().map(|ev| ev[index]));
+                self.num_rows += 1;
+            }
+        }
+    }
+
+    /// Builds a record batch of the accumulated rows and resets the builder.
+    fn finish(&mut self) -> Result<RecordBatch, SolverError> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.line.finish()),
+            Arc::new(self.street.finish()),
+            Arc::new(self.board.finish()),
+            Arc::new(self.player.finish()),
+            Arc::new(self.hand.finish()),
+            Arc::new(self.weight.finish()),
+            Arc::new(self.action.finish()),
+            Arc::new(self.frequency.finish()),
+            Arc::new(self.ev.finish()),
+        ];
+        self.num_rows = 0;
+        RecordBatch::try_new(strategy_table_schema(), columns)
+            .map_err(|e| SolverError::Other(format!("Failed to build record batch: {e}")))
+    }
+}
+
+impl PostFlopGame {
+    /// Walks the game tree and passes the strategy of every player node to `f` as Apache Arrow
+    /// record batches of [`strategy_table_schema`].
+    ///
+    /// The nodes are visited as in [`export_strategy`] (`options.format` is ignored), with one row
+    /// per node, private hand, and action. Each record batch contains whole nodes and at least
+    /// `batch_rows` rows, except the last one. No batch is passed if no node is visited.
+    ///
+    /// The current node is restored after the call.
+    ///
+    /// [`export_strategy`]: PostFlopGame::export_strategy
+    pub fn export_arrow<F>(
+        &mut self,
+        options: &ExportOptions,
+        batch_rows: usize,
+        mut f: F,
+    ) -> Result<(), SolverError>
+    where
+        F: FnMut(RecordBatch) -> Result<(), SolverError>,
+    {
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+
+        // validates the history before passing anything
+        let original_history = self.history().to_vec();
+        let result = self.line_from_history(&options.history);
+        self.apply_history(&original_history);
+        result?;
+
+        let mut builder = StrategyBatchBuilder::new();
+        self.walk_player_nodes(&options.history, options.street, &mut |game, _, line| {
+            let node = game.export_node(line);
+            builder.push_node(&game.private_cards[node.player], &node);
+            if builder.num_rows >= batch_rows {
+                f(builder.finish()?)?;
+            }
+            Ok(())
+        })?;
+
+        if builder.num_rows > 0 {
+            f(builder.finish()?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Walks the game tree and writes the strategy of every player node as a Parquet file of
+    /// [`strategy_table_schema`] (see [`export_arrow`]).
+    ///
+    /// The columns are stored without compression but with dictionary encoding, which keeps the
+    /// repeated strings small.
+    ///
+    /// [`export_arrow`]: PostFlopGame::export_arrow
+    pub fn export_parquet<W: Write + Send>(
+        &mut self,
+        writer: W,
+        options: &ExportOptions,
+    ) -> Result<(), SolverError> {
+        let parquet_err =
+            |e: parquet::errors::ParquetError| SolverError::Other(format!("Failed to write: {e}"));
+        let mut writer =
+            ArrowWriter::try_new(writer, strategy_table_schema(), None).map_err(parquet_err)?;
+        self.export_arrow(options, PARQUET_BATCH_ROWS, |batch| {
+            writer.write(&batch).map_err(parquet_err)
+        })?;
+        writer.close().map_err(parquet_err)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+use super::*;
+use crate::interface::*;
+use std::cmp::Ordering;
+
+/// A private hand in a betting range with its equity against the continuing range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComboEquity {
+    /// Hole cards.
+    pub hand: (Card, Card),
+
+    /// Weight of the hand in the betting range (normalized weight times the betting frequency).
+    pub weight: f32,
+
+    /// Equity against the continuing range of the opponent.
+    pub equity: f32,
+}
+
+/// Value/bluff composition of the range of a bet size, returned by
+/// [`PostFlopGame::value_bluff_report`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BetComposition {
+    /// Index of the bet or raise action.
+    pub action_index: usize,
+
+    /// The bet or raise action.
+    pub action: Action,
+
+    /// Number of value combos, weighted by the betting frequency.
+    pub value_combos: f32,
+
+    /// Number of bluff combos, weighted by the betting frequency.
+    pub bluff_combos: f32,
+
+    /// Share of bluffs in the betting range, i.e., `bluff_combos / (value_combos +
+    /// bluff_combos)`. `NaN` if the action is never taken.
+    pub bluff_share: f32,
+
+    /// Share of bluffs that makes the opponent indifferent to calling (see [`BetOdds`]).
+    pub break_even_bluff_share: f32,
+
+    /// Value hands sorted by equity in descending order.
+    pub value_hands: Vec<ComboEquity>,
+
+    /// Bluff hands sorted by equity in descending order.
+    pub bluff_hands: Vec<ComboEquity>,
+}
+
+impl PostFlopGame {
+    /// Splits the range of each bet size at the current river node into value hands and bluffs.
+    ///
+    /// For each bet or raise action, the betting range is the normalized weights of the current
+    /// player multiplied by the frequency of the action. The continuing range of the opponent is
+    /// the range reaching the node after the bet multiplied by the frequency of not folding
+    /// (calling or raising). A hand is regarded as value if its equity against the continuing range
+    /// is at least 50%, and as a bluff otherwise. If the opponent never continues, the equity is
+    /// computed against the whole range of the opponent instead. Hands that never take the action
+    /// are omitted.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the game is not solved, the current node is not a
+    /// river node of a player, or a split-pot evaluator is set (see [`set_split_pot_evaluator`]).
+    /// The current node is restored after the call.
+    ///
+    /// **Time complexity:** *O*(#(bet actions) * #(OOP private hands) * #(IP private hands)).
+    ///
+    /// [`set_split_pot_evaluator`]: #method.set_split_pot_evaluator
+    pub fn value_bluff_report(&mut self) -> Result<Vec<BetComposition>, SolverError> {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+        if self.split_pot_evaluator.is_some() {
+            return Err(SolverError::InvalidState(
+                "Split-pot games are not supported".to_owned(),
+            ));
+        }
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(SolverError::InvalidState(
+                "Current node is not a player node".to_owned(),
+            ));
+        }
+
+        let board = self.current_board();
+        if board.len() != 5 {
+            return Err(SolverError::InvalidState(
+                "Current node is not a river node".to_owned(),
+            ));
+        }
+
+        let history = self.history().to_vec();
+        self.cache_normalized_weights();
+
+        let player = self.current_player();
+        let opponent = player ^ 1;
+        let actions = self.available_actions();
+        let num_hands = self.num_private_hands(player);
+        let weights = self.normalized_weights(player).to_vec();
+        let strategy = self.strategy();
+        let bet_odds = self.pot_odds().bets;
+
+        let board: [Card; 5] = board.try_into().unwrap();
+        let evaluator = self.hand_evaluator();
+        let strength = |p: usize| {
+            self.private_cards[p]
+                .iter()
+                .map(|&hand| evaluator.evaluate(&board, hand))
+                .collect::<Vec<_>>()
+        };
+        let player_strength = strength(player);
+        let opponent_strength = strength(opponent);
+
+        let mut result = Vec::new();
+
+        for odds in bet_odds {
+            let action_index = odds.action_index;
+
+            // continuing range of the opponent
+            let mut history_after_bet = history.clone();
+            history_after_bet.push(action_index);
+            self.apply_history(&history_after_bet);
+            let opponent_weights = self.weights(opponent).to_vec();
+            let continue_weights = if self.is_terminal_node() {
+                opponent_weights.clone()
+            } else {
+                let opponent_strategy = self.strategy();
+                let num_opponent_hands = opponent_weights.len();
+                let fold_index = self
+                    .available_actions()
+                    .iter()
+                    .position(|&a| a == Action::Fold);
+                opponent_weights
+                    .iter()
+                    .enumerate()
+                    .map(|(j, &w)| match fold_index {
+                        Some(f) => w * (1.0 - opponent_strategy[f * num_opponent_hands + j]),
+                        None => w,
+                    })
+                    .collect()
+            };
+            self.apply_history(&history);
+
+            let mut value_hands = Vec::new();
+            let mut bluff_hands = Vec::new();
+
+            for hand in 0..num_hands {
+                let weight = weights[hand] * strategy[action_index * num_hands + hand];
+                if weight <= 0.0 {
+                    continue;
+                }
+
+                let equity = self
+                    .equity_against(
+                        hand,
+                        player,
+                        &player_strength,
+                        &opponent_strength,
+                        &continue_weights,
+                    )
+                    .or_else(|| {
+                        self.equity_against(
+                            hand,
+                            player,
+                            &player_strength,
+                            &opponent_strength,
+                            &opponent_weights,
+                        )
+                    })
+                    .unwrap_or(0.0);
+
+                let combo = ComboEquity {
+                    hand: self.private_cards[player][hand],
+                    weight,
+                    equity,
+                };
+                if equity >= 0.5 {
+                    value_hands.push(combo);
+                } else {
+                    bluff_hands.push(combo);
+                }
+            }
+
+            let by_equity = |a: &ComboEquity, b: &ComboEquity| b.equity.total_cmp(&a.equity);
+            value_hands.sort_by(by_equity);
+            bluff_hands.sort_by(by_equity);
+
+            let value_combos = value_hands.iter().fold(0.0, |acc, c| acc + c.weight);
+            let bluff_combos = bluff_hands.iter().fold(0.0, |acc, c| acc + c.weight);
+
+            result.push(BetComposition {
+                action_index,
+                action: actions[action_index],
+                value_combos,
+                bluff_combos,
+                bluff_share: bluff_combos / (value_combos + bluff_combos),
+                break_even_bluff_share: odds.bluff_share,
+                value_hands,
+                bluff_hands,
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Computes the showdown equity of `hand` of `player` against the opponent range given by
+    /// `opponent_weights`. Returns `None` if the opponent range is empty.
+    fn equity_against(
+        &self,
+        hand: usize,
+        player: usize,
+        player_strength: &[u32],
+        opponent_strength: &[u32],
+        opponent_weights: &[f32],
+    ) -> Option<f32> {
+        let (c1, c2) = self.private_cards[player][hand];
+        let mut weight_sum = 0.0;
+        let mut equity_sum = 0.0;
+
+        for (j, &w) in opponent_weights.iter().enumerate() {
+            let (c3, c4) = self.private_cards[player ^ 1][j];
+            if w <= 0.0 || c1 == c3 || c1 == c4 || c2 == c3 || c2 == c4 {
+                continue;
+            }
+            let w = w as f64;
+            weight_sum += w;
+            equity_sum += match player_strength[hand].cmp(&opponent_strength[j]) {
+                Ordering::Greater => w,
+                Ordering::Equal => 0.5 * w,
+                Ordering::Less => 0.0,
+            };
+        }
+
+        (weight_sum > 0.0).then(|| (equity_sum / weight_sum) as f32)
+    }
+}
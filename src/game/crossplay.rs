@@ -0,0 +1,237 @@
+use super::*;
+use crate::range::*;
+
+/// Expected values of every combo of a player against the strategy of the opponent at a node,
+/// returned by [`PostFlopGame::crossplay_ev_matrix`].
+///
+/// The expected values follow the convention of [`PostFlopGame::expected_values`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossplayEvMatrix {
+    /// Player of the node.
+    pub player: usize,
+
+    /// Available actions at the node.
+    pub actions: Vec<Action>,
+
+    /// Every combo that does not conflict with the current board and the dead cards.
+    pub hands: Vec<(Card, Card)>,
+
+    /// Weight of each combo at the node in the solved game, i.e., the initial weight multiplied by
+    /// the reach probability of the player's own actions (see [`PostFlopGame::weights`]). `0` if
+    /// the combo is not in the range or never reaches the node.
+    pub reach: Vec<f32>,
+
+    /// Expected value of each action of each combo. The value of the `i`-th action with the `j`-th
+    /// combo is stored in the `i * #(hands) + j`-th element.
+    pub action_evs: Vec<f32>,
+
+    /// Expected value of each combo, i.e., the highest expected value among the actions.
+    pub ev: Vec<f32>,
+}
+
+impl CrossplayEvMatrix {
+    /// Returns the index of `hand` in [`hands`], if any.
+    ///
+    /// [`hands`]: CrossplayEvMatrix::hands
+    #[inline]
+    pub fn hand_index(&self, hand: (Card, Card)) -> Option<usize> {
+        let (c1, c2) = (hand.0.min(hand.1), hand.0.max(hand.1));
+        self.hands.iter().position(|&h| h == (c1, c2))
+    }
+}
+
+impl PostFlopGame {
+    /// Computes the expected values of every combo of the current player at the current node,
+    /// including the combos outside the range or that never reach the node, against the strategy
+    /// of the opponent.
+    ///
+    /// The solved strategy of a player only covers the combos of the range, and the expected
+    /// values of the combos that the strategy never brings to the node are zero. This method
+    /// answers "what if I arrived here with this hand" for any combo: each combo takes each action
+    /// at the node and then plays the best response against the strategy of the opponent, which is
+    /// taken as is across the whole tree. The range of the opponent at the node, and thus the card
+    /// removal by each combo, is that of the solved game.
+    ///
+    /// The values are computed on an auxiliary game in which the current player holds every combo,
+    /// so this method takes about as much time and memory as [`apply_best_response`]. For a combo
+    /// of the range that reaches the node, the values agree with [`expected_values_detail`] if the
+    /// rest of the strategy of the player is a best response (e.g., when no decision of the player
+    /// follows, or the game is solved to a low exploitability).
+    ///
+    /// The game must be solved. Returns [`SolverError::InvalidState`] if it is not, the current node
+    /// is not a player node, or the bunching effect is enabled, and the error of building the
+    /// auxiliary game if it cannot be built (e.g., the tree was modified by [`remove_lines`]). The
+    /// current node is preserved.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["AA,KK,87s,65s,A5s".parse().unwrap(), "QQ-99,AK,KQs,T9s".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("2c").unwrap(),
+    ///     river: card_from_str("3s").unwrap(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::River,
+    ///     starting_pot: 100,
+    ///     effective_stack: 100,
+    ///     river_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// game.allocate_memory(false);
+    /// solve(&mut game, 100, 0.0, false);
+    ///
+    /// // IP faces a bet: how would a set of sixes, which is not in the range, play?
+    /// game.play(1);
+    /// let matrix = game.crossplay_ev_matrix().unwrap();
+    /// let sixes = (card_from_str("6c").unwrap(), card_from_str("6s").unwrap());
+    /// let index = matrix.hand_index(sixes).unwrap();
+    /// assert_eq!(matrix.reach[index], 0.0);
+    /// assert_eq!(matrix.actions, [Action::Fold, Action::Call]);
+    /// assert!(matrix.ev[index] > matrix.action_evs[index]); // better than folding
+    /// ```
+    ///
+    /// [`apply_best_response`]: #method.apply_best_response
+    /// [`expected_values_detail`]: #method.expected_values_detail
+    /// [`remove_lines`]: #method.remove_lines
+    pub fn crossplay_ev_matrix(&mut self) -> Result<CrossplayEvMatrix, SolverError> {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(SolverError::InvalidState(
+                "Current node is not a player node".to_owned(),
+            ));
+        }
+        if self.bunching_num_dead_cards != 0 {
+            return Err(SolverError::InvalidState(
+                "Bunching effect is not supported".to_owned(),
+            ));
+        }
+
+        let player = self.current_player();
+        let opponent = player ^ 1;
+        let history = self.history().to_vec();
+        let actions = self.available_actions();
+        let reach_of = |game: &Self, hand: (Card, Card)| {
+            game.private_hand_index(player, hand)
+                .map_or(0.0, |index| game.weights[player][index])
+        };
+
+        let mut aux = self.crossplay_game(player)?;
+        aux.allocate_memory(self.is_compression_enabled);
+
+        // the opponent plays its solved strategy everywhere
+        self.walk_player_nodes(&[], None, &mut |game, history, _| {
+            if game.current_player() != opponent {
+                return Ok(());
+            }
+            aux.apply_history(history);
+            if aux.available_actions() != game.available_actions() {
+                return Err(SolverError::InvalidState(
+                    "Auxiliary game has a different tree".to_owned(),
+                ));
+            }
+            aux.lock_current_strategy(&game.strategy());
+            Ok(())
+        })?;
+
+        // every combo of the player reaches the current node
+        for len in 0..history.len() {
+            aux.apply_history(&history[..len]);
+            if aux.is_chance_node() || aux.current_player() != player {
+                continue;
+            }
+            let num_hands = aux.num_private_hands(player);
+            let mut strategy = vec![0.0; aux.available_actions().len() * num_hands];
+            let action = history[len];
+            strategy[action * num_hands..(action + 1) * num_hands].fill(1.0);
+            aux.lock_current_strategy(&strategy);
+        }
+
+        aux.apply_best_response(player)?;
+        aux.apply_history(&history);
+        aux.cache_normalized_weights();
+        let aux_evs = aux.expected_values_detail(player);
+
+        let board_mask = self
+            .current_board()
+            .iter()
+            .fold(0u64, |mask, &card| mask | (1 << card));
+        let num_aux_hands = aux.num_private_hands(player);
+        let indices = (0..num_aux_hands)
+            .filter(|&index| {
+                let (c1, c2) = aux.private_cards[player][index];
+                ((1u64 << c1) | (1u64 << c2)) & board_mask == 0
+            })
+            .collect::<Vec<_>>();
+
+        let hands = indices
+            .iter()
+            .map(|&index| {
+                let (c1, c2) = aux.private_cards[player][index];
+                (c1.min(c2), c1.max(c2))
+            })
+            .collect::<Vec<_>>();
+        let reach = hands.iter().map(|&hand| reach_of(self, hand)).collect();
+
+        let mut action_evs = Vec::with_capacity(actions.len() * hands.len());
+        for action in 0..actions.len() {
+            let row = &aux_evs[action * num_aux_hands..(action + 1) * num_aux_hands];
+            action_evs.extend(indices.iter().map(|&index| row[index]));
+        }
+        let ev = (0..hands.len())
+            .map(|hand| {
+                (0..actions.len())
+                    .map(|action| action_evs[action * hands.len() + hand])
+                    .fold(f32::MIN, f32::max)
+            })
+            .collect();
+
+        Ok(CrossplayEvMatrix {
+            player,
+            actions,
+            hands,
+            reach,
+            action_evs,
+            ev,
+        })
+    }
+
+    /// Builds the game in which `player` holds every combo that does not conflict with the board
+    /// and the dead cards, with the same tree as this game.
+    fn crossplay_game(&self, player: usize) -> Result<Self, SolverError> {
+        let dead_mask = self.card_config.dead_mask();
+        let mut range = Range::new();
+        for c1 in 0..52 {
+            for c2 in c1 + 1..52 {
+                if ((1u64 << c1) | (1u64 << c2)) & dead_mask == 0 {
+                    range.set_weight_by_cards(c1, c2, 1.0);
+                }
+            }
+        }
+
+        let mut card_config = self.card_config.clone();
+        card_config.range[player] = range;
+        let mut game = Self::with_shared_tree(card_config, self)?;
+
+        // the isomorphism must not combine the chance events that this game distinguishes
+        if game.isomorphism_mode != self.isomorphism_mode {
+            game.set_isomorphism_mode(self.isomorphism_mode)?;
+        }
+        if game.isomorphism_card_turn != self.isomorphism_card_turn
+            || game.isomorphism_card_river != self.isomorphism_card_river
+        {
+            game.set_isomorphism_mode(IsomorphismMode::Disabled)?;
+        }
+
+        Ok(game)
+    }
+}
@@ -0,0 +1,153 @@
+use super::*;
+use crate::interface::*;
+use crate::utility::*;
+
+/// Options of [`diff_games`].
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// History from the root node (see [`PostFlopGame::apply_history`]) of the node to start the
+    /// comparison from. Only the subtree of the node is compared.
+    pub history: Vec<usize>,
+
+    /// If `Some`, only the nodes of the given street are compared.
+    pub street: Option<BoardState>,
+
+    /// Nodes are reported if the absolute frequency delta of any action exceeds this value.
+    pub frequency_threshold: f32,
+
+    /// Nodes are reported if the absolute EV delta of the acting player exceeds this value.
+    pub ev_threshold: f32,
+}
+
+/// Difference of a node between two solved games.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeDiff {
+    /// History from the root node.
+    pub history: Vec<usize>,
+
+    /// Actions and dealt cards from the root node.
+    pub line: Vec<Action>,
+
+    /// Player to act at the node (0 = OOP, 1 = IP).
+    pub player: usize,
+
+    /// Available actions at the node.
+    pub actions: Vec<Action>,
+
+    /// Overall frequency of each action in the base game and the other game.
+    pub frequencies: [Vec<f32>; 2],
+
+    /// Frequency delta of each action (other - base).
+    pub frequency_delta: Vec<f32>,
+
+    /// Average expected value of the acting player in the base game and the other game.
+    pub ev: [f32; 2],
+
+    /// Expected value delta of the acting player (other - base).
+    pub ev_delta: f32,
+}
+
+impl NodeDiff {
+    /// Returns the largest absolute frequency delta among the actions.
+    #[inline]
+    pub fn max_frequency_delta(&self) -> f32 {
+        self.frequency_delta
+            .iter()
+            .fold(0.0, |acc: f32, &d| acc.max(d.abs()))
+    }
+}
+
+/// Compares two solved games with identical trees and reports the nodes whose frequencies or
+/// expected values differ by more than the thresholds.
+///
+/// The games may differ in anything that does not change the tree, e.g., ranges, rake, or solver
+/// settings. Frequencies and expected values are aggregated over the range of the acting player
+/// (weighted by the normalized weights of each game), so the ranges need not share the same hands.
+/// Nodes that are not reached in either game are skipped. The nodes are listed in depth-first order,
+/// and the current nodes of both games are restored after the call.
+///
+/// Returns [`SolverError::InvalidState`] if either game is not solved, and
+/// [`SolverError::InvalidArgument`] if the trees do not match.
+///
+/// **Time complexity:** *O*(#(compared nodes) * #(depth) * (#(OOP private hands) + #(IP private
+/// hands))).
+pub fn diff_games(
+    base: &mut PostFlopGame,
+    other: &mut PostFlopGame,
+    options: &DiffOptions,
+) -> Result<Vec<NodeDiff>, SolverError> {
+    if !base.is_solved() || !other.is_solved() {
+        return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+    }
+
+    let other_history = other.history().to_vec();
+    let mut result = Vec::new();
+
+    let walk_result = base.walk_player_nodes(
+        &options.history,
+        options.street,
+        &mut |base, history, line| {
+            if other.line_from_history(history).is_err() {
+                return Err(SolverError::InvalidArgument(format!(
+                    "Trees do not match at line {line:?}"
+                )));
+            }
+
+            let actions = base.available_actions();
+            if other.is_terminal_node()
+                || other.is_chance_node()
+                || other.available_actions() != actions
+            {
+                return Err(SolverError::InvalidArgument(format!(
+                    "Trees do not match at line {line:?}"
+                )));
+            }
+
+            base.cache_normalized_weights();
+            other.cache_normalized_weights();
+
+            let player = base.current_player();
+            let frequencies = [base.action_frequencies(), other.action_frequencies()];
+            if frequencies.iter().flatten().any(|f| f.is_nan()) {
+                return Ok(());
+            }
+
+            let ev = [base, &mut *other].map(|game| {
+                compute_average(
+                    &game.expected_values(player),
+                    game.normalized_weights(player),
+                )
+            });
+
+            let frequency_delta = frequencies[1]
+                .iter()
+                .zip(&frequencies[0])
+                .map(|(&f1, &f0)| f1 - f0)
+                .collect::<Vec<_>>();
+
+            let diff = NodeDiff {
+                history: history.to_vec(),
+                line: line.to_vec(),
+                player,
+                actions,
+                frequencies,
+                frequency_delta,
+                ev,
+                ev_delta: ev[1] - ev[0],
+            };
+
+            if diff.max_frequency_delta() > options.frequency_threshold
+                || diff.ev_delta.abs() > options.ev_threshold
+            {
+                result.push(diff);
+            }
+
+            Ok(())
+        },
+    );
+
+    other.apply_history(&other_history);
+    walk_result?;
+
+    Ok(result)
+}
@@ -0,0 +1,231 @@
+use super::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+#[cfg(feature = "bincode")]
+use bincode::{
+    de::Decoder,
+    enc::Encoder,
+    error::{DecodeError, EncodeError},
+};
+
+/// Default capacity of the cache returned by [`EquityCache::global`].
+const GLOBAL_CAPACITY: usize = 64;
+
+/// A cache of the all-in equities of [`PostFlopGame`], shared between instances and solves.
+///
+/// Valuing an all-in node before the river with [`PostFlopGame::run_it_n`] enumerates all runouts
+/// for each pair of private hands, which dominates its cost. The results depend only on the
+/// board, the dead cards, the private hands of both ranges, and the number of runs, so batch runs
+/// over a set of flops that reach the same all-in spots can reuse them by setting the same cache
+/// to the games with [`PostFlopGame::set_equity_cache`].
+///
+/// The cache is a handle: cloning it yields another handle to the same cache. When the number of
+/// entries exceeds the capacity, the oldest entry is evicted. With the `bincode` feature, the
+/// cache can be saved by `save_data_to_file` and loaded by `load_data_from_file` to persist it
+/// across processes.
+#[derive(Clone)]
+pub struct EquityCache {
+    inner: Arc<Mutex<EquityCacheInner>>,
+}
+
+struct EquityCacheInner {
+    capacity: usize,
+    entries: HashMap<EquityKey, Arc<PairMoments>>,
+    order: VecDeque<EquityKey>,
+    num_hits: u64,
+    num_misses: u64,
+}
+
+/// The inputs of the all-in equities.
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+struct EquityKey {
+    board: Vec<Card>,
+    dead_cards: u64,
+    short_deck: bool,
+    num_runs: usize,
+    private_cards: [Vec<(Card, Card)>; 2],
+}
+
+/// `moments[oop_hand][ip_hand]`: (mean, second moment) of the share of the pot of OOP.
+pub(super) type PairMoments = Vec<Vec<(f64, f64)>>;
+
+impl EquityCache {
+    /// Creates a new cache holding the equities of up to `capacity` all-in spots.
+    ///
+    /// Returns [`SolverError::InvalidArgument`] if `capacity` is zero.
+    pub fn new(capacity: usize) -> Result<Self, SolverError> {
+        if capacity == 0 {
+            return Err(SolverError::InvalidArgument(
+                "Capacity must be positive".to_owned(),
+            ));
+        }
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(EquityCacheInner {
+                capacity,
+                entries: HashMap::new(),
+                order: VecDeque::new(),
+                num_hits: 0,
+                num_misses: 0,
+            })),
+        })
+    }
+
+    /// Returns a handle to the process-wide cache, which holds up to 64 all-in spots.
+    pub fn global() -> Self {
+        static GLOBAL: OnceLock<EquityCache> = OnceLock::new();
+        GLOBAL
+            .get_or_init(|| Self::new(GLOBAL_CAPACITY).unwrap())
+            .clone()
+    }
+
+    /// Returns the maximum number of cached all-in spots.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.lock().capacity
+    }
+
+    /// Returns the number of cached all-in spots.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Returns whether the cache is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the numbers of hits and misses so far.
+    #[inline]
+    pub fn stats(&self) -> (u64, u64) {
+        let inner = self.lock();
+        (inner.num_hits, inner.num_misses)
+    }
+
+    /// Removes all entries. The statistics are kept.
+    #[inline]
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        inner.entries.clear();
+        inner.order.clear();
+    }
+
+    /// Returns whether `self` and `other` are handles to the same cache.
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Returns the estimated memory usage of the cached equities in bytes.
+    pub fn memory_usage(&self) -> u64 {
+        let inner = self.lock();
+        inner
+            .entries
+            .values()
+            .map(|moments| {
+                let num_pairs = moments.iter().map(Vec::len).sum::<usize>();
+                (num_pairs * std::mem::size_of::<(f64, f64)>()) as u64
+            })
+            .sum()
+    }
+
+    /// Returns the moments at the current all-in node of `game` with `num_runs` runs, computing
+    /// them with `compute` if they are not cached.
+    ///
+    /// The lock is not held while computing, so that different spots can be valued concurrently;
+    /// the same moments may then be computed more than once.
+    pub(super) fn get_or_compute(
+        &self,
+        game: &PostFlopGame,
+        num_runs: usize,
+        compute: impl FnOnce() -> PairMoments,
+    ) -> Arc<PairMoments> {
+        let config = &game.card_config;
+        let key = EquityKey {
+            board: game.current_board(),
+            dead_cards: config.dead_cards,
+            short_deck: config.short_deck,
+            num_runs,
+            private_cards: game.private_cards.clone(),
+        };
+
+        {
+            let mut inner = self.lock();
+            if let Some(moments) = inner.entries.get(&key) {
+                let moments = Arc::clone(moments);
+                inner.num_hits += 1;
+                return moments;
+            }
+            inner.num_misses += 1;
+        }
+
+        let moments = Arc::new(compute());
+        self.lock().insert(key, Arc::clone(&moments));
+        moments
+    }
+
+    #[inline]
+    fn lock(&self) -> std::sync::MutexGuard<'_, EquityCacheInner> {
+        // the cache is always consistent, so a poisoned lock can be recovered
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl EquityCacheInner {
+    fn insert(&mut self, key: EquityKey, moments: Arc<PairMoments>) {
+        if self.entries.contains_key(&key) {
+            return;
+        }
+        if self.entries.len() == self.capacity
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.entries.remove(&oldest);
+        }
+        self.order.push_back(key.clone());
+        self.entries.insert(key, moments);
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl Encode for EquityCache {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        // the entries are saved from the oldest, so that the eviction order is preserved
+        let inner = self.lock();
+        inner.capacity.encode(encoder)?;
+        inner.order.len().encode(encoder)?;
+        for key in &inner.order {
+            key.encode(encoder)?;
+            inner.entries[key].as_ref().encode(encoder)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl Decode<()> for EquityCache {
+    fn decode<D: Decoder<Context = ()>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        let capacity: usize = Decode::decode(decoder)?;
+        let ret = Self::new(capacity).map_err(|e| DecodeError::OtherString(e.to_string()))?;
+
+        let num_entries: usize = Decode::decode(decoder)?;
+        {
+            let mut inner = ret.lock();
+            for _ in 0..num_entries {
+                let key: EquityKey = Decode::decode(decoder)?;
+                let moments: PairMoments = Decode::decode(decoder)?;
+                let num_hands = key.private_cards.each_ref().map(Vec::len);
+                if moments.len() != num_hands[0] || moments.iter().any(|v| v.len() != num_hands[1])
+                {
+                    return Err(DecodeError::Other("Equity table has an invalid size"));
+                }
+                inner.insert(key, Arc::new(moments));
+            }
+        }
+
+        Ok(ret)
+    }
+}
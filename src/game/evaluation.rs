@@ -2,9 +2,28 @@ use super::*;
 use crate::sliceop::*;
 use std::mem::MaybeUninit;
 
+/// Maximum number of private hands of a player.
+pub(super) const MAX_NUM_PRIVATE_HANDS: usize = 52 * 51 / 2;
+
+/// Reinterprets the initialized prefix of a buffer.
+///
+/// # Safety
+/// All the elements of `slice` must be initialized.
+#[inline]
+unsafe fn assume_init<T>(slice: &[MaybeUninit<T>]) -> &[T] {
+    unsafe { &*(slice as *const [MaybeUninit<T>] as *const [T]) }
+}
+
+/// Returns the street on which the hand of the terminal `node` ends.
 #[inline]
-fn min(x: f64, y: f64) -> f64 {
-    if x < y { x } else { y }
+fn node_street(node: &PostFlopNode) -> BoardState {
+    if node.river != NOT_DEALT {
+        BoardState::River
+    } else if node.turn != NOT_DEALT {
+        BoardState::Turn
+    } else {
+        BoardState::Flop
+    }
 }
 
 impl PostFlopGame {
@@ -16,7 +35,7 @@ impl PostFlopGame {
         cfreach: &[f32],
     ) {
         let pot = (self.tree_config.starting_pot + 2 * node.amount) as f64;
-        let rake = min(pot * self.tree_config.rake_rate, self.tree_config.rake_cap);
+        let rake = self.tree_config.rake(pot, node_street(node));
 
         let (amount_win, amount_lose, amount_tie) =
             self.calculate_amounts(node.amount, rake, player, self.num_combinations);
@@ -87,163 +106,32 @@ impl PostFlopGame {
                 }
             }
         }
-        // showdown (optimized for no rake; 2-pass)
-        else if rake == 0.0 {
+        // showdown of a split-pot game
+        else if !self.split_pot_strength.is_empty() {
             let pair_index = card_pair_to_index(node.turn, node.river);
-            let hand_strength = &self.hand_strength[pair_index];
-            let player_strength = &hand_strength[player];
-            let opponent_strength = &hand_strength[player ^ 1];
-
-            let valid_player_strength = &player_strength[1..player_strength.len() - 1];
-            let mut i = 1;
-
-            for &StrengthItem { strength, index } in valid_player_strength {
-                unsafe {
-                    while opponent_strength.get_unchecked(i).strength < strength {
-                        let opponent_index = opponent_strength.get_unchecked(i).index as usize;
-                        let cfreach_i = *cfreach.get_unchecked(opponent_index);
-                        if cfreach_i != 0.0 {
-                            let (c1, c2) = *opponent_cards.get_unchecked(opponent_index);
-                            let cfreach_i_f64 = cfreach_i as f64;
-                            cfreach_sum += cfreach_i_f64;
-                            *cfreach_minus.get_unchecked_mut(c1 as usize) += cfreach_i_f64;
-                            *cfreach_minus.get_unchecked_mut(c2 as usize) += cfreach_i_f64;
-                        }
-                        i += 1;
-                    }
-                    let (c1, c2) = *player_cards.get_unchecked(index as usize);
-                    let cfreach = cfreach_sum
-                        - cfreach_minus.get_unchecked(c1 as usize)
-                        - cfreach_minus.get_unchecked(c2 as usize);
-                    *result.get_unchecked_mut(index as usize) = (amount_win * cfreach) as f32;
-                }
-            }
-
-            cfreach_sum = 0.0;
-            cfreach_minus.fill(0.0);
-            i = opponent_strength.len() - 2;
-
-            for &StrengthItem { strength, index } in valid_player_strength.iter().rev() {
-                unsafe {
-                    while opponent_strength.get_unchecked(i).strength > strength {
-                        let opponent_index = opponent_strength.get_unchecked(i).index as usize;
-                        let cfreach_i = *cfreach.get_unchecked(opponent_index);
-                        if cfreach_i != 0.0 {
-                            let (c1, c2) = *opponent_cards.get_unchecked(opponent_index);
-                            let cfreach_i_f64 = cfreach_i as f64;
-                            cfreach_sum += cfreach_i_f64;
-                            *cfreach_minus.get_unchecked_mut(c1 as usize) += cfreach_i_f64;
-                            *cfreach_minus.get_unchecked_mut(c2 as usize) += cfreach_i_f64;
-                        }
-                        i -= 1;
-                    }
-                    let (c1, c2) = *player_cards.get_unchecked(index as usize);
-                    let cfreach = cfreach_sum
-                        - cfreach_minus.get_unchecked(c1 as usize)
-                        - cfreach_minus.get_unchecked(c2 as usize);
-                    *result.get_unchecked_mut(index as usize) += (amount_lose * cfreach) as f32;
-                }
+            let mut shares = [0.0; MAX_NUM_PRIVATE_HANDS];
+            let mut totals = [0.0; MAX_NUM_PRIVATE_HANDS];
+            let (shares, totals) = (&mut shares[..result.len()], &mut totals[..result.len()]);
+            self.split_pot_shares(pair_index, player, cfreach, shares, totals);
+
+            // the payoff is linear in the share of the pot (the ICM payoffs are excluded)
+            for ((r, &share), &total) in result.iter_mut().zip(&*shares).zip(&*totals) {
+                *r = (amount_lose * total + (amount_win - amount_lose) * share) as f32;
             }
         }
-        // showdown (raked; 3-pass)
+        // showdown (ties are worth nothing without rake)
         else {
-            let same_hand_index = &self.same_hand_index[player];
-
             let pair_index = card_pair_to_index(node.turn, node.river);
-            let hand_strength = &self.hand_strength[pair_index];
-            let player_strength = &hand_strength[player];
-            let opponent_strength = &hand_strength[player ^ 1];
-
-            let valid_player_strength = &player_strength[1..player_strength.len() - 1];
-            let valid_opponent_strength = &opponent_strength[1..opponent_strength.len() - 1];
-
-            for &StrengthItem { index, .. } in valid_opponent_strength {
-                unsafe {
-                    let cfreach_i = *cfreach.get_unchecked(index as usize);
-                    if cfreach_i != 0.0 {
-                        let (c1, c2) = *opponent_cards.get_unchecked(index as usize);
-                        let cfreach_i_f64 = cfreach_i as f64;
-                        cfreach_sum += cfreach_i_f64;
-                        *cfreach_minus.get_unchecked_mut(c1 as usize) += cfreach_i_f64;
-                        *cfreach_minus.get_unchecked_mut(c2 as usize) += cfreach_i_f64;
-                    }
-                }
-            }
-
-            if cfreach_sum == 0.0 {
-                return;
-            }
-
-            let mut cfreach_sum_win = 0.0;
-            let mut cfreach_sum_tie = 0.0;
-            let mut cfreach_minus_win = [0.0; 52];
-            let mut cfreach_minus_tie = [0.0; 52];
-
-            let mut i = 1;
-            let mut j = 1;
-            let mut prev_strength = 0; // strength is always > 0
-
-            for &StrengthItem { strength, index } in valid_player_strength {
-                unsafe {
-                    if strength > prev_strength {
-                        prev_strength = strength;
-
-                        if i < j {
-                            cfreach_sum_win = cfreach_sum_tie;
-                            cfreach_minus_win = cfreach_minus_tie;
-                            i = j;
-                        }
-
-                        while opponent_strength.get_unchecked(i).strength < strength {
-                            let opponent_index = opponent_strength.get_unchecked(i).index as usize;
-                            let (c1, c2) = *opponent_cards.get_unchecked(opponent_index);
-                            let cfreach_i = *cfreach.get_unchecked(opponent_index) as f64;
-                            cfreach_sum_win += cfreach_i;
-                            *cfreach_minus_win.get_unchecked_mut(c1 as usize) += cfreach_i;
-                            *cfreach_minus_win.get_unchecked_mut(c2 as usize) += cfreach_i;
-                            i += 1;
-                        }
-
-                        if j < i {
-                            cfreach_sum_tie = cfreach_sum_win;
-                            cfreach_minus_tie = cfreach_minus_win;
-                            j = i;
-                        }
-
-                        while opponent_strength.get_unchecked(j).strength == strength {
-                            let opponent_index = opponent_strength.get_unchecked(j).index as usize;
-                            let (c1, c2) = *opponent_cards.get_unchecked(opponent_index);
-                            let cfreach_j = *cfreach.get_unchecked(opponent_index) as f64;
-                            cfreach_sum_tie += cfreach_j;
-                            *cfreach_minus_tie.get_unchecked_mut(c1 as usize) += cfreach_j;
-                            *cfreach_minus_tie.get_unchecked_mut(c2 as usize) += cfreach_j;
-                            j += 1;
-                        }
-                    }
-
-                    let (c1, c2) = *player_cards.get_unchecked(index as usize);
-                    let cfreach_total = cfreach_sum
-                        - cfreach_minus.get_unchecked(c1 as usize)
-                        - cfreach_minus.get_unchecked(c2 as usize);
-                    let cfreach_win = cfreach_sum_win
-                        - cfreach_minus_win.get_unchecked(c1 as usize)
-                        - cfreach_minus_win.get_unchecked(c2 as usize);
-                    let cfreach_tie = cfreach_sum_tie
-                        - cfreach_minus_tie.get_unchecked(c1 as usize)
-                        - cfreach_minus_tie.get_unchecked(c2 as usize);
-                    let same_i = *same_hand_index.get_unchecked(index as usize);
-                    let cfreach_same = if same_i == u16::MAX {
-                        0.0
-                    } else {
-                        *cfreach.get_unchecked(same_i as usize) as f64
-                    };
-
-                    let cfvalue = amount_win * cfreach_win
-                        + amount_tie * (cfreach_tie - cfreach_win + cfreach_same)
-                        + amount_lose * (cfreach_total - cfreach_tie);
-                    *result.get_unchecked_mut(index as usize) = cfvalue as f32;
-                }
-            }
+            let amount_tie = if rake == 0.0 { None } else { Some(amount_tie) };
+            self.evaluate_showdown(
+                result,
+                pair_index,
+                player,
+                cfreach,
+                amount_win,
+                amount_lose,
+                amount_tie,
+            );
         }
     }
 
@@ -255,7 +143,7 @@ impl PostFlopGame {
         cfreach: &[f32],
     ) {
         let pot = (self.tree_config.starting_pot + 2 * node.amount) as f64;
-        let rake = min(pot * self.tree_config.rake_rate, self.tree_config.rake_cap);
+        let rake = self.tree_config.rake(pot, node_street(node));
 
         let (amount_win_f64, amount_lose_f64, amount_tie_f64) =
             self.calculate_amounts(node.amount, rake, player, self.bunching_num_combinations);
@@ -320,6 +208,230 @@ impl PostFlopGame {
         }
     }
 
+    /// Evaluates the showdown on the runout of `pair_index` for the private hands of `player`.
+    ///
+    /// The hands of `player` are evaluated in batches of equal strength during a single sweep over
+    /// the opponent hands in ascending order of strength: each batch first records the weight of
+    /// the weaker opponent hands, then that of the opponent hands that are not stronger after the
+    /// ties are added. A final branch-free pass combines them with the total weights of the
+    /// opponent hands. Ties are worth `amount_tie` if given and nothing otherwise.
+    #[allow(clippy::too_many_arguments)]
+    fn evaluate_showdown(
+        &self,
+        result: &mut [f32],
+        pair_index: usize,
+        player: usize,
+        cfreach: &[f32],
+        amount_win: f64,
+        amount_lose: f64,
+        amount_tie: Option<f64>,
+    ) {
+        let hand_strength = &self.hand_strength[pair_index];
+        let player_strength = &hand_strength[player];
+        let opponent_strength = &hand_strength[player ^ 1];
+        let valid_player_strength = &player_strength[1..player_strength.len() - 1];
+
+        let player_cards = &self.private_cards[player];
+        let opponent_cards = &self.private_cards[player ^ 1];
+
+        // cfvalue = amount_win * win + amount_tie * (tie - win) + amount_lose * (total - tie),
+        // where `win` is the weight of the weaker opponent hands and `tie` that of the opponent
+        // hands that are not stronger (the same hand, which is subtracted twice by the
+        // inclusion-exclusion principle, is added back to `tie - win` in the final pass)
+        let amount_tie_or_zero = amount_tie.unwrap_or(0.0);
+        let coef_win = amount_win - amount_tie_or_zero;
+        let coef_tie = amount_tie_or_zero - amount_lose;
+
+        let mut cfreach_sum = 0.0;
+        let mut cfreach_minus = [0.0; 52];
+        let mut values = [MaybeUninit::<f64>::uninit(); MAX_NUM_PRIVATE_HANDS];
+
+        // adds the opponent hands weaker than `bound` from the `i`-th one (the strongest sentinel
+        // stops the loop)
+        let add_weaker = |i: &mut usize, bound: u16, sum: &mut f64, minus: &mut [f64; 52]| unsafe {
+            while opponent_strength.get_unchecked(*i).strength < bound {
+                let opponent_index = opponent_strength.get_unchecked(*i).index as usize;
+                let (c1, c2) = *opponent_cards.get_unchecked(opponent_index);
+                let cfreach_i = *cfreach.get_unchecked(opponent_index) as f64;
+                *sum += cfreach_i;
+                *minus.get_unchecked_mut(c1 as usize) += cfreach_i;
+                *minus.get_unchecked_mut(c2 as usize) += cfreach_i;
+                *i += 1;
+            }
+        };
+
+        let mut i = 1;
+        let mut p = 0;
+
+        while p < valid_player_strength.len() {
+            unsafe {
+                let strength = valid_player_strength.get_unchecked(p).strength;
+                let batch_begin = p;
+
+                add_weaker(&mut i, strength, &mut cfreach_sum, &mut cfreach_minus);
+
+                // `win` and `tie` are equal if no opponent hand has the same strength
+                let has_ties = opponent_strength.get_unchecked(i).strength == strength;
+                let coef = if has_ties {
+                    coef_win
+                } else {
+                    coef_win + coef_tie
+                };
+
+                while p < valid_player_strength.len()
+                    && valid_player_strength.get_unchecked(p).strength == strength
+                {
+                    let index = valid_player_strength.get_unchecked(p).index;
+                    let (c1, c2) = *player_cards.get_unchecked(index as usize);
+                    let cfreach_win = cfreach_sum
+                        - cfreach_minus.get_unchecked(c1 as usize)
+                        - cfreach_minus.get_unchecked(c2 as usize);
+                    values.get_unchecked_mut(p).write(coef * cfreach_win);
+                    p += 1;
+                }
+
+                if !has_ties {
+                    continue;
+                }
+
+                // strength is always < u16::MAX
+                add_weaker(&mut i, strength + 1, &mut cfreach_sum, &mut cfreach_minus);
+                for q in batch_begin..p {
+                    let index = valid_player_strength.get_unchecked(q).index;
+                    let (c1, c2) = *player_cards.get_unchecked(index as usize);
+                    let cfreach_tie = cfreach_sum
+                        - cfreach_minus.get_unchecked(c1 as usize)
+                        - cfreach_minus.get_unchecked(c2 as usize);
+                    *values.get_unchecked_mut(q).assume_init_mut() += coef_tie * cfreach_tie;
+                }
+            }
+        }
+
+        add_weaker(&mut i, u16::MAX, &mut cfreach_sum, &mut cfreach_minus);
+
+        let values = unsafe { assume_init(&values[..valid_player_strength.len()]) };
+        let same_hand_index = &self.same_hand_index[player];
+
+        for (&StrengthItem { index, .. }, &value) in valid_player_strength.iter().zip(values) {
+            unsafe {
+                let (c1, c2) = *player_cards.get_unchecked(index as usize);
+                let cfreach_total = cfreach_sum
+                    - cfreach_minus.get_unchecked(c1 as usize)
+                    - cfreach_minus.get_unchecked(c2 as usize);
+                let mut cfvalue = value + amount_lose * cfreach_total;
+                if let Some(amount_tie) = amount_tie {
+                    let same_i = *same_hand_index.get_unchecked(index as usize);
+                    if same_i != u16::MAX {
+                        cfvalue += amount_tie * *cfreach.get_unchecked(same_i as usize) as f64;
+                    }
+                }
+                *result.get_unchecked_mut(index as usize) = cfvalue as f32;
+            }
+        }
+    }
+
+    /// Computes the split-pot showdown of each private hand of `player` on the runout of
+    /// `pair_index` against the opponent hands weighted by `opponent_weights`.
+    ///
+    /// `shares` receives the sum of the weights multiplied by the share of the pot won, and
+    /// `totals` the sum of the weights of the opponent hands not conflicting with the hand.
+    pub(super) fn split_pot_shares(
+        &self,
+        pair_index: usize,
+        player: usize,
+        opponent_weights: &[f32],
+        shares: &mut [f64],
+        totals: &mut [f64],
+    ) {
+        let player_strength = &self.split_pot_strength[pair_index][player];
+        let opponent_strength = &self.split_pot_strength[pair_index][player ^ 1];
+        let player_cards = &self.private_cards[player];
+        let opponent_cards = &self.private_cards[player ^ 1];
+        let same_hand_index = &self.same_hand_index[player];
+
+        let same_weight = |index: usize| match same_hand_index[index] {
+            u16::MAX => 0.0,
+            same_i => opponent_weights[same_i as usize] as f64,
+        };
+
+        // The share against each opponent hand is decomposed as follows:
+        // - half of the high against every hand;
+        // - the other half of the high against the hands without a qualifying low, if the hand
+        //   does not qualify either (scoop by high);
+        // - half of the low against the qualifying hands, if the hand qualifies; and
+        // - half of the pot against the hands without a qualifying low, if the hand qualifies.
+        // Since a hand and the same hand of the opponent have the same strengths, the same hand
+        // is always in the same list as the hand.
+        let pairs = [
+            (&player_strength.high, &opponent_strength.high),
+            (&player_strength.high_no_low, &opponent_strength.high_no_low),
+            (&player_strength.low, &opponent_strength.low),
+        ];
+
+        for (player_items, opponent_items) in pairs {
+            // weights of the opponent hands weaker than the hand (`lt`) and not stronger (`le`)
+            let (mut lt_sum, mut lt_minus) = (0.0, [0.0; 52]);
+            let (mut le_sum, mut le_minus) = (0.0, [0.0; 52]);
+            let (mut i, mut j) = (0, 0);
+
+            for &StrengthItem { strength, index } in player_items {
+                while i < opponent_items.len() && opponent_items[i].strength < strength {
+                    let opponent_index = opponent_items[i].index as usize;
+                    let (c1, c2) = opponent_cards[opponent_index];
+                    let weight = opponent_weights[opponent_index] as f64;
+                    lt_sum += weight;
+                    lt_minus[c1 as usize] += weight;
+                    lt_minus[c2 as usize] += weight;
+                    i += 1;
+                }
+                while j < opponent_items.len() && opponent_items[j].strength <= strength {
+                    let opponent_index = opponent_items[j].index as usize;
+                    let (c1, c2) = opponent_cards[opponent_index];
+                    let weight = opponent_weights[opponent_index] as f64;
+                    le_sum += weight;
+                    le_minus[c1 as usize] += weight;
+                    le_minus[c2 as usize] += weight;
+                    j += 1;
+                }
+
+                // inclusion-exclusion principle
+                let index = index as usize;
+                let (c1, c2) = player_cards[index];
+                let lt = lt_sum - lt_minus[c1 as usize] - lt_minus[c2 as usize];
+                let le =
+                    le_sum - le_minus[c1 as usize] - le_minus[c2 as usize] + same_weight(index);
+                shares[index] += 0.25 * (lt + le);
+            }
+        }
+
+        // weights of all the opponent hands and those without a qualifying low
+        let weight_sums = |items: &[StrengthItem]| {
+            let (mut sum, mut minus) = (0.0, [0.0; 52]);
+            for &StrengthItem { index, .. } in items {
+                let (c1, c2) = opponent_cards[index as usize];
+                let weight = opponent_weights[index as usize] as f64;
+                sum += weight;
+                minus[c1 as usize] += weight;
+                minus[c2 as usize] += weight;
+            }
+            (sum, minus)
+        };
+
+        let (sum, minus) = weight_sums(&opponent_strength.high);
+        for &StrengthItem { index, .. } in &player_strength.high {
+            let index = index as usize;
+            let (c1, c2) = player_cards[index];
+            totals[index] = sum - minus[c1 as usize] - minus[c2 as usize] + same_weight(index);
+        }
+
+        let (sum, minus) = weight_sums(&opponent_strength.high_no_low);
+        for &StrengthItem { index, .. } in &player_strength.low {
+            let index = index as usize;
+            let (c1, c2) = player_cards[index];
+            shares[index] += 0.5 * (sum - minus[c1 as usize] - minus[c2 as usize]);
+        }
+    }
+
     fn calculate_amounts(
         &self,
         bet_amount: i32,
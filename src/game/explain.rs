@@ -0,0 +1,276 @@
+use super::*;
+
+/// Response of the opponent to an aggressive action, reported in [`ActionExplanation::response`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpponentResponse {
+    /// Fold frequency of the opponent against the hand, i.e., over the combos of the opponent that
+    /// do not conflict with the hand.
+    pub fold_frequency: f32,
+
+    /// Fold frequency of the opponent over the whole range, ignoring the cards of the hand.
+    /// `fold_frequency - unblocked_fold_frequency` is the effect of the card removal.
+    pub unblocked_fold_frequency: f32,
+
+    /// Share of the folding combos of the opponent that contain a card of the hand.
+    pub blocked_fold_share: f32,
+
+    /// Share of the continuing (calling or raising) combos of the opponent that contain a card of
+    /// the hand.
+    pub blocked_continue_share: f32,
+
+    /// Equity of the hand against the continuing range of the opponent. `NaN` if the opponent
+    /// never continues against the hand.
+    pub continue_equity: f32,
+}
+
+/// Quantitative drivers of an action of a private hand, reported by
+/// [`PostFlopGame::explain_action`].
+///
+/// The expected values follow the convention of [`PostFlopGame::expected_values`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionExplanation {
+    /// Private hand.
+    pub hand: (Card, Card),
+
+    /// Explained action.
+    pub action: Action,
+
+    /// Frequency of the action with the hand.
+    pub frequency: f32,
+
+    /// Expected value of the action with the hand.
+    pub action_ev: f32,
+
+    /// Expected value of the hand with its current strategy.
+    pub ev: f32,
+
+    /// Highest expected value among the actions available to the hand.
+    /// `best_action_ev - action_ev` is the cost of taking the action.
+    pub best_action_ev: f32,
+
+    /// Current pot size.
+    pub pot: i32,
+
+    /// Equity of the hand against the current range of the opponent.
+    pub equity: f32,
+
+    /// Equity realization of the hand (see [`PostFlopGame::equity_realization`]).
+    pub equity_realization: f32,
+
+    /// Response of the opponent if the action is a bet or a raise that the opponent can fold to,
+    /// `None` otherwise.
+    pub response: Option<OpponentResponse>,
+}
+
+impl PostFlopGame {
+    /// Explains why the current player takes (or does not take) the `action`-th action with
+    /// `hand` at the current node.
+    ///
+    /// The explanation assembles the numbers that usually drive the decision: the expected value
+    /// of the action compared with the best action, the equity and its realization, and, for a bet
+    /// or a raise, how the hand affects the response of the opponent through the card removal
+    /// (i.e., whether it blocks the folds or the calls of the opponent) and the equity against
+    /// the continuing range. The card removal is computed from the combos of the opponent without
+    /// the bunching effect, while the equities take it into account.
+    ///
+    /// The game must be solved. Returns [`SolverError::InvalidState`] if it is not or if the current
+    /// node is not a player node, and [`SolverError::InvalidArgument`] if `hand` is not in the range
+    /// of the current player or does not reach the current node, or `action` is out of range. The
+    /// current node is preserved.
+    ///
+    /// **Time complexity:** see [`equity`] (computed at most twice), plus *O*(#(OOP private hands)
+    /// + #(IP private hands)).
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["AA,KK,87s,65s,A5s".parse().unwrap(), "QQ-99,AK,KQs,T9s".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("2c").unwrap(),
+    ///     river: card_from_str("3s").unwrap(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::River,
+    ///     starting_pot: 100,
+    ///     effective_stack: 100,
+    ///     river_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// game.allocate_memory(false);
+    /// solve(&mut game, 100, 0.0, false);
+    ///
+    /// // why does A5s bet?
+    /// let hand = (card_from_str("As").unwrap(), card_from_str("5s").unwrap());
+    /// let explanation = game.explain_action(hand, 1).unwrap();
+    /// let response = explanation.response.unwrap();
+    /// println!(
+    ///     "blocks {:.1}% of folds and {:.1}% of calls",
+    ///     response.blocked_fold_share * 100.0,
+    ///     response.blocked_continue_share * 100.0
+    /// );
+    /// ```
+    ///
+    /// [`equity`]: #method.equity
+    pub fn explain_action(
+        &mut self,
+        hand: (Card, Card),
+        action: usize,
+    ) -> Result<ActionExplanation, SolverError> {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(SolverError::InvalidState(
+                "Current node is not a player node".to_owned(),
+            ));
+        }
+
+        let player = self.current_player();
+        let opponent = player ^ 1;
+        let hand_index = self
+            .private_hand_index(player, hand)
+            .ok_or_else(|| SolverError::InvalidArgument("Hand is not in the range".to_owned()))?;
+        let actions = self.available_actions();
+        let Some(&action_kind) = actions.get(action) else {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid action index: {action}"
+            )));
+        };
+
+        let bookmark = self.bookmark();
+        self.cache_normalized_weights();
+        if self.normalized_weights[player][hand_index] <= 0.0 {
+            self.restore_bookmark(&bookmark)?;
+            return Err(SolverError::InvalidArgument(
+                "Hand does not reach the current node".to_owned(),
+            ));
+        }
+
+        let num_hands = self.num_private_hands(player);
+        let num_actions = actions.len();
+        let column = |values: &[f32]| {
+            (0..num_actions)
+                .map(|a| values[a * num_hands + hand_index])
+                .collect::<Vec<_>>()
+        };
+
+        let strategy = column(&self.strategy());
+        let action_evs = column(&self.expected_values_detail(player));
+        let ev = self.expected_values(player)[hand_index];
+        let equity = self.equity(player)[hand_index];
+        let pot = self.current_pot();
+        let equity_realization = if equity > 0.0 {
+            ev / (equity * pot as f32)
+        } else {
+            f32::NAN
+        };
+
+        // fold frequency of the opponent at the child node
+        self.play(action);
+        let fold_frequency = if !self.is_terminal_node()
+            && !self.is_chance_node()
+            && self.current_player() == opponent
+        {
+            self.available_actions()
+                .iter()
+                .position(|&a| a == Action::Fold)
+                .map(|fold| {
+                    let num_opponent_hands = self.num_private_hands(opponent);
+                    let strategy = self.strategy();
+                    strategy[fold * num_opponent_hands..(fold + 1) * num_opponent_hands].to_vec()
+                })
+        } else {
+            None
+        };
+        self.restore_bookmark(&bookmark)?;
+
+        let response = fold_frequency
+            .map(|fold_frequency| self.opponent_response(player, hand_index, &fold_frequency));
+        if response.is_some() {
+            self.restore_bookmark(&bookmark)?;
+        }
+
+        Ok(ActionExplanation {
+            hand: self.private_cards[player][hand_index],
+            action: action_kind,
+            frequency: strategy[action],
+            action_ev: action_evs[action],
+            ev,
+            best_action_ev: action_evs.iter().fold(f32::MIN, |acc, &v| acc.max(v)),
+            pot,
+            equity,
+            equity_realization,
+            response,
+        })
+    }
+
+    /// Computes the response of the opponent of `player` against `hand_index`, given the fold
+    /// frequency of each hand of the opponent. The weights of the opponent are replaced by its
+    /// continuing range, so the caller must restore the current node.
+    fn opponent_response(
+        &mut self,
+        player: usize,
+        hand_index: usize,
+        fold_frequency: &[f32],
+    ) -> OpponentResponse {
+        let opponent = player ^ 1;
+        let (c1, c2) = self.private_cards[player][hand_index];
+        let hand_mask: u64 = (1 << c1) | (1 << c2);
+        let board_mask = self
+            .current_board()
+            .iter()
+            .fold(0u64, |mask, &card| mask | (1 << card));
+
+        // (fold, continue) weights of the opponent: all and blocked by the hand
+        let mut total = [0.0f64; 2];
+        let mut blocked = [0.0f64; 2];
+        for ((&(c3, c4), &w), &fold) in self.private_cards[opponent]
+            .iter()
+            .zip(&self.weights[opponent])
+            .zip(fold_frequency)
+        {
+            let mask: u64 = (1 << c3) | (1 << c4);
+            if mask & board_mask != 0 {
+                continue;
+            }
+            let split = [w as f64 * fold as f64, w as f64 * (1.0 - fold as f64)];
+            for i in 0..2 {
+                total[i] += split[i];
+                if mask & hand_mask != 0 {
+                    blocked[i] += split[i];
+                }
+            }
+        }
+
+        let ratio = |x: f64, y: f64| if y > 0.0 { (x / y) as f32 } else { 0.0 };
+        let unblocked = [total[0] - blocked[0], total[1] - blocked[1]];
+
+        // equity against the continuing range
+        self.weights[opponent]
+            .iter_mut()
+            .zip(fold_frequency)
+            .for_each(|(w, &fold)| *w *= 1.0 - fold);
+        self.is_normalized_weight_cached = false;
+        self.cache_normalized_weights();
+        let continue_equity = if self.normalized_weights[player][hand_index] > 0.0 {
+            self.equity(player)[hand_index]
+        } else {
+            f32::NAN
+        };
+
+        OpponentResponse {
+            fold_frequency: ratio(unblocked[0], unblocked[0] + unblocked[1]),
+            unblocked_fold_frequency: ratio(total[0], total[0] + total[1]),
+            blocked_fold_share: ratio(blocked[0], total[0]),
+            blocked_continue_share: ratio(blocked[1], total[1]),
+            continue_equity,
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use super::*;
+use crate::interface::*;
+use crate::utility::*;
+use std::sync::Mutex;
+
+impl PostFlopGame {
+    /// Replaces the strategy of `player` with the maximally exploitative strategy (i.e., the pure
+    /// best response) against the current strategy of the opponent.
+    ///
+    /// The strategy of the opponent is taken as is across the whole tree, so it can be supplied by
+    /// any means: solving, loading a saved game, [`lock_current_strategy`] (e.g., a population
+    /// model), or [`override_current_strategy`]. If the game is not solved, it is finalized first
+    /// without solving, so the nodes of the opponent that are not locked use the average strategy
+    /// of the iterations performed so far (uniform if none). In this mode, locking all the nodes of
+    /// the opponent specifies its strategy completely.
+    ///
+    /// The best response is applied as strategy overrides (see [`override_current_strategy`]):
+    /// every hand takes the action with the highest expected value at every node of `player`,
+    /// except the hands locked at the node, and the expected values of the whole tree are
+    /// recomputed, so that [`expected_values`] and related methods return the expected values of
+    /// the best response at any node. Call [`clear_strategy_overrides`] to restore the original
+    /// strategy. The current node is preserved.
+    ///
+    /// The expected values follow the convention of [`compute_current_ev`], i.e., (starting pot) /
+    /// 2 is subtracted, and `ev_shift[player]` of the returned report is the gain from exploiting
+    /// the opponent.
+    ///
+    /// Returns [`SolverError::InvalidArgument`] if `player` is not 0 or 1, and
+    /// [`SolverError::InvalidState`] if the memory is not allocated or the game is not ready to be
+    /// finalized.
+    ///
+    /// **Time complexity:** *O*(#(nodes) * #(private hands)), the same as [`finalize`].
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["AA,KK,87s,65s,A5s".parse().unwrap(), "QQ-99,AK,KQs,T9s".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("2c").unwrap(),
+    ///     river: card_from_str("3s").unwrap(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::River,
+    ///     starting_pot: 100,
+    ///     effective_stack: 100,
+    ///     river_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// game.allocate_memory(false);
+    ///
+    /// // population model: OOP always checks
+    /// let num_hands = game.private_cards(0).len();
+    /// let num_actions = game.available_actions().len();
+    /// let mut strategy = vec![0.0; num_actions * num_hands];
+    /// strategy[..num_hands].fill(1.0);
+    /// game.lock_current_strategy(&strategy);
+    ///
+    /// let report = game.apply_best_response(1).unwrap();
+    /// assert!(game.is_solved());
+    /// assert!(report.ev_shift[1] >= 0.0);
+    /// ```
+    ///
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    /// [`override_current_strategy`]: #method.override_current_strategy
+    /// [`expected_values`]: #method.expected_values
+    /// [`clear_strategy_overrides`]: #method.clear_strategy_overrides
+    /// [`compute_current_ev`]: crate::compute_current_ev
+    /// [`finalize`]: crate::finalize
+    pub fn apply_best_response(&mut self, player: usize) -> Result<WhatIfReport, SolverError> {
+        if player > 1 {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid player: {player}"
+            )));
+        }
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+        if self.state != State::Solved {
+            if !self.is_ready() {
+                return Err(SolverError::InvalidState("Game is not ready".to_owned()));
+            }
+            finalize(self);
+        }
+
+        let baseline_ev = compute_current_ev(self);
+
+        // (node index, locking strategy)
+        let responses = Mutex::new(Vec::new());
+        let record = |node: &PostFlopNode, cfv_actions: &[f32]| {
+            let num_actions = node.num_actions();
+            let num_hands = cfv_actions.len() / num_actions;
+            let locking = self.locking_strategy(node);
+
+            // the locked hands are left unspecified to keep their locked strategy
+            let mut response = vec![-1.0; cfv_actions.len()];
+            for hand in 0..num_hands {
+                if !locking.is_empty() && locking[hand] >= 0.0 {
+                    continue;
+                }
+                let best = (1..num_actions).fold(0, |best, action| {
+                    if cfv_actions[action * num_hands + hand] > cfv_actions[best * num_hands + hand]
+                    {
+                        action
+                    } else {
+                        best
+                    }
+                });
+                for action in 0..num_actions {
+                    response[action * num_hands + hand] = (action == best) as u8 as f32;
+                }
+            }
+
+            let index = self.node_index(node);
+            responses.lock().unwrap().push((index, response));
+        };
+        compute_best_cfvalues(self, player, Some(&record));
+
+        for (index, response) in responses.into_inner().unwrap() {
+            self.insert_strategy_override(index, response);
+        }
+        self.refresh_after_override();
+
+        let ev = compute_current_ev(self);
+        Ok(WhatIfReport {
+            baseline_ev,
+            ev,
+            ev_shift: [ev[0] - baseline_ev[0], ev[1] - baseline_ev[1]],
+        })
+    }
+}
@@ -0,0 +1,864 @@
+use super::walk::*;
+use super::*;
+use crate::bet_size::*;
+use crate::interface::*;
+use crate::position::*;
+use crate::range::*;
+use crate::utility::*;
+use std::io::Write;
+
+/// Output format of [`PostFlopGame::export_strategy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// CSV with one row per node, private hand, and action.
+    #[default]
+    Csv,
+
+    /// JSON with one object per node.
+    Json,
+
+    /// Text blocks in the layout of the PioSOLVER outputs, with one block per node (see
+    /// [`PostFlopGame::export_strategy`]).
+    Pio,
+}
+
+/// Options of [`PostFlopGame::export_strategy`].
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Output format.
+    pub format: ExportFormat,
+
+    /// If `Some`, only the nodes of the given street are exported.
+    pub street: Option<BoardState>,
+
+    /// History from the root node (see [`PostFlopGame::apply_history`]) of the node to start the
+    /// export from. Only the subtree of the node is exported.
+    pub history: Vec<usize>,
+}
+
+/// Options of [`PostFlopGame::export_dot`].
+#[derive(Debug, Clone, Default)]
+pub struct DotOptions {
+    /// History from the root node (see [`PostFlopGame::apply_history`]) of the node to start the
+    /// export from.
+    pub history: Vec<usize>,
+
+    /// Maximum depth (number of actions and dealt cards) below the starting node. `None` means no
+    /// limit.
+    pub max_depth: Option<usize>,
+
+    /// Actions whose overall frequency is less than this value are pruned with their subtrees.
+    pub min_frequency: f32,
+
+    /// Whether to expand every possible card at chance nodes. If `false`, chance nodes are drawn
+    /// as leaves.
+    pub expand_chance: bool,
+}
+
+/// Data of a private hand at a player node for scatter plots, returned by
+/// [`PostFlopGame::scatter_data`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScatterPoint {
+    /// Hole cards.
+    pub hand: (Card, Card),
+
+    /// Normalized weight of the hand.
+    pub weight: f32,
+
+    /// Frequency of each action.
+    pub frequencies: Vec<f32>,
+
+    /// Expected value of each action. Empty if the game is not solved.
+    pub action_ev: Vec<f32>,
+
+    /// Expected value of the hand. `NaN` if the game is not solved.
+    pub ev: f32,
+
+    /// Equity of the hand.
+    pub equity: f32,
+
+    /// Share of the opponent's range (weighted by the reach) that contains a card of the hand.
+    pub blocker_score: f32,
+}
+
+/// Data of a player node to be exported.
+pub(super) struct ExportNode {
+    pub(super) line: String,
+    pio_node_id: String,
+    pio_action_codes: Vec<String>,
+    pub(super) street: BoardState,
+    pub(super) board: String,
+    pub(super) player: usize,
+    pub(super) actions: Vec<Action>,
+    pub(super) hands: Vec<usize>,
+    pub(super) weights: Vec<f32>,
+    pub(super) strategy: Vec<f32>,
+    pub(super) ev_detail: Option<Vec<f32>>,
+}
+
+#[inline]
+pub(super) fn street_to_str(street: BoardState) -> &'static str {
+    match street {
+        BoardState::Flop => "flop",
+        BoardState::Turn => "turn",
+        BoardState::River => "river",
+    }
+}
+
+#[inline]
+fn json_number(x: f32) -> String {
+    if x.is_finite() {
+        x.to_string()
+    } else {
+        "null".to_owned()
+    }
+}
+
+/// Formats a list of values as a JSON array.
+fn json_array<T>(values: impl IntoIterator<Item = T>, f: impl Fn(T) -> String) -> String {
+    let values = values.into_iter().map(f).collect::<Vec<_>>();
+    format!("[{}]", values.join(","))
+}
+
+fn json_bet_sizes(options: &BetSizeOptions) -> String {
+    format!(
+        "{{\"bet\":{},\"raise\":{}}}",
+        json_array(&options.bet, |size| format!("\"{size}\"")),
+        json_array(&options.raise, |size| format!("\"{size}\"")),
+    )
+}
+
+fn json_donk_sizes(options: &Option<DonkSizeOptions>) -> String {
+    match options {
+        Some(options) => json_array(&options.donk, |size| format!("\"{size}\"")),
+        None => "null".to_owned(),
+    }
+}
+
+fn json_card(card: Card) -> String {
+    match card_to_string(card) {
+        Ok(card) => format!("\"{card}\""),
+        Err(_) => "null".to_owned(),
+    }
+}
+
+impl PostFlopGame {
+    /// Walks the game tree and writes the strategy of every player node in CSV or JSON format.
+    ///
+    /// Nodes are identified by stable line identifiers (see [`line_to_string`]), which contain all
+    /// the actions and the dealt cards from the root node. Every possible turn and river card is
+    /// visited (not only the representative of isomorphic cards). For each node, private hands with
+    /// zero normalized weight are omitted. The expected values are written only if the game is
+    /// solved.
+    ///
+    /// - CSV columns: `line`, `street`, `board`, `player`, `hand`, `weight`, `action`,
+    ///   `frequency`, and `ev` (empty if not solved).
+    /// - JSON: `{"nodes": [...]}` where each node has `line`, `street`, `board`, `player`,
+    ///   `actions`, `hands`, `weights`, `strategy` (indexed by action and then by hand), and `ev`
+    ///   (same layout as `strategy`; `null` if not solved).
+    /// - Pio: for each node, the lines `node <node ID>` (see [`line_to_pio_node_id`]), `player`
+    ///   (`OOP` or `IP`), `actions` (the codes of the node ID separated by spaces), `range`, one
+    ///   `strategy <action code>` line per action, and `ev` (only if solved), followed by an empty
+    ///   line. Like the `show_range`, `show_strategy`, and `calc_ev` commands of PioSOLVER, the
+    ///   values are given for all 1326 hole-card combinations separated by spaces, ordered by the
+    ///   higher card and then by the lower card (`2d2c`, `2h2c`, `2h2d`, `2s2c`, ..., `AsAh`),
+    ///   where the cards are ordered by rank and then by suit (`c`, `d`, `h`, `s`). The values of
+    ///   the hands with zero normalized weight are `0`. `range` is the normalized weight of each
+    ///   hand and `ev` is the expected value of each hand.
+    ///
+    /// The current node is restored after the call.
+    ///
+    /// **Time complexity:** *O*(#(exported nodes) * #(depth) * (#(OOP private hands) + #(IP
+    /// private hands))). Exporting a whole flop tree can produce a very large output.
+    ///
+    /// [`line_to_string`]: crate::line_to_string
+    /// [`line_to_pio_node_id`]: crate::line_to_pio_node_id
+    pub fn export_strategy<W: Write>(
+        &mut self,
+        writer: &mut W,
+        options: &ExportOptions,
+    ) -> Result<(), SolverError> {
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+
+        let write_err = |e: std::io::Error| SolverError::io("Failed to write", &e);
+
+        // validates the history before writing anything
+        let original_history = self.history().to_vec();
+        let result = self.line_from_history(&options.history);
+        self.apply_history(&original_history);
+        result?;
+
+        match options.format {
+            ExportFormat::Csv => {
+                writeln!(
+                    writer,
+                    "line,street,board,player,hand,weight,action,frequency,ev"
+                )
+                .map_err(write_err)?;
+            }
+            ExportFormat::Json => {
+                write!(writer, "{{\"nodes\":[").map_err(write_err)?;
+            }
+            ExportFormat::Pio => {}
+        }
+
+        let mut is_first = true;
+        self.walk_player_nodes(&options.history, options.street, &mut |game, _, line| {
+            let node = game.export_node(line);
+            match options.format {
+                ExportFormat::Csv => game.write_csv_node(writer, &node),
+                ExportFormat::Json => {
+                    let separator = if is_first { "" } else { "," };
+                    is_first = false;
+                    game.write_json_node(writer, &node, separator)
+                }
+                ExportFormat::Pio => game.write_pio_node(writer, &node),
+            }
+            .map_err(write_err)
+        })?;
+
+        if options.format == ExportFormat::Json {
+            writeln!(writer, "]}}").map_err(write_err)?;
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn export_node(&mut self, line: &[Action]) -> ExportNode {
+        self.cache_normalized_weights();
+        let board = self.current_board();
+        let player = self.current_player();
+        let actions = self.available_actions();
+        let mut child_line = line.to_vec();
+        let pio_action_codes = actions
+            .iter()
+            .map(|&action| {
+                child_line.push(action);
+                let node_id = line_to_pio_node_id(&child_line);
+                child_line.pop();
+                node_id.rsplit(':').next().unwrap().to_owned()
+            })
+            .collect();
+        let weights = self.normalized_weights(player);
+        let hands = (0..weights.len())
+            .filter(|&hand| weights[hand] > 0.0)
+            .collect::<Vec<_>>();
+        ExportNode {
+            line: line_to_string(line),
+            pio_node_id: line_to_pio_node_id(line),
+            pio_action_codes,
+            street: street_of_board(&board),
+            board: board.iter().map(|&c| card_to_string(c).unwrap()).collect(),
+            player,
+            actions,
+            weights: hands.iter().map(|&hand| weights[hand]).collect(),
+            hands,
+            strategy: self.strategy(),
+            ev_detail: self
+                .is_solved()
+                .then(|| self.expected_values_detail(player)),
+        }
+    }
+
+    fn write_csv_node<W: Write>(&self, writer: &mut W, node: &ExportNode) -> std::io::Result<()> {
+        let private_cards = &self.private_cards[node.player];
+        let num_hands = private_cards.len();
+        let street = street_to_str(node.street);
+
+        for (i, &hand) in node.hands.iter().enumerate() {
+            let hand_str = hole_to_string(private_cards[hand]).unwrap();
+            for (action_index, action) in node.actions.iter().enumerate() {
+                let index = action_index * num_hands + hand;
+                let ev = match &node.ev_detail {
+                    Some(ev_detail) => ev_detail[index].to_string(),
+                    None => String::new(),
+                };
+                writeln!(
+                    writer,
+                    "{},{street},{},{},{hand_str},{},{},{},{ev}",
+                    node.line,
+                    node.board,
+                    node.player,
+                    node.weights[i],
+                    csv_escape(&action.to_string()),
+                    node.strategy[index],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_json_node<W: Write>(
+        &self,
+        writer: &mut W,
+        node: &ExportNode,
+        separator: &str,
+    ) -> std::io::Result<()> {
+        let private_cards = &self.private_cards[node.player];
+        let num_hands = private_cards.len();
+
+        let join = |iter: &mut dyn Iterator<Item = String>| iter.collect::<Vec<_>>().join(",");
+        let per_action = |values: &[f32]| {
+            join(&mut (0..node.actions.len()).map(|action| {
+                let row = &values[action * num_hands..(action + 1) * num_hands];
+                let row = join(&mut node.hands.iter().map(|&hand| json_number(row[hand])));
+                format!("[{row}]")
+            }))
+        };
+
+        let actions = join(&mut node.actions.iter().map(|a| format!("\"{a}\"")));
+        let hands = join(
+            &mut node
+                .hands
+                .iter()
+                .map(|&hand| format!("\"{}\"", hole_to_string(private_cards[hand]).unwrap())),
+        );
+        let weights = join(&mut node.weights.iter().map(|&w| json_number(w)));
+        let strategy = per_action(&node.strategy);
+        let ev = match &node.ev_detail {
+            Some(ev_detail) => format!("[{}]", per_action(ev_detail)),
+            None => "null".to_owned(),
+        };
+
+        write!(
+            writer,
+            "{separator}{{\"line\":\"{}\",\"street\":\"{}\",\"board\":\"{}\",\"player\":{},\
+             \"actions\":[{actions}],\"hands\":[{hands}],\"weights\":[{weights}],\
+             \"strategy\":[{strategy}],\"ev\":{ev}}}",
+            node.line,
+            street_to_str(node.street),
+            node.board,
+            node.player,
+        )
+    }
+
+    fn write_pio_node<W: Write>(&self, writer: &mut W, node: &ExportNode) -> std::io::Result<()> {
+        let private_cards = &self.private_cards[node.player];
+        let num_hands = private_cards.len();
+
+        // index of each private hand in the PioSOLVER order
+        let pio_index = |hand: usize| {
+            let (c1, c2) = private_cards[hand];
+            let (low, high) = (c1.min(c2) as usize, c1.max(c2) as usize);
+            high * (high - 1) / 2 + low
+        };
+
+        let write_values = |writer: &mut W, label: &str, values: &[f32]| {
+            let mut row = [0.0; 1326];
+            for (i, &hand) in node.hands.iter().enumerate() {
+                row[pio_index(hand)] = values[i];
+            }
+            let row = row.map(|x| if x.is_finite() { x } else { 0.0 });
+            let row = row.iter().map(f32::to_string).collect::<Vec<_>>();
+            writeln!(writer, "{label} {}", row.join(" "))
+        };
+
+        let codes = &node.pio_action_codes;
+
+        writeln!(writer, "node {}", node.pio_node_id)?;
+        writeln!(writer, "player {}", ["OOP", "IP"][node.player])?;
+        writeln!(writer, "actions {}", codes.join(" "))?;
+        write_values(writer, "range", &node.weights)?;
+
+        let column = |values: &[f32], action: usize| {
+            let row = &values[action * num_hands..(action + 1) * num_hands];
+            node.hands.iter().map(|&hand| row[hand]).collect::<Vec<_>>()
+        };
+
+        for (action, code) in codes.iter().enumerate() {
+            write_values(
+                writer,
+                &format!("strategy {code}"),
+                &column(&node.strategy, action),
+            )?;
+        }
+
+        if let Some(ev_detail) = &node.ev_detail {
+            let ev = node
+                .hands
+                .iter()
+                .map(|&hand| {
+                    (0..codes.len()).fold(0.0, |acc, action| {
+                        let index = action * num_hands + hand;
+                        acc + node.strategy[index] * ev_detail[index]
+                    })
+                })
+                .collect::<Vec<_>>();
+            write_values(writer, "ev", &ev)?;
+        }
+
+        writeln!(writer)
+    }
+
+    /// Writes the whole game in a human-readable JSON format.
+    ///
+    /// Unlike the compact binary save format, the output is intended for external tooling and
+    /// debugging. The output is a single object with the following fields:
+    ///
+    /// - `card_config`: `ranges` (range strings of OOP and IP), `flop`, `turn`, `river` (`null` if
+    ///   not dealt), `dead_cards`, `short_deck`, and `positions` (e.g., `"BTN"`; `null` if not
+    ///   specified).
+    /// - `tree_config`: every field of [`TreeConfig`], where the bet sizes are given as strings in
+    ///   the syntax of [`BetSizeOptions`].
+    /// - `is_solved`: whether the game is solved.
+    /// - `hands`: private hands of OOP and IP. The per-hand values below are indexed in this order.
+    /// - `root`: the root node.
+    ///
+    /// Each node has `line` (see [`line_to_string`]), `type` (`player`, `chance`, or `terminal`),
+    /// and `pot`. Player nodes additionally have `player`, `actions`, `strategy` (indexed by action
+    /// and then by hand), `ev` (same layout as `strategy`; `null` if not solved), and `children`
+    /// (in the order of `actions`). Chance nodes additionally have `children`, one for each
+    /// possible card (not only the representative of isomorphic cards).
+    ///
+    /// The current node is restored after the call.
+    ///
+    /// **Time complexity:** *O*(#(nodes) * #(depth) * (#(OOP private hands) + #(IP private
+    /// hands))). Dumping a whole flop tree can produce a very large output.
+    ///
+    /// [`line_to_string`]: crate::line_to_string
+    pub fn export_json<W: Write>(&mut self, writer: &mut W) -> Result<(), SolverError> {
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+
+        let write_err = |e: std::io::Error| SolverError::io("Failed to write", &e);
+        let original_history = self.history().to_vec();
+
+        let card_config = &self.card_config;
+        let card_config_json = format!(
+            "{{\"ranges\":{},\"flop\":{},\"turn\":{},\"river\":{},\"dead_cards\":{},\"short_deck\":{},\
+             \"positions\":{}}}",
+            json_array(&card_config.range, |range| format!("\"{range}\"")),
+            json_array(card_config.flop, json_card),
+            json_card(card_config.turn),
+            json_card(card_config.river),
+            json_array(
+                (0..52).filter(|&card| card_config.dead_cards & (1 << card) != 0),
+                json_card
+            ),
+            card_config.short_deck,
+            json_array(card_config.positions, |position| match position {
+                Some(position) => format!("\"{position}\""),
+                None => "null".to_owned(),
+            }),
+        );
+
+        let config = &self.tree_config;
+        let icm_config = match &config.icm_config {
+            Some(icm) => format!(
+                "{{\"player_stacks\":{},\"other_players_stacks\":{},\"payout_structure\":{}}}",
+                json_array(icm.player_stacks, |x| x.to_string()),
+                json_array(&icm.other_players_stacks, i32::to_string),
+                json_array(&icm.payout_structure, i32::to_string),
+            ),
+            None => "null".to_owned(),
+        };
+        let rake_config = match &config.rake_config {
+            Some(rake) => format!(
+                "{{\"rates\":{},\"caps\":{}}}",
+                json_array(rake.rates, |x| x.to_string()),
+                json_array(rake.caps, |x| x.to_string()),
+            ),
+            None => "null".to_owned(),
+        };
+        let sizing_rules = json_array(&config.sizing_rules, |rule| {
+            format!(
+                "{{\"street\":{},\"pot_to_stack_ratio\":{},\"bet_sizes\":{}}}",
+                match rule.street {
+                    Some(street) => format!("\"{}\"", street_to_str(street)),
+                    None => "null".to_owned(),
+                },
+                rule.pot_to_stack_ratio,
+                json_bet_sizes(&rule.bet_sizes),
+            )
+        });
+        let bomb_pot = match &config.bomb_pot {
+            Some(bomb_pot) => format!(
+                "{{\"ante\":{},\"stack\":{}}}",
+                bomb_pot.ante, bomb_pot.stack
+            ),
+            None => "null".to_owned(),
+        };
+        let tree_config_json = format!(
+            "{{\"initial_state\":\"{}\",\"starting_pot\":{},\"effective_stack\":{},\
+             \"rake_rate\":{},\"rake_cap\":{},\"flop_bet_sizes\":{},\"turn_bet_sizes\":{},\
+             \"river_bet_sizes\":{},\"turn_donk_sizes\":{},\"river_donk_sizes\":{},\
+             \"add_allin_threshold\":{},\"force_allin_threshold\":{},\
+             \"merging_threshold\":{},\"icm_config\":{icm_config},\
+             \"rake_config\":{rake_config},\"sizing_rules\":{sizing_rules},\
+             \"bomb_pot\":{bomb_pot}}}",
+            street_to_str(config.initial_state),
+            config.starting_pot,
+            config.effective_stack,
+            config.rake_rate,
+            config.rake_cap,
+            json_array(&config.flop_bet_sizes, json_bet_sizes),
+            json_array(&config.turn_bet_sizes, json_bet_sizes),
+            json_array(&config.river_bet_sizes, json_bet_sizes),
+            json_donk_sizes(&config.turn_donk_sizes),
+            json_donk_sizes(&config.river_donk_sizes),
+            config.add_allin_threshold,
+            config.force_allin_threshold,
+            config.merging_threshold,
+        );
+
+        let hands_json = json_array(&self.private_cards, |cards| {
+            json_array(cards, |&hand| {
+                format!("\"{}\"", hole_to_string(hand).unwrap())
+            })
+        });
+
+        let result = (|| {
+            write!(
+                writer,
+                "{{\"card_config\":{card_config_json},\"tree_config\":{tree_config_json},\
+                 \"is_solved\":{},\"hands\":{hands_json},\"root\":",
+                self.is_solved(),
+            )?;
+            self.write_json_tree(writer, &mut Vec::new(), &mut Vec::new())?;
+            writeln!(writer, "}}")
+        })()
+        .map_err(write_err);
+
+        self.apply_history(&original_history);
+        result
+    }
+
+    /// Writes the node reached by `history` and its subtree in JSON format.
+    fn write_json_tree<W: Write>(
+        &mut self,
+        writer: &mut W,
+        history: &mut Vec<usize>,
+        line: &mut Vec<Action>,
+    ) -> std::io::Result<()> {
+        self.apply_history(history);
+        let line_str = line_to_string(line);
+        let pot = self.current_pot();
+
+        if self.is_terminal_node() {
+            return write!(
+                writer,
+                "{{\"line\":\"{line_str}\",\"type\":\"terminal\",\"pot\":{pot}}}"
+            );
+        }
+
+        let children = if self.is_chance_node() {
+            write!(
+                writer,
+                "{{\"line\":\"{line_str}\",\"type\":\"chance\",\"pot\":{pot},\"children\":["
+            )?;
+            let possible_cards = self.possible_cards();
+            (0..52)
+                .filter(|&card| possible_cards & (1 << card) != 0)
+                .map(|card| (card, Action::Chance(card as Card)))
+                .collect::<Vec<_>>()
+        } else {
+            self.cache_normalized_weights();
+            let player = self.current_player();
+            let actions = self.available_actions();
+            let num_hands = self.private_cards[player].len();
+            let per_action = |values: &[f32]| {
+                json_array(values.chunks(num_hands), |row| {
+                    json_array(row, |&x| json_number(x))
+                })
+            };
+            let ev = if self.is_solved() {
+                per_action(&self.expected_values_detail(player))
+            } else {
+                "null".to_owned()
+            };
+            write!(
+                writer,
+                "{{\"line\":\"{line_str}\",\"type\":\"player\",\"pot\":{pot},\
+                 \"player\":{player},\"actions\":{},\"strategy\":{},\"ev\":{ev},\
+                 \"children\":[",
+                json_array(&actions, |action| format!("\"{action}\"")),
+                per_action(&self.strategy()),
+            )?;
+            actions.into_iter().enumerate().collect()
+        };
+
+        for (i, &(index, action)) in children.iter().enumerate() {
+            if i > 0 {
+                write!(writer, ",")?;
+            }
+            history.push(index);
+            line.push(action);
+            self.write_json_tree(writer, history, line)?;
+            line.pop();
+            history.pop();
+        }
+
+        write!(writer, "]}}")
+    }
+
+    /// Writes the game tree as a graph in the Graphviz DOT format.
+    ///
+    /// Each node is annotated with the pot size and, if the game is solved, the average expected
+    /// values of both players (OOP / IP). Each edge from a player node is annotated with the
+    /// action and its overall frequency (see [`action_frequencies`]). The graph can be pruned by
+    /// depth and by frequency with [`DotOptions`].
+    ///
+    /// The current node is restored after the call.
+    ///
+    /// **Time complexity:** *O*(#(exported nodes) * #(depth) * (#(OOP private hands) + #(IP
+    /// private hands))).
+    ///
+    /// [`action_frequencies`]: #method.action_frequencies
+    pub fn export_dot<W: Write>(
+        &mut self,
+        writer: &mut W,
+        options: &DotOptions,
+    ) -> Result<(), SolverError> {
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+
+        let write_err = |e: std::io::Error| SolverError::io("Failed to write", &e);
+        let original_history = self.history().to_vec();
+
+        let result = self.line_from_history(&options.history).and_then(|_| {
+            writeln!(writer, "digraph tree {{").map_err(write_err)?;
+            writeln!(writer, "  node [shape=box];").map_err(write_err)?;
+            let mut history = options.history.clone();
+            let mut next_id = 0;
+            self.write_dot_node(writer, options, &mut history, 0, &mut next_id)
+                .map_err(write_err)?;
+            writeln!(writer, "}}").map_err(write_err)
+        });
+
+        self.apply_history(&original_history);
+        result
+    }
+
+    /// Writes the node reached by `history` and its subtree, and returns the ID of the node.
+    fn write_dot_node<W: Write>(
+        &mut self,
+        writer: &mut W,
+        options: &DotOptions,
+        history: &mut Vec<usize>,
+        depth: usize,
+        next_id: &mut usize,
+    ) -> std::io::Result<usize> {
+        self.apply_history(history);
+        self.cache_normalized_weights();
+
+        let id = *next_id;
+        *next_id += 1;
+
+        let (title, shape) = if self.is_terminal_node() {
+            let title = match self.node().prev_action {
+                Action::Fold => "Fold",
+                _ => "Showdown",
+            };
+            (title.to_owned(), "ellipse")
+        } else if self.is_chance_node() {
+            let street = match self.current_board().len() {
+                3 => "Deal turn",
+                _ => "Deal river",
+            };
+            (street.to_owned(), "diamond")
+        } else {
+            let player = self.current_player();
+            (
+                player_label(player, self.card_config.positions).to_owned(),
+                "box",
+            )
+        };
+
+        let mut ev_label = String::new();
+        if self.is_solved() {
+            let ev = [0, 1].map(|player| {
+                compute_average(
+                    &self.expected_values(player),
+                    self.normalized_weights(player),
+                )
+            });
+            if ev.iter().all(|ev| ev.is_finite()) {
+                ev_label = format!("\\nEV {:.2} / {:.2}", ev[0], ev[1]);
+            }
+        }
+        writeln!(
+            writer,
+            "  n{id} [label=\"{title}\\npot {}{ev_label}\", shape={shape}];",
+            self.current_pot()
+        )?;
+
+        if self.is_terminal_node() || options.max_depth.is_some_and(|max| depth >= max) {
+            return Ok(id);
+        }
+
+        if self.is_chance_node() {
+            if !options.expand_chance {
+                return Ok(id);
+            }
+            let possible_cards = self.possible_cards();
+            for card in 0..52 {
+                if possible_cards & (1 << card) != 0 {
+                    history.push(card);
+                    let child =
+                        self.write_dot_node(writer, options, history, depth + 1, next_id)?;
+                    history.pop();
+                    let card = card_to_string(card as Card).unwrap();
+                    writeln!(writer, "  n{id} -> n{child} [label=\"{card}\"];")?;
+                }
+            }
+            return Ok(id);
+        }
+
+        let actions = self.available_actions();
+        let frequencies = self.action_frequencies();
+        for (index, (action, freq)) in actions.iter().zip(frequencies).enumerate() {
+            // unreachable nodes have `NaN` frequencies
+            let is_pruned = if freq.is_nan() {
+                options.min_frequency > 0.0
+            } else {
+                freq < options.min_frequency
+            };
+            if is_pruned {
+                continue;
+            }
+            history.push(index);
+            let child = self.write_dot_node(writer, options, history, depth + 1, next_id)?;
+            history.pop();
+            writeln!(
+                writer,
+                "  n{id} -> n{child} [label=\"{action}\\n{:.1}%\"];",
+                freq * 100.0
+            )?;
+        }
+
+        Ok(id)
+    }
+
+    /// Returns the data of each private hand of the current player for scatter plots of the
+    /// action frequency against the EV, the equity, and the blocker score.
+    ///
+    /// Hands with zero normalized weight are omitted. The blocker score of a hand is the share of
+    /// the opponent's range (weighted by the reach of the opponent) that is blocked by the hole
+    /// cards, which tells how strongly the hand removes the opponent's combos.
+    ///
+    /// Panics if the memory is not yet allocated or the current node is a terminal node or a chance
+    /// node.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// **Time complexity:** see [`equity`], plus *O*(#(OOP private hands) * #(IP private hands)).
+    ///
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    /// [`equity`]: #method.equity
+    pub fn scatter_data(&self) -> Vec<ScatterPoint> {
+        assert!(
+            self.state >= State::MemoryAllocated,
+            "Memory is not allocated"
+        );
+        assert!(!self.is_terminal_node(), "Terminal node is not allowed");
+        assert!(!self.is_chance_node(), "Chance node is not allowed");
+
+        let player = self.current_player();
+        let weights = self.normalized_weights(player);
+        let num_hands = weights.len();
+        let strategy = self.strategy();
+        let num_actions = strategy.len() / num_hands;
+        let equity = self.equity(player);
+        let (ev, ev_detail) = if self.is_solved() {
+            (
+                self.expected_values(player),
+                self.expected_values_detail(player),
+            )
+        } else {
+            (vec![f32::NAN; num_hands], Vec::new())
+        };
+
+        let opponent_cards = &self.private_cards[player ^ 1];
+        let opponent_weights = self.weights(player ^ 1);
+        let opponent_sum = opponent_weights.iter().fold(0.0, |acc, &w| acc + w as f64);
+
+        let column = |values: &[f32], hand: usize| {
+            (0..num_actions)
+                .map(|action| values[action * num_hands + hand])
+                .collect::<Vec<_>>()
+        };
+
+        (0..num_hands)
+            .filter(|&hand| weights[hand] > 0.0)
+            .map(|hand| {
+                let (c1, c2) = self.private_cards[player][hand];
+                let blocked = opponent_cards
+                    .iter()
+                    .zip(opponent_weights)
+                    .filter(|&(&(c3, c4), _)| c1 == c3 || c1 == c4 || c2 == c3 || c2 == c4)
+                    .fold(0.0, |acc, (_, &w)| acc + w as f64);
+
+                ScatterPoint {
+                    hand: (c1, c2),
+                    weight: weights[hand],
+                    frequencies: column(&strategy, hand),
+                    action_ev: if ev_detail.is_empty() {
+                        Vec::new()
+                    } else {
+                        column(&ev_detail, hand)
+                    },
+                    ev: ev[hand],
+                    equity: equity[hand],
+                    blocker_score: (blocked / opponent_sum) as f32,
+                }
+            })
+            .collect()
+    }
+
+    /// Writes the data returned by [`scatter_data`] in CSV format.
+    ///
+    /// The columns are `hand`, `weight`, `equity`, `blocker_score`, `ev`, `action`, `frequency`,
+    /// and `action_ev`, with one row per private hand and action. The EV columns are empty if the
+    /// game is not solved.
+    ///
+    /// [`scatter_data`]: #method.scatter_data
+    pub fn export_scatter<W: Write>(&self, writer: &mut W) -> Result<(), SolverError> {
+        let write_err = |e: std::io::Error| SolverError::io("Failed to write", &e);
+        let actions = self.available_actions();
+
+        writeln!(
+            writer,
+            "hand,weight,equity,blocker_score,ev,action,frequency,action_ev"
+        )
+        .map_err(write_err)?;
+
+        for point in self.scatter_data() {
+            let hand = hole_to_string(point.hand).unwrap();
+            let ev = if point.ev.is_nan() {
+                String::new()
+            } else {
+                point.ev.to_string()
+            };
+            for (index, action) in actions.iter().enumerate() {
+                let action_ev = match point.action_ev.get(index) {
+                    Some(v) => v.to_string(),
+                    None => String::new(),
+                };
+                writeln!(
+                    writer,
+                    "{hand},{},{},{},{ev},{},{},{action_ev}",
+                    point.weight,
+                    point.equity,
+                    point.blocker_score,
+                    csv_escape(&action.to_string()),
+                    point.frequencies[index],
+                )
+                .map_err(write_err)?;
+            }
+        }
+
+        Ok(())
+    }
+}
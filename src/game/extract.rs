@@ -0,0 +1,153 @@
+use super::*;
+use crate::range::*;
+
+#[cfg(feature = "rayon")]
+use crate::utility::*;
+
+impl PostFlopGame {
+    /// Extracts the subtree rooted at the current node into a standalone game.
+    ///
+    /// The current node must be the root node or the first decision of the turn or the river
+    /// (i.e., the node right after a card is dealt). The returned game starts on that street with
+    /// the current board, and its starting pot and effective stack are those of the current node.
+    /// The range of each player is the current [`weights`], i.e., the initial range weighted by the
+    /// reach probabilities of the player's own actions. The bet size options are inherited, and the
+    /// lines added to or removed from the tree in the subtree are applied to the new tree.
+    ///
+    /// The returned game is not solved: allocate the memory and solve it (e.g., with more
+    /// iterations for higher accuracy) as usual. The dedicated thread pool, the table cache, the
+    /// custom hand evaluator, the compression parameters, and the update schedules are inherited,
+    /// while the locked strategies and the bunching effect are not.
+    ///
+    /// Returns an error if the current node is not a valid root of a game, the game uses ICM, or
+    /// the tree of the new game cannot be built. The current node is preserved.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["AA,KK,87s,65s,A5s".parse().unwrap(), "QQ-99,AK,KQs,T9s".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("2c").unwrap(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::Turn,
+    ///     starting_pot: 100,
+    ///     effective_stack: 200,
+    ///     turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+    ///     river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// game.allocate_memory(false);
+    /// solve(&mut game, 100, 0.0, false);
+    ///
+    /// // bet-call on the turn, and the 3s on the river
+    /// game.apply_history(&[1, 1, card_from_str("3s").unwrap() as usize]);
+    /// let mut river_game = game.extract_subtree().unwrap();
+    /// assert_eq!(river_game.tree_config().starting_pot, 200);
+    /// assert_eq!(river_game.tree_config().effective_stack, 150);
+    ///
+    /// river_game.allocate_memory(false);
+    /// solve(&mut river_game, 1000, 0.1, false);
+    /// ```
+    ///
+    /// [`weights`]: #method.weights
+    pub fn extract_subtree(&mut self) -> Result<Self, SolverError> {
+        if self.state <= State::Uninitialized {
+            return Err(SolverError::InvalidState(
+                "Game is not successfully initialized".to_owned(),
+            ));
+        }
+        if self.tree_config.icm_config.is_some() {
+            return Err(SolverError::InvalidTreeConfig(
+                "Subtree of a game with ICM cannot be extracted".to_owned(),
+            ));
+        }
+
+        let history = self.history().to_vec();
+        let is_street_root = history.is_empty()
+            || (!self.is_terminal_node()
+                && !self.is_chance_node()
+                && matches!(self.node().prev_action, Action::Chance(_)));
+        if !is_street_root {
+            return Err(SolverError::InvalidState(
+                "Current node is not the first decision of a street".to_owned(),
+            ));
+        }
+
+        let board = self.current_board();
+        let card_config = CardConfig {
+            range: [0, 1].map(|player| {
+                Range::from_hands_weights(&self.private_cards[player], self.weights(player))
+                    .unwrap()
+            }),
+            turn: board.get(3).copied().unwrap_or(NOT_DEALT),
+            river: board.get(4).copied().unwrap_or(NOT_DEALT),
+            ..self.card_config.clone()
+        };
+
+        let bet = self.total_bet_amount();
+        let tree_config = TreeConfig {
+            initial_state: match board.len() {
+                3 => BoardState::Flop,
+                4 => BoardState::Turn,
+                _ => BoardState::River,
+            },
+            starting_pot: self.tree_config.starting_pot + bet[0] + bet[1],
+            effective_stack: self.tree_config.effective_stack - bet[0],
+            ..self.tree_config.clone()
+        };
+
+        // the lines of the action tree omit the dealt cards
+        let prefix = self
+            .line_from_history(&history)?
+            .into_iter()
+            .filter(|action| !matches!(action, Action::Chance(_)))
+            .collect::<Vec<_>>();
+        self.apply_history(&history);
+
+        let mut action_tree = ActionTree::new(tree_config)?;
+        for line in &self.added_lines {
+            if line.len() > prefix.len() && line.starts_with(&prefix) {
+                action_tree.add_line(&line[prefix.len()..])?;
+            }
+        }
+        for line in &self.removed_lines {
+            if line.len() > prefix.len() && line.starts_with(&prefix) {
+                action_tree.remove_line(&line[prefix.len()..])?;
+            }
+        }
+
+        let (tree_config, added_lines, removed_lines, action_nodes) = action_tree.eject();
+        let mut ret = Self {
+            tree_config,
+            added_lines,
+            removed_lines,
+            action_nodes: action_nodes.into(),
+            compression_params: self.compression_params,
+            update_schedule: self.update_schedule,
+            table_cache: self.table_cache.clone(),
+            equity_cache: self.equity_cache.clone(),
+            hand_evaluator: self.hand_evaluator.clone(),
+            split_pot_evaluator: self.split_pot_evaluator.clone(),
+            #[cfg(feature = "rayon")]
+            thread_pool: self.thread_pool.clone(),
+            ..Default::default()
+        };
+
+        #[cfg(feature = "rayon")]
+        if let Some(pool) = outer_thread_pool(&ret) {
+            pool.install(|| ret.init_with_action_tree(card_config))?;
+            return Ok(ret);
+        }
+
+        ret.init_with_action_tree(card_config)?;
+        Ok(ret)
+    }
+}
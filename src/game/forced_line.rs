@@ -0,0 +1,146 @@
+use super::*;
+
+/// Result of [`PostFlopGame::force_line`].
+///
+/// The expected values follow the convention of [`compute_current_ev`], i.e., (starting pot) / 2
+/// is subtracted.
+///
+/// [`compute_current_ev`]: crate::compute_current_ev
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForcedLineReport {
+    /// Player whose line is forced.
+    pub player: usize,
+
+    /// Number of nodes of the player at which an action was forced, counting every dealt card
+    /// separately.
+    pub num_nodes: usize,
+
+    /// Expected value of the player with the strategy before forcing the line.
+    pub baseline_ev: f32,
+
+    /// Expected value of the player following the line against the strategy of the opponent.
+    pub ev: f32,
+
+    /// Loss of the expected value by following the line, i.e., `baseline_ev - ev`.
+    pub ev_loss: f32,
+}
+
+impl PostFlopGame {
+    /// Forces `player` to follow a deterministic line against the strategy of the opponent and
+    /// reports the loss of the expected value.
+    ///
+    /// At every node of `player` in the whole tree (every runout is visited), `policy` is called
+    /// with the line reaching the node (see [`line_to_string`]) and the available actions, and
+    /// returns the action to take with every hand. This quantifies the cost of a simple strategy
+    /// that a human can follow, such as "always check and call down". The opponent keeps its
+    /// strategy (i.e., without re-solving), so the result is the expected value of the line
+    /// against the solved opponent, not against an opponent adapting to it.
+    ///
+    /// The line is applied as strategy overrides (see [`what_if`]), so the expected values of the
+    /// line can be inspected at any node with the usual methods; call
+    /// [`clear_strategy_overrides`] to restore the original strategy. Isomorphic runouts share
+    /// their nodes, so `policy` must return the same action for lines that differ only in the
+    /// suits of the dealt cards. The current node is preserved.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the game is not solved,
+    /// [`SolverError::InvalidArgument`] if `player` is not 0 or 1, and [`SolverError::InvalidLine`]
+    /// if `policy` returns an action that is not available. Nothing is changed in case of an
+    /// error.
+    ///
+    /// **Time complexity:** *O*(#(nodes) * #(private hands)).
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["AA,KK,87s,65s,A5s".parse().unwrap(), "QQ-99,AK,KQs,T9s".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("2c").unwrap(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::Turn,
+    ///     starting_pot: 100,
+    ///     effective_stack: 100,
+    ///     turn_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+    ///     river_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// game.allocate_memory(false);
+    /// solve(&mut game, 100, 0.0, false);
+    ///
+    /// // OOP always checks and calls down
+    /// let report = game
+    ///     .force_line(0, |_, actions| {
+    ///         if actions.contains(&Action::Call) {
+    ///             Action::Call
+    ///         } else {
+    ///             Action::Check
+    ///         }
+    ///     })
+    ///     .unwrap();
+    /// println!("check-call down loses {:.2} chips", report.ev_loss);
+    ///
+    /// game.clear_strategy_overrides();
+    /// ```
+    ///
+    /// [`line_to_string`]: crate::line_to_string
+    /// [`what_if`]: #method.what_if
+    /// [`clear_strategy_overrides`]: #method.clear_strategy_overrides
+    pub fn force_line<F>(
+        &mut self,
+        player: usize,
+        mut policy: F,
+    ) -> Result<ForcedLineReport, SolverError>
+    where
+        F: FnMut(&[Action], &[Action]) -> Action,
+    {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+        if player > 1 {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid player: {player}"
+            )));
+        }
+
+        let mut overrides = Vec::new();
+        self.walk_player_nodes(&[], None, &mut |game, history, line| {
+            if game.current_player() != player {
+                return Ok(());
+            }
+
+            let actions = game.available_actions();
+            let action = policy(line, &actions);
+            let Some(index) = actions.iter().position(|&a| a == action) else {
+                return Err(SolverError::InvalidLine {
+                    line: [line, &[action]].concat(),
+                    message: format!(
+                        "Action {action} is not available at the line `{}`",
+                        line_to_string(line)
+                    ),
+                });
+            };
+
+            let num_hands = game.num_private_hands(player);
+            let mut strategy = vec![0.0; actions.len() * num_hands];
+            strategy[index * num_hands..(index + 1) * num_hands].fill(1.0);
+            overrides.push((history.to_vec(), strategy));
+            Ok(())
+        })?;
+
+        let report = self.what_if(&overrides)?;
+        Ok(ForcedLineReport {
+            player,
+            num_nodes: overrides.len(),
+            baseline_ev: report.baseline_ev[player],
+            ev: report.ev[player],
+            ev_loss: -report.ev_shift[player],
+        })
+    }
+}
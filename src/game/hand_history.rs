@@ -0,0 +1,598 @@
+use super::*;
+use crate::position::*;
+use crate::range::*;
+
+/// Site format of a hand history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandHistoryFormat {
+    /// PokerStars (including Zoom and Home Games).
+    PokerStars,
+
+    /// GGPoker (as exported by PokerCraft).
+    GGPoker,
+}
+
+/// A heads-up postflop spot extracted from a hand history by [`parse_hand_history`].
+///
+/// The amounts are converted to integers by the `scale` passed to [`parse_hand_history`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandHistorySpot {
+    /// Site format of the hand history.
+    pub format: HandHistoryFormat,
+
+    /// Hand ID.
+    pub hand_id: String,
+
+    /// Names of the players (OOP, IP).
+    pub players: [String; 2],
+
+    /// Positions of the players (OOP, IP), determined from the button and the seated players.
+    /// `None` if the table has more than 9 seated players.
+    pub positions: [Option<Position>; 2],
+
+    /// Hole cards of each player (OOP, IP) if known (i.e., dealt to the hero or shown).
+    pub hands: [Option<(Card, Card)>; 2],
+
+    /// Flop cards.
+    pub flop: [Card; 3],
+
+    /// Turn card, or `NOT_DEALT` if the hand ended on the flop.
+    pub turn: Card,
+
+    /// River card, or `NOT_DEALT` if the hand ended before the river.
+    pub river: Card,
+
+    /// Bitmask of the cards shown by the other players who folded preflop.
+    pub dead_cards: u64,
+
+    /// Pot size at the start of the flop, including antes and the money of the folded players.
+    pub starting_pot: i32,
+
+    /// Effective stack at the start of the flop.
+    pub effective_stack: i32,
+
+    /// Postflop line: the actions taken and the turn and river cards dealt (as
+    /// [`Action::Chance`]), in the format of [`PostFlopGame::annotate_hand_history`].
+    ///
+    /// Bet and raise amounts are the total amounts put in the pot on the street. An action that
+    /// puts the effective stack in the pot is [`Action::AllIn`].
+    pub line: Vec<Action>,
+}
+
+impl HandHistorySpot {
+    /// Returns the card configuration of the spot from the flop with the given ranges (OOP, IP).
+    ///
+    /// The turn and river are left undealt so that the whole line can be replayed.
+    pub fn card_config(&self, range: [Range; 2]) -> CardConfig {
+        CardConfig {
+            range,
+            flop: self.flop,
+            turn: NOT_DEALT,
+            river: NOT_DEALT,
+            dead_cards: self.dead_cards,
+            short_deck: false,
+            positions: self.positions,
+        }
+    }
+
+    /// Returns the tree configuration of the spot from the flop: the pot size and effective stack
+    /// of the spot with the other settings (e.g., bet sizes) of `template`.
+    pub fn tree_config(&self, template: &TreeConfig) -> TreeConfig {
+        TreeConfig {
+            initial_state: BoardState::Flop,
+            starting_pot: self.starting_pot,
+            effective_stack: self.effective_stack,
+            ..template.clone()
+        }
+    }
+}
+
+/// Returns the position of the `rank`-th seat in the postflop order of action at a table of
+/// `num_seats` players, or `None` if the table has more than 9 players.
+///
+/// The seats after the blinds are named from the button backward (`BTN`, `CO`, `HJ`, `LJ`,
+/// `UTG+2`, `UTG+1`), except that the first seat after the blinds is always `UTG`.
+fn table_position(rank: usize, num_seats: usize) -> Option<Position> {
+    const FROM_BUTTON: [Position; 6] = [
+        Position::Button,
+        Position::Cutoff,
+        Position::Hijack,
+        Position::Lojack,
+        Position::UnderTheGun2,
+        Position::UnderTheGun1,
+    ];
+
+    match (num_seats, rank) {
+        (n, _) if n > 9 => None,
+        // heads-up: the button posts the small blind and acts last postflop
+        (2, 0) => Some(Position::BigBlind),
+        (2, _) => Some(Position::Button),
+        (_, 0) => Some(Position::SmallBlind),
+        (_, 1) => Some(Position::BigBlind),
+        (n, 2) if n > 3 => Some(Position::UnderTheGun),
+        (n, r) => Some(FROM_BUTTON[n - 1 - r]),
+    }
+}
+
+/// A seated player during parsing.
+struct Seat {
+    seat: usize,
+    name: String,
+    stack: i64,
+    committed: i64,
+    street_committed: i64,
+    folded: bool,
+    hand: Option<(Card, Card)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Street {
+    Setup,
+    Preflop,
+    Flop,
+    Turn,
+    River,
+    Showdown,
+}
+
+/// Returns the format of the hand history starting with the given header line.
+fn header_format(line: &str) -> Option<HandHistoryFormat> {
+    let line = line.trim_start_matches('\u{feff}').trim_start();
+    if line.starts_with("PokerStars ") && line.contains(" Hand #") {
+        Some(HandHistoryFormat::PokerStars)
+    } else if line.starts_with("Poker Hand #") {
+        Some(HandHistoryFormat::GGPoker)
+    } else {
+        None
+    }
+}
+
+/// Splits a text containing multiple hand histories into the individual hands.
+///
+/// Each hand starts with a PokerStars or GGPoker header line; any text before the first header is
+/// ignored.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let text = "PokerStars Hand #1: ...\n...\n\n\nPokerStars Hand #2: ...\n...\n";
+/// let hands = split_hand_histories(text);
+/// assert_eq!(hands, ["PokerStars Hand #1: ...\n...", "PokerStars Hand #2: ...\n..."]);
+/// ```
+pub fn split_hand_histories(text: &str) -> Vec<&str> {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if header_format(line).is_some() {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+
+    let ends = starts.iter().skip(1).copied().chain([text.len()]);
+    starts
+        .iter()
+        .zip(ends)
+        .map(|(&start, end)| text[start..end].trim())
+        .collect()
+}
+
+/// Parses a No-Limit Hold'em hand history of PokerStars or GGPoker and extracts the heads-up
+/// postflop spot.
+///
+/// The game tree uses integer amounts, so the amounts in the hand history are multiplied by
+/// `scale` and rounded (e.g., `100.0` converts dollars into cents, and `1.0` keeps tournament
+/// chips). The positions are determined from the button: the player to the left of the button acts
+/// first (OOP) on the flop.
+///
+/// Returns [`SolverError::InvalidData`] if the text cannot be parsed, or the hand is not No-Limit
+/// Hold'em, does not reach the flop, or is not heads up on the flop. Run-it-twice hands are not
+/// supported.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let text = "\
+/// PokerStars Hand #1: Hold'em No Limit ($0.50/$1.00 USD) - 2024/01/01 12:00:00 ET
+/// Table 'Alpha' 6-max Seat #1 is the button
+/// Seat 1: Alice ($100 in chips)
+/// Seat 2: Bob ($100 in chips)
+/// Seat 3: Carol ($100 in chips)
+/// Bob: posts small blind $0.50
+/// Carol: posts big blind $1
+/// *** HOLE CARDS ***
+/// Dealt to Alice [Ah Kd]
+/// Alice: raises $1.50 to $2.50
+/// Bob: folds
+/// Carol: calls $1.50
+/// *** FLOP *** [Kh 9h 4c]
+/// Carol: checks
+/// Alice: bets $3
+/// Carol: calls $3
+/// *** TURN *** [Kh 9h 4c] [2s]
+/// Carol: checks
+/// Alice: bets $7.50
+/// Carol: folds
+/// Uncalled bet ($7.50) returned to Alice
+/// Alice collected $11.50 from pot
+/// *** SUMMARY ***
+/// Total pot $11.50 | Rake $0
+/// Board [Kh 9h 4c 2s]";
+///
+/// let spot = parse_hand_history(text, 100.0).unwrap();
+/// assert_eq!(spot.players, ["Carol", "Alice"]);
+/// assert_eq!(spot.positions, [Some(Position::BigBlind), Some(Position::Button)]);
+/// assert_eq!((spot.starting_pot, spot.effective_stack), (550, 9750));
+/// assert_eq!(
+///     line_to_string(&spot.line),
+///     "X:B300:C:2s:X:B750:F"
+/// );
+/// assert_eq!(spot.hands[1], Some((card_from_str("Ah").unwrap(), card_from_str("Kd").unwrap())));
+///
+/// // the spot can be solved and annotated with `annotate_hand_history`
+/// let bet_sizes = BetSizeOptions::try_from(("50%, 100%", "")).unwrap();
+/// let template = TreeConfig {
+///     flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+///     ..Default::default()
+/// };
+/// let tree_config = spot.tree_config(&template);
+/// assert_eq!(tree_config.starting_pot, 550);
+/// ```
+pub fn parse_hand_history(text: &str, scale: f64) -> Result<HandHistorySpot, SolverError> {
+    if !(scale.is_finite() && scale > 0.0) {
+        return Err(SolverError::InvalidArgument(format!(
+            "Invalid scale: {scale}"
+        )));
+    }
+
+    let invalid = SolverError::InvalidData;
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    let header = lines
+        .next()
+        .ok_or_else(|| invalid("Empty hand history".to_owned()))?;
+    let format =
+        header_format(header).ok_or_else(|| invalid(format!("Unknown header: {header}")))?;
+    if !header.contains("Hold'em No Limit") {
+        return Err(invalid(format!("Unsupported game: {header}")));
+    }
+    let hand_id = header
+        .split_once('#')
+        .and_then(|(_, rest)| rest.split(':').next())
+        .map(|id| id.trim().to_owned())
+        .ok_or_else(|| invalid(format!("Invalid header: {header}")))?;
+
+    let mut parser = Parser {
+        scale,
+        seats: Vec::new(),
+        button: None,
+        street: Street::Setup,
+        board: Vec::new(),
+        positions: None,
+        table_positions: [None; 2],
+        starting_pot: 0,
+        effective_stack: 0,
+        prev_streets: 0,
+        line: Vec::new(),
+    };
+
+    for line in lines {
+        if line.starts_with("*** SUMMARY") {
+            break;
+        }
+        parser.parse_line(line).map_err(invalid)?;
+    }
+
+    let positions = parser
+        .positions
+        .ok_or_else(|| invalid("The hand did not reach the flop".to_owned()))?;
+    let mut dead_cards = 0;
+    for (index, seat) in parser.seats.iter().enumerate() {
+        if let Some((c1, c2)) = seat.hand
+            && !positions.contains(&index)
+        {
+            dead_cards |= (1 << c1) | (1 << c2);
+        }
+    }
+
+    let card = |i: usize| parser.board.get(i).copied().unwrap_or(NOT_DEALT);
+    let to_i32 = |amount: i64| {
+        i32::try_from(amount).map_err(|_| invalid(format!("Amount is too large: {amount}")))
+    };
+
+    Ok(HandHistorySpot {
+        format,
+        hand_id,
+        players: positions.map(|index| parser.seats[index].name.clone()),
+        positions: parser.table_positions,
+        hands: positions.map(|index| parser.seats[index].hand),
+        flop: [card(0), card(1), card(2)],
+        turn: card(3),
+        river: card(4),
+        dead_cards,
+        starting_pot: to_i32(parser.starting_pot)?,
+        effective_stack: to_i32(parser.effective_stack)?,
+        line: parser.line,
+    })
+}
+
+struct Parser {
+    scale: f64,
+    seats: Vec<Seat>,
+    button: Option<usize>,
+    street: Street,
+    board: Vec<Card>,
+    positions: Option<[usize; 2]>,
+    table_positions: [Option<Position>; 2],
+    starting_pot: i64,
+    effective_stack: i64,
+    prev_streets: i64,
+    line: Vec<Action>,
+}
+
+impl Parser {
+    fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        if let Some(marker) = line.strip_prefix("*** ") {
+            return self.parse_marker(marker);
+        }
+
+        if self.street == Street::Setup {
+            if let Some(rest) = line.strip_prefix("Table ") {
+                if let Some((_, rest)) = rest.split_once("Seat #") {
+                    let seat = rest.split(' ').next().unwrap_or_default();
+                    self.button = Some(seat.parse().map_err(|_| format!("Invalid table: {line}"))?);
+                }
+                return Ok(());
+            }
+            if let Some(rest) = line.strip_prefix("Seat ") {
+                return self.parse_seat(rest);
+            }
+        }
+
+        if let Some(rest) = line.strip_prefix("Dealt to ") {
+            // other players may be listed without hole cards
+            if let Some(index) = self.find_player(rest)
+                && let Some(cards) = bracketed(&rest[self.seats[index].name.len()..])
+            {
+                self.seats[index].hand = Some(parse_hole(cards)?);
+            }
+            return Ok(());
+        }
+
+        if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+            let (amount, name) = rest
+                .split_once(") returned to ")
+                .ok_or_else(|| format!("Invalid line: {line}"))?;
+            let amount = self.parse_amount(amount)?;
+            let index = self
+                .seats
+                .iter()
+                .position(|seat| seat.name == name)
+                .ok_or_else(|| format!("Unknown player: {name}"))?;
+            let seat = &mut self.seats[index];
+            seat.committed -= amount;
+            seat.street_committed -= amount;
+            return Ok(());
+        }
+
+        // other lines (e.g., chat and summary) are ignored
+        match self.find_player(line) {
+            Some(index) => match line[self.seats[index].name.len()..].strip_prefix(": ") {
+                Some(action) => self.parse_action(index, action),
+                None => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+
+    fn parse_seat(&mut self, rest: &str) -> Result<(), String> {
+        let invalid = || format!("Invalid seat: Seat {rest}");
+        let (seat, rest) = rest.split_once(": ").ok_or_else(invalid)?;
+        let seat = seat.parse().map_err(|_| invalid())?;
+        let chips = rest.find(" in chips").ok_or_else(invalid)?;
+        let open = rest[..chips].rfind(" (").ok_or_else(invalid)?;
+        let stack = self.parse_amount(&rest[open + 2..chips])?;
+
+        // players sitting out are not dealt in
+        let status = &rest[chips..];
+        let is_out = status.contains("sitting out") || status.contains("out of hand");
+
+        self.seats.push(Seat {
+            seat,
+            name: rest[..open].to_owned(),
+            stack,
+            committed: 0,
+            street_committed: 0,
+            folded: is_out,
+            hand: None,
+        });
+        Ok(())
+    }
+
+    fn parse_marker(&mut self, marker: &str) -> Result<(), String> {
+        let name = marker.split(" ***").next().unwrap_or_default();
+        let next_street = match name {
+            "HOLE CARDS" => Street::Preflop,
+            "FLOP" => Street::Flop,
+            "TURN" => Street::Turn,
+            "RIVER" => Street::River,
+            "SHOW DOWN" | "SHOWDOWN" => Street::Showdown,
+            _ if name.starts_with("FIRST ") || name.starts_with("SECOND ") => {
+                return Err("Run-it-twice hands are not supported".to_owned());
+            }
+            _ => return Ok(()),
+        };
+
+        if next_street <= self.street {
+            return Err(format!("Unexpected street: *** {marker}"));
+        }
+        if next_street == Street::Showdown {
+            self.street = next_street;
+            return Ok(());
+        }
+
+        // the newly dealt cards are in the last brackets
+        if next_street > Street::Preflop {
+            let cards = marker
+                .rfind('[')
+                .and_then(|open| bracketed(&marker[open..]))
+                .ok_or_else(|| format!("Board is missing: *** {marker}"))?;
+            let cards = cards_from_str(cards).map_err(|e| e.to_string())?;
+            let expected = if next_street == Street::Flop { 3 } else { 1 };
+            if cards.len() != expected || self.board.len() != next_street as usize + 1 - expected {
+                return Err(format!("Invalid board: *** {marker}"));
+            }
+
+            if next_street == Street::Flop {
+                self.start_flop()?;
+            } else {
+                self.prev_streets += self.seats[self.positions.unwrap()[0]].street_committed;
+                self.line.push(Action::Chance(cards[0]));
+            }
+
+            self.board.extend(cards);
+            self.seats
+                .iter_mut()
+                .for_each(|seat| seat.street_committed = 0);
+        }
+
+        self.street = next_street;
+        Ok(())
+    }
+
+    fn start_flop(&mut self) -> Result<(), String> {
+        let button = self.button.ok_or("Button is missing")?;
+        let mut active = (0..self.seats.len())
+            .filter(|&index| !self.seats[index].folded)
+            .collect::<Vec<_>>();
+        if active.len() != 2 {
+            return Err(format!(
+                "Only heads-up postflop spots are supported: {} players saw the flop",
+                active.len()
+            ));
+        }
+
+        // the player to the left of the button acts first
+        let order_key = |&index: &usize| {
+            let seat = self.seats[index].seat;
+            (seat <= button, seat)
+        };
+        active.sort_by_key(order_key);
+
+        let mut order = (0..self.seats.len()).collect::<Vec<_>>();
+        order.sort_by_key(order_key);
+        self.table_positions = [active[0], active[1]].map(|index| {
+            let rank = order.iter().position(|&i| i == index).unwrap();
+            table_position(rank, order.len())
+        });
+
+        self.positions = Some([active[0], active[1]]);
+        self.starting_pot = self.seats.iter().map(|seat| seat.committed).sum();
+        self.effective_stack = active
+            .iter()
+            .map(|&index| self.seats[index].stack - self.seats[index].committed)
+            .min()
+            .unwrap();
+        Ok(())
+    }
+
+    fn parse_action(&mut self, index: usize, action: &str) -> Result<(), String> {
+        let invalid = || format!("Invalid action: {action}");
+        let is_postflop = (Street::Flop..=Street::River).contains(&self.street)
+            && self
+                .positions
+                .is_some_and(|positions| positions.contains(&index));
+        let action = action.trim_end_matches(" and is all-in");
+
+        let ante = action
+            .strip_prefix("posts the ante ")
+            .or_else(|| action.strip_prefix("posts ante "));
+        if let Some(amount) = ante {
+            self.seats[index].committed += self.parse_amount(amount)?;
+            return Ok(());
+        }
+
+        let seat = &self.seats[index];
+        let (action, added) = if let Some(rest) = action.strip_prefix("posts ") {
+            // blinds and straddles
+            let amount = rest.rsplit(' ').next().ok_or_else(invalid)?;
+            (Action::None, self.parse_amount(amount)?)
+        } else if let Some(cards) = action.strip_prefix("shows ") {
+            let cards = bracketed(cards).ok_or_else(invalid)?;
+            self.seats[index].hand = Some(parse_hole(cards)?);
+            return Ok(());
+        } else if let Some(amount) = action.strip_prefix("calls ") {
+            (Action::Call, self.parse_amount(amount)?)
+        } else if let Some(amount) = action.strip_prefix("bets ") {
+            let amount = self.parse_amount(amount)?;
+            (Action::Bet(0), amount)
+        } else if let Some(rest) = action.strip_prefix("raises ") {
+            let (_, amount) = rest.split_once(" to ").ok_or_else(invalid)?;
+            let amount = self.parse_amount(amount)?;
+            (Action::Raise(0), amount - seat.street_committed)
+        } else {
+            match action {
+                "folds" => (Action::Fold, 0),
+                "checks" => (Action::Check, 0),
+                _ => return Ok(()),
+            }
+        };
+
+        let seat = &mut self.seats[index];
+        seat.committed += added;
+        seat.street_committed += added;
+        if action == Action::Fold {
+            seat.folded = true;
+        }
+
+        if is_postflop && action != Action::None {
+            let max_amount = self.effective_stack - self.prev_streets;
+            let amount = seat.street_committed;
+            self.line.push(match action {
+                Action::Bet(_) | Action::Raise(_) if amount >= max_amount => {
+                    Action::AllIn(i32::try_from(max_amount).map_err(|_| invalid())?)
+                }
+                Action::Bet(_) => Action::Bet(i32::try_from(amount).map_err(|_| invalid())?),
+                Action::Raise(_) => Action::Raise(i32::try_from(amount).map_err(|_| invalid())?),
+                _ => action,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the index of the player whose name is the longest prefix of `s`.
+    fn find_player(&self, s: &str) -> Option<usize> {
+        (0..self.seats.len())
+            .filter(|&index| s.starts_with(&self.seats[index].name))
+            .max_by_key(|&index| self.seats[index].name.len())
+    }
+
+    /// Parses an amount such as `$1,234.50` and converts it with the scale.
+    fn parse_amount(&self, s: &str) -> Result<i64, String> {
+        let digits = s
+            .trim()
+            .chars()
+            .filter(|&c| c.is_ascii_digit() || c == '.')
+            .collect::<String>();
+        let value = digits
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid amount: {s}"))?;
+        Ok((value * self.scale).round() as i64)
+    }
+}
+
+/// Returns the contents of the first brackets (`[...]`) of `s`.
+fn bracketed(s: &str) -> Option<&str> {
+    let (_, rest) = s.split_once('[')?;
+    rest.split_once(']').map(|(inner, _)| inner)
+}
+
+/// Parses hole cards such as `Ah Kd`.
+fn parse_hole(s: &str) -> Result<(Card, Card), String> {
+    match cards_from_str(s).map_err(|e| e.to_string())?[..] {
+        [c1, c2] => Ok((c1, c2)),
+        _ => Err(format!("Invalid hole cards: {s}")),
+    }
+}
@@ -2,10 +2,16 @@ use dashmap::DashMap;
 use fastrand::Rng;
 use foldhash::HashMap;
 use foldhash::fast::RandomState;
+
+#[cfg(feature = "rayon")]
 use rayon::prelude::*;
 
 const NUM_ITERS: usize = 80000;
 
+/// Number of chunks of the Monte Carlo estimation, which is fixed so that the result does not
+/// depend on the number of threads.
+const NUM_CHUNKS: usize = 64;
+
 #[derive(Clone, Copy)]
 struct ICMEquity {
     short_stack_player: f64,
@@ -17,9 +23,12 @@ pub struct ICMCalculator {
     payouts: Vec<f64>,
     // Stack list of other players, remains constant across multiple calculations
     other_players_stacks: Vec<f64>,
-    // Top-level cache: (Player A's stack) -> (Equity of A and B)
-    // Since the total stacks of A and B remain unchanged, using A's stack as key uniquely determines the state
-    calculation_cache: DashMap<i32, ICMEquity, RandomState>,
+    // Top-level cache: (Shorter stack, deeper stack) -> (Equity of the short and deep players)
+    // The total stacks of A and B differ between the current stacks and the stacks after the
+    // showdown (the pot is excluded from the former), so both stacks are needed as the key
+    calculation_cache: DashMap<(i32, i32), ICMEquity, RandomState>,
+    // Seed of the Monte Carlo estimation
+    seed: u64,
 }
 
 impl ICMCalculator {
@@ -29,7 +38,8 @@ impl ICMCalculator {
     ///
     /// * `other_players_stacks` - A `Vec<i32>` containing the stacks of all players except A and B.
     /// * `payout_structure` - A `Vec<i32>` containing the payout distribution starting from first place.
-    pub fn new(other_players_stacks: Vec<i32>, payout_structure: Vec<i32>) -> Self {
+    /// * `seed` - The seed of the Monte Carlo estimation used when there are many players or payouts.
+    pub fn new(other_players_stacks: Vec<i32>, payout_structure: Vec<i32>, seed: u64) -> Self {
         // Convert payouts to f64 for calculation convenience
         let other_players_stacks = other_players_stacks.into_iter().map(|p| p as f64).collect();
         let payouts = payout_structure.into_iter().map(|p| p as f64).collect();
@@ -37,18 +47,18 @@ impl ICMCalculator {
             payouts,
             other_players_stacks,
             calculation_cache: DashMap::default(),
+            seed,
         }
     }
 
     /// Calculate ICM equity for players A and B given their stacks.
     pub fn calculate(&self, stacks_a: i32, stacks_b: i32) -> (f64, f64) {
-        let cache_key = stacks_a.min(stacks_b);
+        let cache_key = (stacks_a.min(stacks_b), stacks_a.max(stacks_b));
         if let Some(equity) = self.calculation_cache.get(&cache_key) {
             if stacks_a <= stacks_b {
                 return (equity.short_stack_player, equity.deep_stack_player);
-            } else {
-                return (equity.deep_stack_player, equity.short_stack_player);
             }
+            return (equity.deep_stack_player, equity.short_stack_player);
         }
 
         if self.payouts.is_empty() {
@@ -56,14 +66,15 @@ impl ICMCalculator {
                 short_stack_player: 0.0,
                 deep_stack_player: 0.0,
             };
-            self.calculation_cache.insert(stacks_a, result);
+            self.calculation_cache.insert(cache_key, result);
             return (result.short_stack_player, result.deep_stack_player);
         }
 
+        // the shorter stack comes first so that the estimate does not depend on the order
         let num_players = self.other_players_stacks.len() + 2;
         let mut all_stacks = Vec::with_capacity(num_players);
-        all_stacks.push(stacks_a as f64);
-        all_stacks.push(stacks_b as f64);
+        all_stacks.push(cache_key.0 as f64);
+        all_stacks.push(cache_key.1 as f64);
         all_stacks.extend_from_slice(&self.other_players_stacks);
 
         let all_equities = if self.payouts.len() > 16 || num_players > 64 {
@@ -77,22 +88,19 @@ impl ICMCalculator {
             self.calculate_exact_recursive(&all_stacks, initial_mask, 0, &mut memo)
         };
 
-        // Extract results for A and B
-        let equities_a = all_equities[0];
-        let equities_b = all_equities[1];
-        let (short_stack_player, deep_stack_player) = if stacks_a <= stacks_b {
-            (equities_a, equities_b)
-        } else {
-            (equities_b, equities_a)
-        };
+        // Extract results for the short and deep players
         let result = ICMEquity {
-            short_stack_player,
-            deep_stack_player,
+            short_stack_player: all_equities[0],
+            deep_stack_player: all_equities[1],
         };
 
         // Store in top-level cache and return
         self.calculation_cache.insert(cache_key, result);
-        (equities_a, equities_b)
+        if stacks_a <= stacks_b {
+            (result.short_stack_player, result.deep_stack_player)
+        } else {
+            (result.deep_stack_player, result.short_stack_player)
+        }
     }
 
     /// Internal recursive function that computes ICM using bitmask and memoization
@@ -197,56 +205,64 @@ impl ICMCalculator {
             .collect();
 
         let total_iters = num_iters * num_players;
-        let num_threads = rayon::current_num_threads();
-        let iters_per_thread = total_iters / num_threads + 1;
-        (0..num_threads)
-            .into_par_iter()
-            .map(|_| {
-                let mut rng = Rng::new();
-                let mut indexed_values: Vec<(usize, f32)> =
-                    (0..num_players).map(|i| (i, 0.0)).collect();
-                let mut equities = vec![0.0; num_players];
-                for _ in 0..iters_per_thread {
-                    // Generate random values with exponents
-                    for (id, (v, &exp)) in indexed_values.iter_mut().zip(&exponents).enumerate() {
-                        *v = (id, rng.f32().powf(exp));
-                    }
-
-                    // Only sort the top-k elements needed for payouts
-                    if num_payouts < num_players {
-                        indexed_values.select_nth_unstable_by(num_payouts, |a, b| {
-                            b.1.partial_cmp(&a.1).unwrap()
-                        });
-                        indexed_values[..num_payouts]
-                            .sort_unstable_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-                    } else {
-                        indexed_values.sort_unstable_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-                    }
+        let iters_per_chunk = total_iters / NUM_CHUNKS + 1;
+
+        // the random numbers depend only on the seed and the stacks of the two players
+        let stacks_seed = chip_stacks[..2].iter().fold(self.seed, |seed, &stack| {
+            seed.rotate_left(32) ^ stack as u64
+        });
+
+        let simulate = |chunk: usize| {
+            let mut rng = Rng::with_seed(stacks_seed.wrapping_add(chunk as u64));
+            let mut indexed_values: Vec<(usize, f32)> =
+                (0..num_players).map(|i| (i, 0.0)).collect();
+            let mut equities = vec![0.0; num_players];
+            for _ in 0..iters_per_chunk {
+                // Generate random values with exponents
+                for (id, (v, &exp)) in indexed_values.iter_mut().zip(&exponents).enumerate() {
+                    *v = (id, rng.f32().powf(exp));
+                }
 
-                    // Distribute payouts to top finishers
-                    for (&(player_id, _), &payout) in indexed_values.iter().zip(&self.payouts) {
-                        equities[player_id] += payout;
-                    }
+                // Only sort the top-k elements needed for payouts
+                if num_payouts < num_players {
+                    indexed_values
+                        .select_nth_unstable_by(num_payouts, |a, b| b.1.partial_cmp(&a.1).unwrap());
+                    indexed_values[..num_payouts]
+                        .sort_unstable_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+                } else {
+                    indexed_values.sort_unstable_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
                 }
-                for equity in &mut equities {
-                    *equity /= iters_per_thread as f64;
+
+                // Distribute payouts to top finishers
+                for (&(player_id, _), &payout) in indexed_values.iter().zip(&self.payouts) {
+                    equities[player_id] += payout;
                 }
-                equities
-            })
-            .reduce(
-                || vec![0.0; num_players],
-                |a, b| {
-                    let (mut a, b) = if a.capacity() >= b.capacity() {
-                        (a, b)
-                    } else {
-                        (b, a)
-                    };
-                    a.iter_mut()
-                        .zip(b)
-                        .for_each(|(a, b)| *a += b / num_threads as f64);
-                    a
-                },
-            )
+            }
+            for equity in &mut equities {
+                *equity /= (iters_per_chunk * NUM_CHUNKS) as f64;
+            }
+            equities
+        };
+
+        let merge = |mut acc: Vec<f64>, chunk: Vec<f64>| {
+            acc.iter_mut().zip(chunk).for_each(|(a, b)| *a += b);
+            acc
+        };
+
+        // the chunks are summed in a fixed order to make the result deterministic
+        #[cfg(feature = "rayon")]
+        let equities = (0..NUM_CHUNKS)
+            .into_par_iter()
+            .map(simulate)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .fold(vec![0.0; num_players], merge);
+        #[cfg(not(feature = "rayon"))]
+        let equities = (0..NUM_CHUNKS)
+            .map(simulate)
+            .fold(vec![0.0; num_players], merge);
+
+        equities
     }
 }
 
@@ -258,7 +274,7 @@ mod test {
     fn basic() {
         let other_players_stacks = vec![1000; 14];
         let payout_structure = vec![50, 30, 20];
-        let calculator = ICMCalculator::new(other_players_stacks, payout_structure);
+        let calculator = ICMCalculator::new(other_players_stacks, payout_structure, 0);
 
         let (equity_a, equity_b) = calculator.calculate(1000, 1000);
 
@@ -283,7 +299,7 @@ mod test {
     fn player_5_payouts_3() {
         let other_players_stacks = (1..9).collect();
         let payout_structure = vec![50, 30, 20];
-        let calculator = ICMCalculator::new(other_players_stacks, payout_structure);
+        let calculator = ICMCalculator::new(other_players_stacks, payout_structure, 0);
 
         let (equity_a, equity_b) = calculator.calculate(9, 10);
         assert!((equity_a - 15.794621704108263).abs() <= f64::EPSILON);
@@ -295,7 +311,7 @@ mod test {
         let other_players_stacks = vec![1000; 14];
         let mut payout_structure = vec![50, 30, 20, 10, 5, 2];
         payout_structure.extend_from_slice(&[1; 10]);
-        let calculator = ICMCalculator::new(other_players_stacks, payout_structure);
+        let calculator = ICMCalculator::new(other_players_stacks, payout_structure, 0);
 
         let (equity_a_0, equity_b_0) = calculator.calculate(800, 1200);
         let (equity_a_1, equity_b_1) = calculator.calculate(800, 1200);
@@ -307,7 +323,7 @@ mod test {
     fn player_64_payouts_4() {
         let other_players_stacks = vec![1000; 62];
         let payout_structure = vec![50, 30, 20, 10];
-        let calculator = ICMCalculator::new(other_players_stacks, payout_structure);
+        let calculator = ICMCalculator::new(other_players_stacks, payout_structure, 0);
 
         let (equity_a, equity_b) = calculator.calculate(800, 1200);
         eprintln!("{equity_a}, {equity_b}");
@@ -318,9 +334,30 @@ mod test {
         let other_players_stacks = vec![1000; 100];
         let mut payout_structure = vec![200, 100, 80, 50, 30, 20, 10, 5, 2];
         payout_structure.extend_from_slice(&[1; 20]);
-        let calculator = ICMCalculator::new(other_players_stacks, payout_structure);
+        let calculator =
+            ICMCalculator::new(other_players_stacks.clone(), payout_structure.clone(), 0);
 
         let (equity_a, equity_b) = calculator.calculate(800, 1200);
         eprintln!("{equity_a}, {equity_b}");
+
+        // reproducible with the same seed
+        let same_seed =
+            ICMCalculator::new(other_players_stacks.clone(), payout_structure.clone(), 0);
+        assert_eq!(same_seed.calculate(800, 1200), (equity_a, equity_b));
+
+        let other_seed =
+            ICMCalculator::new(other_players_stacks.clone(), payout_structure.clone(), 1);
+        assert_ne!(other_seed.calculate(800, 1200), (equity_a, equity_b));
+
+        // independent of the order of the players
+        let swapped = ICMCalculator::new(other_players_stacks.clone(), payout_structure.clone(), 0);
+        assert_eq!(swapped.calculate(1200, 800), (equity_b, equity_a));
+
+        // with equal stacks, the equity of each player is the average payout
+        let total_payout = payout_structure.iter().sum::<i32>() as f64;
+        let calculator = ICMCalculator::new(other_players_stacks, payout_structure, 0);
+        let (equity_a, equity_b) = calculator.calculate(1000, 1000);
+        assert!((equity_a / (total_payout / 102.0) - 1.0).abs() < 0.05);
+        assert!((equity_b / (total_payout / 102.0) - 1.0).abs() < 0.05);
     }
 }
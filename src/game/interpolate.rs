@@ -0,0 +1,224 @@
+use super::*;
+use crate::interface::*;
+
+/// Response to an off-tree bet, returned by [`PostFlopGame::interpolate_bet_response`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterpolatedResponse {
+    /// Tree actions bracketing the off-tree bet and their weights, which sum to 1. There is only
+    /// one entry if the bet matches a tree action or is outside the range of the tree actions.
+    pub brackets: Vec<(Action, f32)>,
+
+    /// Player responding to the bet (0 = OOP, 1 = IP).
+    pub player: usize,
+
+    /// Available actions of the responding player, taken from the bracket with the larger weight.
+    pub actions: Vec<Action>,
+
+    /// Interpolated strategy of the responding player. The probability of the `i`-th action with
+    /// the `j`-th private hand is stored in the `i * #(private hands) + j`-th element.
+    pub strategy: Vec<f32>,
+
+    /// Interpolated expected values of each private hand of each player (OOP, IP) after the bet.
+    /// `None` if the game is not solved.
+    pub expected_values: Option<[Vec<f32>; 2]>,
+}
+
+impl PostFlopGame {
+    /// Interpolates the response to a bet or raise of `amount` that is not in the tree at the
+    /// current node, from the responses to the nearest tree actions.
+    ///
+    /// `amount` is the total amount of the bet on the current street, like the amount of
+    /// [`Action::Bet`] and [`Action::Raise`]. The bet is mapped to the tree actions with the
+    /// pseudo-harmonic mapping: when the size of the bet is `x` and the sizes of the nearest
+    /// smaller and larger tree actions are `a` and `b`, where a size is the number of the chips
+    /// added beyond a call divided by the pot after the call, the weights of the smaller and larger
+    /// actions are `(b - x) * (1 + a) / ((b - a) * (1 + x))` and the rest, respectively. A bet
+    /// smaller or larger than all the tree actions is mapped entirely to the nearest one.
+    ///
+    /// The strategies and the expected values of the two responses are averaged with these
+    /// weights. The responses must have the same kinds of actions in the same order (fold, check,
+    /// call, or a bet, raise, or all-in, whose amounts may differ); otherwise, an error is
+    /// returned. The current node is preserved.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the current node is not a player node or the memory
+    /// is not allocated, and [`SolverError::InvalidArgument`] if `amount` does not exceed the
+    /// current bet or exceeds the all-in amount.
+    ///
+    /// **Time complexity:** *O*(#(depth) * (#(OOP private hands) + #(IP private hands))).
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["AA,KK,QQ,87s".parse().unwrap(), "JJ,AK,AQ".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("2c").unwrap(),
+    ///     river: card_from_str("3s").unwrap(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::River,
+    ///     starting_pot: 100,
+    ///     effective_stack: 200,
+    ///     river_bet_sizes: [("50%, 100%", "").try_into().unwrap(), Default::default()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// game.allocate_memory(false);
+    /// solve(&mut game, 100, 0.0, false);
+    ///
+    /// // a 75% pot bet is mapped to the 50% and 100% bets
+    /// let response = game.interpolate_bet_response(75).unwrap();
+    /// assert_eq!(response.brackets[0].0, Action::Bet(50));
+    /// assert_eq!(response.brackets[1].0, Action::Bet(100));
+    /// assert_eq!(response.actions, vec![Action::Fold, Action::Call]);
+    /// ```
+    pub fn interpolate_bet_response(
+        &mut self,
+        amount: i32,
+    ) -> Result<InterpolatedResponse, SolverError> {
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(SolverError::InvalidState(
+                "Current node is not a player node".to_owned(),
+            ));
+        }
+
+        let player = self.current_player();
+        let bet = self.total_bet_amount();
+        let prev_bet = match self.node().prev_action {
+            Action::Bet(a) | Action::Raise(a) | Action::AllIn(a) => a,
+            _ => 0,
+        };
+        let all_in = self.tree_config.effective_stack - bet[player ^ 1] + prev_bet;
+        if amount <= prev_bet || amount > all_in {
+            return Err(SolverError::InvalidArgument(format!(
+                "Bet amount must be greater than {prev_bet} and at most {all_in}: {amount}"
+            )));
+        }
+
+        // size of a bet: the number of the chips added beyond a call divided by the pot after the
+        // call
+        let pot = self.tree_config.starting_pot + bet[0] + bet[1];
+        let to_call = (bet[player ^ 1] - bet[player]).max(0);
+        let size = |amount: i32| (amount - prev_bet) as f64 / (pot + to_call) as f64;
+
+        let mut bets = self
+            .available_actions()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, action)| match action {
+                Action::Bet(a) | Action::Raise(a) | Action::AllIn(a) => Some((index, action, a)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        bets.sort_unstable_by_key(|&(_, _, a)| a);
+
+        let (Some(&smallest), Some(&largest)) = (bets.first(), bets.last()) else {
+            return Err(SolverError::InvalidState(
+                "No bet or raise is available at the current node".to_owned(),
+            ));
+        };
+
+        // (index, action, weight)
+        let brackets = if amount <= smallest.2 {
+            vec![(smallest.0, smallest.1, 1.0)]
+        } else if amount >= largest.2 {
+            vec![(largest.0, largest.1, 1.0)]
+        } else {
+            let upper = bets.iter().position(|&(_, _, a)| a >= amount).unwrap();
+            let (lower, upper) = (bets[upper - 1], bets[upper]);
+            if upper.2 == amount {
+                vec![(upper.0, upper.1, 1.0)]
+            } else {
+                let (x, a, b) = (size(amount), size(lower.2), size(upper.2));
+                let weight = ((b - x) * (1.0 + a) / ((b - a) * (1.0 + x))) as f32;
+                vec![(lower.0, lower.1, weight), (upper.0, upper.1, 1.0 - weight)]
+            }
+        };
+
+        let history = self.history().to_vec();
+        let result = self.interpolate_internal(&brackets, player ^ 1);
+        self.apply_history(&history);
+        result
+    }
+
+    fn interpolate_internal(
+        &mut self,
+        brackets: &[(usize, Action, f32)],
+        player: usize,
+    ) -> Result<InterpolatedResponse, SolverError> {
+        let kind = |action: &Action| match action {
+            Action::Bet(_) | Action::Raise(_) | Action::AllIn(_) => Action::Bet(0),
+            _ => *action,
+        };
+
+        let history = self.history().to_vec();
+        let mut ret: Option<InterpolatedResponse> = None;
+        let mut max_weight = 0.0;
+
+        for &(index, _, weight) in brackets {
+            self.apply_history(&history);
+            self.play(index);
+            self.cache_normalized_weights();
+
+            let actions = self.available_actions();
+            let strategy = self.strategy();
+            let expected_values = self
+                .is_solved()
+                .then(|| [self.expected_values(0), self.expected_values(1)]);
+
+            let Some(ret) = ret.as_mut() else {
+                let scale = |v: Vec<f32>| v.into_iter().map(|x| x * weight).collect();
+                ret = Some(InterpolatedResponse {
+                    brackets: brackets
+                        .iter()
+                        .map(|&(_, action, weight)| (action, weight))
+                        .collect(),
+                    player,
+                    actions,
+                    strategy: scale(strategy),
+                    expected_values: expected_values.map(|ev| ev.map(scale)),
+                });
+                max_weight = weight;
+                continue;
+            };
+
+            if actions.len() != ret.actions.len()
+                || actions
+                    .iter()
+                    .zip(&ret.actions)
+                    .any(|(a, b)| kind(a) != kind(b))
+            {
+                return Err(SolverError::InvalidState(format!(
+                    "Responses to the bracketing actions do not match: {:?} and {actions:?}",
+                    ret.actions
+                )));
+            }
+
+            let add = |dst: &mut [f32], src: &[f32]| {
+                dst.iter_mut().zip(src).for_each(|(d, &s)| *d += s * weight);
+            };
+            add(&mut ret.strategy, &strategy);
+            if let (Some(ret_ev), Some(ev)) = (ret.expected_values.as_mut(), &expected_values) {
+                ret_ev
+                    .iter_mut()
+                    .zip(ev)
+                    .for_each(|(dst, src)| add(dst, src));
+            }
+            if weight > max_weight {
+                ret.actions = actions;
+            }
+        }
+
+        Ok(ret.unwrap())
+    }
+}
@@ -1,4 +1,6 @@
+use super::evaluation::MAX_NUM_PRIVATE_HANDS;
 use super::*;
+use crate::error::*;
 use crate::interface::*;
 use crate::sliceop::*;
 use crate::utility::*;
@@ -10,6 +12,37 @@ fn decode_signed_slice(slice: &[i16], scale: f32) -> Vec<f32> {
     slice.iter().map(|&x| x as f32 * decoder).collect()
 }
 
+/// A snapshot of the current node of [`PostFlopGame`], created by [`PostFlopGame::bookmark`].
+///
+/// Restoring a bookmark with [`PostFlopGame::restore_bookmark`] moves to the node without
+/// replaying the history, including the weights and the cached normalized weights. The weights
+/// are those at the time of bookmarking, so a bookmark should be created again after the strategy
+/// is changed (e.g., by solving or node locking).
+#[derive(Debug, Clone)]
+pub struct Bookmark {
+    arena: (usize, usize),
+    action_history: Vec<usize>,
+    node_history: Vec<usize>,
+    is_normalized_weight_cached: bool,
+    turn: Card,
+    river: Card,
+    turn_swapped_suit: Option<(u8, u8)>,
+    turn_swap: Option<u8>,
+    river_swap: Option<(u8, u8)>,
+    total_bet_amount: [i32; 2],
+    weights: [Vec<f32>; 2],
+    normalized_weights: [Vec<f32>; 2],
+    cfvalues_cache: [Vec<f32>; 2],
+}
+
+impl Bookmark {
+    /// Returns the history of the bookmarked node (see [`PostFlopGame::history`]).
+    #[inline]
+    pub fn history(&self) -> &[usize] {
+        &self.action_history
+    }
+}
+
 impl PostFlopGame {
     /// Moves the current node back to the root node.
     #[inline]
@@ -70,6 +103,201 @@ impl PostFlopGame {
         }
     }
 
+    /// Creates a bookmark of the current node, which can be restored by [`restore_bookmark`].
+    ///
+    /// **Time complexity:** *O*(#(OOP private hands) + #(IP private hands) + length of history)
+    ///
+    /// [`restore_bookmark`]: #method.restore_bookmark
+    pub fn bookmark(&self) -> Bookmark {
+        assert!(
+            self.state > State::Uninitialized,
+            "Game is not successfully initialized"
+        );
+
+        Bookmark {
+            arena: self.arena_id(),
+            action_history: self.action_history.clone(),
+            node_history: self.node_history.clone(),
+            is_normalized_weight_cached: self.is_normalized_weight_cached,
+            turn: self.turn,
+            river: self.river,
+            turn_swapped_suit: self.turn_swapped_suit,
+            turn_swap: self.turn_swap,
+            river_swap: self.river_swap,
+            total_bet_amount: self.total_bet_amount,
+            weights: self.weights.clone(),
+            normalized_weights: self.normalized_weights.clone(),
+            cfvalues_cache: self.cfvalues_cache.clone(),
+        }
+    }
+
+    /// Moves the current node to the node of `bookmark` without replaying its history.
+    ///
+    /// The result is the same as [`apply_history`] with the history of the bookmark, followed by
+    /// [`cache_normalized_weights`] if they were cached at the time of bookmarking.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the bookmark was created by another instance or
+    /// before the game tree was rebuilt.
+    ///
+    /// **Time complexity:** *O*(#(OOP private hands) + #(IP private hands) + length of history)
+    ///
+    /// [`apply_history`]: #method.apply_history
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    pub fn restore_bookmark(&mut self, bookmark: &Bookmark) -> Result<(), SolverError> {
+        if self.state <= State::Uninitialized {
+            return Err(SolverError::InvalidState(
+                "Game is not successfully initialized".to_owned(),
+            ));
+        }
+
+        if bookmark.arena != self.arena_id()
+            || (0..2).any(|player| bookmark.weights[player].len() != self.weights[player].len())
+        {
+            return Err(SolverError::InvalidState(
+                "Bookmark does not belong to this game tree".to_owned(),
+            ));
+        }
+
+        self.action_history.clone_from(&bookmark.action_history);
+        self.node_history.clone_from(&bookmark.node_history);
+        self.is_normalized_weight_cached = bookmark.is_normalized_weight_cached;
+        self.turn = bookmark.turn;
+        self.river = bookmark.river;
+        self.turn_swapped_suit = bookmark.turn_swapped_suit;
+        self.turn_swap = bookmark.turn_swap;
+        self.river_swap = bookmark.river_swap;
+        self.total_bet_amount = bookmark.total_bet_amount;
+
+        for player in 0..2 {
+            self.weights[player].copy_from_slice(&bookmark.weights[player]);
+            self.normalized_weights[player].copy_from_slice(&bookmark.normalized_weights[player]);
+            self.cfvalues_cache[player].copy_from_slice(&bookmark.cfvalues_cache[player]);
+        }
+
+        Ok(())
+    }
+
+    /// Undoes the last action played, i.e., moves the current node back to its parent.
+    ///
+    /// The undone actions can be replayed by [`redo`]. If the normalized weights were cached, they
+    /// are cached again at the parent node.
+    ///
+    /// Returns an error if the current node is the root node.
+    ///
+    /// **Time complexity:** *O*(length of history × (#(OOP private hands) + #(IP private hands)))
+    ///
+    /// [`redo`]: #method.redo
+    pub fn undo(&mut self) -> Result<(), SolverError> {
+        assert!(
+            self.state > State::Uninitialized,
+            "Game is not successfully initialized"
+        );
+
+        if self.action_history.is_empty() {
+            return Err(SolverError::InvalidState("No action to undo".to_owned()));
+        }
+
+        // remembers the current line unless it is already a part of the line to redo
+        if !self.redo_history.starts_with(&self.action_history) {
+            self.redo_history.clone_from(&self.action_history);
+        }
+
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
+        let history = self.action_history[..self.action_history.len() - 1].to_vec();
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
+        }
+
+        Ok(())
+    }
+
+    /// Replays the next action undone by [`undo`].
+    ///
+    /// The undone actions can be replayed as long as the current node lies on the line from which
+    /// they were undone; playing another action discards them. If the normalized weights were
+    /// cached, they are cached again at the child node.
+    ///
+    /// Returns an error if there is no action to redo.
+    ///
+    /// **Time complexity:** *O*(#(OOP private hands) + #(IP private hands))
+    ///
+    /// [`undo`]: #method.undo
+    pub fn redo(&mut self) -> Result<(), SolverError> {
+        if !self.can_redo() {
+            return Err(SolverError::InvalidState("No action to redo".to_owned()));
+        }
+
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
+        self.play(self.redo_history[self.action_history.len()]);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether [`undo`] can be called, i.e., the current node is not the root node.
+    ///
+    /// [`undo`]: #method.undo
+    #[inline]
+    pub fn can_undo(&self) -> bool {
+        !self.history().is_empty()
+    }
+
+    /// Returns whether [`redo`] can be called.
+    ///
+    /// [`redo`]: #method.redo
+    #[inline]
+    pub fn can_redo(&self) -> bool {
+        let history = self.history();
+        self.redo_history.len() > history.len() && self.redo_history.starts_with(history)
+    }
+
+    /// Creates a bookmark of the current node named `name`, replacing the existing one with the
+    /// same name (see [`bookmark`]).
+    ///
+    /// The named bookmarks are kept in the game and can be restored by [`goto_bookmark`].
+    ///
+    /// [`bookmark`]: #method.bookmark
+    /// [`goto_bookmark`]: #method.goto_bookmark
+    pub fn set_bookmark(&mut self, name: &str) {
+        let bookmark = self.bookmark();
+        self.named_bookmarks.insert(name.to_owned(), bookmark);
+    }
+
+    /// Moves the current node to the bookmark named `name` (see [`restore_bookmark`]).
+    ///
+    /// Returns an error if there is no such bookmark or it was created before the game tree was
+    /// rebuilt.
+    ///
+    /// [`restore_bookmark`]: #method.restore_bookmark
+    pub fn goto_bookmark(&mut self, name: &str) -> Result<(), SolverError> {
+        let Some(bookmark) = self.named_bookmarks.remove(name) else {
+            return Err(SolverError::InvalidState(format!(
+                "Bookmark not found: {name}"
+            )));
+        };
+
+        let result = self.restore_bookmark(&bookmark);
+        self.named_bookmarks.insert(name.to_owned(), bookmark);
+        result
+    }
+
+    /// Removes the bookmark named `name` and returns it, if any.
+    #[inline]
+    pub fn remove_bookmark(&mut self, name: &str) -> Option<Bookmark> {
+        self.named_bookmarks.remove(name)
+    }
+
+    /// Returns the names of the bookmarks created by [`set_bookmark`] in sorted order.
+    ///
+    /// [`set_bookmark`]: #method.set_bookmark
+    #[inline]
+    pub fn bookmark_names(&self) -> Vec<&str> {
+        self.named_bookmarks.keys().map(String::as_str).collect()
+    }
+
     /// Returns whether the current node is a terminal node.
     ///
     /// Note that the turn/river node after the call action after the all-in action is considered
@@ -128,7 +356,21 @@ impl PostFlopGame {
     /// The returned value is a 64-bit integer.
     /// The `i`-th bit is set to 1 if the card of ID `i` can be dealt (see [`Card`] for encoding).
     /// If the current node is not a chance node, `0` is returned.
+    #[inline]
     pub fn possible_cards(&self) -> u64 {
+        self.possible_cards_with_dead_cards(0)
+    }
+
+    /// Same as [`possible_cards`], but additionally treats the cards in the given bitmask as dead.
+    ///
+    /// The dead cards can neither be dealt nor be held by either player, so a card is regarded as
+    /// impossible if every valid assignment of private hands conflicts with the dead cards. This
+    /// allows queries like "which turns are possible if the A♠ is known to be mucked" without
+    /// rebuilding the game. With the bunching effect enabled, the dead cards are only excluded from
+    /// the returned cards themselves.
+    ///
+    /// [`possible_cards`]: #method.possible_cards
+    pub fn possible_cards_with_dead_cards(&self, dead_cards: u64) -> u64 {
         assert!(
             self.state > State::Uninitialized,
             "Game is not successfully initialized"
@@ -140,10 +382,12 @@ impl PostFlopGame {
 
         let flop = self.card_config.flop;
         let mut board_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+        board_mask |= self.card_config.dead_mask();
         let mut dead_mask: u64 = 0;
 
         // no bunching
         if self.bunching_num_dead_cards == 0 {
+            board_mask |= dead_cards;
             if self.turn != NOT_DEALT {
                 board_mask |= 1 << self.turn;
             }
@@ -227,9 +471,11 @@ impl PostFlopGame {
                 let swapped2 = ((dead_mask >> suit2) & suit_mask) << suit1;
                 dead_mask = (dead_mask & !mod_mask) | swapped1 | swapped2;
             }
+
+            dead_mask |= dead_cards;
         }
 
-        ((1 << 52) - 1) ^ dead_mask
+        ((1 << 52) - 1) & !dead_mask
     }
 
     /// Returns the current player (0 = OOP, 1 = IP).
@@ -293,7 +539,16 @@ impl PostFlopGame {
             if self.storage_mode == BoardState::Flop
                 || (!is_turn && self.storage_mode == BoardState::Turn)
             {
-                panic!("Storage mode is not compatible");
+                // allocates the storage of the next street lazily
+                let street = if is_turn {
+                    BoardState::Turn
+                } else {
+                    BoardState::River
+                };
+                assert!(
+                    self.extend_memory(street).is_ok(),
+                    "Storage mode is not compatible"
+                );
             }
 
             let actual_card = if action == usize::MAX {
@@ -390,15 +645,17 @@ impl PostFlopGame {
             }
 
             // cache the counterfactual values
-            let node = self.node();
-            let vec = if self.is_compression_enabled {
-                let slice = row(node.cfvalues_compressed(), action, num_hands);
-                let scale = node.cfvalue_scale();
-                decode_signed_slice(slice, scale)
-            } else {
-                row(node.cfvalues(), action, num_hands).to_vec()
-            };
-            self.cfvalues_cache[player].copy_from_slice(&vec);
+            if !self.is_strategy_only {
+                let node = self.node();
+                let vec = if self.is_compression_enabled {
+                    let slice = row(node.cfvalues_compressed(), action, num_hands);
+                    let scale = node.cfvalue_scale();
+                    decode_signed_slice(slice, scale)
+                } else {
+                    row(node.cfvalues(), action, num_hands).to_vec()
+                };
+                self.cfvalues_cache[player].copy_from_slice(&vec);
+            }
 
             // update the bet amounts
             let node = self.node();
@@ -622,15 +879,18 @@ impl PostFlopGame {
             if self.river != NOT_DEALT {
                 self.equity_internal(&mut tmp, player, self.turn, self.river, 0.5);
             } else if self.turn != NOT_DEALT {
+                let amount = 0.5 / (44 - self.num_dead_cards()) as f64;
                 for river in 0..52 {
                     if self.turn != river {
-                        self.equity_internal(&mut tmp, player, self.turn, river, 0.5 / 44.0);
+                        self.equity_internal(&mut tmp, player, self.turn, river, amount);
                     }
                 }
             } else {
+                let num_dead_cards = self.num_dead_cards();
+                let amount = 1.0 / ((45 - num_dead_cards) * (44 - num_dead_cards)) as f64;
                 for turn in 0..52 {
                     for river in turn + 1..52 {
-                        self.equity_internal(&mut tmp, player, turn, river, 1.0 / (45.0 * 44.0));
+                        self.equity_internal(&mut tmp, player, turn, river, amount);
                     }
                 }
             }
@@ -725,13 +985,7 @@ impl PostFlopGame {
         let node = self.node();
         let num_hands = self.num_private_hands(player);
 
-        let mut chance_factor = 1;
-        if self.card_config.turn == NOT_DEALT && self.turn != NOT_DEALT {
-            chance_factor *= 45 - self.bunching_num_dead_cards;
-        }
-        if self.card_config.river == NOT_DEALT && self.river != NOT_DEALT {
-            chance_factor *= 44 - self.bunching_num_dead_cards;
-        }
+        let chance_factor = self.current_chance_factor();
 
         let num_combinations = match self.bunching_num_dead_cards {
             0 => self.num_combinations,
@@ -823,28 +1077,54 @@ impl PostFlopGame {
         assert!(!self.is_terminal_node(), "Terminal node is not allowed");
         assert!(!self.is_chance_node(), "Chance node is not allowed");
 
-        let node = self.node();
         let player = self.current_player();
-        let num_actions = node.num_actions();
         let num_hands = self.num_private_hands(player);
+        let mut ret = self.normalized_node_strategy(&self.node());
 
-        let mut ret = if self.is_compression_enabled {
+        ret.chunks_exact_mut(num_hands).for_each(|chunk| {
+            self.apply_swap(chunk, player, false);
+        });
+
+        ret
+    }
+
+    /// Returns the normalized strategy of `node` with the locking strategy applied, in the
+    /// internal order of the hands (i.e., without swapping the suits of isomorphic chances).
+    pub(super) fn normalized_node_strategy(&self, node: &PostFlopNode) -> Vec<f32> {
+        let num_actions = node.num_actions();
+
+        let mut ret = if self.is_strategy_quantized {
+            normalized_strategy_compressed(node.strategy_quantized(), num_actions)
+        } else if self.is_compression_enabled {
             normalized_strategy_compressed(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy(node.strategy(), num_actions)
         };
 
-        let locking = self.locking_strategy(&node);
-        apply_locking_strategy(&mut ret, locking);
-
-        ret.chunks_exact_mut(num_hands).for_each(|chunk| {
-            self.apply_swap(chunk, player, false);
-        });
+        apply_locking_strategy(&mut ret, self.locking_strategy(node));
+        ret
+    }
 
+    /// Returns the strategy of the current player adjusted by `temperature` (see
+    /// [`apply_temperature`]).
+    ///
+    /// A temperature above 1 softens the strategy and a temperature below 1 sharpens it, e.g., to
+    /// make a trainer opponent easier or harder to exploit, or to test the robustness of a
+    /// strategy against deviations. The game is not modified.
+    ///
+    /// Panics in the same cases as [`strategy`], or if `temperature` is negative or NaN.
+    ///
+    /// **Time complexity:** *O*(#(actions) * #(private hands)).
+    ///
+    /// [`apply_temperature`]: crate::apply_temperature
+    /// [`strategy`]: #method.strategy
+    pub fn strategy_with_temperature(&self, temperature: f32) -> Vec<f32> {
+        let mut ret = self.strategy();
+        let num_hands = self.num_private_hands(self.current_player());
+        apply_temperature(&mut ret, num_hands, temperature);
         ret
     }
 
-    /// Returns the total bet amount of each player (OOP, IP).
     #[inline]
     pub fn total_bet_amount(&self) -> [i32; 2] {
         self.total_bet_amount
@@ -874,9 +1154,20 @@ impl PostFlopGame {
         assert!(!self.is_terminal_node(), "Terminal node is not allowed");
         assert!(!self.is_chance_node(), "Chance node is not allowed");
 
+        let locking = self.locking_from_strategy(strategy);
+
         let mut node = self.node();
+        node.is_locked = true;
+        let index = self.node_index(&node);
+        self.locking_strategy.insert(index, locking);
+    }
+
+    /// Converts the strategy of the current node into the internal locking format.
+    ///
+    /// Panics if the length of the `strategy` is invalid.
+    pub(super) fn locking_from_strategy(&self, strategy: &[f32]) -> Vec<f32> {
         let player = self.current_player();
-        let num_actions = node.num_actions();
+        let num_actions = self.node().num_actions();
         let num_hands = self.num_private_hands(player);
 
         assert!(
@@ -910,9 +1201,7 @@ impl PostFlopGame {
             self.apply_swap(chunk, player, true);
         });
 
-        node.is_locked = true;
-        let index = self.node_index(&node);
-        self.locking_strategy.insert(index, locking);
+        locking
     }
 
     /// Unlocks the strategy of the current node.
@@ -970,12 +1259,32 @@ impl PostFlopGame {
         })
     }
 
+    /// Returns the number of possible deals of the chance nodes from the root to the current node
+    /// (excluding the cards that conflict with the private hands of both players).
+    #[inline]
+    pub(super) fn current_chance_factor(&self) -> usize {
+        let mut chance_factor = 1;
+        if self.card_config.turn == NOT_DEALT && self.turn != NOT_DEALT {
+            chance_factor *= 45 - self.bunching_num_dead_cards - self.num_dead_cards();
+        }
+        if self.card_config.river == NOT_DEALT && self.river != NOT_DEALT {
+            chance_factor *= 44 - self.bunching_num_dead_cards - self.num_dead_cards();
+        }
+        chance_factor
+    }
+
     /// Returns the reference to the current node.
     #[inline]
-    fn node(&self) -> MutexGuardLike<'_, PostFlopNode> {
+    pub(super) fn node(&self) -> MutexGuardLike<'_, PostFlopNode> {
         self.node_arena[self.node_history.last().copied().unwrap_or(0)].lock()
     }
 
+    /// Returns the identity of the node arena, which changes when the game tree is rebuilt.
+    #[inline]
+    fn arena_id(&self) -> (usize, usize) {
+        (self.node_arena.as_ptr() as usize, self.node_arena.len())
+    }
+
     /// Returns the index of the given node.
     #[inline]
     pub(super) fn node_index(&self, node: &PostFlopNode) -> usize {
@@ -1061,7 +1370,7 @@ impl PostFlopGame {
 
     /// Applies the swap.
     #[inline]
-    fn apply_swap(&self, slice: &mut [f32], player: usize, reverse: bool) {
+    pub(super) fn apply_swap(&self, slice: &mut [f32], player: usize, reverse: bool) {
         let turn_swap = self
             .turn_swap
             .map(|suit| &self.isomorphism_swap_turn[suit as usize][player]);
@@ -1093,6 +1402,22 @@ impl PostFlopGame {
         amount: f64,
     ) {
         let pair_index = card_pair_to_index(turn, river);
+
+        if !self.split_pot_strength.is_empty() {
+            if self.split_pot_strength[pair_index][player].high.is_empty() {
+                return;
+            }
+            let mut shares = [0.0; MAX_NUM_PRIVATE_HANDS];
+            let mut totals = [0.0; MAX_NUM_PRIVATE_HANDS];
+            let (shares, totals) = (&mut shares[..result.len()], &mut totals[..result.len()]);
+            let opponent_weights = &self.weights[player ^ 1];
+            self.split_pot_shares(pair_index, player, opponent_weights, shares, totals);
+            for ((r, &share), &total) in result.iter_mut().zip(&*shares).zip(&*totals) {
+                *r += amount * (2.0 * share - total);
+            }
+            return;
+        }
+
         let hand_strength = &self.hand_strength[pair_index];
         let player_strength = &hand_strength[player];
         let opponent_strength = &hand_strength[player ^ 1];
@@ -0,0 +1,194 @@
+use super::*;
+use crate::interface::*;
+
+/// A deviation of a user-supplied strategy from the solver strategy, reported by
+/// [`PostFlopGame::find_leaks`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategyLeak {
+    /// History from the root node (see [`PostFlopGame::apply_history`]).
+    pub history: Vec<usize>,
+
+    /// Line (list of actions and dealt cards) from the root node.
+    pub line: Vec<Action>,
+
+    /// Available actions at the node.
+    pub actions: Vec<Action>,
+
+    /// Overall frequency of each action in the solver strategy.
+    pub solver_frequencies: Vec<f32>,
+
+    /// Overall frequency of each action in the user-supplied strategy.
+    pub user_frequencies: Vec<f32>,
+
+    /// Average expected value lost at the node by playing the user-supplied strategy, given that
+    /// the node is reached.
+    pub ev_loss: f32,
+
+    /// Probability that the node is reached when both players follow the solver strategy.
+    pub reach: f32,
+
+    /// `ev_loss` multiplied by `reach`, i.e., the expected value lost per hand from the root node.
+    pub weighted_ev_loss: f32,
+}
+
+impl PostFlopGame {
+    /// Compares a user-supplied strategy of the given player with the solver strategy node by node
+    /// and returns the deviations sorted from the costliest one.
+    ///
+    /// Each element of `strategies` is a pair of a history from the root node (see
+    /// [`apply_history`]) and a strategy of the node, which must be a node of `player`. The
+    /// strategy is either:
+    /// - the overall frequency of each action (length `#(actions)`), e.g., imported from tracking
+    ///   data, which is applied to every private hand; or
+    /// - the per-hand strategy in the same layout as [`strategy`] (length `#(actions) *
+    ///   #(private hands)`). A hand whose frequencies are all non-positive follows the solver
+    ///   strategy.
+    ///
+    /// Frequencies are normalized to sum up to 1. The EV loss of each node is computed
+    /// independently from the solved expected values of each action, i.e., assuming that both
+    /// players follow the solver strategy at all the other nodes (the opponent does not adapt to
+    /// the deviation). The ranking uses `weighted_ev_loss`, which accounts for how often the node
+    /// is reached.
+    ///
+    /// The current node is restored after the call.
+    ///
+    /// **Time complexity:** *O*(#(strategies) * #(depth) * (#(OOP private hands) + #(IP private
+    /// hands))).
+    ///
+    /// [`apply_history`]: #method.apply_history
+    /// [`strategy`]: #method.strategy
+    pub fn find_leaks(
+        &mut self,
+        player: usize,
+        strategies: &[(Vec<usize>, Vec<f32>)],
+    ) -> Result<Vec<StrategyLeak>, SolverError> {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+        if player > 1 {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid player: {player}"
+            )));
+        }
+
+        let original_history = self.history().to_vec();
+        let root_reach = self.root_reach();
+
+        let result = strategies
+            .iter()
+            .map(|(history, strategy)| self.compute_leak(player, history, strategy, root_reach))
+            .collect::<Result<Vec<_>, _>>();
+
+        self.apply_history(&original_history);
+
+        let mut leaks = result?;
+        leaks.sort_by(|a, b| b.weighted_ev_loss.total_cmp(&a.weighted_ev_loss));
+        Ok(leaks)
+    }
+
+    fn compute_leak(
+        &mut self,
+        player: usize,
+        history: &[usize],
+        strategy: &[f32],
+        root_reach: f64,
+    ) -> Result<StrategyLeak, SolverError> {
+        let line = self.line_from_history(history)?;
+
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(SolverError::InvalidArgument(format!(
+                "Not a player node: {history:?}"
+            )));
+        }
+        if self.current_player() != player {
+            return Err(SolverError::InvalidArgument(format!(
+                "Node of the other player: {history:?}"
+            )));
+        }
+
+        let actions = self.available_actions();
+        let num_actions = actions.len();
+        let num_hands = self.num_private_hands(player);
+
+        if strategy.iter().any(|&s| !s.is_finite() || s < 0.0) {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid frequencies at {history:?}"
+            )));
+        }
+
+        let solver_strategy = self.strategy();
+        let mut user_strategy = vec![0.0; num_actions * num_hands];
+
+        if strategy.len() == num_actions {
+            let sum = strategy.iter().sum::<f32>();
+            if sum <= 0.0 {
+                return Err(SolverError::InvalidArgument(format!(
+                    "Frequencies sum up to zero at {history:?}"
+                )));
+            }
+            for (action, &freq) in strategy.iter().enumerate() {
+                user_strategy[action * num_hands..(action + 1) * num_hands].fill(freq / sum);
+            }
+        } else if strategy.len() == num_actions * num_hands {
+            for hand in 0..num_hands {
+                let sum = (0..num_actions)
+                    .map(|action| strategy[action * num_hands + hand])
+                    .sum::<f32>();
+                for action in 0..num_actions {
+                    let index = action * num_hands + hand;
+                    user_strategy[index] = if sum > 0.0 {
+                        strategy[index] / sum
+                    } else {
+                        solver_strategy[index]
+                    };
+                }
+            }
+        } else {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid strategy length at {history:?}: expected {num_actions} or {}, but got {}",
+                num_actions * num_hands,
+                strategy.len()
+            )));
+        }
+
+        self.cache_normalized_weights();
+        let weights = self.normalized_weights(player);
+        let ev_detail = self.expected_values_detail(player);
+        let weight_sum = weights.iter().fold(0.0, |acc, &w| acc + w as f64);
+
+        let mut solver_frequencies = vec![0.0; num_actions];
+        let mut user_frequencies = vec![0.0; num_actions];
+        let mut loss_sum = 0.0;
+
+        for (hand, &w) in weights.iter().enumerate() {
+            let w = w as f64;
+            for action in 0..num_actions {
+                let index = action * num_hands + hand;
+                let diff = solver_strategy[index] as f64 - user_strategy[index] as f64;
+                solver_frequencies[action] += w * solver_strategy[index] as f64;
+                user_frequencies[action] += w * user_strategy[index] as f64;
+                loss_sum += w * diff * ev_detail[index] as f64;
+            }
+        }
+
+        let reach = self.current_reach(root_reach);
+        let ev_loss = if weight_sum > 0.0 {
+            loss_sum / weight_sum
+        } else {
+            0.0
+        };
+
+        let normalize = |v: Vec<f64>| v.iter().map(|&x| (x / weight_sum) as f32).collect();
+
+        Ok(StrategyLeak {
+            history: history.to_vec(),
+            line,
+            actions,
+            solver_frequencies: normalize(solver_frequencies),
+            user_frequencies: normalize(user_frequencies),
+            ev_loss: ev_loss as f32,
+            reach: reach as f32,
+            weighted_ev_loss: (ev_loss * reach) as f32,
+        })
+    }
+}
@@ -0,0 +1,420 @@
+use super::*;
+use crate::bet_size::*;
+use crate::range::*;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Record of every input that affects the result of solving a [`PostFlopGame`], returned by
+/// [`PostFlopGame::manifest`] and [`PostFlopGame::solve_manifest`].
+///
+/// The manifest is a list of key-value pairs in a fixed order, covering the version of this crate,
+/// the card and tree configurations, the added and removed lines, the isomorphism mode, the storage
+/// options, the solver settings (e.g., the update schedules and the ICM seed), the bunching effect,
+/// the locked strategies, and the number of iterations. Inputs that do not affect the result are
+/// omitted (e.g., the positions of the players, or the ICM seed without ICM), so that two games
+/// with equal manifests are solved identically. The solver is deterministic regardless of the
+/// number of threads, so the thread count is recorded but not part of the manifest.
+///
+/// The [`digest`] is a stable hash of the [`canonical_string`], which does not depend on the
+/// platform or the build, so it can be compared across runs and machines. Each value is written in
+/// an explicit text form (e.g., the bet sizes in the syntax accepted by [`BetSizeOptions`]), not in
+/// the `Debug` representation of its type.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let card_config = CardConfig {
+///     range: ["AA,KK,87s".parse().unwrap(), "QQ-99,AK".parse().unwrap()],
+///     flop: flop_from_str("Td9d6h").unwrap(),
+///     turn: card_from_str("2c").unwrap(),
+///     river: card_from_str("3s").unwrap(),
+///     ..Default::default()
+/// };
+///
+/// let tree_config = TreeConfig {
+///     initial_state: BoardState::River,
+///     starting_pot: 100,
+///     effective_stack: 100,
+///     river_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+///     ..Default::default()
+/// };
+///
+/// let new_game = |effective_stack| {
+///     let tree_config = TreeConfig { effective_stack, ..tree_config.clone() };
+///     let action_tree = ActionTree::new(tree_config).unwrap();
+///     PostFlopGame::with_config(card_config.clone(), action_tree).unwrap()
+/// };
+///
+/// let manifest = new_game(100).manifest().unwrap();
+/// assert_eq!(manifest.digest(), new_game(100).manifest().unwrap().digest());
+///
+/// let other = new_game(200).manifest().unwrap();
+/// let differences = manifest.diff(&other);
+/// assert_eq!(differences.len(), 1);
+/// assert_eq!(differences[0].key, "tree.effective_stack");
+/// ```
+///
+/// [`digest`]: SolveManifest::digest
+/// [`canonical_string`]: SolveManifest::canonical_string
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct SolveManifest {
+    entries: Vec<(String, String)>,
+    num_threads: usize,
+}
+
+/// An entry that differs between two manifests, returned by [`SolveManifest::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestDifference {
+    /// Key of the entry.
+    pub key: String,
+
+    /// Value in the first manifest, or `None` if the entry is absent.
+    pub left: Option<String>,
+
+    /// Value in the second manifest, or `None` if the entry is absent.
+    pub right: Option<String>,
+}
+
+impl SolveManifest {
+    /// Returns the entries as (key, value) pairs in the canonical order.
+    #[inline]
+    pub fn entries(&self) -> &[(String, String)] {
+        &self.entries
+    }
+
+    /// Returns the value of the entry with `key`, if any.
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the number of threads used when the manifest was captured. This is informational
+    /// only and is not included in the digest.
+    #[inline]
+    pub fn num_threads(&self) -> usize {
+        self.num_threads
+    }
+
+    /// Returns the canonical text form of the manifest: one `key=value` line per entry.
+    pub fn canonical_string(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(key, value)| format!("{key}={value}\n"))
+            .collect()
+    }
+
+    /// Returns the 64-bit FNV-1a hash of the canonical text form.
+    #[inline]
+    pub fn digest(&self) -> u64 {
+        fnv1a(FNV_OFFSET_BASIS, self.canonical_string().as_bytes())
+    }
+
+    /// Returns the entries that differ between `self` and `other`, in the order of `self` followed
+    /// by the entries only in `other`. The result is empty if and only if the digests are equal
+    /// (barring hash collisions).
+    pub fn diff(&self, other: &Self) -> Vec<ManifestDifference> {
+        let mut differences = Vec::new();
+        for (key, value) in &self.entries {
+            let right = other.get(key);
+            if right != Some(value.as_str()) {
+                differences.push(ManifestDifference {
+                    key: key.clone(),
+                    left: Some(value.clone()),
+                    right: right.map(str::to_owned),
+                });
+            }
+        }
+        for (key, value) in &other.entries {
+            if self.get(key).is_none() {
+                differences.push(ManifestDifference {
+                    key: key.clone(),
+                    left: None,
+                    right: Some(value.clone()),
+                });
+            }
+        }
+        differences
+    }
+}
+
+impl PostFlopGame {
+    /// Captures the current inputs of the game into a [`SolveManifest`].
+    ///
+    /// The number of iterations is included only if the game has a solve summary (see
+    /// [`solve_summary`]). A custom hand evaluator (see [`set_hand_evaluator`]) cannot be
+    /// identified, so it is recorded as `custom`, and so is a split-pot evaluator if set.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the game is not successfully initialized.
+    ///
+    /// **Time complexity:** *O*(#(locked nodes) * #(private hands) + size of the bunching table).
+    ///
+    /// [`solve_summary`]: #method.solve_summary
+    /// [`set_hand_evaluator`]: #method.set_hand_evaluator
+    pub fn manifest(&self) -> Result<SolveManifest, SolverError> {
+        if self.state <= State::Uninitialized {
+            return Err(SolverError::InvalidState(
+                "Game is not successfully initialized".to_owned(),
+            ));
+        }
+
+        let mut entries = Vec::new();
+        let mut push = |key: &str, value: String| entries.push((key.to_owned(), value));
+
+        let card = &self.card_config;
+        let tree = &self.tree_config;
+        let cards = |cards: &[Card]| {
+            cards
+                .iter()
+                .map(|&card| card_to_string(card).unwrap_or_else(|_| "-".to_owned()))
+                .collect::<String>()
+        };
+        let lines = |lines: &[Vec<Action>]| {
+            lines
+                .iter()
+                .map(|line| line_to_string(line))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        push("crate_version", env!("CARGO_PKG_VERSION").to_owned());
+
+        push("card.range_oop", card.range[0].to_string());
+        push("card.range_ip", card.range[1].to_string());
+        push("card.flop", cards(&card.flop));
+        push("card.turn", cards(&[card.turn]));
+        push("card.river", cards(&[card.river]));
+        let dead_cards = (0..52).filter(|&c| card.dead_cards & (1 << c) != 0);
+        push("card.dead_cards", cards(&dead_cards.collect::<Vec<_>>()));
+        push("card.short_deck", card.short_deck.to_string());
+
+        push(
+            "tree.initial_state",
+            street_name(tree.initial_state).to_owned(),
+        );
+        push("tree.starting_pot", tree.starting_pot.to_string());
+        push("tree.effective_stack", tree.effective_stack.to_string());
+        push("tree.rake_rate", tree.rake_rate.to_string());
+        push("tree.rake_cap", tree.rake_cap.to_string());
+        push("tree.flop_bet_sizes", bet_sizes(&tree.flop_bet_sizes));
+        push("tree.turn_bet_sizes", bet_sizes(&tree.turn_bet_sizes));
+        push("tree.river_bet_sizes", bet_sizes(&tree.river_bet_sizes));
+        push("tree.turn_donk_sizes", donk_sizes(&tree.turn_donk_sizes));
+        push("tree.river_donk_sizes", donk_sizes(&tree.river_donk_sizes));
+        push(
+            "tree.add_allin_threshold",
+            tree.add_allin_threshold.to_string(),
+        );
+        push(
+            "tree.force_allin_threshold",
+            tree.force_allin_threshold.to_string(),
+        );
+        push("tree.merging_threshold", tree.merging_threshold.to_string());
+        push("tree.icm_config", icm_config(&tree.icm_config));
+        push("tree.rake_config", rake_config(&tree.rake_config));
+        push("tree.sizing_rules", sizing_rules(&tree.sizing_rules));
+        if let Some(bomb_pot) = &tree.bomb_pot {
+            push(
+                "tree.bomb_pot",
+                format!("ante={};stack={}", bomb_pot.ante, bomb_pot.stack),
+            );
+        }
+        push("tree.added_lines", lines(&self.added_lines));
+        push("tree.removed_lines", lines(&self.removed_lines));
+
+        let isomorphism_mode = match self.isomorphism_mode {
+            IsomorphismMode::Full => "full",
+            IsomorphismMode::TurnOnly => "turn_only",
+            IsomorphismMode::RiverOnly => "river_only",
+            IsomorphismMode::Disabled => "disabled",
+        };
+        push("isomorphism_mode", isomorphism_mode.to_owned());
+        push("compression", self.is_compression_enabled.to_string());
+        push("quantized_strategy", self.is_strategy_quantized.to_string());
+        if self.is_compression_enabled {
+            let params = self
+                .compression_params
+                .map(|params| format!("{}:{}", params.reserved_bits, params.rescale_interval));
+            push("compression_params", params.join(","));
+        }
+        let schedules = self.update_schedule.map(|schedule| {
+            let freeze_after = schedule
+                .freeze_after
+                .map_or_else(|| "none".to_owned(), |n| n.to_string());
+            format!("{}:{freeze_after}", schedule.interval)
+        });
+        push("update_schedule", schedules.join(","));
+        if tree.icm_config.is_some() {
+            push("icm_seed", self.icm_seed.to_string());
+        }
+
+        let evaluator = match self.hand_evaluator {
+            Some(_) => "custom",
+            None => "default",
+        };
+        push("hand_evaluator", evaluator.to_owned());
+        if self.split_pot_evaluator.is_some() {
+            push("split_pot_evaluator", "custom".to_owned());
+        }
+
+        let bunching = if self.bunching_num_dead_cards == 0 {
+            "none".to_owned()
+        } else {
+            let digest = self
+                .bunching_arena
+                .iter()
+                .fold(FNV_OFFSET_BASIS, |h, w| fnv1a(h, &w.to_le_bytes()));
+            format!("{} dead cards, {digest:016x}", self.bunching_num_dead_cards)
+        };
+        push("bunching", bunching);
+
+        let locking = if self.locking_strategy.is_empty() {
+            "none".to_owned()
+        } else {
+            let digest =
+                self.locking_strategy
+                    .iter()
+                    .fold(FNV_OFFSET_BASIS, |h, (&index, strategy)| {
+                        let h = fnv1a(h, &(index as u64).to_le_bytes());
+                        strategy.iter().fold(h, |h, w| fnv1a(h, &w.to_le_bytes()))
+                    });
+            format!("{} nodes, {digest:016x}", self.locking_strategy.len())
+        };
+        push("locking_strategy", locking);
+
+        if let Some(summary) = &self.solve_summary {
+            push("num_iterations", summary.num_iterations.to_string());
+        }
+
+        #[cfg(feature = "rayon")]
+        let num_threads = self.num_threads();
+        #[cfg(not(feature = "rayon"))]
+        let num_threads = 1;
+
+        Ok(SolveManifest {
+            entries,
+            num_threads,
+        })
+    }
+
+    /// Returns the manifest captured when the game was last solved by [`solve`] (see
+    /// [`manifest`]), which is also kept in the save files.
+    ///
+    /// Unlike [`manifest`], the returned manifest includes the settings that are not saved (e.g.,
+    /// the update schedules and the ICM seed) as they were at the time of the solving, so it can be
+    /// compared with the manifest of another run to verify that both were solved from identical
+    /// inputs. Returns `None` in the same cases as [`solve_summary`].
+    ///
+    /// [`solve`]: crate::solve
+    /// [`manifest`]: #method.manifest
+    /// [`solve_summary`]: #method.solve_summary
+    #[inline]
+    pub fn solve_manifest(&self) -> Option<&SolveManifest> {
+        self.solve_manifest.as_ref()
+    }
+
+    #[cfg(feature = "bincode")]
+    #[inline]
+    pub(crate) fn set_solve_manifest(&mut self, manifest: Option<SolveManifest>) {
+        self.solve_manifest = manifest;
+    }
+}
+
+/// Returns the lowercase name of `street`.
+#[inline]
+fn street_name(street: BoardState) -> &'static str {
+    match street {
+        BoardState::Flop => "flop",
+        BoardState::Turn => "turn",
+        BoardState::River => "river",
+    }
+}
+
+/// Formats the bet sizes in the syntax accepted by [`BetSizeOptions`], separated by commas.
+fn sizes(sizes: &[BetSize]) -> String {
+    sizes
+        .iter()
+        .map(|size| size.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Formats the bet and raise sizes of each player as `bet/raise`, separated by `|`.
+fn bet_sizes(options: &[BetSizeOptions]) -> String {
+    options
+        .iter()
+        .map(|options| format!("{}/{}", sizes(&options.bet), sizes(&options.raise)))
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+#[inline]
+fn donk_sizes(options: &Option<DonkSizeOptions>) -> String {
+    options
+        .as_ref()
+        .map_or_else(|| "none".to_owned(), |options| sizes(&options.donk))
+}
+
+/// Joins the numbers with commas.
+#[inline]
+fn numbers<T: ToString>(numbers: &[T]) -> String {
+    numbers
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn icm_config(config: &Option<ICMConfig>) -> String {
+    config.as_ref().map_or_else(
+        || "none".to_owned(),
+        |config| {
+            format!(
+                "stacks={};others={};payouts={}",
+                numbers(&config.player_stacks),
+                numbers(&config.other_players_stacks),
+                numbers(&config.payout_structure)
+            )
+        },
+    )
+}
+
+fn rake_config(config: &Option<RakeConfig>) -> String {
+    config.as_ref().map_or_else(
+        || "none".to_owned(),
+        |config| {
+            format!(
+                "rates={};caps={}",
+                numbers(&config.rates),
+                numbers(&config.caps)
+            )
+        },
+    )
+}
+
+/// Formats each rule as `street:ratio:bet/raise` (the street is `any` if not restricted),
+/// separated by `|`.
+fn sizing_rules(rules: &[SizingRule]) -> String {
+    rules
+        .iter()
+        .map(|rule| {
+            let street = rule.street.map_or("any", street_name);
+            let sizes = bet_sizes(std::slice::from_ref(&rule.bet_sizes));
+            format!("{street}:{}:{sizes}", rule.pot_to_stack_ratio)
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// Feeds `bytes` into the FNV-1a hash `hash`.
+#[inline]
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(hash, |h, &b| (h ^ b as u64).wrapping_mul(FNV_PRIME))
+}
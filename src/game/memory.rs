@@ -0,0 +1,220 @@
+use super::*;
+use crate::interface::*;
+use crate::utility::*;
+
+/// Storage format of the game tree used by [`PostFlopGame::estimate_memory_breakdown`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StorageFormat {
+    /// 32-bit floating point numbers (`allocate_memory(false)`).
+    #[default]
+    Uncompressed,
+
+    /// 16-bit integers (`allocate_memory(true)`).
+    Compressed,
+
+    /// 16-bit integers with the strategy stored in 8-bit integers (`allocate_memory_quantized`).
+    Quantized,
+}
+
+/// Fallback policy of [`PostFlopGame::try_allocate_memory`] when the memory cannot be allocated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AllocationFallback {
+    /// Returns an error without falling back.
+    #[default]
+    None,
+
+    /// Falls back to the compressed format, and then to the quantized format.
+    Compress,
+
+    /// Falls back as [`AllocationFallback::Compress`], and then allocates the quantized storage only
+    /// up to the turn, and then only up to the flop (see [`PostFlopGame::allocate_memory_up_to`]).
+    CompressAndDefer,
+}
+
+/// Memory usage of the nodes of a street, returned as a part of [`MemoryBreakdown`].
+///
+/// Chance nodes are counted in the street in which they are reached (e.g., the chance nodes
+/// dealing the turn card are counted in the flop).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreetMemoryUsage {
+    /// Number of player nodes.
+    pub num_player_nodes: u64,
+
+    /// Number of chance nodes.
+    pub num_chance_nodes: u64,
+
+    /// Number of terminal nodes.
+    pub num_terminal_nodes: u64,
+
+    /// Bytes used for the cumulative strategy.
+    pub strategy: u64,
+
+    /// Bytes used for the cumulative regrets. After the game is finalized, the buffer holds the
+    /// counterfactual values of the players to act instead.
+    pub regrets: u64,
+
+    /// Bytes used for the counterfactual values of the IP player at the first node of the street.
+    pub cfvalues_ip: u64,
+
+    /// Bytes used for the counterfactual values at the chance nodes.
+    pub cfvalues_chance: u64,
+}
+
+/// Memory usage of a game broken down by street and by buffer type, returned by
+/// [`PostFlopGame::memory_breakdown`] and [`PostFlopGame::estimate_memory_breakdown`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryBreakdown {
+    /// Memory usage of the flop, turn, and river nodes in this order.
+    pub streets: [StreetMemoryUsage; 3],
+
+    /// Bytes used by the node arena.
+    pub node_arena: u64,
+
+    /// Bytes used by the other data including the node arena (hand strength, isomorphism, etc.).
+    pub misc: u64,
+
+    /// Bytes used by the bunching effect tables (zero if the bunching effect is disabled).
+    pub bunching: u64,
+
+    /// Whether the storage buffers are backed by files (see `PostFlopGame::allocate_memory_on_disk`,
+    /// which requires the `mmap` feature).
+    pub is_on_disk: bool,
+}
+
+impl StreetMemoryUsage {
+    /// Returns the total number of nodes.
+    #[inline]
+    pub fn num_nodes(&self) -> u64 {
+        self.num_player_nodes + self.num_chance_nodes + self.num_terminal_nodes
+    }
+
+    /// Returns the total bytes used by the storage buffers.
+    #[inline]
+    pub fn storage(&self) -> u64 {
+        self.strategy + self.regrets + self.cfvalues_ip + self.cfvalues_chance
+    }
+}
+
+impl MemoryBreakdown {
+    /// Returns the total bytes used by the storage buffers.
+    #[inline]
+    pub fn storage(&self) -> u64 {
+        self.streets.iter().map(StreetMemoryUsage::storage).sum()
+    }
+
+    /// Returns the total memory usage in bytes.
+    #[inline]
+    pub fn total(&self) -> u64 {
+        self.storage() + self.misc + self.bunching
+    }
+}
+
+impl PostFlopGame {
+    /// Returns the breakdown of the memory usage estimated for the given storage format.
+    ///
+    /// The estimate is available once the game tree is built and accounts for the removed lines.
+    /// The total matches [`memory_usage`] (and [`memory_usage_quantized`]) plus
+    /// [`memory_usage_bunching`] if the bunching effect is enabled.
+    ///
+    /// [`memory_usage`]: #method.memory_usage
+    /// [`memory_usage_quantized`]: #method.memory_usage_quantized
+    /// [`memory_usage_bunching`]: #method.memory_usage_bunching
+    pub fn estimate_memory_breakdown(&self, format: StorageFormat) -> MemoryBreakdown {
+        assert!(
+            self.state > State::Uninitialized,
+            "Game is not successfully initialized"
+        );
+
+        let (strategy_num_bytes, num_bytes) = match format {
+            StorageFormat::Uncompressed => (4, 4),
+            StorageFormat::Compressed => (2, 2),
+            StorageFormat::Quantized => (1, 2),
+        };
+
+        let mut ret = self.breakdown_nodes(strategy_num_bytes, num_bytes, BoardState::River);
+        ret.misc = self.misc_memory_usage;
+        ret.bunching = if self.bunching_num_dead_cards == 0 {
+            0
+        } else {
+            self.memory_usage_bunching_internal()
+        };
+        ret
+    }
+
+    /// Returns the breakdown of the memory actually used by the game.
+    ///
+    /// Returns `None` if the memory is not allocated. The storage of the streets beyond the storage
+    /// mode (see [`allocate_memory_up_to`]) is reported as zero bytes.
+    ///
+    /// [`allocate_memory_up_to`]: #method.allocate_memory_up_to
+    pub fn memory_breakdown(&self) -> Option<MemoryBreakdown> {
+        self.is_memory_allocated()?;
+
+        let num_bytes = if self.is_compression_enabled { 2 } else { 4 };
+        let strategy_num_bytes = self.strategy_num_bytes() as u64;
+
+        let mut ret = self.breakdown_nodes(strategy_num_bytes, num_bytes, self.storage_mode);
+        ret.misc = self.memory_usage_internal();
+        ret.bunching = if self.bunching_num_dead_cards == 0 {
+            0
+        } else {
+            self.memory_usage_bunching_internal()
+        };
+        ret.is_on_disk = self.is_memory_on_disk();
+        Some(ret)
+    }
+
+    /// Counts the nodes reachable from the root and their storage sizes. The storage of the streets
+    /// later than `storage_mode` is not counted.
+    fn breakdown_nodes(
+        &self,
+        strategy_num_bytes: u64,
+        num_bytes: u64,
+        storage_mode: BoardState,
+    ) -> MemoryBreakdown {
+        let mut ret = MemoryBreakdown {
+            node_arena: vec_memory_usage(&self.node_arena),
+            ..Default::default()
+        };
+
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = self.node_arena[index].lock();
+            let street = match (node.turn, node.river) {
+                (NOT_DEALT, _) => BoardState::Flop,
+                (_, NOT_DEALT) => BoardState::Turn,
+                _ => BoardState::River,
+            };
+
+            let usage = &mut ret.streets[street as usize];
+            let is_stored = street <= storage_mode;
+
+            if node.is_terminal() {
+                usage.num_terminal_nodes += 1;
+                continue;
+            }
+
+            if node.is_chance() {
+                usage.num_chance_nodes += 1;
+                if is_stored {
+                    usage.cfvalues_chance += num_bytes * node.num_elements as u64;
+                }
+            } else {
+                usage.num_player_nodes += 1;
+                if is_stored {
+                    usage.strategy += strategy_num_bytes * node.num_elements as u64;
+                    usage.regrets += num_bytes * node.num_elements as u64;
+                    usage.cfvalues_ip += num_bytes * node.num_elements_ip as u64;
+                }
+            }
+
+            // the nodes of the later streets do not exist if loaded with a lower storage mode
+            let children_index = index + node.children_offset as usize;
+            if children_index + node.num_children as usize <= self.node_arena.len() {
+                stack.extend(children_index..children_index + node.num_children as usize);
+            }
+        }
+
+        ret
+    }
+}
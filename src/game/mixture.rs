@@ -0,0 +1,219 @@
+use super::*;
+
+impl PostFlopGame {
+    /// Locks the strategy of `player` at every node to a weighted mixture of the strategies of
+    /// `player` in the given games.
+    ///
+    /// Each component is a pair of a weight and a game with the same tree and ranges as this game,
+    /// whose strategy of `player` is taken as is (e.g., a solved baseline, or a game whose
+    /// strategy is locked to model an exploitable population). The mixture models an opponent
+    /// that plays one of the strategies for the whole hand, chosen at random with probabilities
+    /// proportional to the weights. Because the actions of `player` reveal information about the
+    /// chosen strategy, the frequencies at a node are not the weighted average of the components:
+    /// the weight of each component is multiplied by the probability that the component reaches
+    /// the node with the hand, which makes the locked strategy equivalent to the mixture.
+    ///
+    /// Existing locks of the nodes of `player` are replaced. The components may be solved or not,
+    /// and their current nodes are preserved, as well as that of this game.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the memory of this game is not allocated or the game
+    /// is already solved, and [`SolverError::InvalidArgument`] if `player` is not 0 or 1, there is no
+    /// component, a weight is negative or all the weights are zero, or the memory of a component is
+    /// not allocated or it has a different tree or different ranges.
+    ///
+    /// **Time complexity:** *O*(#(components) * #(nodes) * (#(private hands) + depth)).
+    pub fn lock_strategy_mixture(
+        &mut self,
+        player: usize,
+        components: &mut [(f32, &mut Self)],
+    ) -> Result<(), SolverError> {
+        if player > 1 {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid player: {player}"
+            )));
+        }
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+        if self.state == State::Solved {
+            return Err(SolverError::InvalidState(
+                "Game is already solved".to_owned(),
+            ));
+        }
+        if components.is_empty() {
+            return Err(SolverError::InvalidArgument("No components".to_owned()));
+        }
+        if components
+            .iter()
+            .any(|(weight, _)| weight.is_nan() || *weight < 0.0)
+        {
+            return Err(SolverError::InvalidArgument(
+                "Weights must be non-negative".to_owned(),
+            ));
+        }
+        if components.iter().all(|(weight, _)| *weight == 0.0) {
+            return Err(SolverError::InvalidArgument(
+                "Sum of weights must be positive".to_owned(),
+            ));
+        }
+        for (_, game) in components.iter() {
+            if game.state < State::MemoryAllocated {
+                return Err(SolverError::InvalidArgument(
+                    "Memory of a component is not allocated".to_owned(),
+                ));
+            }
+            let board = |game: &Self| {
+                let config = &game.card_config;
+                (config.flop, config.turn, config.river)
+            };
+            if game.private_cards != self.private_cards
+                || game.initial_weights != self.initial_weights
+                || board(game) != board(self)
+            {
+                return Err(SolverError::InvalidArgument(
+                    "Component has different ranges".to_owned(),
+                ));
+            }
+        }
+
+        let original_histories = components
+            .iter()
+            .map(|(_, game)| game.history().to_vec())
+            .collect::<Vec<_>>();
+
+        let result = self.walk_player_nodes(&[], None, &mut |game, history, _| {
+            if game.current_player() != player {
+                return Ok(());
+            }
+
+            let actions = game.available_actions();
+            let num_hands = game.num_private_hands(player);
+            let mut numerator = vec![0.0f64; actions.len() * num_hands];
+            let mut denominator = vec![0.0f64; num_hands];
+            let mut fallback = vec![0.0f64; actions.len() * num_hands];
+
+            for (weight, component) in components.iter_mut() {
+                component.apply_history(history);
+                if component.available_actions() != actions {
+                    return Err(SolverError::InvalidArgument(
+                        "Component has a different tree".to_owned(),
+                    ));
+                }
+
+                let strategy = component.strategy();
+                let reach = component.weights(player);
+                for hand in 0..num_hands {
+                    let w = *weight as f64 * reach[hand] as f64;
+                    denominator[hand] += w;
+                    for action in 0..actions.len() {
+                        let index = action * num_hands + hand;
+                        numerator[index] += w * strategy[index] as f64;
+                        fallback[index] += *weight as f64 * strategy[index] as f64;
+                    }
+                }
+            }
+
+            // hands that no component reaches take the plain weighted average
+            let mixture = (0..actions.len() * num_hands)
+                .map(|index| {
+                    let hand = index % num_hands;
+                    if denominator[hand] > 0.0 {
+                        (numerator[index] / denominator[hand]) as f32
+                    } else {
+                        fallback[index] as f32
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            game.lock_current_strategy(&mixture);
+            Ok(())
+        });
+
+        for ((_, game), history) in components.iter_mut().zip(&original_histories) {
+            game.apply_history(history);
+        }
+
+        result
+    }
+
+    /// Replaces the strategy of `player` with the best response against a weighted mixture of
+    /// strategies of the opponent.
+    ///
+    /// This is a shorthand for [`lock_strategy_mixture`] with the opponent of `player`, followed
+    /// by [`apply_best_response`]: the opponent is locked to the mixture of `components`, and
+    /// `player` takes the action with the highest expected value at every node. Modeling the
+    /// opponent as a mixture (e.g., 60% of a baseline strategy and 40% of an overfolding
+    /// strategy) captures the uncertainty about a population better than a single lock, and the
+    /// expected values of the returned report are those against the mixture.
+    ///
+    /// Returns an error in the same cases as the two methods.
+    ///
+    /// **Time complexity:** the sum of those of the two methods.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["AA,KK,87s,65s,A5s".parse().unwrap(), "QQ-99,AK,KQs,T9s".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("2c").unwrap(),
+    ///     river: card_from_str("3s").unwrap(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::River,
+    ///     starting_pot: 100,
+    ///     effective_stack: 100,
+    ///     river_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let new_game = || {
+    ///     let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    ///     let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    ///     game.allocate_memory(false);
+    ///     game
+    /// };
+    ///
+    /// // baseline: equilibrium strategy
+    /// let mut baseline = new_game();
+    /// solve(&mut baseline, 100, 0.0, false);
+    ///
+    /// // overfolder: IP always folds against a bet
+    /// let mut overfolder = new_game();
+    /// overfolder.play(1);
+    /// let num_hands = overfolder.private_cards(1).len();
+    /// let num_actions = overfolder.available_actions().len();
+    /// let mut strategy = vec![0.0; num_actions * num_hands];
+    /// strategy[..num_hands].fill(1.0);
+    /// overfolder.lock_current_strategy(&strategy);
+    /// overfolder.back_to_root();
+    /// solve(&mut overfolder, 100, 0.0, false);
+    ///
+    /// // best response of OOP against 60% baseline and 40% overfolder
+    /// let mut game = new_game();
+    /// let mut components = [(0.6, &mut baseline), (0.4, &mut overfolder)];
+    /// let report = game.apply_mixture_best_response(0, &mut components).unwrap();
+    /// assert!(report.ev_shift[0] >= 0.0);
+    /// ```
+    ///
+    /// [`lock_strategy_mixture`]: #method.lock_strategy_mixture
+    /// [`apply_best_response`]: #method.apply_best_response
+    pub fn apply_mixture_best_response(
+        &mut self,
+        player: usize,
+        components: &mut [(f32, &mut Self)],
+    ) -> Result<WhatIfReport, SolverError> {
+        if player > 1 {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid player: {player}"
+            )));
+        }
+        self.lock_strategy_mixture(player ^ 1, components)?;
+        self.apply_best_response(player)
+    }
+}
@@ -1,8 +1,47 @@
+mod analysis;
+mod annotate;
 mod base;
+mod cache;
+mod composition;
+mod crossplay;
+mod diff;
+mod equity_cache;
 mod evaluation;
+mod explain;
+mod exploit;
+mod export;
+mod extract;
+mod forced_line;
+mod hand_history;
 mod icm;
+mod interpolate;
 mod interpreter;
+mod leak;
+mod manifest;
+mod memory;
+mod mixture;
+mod nash_distance;
 mod node;
+mod pattern;
+mod query;
+mod range_equity;
+mod resolve;
+mod rules;
+mod run_it;
+mod sampling;
+mod share;
+mod snapshot;
+mod strategy_snapshot;
+mod trainer;
+mod visitor;
+mod walk;
+mod what_if;
+
+#[cfg(feature = "parquet")]
+mod columnar;
+
+#[cfg(all(feature = "numa", target_os = "linux"))]
+mod numa;
 
 #[cfg(feature = "bincode")]
 mod serialization;
@@ -12,8 +51,57 @@ mod tests;
 
 use crate::action_tree::*;
 use crate::card::*;
+use crate::error::*;
+use crate::evaluator::*;
+use crate::hi_lo::*;
+use crate::interface::*;
 use crate::mutex_like::*;
+use crate::storage::*;
 use std::collections::BTreeMap;
+use std::sync::Arc;
+
+#[cfg(feature = "bincode")]
+pub(crate) use serialization::{
+    set_stream_offset, with_corruption_report, with_decode_street, with_decode_structure,
+    with_encode_structure,
+};
+
+#[cfg(feature = "bincode")]
+pub use serialization::{
+    SAVE_FORMAT_VERSION, SUPPORTED_SAVE_FORMAT_VERSIONS, SaveSection, TreeStructure,
+};
+
+#[cfg(feature = "parquet")]
+pub use columnar::*;
+
+pub use analysis::*;
+pub use annotate::*;
+pub use cache::*;
+pub use composition::*;
+pub use crossplay::*;
+pub use diff::*;
+pub use equity_cache::*;
+pub use explain::*;
+pub use export::*;
+pub use forced_line::*;
+pub use hand_history::*;
+pub use interpolate::*;
+pub use interpreter::*;
+pub use leak::*;
+pub use manifest::*;
+pub use memory::*;
+pub use nash_distance::*;
+pub use pattern::*;
+pub use query::*;
+pub use range_equity::*;
+pub use rules::*;
+pub use run_it::*;
+pub use sampling::*;
+pub use snapshot::*;
+pub use strategy_snapshot::*;
+pub use trainer::*;
+pub use visitor::*;
+pub use what_if::*;
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
@@ -30,6 +118,20 @@ enum State {
     Solved = 4,
 }
 
+/// Summary of the solving of a [`PostFlopGame`], returned by [`PostFlopGame::solve_summary`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct SolveSummary {
+    /// Number of iterations performed.
+    pub num_iterations: u32,
+
+    /// Exploitability of the obtained strategy.
+    pub exploitability: f32,
+
+    /// Time when the solving finished (seconds since the Unix epoch; `0` if unavailable).
+    pub solved_at: u64,
+}
+
 /// A struct representing a postflop game.
 #[derive(Default)]
 pub struct PostFlopGame {
@@ -41,7 +143,7 @@ pub struct PostFlopGame {
     tree_config: TreeConfig,
     added_lines: Vec<Vec<Action>>,
     removed_lines: Vec<Vec<Action>>,
-    action_root: Box<MutexLike<ActionTreeNode>>,
+    action_nodes: Arc<ActionTreeArena>,
 
     // computed from configurations
     num_combinations: f64,
@@ -57,11 +159,16 @@ pub struct PostFlopGame {
     // hand strength information: indices are stored in ascending strength order
     hand_strength: Vec<[Vec<StrengthItem>; 2]>,
 
+    // showdown strengths of the split-pot game (empty if no split-pot evaluator is set)
+    split_pot_strength: Vec<[SplitPotStrength; 2]>,
+
     // isomorphism information
+    // - `isomorphism_mode`: which chance events are combined by the isomorphism
     // - `isomorphism_ref_*`: indices to which the eliminated events should refer
     // - `isomorphism_card_*`: list of cards eliminated by the isomorphism
     // - `isomorphism_swap_*`: list of hand index pairs that should be swapped when applying the
     //                         isomorphism with the specified suit
+    isomorphism_mode: IsomorphismMode,
     isomorphism_ref_turn: Vec<u8>,
     isomorphism_card_turn: Vec<Card>,
     isomorphism_swap_turn: [SwapList; 4],
@@ -83,26 +190,65 @@ pub struct PostFlopGame {
     // store options
     storage_mode: BoardState,
     target_storage_mode: BoardState,
+    is_strategy_only: bool,
+    target_strategy_only: bool,
     num_nodes: [u64; 3],
     is_compression_enabled: bool,
+    is_strategy_quantized: bool,
+    compression_params: [CompressionParams; 3],
+    update_schedule: [UpdateSchedule; 2],
     num_storage: u64,
     num_storage_ip: u64,
     num_storage_chance: u64,
     misc_memory_usage: u64,
 
+    // summary and manifest of the last `solve` call
+    solve_summary: Option<SolveSummary>,
+    solve_manifest: Option<SolveManifest>,
+
     // global storage
     // `storage*` are used as a global storage and are referenced by `PostFlopNode::storage*`.
     // Methods like `PostFlopNode::strategy` define how the storage is used.
     node_arena: Vec<MutexLike<PostFlopNode>>,
-    storage1: Vec<u8>,
-    storage2: Vec<u8>,
-    storage_ip: Vec<u8>,
-    storage_chance: Vec<u8>,
+    storage1: Storage,
+    storage2: Storage,
+    storage_ip: Storage,
+    storage_chance: Storage,
     locking_strategy: BTreeMap<usize, Vec<f32>>,
 
+    // temporary strategy overrides: (node index, original locking strategy)
+    strategy_overrides: Vec<(usize, Option<Vec<f32>>)>,
+
     // ICM calculator
     icm_calculator: Option<icm::ICMCalculator>,
 
+    // cache of the card tables shared between instances
+    table_cache: Option<TableCache>,
+
+    // cache of the all-in equities shared between instances
+    equity_cache: Option<EquityCache>,
+
+    // custom hand evaluator (the default one of the card configuration is used if `None`)
+    hand_evaluator: Option<Arc<dyn HandEvaluator>>,
+
+    // split-pot evaluator (the showdowns award the whole pot by `hand_evaluator` if `None`)
+    split_pot_evaluator: Option<Arc<dyn SplitPotEvaluator>>,
+
+    // seed of the Monte Carlo estimation of the ICM equities
+    icm_seed: u64,
+
+    // file from which the later streets can be loaded
+    #[cfg(feature = "bincode")]
+    source_path: Option<std::path::PathBuf>,
+
+    // dedicated thread pool (the global pool is used if `None`)
+    #[cfg(feature = "rayon")]
+    thread_pool: Option<Arc<rayon::ThreadPool>>,
+
+    // partitioning of the subtrees of the root across NUMA nodes
+    #[cfg(all(feature = "numa", target_os = "linux"))]
+    numa_partitions: Option<numa::NumaPartitions>,
+
     // result interpreter
     action_history: Vec<usize>,
     node_history: Vec<usize>,
@@ -116,6 +262,10 @@ pub struct PostFlopGame {
     weights: [Vec<f32>; 2],
     normalized_weights: [Vec<f32>; 2],
     cfvalues_cache: [Vec<f32>; 2],
+
+    // navigation history
+    redo_history: Vec<usize>,
+    named_bookmarks: BTreeMap<String, Bookmark>,
 }
 
 /// A struct representing a node in a postflop game tree.
@@ -0,0 +1,150 @@
+use super::*;
+use crate::interface::*;
+use crate::utility::*;
+
+/// Distance of the strategy of a private hand from the best response, reported by
+/// [`PostFlopGame::nash_distance_ranking`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandNashDistance {
+    /// Private hand.
+    pub hand: (Card, Card),
+
+    /// Index of the private hand in [`PostFlopGame::private_cards`].
+    pub hand_index: usize,
+
+    /// Probability of the hand in the range of the player, accounting for the card removal of the
+    /// opponent's range.
+    pub probability: f32,
+
+    /// Expected value of the hand with the current strategy, in the same convention as
+    /// [`PostFlopGame::expected_values`].
+    pub ev: f32,
+
+    /// Expected value of the hand when the player plays the best response to the current strategy
+    /// of the opponent.
+    pub best_response_ev: f32,
+
+    /// `best_response_ev - ev`, i.e., the expected value the hand gives up by not playing the best
+    /// response (always non-negative up to rounding errors).
+    pub distance: f32,
+
+    /// `distance` multiplied by `probability`. The sum over the range is the expected value the
+    /// player gives up, i.e., the contribution of the player to the exploitability times two.
+    pub weighted_distance: f32,
+}
+
+impl PostFlopGame {
+    /// Ranks the private hands of `player` by the distance of their current strategy from the best
+    /// response to the current strategy of the opponent (i.e., the per-hand regret at the root
+    /// node), from the least converged hand.
+    ///
+    /// A hand with a large `distance` is poorly converged or, in a locked game, sensitive to the
+    /// exploitation of the opponent, while `weighted_distance` tells how much the hand matters for
+    /// the exploitability. The game can be solved or still being solved: in the latter case, the
+    /// current strategy is the average strategy of the iterations performed so far. Hands with
+    /// zero probability are omitted.
+    ///
+    /// Returns [`SolverError::InvalidArgument`] if `player` is not 0 or 1, and
+    /// [`SolverError::InvalidState`] if the memory is not allocated or the game is not ready to be
+    /// solved (e.g., the storage mode is changed). The current node is restored after
+    /// the call.
+    ///
+    /// **Time complexity:** *O*(#(nodes) * #(private hands)), about half of
+    /// [`compute_exploitability`].
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["AA,KK,87s,65s,A5s".parse().unwrap(), "QQ-99,AK,KQs,T9s".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("2c").unwrap(),
+    ///     river: card_from_str("3s").unwrap(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::River,
+    ///     starting_pot: 100,
+    ///     effective_stack: 100,
+    ///     river_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// game.allocate_memory(false);
+    /// solve(&mut game, 100, 0.0, false);
+    ///
+    /// let ranking = game.nash_distance_ranking(0).unwrap();
+    /// let least_converged = &ranking[0];
+    /// println!(
+    ///     "{}: {:.3}",
+    ///     holes_to_strings(&[least_converged.hand]).unwrap()[0],
+    ///     least_converged.distance
+    /// );
+    /// ```
+    ///
+    /// [`compute_exploitability`]: crate::compute_exploitability
+    pub fn nash_distance_ranking(
+        &mut self,
+        player: usize,
+    ) -> Result<Vec<HandNashDistance>, SolverError> {
+        if player > 1 {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid player: {player}"
+            )));
+        }
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+        if self.state != State::Solved && !self.is_ready() {
+            return Err(SolverError::InvalidState("Game is not ready".to_owned()));
+        }
+
+        let history = self.history().to_vec();
+        self.back_to_root();
+        self.cache_normalized_weights();
+
+        let current = compute_current_cfvalues(self, player);
+        let best = compute_best_cfvalues(self, player, None);
+
+        let num_combinations = match self.bunching_num_dead_cards {
+            0 => self.num_combinations,
+            _ => self.bunching_num_combinations,
+        } as f32;
+        let bias = self.tree_config.starting_pot as f32 * 0.5;
+
+        let weights = &self.weights[player];
+        let normalized_weights = &self.normalized_weights[player];
+        let weight_sum = normalized_weights.iter().map(|&w| w as f64).sum::<f64>() as f32;
+
+        let mut ret = (0..self.num_private_hands(player))
+            .filter(|&hand| normalized_weights[hand] > 0.0)
+            .map(|hand| {
+                let scale = num_combinations * (weights[hand] / normalized_weights[hand]);
+                let ev = current[hand] * scale + bias;
+                let best_response_ev = best[hand] * scale + bias;
+                let distance = best_response_ev - ev;
+                let probability = normalized_weights[hand] / weight_sum;
+                HandNashDistance {
+                    hand: self.private_cards[player][hand],
+                    hand_index: hand,
+                    probability,
+                    ev,
+                    best_response_ev,
+                    distance,
+                    weighted_distance: distance * probability,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        ret.sort_by(|a, b| b.distance.total_cmp(&a.distance));
+
+        self.apply_history(&history);
+        Ok(ret)
+    }
+}
@@ -1,5 +1,6 @@
 use super::*;
 use crate::interface::*;
+use std::alloc::{self, Layout};
 use std::ptr;
 use std::slice;
 
@@ -106,6 +107,16 @@ impl GameNode for PostFlopNode {
         unsafe { slice::from_raw_parts_mut(self.storage1 as *mut u16, self.num_elements as usize) }
     }
 
+    #[inline]
+    fn strategy_quantized(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.storage1 as *const u8, self.num_elements as usize) }
+    }
+
+    #[inline]
+    fn strategy_quantized_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.storage1, self.num_elements as usize) }
+    }
+
     #[inline]
     fn regrets_compressed(&self) -> &[i16] {
         unsafe { slice::from_raw_parts(self.storage2 as *const i16, self.num_elements as usize) }
@@ -229,6 +240,39 @@ impl Default for PostFlopNode {
 }
 
 impl PostFlopNode {
+    /// Allocates an arena of `len` nodes from zero-initialized memory, returning `None` if the
+    /// allocation fails.
+    ///
+    /// Unlike filling the arena with default nodes, this does not write to the memory, so the
+    /// operating system commits the pages only when the tree is built into them, and the nodes are
+    /// touched once instead of twice. A zeroed node is identical to the default node except that
+    /// `turn` and `river` are `0` instead of `NOT_DEALT`; the caller must set them for every node.
+    pub(super) fn zeroed_arena(len: usize) -> Option<Vec<MutexLike<Self>>> {
+        if len == 0 {
+            return Some(Vec::new());
+        }
+
+        let layout = Layout::array::<MutexLike<Self>>(len).ok()?;
+
+        // SAFETY: `layout` has a non-zero size because `len > 0` and `PostFlopNode` is not a
+        // zero-sized type.
+        let ptr = unsafe { alloc::alloc_zeroed(layout) } as *mut MutexLike<Self>;
+        if ptr.is_null() {
+            return None;
+        }
+
+        // SAFETY:
+        // - `ptr` is allocated by the global allocator with `Layout::array::<T>(len)`, whose size
+        //   is `len * size_of::<T>()` and whose alignment is `align_of::<T>()`. This is exactly the
+        //   layout that `Vec<T>` passes to the global allocator when deallocating a buffer of
+        //   capacity `len`, so the capacity must be `len` (not rounded up by the allocator).
+        // - All the `len` elements are initialized, because the all-zero bit pattern is a valid
+        //   `PostFlopNode`: `prev_action` is `Action::None` (the discriminant `0` of the
+        //   `repr(u8)` enum `Action`), `player` is `PLAYER_OOP`, the numbers are zero, the
+        //   booleans are `false`, and the pointers are null. `MutexLike` is `repr(transparent)`.
+        unsafe { Some(Vec::from_raw_parts(ptr, len, len)) }
+    }
+
     #[inline]
     pub(super) fn children(&self) -> &[MutexLike<Self>] {
         // This is safe because `MutexLike<T>` is a `repr(transparent)` wrapper around `T`.
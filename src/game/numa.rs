@@ -0,0 +1,188 @@
+use super::*;
+use crate::numa::*;
+
+/// Partitioning of the subtrees of the root across NUMA nodes, set by
+/// [`PostFlopGame::set_numa_nodes`].
+pub(crate) struct NumaPartitions {
+    nodes: Vec<NumaNode>,
+    pools: Vec<Arc<rayon::ThreadPool>>,
+
+    // `assignment[action]`: index of the partition of the `action`-th child of the root
+    assignment: Vec<usize>,
+}
+
+impl PostFlopGame {
+    /// Partitions the subtrees of the root across the given NUMA nodes (see [`numa_nodes`]).
+    ///
+    /// The subtrees of the children of the root are assigned to the nodes so that the sizes of
+    /// their storage are balanced. While solving, each subtree is processed on a thread pool pinned
+    /// to the CPUs of its node (the partitions are processed concurrently), and its storage is
+    /// placed in the memory of the node. The storage is placed again whenever it is allocated. The
+    /// root itself and the computations other than [`solve`] and [`solve_step`] are run on the
+    /// current thread pool.
+    ///
+    /// Passing an empty slice removes the partitioning. The partitioning is also removed when the
+    /// game tree is rebuilt, and it is not saved when the game is serialized. The solved results do
+    /// not depend on the partitioning.
+    ///
+    /// Returns an error if the game tree is not built or a node has no CPUs.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["QQ+,AK".parse().unwrap(), "JJ-99,AQs".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("Qc").unwrap(),
+    ///     river: card_from_str("2s").unwrap(),
+    ///     ..Default::default()
+    /// };
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::River,
+    ///     starting_pot: 100,
+    ///     effective_stack: 100,
+    ///     river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+    ///     ..Default::default()
+    /// };
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    ///
+    /// game.set_numa_nodes(&numa_nodes()).unwrap();
+    /// game.allocate_memory(false);
+    /// solve(&mut game, 100, 0.0, false);
+    /// ```
+    ///
+    /// [`solve`]: crate::solve
+    /// [`solve_step`]: crate::solve_step
+    pub fn set_numa_nodes(&mut self, nodes: &[NumaNode]) -> Result<(), SolverError> {
+        if self.state <= State::Uninitialized {
+            return Err(SolverError::InvalidState(
+                "Game tree is not built".to_owned(),
+            ));
+        }
+
+        if nodes.is_empty() {
+            self.numa_partitions = None;
+            return Ok(());
+        }
+
+        let pools = nodes
+            .iter()
+            .map(numa_thread_pool)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // assign the largest subtree first to the partition with the least storage
+        let root = self.node_arena[0].lock();
+        let children_offset = root.children_offset as usize;
+        let mut sizes = (0..root.num_actions())
+            .map(|action| {
+                let mut size = 0;
+                self.for_each_subtree_node(children_offset + action, &mut |node| {
+                    let num_elements = node.num_elements as usize;
+                    size += num_elements * 2;
+                    size += node.num_elements_ip as usize;
+                });
+                (size, action)
+            })
+            .collect::<Vec<_>>();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut assignment = vec![0; sizes.len()];
+        let mut total = vec![0; nodes.len()];
+        for (size, action) in sizes {
+            let partition = (0..nodes.len()).min_by_key(|&i| total[i]).unwrap();
+            assignment[action] = partition;
+            total[partition] += size;
+        }
+
+        self.numa_partitions = Some(NumaPartitions {
+            nodes: nodes.to_vec(),
+            pools,
+            assignment,
+        });
+        self.place_numa_partitions();
+
+        Ok(())
+    }
+
+    /// Returns the NUMA node assigned to the subtree of each child of the root by
+    /// [`set_numa_nodes`], or `None` if the subtrees are not partitioned.
+    ///
+    /// [`set_numa_nodes`]: #method.set_numa_nodes
+    pub fn numa_assignment(&self) -> Option<Vec<&NumaNode>> {
+        let partitions = self.numa_partitions.as_ref()?;
+        let nodes = &partitions.nodes;
+        Some(partitions.assignment.iter().map(|&i| &nodes[i]).collect())
+    }
+
+    /// Returns the thread pool of the partition of the `action`-th child of the root.
+    #[inline]
+    pub(super) fn numa_thread_pool(&self, action: usize) -> Option<Arc<rayon::ThreadPool>> {
+        let partitions = self.numa_partitions.as_ref()?;
+        let partition = *partitions.assignment.get(action)?;
+        Some(Arc::clone(&partitions.pools[partition]))
+    }
+
+    /// Places the storage of each partition in the memory of its NUMA node.
+    pub(super) fn place_numa_partitions(&self) {
+        let Some(partitions) = &self.numa_partitions else {
+            return;
+        };
+
+        let num_bytes = if self.is_compression_enabled { 2 } else { 4 };
+        let strategy_num_bytes = self.strategy_num_bytes();
+        let children_offset = self.node_arena[0].lock().children_offset as usize;
+
+        for (action, &partition) in partitions.assignment.iter().enumerate() {
+            // the storage of a subtree consists of a few contiguous ranges of each buffer
+            let mut ranges = Vec::new();
+            let mut push = |ptr: *mut u8, len: usize| {
+                if !ptr.is_null() && len > 0 {
+                    ranges.push((ptr as usize, ptr as usize + len));
+                }
+            };
+
+            self.for_each_subtree_node(children_offset + action, &mut |node| {
+                let num_elements = node.num_elements as usize;
+                if node.is_terminal() {
+                    // do nothing
+                } else if node.is_chance() {
+                    push(node.storage1, num_bytes * num_elements);
+                } else {
+                    let num_elements_ip = node.num_elements_ip as usize;
+                    push(node.storage1, strategy_num_bytes * num_elements);
+                    push(node.storage2, num_bytes * num_elements);
+                    push(node.storage3, num_bytes * num_elements_ip);
+                }
+            });
+
+            ranges.sort_unstable();
+            let mut merged: Vec<(usize, usize)> = Vec::new();
+            for (start, end) in ranges {
+                match merged.last_mut() {
+                    Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                    _ => merged.push((start, end)),
+                }
+            }
+
+            let node_id = partitions.nodes[partition].id;
+            for (start, end) in merged {
+                bind_memory(start, end, node_id);
+            }
+        }
+    }
+
+    /// Calls `f` for each node of the subtree rooted at `node_arena[index]`.
+    fn for_each_subtree_node(&self, index: usize, f: &mut impl FnMut(&PostFlopNode)) {
+        let mut stack = vec![index];
+        while let Some(index) = stack.pop() {
+            let node = self.node_arena[index].lock();
+            f(&node);
+            // the nodes of the later streets do not exist if the tree is truncated
+            let children_offset = index + node.children_offset as usize;
+            let children_end = children_offset + node.num_children as usize;
+            stack.extend(children_offset..children_end.min(self.node_arena.len()));
+        }
+    }
+}
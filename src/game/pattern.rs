@@ -0,0 +1,219 @@
+use super::*;
+use crate::range::*;
+use std::str::FromStr;
+
+/// A token of a [`LinePattern`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatternToken {
+    /// Any sequence of actions and dealt cards, including the empty one.
+    AnySequence,
+
+    /// Any single action or dealt card.
+    AnyOne,
+
+    /// Any dealt card.
+    AnyCard,
+
+    /// Any bet.
+    AnyBet,
+
+    /// Any raise.
+    AnyRaise,
+
+    /// Any all-in.
+    AnyAllIn,
+
+    /// The given action or dealt card.
+    Exact(Action),
+}
+
+impl PatternToken {
+    #[inline]
+    fn matches(self, action: Action) -> bool {
+        match self {
+            Self::AnySequence | Self::AnyOne => true,
+            Self::AnyCard => matches!(action, Action::Chance(_)),
+            Self::AnyBet => matches!(action, Action::Bet(_)),
+            Self::AnyRaise => matches!(action, Action::Raise(_)),
+            Self::AnyAllIn => matches!(action, Action::AllIn(_)),
+            Self::Exact(a) => a == action,
+        }
+    }
+}
+
+/// A wildcard pattern of lines, used by [`PostFlopGame::find_nodes`].
+///
+/// A pattern is written in the format of [`line_to_string`] (codes joined with `:`), where the
+/// following wildcards can also be used:
+///
+/// - `*`: any sequence of actions and dealt cards (including the empty one)
+/// - `?`: any single action or dealt card
+/// - `D`: any dealt card
+/// - `B`, `R`, `A`: any bet, raise, or all-in, respectively (without the amount)
+///
+/// The pattern must match the whole line. The empty pattern matches only the root node.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// // any node facing a bet
+/// let pattern: LinePattern = "*:B".parse().unwrap();
+/// assert!(pattern.matches(&[Action::Check, Action::Bet(50)]));
+/// assert!(!pattern.matches(&[Action::Bet(50), Action::Call]));
+///
+/// // check-call on the turn, then any river card
+/// let pattern: LinePattern = "X:B:C:D:*".parse().unwrap();
+/// let river = card_from_str("As").unwrap();
+/// assert!(pattern.matches(&[
+///     Action::Check,
+///     Action::Bet(30),
+///     Action::Call,
+///     Action::Chance(river),
+///     Action::Check,
+/// ]));
+/// ```
+///
+/// [`line_to_string`]: crate::line_to_string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinePattern {
+    tokens: Vec<PatternToken>,
+}
+
+impl FromStr for LinePattern {
+    type Err = SolverError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(Self { tokens: Vec::new() });
+        }
+
+        let parse_amount = |code: &str| {
+            code[1..]
+                .parse::<i32>()
+                .map_err(|_| SolverError::InvalidArgument(format!("Invalid amount: {code}")))
+        };
+
+        let tokens = s
+            .split(':')
+            .map(|code| {
+                let code = code.trim();
+                Ok(match code {
+                    "*" => PatternToken::AnySequence,
+                    "?" => PatternToken::AnyOne,
+                    "D" => PatternToken::AnyCard,
+                    "B" => PatternToken::AnyBet,
+                    "R" => PatternToken::AnyRaise,
+                    "A" => PatternToken::AnyAllIn,
+                    "F" => PatternToken::Exact(Action::Fold),
+                    "X" => PatternToken::Exact(Action::Check),
+                    "C" => PatternToken::Exact(Action::Call),
+                    _ if code.len() == 2 && code.as_bytes()[1].is_ascii_lowercase() => {
+                        PatternToken::Exact(Action::Chance(card_from_str(code)?))
+                    }
+                    _ if code.starts_with('B') => {
+                        PatternToken::Exact(Action::Bet(parse_amount(code)?))
+                    }
+                    _ if code.starts_with('R') => {
+                        PatternToken::Exact(Action::Raise(parse_amount(code)?))
+                    }
+                    _ if code.starts_with('A') => {
+                        PatternToken::Exact(Action::AllIn(parse_amount(code)?))
+                    }
+                    _ => {
+                        return Err(SolverError::InvalidArgument(format!(
+                            "Invalid pattern code: {code}"
+                        )));
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>, SolverError>>()?;
+
+        Ok(Self { tokens })
+    }
+}
+
+impl LinePattern {
+    /// Returns whether the given line (list of actions and dealt cards from the root node) matches
+    /// the pattern.
+    pub fn matches(&self, line: &[Action]) -> bool {
+        // matched[j]: whether the first `j` actions match the tokens processed so far
+        let mut matched = vec![false; line.len() + 1];
+        matched[0] = true;
+
+        for &token in &self.tokens {
+            if token == PatternToken::AnySequence {
+                for j in 1..=line.len() {
+                    matched[j] |= matched[j - 1];
+                }
+            } else {
+                for j in (1..=line.len()).rev() {
+                    matched[j] = matched[j - 1] && token.matches(line[j - 1]);
+                }
+                matched[0] = false;
+            }
+        }
+
+        matched[line.len()]
+    }
+}
+
+impl PostFlopGame {
+    /// Returns the histories (see [`apply_history`]) of the player nodes whose lines match the
+    /// given pattern.
+    ///
+    /// Every possible turn and river card is visited (not only the representative of isomorphic
+    /// cards). If `street` is `Some`, only the nodes of the given street are returned. The current
+    /// node is preserved.
+    ///
+    /// **Time complexity:** *O*(#(nodes) * #(depth) * (#(OOP private hands) + #(IP private
+    /// hands))).
+    ///
+    /// [`apply_history`]: #method.apply_history
+    pub fn find_nodes(
+        &mut self,
+        pattern: &LinePattern,
+        street: Option<BoardState>,
+    ) -> Result<Vec<Vec<usize>>, SolverError> {
+        let mut result = Vec::new();
+        self.for_each_matching_node(pattern, street, &mut |_, history, _| {
+            result.push(history.to_vec());
+            Ok(())
+        })?;
+        Ok(result)
+    }
+
+    /// Calls `f` on each player node whose line matches the given pattern.
+    ///
+    /// The callback receives the game moved to the matched node, its history, and its line, and
+    /// can run any query or lock the strategy of the node (e.g., with
+    /// [`lock_current_strategy`]). The normalized weights are cached before each call. Nodes are
+    /// visited in depth-first order, as in [`find_nodes`]. An error returned by `f` stops the walk
+    /// and is returned as is.
+    ///
+    /// Note that isomorphic turn and river cards share the same nodes, so locking the nodes under
+    /// an isomorphic card overwrites the locks set under its representative.
+    ///
+    /// The current node is restored after the call.
+    ///
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    /// [`find_nodes`]: #method.find_nodes
+    pub fn for_each_matching_node<F>(
+        &mut self,
+        pattern: &LinePattern,
+        street: Option<BoardState>,
+        f: &mut F,
+    ) -> Result<(), SolverError>
+    where
+        F: FnMut(&mut Self, &[usize], &[Action]) -> Result<(), SolverError>,
+    {
+        self.walk_player_nodes(&[], street, &mut |game, history, line| {
+            if !pattern.matches(line) {
+                return Ok(());
+            }
+            game.cache_normalized_weights();
+            f(game, history, line)
+        })
+    }
+}
@@ -0,0 +1,344 @@
+use super::*;
+use crate::interface::*;
+use crate::position::*;
+use crate::range::*;
+use crate::units::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Type of a node queried by [`PostFlopGame::query_node`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum NodeType {
+    /// Terminal node (a fold or a showdown).
+    Terminal,
+
+    /// Chance node (the dealing of a turn or river card).
+    Chance,
+
+    /// Player node with the player to act (0 = OOP, 1 = IP).
+    Player(usize),
+}
+
+/// Data of a node returned by [`PostFlopGame::query_node`].
+///
+/// With the `msgpack` and `cbor` features, the struct can be serialized into MessagePack and CBOR,
+/// which are much more compact than JSON for the large arrays of strategies and expected values.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct NodeQuery {
+    /// History from the root node (see [`PostFlopGame::apply_history`]).
+    pub history: Vec<usize>,
+
+    /// Board cards.
+    pub board: Vec<Card>,
+
+    /// Pot size, including the bets of the current street.
+    pub pot: i32,
+
+    /// Type of the node.
+    pub node_type: NodeType,
+
+    /// Positions of the players (OOP, IP) (see [`CardConfig::positions`]).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub positions: [Option<Position>; 2],
+
+    /// Available actions (empty unless the node is a player node).
+    pub actions: Vec<Action>,
+
+    /// Cards that may be dealt (empty unless the node is a chance node).
+    pub possible_cards: Vec<Card>,
+
+    /// Private hands of each player (OOP, IP).
+    pub hands: [Vec<(Card, Card)>; 2],
+
+    /// Normalized weights of each private hand (see [`PostFlopGame::normalized_weights`]).
+    pub weights: [Vec<f32>; 2],
+
+    /// Equity of each private hand.
+    pub equity: [Vec<f32>; 2],
+
+    /// Unit of `pot_shares` and `expected_values` (see [`PostFlopGame::query_node_in`]).
+    pub unit: ChipUnit,
+
+    /// Share of the pot after the rake of each private hand (see [`PostFlopGame::pot_shares`]).
+    pub pot_shares: [Vec<f32>; 2],
+
+    /// Expected value of each private hand. `None` if the game is not solved.
+    pub expected_values: Option<[Vec<f32>; 2]>,
+
+    /// Strategy of the player to act, laid out as in [`PostFlopGame::strategy`] (empty unless the
+    /// node is a player node).
+    pub strategy: Vec<f32>,
+}
+
+/// Strategy of a private hand at the current player node, returned by
+/// [`PostFlopGame::hand_strategies`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct HandStrategy {
+    /// Normalized weight of the hand (see [`PostFlopGame::normalized_weights`]).
+    pub weight: f32,
+
+    /// Probability of each action, in the order of [`PostFlopGame::available_actions`].
+    pub strategy: Vec<f32>,
+
+    /// Expected value of each action in chips (see [`PostFlopGame::expected_values_detail`]).
+    /// `None` if the game is not solved.
+    pub action_evs: Option<Vec<f32>>,
+
+    /// Expected value of the hand in chips, i.e., the average of `action_evs` weighted by
+    /// `strategy`. `None` if the game is not solved.
+    pub expected_value: Option<f32>,
+}
+
+impl PostFlopGame {
+    /// Returns the strategy of the player to act at the current node, keyed by the hand strings
+    /// (e.g., `"AhKh"`; see [`hole_to_string`]).
+    ///
+    /// This spares the correlation of [`private_cards`], the layout of [`strategy`], and the action
+    /// indices. The normalized weights are cached by this method.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the memory is not allocated or the current node is
+    /// not a player node.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["AA,KK".parse().unwrap(), "QQ,AKs".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("Qc").unwrap(),
+    ///     river: card_from_str("2s").unwrap(),
+    ///     ..Default::default()
+    /// };
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::River,
+    ///     starting_pot: 100,
+    ///     effective_stack: 100,
+    ///     river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+    ///     ..Default::default()
+    /// };
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// game.allocate_memory(false);
+    /// solve(&mut game, 100, 0.0, false);
+    ///
+    /// let strategies = game.hand_strategies().unwrap();
+    /// let aces = &strategies["AhAd"];
+    /// assert_eq!(aces.strategy.len(), game.available_actions().len());
+    /// assert!((aces.strategy.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+    /// assert!(aces.expected_value.unwrap() > 0.0);
+    /// ```
+    ///
+    /// [`private_cards`]: #method.private_cards
+    /// [`strategy`]: #method.strategy
+    pub fn hand_strategies(&mut self) -> Result<BTreeMap<String, HandStrategy>, SolverError> {
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(SolverError::InvalidState(
+                "Current node is not a player node".to_owned(),
+            ));
+        }
+
+        self.cache_normalized_weights();
+
+        let player = self.current_player();
+        let num_hands = self.num_private_hands(player);
+        let num_actions = self.available_actions().len();
+        let strategy = self.strategy();
+        let action_evs = self
+            .is_solved()
+            .then(|| self.expected_values_detail(player));
+
+        let column = |values: &[f32], hand: usize| {
+            (0..num_actions)
+                .map(|action| values[action * num_hands + hand])
+                .collect::<Vec<_>>()
+        };
+
+        let mut ret = BTreeMap::new();
+        for (hand, &hole) in self.private_cards(player).iter().enumerate() {
+            let hand_strategy = column(&strategy, hand);
+            let hand_action_evs = action_evs.as_ref().map(|evs| column(evs, hand));
+            let expected_value = hand_action_evs.as_ref().map(|evs| {
+                evs.iter()
+                    .zip(&hand_strategy)
+                    .map(|(ev, probability)| ev * probability)
+                    .sum()
+            });
+            ret.insert(
+                hole_to_string(hole)?,
+                HandStrategy {
+                    weight: self.normalized_weights(player)[hand],
+                    strategy: hand_strategy,
+                    action_evs: hand_action_evs,
+                    expected_value,
+                },
+            );
+        }
+
+        Ok(ret)
+    }
+
+    /// Collects the data of the current node into a [`NodeQuery`].
+    ///
+    /// The normalized weights are cached by this method.
+    ///
+    /// **Time complexity:** dominated by [`equity`] and [`expected_values`].
+    ///
+    /// [`equity`]: PostFlopGame::equity
+    /// [`expected_values`]: PostFlopGame::expected_values
+    #[inline]
+    pub fn query_node(&mut self) -> Result<NodeQuery, SolverError> {
+        self.query_node_in(ChipUnit::Chips)
+    }
+
+    /// Collects the data of the current node into a [`NodeQuery`], reporting the pot shares and
+    /// the expected values in `unit`. The pot is still reported in chips.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the memory is not allocated, and
+    /// [`SolverError::InvalidArgument`] if `unit` is invalid.
+    pub fn query_node_in(&mut self, unit: ChipUnit) -> Result<NodeQuery, SolverError> {
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+
+        unit.check()?;
+
+        self.cache_normalized_weights();
+
+        let (node_type, actions, possible_cards, strategy) = if self.is_terminal_node() {
+            (NodeType::Terminal, Vec::new(), Vec::new(), Vec::new())
+        } else if self.is_chance_node() {
+            let mask = self.possible_cards();
+            let cards = (0..52).filter(|&card| mask & (1 << card) != 0).collect();
+            (NodeType::Chance, Vec::new(), cards, Vec::new())
+        } else {
+            let player = self.current_player();
+            let actions = self.available_actions();
+            (
+                NodeType::Player(player),
+                actions,
+                Vec::new(),
+                self.strategy(),
+            )
+        };
+
+        let bet = self.total_bet_amount();
+        let equity = [self.equity(0), self.equity(1)];
+        let expected_values = self
+            .is_solved()
+            .then(|| [0, 1].map(|player| self.expected_values_in(player, unit)));
+
+        Ok(NodeQuery {
+            history: self.history().to_vec(),
+            board: self.current_board(),
+            pot: self.tree_config().starting_pot + bet[0] + bet[1],
+            node_type,
+            positions: self.card_config.positions,
+            actions,
+            possible_cards,
+            hands: [0, 1].map(|player| self.private_cards(player).to_vec()),
+            weights: [0, 1].map(|player| self.normalized_weights(player).to_vec()),
+            pot_shares: equity.each_ref().map(|e| self.pot_shares_of(e, unit)),
+            equity,
+            unit,
+            expected_values,
+            strategy,
+        })
+    }
+
+    /// Returns the expected values of each private hand of the given player in `unit` (see
+    /// [`expected_values`]).
+    ///
+    /// Panics if the game is not solved.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// [`expected_values`]: #method.expected_values
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    #[inline]
+    pub fn expected_values_in(&self, player: usize, unit: ChipUnit) -> Vec<f32> {
+        unit.convert_all(&self.expected_values(player))
+    }
+
+    /// Returns the share of the pot of each private hand of the given player in `unit`, i.e., the
+    /// equity times the pot after the rake.
+    ///
+    /// The rake is the one taken if the hand ends with a showdown on the current street (see
+    /// [`TreeConfig::rake`]). Note that the pot share is not the expected value: it ignores the
+    /// remaining betting and the amounts already put into the pot.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// **Time complexity:** see [`equity`].
+    ///
+    /// [`equity`]: #method.equity
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    #[inline]
+    pub fn pot_shares(&self, player: usize, unit: ChipUnit) -> Vec<f32> {
+        self.pot_shares_of(&self.equity(player), unit)
+    }
+
+    fn pot_shares_of(&self, equity: &[f32], unit: ChipUnit) -> Vec<f32> {
+        let bet = self.total_bet_amount();
+        let pot = (self.tree_config.starting_pot + bet[0] + bet[1]) as f64;
+        let street = match self.current_board().len() {
+            3 => BoardState::Flop,
+            4 => BoardState::Turn,
+            _ => BoardState::River,
+        };
+        let rake = self.tree_config.rake(pot, street);
+        equity
+            .iter()
+            .map(|&equity| unit.pot_share(pot, rake, equity as f64) as f32)
+            .collect()
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl NodeQuery {
+    /// Serializes the query result into MessagePack.
+    ///
+    /// Structs are encoded as maps with the field names, so that clients in other languages can
+    /// decode the result without knowing the field order.
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, SolverError> {
+        rmp_serde::to_vec_named(self)
+            .map_err(|e| SolverError::Other(format!("Failed to encode MessagePack: {e}")))
+    }
+
+    /// Deserializes a query result from MessagePack.
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Self, SolverError> {
+        rmp_serde::from_slice(bytes)
+            .map_err(|e| SolverError::InvalidData(format!("Failed to decode MessagePack: {e}")))
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl NodeQuery {
+    /// Serializes the query result into CBOR.
+    pub fn to_cbor(&self) -> Result<Vec<u8>, SolverError> {
+        let mut buf = Vec::new();
+        ciborium::into_writer(self, &mut buf)
+            .map_err(|e| SolverError::Other(format!("Failed to encode CBOR: {e}")))?;
+        Ok(buf)
+    }
+
+    /// Deserializes a query result from CBOR.
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, SolverError> {
+        ciborium::from_reader(bytes)
+            .map_err(|e| SolverError::InvalidData(format!("Failed to decode CBOR: {e}")))
+    }
+}
@@ -0,0 +1,102 @@
+use super::*;
+use crate::bunching::*;
+use crate::utility::*;
+
+/// Range-vs-range equities on a board, computed by [`range_equity`] without solving a game.
+#[derive(Debug, Clone, Default)]
+pub struct RangeEquity {
+    /// Private hands of each player, in the order of [`PostFlopGame::private_cards`].
+    pub private_cards: [Vec<(Card, Card)>; 2],
+
+    /// Normalized weights of the private hands of each player, i.e., the numbers of combinations
+    /// that the player is holding each hand (see [`PostFlopGame::normalized_weights`]).
+    pub weights: [Vec<f32>; 2],
+
+    /// Equity of each private hand of each player.
+    pub equity: [Vec<f32>; 2],
+}
+
+impl RangeEquity {
+    /// Returns the overall equity of the range of the given player.
+    #[inline]
+    pub fn overall(&self, player: usize) -> f32 {
+        compute_average(&self.equity[player], &self.weights[player])
+    }
+}
+
+/// Computes the equities of the ranges of `card_config` on its board by enumerating all runouts.
+///
+/// If `bunching_data` is given, the card removal of the folded players is taken into account in
+/// the same way as a game with [`PostFlopGame::set_bunching_effect`], so that the shift caused by
+/// the bunching effect can be measured before running a full solve with it (see also
+/// [`bunching_equity_shift`]). The same restrictions apply: `bunching_data` must be ready, its
+/// flop must match, and dead cards are not supported.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let card_config = CardConfig {
+///     range: ["AA,KK,AK".parse().unwrap(), "QQ-TT,AQs".parse().unwrap()],
+///     flop: flop_from_str("Td9d6h").unwrap(),
+///     turn: card_from_str("Qc").unwrap(),
+///     ..Default::default()
+/// };
+///
+/// let equity = range_equity(&card_config, None).unwrap();
+/// assert!((equity.overall(0) + equity.overall(1) - 1.0).abs() < 1e-4);
+/// ```
+pub fn range_equity(
+    card_config: &CardConfig,
+    bunching_data: Option<&BunchingData>,
+) -> Result<RangeEquity, SolverError> {
+    let mut game = showdown_game(card_config)?;
+    if let Some(bunching_data) = bunching_data {
+        game.set_bunching_effect(bunching_data)?;
+    }
+    Ok(root_equity(&mut game))
+}
+
+/// Returns how much the bunching effect of `bunching_data` shifts the overall equity of each
+/// player on the board of `card_config` (equity with bunching minus equity without it).
+///
+/// The two shifts sum to zero. See [`range_equity`] for the per-hand equities.
+pub fn bunching_equity_shift(
+    card_config: &CardConfig,
+    bunching_data: &BunchingData,
+) -> Result<[f32; 2], SolverError> {
+    let mut game = showdown_game(card_config)?;
+    let base = root_equity(&mut game);
+    game.set_bunching_effect(bunching_data)?;
+    let bunching = root_equity(&mut game);
+    Ok([0, 1].map(|player| bunching.overall(player) - base.overall(player)))
+}
+
+/// Builds a game whose tree has no betting, which is enough for evaluating the equities.
+fn showdown_game(card_config: &CardConfig) -> Result<PostFlopGame, SolverError> {
+    let initial_state = if card_config.river != NOT_DEALT {
+        BoardState::River
+    } else if card_config.turn != NOT_DEALT {
+        BoardState::Turn
+    } else {
+        BoardState::Flop
+    };
+
+    let action_tree = ActionTree::new(TreeConfig {
+        initial_state,
+        starting_pot: 1,
+        effective_stack: 1,
+        ..Default::default()
+    })?;
+
+    PostFlopGame::with_config(card_config.clone(), action_tree)
+}
+
+fn root_equity(game: &mut PostFlopGame) -> RangeEquity {
+    game.cache_normalized_weights();
+    RangeEquity {
+        private_cards: [0, 1].map(|player| game.private_cards(player).to_vec()),
+        weights: [0, 1].map(|player| game.normalized_weights(player).to_vec()),
+        equity: [0, 1].map(|player| game.equity(player)),
+    }
+}
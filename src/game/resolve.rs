@@ -0,0 +1,173 @@
+use super::*;
+use crate::interface::*;
+use crate::sliceop::*;
+use crate::solver::*;
+use crate::utility::*;
+use std::ptr;
+
+impl PostFlopGame {
+    /// Re-solves only the subtree of the node reached by `history` in the solved game, optionally
+    /// after locking the strategies of some nodes in the subtree.
+    ///
+    /// Each lock is a pair of a history from the root node (see [`apply_history`]) and a strategy
+    /// in the format of [`lock_current_strategy`]; the history must pass through the root of the
+    /// subtree. The cumulative regrets and strategies of the subtree are reset and Discounted CFR
+    /// is run on the subtree for `num_iterations` iterations, holding the reach probabilities of
+    /// both players at the subtree root fixed to those of the current solution. The strategies
+    /// outside the subtree are not changed, but the expected values of the whole tree are updated
+    /// to reflect the new strategies. The current node is preserved.
+    ///
+    /// Because the strategies above the subtree are kept, the result is not an equilibrium of the
+    /// whole game in general; re-solve the whole game for exact results. The locks are permanent
+    /// (unlike [`override_current_strategy`]).
+    ///
+    /// Returns [`SolverError::InvalidState`] if the game is not solved or the history ends in a
+    /// terminal node, [`SolverError::InvalidLine`] if the history is invalid, and
+    /// [`SolverError::InvalidArgument`] if a lock is invalid. Nothing is changed in case of an
+    /// error.
+    ///
+    /// [`apply_history`]: #method.apply_history
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    /// [`override_current_strategy`]: #method.override_current_strategy
+    pub fn resolve_subtree(
+        &mut self,
+        history: &[usize],
+        locks: &[(Vec<usize>, Vec<f32>)],
+        num_iterations: u32,
+    ) -> Result<(), SolverError> {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+        if self.has_strategy_overrides() {
+            return Err(SolverError::InvalidState(
+                "Cannot re-solve while strategy overrides are active".to_owned(),
+            ));
+        }
+
+        let original_history = self.history().to_vec();
+        let result = self.resolve_subtree_internal(history, locks, num_iterations);
+        self.apply_history(&original_history);
+        result
+    }
+
+    fn resolve_subtree_internal(
+        &mut self,
+        history: &[usize],
+        locks: &[(Vec<usize>, Vec<f32>)],
+        num_iterations: u32,
+    ) -> Result<(), SolverError> {
+        self.line_from_history(history)?;
+        self.apply_history(history);
+        if self.is_terminal_node() {
+            return Err(SolverError::InvalidState(
+                "Cannot re-solve a terminal node".to_owned(),
+            ));
+        }
+
+        let root_index = self.node_index(&self.node());
+        let reach = self.reach_in_node_frame();
+
+        // validate all the locks before changing anything
+        let mut new_locks = Vec::with_capacity(locks.len());
+        for (lock_history, strategy) in locks {
+            if !lock_history.starts_with(history) {
+                return Err(SolverError::InvalidArgument(format!(
+                    "Lock is outside of the subtree: {lock_history:?}"
+                )));
+            }
+            self.line_from_history(lock_history)?;
+            self.apply_history(lock_history);
+            if self.is_terminal_node() || self.is_chance_node() {
+                return Err(SolverError::InvalidArgument(format!(
+                    "Lock is not at a player node: {lock_history:?}"
+                )));
+            }
+            let num_actions = self.node().num_actions();
+            if strategy.len() != num_actions * self.num_private_hands(self.current_player()) {
+                return Err(SolverError::InvalidArgument(format!(
+                    "Invalid strategy length: {lock_history:?}"
+                )));
+            }
+            let index = self.node_index(&self.node());
+            new_locks.push((index, self.locking_from_strategy(strategy)));
+        }
+
+        for (index, locking) in new_locks {
+            self.node_arena[index].lock().is_locked = true;
+            self.locking_strategy.insert(index, locking);
+        }
+
+        self.unshare_storage();
+
+        let num_bytes = if self.is_compression_enabled { 2 } else { 4 };
+        let strategy_num_bytes = self.strategy_num_bytes();
+        reset_subtree(
+            &mut self.node_arena[root_index].lock(),
+            strategy_num_bytes,
+            num_bytes,
+        );
+
+        solve_subtree(
+            &*self,
+            &mut self.node_arena[root_index].lock(),
+            &reach,
+            num_iterations,
+        );
+
+        save_cfvalues(self);
+        Ok(())
+    }
+
+    /// Computes the reach probabilities of both players at the current node in the frame of the
+    /// stored node (i.e., without applying the isomorphic swaps). The chance probabilities are
+    /// omitted.
+    fn reach_in_node_frame(&self) -> [Vec<f32>; 2] {
+        let mut reach = self.initial_weights.clone();
+        let mut parent_index = 0;
+
+        for &child_index in &self.node_history {
+            let parent = self.node_arena[parent_index].lock();
+            if !parent.is_chance() && parent.num_actions() > 1 {
+                let player = parent.player();
+                let num_actions = parent.num_actions();
+                let num_hands = self.num_private_hands(player);
+                let action = child_index - parent_index - parent.children_offset as usize;
+
+                let mut strategy = if self.is_strategy_quantized {
+                    normalized_strategy_compressed(parent.strategy_quantized(), num_actions)
+                } else if self.is_compression_enabled {
+                    normalized_strategy_compressed(parent.strategy_compressed(), num_actions)
+                } else {
+                    normalized_strategy(parent.strategy(), num_actions)
+                };
+                apply_locking_strategy(&mut strategy, self.locking_strategy(&parent));
+
+                mul_slice(&mut reach[player], row(&strategy, action, num_hands));
+            }
+            parent_index = child_index;
+        }
+
+        reach
+    }
+}
+
+/// Resets the cumulative regrets and strategies of the player nodes in the subtree.
+fn reset_subtree(node: &mut PostFlopNode, strategy_num_bytes: usize, num_bytes: usize) {
+    if node.is_terminal() {
+        return;
+    }
+
+    if !node.is_chance() {
+        let num_elements = node.num_elements as usize;
+        unsafe {
+            ptr::write_bytes(node.storage1, 0, strategy_num_bytes * num_elements);
+            ptr::write_bytes(node.storage2, 0, num_bytes * num_elements);
+        }
+        node.scale1 = 0.0;
+        node.scale2 = 0.0;
+    }
+
+    for action in node.action_indices() {
+        reset_subtree(&mut node.play(action), strategy_num_bytes, num_bytes);
+    }
+}
@@ -0,0 +1,301 @@
+use super::*;
+use crate::hand_class::*;
+use std::fmt;
+
+/// Feature of a private hand used by [`StrategyRule`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RuleFeature {
+    /// Hand class (see [`classify_hand`]). Values are indices into [`HandClass::ALL`].
+    HandClass,
+
+    /// Equity against the range of the opponent.
+    Equity,
+
+    /// Blocker score (see [`ScatterPoint::blocker_score`]).
+    BlockerScore,
+}
+
+impl RuleFeature {
+    /// List of all features.
+    pub const ALL: [Self; 3] = [Self::HandClass, Self::Equity, Self::BlockerScore];
+}
+
+impl fmt::Display for RuleFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::HandClass => "Hand class",
+            Self::Equity => "Equity",
+            Self::BlockerScore => "Blocker score",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// A segment of a [`StrategyRule`]: hands whose feature value lies in `[lower, upper]` take the
+/// same action.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RuleSegment {
+    /// Feature value of the first hand of the segment.
+    pub lower: f32,
+
+    /// Feature value of the last hand of the segment.
+    pub upper: f32,
+
+    /// Index of the action taken.
+    pub action_index: usize,
+
+    /// The action taken.
+    pub action: Action,
+
+    /// Share of the range covered by the segment.
+    pub share: f32,
+}
+
+/// A simple decision rule approximating the strategy of a node, returned by
+/// [`PostFlopGame::extract_rules`].
+///
+/// Hands are sorted by the feature from the strongest to the weakest (ascending hand class index,
+/// descending equity, and descending blocker score), and each segment of consecutive hands is
+/// assigned a single action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategyRule {
+    /// Feature the rule is based on.
+    pub feature: RuleFeature,
+
+    /// Segments from the strongest to the weakest.
+    pub segments: Vec<RuleSegment>,
+
+    /// Probability that the solver strategy takes the action of the rule, weighted by the
+    /// normalized weights.
+    pub accuracy: f32,
+
+    /// Expected value of the solver strategy minus that of the rule, averaged over the range.
+    pub ev_loss: f32,
+}
+
+impl fmt::Display for StrategyRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} (accuracy {:.1}%, EV loss {:.3}):",
+            self.feature,
+            self.accuracy * 100.0,
+            self.ev_loss
+        )?;
+        for segment in &self.segments {
+            let range = match self.feature {
+                RuleFeature::HandClass => {
+                    let lower = HandClass::ALL[segment.lower as usize];
+                    let upper = HandClass::ALL[segment.upper as usize];
+                    if lower == upper {
+                        format!("{lower}")
+                    } else {
+                        format!("{lower} to {upper}")
+                    }
+                }
+                _ => format!("{:.3} to {:.3}", segment.lower, segment.upper),
+            };
+            writeln!(
+                f,
+                "  {range}: {} ({:.1}% of range)",
+                segment.action,
+                segment.share * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Hands sharing the same feature value.
+struct RuleGroup {
+    value: f32,
+    weight: f64,
+    loss: Vec<f64>,
+    agreement: Vec<f64>,
+}
+
+impl PostFlopGame {
+    /// Fits simple decision rules approximating the strategy of the current player at the current
+    /// node, one for each [`RuleFeature`].
+    ///
+    /// For each feature, the hands are split into at most `max_segments` segments of consecutive
+    /// feature values, and each segment is assigned a single action. The split and the actions are
+    /// chosen to minimize the EV loss compared to the solver strategy (exactly, by dynamic
+    /// programming). Hands with zero normalized weight are ignored. The returned rules are sorted
+    /// by the EV loss in ascending order.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the game is not solved, the current node is not a
+    /// player node, or the range of the current player does not reach the current node, and
+    /// [`SolverError::InvalidArgument`] if `max_segments` is zero.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// **Time complexity:** *O*(`max_segments` * #(actions) * #(private hands)^2), in addition to
+    /// that of [`scatter_data`].
+    ///
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    /// [`scatter_data`]: #method.scatter_data
+    pub fn extract_rules(&self, max_segments: usize) -> Result<Vec<StrategyRule>, SolverError> {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(SolverError::InvalidState(
+                "Current node is not a player node".to_owned(),
+            ));
+        }
+        if max_segments == 0 {
+            return Err(SolverError::InvalidArgument(
+                "max_segments must be positive".to_owned(),
+            ));
+        }
+
+        let points = self.scatter_data();
+        if points.is_empty() {
+            return Err(SolverError::InvalidState(
+                "Current node is unreachable".to_owned(),
+            ));
+        }
+
+        let actions = self.available_actions();
+        let board = self.current_board();
+        let total_weight = points.iter().fold(0.0, |acc, p| acc + p.weight as f64);
+
+        let mut rules = RuleFeature::ALL
+            .iter()
+            .map(|&feature| {
+                let mut keyed = points
+                    .iter()
+                    .map(|p| {
+                        let value = match feature {
+                            RuleFeature::HandClass => classify_hand(p.hand, &board) as usize as f32,
+                            RuleFeature::Equity => p.equity,
+                            RuleFeature::BlockerScore => p.blocker_score,
+                        };
+                        (value, p)
+                    })
+                    .collect::<Vec<_>>();
+
+                // strongest first
+                match feature {
+                    RuleFeature::HandClass => keyed.sort_by(|a, b| a.0.total_cmp(&b.0)),
+                    _ => keyed.sort_by(|a, b| b.0.total_cmp(&a.0)),
+                }
+
+                let mut groups: Vec<RuleGroup> = Vec::new();
+                for (value, p) in keyed {
+                    if groups.last().is_none_or(|g| g.value != value) {
+                        groups.push(RuleGroup {
+                            value,
+                            weight: 0.0,
+                            loss: vec![0.0; actions.len()],
+                            agreement: vec![0.0; actions.len()],
+                        });
+                    }
+                    let group = groups.last_mut().unwrap();
+                    let w = p.weight as f64;
+                    group.weight += w;
+                    for action in 0..actions.len() {
+                        group.loss[action] += w * (p.ev - p.action_ev[action]) as f64;
+                        group.agreement[action] += w * p.frequencies[action] as f64;
+                    }
+                }
+
+                fit_rule(feature, &groups, &actions, max_segments, total_weight)
+            })
+            .collect::<Vec<_>>();
+
+        rules.sort_by(|a, b| a.ev_loss.total_cmp(&b.ev_loss));
+        Ok(rules)
+    }
+}
+
+/// Finds the optimal piecewise-constant assignment of actions to the groups.
+fn fit_rule(
+    feature: RuleFeature,
+    groups: &[RuleGroup],
+    actions: &[Action],
+    max_segments: usize,
+    total_weight: f64,
+) -> StrategyRule {
+    let num_groups = groups.len();
+    let num_actions = actions.len();
+
+    // prefix sums of the loss of each action
+    let mut prefix = vec![vec![0.0; num_groups + 1]; num_actions];
+    for (action, prefix) in prefix.iter_mut().enumerate() {
+        for (i, group) in groups.iter().enumerate() {
+            prefix[i + 1] = prefix[i] + group.loss[action];
+        }
+    }
+
+    // best[k][j]: minimum loss of the first `j` groups with `k` segments
+    let max_segments = max_segments.min(num_groups);
+    let mut best = vec![vec![f64::INFINITY; num_groups + 1]; max_segments + 1];
+    let mut choice = vec![vec![(0, 0); num_groups + 1]; max_segments + 1];
+    best[0][0] = 0.0;
+
+    for k in 1..=max_segments {
+        for j in k..=num_groups {
+            for i in (k - 1)..j {
+                if best[k - 1][i].is_infinite() {
+                    continue;
+                }
+                for (action, prefix) in prefix.iter().enumerate() {
+                    let loss = best[k - 1][i] + prefix[j] - prefix[i];
+                    if loss < best[k][j] {
+                        best[k][j] = loss;
+                        choice[k][j] = (i, action);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut k = (1..=max_segments)
+        .min_by(|&a, &b| best[a][num_groups].total_cmp(&best[b][num_groups]))
+        .unwrap();
+    let min_loss = best[k][num_groups];
+
+    // backtracks the segments
+    let mut bounds = Vec::new();
+    let mut j = num_groups;
+    while k > 0 {
+        let (i, action) = choice[k][j];
+        bounds.push((i, j, action));
+        j = i;
+        k -= 1;
+    }
+    bounds.reverse();
+
+    let mut segments: Vec<RuleSegment> = Vec::new();
+    let mut agreement = 0.0;
+    for (i, j, action) in bounds {
+        let weight = groups[i..j].iter().fold(0.0, |acc, g| acc + g.weight);
+        agreement += groups[i..j]
+            .iter()
+            .fold(0.0, |acc, g| acc + g.agreement[action]);
+        let share = (weight / total_weight) as f32;
+        match segments.last_mut() {
+            Some(last) if last.action_index == action => {
+                last.upper = groups[j - 1].value;
+                last.share += share;
+            }
+            _ => segments.push(RuleSegment {
+                lower: groups[i].value,
+                upper: groups[j - 1].value,
+                action_index: action,
+                action: actions[action],
+                share,
+            }),
+        }
+    }
+
+    StrategyRule {
+        feature,
+        segments,
+        accuracy: (agreement / total_weight) as f32,
+        ev_loss: (min_loss / total_weight) as f32,
+    }
+}
@@ -0,0 +1,269 @@
+use super::*;
+use crate::utility::*;
+use std::cmp::Ordering;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Results of running the remaining board multiple times at an all-in node, returned by
+/// [`PostFlopGame::run_it_n`].
+///
+/// The shares are fractions of the pot before the rake. Since each run has the same distribution
+/// of the board, the expected share does not depend on the number of runs, and running multiple
+/// times only reduces the standard deviation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunItResult {
+    /// Number of runs.
+    pub num_runs: usize,
+
+    /// Size of the pot.
+    pub pot: i32,
+
+    /// Expected share of the pot of each private hand of OOP and IP against the range of the
+    /// opponent. `0.0` if the hand conflicts with the whole range of the opponent.
+    pub equity: [Vec<f32>; 2],
+
+    /// Standard deviation of the share of the pot of each private hand of OOP and IP, taking both
+    /// the range of the opponent and the runouts into account.
+    pub std_dev: [Vec<f32>; 2],
+}
+
+impl PostFlopGame {
+    /// Computes the distribution of the share of the pot when the remaining board is run
+    /// `num_runs` times at the current all-in node.
+    ///
+    /// Each run deals the remaining board from the deck without the cards of the previous runs,
+    /// and each run awards `1 / num_runs` of the pot. The runouts are enumerated exactly, and the
+    /// range of each player is given by the normalized weights at the current node. The values
+    /// are independent of the solution, so the game need not be solved.
+    ///
+    /// Note that the game tree is always solved with a single run. This does not change the
+    /// chip EVs, which are linear in the share of the pot, but the EVs under ICM (see
+    /// [`ICMConfig`]) differ when running multiple times.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the current node is not a terminal node where both
+    /// players are all-in before the river or a split-pot evaluator is set (see
+    /// [`set_split_pot_evaluator`]), and [`SolverError::InvalidArgument`] if `num_runs` is zero or
+    /// the deck does not have enough cards for `num_runs` runs.
+    ///
+    /// The per-hand results before aggregating over the ranges are reused if an [`EquityCache`] is
+    /// set by [`set_equity_cache`].
+    ///
+    /// **Time complexity:** *O*(#(runouts) * #(OOP private hands) * #(IP private hands)).
+    ///
+    /// [`set_equity_cache`]: #method.set_equity_cache
+    /// [`set_split_pot_evaluator`]: #method.set_split_pot_evaluator
+    /// [`ICMConfig`]: crate::ICMConfig
+    pub fn run_it_n(&mut self, num_runs: usize) -> Result<RunItResult, SolverError> {
+        if self.state <= State::Uninitialized {
+            return Err(SolverError::InvalidState(
+                "Game is not successfully initialized".to_owned(),
+            ));
+        }
+        if num_runs == 0 {
+            return Err(SolverError::InvalidArgument(
+                "Number of runs must be positive".to_owned(),
+            ));
+        }
+        if self.split_pot_evaluator.is_some() {
+            return Err(SolverError::InvalidState(
+                "Split-pot games are not supported".to_owned(),
+            ));
+        }
+
+        let effective_stack = self.tree_config.effective_stack;
+        let board = self.current_board();
+        if self.node().amount != effective_stack || board.len() == 5 {
+            return Err(SolverError::InvalidState(
+                "Current node is not an all-in node before the river".to_owned(),
+            ));
+        }
+
+        self.cache_normalized_weights();
+
+        let cards_per_run = 5 - board.len();
+        let board_mask = board
+            .iter()
+            .fold(self.card_config.dead_mask(), |mask, &card| {
+                mask | (1 << card)
+            });
+        let deck = (0..52)
+            .filter(|&card| board_mask & (1 << card) == 0)
+            .collect::<Vec<Card>>();
+        if deck.len() < 4 + num_runs * cards_per_run {
+            return Err(SolverError::InvalidArgument(format!(
+                "Deck does not have enough cards for {num_runs} runs: {} cards",
+                deck.len()
+            )));
+        }
+
+        // all runouts of a single run
+        let mut runouts = Vec::new();
+        for (i, &card1) in deck.iter().enumerate() {
+            if cards_per_run == 1 {
+                runouts.push([card1, NOT_DEALT]);
+            } else {
+                runouts.extend(deck[i + 1..].iter().map(|&card2| [card1, card2]));
+            }
+        }
+
+        let runout_mask = |runout: &[Card; 2]| {
+            runout
+                .iter()
+                .filter(|&&card| card != NOT_DEALT)
+                .fold(0u64, |mask, &card| mask | (1 << card))
+        };
+
+        // strengths[player][runout * #(hands) + hand]
+        let evaluator = self.hand_evaluator();
+        let strengths = [0, 1].map(|player| {
+            let cards = &self.private_cards[player];
+            let mut ret = Vec::with_capacity(runouts.len() * cards.len());
+            for runout in &runouts {
+                let mut full_board = [NOT_DEALT; 5];
+                full_board[..board.len()].copy_from_slice(&board);
+                full_board[board.len()..].copy_from_slice(&runout[..cards_per_run]);
+                let mask = runout_mask(runout);
+                ret.extend(cards.iter().map(|&(c1, c2)| {
+                    if mask & ((1 << c1) | (1 << c2)) == 0 {
+                        evaluator.evaluate(&full_board, (c1, c2))
+                    } else {
+                        0
+                    }
+                }));
+            }
+            ret
+        });
+
+        let cards = &self.private_cards;
+        let weights = &self.normalized_weights;
+        let (num_oop, num_ip) = (cards[0].len(), cards[1].len());
+        let masks = runouts.iter().map(runout_mask).collect::<Vec<_>>();
+        let k = cards_per_run as f64;
+        let n_runs = num_runs as f64;
+
+        // (mean, second moment) of the share of OOP for each pair of hands; all pairs are
+        // computed when the result is cached, since the weights are not part of the key
+        let compute = |skip_zero_weights: bool| -> PairMoments {
+            into_par_iter(0..num_oop)
+                .map(|i| {
+                    let (c1, c2) = cards[0][i];
+                    let oop_mask: u64 = (1 << c1) | (1 << c2);
+                    let mut ret = vec![(0.0, 0.0); num_ip];
+
+                    for (j, &(c3, c4)) in cards[1].iter().enumerate() {
+                        let pair_mask = oop_mask | (1 << c3) | (1 << c4);
+                        if !cards_disjoint(cards, i, j)
+                            || (skip_zero_weights && weights[0][i] + weights[1][j] == 0.0)
+                        {
+                            continue;
+                        }
+
+                        let mut n = 0.0;
+                        let mut sum = 0.0;
+                        let mut sum_sq = 0.0;
+                        let mut count_by_card = [0.0; 52];
+                        let mut sum_by_card = [0.0; 52];
+
+                        for (r, runout) in runouts.iter().enumerate() {
+                            if masks[r] & pair_mask != 0 {
+                                continue;
+                            }
+                            let oop_strength = strengths[0][r * num_oop + i];
+                            let ip_strength = strengths[1][r * num_ip + j];
+                            let x = match oop_strength.cmp(&ip_strength) {
+                                Ordering::Greater => 1.0,
+                                Ordering::Equal => 0.5,
+                                Ordering::Less => 0.0,
+                            };
+                            n += 1.0;
+                            sum += x;
+                            sum_sq += x * x;
+                            for &card in &runout[..cards_per_run] {
+                                count_by_card[card as usize] += 1.0;
+                                sum_by_card[card as usize] += x;
+                            }
+                        }
+
+                        // ordered pairs of disjoint runouts (inclusion-exclusion principle)
+                        let square_sum = |v: &[f64; 52]| v.iter().map(|x| x * x).sum::<f64>();
+                        let num_pairs = n * n - square_sum(&count_by_card) + (k - 1.0) * n;
+                        let pair_sum = sum * sum - square_sum(&sum_by_card) + (k - 1.0) * sum_sq;
+
+                        let mean = sum / n;
+                        let variance_single = sum_sq / n - mean * mean;
+                        let variance = if num_runs == 1 {
+                            variance_single
+                        } else {
+                            let covariance = pair_sum / num_pairs - mean * mean;
+                            variance_single / n_runs + (n_runs - 1.0) / n_runs * covariance
+                        };
+                        ret[j] = (mean, variance + mean * mean);
+                    }
+
+                    ret
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let moments = match &self.equity_cache {
+            Some(cache) if self.hand_evaluator.is_none() => {
+                cache.get_or_compute(self, num_runs, || compute(false))
+            }
+            _ => Arc::new(compute(true)),
+        };
+
+        // aggregates over the range of the opponent
+        let mut equity = [vec![0.0; num_oop], vec![0.0; num_ip]];
+        let mut std_dev = [vec![0.0; num_oop], vec![0.0; num_ip]];
+        for player in 0..2 {
+            let num_hands = cards[player].len();
+            for hand in 0..num_hands {
+                let mut weight_sum = 0.0;
+                let mut mean_sum = 0.0;
+                let mut second_sum = 0.0;
+                for (opponent_hand, &w) in weights[player ^ 1].iter().enumerate() {
+                    let (i, j) = if player == 0 {
+                        (hand, opponent_hand)
+                    } else {
+                        (opponent_hand, hand)
+                    };
+                    if w == 0.0 || !cards_disjoint(cards, i, j) {
+                        continue;
+                    }
+                    let w = w as f64;
+                    let (mean, second) = moments[i][j];
+                    let (mean, second) = if player == 0 {
+                        (mean, second)
+                    } else {
+                        (1.0 - mean, 1.0 - 2.0 * mean + second)
+                    };
+                    weight_sum += w;
+                    mean_sum += w * mean;
+                    second_sum += w * second;
+                }
+
+                if weight_sum > 0.0 {
+                    let mean = mean_sum / weight_sum;
+                    let variance = (second_sum / weight_sum - mean * mean).max(0.0);
+                    equity[player][hand] = mean as f32;
+                    std_dev[player][hand] = variance.sqrt() as f32;
+                }
+            }
+        }
+
+        Ok(RunItResult {
+            num_runs,
+            pot: self.tree_config.starting_pot + 2 * effective_stack,
+            equity,
+            std_dev,
+        })
+    }
+}
+
+#[inline]
+fn cards_disjoint(cards: &[Vec<(Card, Card)>; 2], i: usize, j: usize) -> bool {
+    let (c1, c2) = cards[0][i];
+    let (c3, c4) = cards[1][j];
+    ((1u64 << c1) | (1 << c2) | (1 << c3) | (1 << c4)).count_ones() == 4
+}
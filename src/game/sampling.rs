@@ -0,0 +1,338 @@
+use super::*;
+use crate::interface::*;
+use crate::random::*;
+use fastrand::Rng;
+
+/// Result of [`PostFlopGame::rollout`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rollout {
+    /// Indices of the private hands of each player (OOP, IP).
+    pub hands: [usize; 2],
+
+    /// History from the root node (see [`PostFlopGame::apply_history`]) of the reached terminal
+    /// node.
+    pub history: Vec<usize>,
+
+    /// Actions and dealt cards from the starting node to the terminal node.
+    pub line: Vec<Action>,
+
+    /// Five-card board at the showdown, or the board at the time of the fold.
+    pub board: Vec<Card>,
+
+    /// Whether the hand ended with a fold.
+    pub is_fold: bool,
+
+    /// Payoff of each player (OOP, IP) measured at the starting node, following the convention of
+    /// [`PostFlopGame::expected_values`]: the share of the final pot received minus the chips put
+    /// into the pot after the starting node.
+    pub payoff: [f32; 2],
+}
+
+/// Samples an index from the given non-negative weights, or returns `None` if all are zero.
+fn sample_index<R: RandomSource + ?Sized>(
+    rng: &mut R,
+    weights: impl Iterator<Item = f64> + Clone,
+) -> Option<usize> {
+    let sum = weights.clone().sum::<f64>();
+    if sum.is_nan() || sum <= 0.0 {
+        return None;
+    }
+
+    let mut threshold = rng.next_f64() * sum;
+    let mut last = None;
+    for (i, w) in weights.enumerate() {
+        if w > 0.0 {
+            if threshold < w {
+                return Some(i);
+            }
+            threshold -= w;
+            last = Some(i);
+        }
+    }
+
+    // guards against rounding errors
+    last
+}
+
+/// Samples a card from the given bitmask uniformly at random.
+fn sample_card_from_mask<R: RandomSource + ?Sized>(rng: &mut R, mask: u64) -> Card {
+    let mut nth = rng.next_below(mask.count_ones() as usize);
+    let mut mask = mask;
+    loop {
+        let card = mask.trailing_zeros();
+        if nth == 0 {
+            return card as Card;
+        }
+        mask &= mask - 1;
+        nth -= 1;
+    }
+}
+
+impl PostFlopGame {
+    /// Samples an action of the current node according to the strategy.
+    ///
+    /// If `hand` is `Some`, the action is drawn from the strategy of the given private hand of the
+    /// current player. Otherwise, it is drawn from the overall action frequencies of the range
+    /// reaching the current node (see [`action_frequencies`]). The action is drawn with the random
+    /// number generator seeded with `seed` (see [`sample_action_with_rng`]). Returns the index of
+    /// the action.
+    ///
+    /// Panics if the memory is not yet allocated, the current node is a terminal node or a chance
+    /// node, or the range of the current player does not reach the current node. If `hand` is
+    /// `None`, the [`cache_normalized_weights`] method must be called after mutating the current
+    /// node.
+    ///
+    /// [`action_frequencies`]: #method.action_frequencies
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    /// [`sample_action_with_rng`]: #method.sample_action_with_rng
+    #[inline]
+    pub fn sample_action(&self, seed: u64, hand: Option<usize>) -> usize {
+        self.sample_action_with_rng(&mut Rng::with_seed(seed), hand)
+    }
+
+    /// Same as [`sample_action`], but draws the action from `rng`.
+    ///
+    /// [`sample_action`]: #method.sample_action
+    pub fn sample_action_with_rng<R: RandomSource + ?Sized>(
+        &self,
+        rng: &mut R,
+        hand: Option<usize>,
+    ) -> usize {
+        let index = match hand {
+            Some(hand) => {
+                assert!(
+                    self.state >= State::MemoryAllocated,
+                    "Memory is not allocated"
+                );
+                assert!(!self.is_terminal_node(), "Terminal node is not allowed");
+                assert!(!self.is_chance_node(), "Chance node is not allowed");
+                let num_hands = self.num_private_hands(self.current_player());
+                let strategy = self.strategy();
+                let num_actions = strategy.len() / num_hands;
+                sample_index(
+                    rng,
+                    (0..num_actions).map(|action| strategy[action * num_hands + hand] as f64),
+                )
+            }
+            None => {
+                let frequencies = self.action_frequencies();
+                sample_index(rng, frequencies.iter().map(|&f| f as f64))
+            }
+        };
+
+        index.expect("Current node is unreachable")
+    }
+
+    /// Samples a pair of private hands (OOP, IP) according to the normalized weights of the
+    /// current node, i.e., conditioned on reaching the current node.
+    ///
+    /// The hands are drawn with the random number generator seeded with `seed` (see
+    /// [`sample_hands_with_rng`]). Returns `None` if no pair of non-conflicting hands reaches the
+    /// current node.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// [`sample_hands_with_rng`]: #method.sample_hands_with_rng
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    #[inline]
+    pub fn sample_hands(&self, seed: u64) -> Option<[usize; 2]> {
+        self.sample_hands_with_rng(&mut Rng::with_seed(seed))
+    }
+
+    /// Same as [`sample_hands`], but draws the hands from `rng`.
+    ///
+    /// [`sample_hands`]: #method.sample_hands
+    pub fn sample_hands_with_rng<R: RandomSource + ?Sized>(
+        &self,
+        rng: &mut R,
+    ) -> Option<[usize; 2]> {
+        let weights = [0, 1].map(|player| self.normalized_weights(player));
+        let weights_of = |player: usize| weights[player].iter().map(|&w| w as f64);
+
+        // rejection sampling; the joint weight of a pair is the product of the two weights
+        const MAX_TRIALS: usize = 10000;
+        for _ in 0..MAX_TRIALS {
+            let oop = sample_index(rng, weights_of(0))?;
+            let ip = sample_index(rng, weights_of(1))?;
+            let (c1, c2) = self.private_cards[0][oop];
+            let (c3, c4) = self.private_cards[1][ip];
+            if c1 != c3 && c1 != c4 && c2 != c3 && c2 != c4 {
+                return Some([oop, ip]);
+            }
+        }
+
+        None
+    }
+
+    /// Plays out a hand from the current node until a terminal node is reached.
+    ///
+    /// The private hands are given by `hands` or sampled with [`sample_hands`] if `None`. Each
+    /// action is drawn from the strategy of the acting hand (see [`sample_action`]), and turn and
+    /// river cards are dealt uniformly at random from the cards that do not conflict with the
+    /// hands. If both players are all-in before the river, the remaining board is dealt for the
+    /// showdown. The payoff is computed in chips (ICM is not applied) with the rake taken into
+    /// account. All the random draws are made with the random number generator seeded with `seed`
+    /// (see [`rollout_with_rng`]), so the same `seed` always plays out the same hand.
+    ///
+    /// The current node is restored after the call.
+    ///
+    /// [`sample_hands`]: #method.sample_hands
+    /// [`sample_action`]: #method.sample_action
+    /// [`rollout_with_rng`]: #method.rollout_with_rng
+    #[inline]
+    pub fn rollout(
+        &mut self,
+        seed: u64,
+        hands: Option<[usize; 2]>,
+    ) -> Result<Rollout, SolverError> {
+        self.rollout_with_rng(&mut Rng::with_seed(seed), hands)
+    }
+
+    /// Same as [`rollout`], but makes all the random draws from `rng`.
+    ///
+    /// [`rollout`]: #method.rollout
+    pub fn rollout_with_rng<R: RandomSource + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        hands: Option<[usize; 2]>,
+    ) -> Result<Rollout, SolverError> {
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+
+        let original_history = self.history().to_vec();
+        let original_bet_amount = self.total_bet_amount();
+
+        let hands = match hands {
+            Some(hands) => hands,
+            None => {
+                self.cache_normalized_weights();
+                self.sample_hands_with_rng(rng).ok_or_else(|| {
+                    SolverError::InvalidState(
+                        "No pair of hands reaches the current node".to_owned(),
+                    )
+                })?
+            }
+        };
+
+        let mut hand_mask = 0u64;
+        for (player, &hand) in hands.iter().enumerate() {
+            let Some(&(c1, c2)) = self.private_cards[player].get(hand) else {
+                return Err(SolverError::InvalidArgument(format!(
+                    "Invalid hand index: {hand}"
+                )));
+            };
+            hand_mask |= (1 << c1) | (1 << c2);
+        }
+        if hand_mask.count_ones() != 4 {
+            return Err(SolverError::InvalidArgument(
+                "Private hands conflict with each other".to_owned(),
+            ));
+        }
+        let board_mask = self
+            .current_board()
+            .iter()
+            .fold(0u64, |acc, &c| acc | (1 << c));
+        if hand_mask & board_mask != 0 {
+            return Err(SolverError::InvalidArgument(
+                "Private hands conflict with the board".to_owned(),
+            ));
+        }
+
+        let mut line = Vec::new();
+        while !self.is_terminal_node() {
+            if self.is_chance_node() {
+                let cards = self.possible_cards() & !hand_mask;
+                if cards == 0 {
+                    self.apply_history(&original_history);
+                    return Err(SolverError::InvalidArgument(
+                        "No card can be dealt".to_owned(),
+                    ));
+                }
+                let card = sample_card_from_mask(rng, cards);
+                line.push(Action::Chance(card));
+                self.play(card as usize);
+            } else {
+                let hand = hands[self.current_player()];
+                let action = self.sample_action_with_rng(rng, Some(hand));
+                line.push(self.available_actions()[action]);
+                self.play(action);
+            }
+        }
+
+        let history = self.history().to_vec();
+        let total_bet_amount = self.total_bet_amount();
+        let mut board = self.current_board();
+        let pot = self.current_pot() as f64;
+        let folded_player = match line.last() {
+            Some(Action::Fold) => Some((self.node().player & PLAYER_MASK) as usize),
+            _ => None,
+        };
+        let street = match (folded_player, board.len()) {
+            (Some(_), 3) => BoardState::Flop,
+            (Some(_), 4) => BoardState::Turn,
+            _ => BoardState::River,
+        };
+        let rake = self.tree_config.rake(pot, street);
+        self.apply_history(&original_history);
+
+        let winner_share = (pot - rake) as f32;
+        let received = match folded_player {
+            Some(folded_player) => {
+                let mut payoff = [0.0; 2];
+                payoff[folded_player ^ 1] = winner_share;
+                payoff
+            }
+            None => {
+                // deals the remaining board when both players are all-in
+                let mut dead_mask = hand_mask | self.card_config.dead_mask();
+                dead_mask |= board.iter().fold(0u64, |acc, &c| acc | (1 << c));
+                while board.len() < 5 {
+                    let card = sample_card_from_mask(rng, !dead_mask & ((1 << 52) - 1));
+                    dead_mask |= 1 << card;
+                    board.push(card);
+                }
+
+                let full_board: [Card; 5] = board.as_slice().try_into().unwrap();
+                let hand_cards = [0, 1].map(|player| {
+                    let (c1, c2) = self.private_cards[player][hands[player]];
+                    [c1, c2]
+                });
+
+                if let Some(evaluator) = self.split_pot_evaluator() {
+                    let hand_cards = [&hand_cards[0][..], &hand_cards[1][..]];
+                    let share = hi_lo_share(evaluator, &full_board, hand_cards);
+                    [
+                        share as f32 * winner_share,
+                        (1.0 - share) as f32 * winner_share,
+                    ]
+                } else {
+                    let strength = hand_cards
+                        .map(|[c1, c2]| self.hand_evaluator().evaluate(&full_board, (c1, c2)));
+
+                    match strength[0].cmp(&strength[1]) {
+                        std::cmp::Ordering::Greater => [winner_share, 0.0],
+                        std::cmp::Ordering::Less => [0.0, winner_share],
+                        std::cmp::Ordering::Equal => [0.5 * winner_share; 2],
+                    }
+                }
+            }
+        };
+
+        let payoff = [0, 1].map(|player| {
+            received[player] - (total_bet_amount[player] - original_bet_amount[player]) as f32
+        });
+
+        Ok(Rollout {
+            hands,
+            history,
+            line,
+            board,
+            is_fold: folded_player.is_some(),
+            payoff,
+        })
+    }
+}
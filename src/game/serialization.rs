@@ -2,26 +2,23 @@ use super::*;
 
 use crate::interface::*;
 use crate::utility::*;
-use std::cell::Cell;
-use std::ptr;
+use crc32fast::Hasher;
+use std::cell::{Cell, RefCell};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::{array, fmt, ptr};
+
+#[cfg(all(feature = "mmap", unix))]
+use std::sync::Arc;
 
 use bincode::{
-    de::Decoder,
-    enc::Encoder,
+    config::Config,
+    de::{Decoder, DecoderImpl, read::Reader},
+    enc::{Encoder, EncoderImpl, write::Writer},
     error::{DecodeError, EncodeError},
 };
 
 impl PostFlopGame {
-    /// Returns the storage mode of this instance.
-    ///
-    /// The storage mode represents the deepest accessible node in the game tree.
-    /// For example, if the storage mode is `BoardState::Turn`, then the game tree
-    /// contains no information after the river deal.
-    #[inline]
-    pub fn storage_mode(&self) -> BoardState {
-        self.storage_mode
-    }
-
     /// Returns the target storage mode, which is used for serialization.
     #[inline]
     pub fn target_storage_mode(&self) -> BoardState {
@@ -30,28 +27,178 @@ impl PostFlopGame {
 
     /// Sets the target storage mode.
     #[inline]
-    pub fn set_target_storage_mode(&mut self, mode: BoardState) -> Result<(), String> {
+    pub fn set_target_storage_mode(&mut self, mode: BoardState) -> Result<(), SolverError> {
         if mode > self.storage_mode {
-            return Err("Cannot set target to a higher value than the current storage".to_owned());
+            return Err(SolverError::InvalidArgument(
+                "Cannot set target to a higher value than the current storage".to_owned(),
+            ));
         }
 
         if mode < self.tree_config.initial_state {
-            return Err("Cannot set target to a lower value than the initial state".to_owned());
+            return Err(SolverError::InvalidArgument(
+                "Cannot set target to a lower value than the initial state".to_owned(),
+            ));
         }
 
         self.target_storage_mode = mode;
         Ok(())
     }
 
+    /// Sets whether only the strategy is saved (default: `false`).
+    ///
+    /// A strategy-only file stores the cumulative strategies of the nodes up to the target storage
+    /// mode, dropping the cumulative regrets and the counterfactual values, so the file is much
+    /// smaller. A game loaded from it supports navigation and the queries of the strategy and the
+    /// weights, but not the equity and the expected values (it is reported as unsolved by
+    /// [`is_solved`]), and it cannot be solved further (see [`is_strategy_only`]).
+    ///
+    /// Returns an error if `enable` is `true` and the game is not solved, or if `enable` is `false`
+    /// and the game was loaded from a strategy-only file.
+    ///
+    /// [`is_solved`]: crate::Game::is_solved
+    /// [`is_strategy_only`]: #method.is_strategy_only
+    #[inline]
+    pub fn set_target_strategy_only(&mut self, enable: bool) -> Result<(), SolverError> {
+        if enable && self.state != State::Solved && !self.is_strategy_only {
+            return Err(SolverError::InvalidState(
+                "Strategy-only files can be saved only for solved games".to_owned(),
+            ));
+        }
+
+        if !enable && self.is_strategy_only {
+            return Err(SolverError::InvalidState(
+                "Game loaded from a strategy-only file has no regrets to save".to_owned(),
+            ));
+        }
+
+        self.target_strategy_only = enable;
+        Ok(())
+    }
+
+    /// Returns whether only the strategy is saved (see [`set_target_strategy_only`]).
+    ///
+    /// [`set_target_strategy_only`]: #method.set_target_strategy_only
+    #[inline]
+    pub fn target_strategy_only(&self) -> bool {
+        self.target_strategy_only
+    }
+
+    /// Returns whether the game was loaded from a strategy-only file (see
+    /// [`set_target_strategy_only`]).
+    ///
+    /// [`set_target_strategy_only`]: #method.set_target_strategy_only
+    #[inline]
+    pub fn is_strategy_only(&self) -> bool {
+        self.is_strategy_only
+    }
+
+    /// Returns the file from which the later streets can be loaded with [`load_later_streets`].
+    ///
+    /// [`load_later_streets`]: crate::load_later_streets
+    #[inline]
+    pub fn source_path(&self) -> Option<&Path> {
+        self.source_path.as_deref()
+    }
+
+    #[inline]
+    pub(crate) fn set_source_path(&mut self, path: PathBuf) {
+        self.source_path = Some(path);
+    }
+
+    /// Replaces `self` with `loaded`, which was loaded from the same file up to a later street,
+    /// keeping the current navigation state and the runtime settings.
+    pub(crate) fn replace_with_later_streets(&mut self, mut loaded: Self) {
+        loaded.table_cache = self.table_cache.take();
+        loaded.equity_cache = self.equity_cache.take();
+        if let Some(evaluator) = self.hand_evaluator.take() {
+            loaded.set_hand_evaluator(Some(evaluator)).unwrap();
+        }
+        if let Some(evaluator) = self.split_pot_evaluator.take() {
+            loaded.set_split_pot_evaluator(Some(evaluator)).unwrap();
+        }
+        loaded.icm_seed = self.icm_seed;
+        loaded.init_icm_calculator();
+        #[cfg(feature = "rayon")]
+        {
+            loaded.thread_pool = self.thread_pool.take();
+        }
+        #[cfg(all(feature = "numa", target_os = "linux"))]
+        {
+            loaded.numa_partitions = self.numa_partitions.take();
+            loaded.place_numa_partitions();
+        }
+
+        loaded.apply_history(&self.action_history);
+        if self.is_normalized_weight_cached {
+            loaded.cache_normalized_weights();
+        }
+        loaded.redo_history = std::mem::take(&mut self.redo_history);
+
+        *self = loaded;
+    }
+
+    /// Calls `f` with the game temporarily marked as a solved game to be saved as a strategy-only
+    /// file, which makes a snapshot of the average strategy in the middle of solving.
+    pub(crate) fn with_checkpoint_state<T>(&mut self, f: impl FnOnce(&Self) -> T) -> T {
+        let state = self.state;
+        let target_strategy_only = self.target_strategy_only;
+        self.state = State::Solved;
+        self.target_strategy_only = true;
+        let result = f(self);
+        self.state = state;
+        self.target_strategy_only = target_strategy_only;
+        result
+    }
+
+    /// Calls `f` with the game temporarily set up to save the whole storage of the strategy, which
+    /// makes a snapshot of the solver state together with [`solver_buffers`].
+    ///
+    /// [`solver_buffers`]: PostFlopGame::solver_buffers
+    pub(crate) fn with_resumable_state<T>(&mut self, f: impl FnOnce(&Self) -> T) -> T {
+        let target_storage_mode = self.target_storage_mode;
+        let target_strategy_only = self.target_strategy_only;
+        self.target_storage_mode = BoardState::River;
+        self.target_strategy_only = false;
+        let result = f(self);
+        self.target_storage_mode = target_storage_mode;
+        self.target_strategy_only = target_strategy_only;
+        result
+    }
+
+    /// Returns the buffers of the cumulative regrets and the counterfactual values, which are not
+    /// saved with a game in the middle of solving.
+    #[inline]
+    pub(crate) fn solver_buffers(&self) -> [&[u8]; 3] {
+        [&self.storage2, &self.storage_ip, &self.storage_chance]
+    }
+
+    /// Mutable version of [`solver_buffers`].
+    ///
+    /// [`solver_buffers`]: PostFlopGame::solver_buffers
+    #[inline]
+    pub(crate) fn solver_buffers_mut(&mut self) -> [&mut [u8]; 3] {
+        [
+            &mut self.storage2,
+            &mut self.storage_ip,
+            &mut self.storage_chance,
+        ]
+    }
+
     /// Returns the memory usage when the target storage mode is used for serialization.
     #[inline]
     pub fn target_memory_usage(&self) -> u64 {
+        if self.target_strategy_only {
+            return self.num_target_storage()[0] as u64 + self.misc_memory_usage;
+        }
+
         match self.target_storage_mode {
             BoardState::River => {
                 if !self.is_compression_enabled {
                     self.memory_usage().0
-                } else {
+                } else if !self.is_strategy_quantized {
                     self.memory_usage().1
+                } else {
+                    self.memory_usage_quantized()
                 }
             }
             _ => {
@@ -68,9 +215,10 @@ impl PostFlopGame {
         }
 
         let num_bytes = if self.is_compression_enabled { 2 } else { 4 };
-        if self.target_storage_mode == BoardState::River {
+        let strategy_num_bytes = self.strategy_num_bytes();
+        if self.target_storage_mode == BoardState::River || self.is_strategy_only {
             // omit storing the counterfactual values
-            return [num_bytes * self.num_storage as usize, 0, 0, 0];
+            return [strategy_num_bytes * self.num_storage as usize, 0, 0, 0];
         }
 
         let mut node_index = match self.target_storage_mode {
@@ -85,11 +233,13 @@ impl PostFlopGame {
             let node = self.node_arena[node_index].lock();
             if num_storage[0] == 0 && !node.is_terminal() && !node.is_chance() {
                 let offset = unsafe { node.storage1.offset_from(self.storage1.as_ptr()) };
+                let offset2 = unsafe { node.storage2.offset_from(self.storage2.as_ptr()) };
                 let offset_ip = unsafe { node.storage3.offset_from(self.storage_ip.as_ptr()) };
                 let len = num_bytes * node.num_elements as usize;
+                let len_strategy = strategy_num_bytes * node.num_elements as usize;
                 let len_ip = num_bytes * node.num_elements_ip as usize;
-                num_storage[0] = offset as usize + len;
-                num_storage[1] = offset as usize + len;
+                num_storage[0] = offset as usize + len_strategy;
+                num_storage[1] = offset2 as usize + len;
                 num_storage[2] = offset_ip as usize + len_ip;
             }
             if num_storage[3] == 0 && node.is_chance() {
@@ -99,17 +249,611 @@ impl PostFlopGame {
             }
         }
 
+        if self.target_strategy_only {
+            num_storage[1..].fill(0);
+        }
+
         num_storage
     }
+
+    /// Truncates a game being decoded to the nodes up to `street`.
+    fn truncate_decoded_streets(&mut self, street: BoardState, file_storage_mode: BoardState) {
+        let num_nodes = self.num_nodes_up_to(street);
+        self.locking_strategy.retain(|&i, _| i < num_nodes);
+        self.storage_mode = street;
+        self.target_storage_mode = street;
+        // the counterfactual values of the earlier streets are not saved with the river
+        self.is_strategy_only |= file_storage_mode == BoardState::River;
+        self.target_strategy_only = self.is_strategy_only;
+    }
+
+    /// Returns the ends of the flop and turn nodes in the node arena.
+    #[inline]
+    fn street_node_ends(&self) -> [usize; 2] {
+        [
+            self.num_nodes_up_to(BoardState::Flop),
+            self.num_nodes_up_to(BoardState::Turn),
+        ]
+    }
+
+    /// Decodes a game from `data`, a part of `mapping`, without copying the storage buffers.
+    #[cfg(all(feature = "mmap", unix))]
+    pub(crate) fn decode_from_mapping(
+        mapping: &Arc<FileMapping>,
+        data: &[u8],
+    ) -> Result<Self, String> {
+        MAPPING.with(|c| *c.borrow_mut() = Some(Arc::clone(mapping)));
+        let result = bincode::decode_from_slice(data, bincode::config::standard());
+        MAPPING.with(|c| *c.borrow_mut() = None);
+        result
+            .map(|(game, _)| game)
+            .map_err(|e| format!("Failed to read data: {e}"))
+    }
 }
 
-static VERSION_STR: &str = "2023-03-19";
+/// Layout of a version of the save format of [`PostFlopGame`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FormatVersion {
+    name: &'static str,
+    /// Whether the version is the first one, which has no checksums, no alignment of the storage
+    /// buffers, and none of the fields added since.
+    is_legacy: bool,
+}
+
+/// Supported versions of the save format, from the oldest to the current one. A new entry must be
+/// added whenever the format changes, and the decoder must handle every entry.
+const FORMAT_VERSIONS: [FormatVersion; 2] = [
+    FormatVersion {
+        name: "2023-03-19",
+        is_legacy: true,
+    },
+    FormatVersion {
+        name: "2026-10-27",
+        is_legacy: false,
+    },
+];
+
+const CURRENT_VERSION: FormatVersion = FORMAT_VERSIONS[FORMAT_VERSIONS.len() - 1];
+
+/// The current version of the save format of [`PostFlopGame`].
+///
+/// Files saved in the older versions listed in [`SUPPORTED_SAVE_FORMAT_VERSIONS`] can still be
+/// loaded.
+pub const SAVE_FORMAT_VERSION: &str = CURRENT_VERSION.name;
+
+/// The versions of the save format of [`PostFlopGame`] that can be loaded, from the oldest to the
+/// current one.
+pub const SUPPORTED_SAVE_FORMAT_VERSIONS: [&str; FORMAT_VERSIONS.len()] = {
+    let mut names = [""; FORMAT_VERSIONS.len()];
+    let mut i = 0;
+    while i < FORMAT_VERSIONS.len() {
+        names[i] = FORMAT_VERSIONS[i].name;
+        i += 1;
+    }
+    names
+};
+
+impl FormatVersion {
+    /// Finds the version named `name`, or returns an error describing how to migrate the file.
+    fn find(name: &str) -> Result<Self, String> {
+        if let Some(version) = FORMAT_VERSIONS.iter().find(|v| v.name == name) {
+            return Ok(*version);
+        }
+
+        let supported = SUPPORTED_SAVE_FORMAT_VERSIONS.join("', '");
+        if name > CURRENT_VERSION.name {
+            Err(format!(
+                "Unsupported save format version '{name}' (supported: '{supported}'): the file \
+                 was saved by a newer version of the crate, so update the crate to load it"
+            ))
+        } else {
+            Err(format!(
+                "Unsupported save format version '{name}' (supported: '{supported}'): the file \
+                 was saved by a development version of the crate, so load it with that version \
+                 and save the game again, or solve the game again"
+            ))
+        }
+    }
+}
+
+/// The tree structure of a [`PostFlopGame`]: the tree configuration and the action tree.
+///
+/// A flop-set database solves many boards with the same tree structure. The structure can be
+/// saved once with `save_data_to_file` and the games referring to it with
+/// `save_game_to_file_with_structure`, which omits the structure from the game files. The games
+/// loaded with the structure share its action tree as with [`PostFlopGame::with_shared_tree`].
+#[derive(Clone)]
+pub struct TreeStructure {
+    tree_config: TreeConfig,
+    added_lines: Vec<Vec<Action>>,
+    removed_lines: Vec<Vec<Action>>,
+    action_nodes: Arc<ActionTreeArena>,
+    id: u32,
+    num_bytes: u64,
+}
+
+impl TreeStructure {
+    /// Creates the tree structure of `game`.
+    ///
+    /// Returns [`SolverError::InvalidState`] if `game` is not successfully initialized.
+    pub fn from_game(game: &PostFlopGame) -> Result<Self, SolverError> {
+        if game.state <= State::Uninitialized {
+            return Err(SolverError::InvalidState(
+                "Game is not successfully initialized".to_owned(),
+            ));
+        }
+
+        let mut ret = Self {
+            tree_config: game.tree_config.clone(),
+            added_lines: game.added_lines.clone(),
+            removed_lines: game.removed_lines.clone(),
+            action_nodes: Arc::clone(&game.action_nodes),
+            id: 0,
+            num_bytes: 0,
+        };
+
+        ret.compute_id()?;
+        Ok(ret)
+    }
+
+    /// Returns the tree configuration.
+    #[inline]
+    pub fn tree_config(&self) -> &TreeConfig {
+        &self.tree_config
+    }
+
+    /// Returns the ID of the structure, which is the checksum of its contents.
+    #[inline]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns the estimated memory usage of the structure in bytes.
+    #[inline]
+    pub(crate) fn estimated_memory_usage(&self) -> u64 {
+        self.num_bytes
+    }
+
+    /// Computes the ID and the size of the encoded contents.
+    fn compute_id(&mut self) -> Result<(), SolverError> {
+        let contents = (
+            &self.tree_config,
+            &self.added_lines,
+            &self.removed_lines,
+            &self.action_nodes,
+        );
+        let bytes = bincode::encode_to_vec(contents, bincode::config::standard())
+            .map_err(|e| SolverError::Other(format!("Failed to encode tree structure: {e}")))?;
+        self.id = crc32fast::hash(&bytes);
+        self.num_bytes = bytes.len() as u64;
+        Ok(())
+    }
+}
+
+impl Encode for TreeStructure {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        self.tree_config.encode(encoder)?;
+        self.added_lines.encode(encoder)?;
+        self.removed_lines.encode(encoder)?;
+        self.action_nodes.encode(encoder)
+    }
+}
+
+impl Decode<()> for TreeStructure {
+    fn decode<D: Decoder<Context = ()>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        let mut ret = Self {
+            tree_config: Decode::decode(decoder)?,
+            added_lines: Decode::decode(decoder)?,
+            removed_lines: Decode::decode(decoder)?,
+            action_nodes: Decode::decode(decoder)?,
+            id: 0,
+            num_bytes: 0,
+        };
+        ret.compute_id()
+            .map_err(|e| DecodeError::OtherString(e.to_string()))?;
+        Ok(ret)
+    }
+}
+
+/// A section of a saved [`PostFlopGame`] protected by a checksum.
+///
+/// The storage buffers and the nodes are divided by street, so that the sections of the earlier
+/// streets can be loaded even if a later street is corrupt (see `load_game_from_file_recovering`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveSection {
+    /// Configurations, action tree, and sizes of the storage buffers.
+    Config,
+
+    /// Strategy of the nodes of the street.
+    Strategy(BoardState),
+
+    /// Regrets or counterfactual values of the nodes of the street.
+    Values(BoardState),
+
+    /// Counterfactual values of the IP player of the nodes of the street.
+    ValuesIp(BoardState),
+
+    /// Counterfactual values of the chance nodes of the street.
+    ChanceValues(BoardState),
+
+    /// Locked strategies.
+    LockingStrategy,
+
+    /// Nodes of the street.
+    Tree(BoardState),
+}
+
+impl fmt::Display for SaveSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let street_name = |street: &BoardState| match street {
+            BoardState::Flop => "flop",
+            BoardState::Turn => "turn",
+            BoardState::River => "river",
+        };
+
+        match self {
+            Self::Config => write!(f, "configuration"),
+            Self::Strategy(street) => write!(f, "strategy of the {}", street_name(street)),
+            Self::Values(street) => write!(f, "values of the {}", street_name(street)),
+            Self::ValuesIp(street) => write!(f, "IP values of the {}", street_name(street)),
+            Self::ChanceValues(street) => {
+                write!(f, "chance values of the {}", street_name(street))
+            }
+            Self::LockingStrategy => write!(f, "locking strategy"),
+            Self::Tree(street) => write!(f, "nodes of the {}", street_name(street)),
+        }
+    }
+}
 
 thread_local! {
     static PTR_BASE: Cell<[*const u8; 2]> = const { Cell::new([ptr::null(); 2]) };
     static CHANCE_BASE: Cell<*const u8> = const { Cell::new(ptr::null()) };
     static PTR_BASE_MUT: Cell<[*mut u8; 3]> = const { Cell::new([ptr::null_mut(); 3]) };
     static CHANCE_BASE_MUT: Cell<*mut u8> = const { Cell::new(ptr::null_mut()) };
+    static STORAGE2_RATIO: Cell<isize> = const { Cell::new(1) };
+    static STREAM_OFFSET: Cell<usize> = const { Cell::new(0) };
+    static DECODE_STREET: Cell<BoardState> = const { Cell::new(BoardState::River) };
+    static HAS_CFVALUE_OFFSETS: Cell<bool> = const { Cell::new(true) };
+    static CORRUPT_SECTIONS: RefCell<Option<Vec<SaveSection>>> = const { RefCell::new(None) };
+    static ENCODE_STRUCTURE_ID: Cell<Option<u32>> = const { Cell::new(None) };
+    static DECODE_STRUCTURE: RefCell<Option<TreeStructure>> = const { RefCell::new(None) };
+}
+
+#[cfg(all(feature = "mmap", unix))]
+thread_local! {
+    static MAPPING: RefCell<Option<Arc<FileMapping>>> = const { RefCell::new(None) };
+}
+
+/// Sets the position in the output stream at which the next game is encoded. The storage buffers
+/// are aligned relative to the beginning of the stream.
+pub(crate) fn set_stream_offset(offset: usize) {
+    STREAM_OFFSET.with(|c| c.set(offset));
+}
+
+/// Calls `f` with the games decoded on the current thread truncated to the nodes up to `street`.
+pub(crate) fn with_decode_street<T>(street: BoardState, f: impl FnOnce() -> T) -> T {
+    DECODE_STREET.with(|c| c.set(street));
+    let result = f();
+    DECODE_STREET.with(|c| c.set(BoardState::River));
+    result
+}
+
+/// Calls `f` with the games encoded on the current thread referring to the tree structure with
+/// the ID `id` instead of containing it.
+pub(crate) fn with_encode_structure<T>(id: u32, f: impl FnOnce() -> T) -> T {
+    ENCODE_STRUCTURE_ID.with(|c| c.set(Some(id)));
+    let result = f();
+    ENCODE_STRUCTURE_ID.with(|c| c.set(None));
+    result
+}
+
+/// Calls `f` with the games decoded on the current thread taking their tree structure from
+/// `structure` if they refer to it.
+pub(crate) fn with_decode_structure<T>(structure: &TreeStructure, f: impl FnOnce() -> T) -> T {
+    DECODE_STRUCTURE.with(|c| *c.borrow_mut() = Some(structure.clone()));
+    let result = f();
+    DECODE_STRUCTURE.with(|c| *c.borrow_mut() = None);
+    result
+}
+
+/// Calls `f` with the games decoded on the current thread tolerating corrupt sections, and returns
+/// the result of `f` and the corrupt sections found.
+///
+/// Instead of failing, the decoder drops the corrupt locking strategy and truncates the game to the
+/// streets before the earliest corrupt section. A corrupt configuration is still an error.
+pub(crate) fn with_corruption_report<T>(f: impl FnOnce() -> T) -> (T, Vec<SaveSection>) {
+    CORRUPT_SECTIONS.with(|c| *c.borrow_mut() = Some(Vec::new()));
+    let result = f();
+    let sections = CORRUPT_SECTIONS.with(|c| c.borrow_mut().take().unwrap());
+    (result, sections)
+}
+
+/// Reports that the checksum of `section` does not match. Returns an error unless the corruption
+/// is tolerated (see [`with_corruption_report`]).
+fn report_corruption(section: SaveSection) -> Result<(), DecodeError> {
+    CORRUPT_SECTIONS.with(|c| match c.borrow_mut().as_mut() {
+        Some(sections) if section != SaveSection::Config => {
+            sections.push(section);
+            Ok(())
+        }
+        _ => Err(DecodeError::OtherString(format!(
+            "Checksum mismatch in the {section}"
+        ))),
+    })
+}
+
+/// Returns the latest street that can be loaded when the section of `corrupt_street` is corrupt.
+fn street_before(
+    corrupt_street: BoardState,
+    street: BoardState,
+    initial_state: BoardState,
+) -> Result<BoardState, DecodeError> {
+    let prev = match corrupt_street {
+        BoardState::Flop => None,
+        BoardState::Turn => Some(BoardState::Flop),
+        BoardState::River => Some(BoardState::Turn),
+    };
+    match prev {
+        Some(prev) if prev >= initial_state => Ok(prev.min(street)),
+        _ => Err(DecodeError::Other(
+            "Nodes of the initial street are corrupt",
+        )),
+    }
+}
+
+/// Returns the ranges of a storage buffer of `len` bytes used by the flop, turn, and river nodes,
+/// where `ends` are the ends of the flop and turn parts.
+fn street_ranges(len: usize, ends: [usize; 2]) -> [(BoardState, Range<usize>); 3] {
+    let flop_end = ends[0].min(len);
+    let turn_end = ends[1].clamp(flop_end, len);
+    [
+        (BoardState::Flop, 0..flop_end),
+        (BoardState::Turn, flop_end..turn_end),
+        (BoardState::River, turn_end..len),
+    ]
+}
+
+/// A writer that tracks the position in the output stream and computes the checksum of the
+/// current section.
+struct PositionWriter<W: Writer> {
+    writer: W,
+    position: usize,
+    hasher: Option<Hasher>,
+}
+
+impl<W: Writer> Writer for PositionWriter<W> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+        self.position += bytes.len();
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(bytes);
+        }
+        self.writer.write(bytes)
+    }
+}
+
+/// A reader that computes the checksum of the current section.
+struct ChecksumReader<R: Reader> {
+    reader: R,
+    hasher: Option<Hasher>,
+}
+
+impl<R: Reader> Reader for ChecksumReader<R> {
+    #[inline]
+    fn read(&mut self, bytes: &mut [u8]) -> Result<(), DecodeError> {
+        self.reader.read(bytes)?;
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(bytes);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn peek_read(&mut self, n: usize) -> Option<&[u8]> {
+        self.reader.peek_read(n)
+    }
+
+    #[inline]
+    fn consume(&mut self, n: usize) {
+        if let Some(hasher) = &mut self.hasher
+            && let Some(bytes) = self.reader.peek_read(n)
+        {
+            hasher.update(bytes);
+        }
+        self.reader.consume(n);
+    }
+}
+
+/// Starts a section protected by a checksum.
+#[inline]
+fn begin_encode_section<W: Writer, C: Config>(encoder: &mut EncoderImpl<PositionWriter<W>, C>) {
+    encoder.writer().hasher = Some(Hasher::new());
+}
+
+/// Ends the current section by writing its checksum.
+fn end_encode_section<W: Writer, C: Config>(
+    encoder: &mut EncoderImpl<PositionWriter<W>, C>,
+) -> Result<(), EncodeError> {
+    let hasher = encoder.writer().hasher.take().unwrap();
+    encoder.writer().write(&hasher.finalize().to_le_bytes())
+}
+
+/// Starts a section protected by a checksum.
+#[inline]
+fn begin_decode_section<R: Reader, C: Config>(
+    decoder: &mut DecoderImpl<ChecksumReader<R>, C, ()>,
+    version: FormatVersion,
+) {
+    if !version.is_legacy {
+        decoder.reader().hasher = Some(Hasher::new());
+    }
+}
+
+/// Ends the current section by verifying its checksum. Returns whether the section is intact.
+fn end_decode_section<R: Reader, C: Config>(
+    decoder: &mut DecoderImpl<ChecksumReader<R>, C, ()>,
+    version: FormatVersion,
+    section: SaveSection,
+) -> Result<bool, DecodeError> {
+    if version.is_legacy {
+        return Ok(true);
+    }
+    let checksum = decoder.reader().hasher.take().unwrap().finalize();
+    let mut expected = [0; 4];
+    decoder.reader().read(&mut expected)?;
+    if checksum == u32::from_le_bytes(expected) {
+        return Ok(true);
+    }
+    report_corruption(section)?;
+    Ok(false)
+}
+
+/// Encodes the checksums of the flop, turn, and river parts of a storage buffer.
+fn encode_street_checksums<W: Writer, C: Config>(
+    encoder: &mut EncoderImpl<PositionWriter<W>, C>,
+    data: &[u8],
+    ends: [usize; 2],
+) -> Result<(), EncodeError> {
+    for (_, range) in street_ranges(data.len(), ends) {
+        let checksum = crc32fast::hash(&data[range]);
+        encoder.writer().write(&checksum.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Verifies the checksums of the parts of a storage buffer up to `street`, and returns the
+/// earliest street whose part is corrupt.
+///
+/// The buffers referring to a mapped file are not verified so that the pages are not read.
+fn decode_street_checksums<D: Decoder>(
+    decoder: &mut D,
+    storage: &Storage,
+    ends: [usize; 2],
+    street: BoardState,
+    version: FormatVersion,
+    section: fn(BoardState) -> SaveSection,
+) -> Result<Option<BoardState>, DecodeError> {
+    if version.is_legacy {
+        return Ok(None);
+    }
+    let mut corrupt_street = None;
+    for (part_street, range) in street_ranges(storage.len(), ends) {
+        let mut expected = [0; 4];
+        decoder.reader().read(&mut expected)?;
+        if part_street > street || storage.is_on_disk() {
+            continue;
+        }
+        if crc32fast::hash(&storage[range]) != u32::from_le_bytes(expected) {
+            report_corruption(section(part_street))?;
+            corrupt_street.get_or_insert(part_street);
+        }
+    }
+    Ok(corrupt_street)
+}
+
+/// Decodes a [`TreeConfig`] in the layout of `version`.
+fn decode_tree_config<D: Decoder<Context = ()>>(
+    decoder: &mut D,
+    version: FormatVersion,
+) -> Result<TreeConfig, DecodeError> {
+    if !version.is_legacy {
+        return Decode::decode(decoder);
+    }
+    Ok(TreeConfig {
+        initial_state: Decode::decode(decoder)?,
+        starting_pot: Decode::decode(decoder)?,
+        effective_stack: Decode::decode(decoder)?,
+        rake_rate: Decode::decode(decoder)?,
+        rake_cap: Decode::decode(decoder)?,
+        flop_bet_sizes: Decode::decode(decoder)?,
+        turn_bet_sizes: Decode::decode(decoder)?,
+        river_bet_sizes: Decode::decode(decoder)?,
+        turn_donk_sizes: Decode::decode(decoder)?,
+        river_donk_sizes: Decode::decode(decoder)?,
+        add_allin_threshold: Decode::decode(decoder)?,
+        force_allin_threshold: Decode::decode(decoder)?,
+        merging_threshold: Decode::decode(decoder)?,
+        icm_config: Decode::decode(decoder)?,
+        rake_config: None,
+        sizing_rules: Vec::new(),
+        bomb_pot: None,
+    })
+}
+
+/// Encodes a storage buffer so that its contents start at a multiple of [`STORAGE_ALIGNMENT`]
+/// bytes in the output stream, allowing them to be memory-mapped in place.
+fn encode_storage<W: Writer, C: Config>(
+    encoder: &mut EncoderImpl<PositionWriter<W>, C>,
+    data: &[u8],
+) -> Result<(), EncodeError> {
+    (data.len() as u64).encode(encoder)?;
+    let position = encoder.writer().position + 1;
+    let padding = position.next_multiple_of(STORAGE_ALIGNMENT) - position;
+    (padding as u8).encode(encoder)?;
+    encoder.writer().write(&[0; STORAGE_ALIGNMENT][..padding])?;
+    encoder.writer().write(data)
+}
+
+/// Decodes the first `limit` bytes of a storage buffer whose elements are `align` bytes, skipping
+/// the rest.
+///
+/// When decoding from a mapped file, the buffer refers to the mapping instead of being copied,
+/// provided that it is properly aligned in the file.
+#[cfg_attr(not(all(feature = "mmap", unix)), allow(unused_variables))]
+fn decode_storage<D: Decoder<Context = ()>>(
+    decoder: &mut D,
+    align: usize,
+    version: FormatVersion,
+    limit: usize,
+) -> Result<Storage, DecodeError> {
+    let len = u64::decode(decoder)? as usize;
+    let keep = len.min(limit);
+    let padding = if version.is_legacy {
+        0
+    } else {
+        u8::decode(decoder)? as usize
+    };
+    if padding >= STORAGE_ALIGNMENT {
+        return Err(DecodeError::Other("Invalid storage padding"));
+    }
+    decoder.claim_bytes_read(padding + len)?;
+
+    #[cfg(all(feature = "mmap", unix))]
+    if let Some(mapping) = MAPPING.with(|c| c.borrow().clone()) {
+        let Some(data) = decoder.reader().peek_read(padding + len) else {
+            return Err(DecodeError::Other("Storage is out of the mapping"));
+        };
+
+        let data = &data[padding..];
+        let base = mapping.as_ptr() as usize;
+        let offset = (data.as_ptr() as usize).wrapping_sub(base);
+        let storage = if offset + len <= mapping.len() && offset.is_multiple_of(align) {
+            Storage::mapped(&mapping, offset, keep)
+        } else {
+            let mut storage = Storage::zeroed(keep);
+            storage.copy_from_slice(&data[..keep]);
+            storage
+        };
+
+        decoder.reader().consume(padding + len);
+        return Ok(storage);
+    }
+
+    decoder
+        .reader()
+        .read(&mut [0; STORAGE_ALIGNMENT][..padding])?;
+    let mut storage = Storage::zeroed(keep);
+    decoder.reader().read(&mut storage)?;
+
+    let mut skip = len - keep;
+    let mut buf = [0; 4096];
+    while skip > 0 {
+        let chunk = skip.min(buf.len());
+        decoder.reader().read(&mut buf[..chunk])?;
+        skip -= chunk;
+    }
+
+    Ok(storage)
 }
 
 impl Encode for PostFlopGame {
@@ -120,27 +864,67 @@ impl Encode for PostFlopGame {
 
         let num_storage = self.num_target_storage();
 
+        // track the position to align the storage buffers
+        let config = *encoder.config();
+        let position = STREAM_OFFSET.with(|c| c.get());
+        let writer = PositionWriter {
+            writer: encoder.writer(),
+            position,
+            hasher: None,
+        };
+        let encoder = &mut EncoderImpl::new(writer, config);
+
         // version
-        VERSION_STR.to_owned().encode(encoder)?;
+        CURRENT_VERSION.name.to_owned().encode(encoder)?;
 
         // contents
+        begin_encode_section(encoder);
         self.state.encode(encoder)?;
         self.card_config.encode(encoder)?;
-        self.tree_config.encode(encoder)?;
-        self.added_lines.encode(encoder)?;
-        self.removed_lines.encode(encoder)?;
-        self.action_root.encode(encoder)?;
+        let structure_id = ENCODE_STRUCTURE_ID.with(|c| c.get());
+        structure_id.is_some().encode(encoder)?;
+        if let Some(id) = structure_id {
+            id.encode(encoder)?;
+        } else {
+            self.tree_config.encode(encoder)?;
+            self.added_lines.encode(encoder)?;
+            self.removed_lines.encode(encoder)?;
+            self.action_nodes.encode(encoder)?;
+        }
+        self.isomorphism_mode.encode(encoder)?;
         self.target_storage_mode.encode(encoder)?;
         self.num_nodes.encode(encoder)?;
         self.is_compression_enabled.encode(encoder)?;
+        self.is_strategy_quantized.encode(encoder)?;
+        self.target_strategy_only.encode(encoder)?;
+        self.icm_seed.encode(encoder)?;
         self.num_storage.encode(encoder)?;
         self.num_storage_ip.encode(encoder)?;
         self.num_storage_chance.encode(encoder)?;
         self.misc_memory_usage.encode(encoder)?;
-        self.storage1[0..num_storage[0]].encode(encoder)?;
-        self.storage2[0..num_storage[1]].encode(encoder)?;
-        self.storage_ip[0..num_storage[2]].encode(encoder)?;
-        self.storage_chance[0..num_storage[3]].encode(encoder)?;
+        // end of the storage of each street, which allows loading the earlier streets only
+        let street_storage = [BoardState::Flop, BoardState::Turn].map(|street| {
+            if street >= self.target_storage_mode || self.state <= State::TreeBuilt {
+                return num_storage;
+            }
+            let sizes = self.storage_bytes_up_to(street);
+            array::from_fn::<_, 4, _>(|i| sizes[i].min(num_storage[i]))
+        });
+        street_storage.encode(encoder)?;
+        end_encode_section(encoder)?;
+
+        let storages = [
+            &self.storage1,
+            &self.storage2,
+            &self.storage_ip,
+            &self.storage_chance,
+        ];
+        for (i, storage) in storages.into_iter().enumerate() {
+            let data = &storage[0..num_storage[i]];
+            let ends = [street_storage[0][i], street_storage[1][i]];
+            encode_storage(encoder, data)?;
+            encode_street_checksums(encoder, data, ends)?;
+        }
 
         let num_nodes = match self.target_storage_mode {
             BoardState::Flop => self.num_nodes[0] as usize,
@@ -151,11 +935,15 @@ impl Encode for PostFlopGame {
         // locking strategy (need to filter)
         let mut locking_strategy = self.locking_strategy.clone();
         locking_strategy.retain(|&i, _| i < num_nodes);
+        begin_encode_section(encoder);
         locking_strategy.encode(encoder)?;
+        end_encode_section(encoder)?;
 
         // store base pointers
         PTR_BASE.with(|c| {
-            if self.state >= State::MemoryAllocated {
+            if self.state >= State::MemoryAllocated && self.target_strategy_only {
+                c.set([self.storage1.as_ptr(), ptr::null()]);
+            } else if self.state >= State::MemoryAllocated {
                 c.set([self.storage1.as_ptr(), self.storage_ip.as_ptr()]);
             } else {
                 c.set([ptr::null(); 2]);
@@ -163,7 +951,7 @@ impl Encode for PostFlopGame {
         });
 
         CHANCE_BASE.with(|c| {
-            if self.state >= State::MemoryAllocated {
+            if self.state >= State::MemoryAllocated && !self.target_strategy_only {
                 c.set(self.storage_chance.as_ptr());
             } else {
                 c.set(ptr::null());
@@ -171,7 +959,17 @@ impl Encode for PostFlopGame {
         });
 
         // game tree
-        self.node_arena[0..num_nodes].encode(encoder)?;
+        (num_nodes as u64).encode(encoder)?;
+        for (_, range) in street_ranges(num_nodes, self.street_node_ends()) {
+            if range.start > 0 && range.start == num_nodes {
+                break;
+            }
+            begin_encode_section(encoder);
+            for node in &self.node_arena[range] {
+                node.encode(encoder)?;
+            }
+            end_encode_section(encoder)?;
+        }
 
         Ok(())
     }
@@ -181,46 +979,177 @@ impl Decode<()> for PostFlopGame {
     fn decode<D: Decoder<Context = ()>>(decoder: &mut D) -> Result<Self, DecodeError> {
         // version check
         let version = String::decode(decoder)?;
-        if version != VERSION_STR {
+        let version = FormatVersion::find(&version).map_err(DecodeError::OtherString)?;
+
+        // compute the checksums of the sections while reading
+        let config = *decoder.config();
+        let reader = ChecksumReader {
+            reader: decoder.reader(),
+            hasher: None,
+        };
+        let decoder = &mut DecoderImpl::new(reader, config, ());
+
+        // game instance
+        begin_decode_section(decoder, version);
+        let state = Decode::decode(decoder)?;
+        let card_config = if version.is_legacy {
+            CardConfig {
+                range: Decode::decode(decoder)?,
+                flop: Decode::decode(decoder)?,
+                turn: Decode::decode(decoder)?,
+                river: Decode::decode(decoder)?,
+                ..Default::default()
+            }
+        } else {
+            Decode::decode(decoder)?
+        };
+
+        let is_structure_shared = !version.is_legacy && bool::decode(decoder)?;
+        let mut game = if is_structure_shared {
+            let id = u32::decode(decoder)?;
+            let structure = DECODE_STRUCTURE.with(|c| c.borrow().clone());
+            let Some(structure) = structure else {
+                return Err(DecodeError::Other(
+                    "Game refers to a tree structure saved separately",
+                ));
+            };
+            if structure.id != id {
+                return Err(DecodeError::Other(
+                    "Game refers to a different tree structure",
+                ));
+            }
+            Self {
+                state,
+                card_config,
+                tree_config: structure.tree_config,
+                added_lines: structure.added_lines,
+                removed_lines: structure.removed_lines,
+                action_nodes: structure.action_nodes,
+                ..Default::default()
+            }
+        } else {
+            Self {
+                state,
+                card_config,
+                tree_config: decode_tree_config(decoder, version)?,
+                added_lines: Decode::decode(decoder)?,
+                removed_lines: Decode::decode(decoder)?,
+                action_nodes: Decode::decode(decoder)?,
+                ..Default::default()
+            }
+        };
+
+        if !version.is_legacy {
+            game.isomorphism_mode = Decode::decode(decoder)?;
+        }
+        game.storage_mode = Decode::decode(decoder)?;
+        game.num_nodes = Decode::decode(decoder)?;
+        game.is_compression_enabled = Decode::decode(decoder)?;
+        if !version.is_legacy {
+            game.is_strategy_quantized = Decode::decode(decoder)?;
+            game.is_strategy_only = Decode::decode(decoder)?;
+            game.icm_seed = Decode::decode(decoder)?;
+        }
+        game.num_storage = Decode::decode(decoder)?;
+        game.num_storage_ip = Decode::decode(decoder)?;
+        game.num_storage_chance = Decode::decode(decoder)?;
+        game.misc_memory_usage = Decode::decode(decoder)?;
+        let street_storage: [[usize; 4]; 2] = if version.is_legacy {
+            [[usize::MAX; 4]; 2]
+        } else {
+            Decode::decode(decoder)?
+        };
+        end_decode_section(decoder, version, SaveSection::Config)?;
+
+        // truncate the tree if requested
+        let file_storage_mode = game.storage_mode;
+        let initial_state = game.tree_config.initial_state;
+        let street = DECODE_STREET.with(|c| c.get());
+        let mut street = street.clamp(initial_state, file_storage_mode);
+        let is_truncated = street < file_storage_mode;
+        if is_truncated && version.is_legacy {
             return Err(DecodeError::OtherString(format!(
-                "Version mismatch: expected '{VERSION_STR}', but got '{version}'"
+                "Files saved in save format version '{}' cannot be loaded partially",
+                version.name
             )));
         }
 
-        // game instance
-        let mut game = Self {
-            state: Decode::decode(decoder)?,
-            card_config: Decode::decode(decoder)?,
-            tree_config: Decode::decode(decoder)?,
-            added_lines: Decode::decode(decoder)?,
-            removed_lines: Decode::decode(decoder)?,
-            action_root: Decode::decode(decoder)?,
-            storage_mode: Decode::decode(decoder)?,
-            num_nodes: Decode::decode(decoder)?,
-            is_compression_enabled: Decode::decode(decoder)?,
-            num_storage: Decode::decode(decoder)?,
-            num_storage_ip: Decode::decode(decoder)?,
-            num_storage_chance: Decode::decode(decoder)?,
-            misc_memory_usage: Decode::decode(decoder)?,
-            storage1: Decode::decode(decoder)?,
-            storage2: Decode::decode(decoder)?,
-            storage_ip: Decode::decode(decoder)?,
-            storage_chance: Decode::decode(decoder)?,
-            locking_strategy: Decode::decode(decoder)?,
-            ..Default::default()
+        let limit = if is_truncated {
+            street_storage[street as usize]
+        } else {
+            [usize::MAX; 4]
         };
 
+        let num_bytes = if game.is_compression_enabled { 2 } else { 4 };
+        let strategy_num_bytes = game.strategy_num_bytes();
+        let sections = [
+            (strategy_num_bytes, SaveSection::Strategy as fn(_) -> _),
+            (num_bytes, SaveSection::Values),
+            (num_bytes, SaveSection::ValuesIp),
+            (num_bytes, SaveSection::ChanceValues),
+        ];
+        let mut storages: [Storage; 4] = Default::default();
+        let mut corrupt_street = None;
+        for (i, (align, section)) in sections.into_iter().enumerate() {
+            storages[i] = decode_storage(decoder, align, version, limit[i])?;
+            let ends = [street_storage[0][i], street_storage[1][i]];
+            if let Some(s) =
+                decode_street_checksums(decoder, &storages[i], ends, street, version, section)?
+            {
+                corrupt_street = Some(corrupt_street.map_or(s, |c: BoardState| c.min(s)));
+            }
+        }
+
+        // drop the later streets of the storage buffers if corrupt
+        if let Some(corrupt_street) = corrupt_street {
+            street = street_before(corrupt_street, street, initial_state)?;
+            for (i, storage) in storages.iter_mut().enumerate() {
+                let keep = street_storage[street as usize][i].min(storage.len());
+                let mut truncated = Storage::zeroed(keep);
+                truncated.copy_from_slice(&storage[..keep]);
+                *storage = truncated;
+            }
+        }
+
+        let [storage1, storage2, storage_ip, storage_chance] = storages;
+        game.storage1 = storage1;
+        game.storage2 = storage2;
+        game.storage_ip = storage_ip;
+        game.storage_chance = storage_chance;
+
+        begin_decode_section(decoder, version);
+        game.locking_strategy = Decode::decode(decoder)?;
+        if !end_decode_section(decoder, version, SaveSection::LockingStrategy)? {
+            game.locking_strategy.clear();
+        }
+
+        let has_cfvalue_offsets = game.state >= State::MemoryAllocated && !game.is_strategy_only;
+        if street < file_storage_mode {
+            game.truncate_decoded_streets(street, file_storage_mode);
+        }
+
         game.target_storage_mode = game.storage_mode;
-        if game.storage_mode == BoardState::River && game.state >= State::MemoryAllocated {
+        game.target_strategy_only = game.is_strategy_only;
+        if game.storage_mode == BoardState::River
+            && game.state >= State::MemoryAllocated
+            && !game.is_strategy_only
+        {
             let num_bytes = if game.is_compression_enabled { 2 } else { 4 };
-            game.storage2 = vec![0; (num_bytes * game.num_storage) as usize];
-            game.storage_ip = vec![0; (num_bytes * game.num_storage_ip) as usize];
-            game.storage_chance = vec![0; (num_bytes * game.num_storage_chance) as usize];
+            game.storage2 = Storage::zeroed((num_bytes * game.num_storage) as usize);
+            game.storage_ip = Storage::zeroed((num_bytes * game.num_storage_ip) as usize);
+            game.storage_chance = Storage::zeroed((num_bytes * game.num_storage_chance) as usize);
         }
 
         // store base pointers
+        STORAGE2_RATIO.with(|c| {
+            let num_bytes = if game.is_compression_enabled { 2 } else { 4 };
+            c.set((num_bytes / game.strategy_num_bytes()) as isize);
+        });
+
         PTR_BASE_MUT.with(|c| {
-            if game.state >= State::MemoryAllocated {
+            if game.state >= State::MemoryAllocated && game.is_strategy_only {
+                c.set([game.storage1.as_mut_ptr(), ptr::null_mut(), ptr::null_mut()]);
+            } else if game.state >= State::MemoryAllocated {
                 c.set([
                     game.storage1.as_mut_ptr(),
                     game.storage2.as_mut_ptr(),
@@ -232,7 +1161,7 @@ impl Decode<()> for PostFlopGame {
         });
 
         CHANCE_BASE_MUT.with(|c| {
-            if game.state >= State::MemoryAllocated {
+            if game.state >= State::MemoryAllocated && !game.is_strategy_only {
                 c.set(game.storage_chance.as_mut_ptr());
             } else {
                 c.set(ptr::null_mut());
@@ -240,14 +1169,58 @@ impl Decode<()> for PostFlopGame {
         });
 
         // game tree
-        game.node_arena = Decode::decode(decoder)?;
+        HAS_CFVALUE_OFFSETS.with(|c| c.set(has_cfvalue_offsets));
+        let len = u64::decode(decoder)? as usize;
+        let num_nodes = if street < file_storage_mode {
+            game.num_nodes_up_to(street)
+        } else {
+            len
+        };
+        if num_nodes > len {
+            return Err(DecodeError::Other("Number of nodes is inconsistent"));
+        }
+        decoder.claim_container_read::<MutexLike<PostFlopNode>>(num_nodes)?;
+
+        if version.is_legacy {
+            game.node_arena = (0..num_nodes)
+                .map(|_| Decode::decode(decoder))
+                .collect::<Result<_, _>>()?;
+        } else {
+            let mut node_arena = Vec::with_capacity(num_nodes);
+            for (node_street, range) in street_ranges(len, game.street_node_ends()) {
+                if range.start > 0 && range.start == num_nodes {
+                    break;
+                }
+                begin_decode_section(decoder, version);
+                for _ in range {
+                    node_arena.push(Decode::decode(decoder)?);
+                }
+                if !end_decode_section(decoder, version, SaveSection::Tree(node_street))? {
+                    street = street_before(node_street, street, initial_state)?;
+                    break;
+                }
+            }
+            game.node_arena = node_arena;
+
+            // drop the later streets of the nodes if corrupt
+            if street < game.storage_mode {
+                game.node_arena.truncate(game.num_nodes_up_to(street));
+                game.truncate_decoded_streets(street, file_storage_mode);
+            }
+        }
 
         // initialization
         game.check_card_config().map_err(DecodeError::OtherString)?;
         game.init_card_fields();
+        game.init_icm_calculator();
         game.init_interpreter();
         game.back_to_root();
 
+        // strategy-only games can be queried but not solved
+        if game.is_strategy_only {
+            game.state = State::MemoryAllocated;
+        }
+
         // restore the counterfactual values
         if game.storage_mode == BoardState::River && game.state == State::Solved {
             game.state = State::MemoryAllocated;
@@ -281,12 +1254,14 @@ impl Encode for PostFlopNode {
                 // do nothing
             } else if self.is_chance() {
                 let base = CHANCE_BASE.with(|c| c.get());
-                unsafe { self.storage1.offset_from(base).encode(encoder)? };
+                if !base.is_null() {
+                    unsafe { self.storage1.offset_from(base).encode(encoder)? };
+                }
             } else {
                 let bases = PTR_BASE.with(|c| c.get());
-                unsafe {
-                    self.storage1.offset_from(bases[0]).encode(encoder)?;
-                    self.storage3.offset_from(bases[1]).encode(encoder)?;
+                unsafe { self.storage1.offset_from(bases[0]).encode(encoder)? };
+                if !bases[1].is_null() {
+                    unsafe { self.storage3.offset_from(bases[1]).encode(encoder)? };
                 }
             }
         }
@@ -319,18 +1294,28 @@ impl Decode<()> for PostFlopNode {
         if node.is_terminal() {
             // do nothing
         } else if node.is_chance() {
-            let base = CHANCE_BASE_MUT.with(|c| c.get());
-            if !base.is_null() {
-                node.storage1 = unsafe { base.offset(isize::decode(decoder)?) };
+            if HAS_CFVALUE_OFFSETS.with(|c| c.get()) {
+                let offset = isize::decode(decoder)?;
+                let base = CHANCE_BASE_MUT.with(|c| c.get());
+                if !base.is_null() {
+                    node.storage1 = unsafe { base.offset(offset) };
+                }
             }
         } else {
             let bases = PTR_BASE_MUT.with(|c| c.get());
             if !bases[0].is_null() {
                 let offset = isize::decode(decoder)?;
-                let offset_ip = isize::decode(decoder)?;
                 node.storage1 = unsafe { bases[0].offset(offset) };
-                node.storage2 = unsafe { bases[1].offset(offset) };
-                node.storage3 = unsafe { bases[2].offset(offset_ip) };
+                let offset_ip = if HAS_CFVALUE_OFFSETS.with(|c| c.get()) {
+                    isize::decode(decoder)?
+                } else {
+                    0
+                };
+                if !bases[2].is_null() {
+                    let ratio = STORAGE2_RATIO.with(|c| c.get());
+                    node.storage2 = unsafe { bases[1].offset(offset * ratio) };
+                    node.storage3 = unsafe { bases[2].offset(offset_ip) };
+                }
             }
         }
 
@@ -0,0 +1,273 @@
+use super::*;
+use crate::interface::*;
+
+impl PostFlopGame {
+    /// Creates a copy of the solved game that shares the storage of `self`.
+    ///
+    /// The storage of the strategies and the counterfactual values, which accounts for almost all
+    /// of the memory usage, is shared between `self` and the returned game behind `Arc` instead of
+    /// being duplicated. The other data, including the tree nodes (a few tens of bytes each), the
+    /// locked strategies, and the navigation state such as the current node and the named
+    /// bookmarks, are copied, so each copy can be navigated and queried independently (e.g., by a
+    /// thread per query session) without any locking.
+    ///
+    /// The shared storage is read-only. The methods that write to the storage of a solved game
+    /// (i.e., [`override_current_strategy`], [`what_if`], and [`resolve_subtree`]) first copy it
+    /// to a storage private to the game, so the changes are never visible to the other games.
+    ///
+    /// Returns an error if the game is not solved.
+    ///
+    /// **Time complexity:** *O*(#(nodes)).
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["AA,KK,QQ".parse().unwrap(), "JJ,AK".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("2c").unwrap(),
+    ///     river: card_from_str("3s").unwrap(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::River,
+    ///     starting_pot: 100,
+    ///     effective_stack: 100,
+    ///     river_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// game.allocate_memory(false);
+    /// solve(&mut game, 100, 0.0, false);
+    ///
+    /// let sessions = (0..4).map(|_| game.shared_clone().unwrap()).collect::<Vec<_>>();
+    /// std::thread::scope(|scope| {
+    ///     for mut session in sessions {
+    ///         scope.spawn(move || {
+    ///             session.play(1);
+    ///             session.cache_normalized_weights();
+    ///             session.expected_values(0)
+    ///         });
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// [`override_current_strategy`]: #method.override_current_strategy
+    /// [`what_if`]: #method.what_if
+    /// [`resolve_subtree`]: #method.resolve_subtree
+    pub fn shared_clone(&mut self) -> Result<Self, SolverError> {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+
+        let storage = [
+            self.storage1.share(),
+            self.storage2.share(),
+            self.storage_ip.share(),
+            self.storage_chance.share(),
+        ];
+        Ok(self.clone_with_storage(storage))
+    }
+
+    /// Returns whether the storage is shared with `other` (see [`shared_clone`]).
+    ///
+    /// [`shared_clone`]: #method.shared_clone
+    #[inline]
+    pub fn shares_storage_with(&self, other: &Self) -> bool {
+        [
+            (&self.storage1, &other.storage1),
+            (&self.storage2, &other.storage2),
+            (&self.storage_ip, &other.storage_ip),
+            (&self.storage_chance, &other.storage_chance),
+        ]
+        .iter()
+        .any(|(a, b)| a.ptr_eq(b))
+    }
+
+    /// Creates a copy of the game with the given storage (`storage1`, `storage2`, `storage_ip`, and
+    /// `storage_chance`), to which the pointers of the copied nodes are not adjusted.
+    pub(super) fn clone_with_storage(&self, storage: [Storage; 4]) -> Self {
+        let [storage1, storage2, storage_ip, storage_chance] = storage;
+        let mut ret = Self {
+            state: self.state,
+            card_config: self.card_config.clone(),
+            tree_config: self.tree_config.clone(),
+            added_lines: self.added_lines.clone(),
+            removed_lines: self.removed_lines.clone(),
+            action_nodes: Arc::clone(&self.action_nodes),
+            num_combinations: self.num_combinations,
+            initial_weights: self.initial_weights.clone(),
+            private_cards: self.private_cards.clone(),
+            same_hand_index: self.same_hand_index.clone(),
+            valid_indices_flop: self.valid_indices_flop.clone(),
+            valid_indices_turn: self.valid_indices_turn.clone(),
+            valid_indices_river: self.valid_indices_river.clone(),
+            hand_strength: self.hand_strength.clone(),
+            split_pot_strength: self.split_pot_strength.clone(),
+            isomorphism_mode: self.isomorphism_mode,
+            isomorphism_ref_turn: self.isomorphism_ref_turn.clone(),
+            isomorphism_card_turn: self.isomorphism_card_turn.clone(),
+            isomorphism_swap_turn: self.isomorphism_swap_turn.clone(),
+            isomorphism_ref_river: self.isomorphism_ref_river.clone(),
+            isomorphism_card_river: self.isomorphism_card_river.clone(),
+            isomorphism_swap_river: self.isomorphism_swap_river.clone(),
+            bunching_num_dead_cards: self.bunching_num_dead_cards,
+            bunching_num_combinations: self.bunching_num_combinations,
+            bunching_arena: self.bunching_arena.clone(),
+            bunching_strength: self.bunching_strength.clone(),
+            bunching_num_flop: self.bunching_num_flop.clone(),
+            bunching_num_turn: self.bunching_num_turn.clone(),
+            bunching_num_river: self.bunching_num_river.clone(),
+            bunching_coef_flop: self.bunching_coef_flop.clone(),
+            bunching_coef_turn: self.bunching_coef_turn.clone(),
+            storage_mode: self.storage_mode,
+            target_storage_mode: self.target_storage_mode,
+            is_strategy_only: self.is_strategy_only,
+            target_strategy_only: self.target_strategy_only,
+            num_nodes: self.num_nodes,
+            is_compression_enabled: self.is_compression_enabled,
+            is_strategy_quantized: self.is_strategy_quantized,
+            compression_params: self.compression_params,
+            update_schedule: self.update_schedule,
+            num_storage: self.num_storage,
+            num_storage_ip: self.num_storage_ip,
+            num_storage_chance: self.num_storage_chance,
+            misc_memory_usage: self.misc_memory_usage,
+            solve_summary: self.solve_summary,
+            solve_manifest: self.solve_manifest.clone(),
+            // the nodes keep pointing to the same addresses, which stay valid while shared
+            node_arena: self
+                .node_arena
+                .iter()
+                .map(|node| MutexLike::new(*node.lock()))
+                .collect(),
+            storage1,
+            storage2,
+            storage_ip,
+            storage_chance,
+            locking_strategy: self.locking_strategy.clone(),
+            strategy_overrides: self.strategy_overrides.clone(),
+            icm_calculator: None,
+            table_cache: self.table_cache.clone(),
+            equity_cache: self.equity_cache.clone(),
+            hand_evaluator: self.hand_evaluator.clone(),
+            split_pot_evaluator: self.split_pot_evaluator.clone(),
+            icm_seed: self.icm_seed,
+            #[cfg(feature = "bincode")]
+            source_path: self.source_path.clone(),
+            #[cfg(feature = "rayon")]
+            thread_pool: self.thread_pool.clone(),
+            #[cfg(all(feature = "numa", target_os = "linux"))]
+            numa_partitions: None,
+            action_history: self.action_history.clone(),
+            node_history: self.node_history.clone(),
+            is_normalized_weight_cached: self.is_normalized_weight_cached,
+            turn: self.turn,
+            river: self.river,
+            turn_swapped_suit: self.turn_swapped_suit,
+            turn_swap: self.turn_swap,
+            river_swap: self.river_swap,
+            total_bet_amount: self.total_bet_amount,
+            weights: self.weights.clone(),
+            normalized_weights: self.normalized_weights.clone(),
+            cfvalues_cache: self.cfvalues_cache.clone(),
+            redo_history: self.redo_history.clone(),
+            named_bookmarks: self.named_bookmarks.clone(),
+        };
+
+        // the cache of the ICM calculator is not shared; the estimation is deterministic
+        ret.init_icm_calculator();
+        ret
+    }
+
+    /// Copies the storage shared with other games (see [`shared_clone`]) to a storage private to
+    /// this game, so that it can be written to.
+    ///
+    /// [`shared_clone`]: #method.shared_clone
+    pub(super) fn unshare_storage(&mut self) {
+        let is_shared = [
+            &self.storage1,
+            &self.storage2,
+            &self.storage_ip,
+            &self.storage_chance,
+        ]
+        .iter()
+        .any(|storage| storage.is_shared());
+
+        if is_shared {
+            let addresses = self.storage_addresses();
+            [
+                self.storage1,
+                self.storage2,
+                self.storage_ip,
+                self.storage_chance,
+            ] = self.copy_storage();
+            self.rebase_nodes(addresses);
+        }
+    }
+
+    /// Returns copies of the storage (`storage1`, `storage2`, `storage_ip`, and `storage_chance`).
+    pub(super) fn copy_storage(&self) -> [Storage; 4] {
+        [
+            &self.storage1,
+            &self.storage2,
+            &self.storage_ip,
+            &self.storage_chance,
+        ]
+        .map(|storage| {
+            let mut copy = Storage::zeroed(storage.len());
+            copy.copy_from_slice(storage);
+            copy
+        })
+    }
+
+    /// Returns the start addresses of the storage, which are passed to [`rebase_nodes`] after the
+    /// storage is replaced.
+    ///
+    /// [`rebase_nodes`]: PostFlopGame::rebase_nodes
+    pub(super) fn storage_addresses(&self) -> [usize; 4] {
+        [
+            &self.storage1,
+            &self.storage2,
+            &self.storage_ip,
+            &self.storage_chance,
+        ]
+        .map(|storage| storage.as_ptr() as usize)
+    }
+
+    /// Moves the pointers of the nodes from the storage that started at `addresses` to the current
+    /// storage, keeping their offsets.
+    pub(super) fn rebase_nodes(&mut self, addresses: [usize; 4]) {
+        let bases = [
+            self.storage1.as_mut_ptr(),
+            self.storage2.as_mut_ptr(),
+            self.storage_ip.as_mut_ptr(),
+            self.storage_chance.as_mut_ptr(),
+        ];
+
+        let rebase = |ptr: *mut u8, i: usize| {
+            if ptr.is_null() {
+                ptr
+            } else {
+                bases[i].wrapping_add((ptr as usize).wrapping_sub(addresses[i]))
+            }
+        };
+
+        for node in &self.node_arena {
+            let mut node = node.lock();
+            if node.is_terminal() {
+                // do nothing
+            } else if node.is_chance() {
+                node.storage1 = rebase(node.storage1, 3);
+            } else {
+                node.storage1 = rebase(node.storage1, 0);
+                node.storage2 = rebase(node.storage2, 1);
+                node.storage3 = rebase(node.storage3, 2);
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use super::*;
+use crate::convergence::*;
+use crate::solver::*;
+use crate::utility::*;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Holder of the latest snapshot of a game being solved by [`solve_with_snapshots`].
+///
+/// The solving thread publishes a new snapshot every few iterations, and any number of threads can
+/// read the latest one at the same time. A snapshot is a finalized copy of the game, so it can be
+/// navigated and queried like a solved game (e.g., [`PostFlopGame::strategy`] and
+/// [`PostFlopGame::expected_values`]) while the iterations continue on the original game.
+///
+/// The slot also has a readiness flag of each street (see [`is_street_ready`]). All the streets
+/// become ready when the solving is finished. With [`with_stability_threshold`], a street becomes
+/// ready earlier once its strategy stabilizes, so that, e.g., the flop strategy can be shown before
+/// the river converges.
+///
+/// [`is_street_ready`]: SnapshotSlot::is_street_ready
+/// [`with_stability_threshold`]: SnapshotSlot::with_stability_threshold
+#[derive(Default)]
+pub struct SnapshotSlot {
+    latest: Mutex<Option<(u32, PostFlopGame)>>,
+    iteration: AtomicU32,
+    stability_threshold: Option<f32>,
+    ready: [AtomicBool; 3],
+}
+
+impl SnapshotSlot {
+    /// Creates an empty slot.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty slot that marks a street ready once its strategy is stable between two
+    /// consecutive snapshots.
+    ///
+    /// A street is stable if no action frequency of any node of the street, aggregated over the
+    /// range of the acting player (see [`diff_games`]), changes by more than `threshold`. The
+    /// streets become ready in order: a street is not checked until the previous streets are
+    /// ready, and a ready street stays ready. Note that the streets are solved together, so the
+    /// strategy of a ready street may still change slightly as the later streets converge.
+    ///
+    /// The previous snapshot is kept for the comparison, so the solving uses up to three times as
+    /// much memory as the game.
+    #[inline]
+    pub fn with_stability_threshold(threshold: f32) -> Self {
+        Self {
+            stability_threshold: Some(threshold),
+            ..Default::default()
+        }
+    }
+
+    /// Returns whether the results of `street` in the latest snapshot are ready (see
+    /// [`with_stability_threshold`]).
+    ///
+    /// [`with_stability_threshold`]: SnapshotSlot::with_stability_threshold
+    #[inline]
+    pub fn is_street_ready(&self, street: BoardState) -> bool {
+        self.ready[street as usize].load(Ordering::Acquire)
+    }
+
+    /// Returns the number of iterations of the latest snapshot (0 if no snapshot is published).
+    ///
+    /// This is cheap to call, so it can be polled to check whether a new snapshot is available.
+    #[inline]
+    pub fn iteration(&self) -> u32 {
+        self.iteration.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of iterations and a copy of the latest snapshot, or `None` if no
+    /// snapshot is published yet.
+    ///
+    /// The copies share the storage of the snapshot (see [`PostFlopGame::shared_clone`]), so each
+    /// reader has its own navigation state without duplicating the storage.
+    pub fn latest(&self) -> Option<(u32, PostFlopGame)> {
+        let mut latest = self.latest.lock().unwrap();
+        let (iteration, game) = latest.as_mut()?;
+        Some((*iteration, game.shared_clone().ok()?))
+    }
+
+    /// Replaces the snapshot with `game`, which must be solved.
+    fn publish(&self, iteration: u32, game: PostFlopGame) {
+        debug_assert!(game.is_solved());
+        *self.latest.lock().unwrap() = Some((iteration, game));
+        self.iteration.store(iteration, Ordering::Release);
+    }
+
+    /// Marks the streets up to `street` ready.
+    fn set_ready_up_to(&self, street: BoardState) {
+        for ready in &self.ready[..=street as usize] {
+            ready.store(true, Ordering::Release);
+        }
+    }
+
+    /// Returns the last street whose strategy is stable between `previous` and `current` in
+    /// addition to the already ready streets, or `None` if the flop is not ready.
+    fn stable_street(
+        &self,
+        previous: &mut PostFlopGame,
+        current: &mut PostFlopGame,
+    ) -> Result<Option<BoardState>, SolverError> {
+        let Some(threshold) = self.stability_threshold else {
+            return Ok(None);
+        };
+
+        let mut ret = None;
+        for street in [BoardState::Flop, BoardState::Turn, BoardState::River] {
+            if !self.is_street_ready(street) {
+                let options = DiffOptions {
+                    street: Some(street),
+                    frequency_threshold: threshold,
+                    ev_threshold: f32::INFINITY,
+                    ..Default::default()
+                };
+                if !diff_games(previous, current, &options)?.is_empty() {
+                    break;
+                }
+            }
+            ret = Some(street);
+        }
+
+        Ok(ret)
+    }
+}
+
+impl PostFlopGame {
+    /// Creates a finalized copy of the game in the middle of solving.
+    ///
+    /// The copy has the average strategy of the iterations performed so far and the expected values
+    /// under that strategy, as if the solving was finished now, while the solving of `self` can be
+    /// continued. If the game is already solved, the copy has the same results.
+    ///
+    /// Returns an error if the memory is not allocated.
+    ///
+    /// **Time complexity:** *O*(#(nodes) * #(private hands)), the same as [`finalize`].
+    ///
+    /// [`finalize`]: crate::finalize
+    pub fn snapshot(&self) -> Result<Self, SolverError> {
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+
+        let addresses = self.storage_addresses();
+        let mut ret = self.clone_with_storage(self.copy_storage());
+        ret.rebase_nodes(addresses);
+
+        if !ret.is_solved() {
+            finalize(&mut ret);
+        }
+
+        Ok(ret)
+    }
+}
+
+/// Same as [`solve`], but publishes a snapshot of the game (see [`PostFlopGame::snapshot`]) to
+/// `slot` every `interval` iterations, so that the evolving strategy can be queried from other
+/// threads while solving.
+///
+/// The finished game is also published, so the latest snapshot of `slot` has the final results
+/// and all the streets are ready after this function returns (see [`SnapshotSlot`] for the
+/// readiness of the streets while solving). Taking a snapshot costs as much memory as the game
+/// and about as much time as an iteration, so `interval` should not be too small.
+///
+/// This method returns the exploitability of the obtained strategy.
+///
+/// # Panics
+///
+/// Panics if `interval` is zero.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let card_config = CardConfig {
+///     range: ["QQ+,AK".parse().unwrap(), "JJ-99,AQs".parse().unwrap()],
+///     flop: flop_from_str("Td9d6h").unwrap(),
+///     turn: card_from_str("Qc").unwrap(),
+///     river: card_from_str("2s").unwrap(),
+///     ..Default::default()
+/// };
+/// let tree_config = TreeConfig {
+///     initial_state: BoardState::River,
+///     starting_pot: 100,
+///     effective_stack: 100,
+///     river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+///     ..Default::default()
+/// };
+/// let action_tree = ActionTree::new(tree_config).unwrap();
+/// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+/// game.allocate_memory(false);
+///
+/// let slot = SnapshotSlot::new();
+/// std::thread::scope(|scope| {
+///     scope.spawn(|| solve_with_snapshots(&mut game, 100, 0.0, false, 10, &slot));
+///
+///     // e.g., a GUI thread showing the evolving strategy
+///     if let Some((_iteration, mut snapshot)) = slot.latest() {
+///         snapshot.cache_normalized_weights();
+///         let _strategy = snapshot.strategy();
+///     }
+/// });
+///
+/// assert!(slot.latest().unwrap().1.is_solved());
+/// ```
+pub fn solve_with_snapshots(
+    game: &mut PostFlopGame,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    print_progress: bool,
+    interval: u32,
+    slot: &SnapshotSlot,
+) -> f32 {
+    assert!(interval > 0, "Interval must be positive");
+
+    // shares the storage of the last published snapshot
+    let mut previous = None;
+
+    let result: Result<f32, SolverError> = solve_with_callback(
+        game,
+        max_num_iterations,
+        &mut TargetExploitability(target_exploitability),
+        print_progress,
+        None,
+        &mut |game, iteration| {
+            if iteration % interval != 0 {
+                return Ok(());
+            }
+
+            let mut snapshot = game.snapshot()?;
+            let stable_street = match previous.as_mut() {
+                Some(previous) => slot.stable_street(previous, &mut snapshot)?,
+                None => None,
+            };
+            if slot.stability_threshold.is_some() {
+                previous = Some(snapshot.shared_clone()?);
+            }
+
+            slot.publish(iteration, snapshot);
+            if let Some(street) = stable_street {
+                slot.set_ready_up_to(street);
+            }
+
+            Ok(())
+        },
+    );
+
+    let exploitability = result.unwrap();
+    let num_iterations = game
+        .solve_summary()
+        .map_or(0, |summary| summary.num_iterations);
+    slot.publish(num_iterations, game.shared_clone().unwrap());
+    slot.set_ready_up_to(BoardState::River);
+    exploitability
+}
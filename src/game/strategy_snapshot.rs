@@ -0,0 +1,349 @@
+use super::*;
+use crate::interface::*;
+use crate::utility::*;
+
+/// Compact in-memory copy of the normalized strategy of a solved game, returned by
+/// [`PostFlopGame::strategy_snapshot`].
+///
+/// Only the strategy is kept (not the regrets or the expected values), with each frequency stored
+/// in 16 bits, so a snapshot takes about half the memory of the cumulative strategy of an
+/// uncompressed game, and far less than the game itself. Several snapshots of the same tree can
+/// be kept to compare the results of an iterative workflow (e.g., lock, re-solve, and compare)
+/// without saving and loading the games: snapshots can be compared with [`max_difference`],
+/// averaged with [`blend`], queried node by node with [`PostFlopGame::snapshot_strategy`], and
+/// played back in a game with [`PostFlopGame::apply_strategy_snapshot`].
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let card_config = CardConfig {
+///     range: ["AA,KK,87s,65s,A5s".parse().unwrap(), "QQ-99,AK,KQs,T9s".parse().unwrap()],
+///     flop: flop_from_str("Td9d6h").unwrap(),
+///     turn: card_from_str("2c").unwrap(),
+///     river: card_from_str("3s").unwrap(),
+///     ..Default::default()
+/// };
+///
+/// let tree_config = TreeConfig {
+///     initial_state: BoardState::River,
+///     starting_pot: 100,
+///     effective_stack: 100,
+///     river_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+///     ..Default::default()
+/// };
+///
+/// let new_game = || {
+///     let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+///     let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+///     game.allocate_memory(false);
+///     game
+/// };
+///
+/// let mut game = new_game();
+/// solve(&mut game, 100, 0.0, false);
+/// let before = game.strategy_snapshot().unwrap();
+///
+/// // lock IP to always call the bet, and re-solve
+/// let mut locked = new_game();
+/// locked.apply_history(&[1]);
+/// let num_hands = locked.private_cards(1).len();
+/// let mut strategy = vec![0.0; 2 * num_hands];
+/// strategy[num_hands..].fill(1.0);
+/// locked.lock_current_strategy(&strategy);
+/// locked.back_to_root();
+/// solve(&mut locked, 100, 0.0, false);
+/// let after = locked.strategy_snapshot().unwrap();
+///
+/// println!("largest change: {:.3}", before.max_difference(&after).unwrap());
+///
+/// // the expected values of the original strategy against the locked opponent
+/// let report = locked.apply_strategy_snapshot(&before).unwrap();
+/// println!("EV of OOP: {:.2}", report.ev[0]);
+/// locked.clear_strategy_overrides();
+/// ```
+///
+/// [`max_difference`]: StrategySnapshot::max_difference
+/// [`blend`]: StrategySnapshot::blend
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrategySnapshot {
+    private_cards: [Vec<(Card, Card)>; 2],
+    num_nodes: usize,
+    nodes: Vec<SnapshotNode>,
+    data: Vec<u16>,
+}
+
+/// Location of the strategy of a player node in [`StrategySnapshot::data`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SnapshotNode {
+    index: u32,
+    offset: u32,
+    num_actions: u16,
+    player: u8,
+}
+
+impl StrategySnapshot {
+    /// Returns the number of player nodes in the snapshot.
+    #[inline]
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the approximate memory usage of the snapshot in bytes.
+    #[inline]
+    pub fn memory_usage(&self) -> u64 {
+        let cards = self.private_cards.iter().map(Vec::len).sum::<usize>();
+        (size_of::<Self>()
+            + cards * size_of::<(Card, Card)>()
+            + self.nodes.len() * size_of::<SnapshotNode>()
+            + self.data.len() * size_of::<u16>()) as u64
+    }
+
+    /// Returns the largest absolute difference of the frequency of any action of any hand at any
+    /// node between `self` and `other`.
+    ///
+    /// Every hand counts equally, including the hands that never reach the node; use
+    /// [`diff_games`] on games with the snapshots applied (see
+    /// [`PostFlopGame::apply_strategy_snapshot`]) to compare frequencies weighted by the ranges.
+    ///
+    /// Returns [`SolverError::InvalidArgument`] if the snapshots are not of the same tree and
+    /// ranges.
+    pub fn max_difference(&self, other: &Self) -> Result<f32, SolverError> {
+        self.check_compatible(other)?;
+        let mut max_difference = 0.0f32;
+        for node in &self.nodes {
+            let a = self.node_strategy(node);
+            let b = other.node_strategy(node);
+            for (a, b) in a.iter().zip(&b) {
+                max_difference = max_difference.max((a - b).abs());
+            }
+        }
+        Ok(max_difference)
+    }
+
+    /// Returns the snapshot whose strategy at each node is the weighted average of the strategies
+    /// of `self` and `other`, with the weight `1.0 - ratio` for `self` and `ratio` for `other`.
+    ///
+    /// The average is taken node by node for each hand, so the result is a behavioral blend, not
+    /// the mixture of playing one of the strategies for the whole hand (see
+    /// [`PostFlopGame::lock_strategy_mixture`]).
+    ///
+    /// Returns [`SolverError::InvalidArgument`] if `ratio` is not between `0.0` and `1.0`, or the
+    /// snapshots are not of the same tree and ranges.
+    pub fn blend(&self, other: &Self, ratio: f32) -> Result<Self, SolverError> {
+        if !(0.0..=1.0).contains(&ratio) {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid blend ratio: {ratio}"
+            )));
+        }
+        self.check_compatible(other)?;
+
+        let mut data = Vec::with_capacity(self.data.len());
+        for node in &self.nodes {
+            let a = self.node_strategy(node);
+            let b = other.node_strategy(node);
+            let blended = a
+                .iter()
+                .zip(&b)
+                .map(|(a, b)| a * (1.0 - ratio) + b * ratio)
+                .collect::<Vec<_>>();
+            data.extend(quantize(&blended));
+        }
+
+        Ok(Self {
+            private_cards: self.private_cards.clone(),
+            num_nodes: self.num_nodes,
+            nodes: self.nodes.clone(),
+            data,
+        })
+    }
+
+    /// Returns the normalized strategy of `node` in the internal order of the hands.
+    fn node_strategy(&self, node: &SnapshotNode) -> Vec<f32> {
+        let num_actions = node.num_actions as usize;
+        let num_hands = self.private_cards[node.player as usize].len();
+        let offset = node.offset as usize;
+        let row = &self.data[offset..offset + num_actions * num_hands];
+
+        let mut ret = vec![0.0; row.len()];
+        for hand in 0..num_hands {
+            let sum = (0..num_actions)
+                .map(|action| row[action * num_hands + hand] as f32)
+                .sum::<f32>();
+            for action in 0..num_actions {
+                let index = action * num_hands + hand;
+                ret[index] = if sum > 0.0 {
+                    row[index] as f32 / sum
+                } else {
+                    1.0 / num_actions as f32
+                };
+            }
+        }
+        ret
+    }
+
+    /// Returns the snapshot node of the node at `index` in the arena, if any.
+    fn find_node(&self, index: usize) -> Option<&SnapshotNode> {
+        self.nodes
+            .binary_search_by_key(&index, |node| node.index as usize)
+            .ok()
+            .map(|i| &self.nodes[i])
+    }
+
+    fn check_compatible(&self, other: &Self) -> Result<(), SolverError> {
+        if self.private_cards != other.private_cards
+            || self.num_nodes != other.num_nodes
+            || self.nodes != other.nodes
+        {
+            return Err(SolverError::InvalidArgument(
+                "Snapshots have different trees or ranges".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Quantizes the normalized frequencies to 16 bits.
+#[inline]
+fn quantize(strategy: &[f32]) -> impl Iterator<Item = u16> + '_ {
+    strategy
+        .iter()
+        .map(|&f| (f.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16)
+}
+
+impl PostFlopGame {
+    /// Takes a [`StrategySnapshot`] of the normalized strategy of every player node.
+    ///
+    /// The locked strategies and the active strategy overrides (see [`what_if`]) are included,
+    /// as [`strategy`] returns them. If the game was loaded from a file without the later streets
+    /// (see [`storage_mode`]), only the stored streets are included.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the game is not solved.
+    ///
+    /// **Time complexity:** *O*(#(nodes) * #(private hands)).
+    ///
+    /// [`what_if`]: #method.what_if
+    /// [`strategy`]: #method.strategy
+    /// [`storage_mode`]: #method.storage_mode
+    pub fn strategy_snapshot(&self) -> Result<StrategySnapshot, SolverError> {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+
+        let mut nodes = Vec::new();
+        let mut data = Vec::new();
+        for index in 0..self.num_nodes_up_to(self.storage_mode) {
+            let node = self.node_arena[index].lock();
+            if node.is_terminal() || node.is_chance() {
+                continue;
+            }
+            nodes.push(SnapshotNode {
+                index: index as u32,
+                offset: data.len() as u32,
+                num_actions: node.num_actions() as u16,
+                player: node.player() as u8,
+            });
+            data.extend(quantize(&self.normalized_node_strategy(&node)));
+        }
+
+        Ok(StrategySnapshot {
+            private_cards: self.private_cards.clone(),
+            num_nodes: self.node_arena.len(),
+            nodes,
+            data,
+        })
+    }
+
+    /// Returns the strategy of the current node in `snapshot`, in the same format as
+    /// [`strategy`].
+    ///
+    /// Returns [`SolverError::InvalidArgument`] if `snapshot` is not of the same tree and ranges
+    /// as this game, and [`SolverError::InvalidState`] if the current node is not a player node
+    /// in the snapshot.
+    ///
+    /// [`strategy`]: #method.strategy
+    pub fn snapshot_strategy(&self, snapshot: &StrategySnapshot) -> Result<Vec<f32>, SolverError> {
+        self.check_snapshot(snapshot)?;
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(SolverError::InvalidState(
+                "Current node is not a player node".to_owned(),
+            ));
+        }
+
+        let index = self.node_index(&self.node());
+        let Some(node) = snapshot.find_node(index) else {
+            return Err(SolverError::InvalidState(
+                "Current node is not in the snapshot".to_owned(),
+            ));
+        };
+
+        let player = self.current_player();
+        let num_hands = self.num_private_hands(player);
+        let mut ret = snapshot.node_strategy(node);
+        ret.chunks_exact_mut(num_hands).for_each(|chunk| {
+            self.apply_swap(chunk, player, false);
+        });
+        Ok(ret)
+    }
+
+    /// Overrides the strategy of every node in `snapshot` with that of the snapshot and reports
+    /// the resulting shift of the expected values.
+    ///
+    /// The overrides work as in [`what_if`]: the expected values of the whole tree are recomputed
+    /// without re-solving, so the snapshot can be inspected with the usual methods (or compared
+    /// with another game by [`diff_games`]) until [`clear_strategy_overrides`] is called. The
+    /// current node is preserved.
+    ///
+    /// Returns [`SolverError::InvalidState`] if the game is not solved, and
+    /// [`SolverError::InvalidArgument`] if `snapshot` is not of the same tree and ranges as this
+    /// game.
+    ///
+    /// **Time complexity:** *O*(#(nodes) * #(private hands)).
+    ///
+    /// [`what_if`]: #method.what_if
+    /// [`clear_strategy_overrides`]: #method.clear_strategy_overrides
+    pub fn apply_strategy_snapshot(
+        &mut self,
+        snapshot: &StrategySnapshot,
+    ) -> Result<WhatIfReport, SolverError> {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+        self.check_snapshot(snapshot)?;
+
+        let baseline_ev = compute_current_ev(self);
+        for node in &snapshot.nodes {
+            let locking = snapshot.node_strategy(node);
+            self.insert_strategy_override(node.index as usize, locking);
+        }
+        self.refresh_after_override();
+
+        let ev = compute_current_ev(self);
+        Ok(WhatIfReport {
+            baseline_ev,
+            ev,
+            ev_shift: [ev[0] - baseline_ev[0], ev[1] - baseline_ev[1]],
+        })
+    }
+
+    fn check_snapshot(&self, snapshot: &StrategySnapshot) -> Result<(), SolverError> {
+        let is_compatible = snapshot.private_cards == self.private_cards
+            && snapshot.num_nodes == self.node_arena.len()
+            && snapshot.nodes.iter().all(|node| {
+                let index = node.index as usize;
+                index < self.num_nodes_up_to(self.storage_mode) && {
+                    let game_node = self.node_arena[index].lock();
+                    !game_node.is_terminal()
+                        && !game_node.is_chance()
+                        && game_node.player() == node.player as usize
+                        && game_node.num_actions() == node.num_actions as usize
+                }
+            });
+        if !is_compatible {
+            return Err(SolverError::InvalidArgument(
+                "Snapshot has a different tree or different ranges".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
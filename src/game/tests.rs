@@ -1,9 +1,20 @@
 use super::*;
 use crate::BunchingData;
+use crate::bomb_pot::*;
+use crate::convergence::*;
+use crate::hand_class::*;
+use crate::interface::*;
+use crate::position::*;
+use crate::random::*;
 use crate::range::*;
 use crate::solver::*;
+use crate::texture::*;
+use crate::units::*;
 use crate::utility::*;
 
+#[cfg(feature = "profiling")]
+use crate::profiling::*;
+
 #[test]
 fn all_check_all_range() {
     let card_config = CardConfig {
@@ -350,6 +361,9 @@ fn one_raise_all_range_with_river() {
         flop: flop_from_str("Td9d6h").unwrap(),
         turn: card_from_str("Qc").unwrap(),
         river: card_from_str("7s").unwrap(),
+        dead_cards: 0,
+        short_deck: false,
+        positions: [None; 2],
     };
 
     let tree_config = TreeConfig {
@@ -684,395 +698,810 @@ fn always_tie_raked() {
 }
 
 #[test]
-fn no_assignment() {
+fn rake_config() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
     let card_config = CardConfig {
-        range: ["TT".parse().unwrap(), "TT".parse().unwrap()],
-        flop: flop_from_str("Td9d6h").unwrap(),
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
         ..Default::default()
     };
 
+    // 10% rake capped at 1 on the flop, 50% rake capped at 100 on the later streets
+    let rake_config = RakeConfig {
+        rates: [0.1, 0.5, 0.5],
+        caps: [1.0, 100.0, 100.0],
+    };
     let tree_config = TreeConfig {
-        starting_pot: 60,
-        effective_stack: 970,
+        starting_pot: 20,
+        effective_stack: 10,
+        flop_bet_sizes: [("a", "").try_into().unwrap(), Default::default()],
+        rake_config: Some(rake_config),
         ..Default::default()
     };
+    assert!(tree_config.is_raked());
+    assert_eq!(tree_config.rake(20.0, BoardState::Flop), 1.0);
+    assert_eq!(tree_config.rake(20.0, BoardState::River), 10.0);
 
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let game = PostFlopGame::with_config(card_config, action_tree);
-    assert!(game.is_err());
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    game.lock_current_strategy(&[0.0, 1.0]); // OOP all-in
+    game.play(1);
+    let num_hands = game.private_cards(1).len();
+    let mut strategy = vec![0.0; 2 * num_hands];
+    strategy[..num_hands].fill(1.0);
+    game.lock_current_strategy(&strategy); // IP fold
+    game.back_to_root();
+    solve(&mut game, 10, 0.0, false);
+
+    // the hand ends on the flop
+    game.cache_normalized_weights();
+    assert!((game.expected_values(0)[0] - 19.0).abs() < 1e-4);
+    let rollout = game.rollout(0, None).unwrap();
+    assert!(rollout.is_fold);
+    assert!((rollout.payoff[0] - 19.0).abs() < 1e-4);
+
+    // the check-check lines end on the river
+    game.allocate_memory(false);
+    game.lock_current_strategy(&[1.0, 0.0]); // OOP check
+    solve(&mut game, 10, 0.0, false);
+    game.cache_normalized_weights();
+    assert!((game.expected_values(0)[0] - 10.0).abs() < 1e-4);
+
+    // the rake configuration is saved
+    #[cfg(feature = "bincode")]
+    {
+        let config = bincode::config::standard();
+        let encoded = bincode::encode_to_vec(&game, config).unwrap();
+        let (decoded, _): (PostFlopGame, _) = bincode::decode_from_slice(&encoded, config).unwrap();
+        assert_eq!(decoded.tree_config().rake_config, Some(rake_config));
+    }
+
+    // invalid configurations
+    for rake_config in [
+        RakeConfig {
+            rates: [0.1, 1.5, 0.1],
+            caps: [1.0; 3],
+        },
+        RakeConfig {
+            rates: [0.1; 3],
+            caps: [1.0, 1.0, -1.0],
+        },
+    ] {
+        let tree_config = TreeConfig {
+            rake_config: Some(rake_config),
+            ..tree_config.clone()
+        };
+        ActionTree::new(tree_config).map(|_| ()).unwrap_err();
+    }
 }
 
 #[test]
-fn remove_lines() {
-    use crate::bet_size::BetSizeOptions;
+fn sizing_rules() {
     let card_config = CardConfig {
-        range: ["TT+,AKo,AQs+".parse().unwrap(), "AA".parse().unwrap()],
-        flop: flop_from_str("2c6dTh").unwrap(),
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
         ..Default::default()
     };
 
-    // simple tree: force checks on flop, and only use 1/2 pot bets on turn and river
+    let bet_sizes = crate::BetSizeOptions::try_from(("33%, 75%, 150%", "60%, 2.5x")).unwrap();
     let tree_config = TreeConfig {
-        starting_pot: 60,
-        effective_stack: 970,
-        turn_bet_sizes: [
-            BetSizeOptions::try_from(("50%", "")).unwrap(),
-            Default::default(),
-        ],
-        river_bet_sizes: [
-            BetSizeOptions::try_from(("50%", "")).unwrap(),
-            Default::default(),
+        starting_pot: 100,
+        effective_stack: 400,
+        flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        sizing_rules: vec![
+            // river only: SPR below 1
+            SizingRule {
+                street: Some(BoardState::River),
+                pot_to_stack_ratio: 1.0,
+                bet_sizes: ("a", "").try_into().unwrap(),
+            },
+            SizingRule {
+                street: None,
+                pot_to_stack_ratio: 0.5,
+                bet_sizes: ("60%, a", "a").try_into().unwrap(),
+            },
         ],
         ..Default::default()
     };
 
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
-
-    let lines = vec![
-        vec![
-            Action::Check,
-            Action::Check,
-            Action::Chance(2),
-            Action::Check,
-        ],
-        vec![
-            Action::Check,
-            Action::Check,
-            Action::Chance(2),
-            Action::Bet(30),
-            Action::Call,
-            Action::Chance(3),
-            Action::Bet(60),
-        ],
+    let mut action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let default_actions = [
+        Action::Check,
+        Action::Bet(33),
+        Action::Bet(75),
+        Action::Bet(150),
     ];
+    assert_eq!(action_tree.available_actions(), default_actions);
 
-    let res = game.remove_lines(&lines);
-    res.unwrap();
-
-    game.allocate_memory(false);
-
-    // check that the turn line is removed
-    game.apply_history(&[0, 0, 2]);
-    assert_eq!(game.available_actions(), vec![Action::Bet(30)]);
-
-    // check that other turn lines are correct
-    game.apply_history(&[0, 0, 3]);
+    // pot 166, stack 367: no rule matches
+    action_tree.play(Action::Bet(33)).unwrap();
+    action_tree.play(Action::Call).unwrap();
     assert_eq!(
-        game.available_actions(),
-        vec![Action::Check, Action::Bet(30)]
+        action_tree.available_actions(),
+        [
+            Action::Check,
+            Action::Bet(55),
+            Action::Bet(125),
+            Action::Bet(249)
+        ]
     );
 
-    // check that the river line is removed
-    game.apply_history(&[0, 0, 2, 0, 1, 3]);
-    assert_eq!(game.available_actions(), vec![Action::Check]);
+    // pot 416 > 0.5 * 242 on the turn: the second rule matches
+    action_tree.play(Action::Bet(125)).unwrap();
+    assert_eq!(
+        action_tree.available_actions(),
+        [Action::Fold, Action::Call, Action::AllIn(367)]
+    );
 
-    // check that other river lines are correct
-    game.apply_history(&[0, 0, 2, 0, 1, 4]);
+    // pot 416 > 1.0 * 242 on the river: the first rule matches
+    action_tree.play(Action::Call).unwrap();
     assert_eq!(
-        game.available_actions(),
-        vec![Action::Check, Action::Bet(60)]
+        action_tree.available_actions(),
+        [Action::Check, Action::AllIn(242)]
     );
 
-    game.apply_history(&[0, 0, 3, 1, 1, 4]);
+    // pot 276 > 0.5 * 312 but not > 1.0 * 312 on the river: the second rule matches
+    action_tree
+        .apply_history(&[Action::Bet(33), Action::Call, Action::Bet(55), Action::Call])
+        .unwrap();
     assert_eq!(
-        game.available_actions(),
-        vec![Action::Check, Action::Bet(60)]
+        action_tree.available_actions(),
+        [Action::Check, Action::Bet(166), Action::AllIn(312)]
     );
 
-    // check that `solve()` does not crash
-    solve(&mut game, 10, 0.01, false);
+    // the game can be solved and saved only in the format versions with sizing rules
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+
+    #[cfg(feature = "bincode")]
+    {
+        let config = bincode::config::standard();
+        let encoded = bincode::encode_to_vec(&game, config).unwrap();
+        let (decoded, _): (PostFlopGame, _) = bincode::decode_from_slice(&encoded, config).unwrap();
+        assert_eq!(decoded.tree_config().sizing_rules, tree_config.sizing_rules);
+    }
+
+    // invalid configuration
+    let tree_config = TreeConfig {
+        sizing_rules: vec![SizingRule {
+            pot_to_stack_ratio: -1.0,
+            ..Default::default()
+        }],
+        ..tree_config
+    };
+    ActionTree::new(tree_config).map(|_| ()).unwrap_err();
 }
 
 #[test]
-fn isomorphism_monotone() {
-    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
-    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
+fn bomb_pot() {
+    let bomb_pot = BombPot {
+        ante: 30,
+        stack: 200,
+    };
+
+    // the pot and the stack are derived from the antes
+    let bet_sizes = crate::BetSizeOptions::try_from(("50%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        bomb_pot: Some(bomb_pot),
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    assert_eq!(action_tree.config().starting_pot, 60);
+    assert_eq!(action_tree.config().effective_stack, 170);
+    assert_eq!(action_tree.available_actions()[2], Action::AllIn(170));
 
+    // the full ranges are narrowed to keep the game small
     let card_config = CardConfig {
-        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
-        flop: flop_from_str("QhJh2h").unwrap(),
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
+        ],
+        ..bomb_pot.card_config(flop_from_str("Td9d6h").unwrap())
+    };
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.allocate_memory(false);
+    let exploitability = solve(&mut game, 1000, 0.001 * 60.0, false);
+    assert!(exploitability <= 0.001 * 60.0);
+
+    // the same as the equivalent single-raised pot
+    let explicit_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 170,
+        bomb_pot: None,
+        ..tree_config.clone()
+    };
+    let action_tree = ActionTree::new(explicit_config).unwrap();
+    let mut explicit = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    explicit.allocate_memory(false);
+    solve(&mut explicit, 1000, 0.001 * 60.0, false);
+    game.cache_normalized_weights();
+    explicit.cache_normalized_weights();
+    for player in 0..2 {
+        assert_eq!(
+            game.expected_values(player),
+            explicit.expected_values(player)
+        );
+    }
+
+    #[cfg(feature = "bincode")]
+    {
+        let config = bincode::config::standard();
+        let encoded = bincode::encode_to_vec(&game, config).unwrap();
+        let (decoded, _): (PostFlopGame, _) = bincode::decode_from_slice(&encoded, config).unwrap();
+        assert_eq!(decoded.tree_config().bomb_pot, Some(bomb_pot));
+    }
+
+    // a bomb pot starts on the flop
+    let card_config = CardConfig {
+        turn: card_from_str("2c").unwrap(),
+        ..card_config
+    };
+    let turn_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        ..tree_config.clone()
+    };
+    ActionTree::new(turn_config).map(|_| ()).unwrap_err();
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    PostFlopGame::with_config(card_config, action_tree)
+        .map(|_| ())
+        .unwrap_err();
+
+    // invalid configurations
+    let invalid = [
+        BombPot {
+            ante: 0,
+            stack: 200,
+        },
+        BombPot {
+            ante: 30,
+            stack: 30,
+        },
+    ];
+    for bomb_pot in invalid {
+        let tree_config = TreeConfig {
+            bomb_pot: Some(bomb_pot),
+            ..tree_config.clone()
+        };
+        ActionTree::new(tree_config).map(|_| ()).unwrap_err();
+    }
+    let mismatched_config = TreeConfig {
+        starting_pot: 50,
+        ..tree_config
+    };
+    ActionTree::new(mismatched_config).map(|_| ()).unwrap_err();
+}
+
+#[test]
+fn positions() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        positions: [Some(Position::BigBlind), Some(Position::Button)],
         ..Default::default()
     };
 
     let tree_config = TreeConfig {
+        initial_state: BoardState::River,
         starting_pot: 100,
         effective_stack: 100,
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
         ..Default::default()
     };
 
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
-
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
     game.allocate_memory(false);
-    finalize(&mut game);
-
-    let mut check = |history: &[usize],
-                     expected_turn_swap: Option<u8>,
-                     expected_river_swap: Option<(u8, u8)>| {
-        game.apply_history(history);
-        game.cache_normalized_weights();
-        let weights = game.normalized_weights(0);
-        let ev = game.expected_values(0);
-        weights.iter().zip(ev.iter()).for_each(|(&w, &v)| {
-            assert!(!(w > 0.0 && v == 50.0));
-        });
-        assert_eq!(game.turn_swap, expected_turn_swap);
-        assert_eq!(game.river_swap, expected_river_swap);
+    solve(&mut game, 10, 0.0, false);
+
+    let query = game.query_node().unwrap();
+    assert_eq!(query.positions, card_config.positions);
+    let mut json = Vec::new();
+    game.export_json(&mut json).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    assert!(json.contains(",\"positions\":[\"BB\",\"BTN\"]}"));
+
+    // the positions are saved
+    #[cfg(feature = "bincode")]
+    {
+        let config = bincode::config::standard();
+        let encoded = bincode::encode_to_vec(&game, config).unwrap();
+        let (decoded, _): (PostFlopGame, _) = bincode::decode_from_slice(&encoded, config).unwrap();
+        assert_eq!(decoded.card_config().positions, card_config.positions);
+    }
+
+    // OOP must act first postflop
+    let card_config = CardConfig {
+        positions: [Some(Position::Button), Some(Position::Cutoff)],
+        ..card_config
     };
-
-    check(&[0, 0, 4], None, None);
-    check(&[0, 0, 5], Some(1), None);
-    check(&[0, 0, 6], None, None);
-    check(&[0, 0, 7], Some(3), None);
-
-    check(&[0, 0, 4, 0, 0, 8], None, None);
-    check(&[0, 0, 4, 0, 0, 9], None, None);
-    check(&[0, 0, 4, 0, 0, 10], None, None);
-    check(&[0, 0, 4, 0, 0, 11], None, Some((0, 3)));
-
-    check(&[0, 0, 5, 0, 0, 8], Some(1), None);
-    check(&[0, 0, 5, 0, 0, 9], Some(1), None);
-    check(&[0, 0, 5, 0, 0, 10], Some(1), None);
-    check(&[0, 0, 5, 0, 0, 11], Some(1), Some((1, 3)));
-
-    check(&[0, 0, 6, 0, 0, 8], None, None);
-    check(&[0, 0, 6, 0, 0, 9], None, Some((2, 1)));
-    check(&[0, 0, 6, 0, 0, 10], None, None);
-    check(&[0, 0, 6, 0, 0, 11], None, Some((2, 3)));
-
-    check(&[0, 0, 7, 0, 0, 8], Some(3), Some((3, 1)));
-    check(&[0, 0, 7, 0, 0, 9], Some(3), None);
-    check(&[0, 0, 7, 0, 0, 10], Some(3), None);
-    check(&[0, 0, 7, 0, 0, 11], Some(3), None);
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    PostFlopGame::with_config(card_config, action_tree)
+        .map(|_| ())
+        .unwrap_err();
 }
 
 #[test]
-fn node_locking() {
+fn no_assignment() {
     let card_config = CardConfig {
-        range: ["AsAh,QsQh".parse().unwrap(), "KsKh".parse().unwrap()],
-        flop: flop_from_str("2s3h4d").unwrap(),
-        turn: card_from_str("6c").unwrap(),
-        river: card_from_str("7c").unwrap(),
+        range: ["TT".parse().unwrap(), "TT".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
     };
 
     let tree_config = TreeConfig {
-        initial_state: BoardState::River,
-        starting_pot: 20,
-        effective_stack: 10,
-        river_bet_sizes: [("a", "").try_into().unwrap(), ("a", "").try_into().unwrap()],
+        starting_pot: 60,
+        effective_stack: 970,
         ..Default::default()
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree);
+    assert!(game.is_err());
+}
 
-    game.allocate_memory(false);
-    game.play(1); // all-in
-    game.lock_current_strategy(&[0.25, 0.75]); // 25% fold, 75% call
-    game.back_to_root();
+#[test]
+fn dead_cards() {
+    let dead_cards = (1 << card_from_str("Kh").unwrap()) | (1 << card_from_str("Ks").unwrap());
+    let card_config = CardConfig {
+        range: ["AcAd".parse().unwrap(), "KcKd".parse().unwrap()],
+        flop: flop_from_str("2s3s4h").unwrap(),
+        dead_cards,
+        ..Default::default()
+    };
 
-    solve(&mut game, 1000, 0.0, false);
-    game.cache_normalized_weights();
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
 
-    let ev_oop = game.expected_values(0);
-    let ev_ip = game.expected_values(1);
-    assert!((ev_oop[0] - 0.0).abs() < 1e-2);
-    assert!((ev_oop[1] - 27.5).abs() < 5e-2);
-    assert!((ev_ip[0] - 6.25).abs() < 1e-2);
+    let mut game_alive = PostFlopGame::with_config(
+        CardConfig {
+            dead_cards: 0,
+            ..card_config.clone()
+        },
+        ActionTree::new(tree_config.clone()).unwrap(),
+    )
+    .unwrap();
 
-    let strategy_oop = game.strategy();
-    assert!((strategy_oop[0] - 1.0).abs() < 1e-3); // QQ check
-    assert!((strategy_oop[1] - 0.0).abs() < 1e-3); // AA check
-    assert!((strategy_oop[2] - 0.0).abs() < 1e-3); // QQ bet
-    assert!((strategy_oop[3] - 1.0).abs() < 1e-3); // AA bet
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
 
+    game_alive.allocate_memory(false);
     game.allocate_memory(false);
-    game.play(1); // all-in
-    game.lock_current_strategy(&[0.5, 0.5]); // 50% fold, 50% call
-    game.back_to_root();
+    finalize(&mut game_alive);
+    finalize(&mut game);
 
-    solve(&mut game, 1000, 0.0, false);
+    game_alive.cache_normalized_weights();
     game.cache_normalized_weights();
+    let equity_alive = game_alive.equity(1)[0];
+    let equity = game.equity(1)[0];
+    assert!(equity < equity_alive);
+    assert!((game.equity(0)[0] + equity - 1.0).abs() < 1e-5);
+    assert!((game.expected_values(1)[0] - 60.0 * equity).abs() < 1e-3);
 
-    let ev_oop = game.expected_values(0);
-    let ev_ip = game.expected_values(1);
-    assert!((ev_oop[0] - 5.0).abs() < 1e-2);
-    assert!((ev_oop[1] - 25.0).abs() < 5e-2);
-    assert!((ev_ip[0] - 5.0).abs() < 1e-2);
+    game.play(0);
+    game.play(0);
+    assert!(game.is_chance_node());
+    assert_eq!(game.possible_cards() & dead_cards, 0);
+    assert_eq!(game.possible_cards().count_ones(), 52 - 3 - 4 - 2);
 
-    let strategy_oop = game.strategy();
-    assert!((strategy_oop[0] - 0.0).abs() < 1e-3); // QQ check
-    assert!((strategy_oop[1] - 0.0).abs() < 1e-3); // AA check
-    assert!((strategy_oop[2] - 1.0).abs() < 1e-3); // QQ bet
-    assert!((strategy_oop[3] - 1.0).abs() < 1e-3); // AA bet
+    let mut card_config = card_config;
+    card_config.dead_cards = 1 << card_config.flop[0];
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    assert!(PostFlopGame::with_config(card_config, action_tree).is_err());
 }
 
 #[test]
-fn node_locking_partial() {
+fn short_deck() {
+    // the flush of OOP loses to the full house of IP only in the regular rankings
     let card_config = CardConfig {
-        range: ["AsAh,QsQh,JsJh".parse().unwrap(), "KsKh".parse().unwrap()],
-        flop: flop_from_str("2s3h4d").unwrap(),
-        turn: card_from_str("6c").unwrap(),
-        river: card_from_str("7c").unwrap(),
+        range: [
+            Range::from_short_deck_str("AhKh").unwrap(),
+            Range::from_short_deck_str("6c6d").unwrap(),
+        ],
+        flop: flop_from_str("Th9h6h").unwrap(),
+        turn: card_from_str("Ts").unwrap(),
+        river: card_from_str("9c").unwrap(),
+        short_deck: true,
+        ..Default::default()
     };
 
     let tree_config = TreeConfig {
         initial_state: BoardState::River,
-        starting_pot: 10,
-        effective_stack: 10,
-        river_bet_sizes: [("a", "").try_into().unwrap(), ("a", "").try_into().unwrap()],
+        starting_pot: 60,
+        effective_stack: 970,
         ..Default::default()
     };
 
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let mut game_regular = PostFlopGame::with_config(
+        CardConfig {
+            short_deck: false,
+            positions: [None; 2],
+            ..card_config.clone()
+        },
+        ActionTree::new(tree_config.clone()).unwrap(),
+    )
+    .unwrap();
 
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+
+    game_regular.allocate_memory(false);
     game.allocate_memory(false);
-    game.lock_current_strategy(&[0.8, 0.0, 0.0, 0.2, 0.0, 0.0]); // JJ -> 80% check, 20% all-in
+    finalize(&mut game_regular);
+    finalize(&mut game);
 
-    solve(&mut game, 1000, 0.0, false);
+    game_regular.cache_normalized_weights();
     game.cache_normalized_weights();
+    assert_eq!(game_regular.equity(0), [0.0]);
+    assert_eq!(game.equity(0), [1.0]);
+
+    #[cfg(feature = "bincode")]
+    {
+        let mut buffer = Vec::new();
+        crate::save_data_into_std_write(&game, "", &mut buffer, None).unwrap();
+        let (mut loaded, _): (PostFlopGame, _) =
+            crate::load_data_from_std_read(&mut buffer.as_slice(), None).unwrap();
+        assert!(loaded.card_config().short_deck);
+        loaded.cache_normalized_weights();
+        assert_eq!(loaded.equity(0), [1.0]);
+    }
+
+    // only the cards of ranks 6-A are dealt
+    let flop_config = CardConfig {
+        turn: NOT_DEALT,
+        river: NOT_DEALT,
+        ..card_config.clone()
+    };
+    let flop_tree_config = TreeConfig {
+        initial_state: BoardState::Flop,
+        ..tree_config.clone()
+    };
+    let action_tree = ActionTree::new(flop_tree_config).unwrap();
+    let mut flop_game = PostFlopGame::with_config(flop_config, action_tree).unwrap();
+    flop_game.allocate_memory(false);
+    flop_game.play(0);
+    flop_game.play(0);
+    assert!(flop_game.is_chance_node());
+    assert_eq!(flop_game.possible_cards() & SHORT_DECK_DEAD_CARDS, 0);
+    assert_eq!(flop_game.possible_cards().count_ones(), 36 - 3 - 4);
+
+    // the removed cards are rejected
+    let invalid_configs = [
+        CardConfig {
+            river: card_from_str("5c").unwrap(),
+            ..card_config.clone()
+        },
+        CardConfig {
+            range: ["AhKh,A5s".parse().unwrap(), card_config.range[1]],
+            ..card_config
+        },
+    ];
+    for config in invalid_configs {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        assert!(PostFlopGame::with_config(config, action_tree).is_err());
+    }
+}
 
-    let ev_oop = game.expected_values(0);
-    let ev_ip = game.expected_values(1);
-    assert!((ev_oop[0] - 0.0).abs() < 1e-2);
-    assert!((ev_oop[1] - 0.0).abs() < 1e-2);
-    assert!((ev_oop[2] - 15.0).abs() < 5e-2);
-    assert!((ev_ip[0] - 5.0).abs() < 1e-2);
+#[test]
+fn hand_evaluator() {
+    struct ReversedEvaluator;
 
-    let strategy_oop = game.strategy();
-    assert!((strategy_oop[0] - 0.8).abs() < 1e-3); // JJ check
-    assert!((strategy_oop[1] - 0.7).abs() < 1e-3); // QQ check
-    assert!((strategy_oop[2] - 0.0).abs() < 1e-3); // AA check
-    assert!((strategy_oop[3] - 0.2).abs() < 1e-3); // JJ bet
-    assert!((strategy_oop[4] - 0.3).abs() < 1e-3); // QQ bet
-    assert!((strategy_oop[5] - 1.0).abs() < 1e-3); // AA bet
+    impl HandEvaluator for ReversedEvaluator {
+        fn evaluate(&self, board: &[Card; 5], hand: (Card, Card)) -> u32 {
+            u32::MAX - HoldemEvaluator.evaluate(board, hand)
+        }
+    }
+
+    let card_config = CardConfig {
+        range: ["AhKh".parse().unwrap(), "6c6d".parse().unwrap()],
+        flop: flop_from_str("Th9h6h").unwrap(),
+        turn: card_from_str("Ts").unwrap(),
+        river: card_from_str("9c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let evaluators: [(Option<Arc<dyn HandEvaluator>>, f32); 3] = [
+        (Some(Arc::new(ReversedEvaluator)), 1.0),
+        (Some(Arc::new(ShortDeckEvaluator)), 1.0),
+        (None, 0.0),
+    ];
+    for (evaluator, expected) in evaluators {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.set_hand_evaluator(evaluator).unwrap();
+        game.allocate_memory(false);
+        finalize(&mut game);
+        game.cache_normalized_weights();
+        assert_eq!(game.equity(0), [expected]);
+    }
 }
 
 #[test]
-fn node_locking_isomorphism() {
+fn split_pot_evaluator() {
     let card_config = CardConfig {
-        range: ["AKs".parse().unwrap(), "AKs".parse().unwrap()],
-        flop: flop_from_str("2c3c4c").unwrap(),
+        range: [
+            "KK,QQ,A2s,A3,86s,54s".parse().unwrap(),
+            "AA,A4,43s,KQ,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("2c5d7h").unwrap(),
+        turn: card_from_str("Ks").unwrap(),
+        river: card_from_str("8d").unwrap(),
         ..Default::default()
     };
 
     let tree_config = TreeConfig {
-        starting_pot: 10,
-        effective_stack: 10,
-        river_bet_sizes: [("a", "").try_into().unwrap(), Default::default()],
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
         ..Default::default()
     };
 
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
-
+    // check-down: the values are the shares of the pot
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.set_split_pot_evaluator(Some(Arc::new(HoldemHiLoEvaluator)))
+        .unwrap();
     game.allocate_memory(false);
-    game.apply_history(&[0, 0, 15, 0, 0, 14]); // Turn: Spades, River: Hearts
-    game.lock_current_strategy(&[0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0]); // AhKh -> check
-
     finalize(&mut game);
+    game.cache_normalized_weights();
 
-    game.apply_history(&[0, 0, 13, 0, 0, 14]);
-    assert_eq!(
-        game.strategy(),
-        vec![0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5]
-    );
-
-    game.apply_history(&[0, 0, 13, 0, 0, 15]);
-    assert_eq!(
-        game.strategy(),
-        vec![0.5, 0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0]
-    );
-
-    game.apply_history(&[0, 0, 14, 0, 0, 13]);
-    assert_eq!(
-        game.strategy(),
-        vec![0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5, 0.5]
-    );
-
-    game.apply_history(&[0, 0, 14, 0, 0, 15]);
-    assert_eq!(
-        game.strategy(),
-        vec![0.5, 0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0]
-    );
-
-    game.apply_history(&[0, 0, 15, 0, 0, 13]);
-    assert_eq!(
-        game.strategy(),
-        vec![0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5, 0.5]
-    );
+    let board = board_from_str("2c5d7hKs8d").unwrap().try_into().unwrap();
+    for player in 0..2 {
+        let equity = game.equity(player);
+        let expected_values = game.expected_values(player);
+        for (i, &(c1, c2)) in game.private_cards(player).iter().enumerate() {
+            let (mut share_sum, mut count) = (0.0, 0);
+            for &(d1, d2) in game.private_cards(player ^ 1) {
+                if [c1, c2].contains(&d1) || [c1, c2].contains(&d2) {
+                    continue;
+                }
+                share_sum += hi_lo_share(&HoldemHiLoEvaluator, &board, [&[c1, c2], &[d1, d2]]);
+                count += 1;
+            }
+            let expected = (share_sum / count as f64) as f32;
+            assert!((equity[i] - expected).abs() < 1e-5);
+            assert!((expected_values[i] - 60.0 * expected).abs() < 1e-3);
+        }
+    }
+
+    // the showdowns of the whole pot are restored without the split-pot evaluator
+    let equity = game.equity(0);
+    game.set_split_pot_evaluator(None).unwrap();
+    assert!(game.equity(0) != equity);
+
+    // solving with bets
+    let tree_config = TreeConfig {
+        river_bet_sizes: [
+            ("50%, a", "60%").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..tree_config
+    };
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.set_split_pot_evaluator(Some(Arc::new(HoldemHiLoEvaluator)))
+        .unwrap();
+    game.allocate_memory(false);
+    let exploitability = solve(&mut game, 1000, 0.001 * 60.0, false);
+    assert!(exploitability <= 0.001 * 60.0);
 
-    game.apply_history(&[0, 0, 15, 0, 0, 14]);
-    assert_eq!(
-        game.strategy(),
-        vec![0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5]
-    );
+    // no rake: the values of the players sum to the pot
+    game.cache_normalized_weights();
+    let ev = [0, 1].map(|player| {
+        compute_average(
+            &game.expected_values(player),
+            game.normalized_weights(player),
+        )
+    });
+    assert!((ev[0] + ev[1] - 60.0).abs() < 1e-2);
+    assert!(matches!(
+        game.run_it_n(2),
+        Err(SolverError::InvalidState(_))
+    ));
+
+    // the split-pot showdowns are not combined with the ICM and bunching payoffs
+    let action_tree = ActionTree::new(TreeConfig {
+        icm_config: Some(ICMConfig {
+            player_stacks: [1000, 1000],
+            other_players_stacks: vec![500],
+            payout_structure: vec![60, 40],
+        }),
+        ..tree_config
+    })
+    .unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_split_pot_evaluator(Some(Arc::new(HoldemHiLoEvaluator)))
+        .unwrap_err();
+    game.set_split_pot_evaluator(None).unwrap();
 }
 
 #[test]
-fn set_bunching_effect() {
-    let flop = flop_from_str("Td9d6h").unwrap();
+fn run_it_n() {
     let card_config = CardConfig {
-        flop,
-        range: [Range::ones(); 2],
-        turn: card_from_str("Qc").unwrap(),
+        range: ["AcAd".parse().unwrap(), "KcKd".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
         ..Default::default()
     };
 
     let tree_config = TreeConfig {
-        initial_state: BoardState::Turn,
         starting_pot: 60,
         effective_stack: 970,
-        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        flop_bet_sizes: [("a", "").try_into().unwrap(), Default::default()],
         ..Default::default()
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
     let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert!(matches!(
+        game.run_it_n(2),
+        Err(SolverError::InvalidState(_))
+    ));
 
-    let co_range = "33:0.59,22:0.635,A8o:0.265,A7o-A6o,A5o:0.445,A4o-A2o,K2s,K9o:0.905,K8o-K2o,Q4s-Q2s,Q9o-Q2o,J6s-J2s,J9o:0.88,J8o-J2o,T7s:0.405,T6s-T2s,T9o:0.96,T8o-T2o,96s-92s,92o+,86s:0.57,85s-82s,82o+,76s:0.37,75s-72s,72o+,65s:0.475,64s-62s,62o+,54s:0.68,53s-52s,52o+,42+,32";
-    let sb_range = "66:0.46,55:0.821,44:0.92,33:0.93,22:0.925,A6s:0.73,A3s:0.47,A2s,ATo:0.105,A9o-A2o,K8s:0.795,K7s,K6s:0.85,K5s:0.965,K4s-K2s,KJo:0.085,KTo:0.645,K9o-K2o,Q8s-Q2s,QJo:0.765,QTo-Q2o,J8s-J2s,J2o+,T8s:0.69,T7s-T2s,T2o+,98s:0.905,97s-92s,92o+,87s:0.78,86s-82s,82o+,76s:0.77,75s-72s,72o+,65s:0.845,64s-62s,62o+,54s:0.735,53s-52s,52o+,42+,32";
-
-    let mut bunching_data = BunchingData::new(
-        &[co_range.parse().unwrap(), sb_range.parse().unwrap()],
-        flop,
-    )
-    .unwrap();
+    game.play(1); // all-in
+    game.play(1); // call
+    assert!(game.is_terminal_node());
+    assert!(matches!(
+        game.run_it_n(0),
+        Err(SolverError::InvalidArgument(_))
+    ));
+
+    // the share of OOP in each runout
+    let board = game.current_board();
+    let hands = [game.private_cards(0)[0], game.private_cards(1)[0]];
+    let used = board
+        .iter()
+        .chain([hands[0].0, hands[0].1, hands[1].0, hands[1].1].iter())
+        .fold(0u64, |mask, &card| mask | (1 << card));
+    let deck = (0..52)
+        .filter(|&c| used & (1 << c) == 0)
+        .collect::<Vec<_>>();
+    let mut runouts = Vec::new();
+    for (i, &turn) in deck.iter().enumerate() {
+        for &river in &deck[i + 1..] {
+            let full_board = [board[0], board[1], board[2], turn, river];
+            let oop = HoldemEvaluator.evaluate(&full_board, hands[0]);
+            let ip = HoldemEvaluator.evaluate(&full_board, hands[1]);
+            let share = match oop.cmp(&ip) {
+                std::cmp::Ordering::Greater => 1.0,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Less => 0.0,
+            };
+            runouts.push(((1u64 << turn) | (1 << river), share));
+        }
+    }
+
+    let n = runouts.len() as f64;
+    let mean = runouts.iter().map(|r| r.1).sum::<f64>() / n;
+    let variance = runouts.iter().map(|r| r.1 * r.1).sum::<f64>() / n - mean * mean;
+
+    // run it twice by brute force
+    let mut num_pairs = 0.0;
+    let mut second_moment = 0.0;
+    for &(mask1, share1) in &runouts {
+        for &(mask2, share2) in &runouts {
+            if mask1 & mask2 == 0 {
+                let share: f64 = 0.5 * (share1 + share2);
+                num_pairs += 1.0;
+                second_moment += share * share;
+            }
+        }
+    }
+    let variance_twice = second_moment / num_pairs - mean * mean;
+
+    let once = game.run_it_n(1).unwrap();
+    let twice = game.run_it_n(2).unwrap();
+    assert_eq!(once.pot, 60 + 2 * 970);
+    assert!((once.equity[0][0] as f64 - mean).abs() < 1e-6);
+    assert!((twice.equity[0][0] as f64 - mean).abs() < 1e-6);
+    assert!((once.equity[1][0] as f64 - (1.0 - mean)).abs() < 1e-6);
+    assert!((once.std_dev[0][0] as f64 - variance.sqrt()).abs() < 1e-6);
+    assert!((twice.std_dev[0][0] as f64 - variance_twice.sqrt()).abs() < 1e-6);
+    assert!((twice.std_dev[1][0] - twice.std_dev[0][0]).abs() < 1e-6);
+    assert!(twice.std_dev[0][0] < once.std_dev[0][0]);
+}
 
-    bunching_data.process(false);
-    game.set_bunching_effect(&bunching_data).unwrap();
+#[test]
+fn equity_cache() {
+    let card_config = CardConfig {
+        range: ["AA,KK".parse().unwrap(), "QQ,AKs".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
 
-    game.allocate_memory(false);
-    finalize(&mut game);
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [("a", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
 
-    let current_ev = compute_current_ev(&game);
-    assert!((current_ev[0] - 7.5).abs() < 1e-4);
-    assert!((current_ev[1] - -7.5).abs() < 1e-4);
+    assert!(EquityCache::new(0).is_err());
+    assert!(EquityCache::global().ptr_eq(&EquityCache::global()));
+    let cache = EquityCache::new(1).unwrap();
 
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let root_equity_oop = compute_average(&game.equity(0), weights_oop);
-    let root_equity_ip = compute_average(&game.equity(1), weights_ip);
-    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut reference = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    reference.allocate_memory(false);
+    reference.apply_history(&[1, 1]);
+    let expected = reference.run_it_n(2).unwrap();
 
-    assert!((root_equity_oop - 0.5).abs() < 1e-5);
-    assert!((root_equity_ip - 0.5).abs() < 1e-5);
-    assert!((root_ev_oop - 37.5).abs() < 1e-4);
-    assert!((root_ev_ip - 22.5).abs() < 1e-4);
+    let mut game = PostFlopGame::with_shared_tree(card_config.clone(), &reference).unwrap();
+    game.set_equity_cache(Some(cache.clone()));
+    game.allocate_memory(false);
+    game.apply_history(&[1, 1]);
+    assert_eq!(game.run_it_n(2).unwrap(), expected);
+    assert_eq!(cache.stats(), (0, 1));
+
+    // the shared instance inherits the cache and hits it
+    let mut shared = PostFlopGame::with_shared_tree(card_config.clone(), &game).unwrap();
+    assert!(shared.equity_cache().unwrap().ptr_eq(&cache));
+    shared.allocate_memory(false);
+    shared.apply_history(&[1, 1]);
+    assert_eq!(shared.run_it_n(2).unwrap(), expected);
+    assert_eq!(cache.stats(), (1, 1));
+
+    // a different number of runs evicts the oldest entry
+    assert_eq!(shared.run_it_n(1).unwrap().equity, expected.equity);
+    assert_eq!(cache.stats(), (1, 2));
+    assert_eq!(cache.len(), 1);
+
+    // the cache is saved and loaded as a file
+    #[cfg(feature = "bincode")]
+    {
+        let mut bytes = Vec::new();
+        crate::save_data_into_std_write(&cache, "", &mut bytes, None).unwrap();
+        let loaded: EquityCache = crate::load_data_from_std_read(&mut bytes.as_slice(), None)
+            .unwrap()
+            .0;
+        assert_eq!((loaded.len(), loaded.capacity()), (1, 1));
+        assert_eq!(loaded.memory_usage(), cache.memory_usage());
+        shared.set_equity_cache(Some(loaded.clone()));
+        let once = reference.run_it_n(1).unwrap();
+        assert_eq!(shared.run_it_n(1).unwrap(), once);
+        assert_eq!(loaded.stats(), (1, 0));
+    }
+
+    cache.clear();
+    assert!(cache.is_empty());
 }
 
 #[test]
-fn set_bunching_effect_always_win() {
-    let flop = flop_from_str("AcAdKh").unwrap();
-    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
-
+fn possible_cards_with_dead_cards() {
     let card_config = CardConfig {
-        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
-        flop,
+        range: ["AsAh".parse().unwrap(), "KsKh,KdKc".parse().unwrap()],
+        flop: flop_from_str("2c3d4h").unwrap(),
         ..Default::default()
     };
 
@@ -1084,167 +1513,6289 @@ fn set_bunching_effect_always_win() {
 
     let action_tree = ActionTree::new(tree_config).unwrap();
     let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
-
-    let co_range = "33:0.59,22:0.635,A8o:0.265,A7o-A6o,A5o:0.445,A4o-A2o,K2s,K9o:0.905,K8o-K2o,Q4s-Q2s,Q9o-Q2o,J6s-J2s,J9o:0.88,J8o-J2o,T7s:0.405,T6s-T2s,T9o:0.96,T8o-T2o,96s-92s,92o+,86s:0.57,85s-82s,82o+,76s:0.37,75s-72s,72o+,65s:0.475,64s-62s,62o+,54s:0.68,53s-52s,52o+,42+,32";
-    let sb_range = "66:0.46,55:0.821,44:0.92,33:0.93,22:0.925,A6s:0.73,A3s:0.47,A2s,ATo:0.105,A9o-A2o,K8s:0.795,K7s,K6s:0.85,K5s:0.965,K4s-K2s,KJo:0.085,KTo:0.645,K9o-K2o,Q8s-Q2s,QJo:0.765,QTo-Q2o,J8s-J2s,J2o+,T8s:0.69,T7s-T2s,T2o+,98s:0.905,97s-92s,92o+,87s:0.78,86s-82s,82o+,76s:0.77,75s-72s,72o+,65s:0.845,64s-62s,62o+,54s:0.735,53s-52s,52o+,42+,32";
-
-    let mut bunching_data = BunchingData::new(
-        &[co_range.parse().unwrap(), sb_range.parse().unwrap()],
-        flop,
-    )
-    .unwrap();
-
-    bunching_data.process(false);
-    game.set_bunching_effect(&bunching_data).unwrap();
-
     game.allocate_memory(false);
-    finalize(&mut game);
-
-    let current_ev = compute_current_ev(&game);
-    assert!((current_ev[0] - 30.0).abs() < 1e-4);
-    assert!((current_ev[1] - -30.0).abs() < 1e-4);
 
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let equity_oop = compute_average(&game.equity(0), weights_oop);
-    let equity_ip = compute_average(&game.equity(1), weights_ip);
-    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
-    assert!((equity_oop - 1.0).abs() < 1e-5);
-    assert!((equity_ip - 0.0).abs() < 1e-5);
-    assert!((ev_oop - 60.0).abs() < 1e-4);
-    assert!((ev_ip - 0.0).abs() < 1e-4);
+    let card_ks = card_from_str("Ks").unwrap();
+    let card_kd = card_from_str("Kd").unwrap();
+    let card_qs = card_from_str("Qs").unwrap();
 
     game.play(0);
     game.play(0);
     assert!(game.is_chance_node());
-    game.play(usize::MAX);
-    game.play(0);
-    game.play(0);
-    assert!(game.is_chance_node());
-    game.play(usize::MAX);
-    game.play(0);
-    game.play(0);
-    assert!(game.is_terminal_node());
 
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let equity_oop = compute_average(&game.equity(0), weights_oop);
-    let equity_ip = compute_average(&game.equity(1), weights_ip);
-    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
-    assert!((equity_oop - 1.0).abs() < 1e-5);
-    assert!((equity_ip - 0.0).abs() < 1e-5);
-    assert!((ev_oop - 60.0).abs() < 1e-4);
-    assert!((ev_ip - 0.0).abs() < 1e-4);
+    let possible_cards = game.possible_cards();
+    assert_eq!(possible_cards, game.possible_cards_with_dead_cards(0));
+    assert_ne!(possible_cards & (1 << card_ks), 0);
+
+    // KdKc is no longer a valid IP hand, so Ks cannot be dealt
+    let possible_cards = game.possible_cards_with_dead_cards(1 << card_kd);
+    assert_eq!(possible_cards & ((1 << card_kd) | (1 << card_ks)), 0);
+
+    let possible_cards = game.possible_cards_with_dead_cards(1 << card_qs);
+    assert_eq!(possible_cards, game.possible_cards() & !(1 << card_qs));
 }
 
 #[test]
-#[ignore]
-fn solve_pio_preset_normal() {
-    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
-    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
-
+fn remove_lines() {
+    use crate::bet_size::BetSizeOptions;
     let card_config = CardConfig {
-        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
-        flop: flop_from_str("QsJh2h").unwrap(),
+        range: ["TT+,AKo,AQs+".parse().unwrap(), "AA".parse().unwrap()],
+        flop: flop_from_str("2c6dTh").unwrap(),
         ..Default::default()
     };
 
+    // simple tree: force checks on flop, and only use 1/2 pot bets on turn and river
     let tree_config = TreeConfig {
-        starting_pot: 180,
-        effective_stack: 910,
-        flop_bet_sizes: [
-            ("52%", "45%").try_into().unwrap(),
-            ("52%", "45%").try_into().unwrap(),
-        ],
+        starting_pot: 60,
+        effective_stack: 970,
         turn_bet_sizes: [
-            ("55%", "45%").try_into().unwrap(),
-            ("55%", "45%").try_into().unwrap(),
+            BetSizeOptions::try_from(("50%", "")).unwrap(),
+            Default::default(),
         ],
         river_bet_sizes: [
-            ("70%", "45%").try_into().unwrap(),
-            ("70%", "45%").try_into().unwrap(),
+            BetSizeOptions::try_from(("50%", "")).unwrap(),
+            Default::default(),
         ],
         ..Default::default()
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
     let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
-    println!(
-        "memory usage: {:.2}GB",
-        game.memory_usage().0 as f64 / (1024.0 * 1024.0 * 1024.0)
-    );
+
+    let lines = vec![
+        vec![
+            Action::Check,
+            Action::Check,
+            Action::Chance(2),
+            Action::Check,
+        ],
+        vec![
+            Action::Check,
+            Action::Check,
+            Action::Chance(2),
+            Action::Bet(30),
+            Action::Call,
+            Action::Chance(3),
+            Action::Bet(60),
+        ],
+    ];
+
+    let res = game.remove_lines(&lines);
+    res.unwrap();
+
     game.allocate_memory(false);
 
-    solve(&mut game, 1000, 180.0 * 0.001, true);
+    // check that the turn line is removed
+    game.apply_history(&[0, 0, 2]);
+    assert_eq!(game.available_actions(), vec![Action::Bet(30)]);
 
+    // check that other turn lines are correct
+    game.apply_history(&[0, 0, 3]);
+    assert_eq!(
+        game.available_actions(),
+        vec![Action::Check, Action::Bet(30)]
+    );
+
+    // check that the river line is removed
+    game.apply_history(&[0, 0, 2, 0, 1, 3]);
+    assert_eq!(game.available_actions(), vec![Action::Check]);
+
+    // check that other river lines are correct
+    game.apply_history(&[0, 0, 2, 0, 1, 4]);
+    assert_eq!(
+        game.available_actions(),
+        vec![Action::Check, Action::Bet(60)]
+    );
+
+    game.apply_history(&[0, 0, 3, 1, 1, 4]);
+    assert_eq!(
+        game.available_actions(),
+        vec![Action::Check, Action::Bet(60)]
+    );
+
+    // check that `solve()` does not crash
+    solve(&mut game, 10, 0.01, false);
+}
+
+#[test]
+fn isomorphism_monotone() {
+    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
+    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
+
+    let card_config = CardConfig {
+        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
+        flop: flop_from_str("QhJh2h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 100,
+        effective_stack: 100,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    let mut check = |history: &[usize],
+                     expected_turn_swap: Option<u8>,
+                     expected_river_swap: Option<(u8, u8)>| {
+        game.apply_history(history);
+        game.cache_normalized_weights();
+        let weights = game.normalized_weights(0);
+        let ev = game.expected_values(0);
+        weights.iter().zip(ev.iter()).for_each(|(&w, &v)| {
+            assert!(!(w > 0.0 && v == 50.0));
+        });
+        assert_eq!(game.turn_swap, expected_turn_swap);
+        assert_eq!(game.river_swap, expected_river_swap);
+    };
+
+    check(&[0, 0, 4], None, None);
+    check(&[0, 0, 5], Some(1), None);
+    check(&[0, 0, 6], None, None);
+    check(&[0, 0, 7], Some(3), None);
+
+    check(&[0, 0, 4, 0, 0, 8], None, None);
+    check(&[0, 0, 4, 0, 0, 9], None, None);
+    check(&[0, 0, 4, 0, 0, 10], None, None);
+    check(&[0, 0, 4, 0, 0, 11], None, Some((0, 3)));
+
+    check(&[0, 0, 5, 0, 0, 8], Some(1), None);
+    check(&[0, 0, 5, 0, 0, 9], Some(1), None);
+    check(&[0, 0, 5, 0, 0, 10], Some(1), None);
+    check(&[0, 0, 5, 0, 0, 11], Some(1), Some((1, 3)));
+
+    check(&[0, 0, 6, 0, 0, 8], None, None);
+    check(&[0, 0, 6, 0, 0, 9], None, Some((2, 1)));
+    check(&[0, 0, 6, 0, 0, 10], None, None);
+    check(&[0, 0, 6, 0, 0, 11], None, Some((2, 3)));
+
+    check(&[0, 0, 7, 0, 0, 8], Some(3), Some((3, 1)));
+    check(&[0, 0, 7, 0, 0, 9], Some(3), None);
+    check(&[0, 0, 7, 0, 0, 10], Some(3), None);
+    check(&[0, 0, 7, 0, 0, 11], Some(3), None);
+}
+
+#[test]
+fn isomorphism_disabled() {
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("QhJh2h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 100,
+        effective_stack: 100,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game_full = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    assert_eq!(game.isomorphism_mode(), IsomorphismMode::Full);
+    let mem_usage_full = game.memory_usage().0;
+    game.set_isomorphism_mode(IsomorphismMode::Disabled)
+        .unwrap();
+    assert_eq!(game.isomorphism_mode(), IsomorphismMode::Disabled);
+    assert!(game.memory_usage().0 > mem_usage_full);
+
+    game_full.allocate_memory(false);
+    game.allocate_memory(false);
+    assert!(game.set_isomorphism_mode(IsomorphismMode::Full).is_err());
+    finalize(&mut game_full);
+    finalize(&mut game);
+
+    for history in [[0, 0, 5, 0, 0, 8], [0, 0, 7, 0, 0, 9], [0, 0, 6, 0, 0, 11]] {
+        game_full.apply_history(&history);
+        game.apply_history(&history);
+        assert_eq!(game.turn_swap, None);
+        assert_eq!(game.river_swap, None);
+
+        game_full.cache_normalized_weights();
+        game.cache_normalized_weights();
+        let ev_full = game_full.expected_values(0);
+        let ev = game.expected_values(0);
+        ev_full.iter().zip(ev.iter()).for_each(|(&x, &y)| {
+            assert!((x - y).abs() < 1e-4);
+        });
+    }
+}
+
+#[test]
+fn node_locking() {
+    let card_config = CardConfig {
+        range: ["AsAh,QsQh".parse().unwrap(), "KsKh".parse().unwrap()],
+        flop: flop_from_str("2s3h4d").unwrap(),
+        turn: card_from_str("6c").unwrap(),
+        river: card_from_str("7c").unwrap(),
+        dead_cards: 0,
+        short_deck: false,
+        positions: [None; 2],
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 20,
+        effective_stack: 10,
+        river_bet_sizes: [("a", "").try_into().unwrap(), ("a", "").try_into().unwrap()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    game.play(1); // all-in
+    game.lock_current_strategy(&[0.25, 0.75]); // 25% fold, 75% call
+    game.back_to_root();
+
+    solve(&mut game, 1000, 0.0, false);
+    game.cache_normalized_weights();
+
+    let ev_oop = game.expected_values(0);
+    let ev_ip = game.expected_values(1);
+    assert!((ev_oop[0] - 0.0).abs() < 1e-2);
+    assert!((ev_oop[1] - 27.5).abs() < 5e-2);
+    assert!((ev_ip[0] - 6.25).abs() < 1e-2);
+
+    let strategy_oop = game.strategy();
+    assert!((strategy_oop[0] - 1.0).abs() < 1e-3); // QQ check
+    assert!((strategy_oop[1] - 0.0).abs() < 1e-3); // AA check
+    assert!((strategy_oop[2] - 0.0).abs() < 1e-3); // QQ bet
+    assert!((strategy_oop[3] - 1.0).abs() < 1e-3); // AA bet
+
+    game.allocate_memory(false);
+    game.play(1); // all-in
+    game.lock_current_strategy(&[0.5, 0.5]); // 50% fold, 50% call
+    game.back_to_root();
+
+    solve(&mut game, 1000, 0.0, false);
+    game.cache_normalized_weights();
+
+    let ev_oop = game.expected_values(0);
+    let ev_ip = game.expected_values(1);
+    assert!((ev_oop[0] - 5.0).abs() < 1e-2);
+    assert!((ev_oop[1] - 25.0).abs() < 5e-2);
+    assert!((ev_ip[0] - 5.0).abs() < 1e-2);
+
+    let strategy_oop = game.strategy();
+    assert!((strategy_oop[0] - 0.0).abs() < 1e-3); // QQ check
+    assert!((strategy_oop[1] - 0.0).abs() < 1e-3); // AA check
+    assert!((strategy_oop[2] - 1.0).abs() < 1e-3); // QQ bet
+    assert!((strategy_oop[3] - 1.0).abs() < 1e-3); // AA bet
+}
+
+#[test]
+fn node_locking_partial() {
+    let card_config = CardConfig {
+        range: ["AsAh,QsQh,JsJh".parse().unwrap(), "KsKh".parse().unwrap()],
+        flop: flop_from_str("2s3h4d").unwrap(),
+        turn: card_from_str("6c").unwrap(),
+        river: card_from_str("7c").unwrap(),
+        dead_cards: 0,
+        short_deck: false,
+        positions: [None; 2],
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 10,
+        effective_stack: 10,
+        river_bet_sizes: [("a", "").try_into().unwrap(), ("a", "").try_into().unwrap()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    game.lock_current_strategy(&[0.8, 0.0, 0.0, 0.2, 0.0, 0.0]); // JJ -> 80% check, 20% all-in
+
+    solve(&mut game, 1000, 0.0, false);
+    game.cache_normalized_weights();
+
+    let ev_oop = game.expected_values(0);
+    let ev_ip = game.expected_values(1);
+    assert!((ev_oop[0] - 0.0).abs() < 1e-2);
+    assert!((ev_oop[1] - 0.0).abs() < 1e-2);
+    assert!((ev_oop[2] - 15.0).abs() < 5e-2);
+    assert!((ev_ip[0] - 5.0).abs() < 1e-2);
+
+    let strategy_oop = game.strategy();
+    assert!((strategy_oop[0] - 0.8).abs() < 1e-3); // JJ check
+    assert!((strategy_oop[1] - 0.7).abs() < 1e-3); // QQ check
+    assert!((strategy_oop[2] - 0.0).abs() < 1e-3); // AA check
+    assert!((strategy_oop[3] - 0.2).abs() < 1e-3); // JJ bet
+    assert!((strategy_oop[4] - 0.3).abs() < 1e-3); // QQ bet
+    assert!((strategy_oop[5] - 1.0).abs() < 1e-3); // AA bet
+}
+
+#[test]
+fn node_locking_isomorphism() {
+    let card_config = CardConfig {
+        range: ["AKs".parse().unwrap(), "AKs".parse().unwrap()],
+        flop: flop_from_str("2c3c4c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 10,
+        effective_stack: 10,
+        river_bet_sizes: [("a", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    game.apply_history(&[0, 0, 15, 0, 0, 14]); // Turn: Spades, River: Hearts
+    game.lock_current_strategy(&[0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0]); // AhKh -> check
+
+    finalize(&mut game);
+
+    game.apply_history(&[0, 0, 13, 0, 0, 14]);
+    assert_eq!(
+        game.strategy(),
+        vec![0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5]
+    );
+
+    game.apply_history(&[0, 0, 13, 0, 0, 15]);
+    assert_eq!(
+        game.strategy(),
+        vec![0.5, 0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0]
+    );
+
+    game.apply_history(&[0, 0, 14, 0, 0, 13]);
+    assert_eq!(
+        game.strategy(),
+        vec![0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5, 0.5]
+    );
+
+    game.apply_history(&[0, 0, 14, 0, 0, 15]);
+    assert_eq!(
+        game.strategy(),
+        vec![0.5, 0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0]
+    );
+
+    game.apply_history(&[0, 0, 15, 0, 0, 13]);
+    assert_eq!(
+        game.strategy(),
+        vec![0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5, 0.5]
+    );
+
+    game.apply_history(&[0, 0, 15, 0, 0, 14]);
+    assert_eq!(
+        game.strategy(),
+        vec![0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5]
+    );
+}
+
+#[test]
+fn set_bunching_effect() {
+    let flop = flop_from_str("Td9d6h").unwrap();
+    let card_config = CardConfig {
+        flop,
+        range: [Range::ones(); 2],
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let co_range = "33:0.59,22:0.635,A8o:0.265,A7o-A6o,A5o:0.445,A4o-A2o,K2s,K9o:0.905,K8o-K2o,Q4s-Q2s,Q9o-Q2o,J6s-J2s,J9o:0.88,J8o-J2o,T7s:0.405,T6s-T2s,T9o:0.96,T8o-T2o,96s-92s,92o+,86s:0.57,85s-82s,82o+,76s:0.37,75s-72s,72o+,65s:0.475,64s-62s,62o+,54s:0.68,53s-52s,52o+,42+,32";
+    let sb_range = "66:0.46,55:0.821,44:0.92,33:0.93,22:0.925,A6s:0.73,A3s:0.47,A2s,ATo:0.105,A9o-A2o,K8s:0.795,K7s,K6s:0.85,K5s:0.965,K4s-K2s,KJo:0.085,KTo:0.645,K9o-K2o,Q8s-Q2s,QJo:0.765,QTo-Q2o,J8s-J2s,J2o+,T8s:0.69,T7s-T2s,T2o+,98s:0.905,97s-92s,92o+,87s:0.78,86s-82s,82o+,76s:0.77,75s-72s,72o+,65s:0.845,64s-62s,62o+,54s:0.735,53s-52s,52o+,42+,32";
+
+    let mut bunching_data = BunchingData::new(
+        &[co_range.parse().unwrap(), sb_range.parse().unwrap()],
+        flop,
+    )
+    .unwrap();
+
+    bunching_data.process(false);
+    game.set_bunching_effect(&bunching_data).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    let current_ev = compute_current_ev(&game);
+    assert!((current_ev[0] - 7.5).abs() < 1e-4);
+    assert!((current_ev[1] - -7.5).abs() < 1e-4);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let root_equity_oop = compute_average(&game.equity(0), weights_oop);
+    let root_equity_ip = compute_average(&game.equity(1), weights_ip);
+    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+    assert!((root_equity_oop - 0.5).abs() < 1e-5);
+    assert!((root_equity_ip - 0.5).abs() < 1e-5);
+    assert!((root_ev_oop - 37.5).abs() < 1e-4);
+    assert!((root_ev_ip - 22.5).abs() < 1e-4);
+}
+
+#[test]
+fn set_bunching_effect_always_win() {
+    let flop = flop_from_str("AcAdKh").unwrap();
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop,
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let co_range = "33:0.59,22:0.635,A8o:0.265,A7o-A6o,A5o:0.445,A4o-A2o,K2s,K9o:0.905,K8o-K2o,Q4s-Q2s,Q9o-Q2o,J6s-J2s,J9o:0.88,J8o-J2o,T7s:0.405,T6s-T2s,T9o:0.96,T8o-T2o,96s-92s,92o+,86s:0.57,85s-82s,82o+,76s:0.37,75s-72s,72o+,65s:0.475,64s-62s,62o+,54s:0.68,53s-52s,52o+,42+,32";
+    let sb_range = "66:0.46,55:0.821,44:0.92,33:0.93,22:0.925,A6s:0.73,A3s:0.47,A2s,ATo:0.105,A9o-A2o,K8s:0.795,K7s,K6s:0.85,K5s:0.965,K4s-K2s,KJo:0.085,KTo:0.645,K9o-K2o,Q8s-Q2s,QJo:0.765,QTo-Q2o,J8s-J2s,J2o+,T8s:0.69,T7s-T2s,T2o+,98s:0.905,97s-92s,92o+,87s:0.78,86s-82s,82o+,76s:0.77,75s-72s,72o+,65s:0.845,64s-62s,62o+,54s:0.735,53s-52s,52o+,42+,32";
+
+    let mut bunching_data = BunchingData::new(
+        &[co_range.parse().unwrap(), sb_range.parse().unwrap()],
+        flop,
+    )
+    .unwrap();
+
+    bunching_data.process(false);
+    game.set_bunching_effect(&bunching_data).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    let current_ev = compute_current_ev(&game);
+    assert!((current_ev[0] - 30.0).abs() < 1e-4);
+    assert!((current_ev[1] - -30.0).abs() < 1e-4);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let equity_oop = compute_average(&game.equity(0), weights_oop);
+    let equity_ip = compute_average(&game.equity(1), weights_ip);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!((equity_oop - 1.0).abs() < 1e-5);
+    assert!((equity_ip - 0.0).abs() < 1e-5);
+    assert!((ev_oop - 60.0).abs() < 1e-4);
+    assert!((ev_ip - 0.0).abs() < 1e-4);
+
+    game.play(0);
+    game.play(0);
+    assert!(game.is_chance_node());
+    game.play(usize::MAX);
+    game.play(0);
+    game.play(0);
+    assert!(game.is_chance_node());
+    game.play(usize::MAX);
+    game.play(0);
+    game.play(0);
+    assert!(game.is_terminal_node());
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let equity_oop = compute_average(&game.equity(0), weights_oop);
+    let equity_ip = compute_average(&game.equity(1), weights_ip);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!((equity_oop - 1.0).abs() < 1e-5);
+    assert!((equity_ip - 0.0).abs() < 1e-5);
+    assert!((ev_oop - 60.0).abs() < 1e-4);
+    assert!((ev_ip - 0.0).abs() < 1e-4);
+}
+
+#[test]
+fn set_bunching_effect_turn_river_root() {
+    let flop = flop_from_str("Td9d6h").unwrap();
+    let turn = card_from_str("Qh").unwrap();
+    let river = card_from_str("2s").unwrap();
+    let range = [
+        "AA-88,AK,KQ,QJs".parse().unwrap(),
+        "TT-22,AT+,KJ+,T9s".parse().unwrap(),
+    ];
+
+    let mut bunching_data = BunchingData::new(&["A2+,K9+,QT+,22+".parse().unwrap()], flop).unwrap();
+    bunching_data.process(false);
+
+    let build_game = |turn: Card, river: Card| {
+        let card_config = CardConfig {
+            range,
+            flop,
+            turn,
+            river,
+            ..Default::default()
+        };
+        let initial_state = match (turn, river) {
+            (NOT_DEALT, _) => BoardState::Flop,
+            (_, NOT_DEALT) => BoardState::Turn,
+            _ => BoardState::River,
+        };
+        let tree_config = TreeConfig {
+            initial_state,
+            starting_pot: 60,
+            effective_stack: 970,
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.set_bunching_effect(&bunching_data).unwrap();
+        game.allocate_memory(false);
+        finalize(&mut game);
+        game
+    };
+
+    let mut flop_game = build_game(NOT_DEALT, NOT_DEALT);
+    for (street_root, card) in [((turn, NOT_DEALT), turn), ((turn, river), river)] {
+        flop_game.play(0);
+        flop_game.play(0);
+        flop_game.play(card as usize);
+
+        let mut game = build_game(street_root.0, street_root.1);
+        flop_game.cache_normalized_weights();
+        game.cache_normalized_weights();
+        for player in 0..2 {
+            // hands overlapping with the board are removed from the ranges of `game`
+            let expected_weights = flop_game.normalized_weights(player);
+            let expected_equity = flop_game.equity(player);
+            let weights = game.normalized_weights(player);
+            let equity = game.equity(player);
+            let expected_sum = expected_weights.iter().sum::<f32>();
+            let sum = weights.iter().sum::<f32>();
+            for (i, hand) in game.private_cards(player).iter().enumerate() {
+                let j = flop_game
+                    .private_cards(player)
+                    .iter()
+                    .position(|h| h == hand);
+                let j = j.unwrap();
+                assert!((weights[i] / sum - expected_weights[j] / expected_sum).abs() < 1e-5);
+                assert!((equity[i] - expected_equity[j]).abs() < 1e-4);
+            }
+        }
+    }
+}
+
+#[test]
+fn set_bunching_effect_with_icm() {
+    let flop = flop_from_str("AcAdKh").unwrap();
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop,
+        ..Default::default()
+    };
+
+    let icm_config = ICMConfig {
+        player_stacks: [970, 970],
+        other_players_stacks: vec![1500, 500],
+        payout_structure: vec![50, 30, 20],
+    };
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        icm_config: Some(icm_config.clone()),
+        ..Default::default()
+    };
+
+    let co_range = "A2+,K9+,QT+,22+";
+    let mut bunching_data = BunchingData::new(&[co_range.parse().unwrap()], flop).unwrap();
+    bunching_data.process(false);
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_bunching_effect(&bunching_data).unwrap();
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    // OOP always wins the pot, so the payoffs are the ICM equity differences of winning it
+    let icm_calc = icm::ICMCalculator::new(
+        icm_config.other_players_stacks.clone(),
+        icm_config.payout_structure.clone(),
+        0,
+    );
+    let (current_0, current_1) = icm_calc.calculate(970, 970);
+    let (win_0, win_1) = icm_calc.calculate(1030, 970);
+    let current_ev = compute_current_ev(&game);
+    assert!((current_ev[0] as f64 - (win_0 - current_0)).abs() < 1e-4);
+    assert!((current_ev[1] as f64 - (win_1 - current_1)).abs() < 1e-4);
+
+    // both effects are taken into account when solving
+    let card_config = CardConfig {
+        range: [
+            "AA-22,AK,KQ".parse().unwrap(),
+            "JJ-22,AQ-A2,KQ-K9,QJ,JT,T9s".parse().unwrap(),
+        ],
+        flop,
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        icm_config: Some(icm_config),
+        flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_bunching_effect(&bunching_data).unwrap();
+    game.allocate_memory(false);
+    let exploitability = solve(&mut game, 1000, 1e-4, false);
+    assert!(exploitability < 1e-4);
+}
+
+#[test]
+#[cfg(feature = "bincode")]
+fn save_load_icm_seed() {
+    let card_config = CardConfig {
+        range: ["QQ+,AK".parse().unwrap(), "JJ-99,AQs".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    // the equities are estimated by sampling with more than 16 payouts
+    let icm_config = ICMConfig {
+        player_stacks: [900, 900],
+        other_players_stacks: (1..=18).map(|i| 100 * i).collect(),
+        payout_structure: (1..=17).rev().map(|i| 10 * i).collect(),
+    };
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 200,
+        effective_stack: 900,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        icm_config: Some(icm_config),
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_icm_seed(7);
+    game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+    game.cache_normalized_weights();
+    let ev = game.expected_values(0);
+
+    let config = bincode::config::standard();
+    let encoded = bincode::encode_to_vec(&game, config).unwrap();
+    let (mut decoded, _): (PostFlopGame, _) = bincode::decode_from_slice(&encoded, config).unwrap();
+    assert_eq!(decoded.icm_seed(), 7);
+    decoded.cache_normalized_weights();
+    assert_eq!(decoded.expected_values(0), ev);
+}
+
+#[test]
+fn range_equity_with_bunching() {
+    let flop = flop_from_str("Td9d6h").unwrap();
+    let card_config = CardConfig {
+        range: [
+            "AA-TT,AK,AQ".parse().unwrap(),
+            "99-22,A9s-A2s,KQ".parse().unwrap(),
+        ],
+        flop,
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let mut bunching_data = BunchingData::new(&["A2+,K9+,QT+".parse().unwrap()], flop).unwrap();
+    bunching_data.process(false);
+
+    // the standalone equities match those of a game with the bunching effect
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.set_bunching_effect(&bunching_data).unwrap();
+    game.cache_normalized_weights();
+
+    let equity = range_equity(&card_config, Some(&bunching_data)).unwrap();
+    for player in 0..2 {
+        assert_eq!(equity.private_cards[player], game.private_cards(player));
+        assert_eq!(equity.weights[player], game.normalized_weights(player));
+        assert_eq!(equity.equity[player], game.equity(player));
+    }
+    assert!((equity.overall(0) + equity.overall(1) - 1.0).abs() < 1e-4);
+
+    // the folded aces and broadways shift the equity
+    let base = range_equity(&card_config, None).unwrap();
+    let shift = bunching_equity_shift(&card_config, &bunching_data).unwrap();
+    assert!((shift[0] - (equity.overall(0) - base.overall(0))).abs() < 1e-6);
+    assert!((shift[0] + shift[1]).abs() < 1e-4);
+    assert!(shift[0].abs() > 1e-4);
+
+    // dead cards are not supported
+    let card_config = CardConfig {
+        dead_cards: 1 << card_from_str("2c").unwrap(),
+        ..card_config
+    };
+    range_equity(&card_config, None).unwrap();
+    range_equity(&card_config, Some(&bunching_data)).unwrap_err();
+}
+
+#[test]
+#[ignore]
+fn solve_pio_preset_normal() {
+    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
+    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
+
+    let card_config = CardConfig {
+        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
+        flop: flop_from_str("QsJh2h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 180,
+        effective_stack: 910,
+        flop_bet_sizes: [
+            ("52%", "45%").try_into().unwrap(),
+            ("52%", "45%").try_into().unwrap(),
+        ],
+        turn_bet_sizes: [
+            ("55%", "45%").try_into().unwrap(),
+            ("55%", "45%").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("70%", "45%").try_into().unwrap(),
+            ("70%", "45%").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    println!(
+        "memory usage: {:.2}GB",
+        game.memory_usage().0 as f64 / (1024.0 * 1024.0 * 1024.0)
+    );
+    game.allocate_memory(false);
+
+    solve(&mut game, 1000, 180.0 * 0.001, true);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let root_equity_oop = compute_average(&game.equity(0), weights_oop);
+    let root_equity_ip = compute_average(&game.equity(1), weights_ip);
+    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+    // verified by PioSOLVER Free
+    assert!((root_equity_oop - 0.55347).abs() < 1e-5);
+    assert!((root_equity_ip - 0.44653).abs() < 1e-5);
+    assert!((root_ev_oop - 105.11).abs() < 0.2);
+    assert!((root_ev_ip - 74.89).abs() < 0.2);
+}
+
+#[test]
+#[ignore]
+fn solve_pio_preset_raked() {
+    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
+    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
+
+    let card_config = CardConfig {
+        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
+        flop: flop_from_str("QsJh2h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 180,
+        effective_stack: 910,
+        rake_rate: 0.05,
+        rake_cap: 30.0,
+        flop_bet_sizes: [
+            ("52%", "45%").try_into().unwrap(),
+            ("52%", "45%").try_into().unwrap(),
+        ],
+        turn_bet_sizes: [
+            ("55%", "45%").try_into().unwrap(),
+            ("55%", "45%").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("70%", "45%").try_into().unwrap(),
+            ("70%", "45%").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    println!(
+        "memory usage: {:.2}GB",
+        game.memory_usage().0 as f64 / (1024.0 * 1024.0 * 1024.0)
+    );
+    game.allocate_memory(false);
+
+    solve(&mut game, 1000, 180.0 * 0.001, true);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+    // verified by PioSOLVER Free (but not theoretically guaranteed to be the same)
+    assert!((root_ev_oop - 95.57).abs() < 0.2);
+    assert!((root_ev_ip - 66.98).abs() < 0.2);
+}
+
+#[test]
+fn hand_class_breakdown() {
+    let card_config = CardConfig {
+        range: [
+            "QQ+,AK,KQs,T9s".parse().unwrap(),
+            "TT+,AQ+,KJs+".parse().unwrap(),
+        ],
+        flop: flop_from_str("Kh9h4c").unwrap(),
+        turn: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    game.cache_normalized_weights();
+    let breakdown = game.hand_class_breakdown();
+    let classes = breakdown.iter().map(|s| s.class).collect::<Vec<_>>();
+    assert_eq!(
+        classes,
+        [
+            HandClass::Set,
+            HandClass::Overpair,
+            HandClass::TopPairTopKicker,
+            HandClass::TopPairGoodKicker,
+            HandClass::Underpair,
+            HandClass::SecondPair,
+        ]
+    );
+
+    let share_sum = breakdown.iter().map(|s| s.share).sum::<f32>();
+    assert!((share_sum - 1.0).abs() < 1e-5);
+
+    let weights = game.normalized_weights(0);
+    let ev = compute_average(&game.expected_values(0), weights);
+    let class_ev = breakdown.iter().map(|s| s.ev * s.share).sum::<f32>();
+    assert!((ev - class_ev).abs() < 1e-3);
+
+    for stats in &breakdown {
+        let freq_sum = stats.frequencies.iter().sum::<f32>();
+        assert!((freq_sum - 1.0).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn ev_decomposition() {
+    let card_config = CardConfig {
+        range: [
+            "QQ+,AK,KQs".parse().unwrap(),
+            "TT+,AQ+,KJs+".parse().unwrap(),
+        ],
+        flop: flop_from_str("Kh9h4c").unwrap(),
+        turn: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    let hand = (card_from_str("Kd").unwrap(), card_from_str("Ad").unwrap());
+    let hand_index = game.private_cards(0).iter().position(|&h| h == hand);
+
+    game.cache_normalized_weights();
+    let ev = game.expected_values(0)[hand_index.unwrap()];
+
+    let lines = game.ev_decomposition(0, hand, false).unwrap();
+    let merged = game.ev_decomposition(0, hand, true).unwrap();
+    assert!(game.history().is_empty());
+    assert!(lines.len() > merged.len());
+
+    for lines in [lines, merged] {
+        let probability = lines.iter().map(|l| l.probability).sum::<f32>();
+        let contribution = lines.iter().map(|l| l.contribution).sum::<f32>();
+        assert!((probability - 1.0).abs() < 1e-4);
+        assert!((contribution - ev).abs() < 1e-3);
+    }
+
+    let hand = (card_from_str("Kh").unwrap(), card_from_str("Qh").unwrap());
+    game.ev_decomposition(0, hand, true).unwrap_err();
+}
+
+#[test]
+fn equity_bucket_breakdown() {
+    let card_config = CardConfig {
+        range: [
+            "QQ+,AK,KQs,T9s,87s".parse().unwrap(),
+            "TT+,AQ+,KJs+".parse().unwrap(),
+        ],
+        flop: flop_from_str("Kh9h4c").unwrap(),
+        turn: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    game.cache_normalized_weights();
+    let buckets = game.equity_bucket_breakdown(4);
+    assert_eq!(buckets.len(), 4);
+
+    let share_sum = buckets.iter().map(|b| b.share).sum::<f32>();
+    assert!((share_sum - 1.0).abs() < 1e-5);
+
+    for pair in buckets.windows(2) {
+        assert!(pair[0].bucket < pair[1].bucket);
+        assert!(pair[0].max_equity <= pair[1].min_equity);
+    }
+
+    let num_hands = buckets.iter().map(|b| b.num_hands).sum::<usize>();
+    let weights = game.normalized_weights(0);
+    assert_eq!(num_hands, weights.iter().filter(|&&w| w > 0.0).count());
+}
+
+#[test]
+fn export_pio() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,AK,T9s".parse().unwrap(),
+            "QQ,AQ,JTs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Kh9h4c").unwrap(),
+        turn: card_from_str("2s").unwrap(),
+        river: card_from_str("7d").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 100,
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    let options = ExportOptions {
+        format: ExportFormat::Pio,
+        ..Default::default()
+    };
+    let mut pio = Vec::new();
+    game.export_strategy(&mut pio, &options).unwrap();
+    let pio = String::from_utf8(pio).unwrap();
+
+    // one block per player node, each ended by an empty line
+    let blocks = pio.split_terminator("\n\n").collect::<Vec<_>>();
+    let ids = blocks
+        .iter()
+        .map(|block| block.lines().next().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ids,
+        ["node r:0", "node r:0:c", "node r:0:c:b50", "node r:0:b50"]
+    );
+
+    // the values of each hand are placed at its PioSOLVER index
+    for (block, history) in blocks.iter().zip([&[][..], &[0], &[0, 1], &[1]]) {
+        game.apply_history(history);
+        game.cache_normalized_weights();
+        let player = game.current_player();
+        let num_hands = game.private_cards(player).len();
+        let strategy = game.strategy();
+        let weights = game.normalized_weights(player);
+        let ev = game.expected_values(player);
+
+        let rows = block
+            .lines()
+            .skip(3)
+            .map(|line| {
+                let mut tokens = line.split(' ');
+                let mut label = tokens.next().unwrap().to_owned();
+                if label == "strategy" {
+                    label = tokens.next().unwrap().to_owned();
+                }
+                let values = tokens
+                    .map(|v| v.parse::<f32>().unwrap())
+                    .collect::<Vec<_>>();
+                assert_eq!(values.len(), 1326);
+                (label, values)
+            })
+            .collect::<Vec<_>>();
+        let num_actions = game.available_actions().len();
+        assert_eq!(rows.len(), num_actions + 2);
+        assert_eq!(rows[0].0, "range");
+        assert_eq!(rows[num_actions + 1].0, "ev");
+        let codes = block.lines().nth(2).unwrap().split(' ').skip(1);
+        assert!(codes.eq(rows[1..=num_actions].iter().map(|row| row.0.as_str())));
+
+        for (hand, &(c1, c2)) in game.private_cards(player).iter().enumerate() {
+            let index = (c2 as usize) * (c2 as usize - 1) / 2 + c1 as usize;
+            assert_eq!(rows[0].1[index], weights[hand]);
+            if weights[hand] == 0.0 {
+                continue;
+            }
+            for action in 0..num_actions {
+                let value = rows[action + 1].1[index];
+                assert!((value - strategy[action * num_hands + hand]).abs() < 1e-6);
+            }
+            assert!((rows[num_actions + 1].1[index] - ev[hand]).abs() < 1e-3);
+        }
+
+        // the hands out of the range are zero
+        let nonzero = rows[0].1.iter().filter(|&&w| w != 0.0).count();
+        assert_eq!(nonzero, weights.iter().filter(|&&w| w != 0.0).count());
+    }
+}
+
+#[test]
+fn export_strategy() {
+    let card_config = CardConfig {
+        range: ["AA,KK,AK".parse().unwrap(), "QQ,AQ".parse().unwrap()],
+        flop: flop_from_str("Kh9h4c").unwrap(),
+        turn: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+    game.play(0);
+
+    let options = ExportOptions {
+        street: Some(BoardState::Turn),
+        ..Default::default()
+    };
+    let mut csv = Vec::new();
+    game.export_strategy(&mut csv, &options).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert_eq!(game.history(), &[0]);
+
+    // 21 OOP hands (AA: 6, KK: 3, AK: 12) with two actions at the root
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("line,street,board,player,hand,weight,action,frequency,ev")
+    );
+    assert_eq!(lines.filter(|l| l.starts_with(",turn,")).count(), 21 * 2);
+    assert!(csv.contains("\nX:B50,turn,4c9hKh2s,0,KsKc,"));
+
+    let options = ExportOptions {
+        format: ExportFormat::Json,
+        street: Some(BoardState::River),
+        history: vec![1, 1, 51],
+    };
+    let mut json = Vec::new();
+    game.export_strategy(&mut json, &options).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    assert!(json.starts_with("{\"nodes\":[{\"line\":\"B50:C:As\",\"street\":\"river\""));
+    assert!(json.trim_end().ends_with("]}"));
+
+    let options = ExportOptions {
+        format: ExportFormat::Pio,
+        street: Some(BoardState::Turn),
+        history: vec![0],
+    };
+    let mut pio = Vec::new();
+    game.export_strategy(&mut pio, &options).unwrap();
+    let pio = String::from_utf8(pio).unwrap();
+    let mut lines = pio.lines();
+    assert_eq!(lines.next(), Some("node r:0:c"));
+    assert_eq!(lines.next(), Some("player IP"));
+    assert_eq!(lines.next(), Some("actions c b50"));
+    let range = lines.next().unwrap().split(' ').collect::<Vec<_>>();
+    assert_eq!((range[0], range.len()), ("range", 1327));
+    assert_ne!(range[1 + 50 * 49 / 2 + 42], "0"); // AhQh
+    assert_eq!(range[1 + 47 * 46 / 2 + 46], "0"); // KsKh
+    assert!(lines.next().unwrap().starts_with("strategy c "));
+    assert!(lines.next().unwrap().starts_with("strategy b50 "));
+    assert!(lines.next().unwrap().starts_with("ev "));
+    assert_eq!(lines.next(), Some(""));
+
+    let options = ExportOptions {
+        history: vec![5],
+        ..Default::default()
+    };
+    game.export_strategy(&mut Vec::new(), &options).unwrap_err();
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn export_parquet() {
+    use arrow_array::{Array, Float32Array, StringArray};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let card_config = CardConfig {
+        range: ["AA,KK,AK".parse().unwrap(), "QQ,AQ".parse().unwrap()],
+        flop: flop_from_str("Kh9h4c").unwrap(),
+        turn: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+    game.play(0);
+
+    // 21 OOP hands (AA: 6, KK: 3, AK: 12) with two actions at the root
+    let options = ExportOptions {
+        street: Some(BoardState::Turn),
+        ..Default::default()
+    };
+    let mut batches = Vec::new();
+    game.export_arrow(&options, 10, |batch| {
+        batches.push(batch);
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(game.history(), &[0]);
+    assert!(batches.iter().all(|batch| batch.num_rows() >= 10));
+    let lines = batches
+        .iter()
+        .flat_map(|batch| {
+            let column = batch.column(0).as_any().downcast_ref::<StringArray>();
+            column.unwrap().iter().map(|s| s.unwrap().to_owned())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(lines.iter().filter(|line| line.is_empty()).count(), 21 * 2);
+    let ev = batches[0].column(8).as_any().downcast_ref::<Float32Array>();
+    assert_eq!(ev.unwrap().null_count(), 0);
+
+    let path = std::env::temp_dir().join(format!(
+        "postflop-solver-test-parquet-{}.parquet",
+        std::process::id()
+    ));
+    let file = std::fs::File::create(&path).unwrap();
+    game.export_parquet(file, &options).unwrap();
+
+    let file = std::fs::File::open(&path).unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+    assert_eq!(reader.schema(), &crate::strategy_table_schema());
+    let num_rows = reader
+        .build()
+        .unwrap()
+        .map(|batch| batch.unwrap().num_rows())
+        .sum::<usize>();
+    assert_eq!(num_rows, lines.len());
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn chip_units() {
+    // $1/$2 stakes played with 20 chips per big blind
+    let big_blinds = ChipUnit::BigBlinds { big_blind: 20.0 };
+    let dollars = ChipUnit::Currency {
+        big_blind: 20.0,
+        big_blind_value: 2.0,
+    };
+    for unit in [ChipUnit::Chips, big_blinds, dollars] {
+        unit.check().unwrap();
+        assert_eq!(unit.to_chips(unit.convert(150.0)), 150.0);
+    }
+    assert_eq!(ChipUnit::Chips.convert(150.0), 150.0);
+    assert_eq!(big_blinds.convert(150.0), 7.5);
+    assert_eq!(dollars.convert(150.0), 15.0);
+    assert_eq!(dollars.convert_all(&[-20.0, 0.0, 30.0]), [-2.0, 0.0, 3.0]);
+    assert!((big_blinds.pot_share(100.0, 4.0, 0.5) - 2.4).abs() < 1e-9);
+
+    for big_blind in [0.0, -20.0, f64::INFINITY, f64::NAN] {
+        assert!(matches!(
+            ChipUnit::BigBlinds { big_blind }.check(),
+            Err(SolverError::InvalidArgument(_))
+        ));
+        let unit = ChipUnit::Currency {
+            big_blind: 20.0,
+            big_blind_value: big_blind,
+        };
+        assert!(matches!(unit.check(), Err(SolverError::InvalidArgument(_))));
+    }
+
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,AK,T9s".parse().unwrap(),
+            "QQ,AQ,JTs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Kh9h4c").unwrap(),
+        turn: card_from_str("2s").unwrap(),
+        river: card_from_str("7d").unwrap(),
+        ..Default::default()
+    };
+
+    // 5% rake capped at 4 chips, which is reached by the pot of 100 chips
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 100,
+        rake_rate: 0.05,
+        rake_cap: 4.0,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+    game.cache_normalized_weights();
+
+    for player in 0..2 {
+        let ev = game.expected_values(player);
+        let ev_dollars = game.expected_values_in(player, dollars);
+        for (&chips, &converted) in ev.iter().zip(&ev_dollars) {
+            assert!((converted - chips * 0.1).abs() < 1e-5);
+        }
+
+        // the pot shares are the equities of the pot after the rake
+        let equity = game.equity(player);
+        let shares = game.pot_shares(player, ChipUnit::Chips);
+        let shares_bb = game.pot_shares(player, big_blinds);
+        for ((&equity, &share), &share_bb) in equity.iter().zip(&shares).zip(&shares_bb) {
+            assert!((share - equity * 96.0).abs() < 1e-4);
+            assert!((share_bb - share / 20.0).abs() < 1e-5);
+        }
+    }
+
+    // the average pot shares of the two players add up to the raked pot
+    let average = |player| {
+        let shares = game.pot_shares(player, big_blinds);
+        compute_average(&shares, game.normalized_weights(player))
+    };
+    assert!((average(0) + average(1) - 4.8).abs() < 1e-4);
+
+    let node = game.query_node_in(dollars).unwrap();
+    assert_eq!(node.unit, dollars);
+    assert_eq!(node.pot, 100);
+    assert_eq!(
+        node.expected_values.unwrap()[1],
+        game.expected_values_in(1, dollars)
+    );
+    game.query_node_in(ChipUnit::BigBlinds {
+        big_blind: f64::NAN,
+    })
+    .unwrap_err();
+}
+
+#[test]
+fn query_node() {
+    let card_config = CardConfig {
+        range: ["AA,KK".parse().unwrap(), "QQ,AQs".parse().unwrap()],
+        flop: flop_from_str("Kh9h4c").unwrap(),
+        turn: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        rake_rate: 0.05,
+        rake_cap: 3.0,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(matches!(
+        game.query_node(),
+        Err(SolverError::InvalidState(_))
+    ));
+    game.allocate_memory(false);
+
+    let node = game.query_node().unwrap();
+    assert_eq!(node.node_type, NodeType::Player(0));
+    assert_eq!(node.expected_values, None);
+
+    solve(&mut game, 10, 0.0, false);
+    let node = game.query_node().unwrap();
+    assert_eq!(node.pot, 100);
+    assert_eq!(node.board.len(), 4);
+    assert_eq!(node.actions, game.available_actions());
+    assert_eq!(node.strategy, game.strategy());
+    assert_eq!(node.hands[1], game.private_cards(1));
+    assert_eq!(
+        node.expected_values.as_ref().unwrap()[0],
+        game.expected_values(0)
+    );
+    assert_eq!(node.unit, ChipUnit::Chips);
+
+    // in big blinds of 10 chips; the rake is capped at 3 chips
+    let unit = ChipUnit::BigBlinds { big_blind: 10.0 };
+    let node_bb = game.query_node_in(unit).unwrap();
+    assert_eq!(node_bb.unit, unit);
+    assert_eq!(node_bb.pot, 100);
+    assert_eq!(
+        node_bb.expected_values.unwrap()[0],
+        unit.convert_all(&game.expected_values(0))
+    );
+    for (&share, &equity) in node_bb.pot_shares[0].iter().zip(&node.equity[0]) {
+        assert!((share - equity * 9.7).abs() < 1e-5);
+    }
+    assert_eq!(node_bb.pot_shares[1], game.pot_shares(1, unit));
+    game.query_node_in(ChipUnit::BigBlinds { big_blind: 0.0 })
+        .unwrap_err();
+    let currency = ChipUnit::Currency {
+        big_blind: 10.0,
+        big_blind_value: f64::NAN,
+    };
+    assert!(matches!(
+        currency.check(),
+        Err(SolverError::InvalidArgument(_))
+    ));
+
+    game.apply_history(&[1, 1]);
+    let node = game.query_node().unwrap();
+    assert_eq!((node.history, node.pot), (vec![1, 1], 200));
+    assert_eq!(node.node_type, NodeType::Chance);
+    assert!(node.actions.is_empty() && node.strategy.is_empty());
+    assert_eq!(node.possible_cards.len(), 52 - 4);
+
+    game.apply_history(&[1, 0]);
+    let node = game.query_node().unwrap();
+    assert_eq!(node.node_type, NodeType::Terminal);
+
+    game.back_to_root();
+
+    #[cfg(feature = "msgpack")]
+    {
+        let node = game.query_node().unwrap();
+        let bytes = node.to_msgpack().unwrap();
+        assert_eq!(NodeQuery::from_msgpack(&bytes).unwrap(), node);
+        assert!(matches!(
+            NodeQuery::from_msgpack(&bytes[..bytes.len() / 2]),
+            Err(SolverError::InvalidData(_))
+        ));
+    }
+
+    #[cfg(feature = "cbor")]
+    {
+        let node = game.query_node().unwrap();
+        let bytes = node.to_cbor().unwrap();
+        assert_eq!(NodeQuery::from_cbor(&bytes).unwrap(), node);
+        assert!(matches!(
+            NodeQuery::from_cbor(&bytes[..bytes.len() / 2]),
+            Err(SolverError::InvalidData(_))
+        ));
+    }
+}
+
+#[test]
+fn export_json() {
+    let card_config = CardConfig {
+        range: ["AA,KK".parse().unwrap(), "QQ,AQs".parse().unwrap()],
+        flop: flop_from_str("Kh9h4c").unwrap(),
+        turn: card_from_str("2s").unwrap(),
+        river: card_from_str("7d").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 100,
+        river_bet_sizes: [
+            ("50%, a", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+    game.play(1);
+
+    let mut json = Vec::new();
+    game.export_json(&mut json).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    assert_eq!(game.history(), &[1]);
+
+    assert!(json.starts_with("{\"card_config\":{\"ranges\":[\"KK+\",\"QQ,AQs\"]"));
+    assert!(json.contains("\"flop_bet_sizes\":[{\"bet\":[],\"raise\":[]},"));
+    assert!(json.contains("\"river_bet_sizes\":[{\"bet\":[\"50%\",\"a\"],\"raise\":[]},"));
+    assert!(json.contains("\"is_solved\":true,\"hands\":[[\"KdKc\","));
+    assert!(json.contains("\"root\":{\"line\":\"\",\"type\":\"player\",\"pot\":100,"));
+    assert!(json.contains("{\"line\":\"B50:F\",\"type\":\"terminal\",\"pot\":150}"));
+    assert!(json.contains("\"actions\":[\"Check\",\"Bet 50\",\"All-in 100\"]"));
+    assert!(json.trim_end().ends_with("]}}"));
+    assert_eq!(json.matches('{').count(), json.matches('}').count());
+    assert_eq!(json.matches('[').count(), json.matches(']').count());
+}
+
+#[test]
+fn strategy_override() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,87s".parse().unwrap(), "JJ,AK,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    game.cache_normalized_weights();
+    let baseline = game.expected_values(0);
+
+    // IP always calls the turn bet
+    game.play(1);
+    let num_hands = game.private_cards(1).len();
+    let mut strategy = vec![0.0; 2 * num_hands];
+    strategy[num_hands..].fill(1.0);
+
+    let report = game.what_if(&[(vec![1], strategy.clone())]).unwrap();
+    assert!(game.has_strategy_overrides());
+    assert_eq!(game.history(), &[1]);
+    assert!(report.ev_shift[0] > 0.0);
+    assert!((report.ev_shift[0] + report.ev_shift[1]).abs() < 1e-3);
+
+    assert_eq!(game.strategy(), strategy);
+
+    game.back_to_root();
+    game.cache_normalized_weights();
+    let ev = game.expected_values(0);
+    assert!(
+        ev.iter()
+            .zip(&baseline)
+            .any(|(&a, &b)| (a - b).abs() > 1e-3)
+    );
+
+    game.clear_strategy_overrides();
+    assert!(!game.has_strategy_overrides());
+    game.cache_normalized_weights();
+    let ev = game.expected_values(0);
+    assert!(
+        ev.iter()
+            .zip(&baseline)
+            .all(|(&a, &b)| (a - b).abs() < 1e-3)
+    );
+
+    game.play(0);
+    game.override_current_strategy(&[1.0]).unwrap_err();
+}
+
+#[test]
+fn diff_games_rake() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let raked_config = TreeConfig {
+        rake_rate: 0.1,
+        rake_cap: 100.0,
+        ..tree_config.clone()
+    };
+
+    let new_game = |tree_config: TreeConfig| {
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 100, 0.0, false);
+        game
+    };
+
+    let mut game1 = new_game(tree_config.clone());
+    let mut game2 = new_game(tree_config.clone());
+    let mut game3 = new_game(raked_config);
+
+    let options = DiffOptions {
+        street: Some(BoardState::Turn),
+        ..Default::default()
+    };
+    assert!(
+        diff_games(&mut game1, &mut game2, &options)
+            .unwrap()
+            .is_empty()
+    );
+
+    let diff = diff_games(&mut game1, &mut game3, &options).unwrap();
+    assert!(!diff.is_empty());
+    assert!(
+        diff.iter()
+            .all(|d| d.ev_delta.abs() > 0.0 || d.max_frequency_delta() > 0.0)
+    );
+
+    let options = DiffOptions {
+        frequency_threshold: 1.0,
+        ev_threshold: f32::INFINITY,
+        ..Default::default()
+    };
+    assert!(
+        diff_games(&mut game1, &mut game3, &options)
+            .unwrap()
+            .is_empty()
+    );
+
+    let other_config = TreeConfig {
+        turn_bet_sizes: [
+            ("75%", "").try_into().unwrap(),
+            ("75%", "").try_into().unwrap(),
+        ],
+        ..tree_config
+    };
+    let mut game4 = new_game(other_config);
+    assert!(matches!(
+        diff_games(&mut game1, &mut game4, &DiffOptions::default()),
+        Err(SolverError::InvalidArgument(_))
+    ));
+}
+
+#[test]
+fn street_frequency_summary() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    let summary = game.street_frequency_summary().unwrap();
+    let streets = summary
+        .iter()
+        .map(|s| (s.street, s.player))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        streets,
+        [
+            (BoardState::Turn, 0),
+            (BoardState::Turn, 1),
+            (BoardState::River, 0),
+            (BoardState::River, 1),
+        ]
+    );
+
+    // OOP always makes exactly one decision at the root of the turn
+    game.cache_normalized_weights();
+    let root_frequencies = game.action_frequencies();
+    assert!(summary[0].decisions >= 1.0);
+    assert!(summary[0].decisions < 2.0);
+    assert!(summary[2].decisions <= 2.0 + 1e-4);
+    assert!(summary[2].decisions > 0.0);
+
+    for s in &summary {
+        let sum = s.fold + s.check + s.call + s.bet + s.raise;
+        assert!((sum - 1.0).abs() < 1e-4);
+    }
+
+    // without raises, the turn decisions of OOP are the root and the response to a bet
+    let oop_turn = &summary[0];
+    assert!(oop_turn.decisions * oop_turn.bet >= root_frequencies[1] - 1e-4);
+}
+
+#[test]
+fn ev_gaps() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 100,
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.01, false);
+
+    // IP facing a bet
+    game.play(1);
+    game.cache_normalized_weights();
+    let gaps = game.ev_gaps(0.5);
+    let weights = game.normalized_weights(1);
+    assert_eq!(gaps.len(), weights.iter().filter(|&&w| w > 0.0).count());
+
+    for gap in &gaps {
+        assert_eq!(gap.gaps[gap.best_action], 0.0);
+        assert!(gap.gaps.iter().all(|&g| g >= 0.0));
+
+        // mixed strategies only arise between (near-)indifferent actions
+        let is_mixed = gap.strategy.iter().filter(|&&s| s > 0.01).count() >= 2;
+        assert!(!is_mixed || gap.is_indifferent, "{gap:?}");
+    }
+}
+
+#[test]
+fn runout_class_report() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    game.runout_class_report().unwrap_err();
+
+    // bet-call
+    game.apply_history(&[1, 1]);
+    let report = game.runout_class_report().unwrap();
+    assert_eq!(game.history(), &[1, 1]);
+
+    let num_cards = report.iter().map(|r| r.cards.len()).sum::<usize>();
+    assert_eq!(num_cards, game.possible_cards().count_ones() as usize);
+
+    let probability = report.iter().map(|r| r.probability).sum::<f32>();
+    assert!((probability - 1.0).abs() < 1e-5);
+
+    let flush = report
+        .iter()
+        .find(|r| r.class == RunoutClass::FlushCompleting)
+        .unwrap();
+    assert!(flush.cards.iter().all(|&card| card & 3 == 1));
+
+    let ev_shift = report
+        .iter()
+        .map(|r| r.probability * r.ev_shift[0])
+        .sum::<f32>();
+    assert!(ev_shift.abs() < 1e-3);
+}
+
+#[test]
+fn equity_realization() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    // bet-call: pot is 200
+    game.apply_history(&[1, 1]);
+    game.cache_normalized_weights();
+
+    for player in 0..2 {
+        let weights = game.normalized_weights(player);
+        let equity = game.equity(player);
+        let ev = game.expected_values(player);
+        let eqr = game.equity_realization(player);
+        for hand in 0..weights.len() {
+            if weights[hand] > 0.0 && equity[hand] > 0.0 {
+                let expected = ev[hand] / (equity[hand] * 200.0);
+                assert!((eqr[hand] - expected).abs() < 1e-5);
+            } else {
+                assert!(eqr[hand].is_nan());
+            }
+        }
+
+        let expected = compute_average(&ev, weights) / (compute_average(&equity, weights) * 200.0);
+        let range_eqr = game.range_equity_realization(player);
+        assert!((range_eqr - expected).abs() < 1e-5);
+    }
+
+    // the sum of realized pot shares equals 1 when no rake is taken
+    let share = |p: usize| {
+        game.range_equity_realization(p)
+            * compute_average(&game.equity(p), game.normalized_weights(p))
+    };
+    assert!((share(0) + share(1) - 1.0).abs() < 0.05);
+}
+
+#[test]
+fn find_leaks() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 200, 0.0, false);
+
+    let num_actions = game.available_actions().len();
+    let solver_strategy = game.strategy();
+    let mut always_check = vec![0.0; num_actions];
+    always_check[0] = 1.0;
+
+    // the solver strategy itself has no leak; always checking the root node does
+    let leaks = game
+        .find_leaks(
+            0,
+            &[(vec![], solver_strategy), (vec![], always_check.clone())],
+        )
+        .unwrap();
+    assert!(game.history().is_empty());
+    assert_eq!(leaks.len(), 2);
+    assert!(leaks[0].ev_loss > 0.1);
+    assert_eq!(leaks[0].user_frequencies[0], 1.0);
+    assert!((leaks[0].reach - 1.0).abs() < 1e-5);
+    assert!(leaks[1].ev_loss.abs() < 1e-3);
+    assert!(leaks[0].weighted_ev_loss >= leaks[1].weighted_ev_loss);
+
+    // errors
+    game.find_leaks(1, &[(vec![], always_check)]).unwrap_err();
+    game.find_leaks(0, &[(vec![], vec![1.0; num_actions + 1])])
+        .unwrap_err();
+    assert!(matches!(
+        game.find_leaks(0, &[(vec![0, 0], vec![1.0])]),
+        Err(SolverError::InvalidArgument(_))
+    ));
+}
+
+#[test]
+fn pot_odds() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "50%").try_into().unwrap(),
+            ("50%", "50%").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    // root: bet 50 into 100
+    game.cache_normalized_weights();
+    let info = game.pot_odds();
+    assert_eq!(info.pot, 100);
+    assert_eq!(info.to_call, 0);
+    assert_eq!(info.pot_odds, None);
+    let bet = info
+        .bets
+        .iter()
+        .find(|b| b.action == Action::Bet(50))
+        .unwrap();
+    assert_eq!(bet.amount, 50);
+    assert!((bet.break_even_fold - 1.0 / 3.0).abs() < 1e-6);
+    assert!((bet.bluff_share - 0.25).abs() < 1e-6);
+
+    // facing bet 50: pot 150, to call 50
+    game.play(bet.action_index);
+    game.cache_normalized_weights();
+    let info = game.pot_odds();
+    assert_eq!(info.pot, 150);
+    assert_eq!(info.to_call, 50);
+    assert!((info.pot_odds.unwrap() - 0.25).abs() < 1e-6);
+    assert!((info.minimum_defense_frequency.unwrap() - 2.0 / 3.0).abs() < 1e-6);
+    let defense = info.defense_frequency.unwrap();
+    assert!((defense - (1.0 - info.frequencies[0])).abs() < 1e-6);
+
+    // all-in raise to 100: puts 100 into the pot of 150, opponent calls 50
+    let raise = info.bets.last().unwrap();
+    assert_eq!(raise.action, Action::AllIn(100));
+    assert_eq!(raise.amount, 100);
+    assert!((raise.break_even_fold - 0.4).abs() < 1e-6);
+    assert!((raise.bluff_share - 1.0 / 6.0).abs() < 1e-6);
+}
+
+#[test]
+fn range_advantage() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    game.cache_normalized_weights();
+    let advantage = game.range_advantage(0.8);
+
+    // OOP has overpairs and straights, IP has an underpair and overcards
+    assert!(advantage.equity_advantage > 0.0);
+    assert!((advantage.equity[0] + advantage.equity[1] - 1.0).abs() < 1e-3);
+    assert!(advantage.nut_share[0] > advantage.nut_share[1]);
+    assert!(advantage.nut_advantage > 0.0);
+    assert!(advantage.nut_combos[0] > 0.0);
+
+    // every hand has equity of at least zero
+    let advantage = game.range_advantage(0.0);
+    assert!((advantage.nut_share[0] - 1.0).abs() < 1e-6);
+    assert!((advantage.nut_share[1] - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn export_dot() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+    game.play(1);
+
+    let options = DotOptions {
+        max_depth: Some(2),
+        ..Default::default()
+    };
+    let mut dot = Vec::new();
+    game.export_dot(&mut dot, &options).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert_eq!(game.history(), &[1]);
+
+    // root -> (check, bet, all-in) -> ...
+    assert!(dot.starts_with("digraph tree {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("n0 [label=\"OOP\\npot 100\\nEV "));
+    assert!(dot.contains("[label=\"Fold\\npot 150"));
+    assert!(dot.contains("shape=diamond"));
+    let num_nodes = dot.lines().filter(|l| l.contains(", shape=")).count();
+    let num_edges = dot.lines().filter(|l| l.contains("->")).count();
+    assert_eq!(num_nodes, num_edges + 1);
+
+    // pruning by frequency removes some edges
+    let options = DotOptions {
+        min_frequency: 0.5,
+        ..Default::default()
+    };
+    let mut pruned = Vec::new();
+    game.export_dot(&mut pruned, &options).unwrap();
+    let pruned = String::from_utf8(pruned).unwrap();
+    let num_pruned_edges = pruned.lines().filter(|l| l.contains("->")).count();
+    assert!(num_pruned_edges > 0);
+    for line in pruned.lines().filter(|l| l.contains("->")) {
+        let percent = line.rsplit("\\n").next().unwrap();
+        let freq = percent.trim_end_matches("%\"];").parse::<f32>().unwrap();
+        assert!(freq >= 50.0);
+    }
+
+    game.export_dot(
+        &mut Vec::new(),
+        &DotOptions {
+            history: vec![9],
+            ..Default::default()
+        },
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn visit_tree() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+    game.play(1);
+
+    struct RiverReach;
+
+    impl TreeVisitor for RiverReach {
+        type Output = (usize, f32);
+
+        fn visit(&self, ctx: &NodeContext) -> Option<Self::Output> {
+            assert_eq!(ctx.strategy.len(), ctx.actions.len() * ctx.num_hands());
+            assert!(ctx.expected_values.is_some());
+            Some((ctx.player, ctx.reach))
+        }
+    }
+
+    // the total reach of the river nodes agrees with the street summary
+    let outputs = game
+        .visit_tree(&RiverReach, &[], Some(BoardState::River))
+        .unwrap();
+    assert_eq!(game.history(), &[1]);
+    assert!(outputs.len() > 256);
+
+    let summary = game.street_frequency_summary().unwrap();
+    for player in 0..2 {
+        let reach = outputs
+            .iter()
+            .filter(|(p, _)| *p == player)
+            .map(|(_, r)| r)
+            .sum::<f32>();
+        let expected = summary
+            .iter()
+            .find(|s| s.player == player && s.street == BoardState::River)
+            .unwrap()
+            .decisions;
+        assert!((reach - expected).abs() < 1e-3);
+    }
+
+    // closures can be used as visitors
+    let visitor = |ctx: &NodeContext| Some(ctx.line.len());
+    let depths = game.visit_tree(&visitor, &[0], None).unwrap();
+    assert_eq!(depths[0], 1);
+}
+
+#[test]
+fn sample_and_rollout() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 200, 0.0, false);
+    game.cache_normalized_weights();
+
+    // the empirical frequencies of the sampled actions match the overall frequencies
+    let frequencies = game.action_frequencies();
+    let num_samples = 20000;
+    let mut counts = vec![0; frequencies.len()];
+    for seed in 0..num_samples {
+        counts[game.sample_action(seed, None)] += 1;
+    }
+    for (&count, &freq) in counts.iter().zip(&frequencies) {
+        assert!((count as f32 / num_samples as f32 - freq).abs() < 0.02);
+    }
+
+    // the average payoff of the rollouts approximates the expected values
+    let ev = [0, 1].map(|p| compute_average(&game.expected_values(p), game.normalized_weights(p)));
+    let mut payoff_sum = [0.0; 2];
+    for seed in 0..num_samples {
+        let rollout = game.rollout(seed, None).unwrap();
+        assert_eq!(rollout.payoff[0] + rollout.payoff[1], 100.0);
+        if !rollout.is_fold {
+            assert_eq!(rollout.board.len(), 5);
+        }
+        payoff_sum[0] += rollout.payoff[0] as f64;
+        payoff_sum[1] += rollout.payoff[1] as f64;
+    }
+    assert!(game.history().is_empty());
+    for player in 0..2 {
+        let average = payoff_sum[player] / num_samples as f64;
+        assert!((average as f32 - ev[player]).abs() < 3.0);
+    }
+
+    assert!(matches!(
+        game.rollout(0, Some([0, 10000])),
+        Err(SolverError::InvalidArgument(_))
+    ));
+
+    // the seeds are shorthands for the generators seeded with them
+    for seed in 0..10 {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let rollout = game.rollout_with_rng(&mut rng, None).unwrap();
+        assert_eq!(rollout, game.rollout(seed, None).unwrap());
+    }
+
+    // a caller-supplied generator is shared across the draws
+    struct SplitMix(u64);
+    impl RandomSource for SplitMix {
+        fn next_u64(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        }
+    }
+
+    game.cache_normalized_weights();
+    let mut rng = SplitMix(1);
+    let rng: &mut dyn RandomSource = &mut rng;
+    let mut counts = vec![0; frequencies.len()];
+    for _ in 0..num_samples {
+        counts[game.sample_action_with_rng(rng, None)] += 1;
+    }
+    for (&count, &freq) in counts.iter().zip(&frequencies) {
+        assert!((count as f32 / num_samples as f32 - freq).abs() < 0.02);
+    }
+    let hands = game.sample_hands_with_rng(rng).unwrap();
+    let rollout = game.rollout_with_rng(rng, Some(hands)).unwrap();
+    assert_eq!(rollout.hands, hands);
+
+    let mut first = SplitMix(7);
+    let mut second = SplitMix(7);
+    assert_eq!(
+        game.rollout_with_rng(&mut first, None),
+        game.rollout_with_rng(&mut second, None)
+    );
+}
+
+#[test]
+fn value_bluff_report() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 200,
+        river_bet_sizes: [
+            ("50%, 100%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.01, false);
+
+    let report = game.value_bluff_report().unwrap();
+    assert!(game.history().is_empty());
+
+    game.cache_normalized_weights();
+    let weights = game.normalized_weights(0).to_vec();
+    let strategy = game.strategy();
+    let num_hands = weights.len();
+    let odds = game.pot_odds();
+
+    // bet 50, bet 100
+    assert_eq!(report.len(), 2);
+    for composition in &report {
+        let action = composition.action_index;
+        let total = (0..num_hands)
+            .map(|hand| weights[hand] * strategy[action * num_hands + hand])
+            .sum::<f32>();
+        assert!((composition.value_combos + composition.bluff_combos - total).abs() < 1e-4);
+        assert!(composition.value_hands.iter().all(|c| c.equity >= 0.5));
+        assert!(composition.bluff_hands.iter().all(|c| c.equity < 0.5));
+        assert!(
+            composition
+                .value_hands
+                .windows(2)
+                .all(|w| w[0].equity >= w[1].equity)
+        );
+
+        let bet_odds = odds.bets.iter().find(|b| b.action_index == action).unwrap();
+        assert_eq!(composition.break_even_bluff_share, bet_odds.bluff_share);
+    }
+
+    // the pot-sized bet is made with value hands and bluffs
+    let pot_bet = &report[1];
+    assert_eq!(pot_bet.action, Action::Bet(100));
+    assert!(pot_bet.value_combos > 0.0);
+    assert!(pot_bet.bluff_share > 0.0 && pot_bet.bluff_share < 1.0);
+
+    // terminal node
+    game.apply_history(&[0, 0]);
+    game.value_bluff_report().unwrap_err();
+}
+
+#[test]
+fn annotate_hand_history() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    let card = |s: &str| card_from_str(s).unwrap();
+    let line = [
+        Action::Bet(60),
+        Action::Call,
+        Action::Chance(card("3s")),
+        Action::Check,
+        Action::Bet(45),
+        Action::Fold,
+    ];
+    let hands = [Some((card("Kc"), card("Ks"))), None];
+
+    let decisions = game.annotate_hand_history(&line, hands).unwrap();
+    assert!(game.history().is_empty());
+    assert_eq!(decisions.len(), 5);
+
+    // the bet of 60 is snapped to the bet of 50; the river bet is snapped to the all-in
+    assert_eq!(
+        decisions[0].actions[decisions[0].action_index],
+        Action::Bet(50)
+    );
+    assert_eq!(
+        decisions[3].actions[decisions[3].action_index],
+        Action::AllIn(50)
+    );
+    assert_eq!(decisions[2].history.len(), 3);
+
+    let mut cumulative = 0.0;
+    for decision in &decisions {
+        let sum = decision.frequencies.iter().sum::<f32>();
+        assert!((sum - 1.0).abs() < 1e-3);
+        if decision.player == 0 {
+            let ev_loss = decision.ev_loss.unwrap();
+            assert!(decision.action_ev.unwrap() <= decision.best_ev.unwrap());
+            cumulative += ev_loss;
+            assert!((decision.cumulative_ev_loss - cumulative).abs() < 1e-4);
+        } else {
+            assert_eq!(decision.ev_loss, None);
+            assert_eq!(decision.cumulative_ev_loss, 0.0);
+        }
+    }
+
+    // errors
+    let hands = [Some((card("Jc"), card("Js"))), None];
+    game.annotate_hand_history(&line, hands).unwrap_err();
+    game.annotate_hand_history(&[Action::Chance(card("3s"))], [None; 2])
+        .unwrap_err();
+    game.annotate_hand_history(&[Action::Check, Action::Fold], [None; 2])
+        .unwrap_err();
+}
+
+#[test]
+fn hand_history_import() {
+    let text = "\
+Poker Hand #RC1000: Hold'em No Limit ($0.05/$0.1) - 2024/01/01 12:00:00
+Table 'RushAndCash1' 6-max Seat #4 is the button
+Seat 1: Hero ($10 in chips)
+Seat 2: a1b2c3 ($12.5 in chips)
+Seat 3: x9 ($8 in chips)
+Seat 4: btn ($10 in chips)
+Seat 5: away ($10 in chips) is sitting out
+Hero: posts small blind $0.05
+a1b2c3: posts big blind $0.1
+*** HOLE CARDS ***
+Dealt to Hero [Qs Qd]
+Dealt to a1b2c3
+Dealt to x9
+Dealt to btn
+x9: folds
+btn: folds
+Hero: raises $0.2 to $0.3
+a1b2c3: calls $0.2
+*** FLOP *** [Qh 8d 3c]
+Hero: checks
+a1b2c3: bets $0.4
+Hero: calls $0.4
+*** TURN *** [Qh 8d 3c] [Tc]
+Hero: checks
+a1b2c3: checks
+*** RIVER *** [Qh 8d 3c Tc] [9s]
+Hero: bets $9.3 and is all-in
+a1b2c3: calls $9.3
+*** SHOWDOWN ***
+Hero: shows [Qs Qd] (Three of a kind)
+a1b2c3: shows [Th 9c] (Two pair)
+Hero collected $19.9 from pot
+*** SUMMARY ***
+Total pot $20 | Rake $0.1 | Jackpot $0 | Bingo $0 | Fortune $0 | Tax $0
+Board [Qh 8d 3c Tc 9s]
+Seat 1: Hero (small blind) showed [Qs Qd] and won ($19.9)";
+
+    let file = format!("Header\n{text}\n\n{text}\n");
+    assert_eq!(split_hand_histories(&file), [text, text]);
+
+    let spot = parse_hand_history(text, 100.0).unwrap();
+    let card = |s: &str| card_from_str(s).unwrap();
+    assert_eq!(spot.format, HandHistoryFormat::GGPoker);
+    assert_eq!(spot.hand_id, "RC1000");
+    assert_eq!(spot.players, ["Hero", "a1b2c3"]);
+    assert_eq!(
+        spot.hands,
+        [
+            Some((card("Qs"), card("Qd"))),
+            Some((card("Th"), card("9c")))
+        ]
+    );
+    assert_eq!(spot.flop, [card("Qh"), card("8d"), card("3c")]);
+    assert_eq!((spot.turn, spot.river), (card("Tc"), card("9s")));
+    assert_eq!((spot.starting_pot, spot.effective_stack), (60, 970));
+    assert_eq!(line_to_string(&spot.line), "X:B40:C:Tc:X:X:9s:A930:C");
+
+    let ranges = ["QQ,AQ,JTs".parse().unwrap(), "T9,AK,88".parse().unwrap()];
+    let template = TreeConfig {
+        flop_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [("a", "").try_into().unwrap(), ("a", "").try_into().unwrap()],
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(spot.tree_config(&template)).unwrap();
+    let mut game = PostFlopGame::with_config(spot.card_config(ranges), action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+
+    let decisions = game.annotate_hand_history(&spot.line, spot.hands).unwrap();
+    assert_eq!(decisions.len(), 7);
+
+    // the flop bet of 40 is snapped to the bet of 30, so the river all-in is 940 in the tree
+    let river = &decisions[5];
+    assert_eq!(river.actions[river.action_index], Action::AllIn(940));
+    assert!(decisions.iter().all(|decision| decision.ev_loss.is_some()));
+
+    // three players on the flop
+    let multiway = text
+        .replace("x9: folds", "x9: calls $0.3")
+        .replace("Hero: raises $0.2 to $0.3", "Hero: calls $0.05");
+    parse_hand_history(&multiway, 100.0).unwrap_err();
+
+    // the hand ended preflop
+    let end = text.find("*** FLOP").unwrap();
+    parse_hand_history(&text[..end], 100.0).unwrap_err();
+
+    // not No-Limit Hold'em
+    let omaha = text.replace("Hold'em No Limit", "Omaha Pot Limit");
+    parse_hand_history(&omaha, 100.0).unwrap_err();
+    parse_hand_history(text, 0.0).unwrap_err();
+}
+
+#[test]
+fn quiz_spot() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+    game.play(0);
+
+    for seed in 0..20 {
+        let spot = game
+            .sample_quiz_spot(seed, Some(BoardState::River))
+            .unwrap();
+        assert_eq!(game.history(), &[0]);
+        assert_eq!(spot.board.len(), 5);
+        assert_eq!(spot.strategy.len(), spot.actions.len());
+
+        // answering with the solver strategy is perfect
+        let grade = spot.grade(&spot.strategy).unwrap();
+        assert!(grade.ev_loss.abs() < 1e-3);
+        assert!(grade.frequency_distance < 1e-5);
+
+        // the best action never loses EV
+        let best = spot.grade_action(grade.best_action).unwrap();
+        assert!(best.ev_loss <= 1e-3);
+
+        assert!(matches!(
+            spot.grade(&[1.0]),
+            Err(SolverError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            spot.grade_action(spot.actions.len()),
+            Err(SolverError::InvalidArgument(_))
+        ));
+
+        // the sharpened spot expects the most frequent action
+        let sharpened = spot.with_temperature(0.0);
+        assert_eq!(sharpened.action_ev, spot.action_ev);
+        assert!(sharpened.strategy.iter().all(|&f| f == 0.0 || f > 0.49));
+    }
+}
+
+#[test]
+fn strategy_with_temperature() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 100,
+        river_bet_sizes: [
+            ("50%, a", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+
+    let num_hands = game.num_private_hands(0);
+    let num_actions = game.available_actions().len();
+    let strategy = game.strategy();
+    let frequencies = |strategy: &[f32], hand: usize| {
+        (0..num_actions)
+            .map(|action| strategy[action * num_hands + hand])
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(game.strategy_with_temperature(1.0), strategy);
+
+    let softened = game.strategy_with_temperature(4.0);
+    let sharpened = game.strategy_with_temperature(0.25);
+    let pure = game.strategy_with_temperature(0.0);
+    let uniform = game.strategy_with_temperature(f32::INFINITY);
+
+    for hand in 0..num_hands {
+        let original = frequencies(&strategy, hand);
+        let max = original.iter().fold(0.0f32, |acc, &f| acc.max(f));
+        for adjusted in [&softened, &sharpened, &pure, &uniform] {
+            let sum = frequencies(adjusted, hand).iter().sum::<f32>();
+            assert!((sum - 1.0).abs() < 1e-5);
+        }
+
+        // the most frequent action gets less frequent when softened and more when sharpened
+        let argmax = original.iter().position(|&f| f == max).unwrap();
+        let at = |strategy: &[f32]| strategy[argmax * num_hands + hand];
+        assert!(at(&softened) <= max + 1e-6);
+        assert!(at(&sharpened) >= max - 1e-6);
+        assert!(at(&pure) >= 0.5 - 1e-6);
+        assert!(frequencies(&sharpened, hand).iter().all(|&f| f >= 0.0));
+        for f in frequencies(&uniform, hand) {
+            assert!((f - 1.0 / num_actions as f32).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn mixing_stats() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    // the initial strategy is uniform
+    game.cache_normalized_weights();
+    let stats = game.mixing_stats(0.9);
+    assert!((stats.average_entropy - 1.0).abs() < 1e-5);
+    assert!((stats.mixed_share - 1.0).abs() < 1e-5);
+    assert_eq!(stats.purification_ev_loss, None);
+
+    solve(&mut game, 100, 0.0, false);
+    game.cache_normalized_weights();
+    let stats = game.mixing_stats(0.9);
+    let weights = game.normalized_weights(0);
+    assert!(
+        stats
+            .entropy
+            .iter()
+            .all(|&h| (0.0..=1.0 + 1e-5).contains(&h))
+    );
+    assert!((stats.average_entropy - compute_average(&stats.entropy, weights)).abs() < 1e-5);
+    assert!(stats.average_entropy < 1.0);
+    assert!(stats.purification_ev_loss.unwrap() > -0.5);
+
+    // everything is pure with a zero threshold
+    assert_eq!(game.mixing_stats(0.0).mixed_share, 0.0);
+}
+
+#[test]
+fn scatter_data() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    // the EV is not available before solving
+    game.cache_normalized_weights();
+    let points = game.scatter_data();
+    assert!(
+        points
+            .iter()
+            .all(|p| p.ev.is_nan() && p.action_ev.is_empty())
+    );
+
+    solve(&mut game, 100, 0.0, false);
+    game.cache_normalized_weights();
+    let points = game.scatter_data();
+    let num_actions = game.available_actions().len();
+    assert!(!points.is_empty());
+    for point in &points {
+        assert_eq!(point.frequencies.len(), num_actions);
+        assert_eq!(point.action_ev.len(), num_actions);
+        assert!((point.frequencies.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+        assert!((0.0..=1.0).contains(&point.equity));
+        assert!((0.0..=1.0).contains(&point.blocker_score));
+        let ev = point
+            .frequencies
+            .iter()
+            .zip(&point.action_ev)
+            .fold(0.0, |acc, (&f, &ev)| acc + f * ev);
+        assert!((ev - point.ev).abs() < 1e-2 * point.ev.abs().max(1.0));
+    }
+
+    // an ace blocks more of the opponent's range than a pair of queens
+    let aces = points
+        .iter()
+        .find(|p| p.hand.0 / 4 == 12 && p.hand.1 / 4 == 12)
+        .unwrap();
+    let queens = points
+        .iter()
+        .find(|p| p.hand.0 / 4 == 10 && p.hand.1 / 4 == 10)
+        .unwrap();
+    assert!(aces.blocker_score > queens.blocker_score);
+
+    let mut output = Vec::new();
+    game.export_scatter(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let mut lines = output.lines();
+    assert_eq!(
+        lines.next(),
+        Some("hand,weight,equity,blocker_score,ev,action,frequency,action_ev")
+    );
+    assert_eq!(lines.count(), points.len() * num_actions);
+}
+
+#[test]
+fn extract_rules() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    game.extract_rules(2).unwrap_err();
+
+    solve(&mut game, 100, 0.0, false);
+    game.cache_normalized_weights();
+    assert!(matches!(
+        game.extract_rules(0),
+        Err(SolverError::InvalidArgument(_))
+    ));
+
+    let rules = game.extract_rules(1).unwrap();
+    assert_eq!(rules.len(), RuleFeature::ALL.len());
+    for rule in &rules {
+        // a single segment gives the same rule regardless of the feature
+        assert_eq!(rule.segments.len(), 1);
+        assert!((rule.segments[0].share - 1.0).abs() < 1e-4);
+        assert!((rule.ev_loss - rules[0].ev_loss).abs() < 1e-4);
+    }
+
+    // more segments never increase the EV loss
+    let detailed = game.extract_rules(4).unwrap();
+    for rule in &detailed {
+        assert!(rule.segments.len() <= 4);
+        assert!(rule.ev_loss >= -1e-3);
+        assert!(rule.ev_loss <= rules[0].ev_loss + 1e-4);
+        assert!((0.0..=1.0 + 1e-4).contains(&rule.accuracy));
+        let share = rule.segments.iter().map(|s| s.share).sum::<f32>();
+        assert!((share - 1.0).abs() < 1e-4);
+        assert!(!rule.to_string().is_empty());
+    }
+    assert!(detailed.windows(2).all(|w| w[0].ev_loss <= w[1].ev_loss));
+}
+
+#[test]
+fn line_pattern() {
+    let pattern = |s: &str| s.parse::<LinePattern>().unwrap();
+    let river = card_from_str("As").unwrap();
+    let line = [
+        Action::Check,
+        Action::Bet(50),
+        Action::Call,
+        Action::Chance(river),
+        Action::Bet(100),
+    ];
+
+    assert!(pattern("").matches(&[]));
+    assert!(!pattern("").matches(&line));
+    assert!(pattern("*").matches(&[]));
+    assert!(pattern("*").matches(&line));
+    assert!(pattern("*:B").matches(&line));
+    assert!(pattern("*:B100").matches(&line));
+    assert!(!pattern("*:B50").matches(&line));
+    assert!(pattern("X:B50:C:As:B100").matches(&line));
+    assert!(pattern("?:B:*:D:?").matches(&line));
+    assert!(pattern("*:D:*").matches(&line));
+    assert!(!pattern("*:R").matches(&line));
+    assert!(!pattern("X:*:C").matches(&line));
+
+    "Z".parse::<LinePattern>().unwrap_err();
+    "Bx".parse::<LinePattern>().unwrap_err();
+}
+
+#[test]
+fn find_nodes() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    let root = game.find_nodes(&"".parse().unwrap(), None).unwrap();
+    assert_eq!(root, vec![Vec::<usize>::new()]);
+
+    // every river node facing a bet
+    let pattern = "*:B".parse().unwrap();
+    let histories = game.find_nodes(&pattern, Some(BoardState::River)).unwrap();
+    assert!(!histories.is_empty());
+    for history in &histories {
+        game.apply_history(history);
+        assert_eq!(game.current_board().len(), 5);
+        assert!(game.available_actions().contains(&Action::Fold));
+    }
+    game.back_to_root();
+
+    // locks every river node facing a bet to always call
+    let mut num_locked = 0;
+    game.for_each_matching_node(&pattern, Some(BoardState::River), &mut |game, _, _| {
+        let actions = game.available_actions();
+        let num_hands = game.private_cards(game.current_player()).len();
+        let call = actions.iter().position(|&a| a == Action::Call).unwrap();
+        let mut strategy = vec![0.0; actions.len() * num_hands];
+        strategy[call * num_hands..(call + 1) * num_hands].fill(1.0);
+        game.lock_current_strategy(&strategy);
+        num_locked += 1;
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(num_locked, histories.len());
+    assert!(game.history().is_empty());
+
+    solve(&mut game, 100, 0.0, false);
+    game.apply_history(&histories[0]);
+    let actions = game.available_actions();
+    let call = actions.iter().position(|&a| a == Action::Call).unwrap();
+    let num_hands = game.private_cards(game.current_player()).len();
+    let strategy = game.strategy();
+    assert!(
+        strategy[call * num_hands..(call + 1) * num_hands]
+            .iter()
+            .all(|&f| (f - 1.0).abs() < 1e-5)
+    );
+
+    // errors returned by the callback stop the walk
+    let stop = SolverError::Other("stop".to_owned());
+    let result = game.for_each_matching_node(&pattern, None, &mut |_, _, _| Err(stop.clone()));
+    assert_eq!(result, Err(stop));
+}
+
+#[test]
+#[cfg(all(feature = "mmap", unix))]
+fn allocate_memory_on_disk() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let new_game = || {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        PostFlopGame::with_config(card_config.clone(), action_tree).unwrap()
+    };
+    let mut game = new_game();
+    let mut disk_game = new_game();
+
+    game.allocate_memory(false);
+    disk_game
+        .allocate_memory_on_disk(false, std::env::temp_dir())
+        .unwrap();
+    assert!(!game.is_memory_on_disk());
+    assert!(disk_game.is_memory_on_disk());
+
+    let exploitability = solve(&mut game, 100, 0.0, false);
+    let disk_exploitability = solve(&mut disk_game, 100, 0.0, false);
+    assert!((exploitability - disk_exploitability).abs() < 1e-4);
+
+    game.cache_normalized_weights();
+    disk_game.cache_normalized_weights();
+    assert_eq!(game.strategy(), disk_game.strategy());
+
+    // reallocating on the heap releases the files
+    disk_game.allocate_memory(false);
+    assert!(!disk_game.is_memory_on_disk());
+
+    // an invalid directory results in an error
+    let mut invalid_game = new_game();
+    invalid_game
+        .allocate_memory_on_disk(false, "/nonexistent-directory")
+        .unwrap_err();
+    assert_eq!(invalid_game.is_memory_allocated(), None);
+}
+
+#[test]
+fn allocate_memory_quantized() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let new_game = || {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        PostFlopGame::with_config(card_config.clone(), action_tree).unwrap()
+    };
+
+    let mut game = new_game();
+    let (_, compressed) = game.memory_usage();
+    assert!(game.memory_usage_quantized() < compressed);
+
+    game.allocate_memory(true);
+    let exploitability = solve(&mut game, 200, 0.0, false);
+
+    let mut quantized_game = new_game();
+    quantized_game.allocate_memory_quantized();
+    assert_eq!(quantized_game.is_memory_allocated(), Some(true));
+    assert!(quantized_game.is_strategy_quantized());
+    let quantized_exploitability = solve(&mut quantized_game, 200, 0.0, false);
+    assert!(quantized_exploitability < exploitability + 0.5);
+
+    game.cache_normalized_weights();
+    quantized_game.cache_normalized_weights();
+    let strategy = game.strategy();
+    let quantized_strategy = quantized_game.strategy();
+    assert!(
+        strategy
+            .iter()
+            .zip(&quantized_strategy)
+            .all(|(a, b)| (a - b).abs() < 0.1)
+    );
+
+    // the quantized storage survives a round trip at every storage mode
+    #[cfg(feature = "bincode")]
+    for mode in [BoardState::River, BoardState::Turn] {
+        quantized_game.set_target_storage_mode(mode).unwrap();
+        let mut buffer = Vec::new();
+        crate::save_data_into_std_write(&quantized_game, "", &mut buffer, None).unwrap();
+        let (mut loaded, _): (PostFlopGame, _) =
+            crate::load_data_from_std_read(&mut buffer.as_slice(), None).unwrap();
+        assert!(loaded.is_strategy_quantized());
+        loaded.cache_normalized_weights();
+        assert_eq!(loaded.strategy(), quantized_strategy);
+
+        let history = [1];
+        quantized_game.apply_history(&history);
+        loaded.apply_history(&history);
+        quantized_game.cache_normalized_weights();
+        loaded.cache_normalized_weights();
+        assert_eq!(loaded.strategy(), quantized_game.strategy());
+        assert_eq!(loaded.expected_values(0), quantized_game.expected_values(0));
+        quantized_game.back_to_root();
+    }
+}
+
+#[test]
+fn allocate_memory_up_to() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let new_game = || {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        PostFlopGame::with_config(card_config.clone(), action_tree).unwrap()
+    };
+
+    let lock = |game: &mut PostFlopGame| {
+        game.apply_history(&[1]);
+        let num_actions = game.available_actions().len();
+        let num_hands = game.private_cards(1).len();
+        let mut strategy = vec![0.0; num_actions * num_hands];
+        strategy[num_hands..2 * num_hands].fill(1.0);
+        game.lock_current_strategy(&strategy);
+        game.back_to_root();
+    };
+
+    let mut game = new_game();
+    game.allocate_memory(false);
+    lock(&mut game);
+    let exploitability = solve(&mut game, 50, 0.0, false);
+
+    // allocates the turn nodes only and locks a turn node
+    let mut lazy_game = new_game();
+    lazy_game.allocate_memory_up_to(false, BoardState::Turn);
+    assert_eq!(lazy_game.storage_mode(), BoardState::Turn);
+    lock(&mut lazy_game);
+
+    // dealing a river card allocates the river storage
+    lazy_game.apply_history(&[0, 0]);
+    lazy_game.play(usize::MAX);
+    assert_eq!(lazy_game.storage_mode(), BoardState::River);
+    lazy_game.back_to_root();
+
+    let mut lazy_game2 = new_game();
+    lazy_game2.allocate_memory_up_to(false, BoardState::Turn);
+    lock(&mut lazy_game2);
+    lazy_game2.extend_memory(BoardState::River).unwrap();
+    assert_eq!(lazy_game2.storage_mode(), BoardState::River);
+
+    for lazy_game in [&mut lazy_game, &mut lazy_game2] {
+        let lazy_exploitability = solve(lazy_game, 50, 0.0, false);
+        assert_eq!(exploitability, lazy_exploitability);
+        lazy_game.extend_memory(BoardState::River).unwrap();
+        lazy_game.extend_memory(BoardState::Turn).unwrap();
+    }
+
+    let mut unallocated = new_game();
+    unallocated.extend_memory(BoardState::River).unwrap_err();
+}
+
+#[test]
+fn memory_breakdown() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(game.memory_breakdown().is_none());
+
+    let (uncompressed, compressed) = game.memory_usage();
+    let estimate = game.estimate_memory_breakdown(StorageFormat::Uncompressed);
+    assert_eq!(estimate.total(), uncompressed);
+    assert_eq!(
+        game.estimate_memory_breakdown(StorageFormat::Compressed)
+            .total(),
+        compressed
+    );
+    assert_eq!(
+        game.estimate_memory_breakdown(StorageFormat::Quantized)
+            .total(),
+        game.memory_usage_quantized()
+    );
+
+    // no flop nodes; all nodes of the tree are reachable
+    let streets = &estimate.streets;
+    assert_eq!(streets[0].num_nodes(), 0);
+    assert!(streets[1].num_nodes() > 0 && streets[2].num_nodes() > 0);
+    assert_eq!(
+        streets
+            .iter()
+            .map(StreetMemoryUsage::num_nodes)
+            .sum::<u64>(),
+        game.node_arena.len() as u64
+    );
+    assert!(streets[1].num_chance_nodes > 0 && streets[1].cfvalues_chance > 0);
+    assert_eq!(streets[2].cfvalues_chance, 0);
+
+    game.allocate_memory_up_to(false, BoardState::Turn);
+    let actual = game.memory_breakdown().unwrap();
+    assert_eq!(actual.streets[1], estimate.streets[1]);
+    assert_eq!(
+        actual.streets[2].num_nodes(),
+        estimate.streets[2].num_nodes()
+    );
+    assert_eq!(actual.streets[2].storage(), 0);
+    assert!(!actual.is_on_disk);
+
+    game.allocate_memory(true);
+    let actual = game.memory_breakdown().unwrap();
+    let estimate = game.estimate_memory_breakdown(StorageFormat::Compressed);
+    assert_eq!(actual.streets, estimate.streets);
+    assert_eq!(actual.storage(), estimate.storage());
+
+    // removed lines are excluded from the estimate
+    let action_tree = ActionTree::new(game.tree_config().clone()).unwrap();
+    let mut game2 = PostFlopGame::with_config(game.card_config().clone(), action_tree).unwrap();
+    game2
+        .remove_lines(&[vec![Action::Check, Action::Bet(50)]])
+        .unwrap();
+    let estimate2 = game2.estimate_memory_breakdown(StorageFormat::Uncompressed);
+    assert_eq!(estimate2.total(), game2.memory_usage().0);
+    assert!(estimate2.streets[1].num_nodes() < estimate.streets[1].num_nodes());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn thread_pool() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.allocate_memory(false);
+    let exploitability = solve(&mut game, 50, 0.0, false);
+
+    let mut pooled_game = PostFlopGame::new();
+    pooled_game.set_num_threads(0).unwrap_err();
+    pooled_game.set_num_threads(2).unwrap();
+    assert_eq!(pooled_game.num_threads(), 2);
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    pooled_game.update_config(card_config, action_tree).unwrap();
+    pooled_game.allocate_memory(false);
+    assert_eq!(solve(&mut pooled_game, 50, 0.0, false), exploitability);
+    assert_eq!(compute_exploitability(&pooled_game), exploitability);
+
+    pooled_game.set_thread_pool(None);
+    assert_eq!(pooled_game.num_threads(), rayon::current_num_threads());
+}
+
+#[test]
+#[cfg(all(feature = "numa", target_os = "linux"))]
+fn numa_partitions() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%, 100%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    // two partitions on the first node, so that the subtrees are dispatched to different pools
+    let node = crate::numa_nodes().swap_remove(0);
+    let half = node.cpus.len().div_ceil(2);
+    let nodes = [
+        crate::NumaNode {
+            id: node.id,
+            cpus: node.cpus[..half].to_vec(),
+        },
+        crate::NumaNode {
+            id: node.id,
+            cpus: node.cpus[node.cpus.len() - half..].to_vec(),
+        },
+    ];
+
+    for enable_compression in [false, true] {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(enable_compression);
+        let exploitability = solve(&mut game, 50, 0.0, false);
+
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut partitioned = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        partitioned.set_numa_nodes(&nodes).unwrap();
+        let assignment = partitioned.numa_assignment().unwrap();
+        assert_eq!(assignment.len(), partitioned.available_actions().len());
+        assert!(assignment.iter().any(|&node| node == &nodes[0]));
+        assert!(assignment.iter().any(|&node| node == &nodes[1]));
+
+        partitioned.allocate_memory(enable_compression);
+        assert_eq!(solve(&mut partitioned, 50, 0.0, false), exploitability);
+        assert_eq!(partitioned.strategy(), game.strategy());
+        for player in 0..2 {
+            partitioned.cache_normalized_weights();
+            game.cache_normalized_weights();
+            assert_eq!(
+                partitioned.expected_values(player),
+                game.expected_values(player)
+            );
+        }
+
+        partitioned.set_numa_nodes(&[]).unwrap();
+        assert!(partitioned.numa_assignment().is_none());
+    }
+}
+
+#[test]
+fn with_shared_tree() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+
+    let card_config2 = CardConfig {
+        flop: flop_from_str("Ks7c3d").unwrap(),
+        turn: card_from_str("Jh").unwrap(),
+        ..card_config
+    };
+
+    let mut shared_game = PostFlopGame::with_shared_tree(card_config2.clone(), &game).unwrap();
+    assert!(shared_game.shares_action_tree_with(&game));
+    assert_eq!(shared_game.card_config().flop, card_config2.flop);
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game2 = PostFlopGame::with_config(card_config2, action_tree).unwrap();
+    assert!(!game2.shares_action_tree_with(&game));
+    assert_eq!(shared_game.memory_usage(), game2.memory_usage());
+
+    shared_game.allocate_memory(false);
+    game2.allocate_memory(false);
+    assert_eq!(
+        solve(&mut shared_game, 50, 0.0, false),
+        solve(&mut game2, 50, 0.0, false)
+    );
+
+    let result = PostFlopGame::with_shared_tree(CardConfig::default(), &PostFlopGame::new());
+    assert!(matches!(result, Err(SolverError::InvalidState(_))));
+}
+
+#[test]
+fn resolve_subtree() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert!(matches!(
+        game.resolve_subtree(&[1], &[], 10),
+        Err(SolverError::InvalidState(_))
+    ));
+    solve(&mut game, 100, 0.0, false);
+
+    game.cache_normalized_weights();
+    let root_strategy = game.strategy();
+    let root_ev = game.expected_values(0);
+
+    // IP always calls the turn bet
+    game.apply_history(&[1]);
+    let num_hands = game.private_cards(1).len();
+    let num_actions = game.available_actions().len();
+    let mut call = vec![0.0; num_actions * num_hands];
+    call[num_hands..2 * num_hands].fill(1.0);
+
+    game.back_to_root();
+    game.resolve_subtree(&[1], &[(vec![0], call.clone())], 10)
+        .unwrap_err();
+    game.resolve_subtree(&[1], &[(vec![1], vec![1.0])], 10)
+        .unwrap_err();
+    assert!(matches!(
+        game.resolve_subtree(&[1, 0], &[], 10),
+        Err(SolverError::InvalidState(_))
+    ));
+    game.resolve_subtree(&[1], &[(vec![1], call)], 100).unwrap();
+    assert!(game.history().is_empty());
+
+    // the strategy above the subtree is kept, but the expected values are updated
+    game.cache_normalized_weights();
+    assert_eq!(game.strategy(), root_strategy);
+    assert_ne!(game.expected_values(0), root_ev);
+    let ev = compute_current_ev(&game);
+    let average = compute_average(&game.expected_values(0), game.normalized_weights(0));
+    assert!((average - (ev[0] + 50.0)).abs() < 1e-3);
+
+    game.apply_history(&[1]);
+    let strategy = game.strategy();
+    assert!(strategy[num_hands..2 * num_hands].iter().all(|&x| x == 1.0));
+
+    // re-solving without locks hardly changes the expected values of the equilibrium
+    game.back_to_root();
+    let ev = compute_current_ev(&game);
+    game.resolve_subtree(&[1, 1], &[], 200).unwrap();
+    let new_ev = compute_current_ev(&game);
+    assert!((new_ev[0] - ev[0]).abs() < 0.5);
+}
+
+#[test]
+fn finalize_progress() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+    game.cache_normalized_weights();
+    let expected = game.expected_values(0);
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    for t in 0..50 {
+        solve_step(&game, t);
+    }
+
+    let reports = std::sync::Mutex::new(Vec::new());
+    finalize_with_progress(&mut game, |done, total| {
+        reports.lock().unwrap().push((done, total));
+    });
+    assert!(game.is_solved());
+
+    let mut reports = reports.into_inner().unwrap();
+    reports.sort_unstable();
+    let (done, total) = *reports.last().unwrap();
+    assert_eq!(done, total);
+    assert!(reports.iter().all(|&(d, t)| t == total && d <= total));
+
+    game.cache_normalized_weights();
+    assert_eq!(game.expected_values(0), expected);
+}
+
+#[test]
+#[cfg(all(feature = "bincode", feature = "mmap", unix))]
+fn load_game_from_file_mmap() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let path = std::env::temp_dir().join(format!(
+        "postflop-solver-test-mmap-{}.bin",
+        std::process::id()
+    ));
+
+    for enable_compression in [false, true] {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(enable_compression);
+        solve(&mut game, 50, 0.0, false);
+        game.cache_normalized_weights();
+
+        for mode in [BoardState::River, BoardState::Turn] {
+            game.set_target_storage_mode(mode).unwrap();
+            crate::save_data_to_file(&game, "memo", &path, None).unwrap();
+            let (mut loaded, memo) = crate::load_game_from_file_mmap(&path).unwrap();
+            assert_eq!(memo, "memo");
+            assert!(loaded.is_memory_on_disk());
+
+            loaded.cache_normalized_weights();
+            for history in [&[][..], &[0], &[1]] {
+                game.apply_history(history);
+                loaded.apply_history(history);
+                game.cache_normalized_weights();
+                loaded.cache_normalized_weights();
+                assert_eq!(loaded.strategy(), game.strategy());
+                if mode == BoardState::Turn {
+                    assert_eq!(loaded.expected_values(0), game.expected_values(0));
+                }
+            }
+            game.back_to_root();
+        }
+
+        // compressed files cannot be mapped
+        #[cfg(feature = "zstd")]
+        {
+            crate::save_data_to_file(&game, "", &path, Some(1)).unwrap();
+            assert!(crate::load_game_from_file_mmap(&path).is_err());
+        }
+    }
+
+    std::fs::remove_file(&path).ok();
+    assert!(crate::load_game_from_file_mmap(&path).is_err());
+}
+
+#[test]
+fn zeroed_node_arena() {
+    let arena = PostFlopNode::zeroed_arena(4).unwrap();
+    let default = PostFlopNode::default();
+    assert_eq!(arena.len(), 4);
+    for node in &arena {
+        let node = node.lock();
+        assert_eq!(node.prev_action, default.prev_action);
+        assert_eq!(node.player, default.player);
+        assert_eq!(node.is_locked, default.is_locked);
+        assert_eq!(node.amount, default.amount);
+        assert_eq!(node.children_offset, default.children_offset);
+        assert_eq!(node.num_children, default.num_children);
+        assert_eq!(node.num_elements, default.num_elements);
+        assert_eq!(node.num_elements_ip, default.num_elements_ip);
+        assert_eq!(node.scale1, default.scale1);
+        assert!(node.storage1.is_null() && node.storage2.is_null() && node.storage3.is_null());
+    }
+    assert!(PostFlopNode::zeroed_arena(0).unwrap().is_empty());
+}
+
+#[test]
+#[cfg(feature = "bincode")]
+fn action_tree_arena() {
+    let card_config = CardConfig {
+        range: ["QQ+,AK".parse().unwrap(), "JJ-99,AQs".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+    let sizes = crate::BetSizeOptions::try_from(("50%, 100%", "2.5x")).unwrap();
+    let tree_config = TreeConfig {
+        starting_pot: 100,
+        effective_stack: 300,
+        flop_bet_sizes: [sizes.clone(), sizes.clone()],
+        turn_bet_sizes: [sizes.clone(), sizes],
+        ..Default::default()
+    };
+
+    let fresh = ActionTree::new(tree_config.clone()).unwrap();
+    let mut edited = ActionTree::new(tree_config).unwrap();
+    edited.add_line(&[Action::Check, Action::Bet(30)]).unwrap();
+    edited.remove_line(&[Action::Bet(50)]).unwrap();
+    edited.add_line(&[Action::Bet(50)]).unwrap();
+    edited
+        .remove_line(&[Action::Check, Action::Bet(30)])
+        .unwrap();
+    assert!(edited.added_lines().is_empty() && edited.removed_lines().is_empty());
+
+    // editing leaves no unreachable nodes, and the nodes are laid out as in a fresh tree
+    let fresh = PostFlopGame::with_config(card_config.clone(), fresh).unwrap();
+    let edited = PostFlopGame::with_config(card_config, edited).unwrap();
+    assert_eq!(
+        fresh.action_nodes.nodes.len(),
+        edited.action_nodes.nodes.len()
+    );
+    let config = bincode::config::standard();
+    let encoded = bincode::encode_to_vec(&*fresh.action_nodes, config).unwrap();
+    assert_eq!(
+        encoded,
+        bincode::encode_to_vec(&*edited.action_nodes, config).unwrap()
+    );
+
+    let (decoded, _): (ActionTreeArena, _) = bincode::decode_from_slice(&encoded, config).unwrap();
+    assert_eq!(decoded.nodes.len(), fresh.action_nodes.nodes.len());
+    assert_eq!(decoded.actions, fresh.action_nodes.actions);
+    assert_eq!(
+        count_num_action_nodes(&decoded),
+        count_num_action_nodes(&fresh.action_nodes)
+    );
+}
+
+#[test]
+fn compression_params() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let new_game = || {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        PostFlopGame::with_config(card_config.clone(), action_tree).unwrap()
+    };
+
+    let mut game = new_game();
+    let invalid = [
+        CompressionParams {
+            reserved_bits: 9,
+            ..Default::default()
+        },
+        CompressionParams {
+            rescale_interval: 0,
+            ..Default::default()
+        },
+    ];
+    for params in invalid {
+        game.set_compression_params(BoardState::River, params)
+            .unwrap_err();
+    }
+    assert_eq!(
+        game.compression_params(BoardState::River),
+        CompressionParams::default()
+    );
+
+    game.allocate_memory(true);
+    let exploitability = solve(&mut game, 200, 0.0, false);
+
+    let params = CompressionParams {
+        reserved_bits: 2,
+        rescale_interval: 4,
+    };
+    let mut tuned_game = new_game();
+    tuned_game
+        .set_compression_params(BoardState::River, params)
+        .unwrap();
+    assert_eq!(tuned_game.compression_params(BoardState::River), params);
+    assert_eq!(
+        tuned_game.compression_params(BoardState::Turn),
+        CompressionParams::default()
+    );
+
+    // the parameters are inherited by a game sharing the action tree
+    let shared_game = PostFlopGame::with_shared_tree(card_config.clone(), &tuned_game).unwrap();
+    assert_eq!(shared_game.compression_params(BoardState::River), params);
+
+    tuned_game.allocate_memory(true);
+    let tuned_exploitability = solve(&mut tuned_game, 200, 0.0, false);
+    assert!(tuned_exploitability < 0.5);
+    assert_ne!(tuned_exploitability, exploitability);
+}
+
+#[test]
+fn update_schedule() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 100,
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let schedule = UpdateSchedule {
+        interval: 0,
+        ..Default::default()
+    };
+    game.set_update_schedule(0, schedule).unwrap_err();
+    game.set_update_schedule(2, Default::default()).unwrap_err();
+    assert_eq!(game.update_schedule(0), UpdateSchedule::default());
+
+    let schedule = UpdateSchedule {
+        interval: 3,
+        freeze_after: Some(10),
+    };
+    assert!(schedule.is_update_iteration(0));
+    assert!(!schedule.is_update_iteration(2));
+    assert!(schedule.is_update_iteration(9));
+    assert!(!schedule.is_update_iteration(12));
+
+    // IP is never updated, so its strategy stays uniform
+    let frozen = UpdateSchedule {
+        freeze_after: Some(0),
+        ..Default::default()
+    };
+    game.set_update_schedule(1, frozen).unwrap();
+    assert_eq!(game.update_schedule(1), frozen);
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    game.play(0);
+    game.cache_normalized_weights();
+    assert_eq!(game.current_player(), 1);
+    let uniform = 1.0 / game.available_actions().len() as f32;
+    assert!(game.strategy().iter().all(|&x| (x - uniform).abs() < 1e-6));
+
+    // OOP converges to the best response to the fixed IP strategy
+    let current_ev = compute_current_ev(&game);
+    let mes_ev = compute_mes_ev(&game);
+    assert!(mes_ev[0] - current_ev[0] < 0.1);
+    assert!(mes_ev[1] - current_ev[1] > 1.0);
+}
+
+#[test]
+fn try_allocate_memory() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let (uncompressed, compressed) = game.memory_usage();
+    let quantized = game.memory_usage_quantized();
+
+    // no limit
+    let result = game.try_allocate_memory(false, None, AllocationFallback::None);
+    assert_eq!(result, Ok((StorageFormat::Uncompressed, BoardState::River)));
+
+    // falls back to the compressed format
+    let result = game.try_allocate_memory(false, Some(compressed), AllocationFallback::Compress);
+    assert_eq!(result, Ok((StorageFormat::Compressed, BoardState::River)));
+
+    // falls back to the quantized format
+    let result = game.try_allocate_memory(true, Some(quantized), AllocationFallback::Compress);
+    assert_eq!(result, Ok((StorageFormat::Quantized, BoardState::River)));
+
+    // defers the allocation of the river storage
+    let result = game.try_allocate_memory(
+        true,
+        Some(quantized - 1),
+        AllocationFallback::CompressAndDefer,
+    );
+    assert_eq!(result, Ok((StorageFormat::Quantized, BoardState::Turn)));
+
+    // no fallback
+    let error = game
+        .try_allocate_memory(false, Some(uncompressed - 1), AllocationFallback::None)
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("Memory usage exceeds the limit by 1 bytes")
+    );
+    assert!(matches!(
+        error,
+        SolverError::MemoryLimitExceeded { required, available, .. }
+            if required == uncompressed && available == uncompressed - 1
+    ));
+    assert!(!game.is_ready());
+
+    // the game can be solved after the fallback
+    let result = game.try_allocate_memory(false, Some(compressed), AllocationFallback::Compress);
+    assert_eq!(result, Ok((StorageFormat::Compressed, BoardState::River)));
+    let exploitability = solve(&mut game, 100, 0.0, false);
+    assert!(exploitability < 1.0);
+}
+
+#[test]
+#[cfg(feature = "profiling")]
+fn profiling() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    // the counters are shared with the other tests running concurrently
+    let before = profiling_report();
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+
+    let after = profiling_report();
+    assert!(after.tree_build > before.tree_build);
+    assert!(after.iterations > before.iterations);
+    assert!(after.num_iterations >= before.num_iterations + 10);
+    assert!(after.evaluation[BoardState::River as usize] > before.evaluation[2]);
+    assert!(after.regret_update > before.regret_update);
+    assert!(after.strategy_sum > before.strategy_sum);
+    assert!(after.finalize > before.finalize);
+    assert!(after.to_string().contains("river evaluation"));
+}
+
+#[test]
+fn table_cache() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    assert!(TableCache::new(0).is_err());
+    let cache = TableCache::new(1).unwrap();
+    assert!(TableCache::global().ptr_eq(&TableCache::global()));
+
+    let reference = PostFlopGame::with_config(
+        card_config.clone(),
+        ActionTree::new(tree_config.clone()).unwrap(),
+    )
+    .unwrap();
+
+    let mut game = PostFlopGame::new();
+    game.set_table_cache(Some(cache.clone()));
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    game.update_config(card_config.clone(), action_tree)
+        .unwrap();
+    assert_eq!(cache.stats(), (0, 1));
+    assert_eq!(cache.len(), 1);
+
+    // the shared instance inherits the cache and hits it
+    let shared = PostFlopGame::with_shared_tree(card_config.clone(), &game).unwrap();
+    assert!(shared.table_cache().unwrap().ptr_eq(&cache));
+    assert_eq!(cache.stats(), (1, 1));
+    assert!(shared.hand_strength == reference.hand_strength);
+    assert!(shared.valid_indices_river == reference.valid_indices_river);
+    assert!(shared.isomorphism_swap_river == reference.isomorphism_swap_river);
+
+    // a different board evicts the oldest entry
+    let other_config = CardConfig {
+        turn: card_from_str("3c").unwrap(),
+        ..card_config.clone()
+    };
+    let other = PostFlopGame::with_shared_tree(other_config, &game).unwrap();
+    assert_eq!(cache.stats(), (1, 2));
+    assert_eq!(cache.len(), 1);
+    assert!(other.hand_strength != reference.hand_strength);
+
+    let mut game = PostFlopGame::with_shared_tree(card_config, &game).unwrap();
+    assert_eq!(cache.stats(), (1, 3));
+
+    // the isomorphism mode is a part of the key
+    game.set_isomorphism_mode(IsomorphismMode::Disabled)
+        .unwrap();
+    assert_eq!(cache.stats(), (1, 4));
+
+    // the cached tables give the same result
+    game.set_isomorphism_mode(IsomorphismMode::default())
+        .unwrap();
+    assert_eq!(cache.stats(), (1, 5));
+    let mut reference = reference;
+    reference.allocate_memory(false);
+    game.allocate_memory(false);
+    let exploitability = solve(&mut reference, 50, 0.0, false);
+    assert_eq!(solve(&mut game, 50, 0.0, false), exploitability);
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn bookmark() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+
+    // river node
+    let history = [0, 1, 1, usize::MAX, 0];
+    game.apply_history(&history);
+    game.cache_normalized_weights();
+    let expected = (
+        game.history().to_vec(),
+        game.current_board(),
+        game.total_bet_amount(),
+        game.strategy(),
+        game.normalized_weights(0).to_vec(),
+        game.expected_values(1),
+    );
+    let bookmark = game.bookmark();
+    assert_eq!(bookmark.history(), &*expected.0);
+
+    game.apply_history(&[1]);
+    let other = game.bookmark();
+    game.restore_bookmark(&bookmark).unwrap();
+    assert_eq!(game.history(), &*expected.0);
+    assert_eq!(game.current_board(), expected.1);
+    assert_eq!(game.total_bet_amount(), expected.2);
+    assert_eq!(game.strategy(), expected.3);
+    assert_eq!(game.normalized_weights(0), &*expected.4);
+    assert_eq!(game.expected_values(1), expected.5);
+
+    game.restore_bookmark(&other).unwrap();
+    assert_eq!(game.history(), &[1]);
+    assert!(!game.is_chance_node());
+
+    // bookmarks of another instance are rejected
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game2 = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game2.allocate_memory(false);
+    assert!(game2.restore_bookmark(&bookmark).is_err());
+}
+
+#[test]
+fn undo_redo_and_named_bookmarks() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s".parse().unwrap(),
+            "JJ,AK,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 20, 0.0, false);
+
+    assert!(!game.can_undo());
+    game.undo().unwrap_err();
+    game.redo().unwrap_err();
+
+    game.play(0);
+    game.cache_normalized_weights();
+    let strategy = game.strategy();
+    game.play(0);
+    game.play(usize::MAX);
+    let history = game.history().to_vec();
+    game.set_bookmark("river");
+
+    // back to the first action, keeping the cached normalized weights
+    game.undo().unwrap();
+    game.undo().unwrap();
+    assert_eq!(game.history(), [0]);
+    assert_eq!(game.strategy(), strategy);
+    assert!(game.can_redo());
+
+    // the undone actions are replayed in order, including the dealt card
+    game.redo().unwrap();
+    game.redo().unwrap();
+    assert_eq!(game.history(), history);
+    assert!(!game.can_redo());
+
+    // playing another action discards the undone actions
+    game.undo().unwrap();
+    game.undo().unwrap();
+    game.play(1);
+    assert!(!game.can_redo());
+    game.undo().unwrap();
+    game.redo().unwrap();
+    assert_eq!(game.history(), [0, 1]);
+    game.undo().unwrap();
+
+    game.set_bookmark("root");
+    assert_eq!(game.bookmark_names(), ["river", "root"]);
+    game.goto_bookmark("river").unwrap();
+    assert_eq!(game.history(), history);
+    game.goto_bookmark("flop").unwrap_err();
+    assert!(game.remove_bookmark("river").is_some());
+    game.goto_bookmark("river").unwrap_err();
+    assert_eq!(game.bookmark_names(), ["root"]);
+}
+
+#[test]
+#[cfg(all(feature = "bincode", feature = "zstd"))]
+fn save_load_zstd() {
+    use crate::{load_data_from_std_read, save_data_into_std_write, zstd_compression_level_range};
+
+    let card_config = CardConfig {
+        range: [
+            "66+,A8s+,KQs".parse().unwrap(),
+            "22+,A2s+,KTs+".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+    game.cache_normalized_weights();
+    let strategy = game.strategy();
+    let expected_values = game.expected_values(0);
+
+    let mut raw = Vec::new();
+    save_data_into_std_write(&game, "raw", &mut raw, None).unwrap();
+
+    // the compressed files are smaller and load into the same game
+    let range = zstd_compression_level_range();
+    for level in [0, 1, *range.end()] {
+        let mut compressed = Vec::new();
+        save_data_into_std_write(&game, "zstd", &mut compressed, Some(level)).unwrap();
+        assert!(compressed.len() < raw.len());
+
+        let (mut loaded, memo): (PostFlopGame, _) =
+            load_data_from_std_read(&mut compressed.as_slice(), None).unwrap();
+        assert_eq!(memo, "zstd");
+        loaded.cache_normalized_weights();
+        assert_eq!(loaded.strategy(), strategy);
+        assert_eq!(loaded.expected_values(0), expected_values);
+    }
+
+    // the levels out of the range are rejected before writing anything
+    for level in [*range.start() - 1, *range.end() + 1] {
+        let mut buffer = Vec::new();
+        let result = save_data_into_std_write(&game, "", &mut buffer, Some(level));
+        assert!(matches!(result, Err(SolverError::InvalidArgument(_))));
+        assert!(buffer.is_empty());
+    }
+
+    // a truncated compressed file fails to load
+    let mut compressed = Vec::new();
+    save_data_into_std_write(&game, "", &mut compressed, Some(3)).unwrap();
+    compressed.truncate(compressed.len() / 2);
+    let result: Result<(PostFlopGame, _), _> =
+        load_data_from_std_read(&mut compressed.as_slice(), None);
+    assert!(result.is_err());
+}
+
+#[test]
+#[cfg(feature = "bincode")]
+fn save_format_versions() {
+    use crate::{load_data_from_std_read, save_data_into_std_write};
+
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(true);
+    solve(&mut game, 50, 0.0, false);
+    game.apply_history(&[1]);
+    game.cache_normalized_weights();
+    let strategy = game.strategy();
+    let expected_values = game.expected_values(1);
+
+    assert_eq!(
+        SUPPORTED_SAVE_FORMAT_VERSIONS.last(),
+        Some(&SAVE_FORMAT_VERSION)
+    );
+
+    // the current version restores the game exactly
+    let mut buffer = Vec::new();
+    save_data_into_std_write(&game, "", &mut buffer, None).unwrap();
+    let (mut loaded, _): (PostFlopGame, _) =
+        load_data_from_std_read(&mut buffer.as_slice(), None).unwrap();
+    loaded.apply_history(&[1]);
+    loaded.cache_normalized_weights();
+    assert_eq!(loaded.strategy(), strategy);
+    assert_eq!(loaded.expected_values(1), expected_values);
+
+    // the same game saved and solved by the release of the oldest version
+    assert_eq!(SUPPORTED_SAVE_FORMAT_VERSIONS[0], "2023-03-19");
+    let golden = include_bytes!("../../tests/data/save_2023-03-19.bin");
+    let (mut loaded, memo): (PostFlopGame, _) =
+        load_data_from_std_read(&mut golden.as_slice(), None).unwrap();
+    assert_eq!(memo, "2023-03-19");
+    assert!(loaded.is_solved());
+    assert!(loaded.is_compression_enabled());
+    assert_eq!(loaded.card_config().range, game.card_config().range);
+    assert_eq!(loaded.card_config().turn, game.card_config().turn);
+    assert_eq!(loaded.tree_config().starting_pot, 100);
+    assert_eq!(loaded.tree_config().rake_config, None);
+    loaded.apply_history(&[1]);
+    loaded.cache_normalized_weights();
+    let close = |a: &[f32], b: &[f32]| {
+        a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-2)
+    };
+    assert!(close(&loaded.strategy(), &strategy));
+    assert!(close(&loaded.expected_values(1), &expected_values));
+
+    // unknown versions are rejected with a hint
+    let mut buffer = Vec::new();
+    save_data_into_std_write(&game, "", &mut buffer, None).unwrap();
+    let position = buffer
+        .windows(SAVE_FORMAT_VERSION.len())
+        .position(|w| w == SAVE_FORMAT_VERSION.as_bytes())
+        .unwrap();
+
+    for (version, hint) in [("2099-01-01", "newer"), ("2024-01-01", "development")] {
+        let mut buffer = buffer.clone();
+        buffer[position..position + version.len()].copy_from_slice(version.as_bytes());
+        let result: Result<(PostFlopGame, _), _> =
+            load_data_from_std_read(&mut buffer.as_slice(), None);
+        assert!(result.is_err_and(|e| {
+            let message = e.to_string();
+            message.contains(version) && message.contains(hint)
+        }));
+    }
+}
+
+#[test]
+#[cfg(feature = "bincode")]
+fn corrupt_save_recovery() {
+    use crate::save_data_into_std_write;
+    use crate::{load_data_from_std_read, load_game_from_std_read_recovering};
+
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+    game.cache_normalized_weights();
+    let strategy = game.strategy();
+
+    let mut buffer = Vec::new();
+    save_data_into_std_write(&game, "", &mut buffer, None).unwrap();
+
+    let (_, _, sections) =
+        load_game_from_std_read_recovering(&mut buffer.as_slice(), None).unwrap();
+    assert!(sections.is_empty());
+
+    // corrupt strategy of the river
+    let river_start = game.storage_bytes_up_to(BoardState::Turn)[0];
+    let window = &game.storage1[..4096];
+    let position = buffer.windows(4096).position(|w| w == window).unwrap();
+    let mut corrupt = buffer.clone();
+    corrupt[position + river_start + 8] ^= 1;
+
+    let result: Result<(PostFlopGame, _), _> =
+        load_data_from_std_read(&mut corrupt.as_slice(), None);
+    assert!(result.is_err_and(|e| e.to_string().contains("strategy of the river")));
+
+    let (mut loaded, _, sections) =
+        load_game_from_std_read_recovering(&mut corrupt.as_slice(), None).unwrap();
+    assert_eq!(sections, [SaveSection::Strategy(BoardState::River)]);
+    assert_eq!(loaded.storage_mode(), BoardState::Turn);
+    assert!(loaded.is_strategy_only());
+    loaded.cache_normalized_weights();
+    assert_eq!(loaded.strategy(), strategy);
+
+    // corrupt nodes of the river (the last node is a river node)
+    let mut corrupt = buffer.clone();
+    *corrupt.last_mut().unwrap() ^= 1;
+
+    let result: Result<(PostFlopGame, _), _> =
+        load_data_from_std_read(&mut corrupt.as_slice(), None);
+    assert!(result.is_err_and(|e| e.to_string().contains("nodes of the river")));
+
+    let (mut loaded, _, sections) =
+        load_game_from_std_read_recovering(&mut corrupt.as_slice(), None).unwrap();
+    assert_eq!(sections, [SaveSection::Tree(BoardState::River)]);
+    assert_eq!(loaded.storage_mode(), BoardState::Turn);
+    loaded.cache_normalized_weights();
+    assert_eq!(loaded.strategy(), strategy);
+
+    // corrupt configuration cannot be recovered
+    let position = buffer
+        .windows(SAVE_FORMAT_VERSION.len())
+        .position(|w| w == SAVE_FORMAT_VERSION.as_bytes())
+        .unwrap();
+    let mut corrupt = buffer.clone();
+    corrupt[position + SAVE_FORMAT_VERSION.len() + 32] ^= 1;
+    let result = load_game_from_std_read_recovering(&mut corrupt.as_slice(), None);
+    assert!(result.is_err_and(|e| e.to_string().contains("configuration")));
+}
+
+#[test]
+#[cfg(feature = "bincode")]
+fn strategy_only_save() {
+    use crate::{load_data_from_std_read, save_data_into_std_write};
+
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s,A5s".parse().unwrap(),
+            "JJ,AK,AQ,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert!(game.set_target_strategy_only(true).is_err());
+
+    solve(&mut game, 50, 0.0, false);
+    let mut full = Vec::new();
+    save_data_into_std_write(&game, "", &mut full, None).unwrap();
+
+    game.set_target_strategy_only(true).unwrap();
+    assert!(game.target_memory_usage() < game.memory_usage().0);
+    let mut buffer = Vec::new();
+    save_data_into_std_write(&game, "", &mut buffer, None).unwrap();
+    assert!(buffer.len() < full.len());
+
+    let (mut loaded, _): (PostFlopGame, _) =
+        load_data_from_std_read(&mut buffer.as_slice(), None).unwrap();
+    assert!(loaded.is_strategy_only());
+    assert!(!loaded.is_solved());
+    assert!(!loaded.is_ready());
+    assert!(loaded.set_target_strategy_only(false).is_err());
+
+    for history in [&[][..], &[1], &[0, 1, 1, 5]] {
+        game.apply_history(history);
+        loaded.apply_history(history);
+        game.cache_normalized_weights();
+        loaded.cache_normalized_weights();
+        assert_eq!(loaded.strategy(), game.strategy());
+        assert_eq!(loaded.normalized_weights(0), game.normalized_weights(0));
+    }
+}
+
+#[test]
+fn solver_error_context() {
+    let tree_config = TreeConfig {
+        starting_pot: 100,
+        effective_stack: 1000,
+        flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+    let mut action_tree = ActionTree::new(tree_config).unwrap();
+
+    // the path stops at the action that does not exist
+    let error = action_tree
+        .add_line(&[
+            Action::Check,
+            Action::Bet(30),
+            Action::Call,
+            Action::Bet(60),
+        ])
+        .unwrap_err();
+    match error {
+        SolverError::InvalidLine { line, .. } => assert_eq!(line, [Action::Check, Action::Bet(30)]),
+        _ => panic!("unexpected error: {error:?}"),
+    }
+
+    let error = "AA,KK,QQ+,T9x".parse::<Range>().unwrap_err();
+    assert!(matches!(error, SolverError::InvalidRange { token, .. } if token == "T9x"));
+
+    let tree_config = TreeConfig {
+        starting_pot: 0,
+        ..Default::default()
+    };
+    let error = ActionTree::new(tree_config).err().unwrap();
+    assert!(matches!(error, SolverError::InvalidTreeConfig(_)));
+}
+
+#[test]
+#[cfg(feature = "tracing")]
+fn tracing_spans() {
+    use std::sync::{Arc, Mutex};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata};
+
+    /// Records the names of the spans and the number of the events.
+    #[derive(Default, Clone)]
+    struct Recorder {
+        spans: Arc<Mutex<Vec<&'static str>>>,
+        num_events: Arc<Mutex<usize>>,
+    }
+
+    impl tracing::Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(span.metadata().name());
+            Id::from_u64(spans.len() as u64)
+        }
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, _: &Event<'_>) {
+            *self.num_events.lock().unwrap() += 1;
+        }
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ".parse().unwrap(), "JJ,AK".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 100,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.try_allocate_memory(false, None, AllocationFallback::None)
+            .unwrap();
+        solve(&mut game, 20, 0.0, false);
+    });
+
+    let spans = recorder.spans.lock().unwrap();
+    for name in [
+        "build_action_tree",
+        "build_game_tree",
+        "try_allocate_memory",
+        "allocate_memory",
+        "solve",
+        "solve_step",
+        "finalize",
+    ] {
+        assert!(spans.contains(&name), "missing span: {name}");
+    }
+    assert!(*recorder.num_events.lock().unwrap() >= 4);
+}
+
+#[test]
+fn solve_with_stop_flag() {
+    let card_config = CardConfig {
+        range: [
+            "QQ+,AK,87s".parse().unwrap(),
+            "JJ-99,AQs,KQ".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 100,
+        river_bet_sizes: [
+            ("50%", "60%").try_into().unwrap(),
+            ("50%", "60%").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    // stops after the iteration in which the flag is set
+    let stop = std::sync::atomic::AtomicBool::new(false);
+    let exploitability = solve_with_callback(
+        &mut game,
+        1000,
+        &mut TargetExploitability(0.0),
+        false,
+        Some(&stop),
+        &mut |_, num_iterations| {
+            if num_iterations == 15 {
+                stop.store(true, std::sync::atomic::Ordering::Relaxed);
+            }
+            Ok::<_, String>(())
+        },
+    )
+    .unwrap();
+
+    assert!(game.is_solved());
+    let summary = game.solve_summary().unwrap();
+    assert_eq!(summary.num_iterations, 15);
+    assert_eq!(summary.exploitability, exploitability);
+    assert_eq!(compute_exploitability(&game), exploitability);
+
+    // the finalized strategy can be queried
+    game.cache_normalized_weights();
+    let strategy = game.strategy();
+    let num_hands = game.private_cards(0).len();
+    for i in 0..num_hands {
+        let sum = (0..game.available_actions().len())
+            .map(|action| strategy[action * num_hands + i])
+            .sum::<f32>();
+        assert!((sum - 1.0).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn hand_strategies() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s".parse().unwrap(),
+            "JJ,AK,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    // unsolved: no expected values
+    let strategies = game.hand_strategies().unwrap();
+    assert_eq!(strategies.len(), game.private_cards(0).len());
+    assert!(strategies.values().all(|s| s.action_evs.is_none()));
+
+    solve(&mut game, 20, 0.0, false);
+    game.play(1);
+    game.cache_normalized_weights();
+    let strategy = game.strategy();
+    let expected_values = game.expected_values(1);
+    let num_hands = game.private_cards(1).len();
+
+    let strategies = game.hand_strategies().unwrap();
+    for (i, &hole) in game.private_cards(1).iter().enumerate() {
+        let hand = &strategies[&hole_to_string(hole).unwrap()];
+        assert_eq!(hand.weight, game.normalized_weights(1)[i]);
+        assert_eq!(hand.strategy, [strategy[i], strategy[num_hands + i]]);
+        assert!((hand.expected_value.unwrap() - expected_values[i]).abs() < 1e-3);
+    }
+
+    // call -> river card
+    game.play(1);
+    assert!(matches!(
+        game.hand_strategies(),
+        Err(SolverError::InvalidState(_))
+    ));
+}
+
+#[test]
+fn update_tree_config() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,87s".parse().unwrap(),
+            "JJ,AK,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+    let tree_config = |sizes: &str| TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [(sizes, "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [(sizes, "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config("50%")).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+
+    let action_tree = || {
+        let mut action_tree = ActionTree::new(tree_config("33%, 75%")).unwrap();
+        action_tree.remove_line(&[Action::Bet(75)]).unwrap();
+        action_tree
+    };
+    game.update_tree_config(action_tree()).unwrap();
+    assert!(!game.is_solved());
+    assert!(game.solve_summary().is_none());
+    assert_eq!(game.available_actions().len(), 2);
+
+    // same as building the game from scratch
+    let mut expected = PostFlopGame::with_config(card_config, action_tree()).unwrap();
+    assert_eq!(game.memory_usage(), expected.memory_usage());
+    game.allocate_memory(false);
+    expected.allocate_memory(false);
+    assert_eq!(
+        solve(&mut game, 20, 0.0, false),
+        solve(&mut expected, 20, 0.0, false)
+    );
+    game.cache_normalized_weights();
+    expected.cache_normalized_weights();
+    assert_eq!(game.strategy(), expected.strategy());
+
+    // the initial state cannot be changed
+    let river_config = TreeConfig {
+        initial_state: BoardState::River,
+        ..tree_config("50%")
+    };
+    let error = game
+        .update_tree_config(ActionTree::new(river_config).unwrap())
+        .unwrap_err();
+    assert!(matches!(error, SolverError::InvalidTreeConfig(_)));
+    assert!(game.is_solved());
+}
+
+#[test]
+fn shared_clone() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,87s".parse().unwrap(), "JJ,AK,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert!(matches!(
+        game.shared_clone(),
+        Err(SolverError::InvalidState(_))
+    ));
+    solve(&mut game, 100, 0.0, false);
+
+    let query = |game: &mut PostFlopGame, history: &[usize]| {
+        game.apply_history(history);
+        game.cache_normalized_weights();
+        (game.strategy(), game.expected_values(0))
+    };
+    let histories = [vec![], vec![1], vec![0, 1], vec![0, 0, 3, 1]];
+    let expected = histories
+        .iter()
+        .map(|history| query(&mut game, history))
+        .collect::<Vec<_>>();
+    game.back_to_root();
+
+    // the clones are navigated independently from different threads
+    let mut clones = (0..histories.len())
+        .map(|_| game.shared_clone().unwrap())
+        .collect::<Vec<_>>();
+    assert!(clones.iter().all(|clone| clone.shares_storage_with(&game)));
+    std::thread::scope(|scope| {
+        for ((clone, history), expected) in clones.iter_mut().zip(&histories).zip(&expected) {
+            scope.spawn(move || assert_eq!(&query(clone, history), expected));
+        }
+    });
+    assert!(game.history().is_empty());
+
+    // the storage is copied before being written to
+    let clone = &mut clones[0];
+    clone.apply_history(&[1]);
+    let num_hands = clone.private_cards(1).len();
+    let mut strategy = vec![0.0; 2 * num_hands];
+    strategy[num_hands..].fill(1.0);
+    clone.override_current_strategy(&strategy).unwrap();
+    assert!(!clone.shares_storage_with(&game));
+    assert_ne!(query(clone, &[]), expected[0]);
+    assert_eq!(query(&mut clones[1], &[]), expected[0]);
+    assert_eq!(query(&mut game, &[]), expected[0]);
+
+    drop(clones);
+    let history = &histories[3];
+    assert_eq!(query(&mut game, history), expected[3]);
+}
+
+#[test]
+fn solve_snapshots() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,87s".parse().unwrap(), "JJ,AK,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let new_game = || {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        game
+    };
+    let query = |game: &mut PostFlopGame| {
+        game.apply_history(&[0, 1]);
+        game.cache_normalized_weights();
+        (game.strategy(), game.expected_values(0))
+    };
+
+    // a snapshot is the same as the game solved for the same number of iterations
+    let game = new_game();
+    assert!(matches!(
+        PostFlopGame::default().snapshot(),
+        Err(SolverError::InvalidState(_))
+    ));
+    for t in 0..5 {
+        solve_step(&game, t);
+    }
+    let mut snapshot = game.snapshot().unwrap();
+    let mut expected = new_game();
+    solve(&mut expected, 5, 0.0, false);
+    assert!(snapshot.is_solved());
+    assert!(!game.is_solved());
+    assert_eq!(query(&mut snapshot), query(&mut expected));
+
+    // the solving continues after taking a snapshot
+    for t in 5..10 {
+        solve_step(&game, t);
+    }
+    let mut expected = new_game();
+    solve(&mut expected, 10, 0.0, false);
+    assert_eq!(query(&mut game.snapshot().unwrap()), query(&mut expected));
+
+    // the snapshots are read while solving
+    let mut game = new_game();
+    let slot = SnapshotSlot::new();
+    assert!(slot.latest().is_none());
+    std::thread::scope(|scope| {
+        let solver = scope.spawn(|| solve_with_snapshots(&mut game, 30, 0.0, false, 5, &slot));
+        while !solver.is_finished() {
+            if let Some((iteration, mut snapshot)) = slot.latest() {
+                assert!(iteration % 5 == 0 || iteration == 30);
+                assert!(snapshot.is_solved());
+                query(&mut snapshot);
+            }
+        }
+    });
+
+    let (iteration, mut snapshot) = slot.latest().unwrap();
+    assert_eq!(iteration, 30);
+    assert_eq!(slot.iteration(), 30);
+    assert!(snapshot.shares_storage_with(&game));
+    assert_eq!(query(&mut snapshot), query(&mut game));
+}
+
+#[test]
+fn snapshot_street_readiness() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 100,
+        effective_stack: 100,
+        flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    let streets = [BoardState::Flop, BoardState::Turn, BoardState::River];
+
+    // every street is stable with the threshold of 1
+    let slot = SnapshotSlot::with_stability_threshold(1.0);
+    std::thread::scope(|scope| {
+        let solver = scope.spawn(|| solve_with_snapshots(&mut game, 30, 0.0, false, 5, &slot));
+        while !solver.is_finished() {
+            // the flags are read in the reverse order of setting them
+            let iteration = slot.iteration();
+            let [river, turn, flop] = [BoardState::River, BoardState::Turn, BoardState::Flop]
+                .map(|street| slot.is_street_ready(street));
+            assert!(flop >= turn && turn >= river);
+
+            // the streets are marked ready after publishing the snapshot of the iteration 10
+            assert!(iteration < 15 || river);
+            assert!(!flop || slot.iteration() >= 10);
+        }
+    });
+
+    // nothing is ready until the end with the threshold of 0
+    let action_tree = ActionTree::new(game.tree_config().clone()).unwrap();
+    let mut game = PostFlopGame::with_config(game.card_config().clone(), action_tree).unwrap();
+    game.allocate_memory(false);
+    let slot = SnapshotSlot::with_stability_threshold(0.0);
+    std::thread::scope(|scope| {
+        let solver = scope.spawn(|| solve_with_snapshots(&mut game, 30, 0.0, false, 5, &slot));
+        while !solver.is_finished() {
+            // the flags are set after the iteration of the final snapshot
+            let is_ready = slot.is_street_ready(BoardState::Flop);
+            assert!(!is_ready || slot.iteration() == 30);
+        }
+    });
+    assert!(streets.iter().all(|&street| slot.is_street_ready(street)));
+}
+
+#[test]
+fn interpolate_bet_response() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,87s".parse().unwrap(), "JJ,AK,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 200,
+        river_bet_sizes: [("50%, 100%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    let actions = game.available_actions();
+    let response_at = |game: &mut PostFlopGame, action: Action| {
+        let index = actions.iter().position(|&a| a == action).unwrap();
+        game.play(index);
+        game.cache_normalized_weights();
+        let ret = (
+            game.strategy(),
+            [game.expected_values(0), game.expected_values(1)],
+        );
+        game.back_to_root();
+        ret
+    };
+    let (strategy50, ev50) = response_at(&mut game, Action::Bet(50));
+    let (strategy100, ev100) = response_at(&mut game, Action::Bet(100));
+
+    // tree sizes and sizes outside the tree range are mapped to a single action
+    for (amount, action, strategy) in [
+        (100, Action::Bet(100), &strategy100),
+        (30, Action::Bet(50), &strategy50),
+    ] {
+        let response = game.interpolate_bet_response(amount).unwrap();
+        assert_eq!(response.brackets, vec![(action, 1.0)]);
+        assert_eq!(response.player, 1);
+        assert_eq!(&response.strategy, strategy);
+    }
+
+    // pseudo-harmonic mapping of a 75% pot bet
+    let response = game.interpolate_bet_response(75).unwrap();
+    let weight = (0.25 * 1.5) / (0.5 * 1.75);
+    assert_eq!(
+        response.brackets,
+        vec![(Action::Bet(50), weight), (Action::Bet(100), 1.0 - weight)]
+    );
+    assert_eq!(response.actions, vec![Action::Fold, Action::Call]);
+    let mix = |a: &[f32], b: &[f32]| {
+        a.iter()
+            .zip(b)
+            .map(|(&a, &b)| a * weight + b * (1.0 - weight))
+            .collect::<Vec<_>>()
+    };
+    let close = |a: &[f32], b: &[f32]| a.iter().zip(b).all(|(&a, &b)| (a - b).abs() < 1e-4);
+    assert!(close(&response.strategy, &mix(&strategy50, &strategy100)));
+    let ev = response.expected_values.unwrap();
+    assert!(close(&ev[0], &mix(&ev50[0], &ev100[0])));
+    assert!(close(&ev[1], &mix(&ev50[1], &ev100[1])));
+    assert!(game.history().is_empty());
+
+    for amount in [0, 201] {
+        assert!(matches!(
+            game.interpolate_bet_response(amount),
+            Err(SolverError::InvalidArgument(_))
+        ));
+    }
+    game.play(0);
+    game.play(0);
+    game.interpolate_bet_response(50).unwrap_err();
+}
+
+#[test]
+fn extract_subtree() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 200,
+        rake_rate: 0.05,
+        rake_cap: 10.0,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let mut action_tree = ActionTree::new(tree_config).unwrap();
+    action_tree
+        .add_line(&[Action::Bet(50), Action::Call, Action::Bet(40)])
+        .unwrap();
+    action_tree
+        .add_line(&[Action::Check, Action::Check, Action::Bet(30)])
+        .unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    // only the first decision of a street can be extracted
+    game.play(1);
+    assert!(matches!(
+        game.extract_subtree(),
+        Err(SolverError::InvalidState(_))
+    ));
+    game.play(1);
+    assert!(game.extract_subtree().is_err());
+    assert_eq!(game.history(), &[1, 1]);
+    game.back_to_root();
+    game.apply_history(&[0, 0]);
+
+    let river = card_from_str("3s").unwrap();
+    game.play(river as usize);
+    let mut river_game = game.extract_subtree().unwrap();
+    assert_eq!(game.history(), &[0, 0, river as usize]);
+
+    assert_eq!(river_game.card_config().river, river);
+    let config = river_game.tree_config();
+    assert_eq!(config.initial_state, BoardState::River);
+    assert_eq!((config.starting_pot, config.effective_stack), (100, 200));
+    assert_eq!(config.rake_cap, 10.0);
+
+    // the added line of the subtree is applied, while the other one is not
+    assert_eq!(river_game.added_lines(), &[vec![Action::Bet(30)]]);
+    assert_eq!(river_game.available_actions(), game.available_actions());
+
+    // the ranges are weighted by the reach probabilities
+    for player in 0..2 {
+        let weights = game.weights(player);
+        for (i, &hand) in river_game.private_cards(player).iter().enumerate() {
+            let index = game.private_hand_index(player, hand).unwrap();
+            assert_eq!(river_game.weights(player)[i], weights[index]);
+        }
+        let num_hands = weights.iter().filter(|&&w| w > 0.0).count();
+        assert_eq!(river_game.private_cards(player).len(), num_hands);
+    }
+
+    river_game.allocate_memory(false);
+    assert!(solve(&mut river_game, 1000, 0.1, false) <= 0.1);
+}
+
+#[test]
+fn apply_best_response() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 100,
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(matches!(
+        game.apply_best_response(0),
+        Err(SolverError::InvalidState(_))
+    ));
+
+    game.allocate_memory(false);
+    assert!(matches!(
+        game.apply_best_response(2),
+        Err(SolverError::InvalidArgument(_))
+    ));
+    solve(&mut game, 20, 0.0, false);
+
+    let baseline_ev = compute_current_ev(&game);
+    let mes_ev = compute_mes_ev(&game);
+    game.play(1);
+    let report = game.apply_best_response(1).unwrap();
+    assert_eq!(game.history(), &[1]);
+    assert_eq!(report.baseline_ev, baseline_ev);
+    assert!((report.ev[1] - mes_ev[1]).abs() < 1e-3);
+    assert!(report.ev_shift[1] > 0.0);
+
+    // the strategy of IP is pure
+    game.cache_normalized_weights();
+    assert_eq!(game.current_player(), 1);
+    assert!(game.strategy().iter().all(|&x| x == 0.0 || x == 1.0));
+
+    game.clear_strategy_overrides();
+    assert_eq!(compute_current_ev(&game), baseline_ev);
+}
+
+#[test]
+fn apply_mixture_best_response() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 100,
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let new_game = || {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut baseline = new_game();
+    solve(&mut baseline, 50, 0.0, false);
+
+    // OOP always checks
+    let mut checker = new_game();
+    let num_hands = checker.num_private_hands(0);
+    let mut strategy = vec![0.0; checker.available_actions().len() * num_hands];
+    strategy[..num_hands].fill(1.0);
+    checker.lock_current_strategy(&strategy);
+    solve(&mut checker, 50, 0.0, false);
+
+    // single component: the best response against the component itself
+    let mut game = new_game();
+    let report = game
+        .apply_mixture_best_response(1, &mut [(1.0, &mut baseline)])
+        .unwrap();
+    assert!((report.ev[1] - compute_mes_ev(&baseline)[1]).abs() < 1e-3);
+
+    // at the root node, every hand is reached by both components
+    let mut game = new_game();
+    let report = game
+        .apply_mixture_best_response(1, &mut [(0.6, &mut baseline), (0.4, &mut checker)])
+        .unwrap();
+    assert!(game.history().is_empty());
+    let expected = baseline
+        .strategy()
+        .iter()
+        .zip(checker.strategy())
+        .map(|(a, b)| 0.6 * a + 0.4 * b)
+        .collect::<Vec<_>>();
+    for (a, b) in game.strategy().iter().zip(&expected) {
+        assert!((a - b).abs() < 1e-5);
+    }
+
+    // the best response against the mixture is not better than against each component
+    let bound = 0.6 * compute_mes_ev(&baseline)[1] + 0.4 * compute_mes_ev(&checker)[1];
+    assert!(report.ev[1] <= bound + 1e-3);
+
+    // errors
+    assert!(matches!(
+        game.lock_strategy_mixture(0, &mut [(1.0, &mut baseline)]),
+        Err(SolverError::InvalidState(_))
+    ));
+    let mut game = new_game();
+    assert!(matches!(
+        game.lock_strategy_mixture(0, &mut []),
+        Err(SolverError::InvalidArgument(_))
+    ));
+    game.lock_strategy_mixture(0, &mut [(-1.0, &mut baseline)])
+        .unwrap_err();
+    game.lock_strategy_mixture(2, &mut [(1.0, &mut baseline)])
+        .unwrap_err();
+}
+
+#[test]
+fn explain_action() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 100,
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    let hand = game.private_cards(0)[0];
+    assert!(matches!(
+        game.explain_action(hand, 0),
+        Err(SolverError::InvalidState(_))
+    ));
+    solve(&mut game, 200, 0.0, false);
+
+    // the hand that bets the most
     game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let root_equity_oop = compute_average(&game.equity(0), weights_oop);
-    let root_equity_ip = compute_average(&game.equity(1), weights_ip);
-    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    let num_hands = game.private_cards(0).len();
+    let strategy = game.strategy();
+    let hand_index = (0..num_hands)
+        .max_by(|&a, &b| strategy[num_hands + a].total_cmp(&strategy[num_hands + b]))
+        .unwrap();
+    let hand = game.private_cards(0)[hand_index];
+    let equity = game.equity(0);
+    let ev_detail = game.expected_values_detail(0);
+
+    let explanation = game.explain_action(hand, 1).unwrap();
+    assert!(game.history().is_empty());
+    assert_eq!(game.equity(0), equity);
+
+    assert_eq!(explanation.action, Action::Bet(50));
+    assert_eq!(explanation.pot, 100);
+    assert!((explanation.frequency - strategy[num_hands + hand_index]).abs() < 1e-6);
+    assert!((explanation.action_ev - ev_detail[num_hands + hand_index]).abs() < 1e-4);
+    assert!((explanation.equity - equity[hand_index]).abs() < 1e-4);
+    assert!(explanation.best_action_ev >= explanation.action_ev);
+
+    // the continuing range of IP is the calling range
+    let response = explanation.response.clone().unwrap();
+    assert!((0.0..=1.0).contains(&response.fold_frequency));
+    assert!((0.0..=1.0).contains(&response.blocked_fold_share));
+    assert!((0.0..=1.0).contains(&response.blocked_continue_share));
+    game.apply_history(&[1, 1]);
+    game.cache_normalized_weights();
+    assert!((response.continue_equity - game.equity(0)[hand_index]).abs() < 1e-4);
+    game.back_to_root();
 
-    // verified by PioSOLVER Free
-    assert!((root_equity_oop - 0.55347).abs() < 1e-5);
-    assert!((root_equity_ip - 0.44653).abs() < 1e-5);
-    assert!((root_ev_oop - 105.11).abs() < 0.2);
-    assert!((root_ev_ip - 74.89).abs() < 0.2);
+    // no response to a check
+    let explanation = game.explain_action(hand, 0).unwrap();
+    assert!(explanation.response.is_none());
+
+    // invalid arguments
+    assert!(matches!(
+        game.explain_action(hand, 2),
+        Err(SolverError::InvalidArgument(_))
+    ));
+    let ip_hand = game.private_cards(1)[0];
+    game.explain_action(ip_hand, 0).unwrap_err();
+    game.apply_history(&[1, 1]);
+    game.explain_action(hand, 0).unwrap_err();
 }
 
 #[test]
-#[ignore]
-fn solve_pio_preset_raked() {
-    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
-    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
+fn nash_distance_ranking() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 100,
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(matches!(
+        game.nash_distance_ranking(0),
+        Err(SolverError::InvalidState(_))
+    ));
+
+    game.allocate_memory(false);
+    assert!(matches!(
+        game.nash_distance_ranking(2),
+        Err(SolverError::InvalidArgument(_))
+    ));
+
+    // still being solved
+    for t in 0..10 {
+        solve_step(&game, t);
+    }
+    game.play(0);
+
+    let current_ev = compute_current_ev(&game);
+    let mes_ev = compute_mes_ev(&game);
+    for player in 0..2 {
+        let ranking = game.nash_distance_ranking(player).unwrap();
+        assert_eq!(game.history(), &[0]);
+        assert!(!ranking.is_empty());
+        assert!(ranking.windows(2).all(|w| w[0].distance >= w[1].distance));
+        assert!(ranking.iter().all(|hand| hand.distance > -1e-3));
+
+        let probability = ranking.iter().map(|hand| hand.probability).sum::<f32>();
+        assert!((probability - 1.0).abs() < 1e-4);
+
+        let total = ranking.iter().map(|h| h.weighted_distance).sum::<f32>();
+        let expected = mes_ev[player] - current_ev[player];
+        assert!((total - expected).abs() < 1e-3 * expected.abs().max(1.0));
+    }
+
+    // the expected values agree with those of a solved game
+    finalize(&mut game);
+    let ranking = game.nash_distance_ranking(0).unwrap();
+    game.back_to_root();
+    game.cache_normalized_weights();
+    let ev = game.expected_values(0);
+    for hand in &ranking {
+        assert!((hand.ev - ev[hand.hand_index]).abs() < 1e-2);
+    }
+}
 
+#[test]
+fn solve_until_criteria() {
     let card_config = CardConfig {
-        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
-        flop: flop_from_str("QsJh2h").unwrap(),
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
         ..Default::default()
     };
 
     let tree_config = TreeConfig {
-        starting_pot: 180,
-        effective_stack: 910,
-        rake_rate: 0.05,
-        rake_cap: 30.0,
-        flop_bet_sizes: [
-            ("52%", "45%").try_into().unwrap(),
-            ("52%", "45%").try_into().unwrap(),
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let new_game = || {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    // custom closure
+    let mut game = new_game();
+    let mut calls = Vec::new();
+    solve_until(
+        &mut game,
+        100,
+        &mut |_: &PostFlopGame, progress: &SolveProgress| {
+            calls.push(*progress);
+            progress.num_iterations == 25
+        },
+        false,
+    );
+    assert_eq!(game.solve_summary().unwrap().num_iterations, 25);
+    assert_eq!(calls.len(), 26);
+    assert!(calls[0].is_exploitability_updated);
+    assert!(!calls[5].is_exploitability_updated);
+    assert!(calls[20].is_exploitability_updated);
+    assert_eq!(calls[15].exploitability, calls[10].exploitability);
+
+    // the same as `solve` with a target exploitability
+    let mut game = new_game();
+    let exploitability = solve(&mut game, 1000, 1.0, false);
+    let mut other_game = new_game();
+    let other_exploitability =
+        solve_until(&mut other_game, 1000, &mut TargetExploitability(1.0), false);
+    assert_eq!(exploitability, other_exploitability);
+    assert_eq!(
+        game.solve_summary().unwrap().num_iterations,
+        other_game.solve_summary().unwrap().num_iterations
+    );
+
+    // stall: the exploitability is always compared with that of 20 iterations ago
+    let mut stall = ImprovementStall::new(2, 0.5);
+    let progress = |exploitability| SolveProgress {
+        num_iterations: 0,
+        exploitability,
+        is_exploitability_updated: true,
+    };
+    let game = new_game();
+    assert!(!stall.is_converged(&game, &progress(10.0)));
+    assert!(!stall.is_converged(&game, &progress(4.0)));
+    assert!(!stall.is_converged(&game, &progress(4.0)));
+    assert!(stall.is_converged(&game, &progress(3.0)));
+
+    // per-street stability and combination
+    let mut game = new_game();
+    let mut criterion = AnyOf(vec![
+        Box::new(
+            StreetStability::new(10)
+                .with_threshold(BoardState::Turn, 0.05)
+                .with_threshold(BoardState::River, 0.1),
+        ),
+        Box::new(TargetExploitability(0.0)),
+    ]);
+    solve_until(&mut game, 1000, &mut criterion, false);
+    let num_iterations = game.solve_summary().unwrap().num_iterations;
+    assert!((20..1000).contains(&num_iterations));
+    assert_eq!(num_iterations % 10, 0);
+}
+
+#[test]
+fn estimate_exploitability_sampled() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
         ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
         turn_bet_sizes: [
-            ("55%", "45%").try_into().unwrap(),
-            ("55%", "45%").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
         ],
         river_bet_sizes: [
-            ("70%", "45%").try_into().unwrap(),
-            ("70%", "45%").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
         ],
+        rake_rate: 0.05,
+        rake_cap: 10.0,
         ..Default::default()
     };
 
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.allocate_memory(false);
+    for t in 0..20 {
+        solve_step(&game, t);
+    }
+
+    let exploitability = compute_exploitability(&game);
+    let mut gaps = Vec::new();
+    for cards_per_chance in [1, 16] {
+        let estimate = estimate_exploitability(&game, 100, cards_per_chance, 0);
+        assert_eq!(estimate.num_samples, 100);
+        let (lower, upper) = estimate.confidence_interval(4.0);
+        assert!(lower < exploitability && exploitability < upper);
+        gaps.push(estimate.upper_bound - estimate.lower_bound);
+    }
+
+    // more cards give tighter bounds
+    assert!(gaps[1] < gaps[0]);
+
+    // exact without chance nodes
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        ..tree_config
+    };
+    let card_config = CardConfig {
+        river: card_from_str("3s").unwrap(),
+        ..card_config
+    };
     let action_tree = ActionTree::new(tree_config).unwrap();
     let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
-    println!(
-        "memory usage: {:.2}GB",
-        game.memory_usage().0 as f64 / (1024.0 * 1024.0 * 1024.0)
+    game.allocate_memory(false);
+    for t in 0..5 {
+        solve_step(&game, t);
+    }
+    let exploitability = compute_exploitability(&game);
+    let estimate = estimate_exploitability(&game, 3, 1, 0);
+    assert!((estimate.lower_bound - exploitability).abs() < 1e-4);
+    assert!((estimate.upper_bound - exploitability).abs() < 1e-4);
+    assert!(estimate.standard_error.iter().all(|&e| e < 1e-4));
+}
+
+#[test]
+fn solve_manifest() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,87s,65s".parse().unwrap(),
+            "QQ-99,AK".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 100,
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let new_game = |card_config: &CardConfig| {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        PostFlopGame::with_config(card_config.clone(), action_tree).unwrap()
+    };
+
+    let mut game = new_game(&card_config);
+    let manifest = game.manifest().unwrap();
+    assert_eq!(
+        manifest.get("crate_version"),
+        Some(env!("CARGO_PKG_VERSION"))
     );
+    assert_eq!(manifest.get("card.river"), Some("3s"));
+    assert_eq!(manifest.get("num_iterations"), None);
+    assert!(
+        manifest
+            .canonical_string()
+            .contains("tree.starting_pot=100\n")
+    );
+    assert!(game.solve_manifest().is_none());
+
+    // the positions do not affect the solving
+    let with_positions = CardConfig {
+        positions: [Some(Position::BigBlind), Some(Position::Button)],
+        ..card_config.clone()
+    };
+    let other = new_game(&with_positions).manifest().unwrap();
+    assert_eq!(other.digest(), manifest.digest());
+    assert!(manifest.diff(&other).is_empty());
+
+    // a locked strategy does
+    let mut locked = new_game(&card_config);
+    locked.allocate_memory(false);
+    let num_hands = locked.num_private_hands(0);
+    let mut strategy = vec![0.0; 2 * num_hands];
+    strategy[..num_hands].fill(1.0);
+    locked.lock_current_strategy(&strategy);
+    let other = locked.manifest().unwrap();
+    let differences = other.diff(&manifest);
+    assert_eq!(differences.len(), 1);
+    assert_eq!(differences[0].key, "locking_strategy");
+    assert_eq!(differences[0].right.as_deref(), Some("none"));
+
+    // the manifest of the solving is captured
+    game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+    let solved = game.solve_manifest().unwrap().clone();
+    assert_eq!(solved.get("num_iterations"), Some("10"));
+    let differences = manifest.diff(&solved);
+    assert_eq!(differences.len(), 1);
+    assert_eq!(differences[0].left, None);
+
+    // identical inputs give identical digests
+    let mut same = new_game(&card_config);
+    same.allocate_memory(false);
+    solve(&mut same, 10, 0.0, false);
+    assert_eq!(same.solve_manifest().unwrap().digest(), solved.digest());
+
+    // the manifest is cleared by reallocating
     game.allocate_memory(false);
+    assert!(game.solve_manifest().is_none());
+}
 
-    solve(&mut game, 1000, 180.0 * 0.001, true);
+#[test]
+fn manifest_golden_digest() {
+    let card_config = CardConfig {
+        range: ["AA,KK".parse().unwrap(), "QQ,AKs".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 300,
+        turn_bet_sizes: [
+            ("50%, a", "2.5x").try_into().unwrap(),
+            ("e", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("75%", "").try_into().unwrap(),
+            ("20c", "3x").try_into().unwrap(),
+        ],
+        river_donk_sizes: Some("33%".try_into().unwrap()),
+        add_allin_threshold: 1.5,
+        icm_config: Some(ICMConfig {
+            player_stacks: [400, 400],
+            other_players_stacks: vec![200],
+            payout_structure: vec![50, 30, 20],
+        }),
+        rake_config: Some(RakeConfig {
+            rates: [0.05, 0.05, 0.05],
+            caps: [10.0, 20.0, 30.0],
+        }),
+        sizing_rules: vec![SizingRule {
+            street: Some(BoardState::River),
+            pot_to_stack_ratio: 0.5,
+            bet_sizes: ("a", "").try_into().unwrap(),
+        }],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.set_isomorphism_mode(IsomorphismMode::RiverOnly)
+        .unwrap();
+    let schedule = UpdateSchedule {
+        interval: 2,
+        freeze_after: Some(100),
+    };
+    game.set_update_schedule(1, schedule).unwrap();
+    let params = CompressionParams {
+        reserved_bits: 2,
+        rescale_interval: 4,
+    };
+    game.set_compression_params(BoardState::River, params)
+        .unwrap();
+    game.set_icm_seed(7);
+    game.allocate_memory(true);
+
+    // any change of these values (including the digest, which also covers the crate version)
+    // breaks the comparison with the saved manifests
+    let version = env!("CARGO_PKG_VERSION");
+    let expected = format!(
+        "crate_version={version}
+card.range_oop=KK+
+card.range_ip=QQ,AKs
+card.flop=6h9dTd
+card.turn=2c
+card.river=-
+card.dead_cards=
+card.short_deck=false
+tree.initial_state=turn
+tree.starting_pot=100
+tree.effective_stack=400
+tree.rake_rate=0
+tree.rake_cap=0
+tree.flop_bet_sizes=/|/
+tree.turn_bet_sizes=50%,a/2.5x|e/
+tree.river_bet_sizes=75%/|20c/3x
+tree.turn_donk_sizes=none
+tree.river_donk_sizes=33%
+tree.add_allin_threshold=1.5
+tree.force_allin_threshold=0
+tree.merging_threshold=0
+tree.icm_config=stacks=400,400;others=200;payouts=50,30,20
+tree.rake_config=rates=0.05,0.05,0.05;caps=10,20,30
+tree.sizing_rules=river:0.5:a/
+tree.added_lines=
+tree.removed_lines=
+isomorphism_mode=river_only
+compression=true
+quantized_strategy=false
+compression_params=0:1,0:1,2:4
+update_schedule=1:none,2:100
+icm_seed=7
+hand_evaluator=default
+bunching=none
+locking_strategy=none
+"
+    );
+    let manifest = game.manifest().unwrap();
+    assert_eq!(manifest.canonical_string(), expected);
+    assert_eq!(manifest.digest(), 0x51fa_1773_aaff_61e1);
+}
+
+#[test]
+fn crossplay_ev_matrix() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
 
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert!(matches!(
+        game.crossplay_ev_matrix(),
+        Err(SolverError::InvalidState(_))
+    ));
+    solve(&mut game, 200, 0.0, false);
+
+    // every combo that does not conflict with the board
+    let matrix = game.crossplay_ev_matrix().unwrap();
+    assert_eq!(matrix.player, 0);
+    assert_eq!(matrix.hands.len(), 48 * 47 / 2);
+    assert!(game.history().is_empty());
+    let outside = matrix.reach.iter().filter(|&&w| w == 0.0).count();
+    assert_eq!(outside, matrix.hands.len() - game.private_cards(0).len());
+
+    // the best response is at least as good as the solved strategy
     game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    let ev = game.expected_values(0);
+    for (index, &hand) in game.private_cards(0).iter().enumerate() {
+        let i = matrix.hand_index(hand).unwrap();
+        assert_eq!(matrix.reach[i], game.weights(0)[index]);
+        assert!(matrix.ev[i] >= ev[index] - 0.5);
+    }
+
+    // no decision of IP follows a river bet: the values are exact for the reached combos
+    game.apply_history(&[0, 0, card_from_str("3s").unwrap() as usize, 1]);
+    let matrix = game.crossplay_ev_matrix().unwrap();
+    assert_eq!(matrix.player, 1);
+    assert_eq!(matrix.actions, [Action::Fold, Action::Call]);
+    assert_eq!(matrix.hands.len(), 47 * 46 / 2);
+    game.cache_normalized_weights();
+    let detail = game.expected_values_detail(1);
+    let num_hands = game.private_cards(1).len();
+    for (index, &hand) in game.private_cards(1).iter().enumerate() {
+        let Some(i) = matrix.hand_index(hand) else {
+            continue;
+        };
+        if game.normalized_weights(1)[index] == 0.0 {
+            continue;
+        }
+        for action in 0..2 {
+            let expected = detail[action * num_hands + index];
+            let actual = matrix.action_evs[action * matrix.hands.len() + i];
+            assert!((expected - actual).abs() < 1e-3);
+        }
+    }
+
+    // a combo outside the range: a set of sixes calls
+    let sixes = (card_from_str("6c").unwrap(), card_from_str("6s").unwrap());
+    let i = matrix.hand_index(sixes).unwrap();
+    assert_eq!(matrix.reach[i], 0.0);
+    assert_eq!(matrix.action_evs[i], 0.0);
+    assert_eq!(matrix.ev[i], matrix.action_evs[matrix.hands.len() + i]);
+    assert!(matrix.ev[i] > 0.0);
+
+    game.apply_history(&[0, 0]);
+    game.crossplay_ev_matrix().unwrap_err();
+}
 
-    // verified by PioSOLVER Free (but not theoretically guaranteed to be the same)
-    assert!((root_ev_oop - 95.57).abs() < 0.2);
-    assert!((root_ev_ip - 66.98).abs() < 0.2);
+#[test]
+fn force_line() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,87s,65s,A5s".parse().unwrap(),
+            "QQ-99,AK,KQs,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    let check_call = |_: &[Action], actions: &[Action]| {
+        if actions.contains(&Action::Call) {
+            Action::Call
+        } else {
+            Action::Check
+        }
+    };
+    assert!(matches!(
+        game.force_line(0, check_call),
+        Err(SolverError::InvalidState(_))
+    ));
+    let exploitability = solve(&mut game, 200, 0.0, false);
+
+    let baseline = compute_current_ev(&game);
+    let report = game.force_line(0, check_call).unwrap();
+    assert_eq!(report.player, 0);
+    assert_eq!(report.baseline_ev, baseline[0]);
+    assert!((report.ev_loss - (report.baseline_ev - report.ev)).abs() < 1e-4);
+    assert!(report.ev_loss > -exploitability);
+    assert!(game.has_strategy_overrides());
+    assert!(game.history().is_empty());
+
+    // two nodes on the turn (root and X-B), and two nodes on each of the 48 river cards after each
+    // of the three lines reaching the river (X-X, X-B-C, and B-C)
+    assert_eq!(report.num_nodes, 2 + 48 * 3 * 2);
+
+    // the line is followed everywhere
+    let num_hands = game.private_cards(0).len();
+    assert!(game.strategy()[..num_hands].iter().all(|&f| f == 1.0));
+    game.apply_history(&[0, 1]);
+    assert_eq!(game.available_actions()[1], Action::Call);
+    assert!(
+        game.strategy()[num_hands..2 * num_hands]
+            .iter()
+            .all(|&f| f == 1.0)
+    );
+    game.back_to_root();
+
+    // always folding to a bet loses more than calling
+    game.clear_strategy_overrides();
+    assert!(!game.has_strategy_overrides());
+    let fold = game
+        .force_line(0, |_, actions| {
+            if actions.contains(&Action::Fold) {
+                Action::Fold
+            } else {
+                Action::Check
+            }
+        })
+        .unwrap();
+    assert!(fold.ev_loss > report.ev_loss);
+    game.clear_strategy_overrides();
+
+    // unavailable action
+    let error = game.force_line(1, |_, _| Action::Bet(1000)).unwrap_err();
+    match error {
+        SolverError::InvalidLine { line, .. } => {
+            assert_eq!(line, [Action::Check, Action::Bet(1000)]);
+        }
+        _ => panic!("unexpected error: {error:?}"),
+    }
+    assert!(!game.has_strategy_overrides());
+    assert!(matches!(
+        game.force_line(2, check_call),
+        Err(SolverError::InvalidArgument(_))
+    ));
+}
+
+#[test]
+fn strategy_snapshot() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,87s".parse().unwrap(), "JJ,AK,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let new_game = || {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut game = new_game();
+    assert!(matches!(
+        game.strategy_snapshot(),
+        Err(SolverError::InvalidState(_))
+    ));
+    solve(&mut game, 200, 0.0, false);
+    let snapshot = game.strategy_snapshot().unwrap();
+    assert_eq!(snapshot.max_difference(&snapshot).unwrap(), 0.0);
+    assert!(snapshot.memory_usage() < game.memory_usage().0);
+
+    // the snapshot reproduces the strategy, including the swapped river cards
+    let histories = [
+        vec![],
+        vec![0, 0, card_from_str("3c").unwrap() as usize],
+        vec![0, 0, card_from_str("3s").unwrap() as usize],
+    ];
+    for history in &histories {
+        game.apply_history(history);
+        let expected = game.strategy();
+        let actual = game.snapshot_strategy(&snapshot).unwrap();
+        assert_eq!(actual.len(), expected.len());
+        for (a, b) in actual.iter().zip(&expected) {
+            assert!((a - b).abs() < 1e-4);
+        }
+    }
+    game.back_to_root();
+
+    // a less converged solve differs, and blending interpolates
+    let mut other = new_game();
+    solve(&mut other, 5, 0.0, false);
+    let other_snapshot = other.strategy_snapshot().unwrap();
+    let difference = snapshot.max_difference(&other_snapshot).unwrap();
+    assert!(difference > 0.01);
+    let blended = snapshot.blend(&other_snapshot, 0.0).unwrap();
+    assert!(blended.max_difference(&snapshot).unwrap() < 1e-4);
+    let blended = snapshot.blend(&other_snapshot, 0.5).unwrap();
+    for reference in [&snapshot, &other_snapshot] {
+        let half = blended.max_difference(reference).unwrap();
+        assert!((half - difference / 2.0).abs() < 1e-3);
+    }
+    assert!(matches!(
+        snapshot.blend(&other_snapshot, 1.5),
+        Err(SolverError::InvalidArgument(_))
+    ));
+
+    // playing back the converged strategy in the other game
+    other.apply_history(&[1]);
+    let report = other.apply_strategy_snapshot(&snapshot).unwrap();
+    assert_eq!(other.history(), [1]);
+    let ev = compute_current_ev(&game);
+    for (a, b) in report.ev.iter().zip(&ev) {
+        assert!((a - b).abs() < 1e-2);
+    }
+    other.back_to_root();
+    for (a, b) in other.strategy().iter().zip(&game.strategy()) {
+        assert!((a - b).abs() < 1e-4);
+    }
+    other.clear_strategy_overrides();
+    assert_eq!(other.strategy_snapshot().unwrap(), other_snapshot);
+
+    // snapshots of a different game are rejected
+    let mut card_config = card_config.clone();
+    card_config.range[1] = "JJ,AK".parse().unwrap();
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut different = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    different.allocate_memory(false);
+    solve(&mut different, 5, 0.0, false);
+    assert!(matches!(
+        different.apply_strategy_snapshot(&snapshot),
+        Err(SolverError::InvalidArgument(_))
+    ));
+    assert!(matches!(
+        different.snapshot_strategy(&snapshot),
+        Err(SolverError::InvalidArgument(_))
+    ));
+    let different_snapshot = different.strategy_snapshot().unwrap();
+    assert!(matches!(
+        snapshot.max_difference(&different_snapshot),
+        Err(SolverError::InvalidArgument(_))
+    ));
+}
+
+#[test]
+fn showdown_against_brute_force() {
+    let card_config = CardConfig {
+        range: [
+            "22+,A2s+,K9s+,QTs+,JTs,ATo+,KJo+".parse().unwrap(),
+            "55+,A8s+,KTs+,QJs,AJo+,KQo".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("Ts").unwrap(),
+        ..Default::default()
+    };
+
+    let mut rng = fastrand::Rng::with_seed(7);
+
+    for rake_rate in [0.0, 0.05] {
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 60,
+            effective_stack: 970,
+            rake_rate,
+            rake_cap: 100.0,
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+
+        let node = game
+            .node_arena
+            .iter()
+            .map(|node| node.lock())
+            .find(|node| node.is_terminal() && node.player & PLAYER_FOLD_FLAG != PLAYER_FOLD_FLAG)
+            .unwrap();
+
+        let pair_index = card_pair_to_index(node.turn, node.river);
+        let pot = (game.tree_config.starting_pot + 2 * node.amount) as f64;
+        let rake = game.tree_config.rake(pot, BoardState::River);
+        let amount_win = (0.5 * pot - rake) / game.num_combinations;
+        let amount_lose = -0.5 * pot / game.num_combinations;
+        let amount_tie = -0.5 * rake / game.num_combinations;
+
+        for player in 0..2 {
+            let strength = |player: usize| {
+                let mut ret = vec![0; game.num_private_hands(player)];
+                for item in &game.hand_strength[pair_index][player] {
+                    if item.index != u16::MAX && item.strength != 0 {
+                        ret[item.index as usize] = item.strength;
+                    }
+                }
+                ret
+            };
+
+            let player_strength = strength(player);
+            let opponent_strength = strength(player ^ 1);
+            let cfreach = (0..game.num_private_hands(player ^ 1))
+                .map(|_| if rng.bool() { rng.f32() } else { 0.0 })
+                .collect::<Vec<_>>();
+
+            let mut result = vec![std::mem::MaybeUninit::uninit(); game.num_private_hands(player)];
+            game.evaluate_internal(&mut result, &node, player, &cfreach);
+
+            for (i, &(c1, c2)) in game.private_cards[player].iter().enumerate() {
+                let mut expected = 0.0;
+                for (j, &(o1, o2)) in game.private_cards[player ^ 1].iter().enumerate() {
+                    if [c1, c2].contains(&o1) || [c1, c2].contains(&o2) {
+                        continue;
+                    }
+                    let amount = match player_strength[i].cmp(&opponent_strength[j]) {
+                        std::cmp::Ordering::Greater => amount_win,
+                        std::cmp::Ordering::Less => amount_lose,
+                        std::cmp::Ordering::Equal => amount_tie,
+                    };
+                    expected += amount * cfreach[j] as f64;
+                }
+                let actual = unsafe { result[i].assume_init() } as f64;
+                assert!((actual - expected).abs() < 1e-8, "{actual} != {expected}");
+            }
+        }
+    }
 }
@@ -0,0 +1,245 @@
+use super::walk::*;
+use super::*;
+use crate::interface::*;
+use crate::random::*;
+use crate::utility::*;
+use fastrand::Rng;
+
+/// Maximum number of rollouts tried by [`PostFlopGame::sample_quiz_spot`].
+const MAX_QUIZ_TRIALS: usize = 1000;
+
+/// A decision point presented as a quiz, returned by [`PostFlopGame::sample_quiz_spot`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuizSpot {
+    /// History from the root node (see [`PostFlopGame::apply_history`]).
+    pub history: Vec<usize>,
+
+    /// Line (list of actions and dealt cards) from the root node.
+    pub line: Vec<Action>,
+
+    /// Board cards.
+    pub board: Vec<Card>,
+
+    /// Player to act (0 = OOP, 1 = IP).
+    pub player: usize,
+
+    /// Hole cards of the player.
+    pub hand: (Card, Card),
+
+    /// Pot size.
+    pub pot: i32,
+
+    /// Available actions.
+    pub actions: Vec<Action>,
+
+    /// Solver strategy of the hand (frequency of each action).
+    pub strategy: Vec<f32>,
+
+    /// Expected value of each action for the hand.
+    pub action_ev: Vec<f32>,
+}
+
+/// Grade of an answer to a [`QuizSpot`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuizGrade {
+    /// Expected value of the solver strategy minus that of the answer.
+    pub ev_loss: f32,
+
+    /// Total variation distance between the answer and the solver strategy (0 = identical, 1 =
+    /// disjoint).
+    pub frequency_distance: f32,
+
+    /// Index of the action with the highest expected value.
+    pub best_action: usize,
+}
+
+impl QuizSpot {
+    /// Grades an answer given as the frequency of each action.
+    ///
+    /// The frequencies are normalized to sum up to 1. Returns
+    /// [`SolverError::InvalidArgument`] if the length does not match the number of actions or the
+    /// frequencies are invalid.
+    pub fn grade(&self, answer: &[f32]) -> Result<QuizGrade, SolverError> {
+        if answer.len() != self.actions.len() {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid answer length: expected {}, but got {}",
+                self.actions.len(),
+                answer.len()
+            )));
+        }
+        if answer.iter().any(|&f| !f.is_finite() || f < 0.0) {
+            return Err(SolverError::InvalidArgument(
+                "Answer contains invalid frequencies".to_owned(),
+            ));
+        }
+        let sum = answer.iter().sum::<f32>();
+        if sum <= 0.0 {
+            return Err(SolverError::InvalidArgument(
+                "Answer frequencies sum up to zero".to_owned(),
+            ));
+        }
+
+        let ev = |strategy: &mut dyn Iterator<Item = f32>| {
+            strategy
+                .zip(&self.action_ev)
+                .fold(0.0, |acc, (f, &ev)| acc + f as f64 * ev as f64)
+        };
+        let solver_ev = ev(&mut self.strategy.iter().copied());
+        let answer_ev = ev(&mut answer.iter().map(|&f| f / sum));
+
+        let distance = answer
+            .iter()
+            .zip(&self.strategy)
+            .fold(0.0, |acc, (&a, &s)| acc + (a / sum - s).abs())
+            * 0.5;
+
+        let best_action = (0..self.actions.len())
+            .max_by(|&i, &j| self.action_ev[i].total_cmp(&self.action_ev[j]))
+            .unwrap();
+
+        Ok(QuizGrade {
+            ev_loss: (solver_ev - answer_ev) as f32,
+            frequency_distance: distance,
+            best_action,
+        })
+    }
+
+    /// Returns the spot with the solver strategy adjusted by `temperature` (see
+    /// [`apply_temperature`]).
+    ///
+    /// Grading against a softened strategy (`temperature > 1.0`) penalizes mixing less, which
+    /// suits an easier difficulty setting, while a sharpened strategy (`temperature < 1.0`)
+    /// expects the most frequent action. The expected values are not changed.
+    ///
+    /// Panics if `temperature` is negative or NaN.
+    ///
+    /// [`apply_temperature`]: crate::apply_temperature
+    pub fn with_temperature(&self, temperature: f32) -> Self {
+        let mut ret = self.clone();
+        apply_temperature(&mut ret.strategy, 1, temperature);
+        ret
+    }
+
+    /// Grades an answer given as a single action.
+    ///
+    /// Returns [`SolverError::InvalidArgument`] if the action index is out of range.
+    pub fn grade_action(&self, action: usize) -> Result<QuizGrade, SolverError> {
+        if action >= self.actions.len() {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid action index: {action}"
+            )));
+        }
+        let mut answer = vec![0.0; self.actions.len()];
+        answer[action] = 1.0;
+        self.grade(&answer)
+    }
+}
+
+impl PostFlopGame {
+    /// Samples a decision point of the solved game as a quiz.
+    ///
+    /// A hand is played out from the root node with [`rollout`], so decision points are sampled
+    /// in proportion to how often they are reached under the solver strategy. One decision point
+    /// of the rollout (of the given `street` if `Some`) is then chosen uniformly at random, so that
+    /// points on longer lines are slightly less likely than their reach probabilities. The spot
+    /// is presented from the perspective of the acting player with the hole cards dealt in the
+    /// rollout.
+    ///
+    /// The random draws are made with the random number generator seeded with `seed` (see
+    /// [`sample_quiz_spot_with_rng`]). Returns [`SolverError::InvalidState`] if the game is
+    /// not solved, and [`SolverError::InvalidArgument`] if no decision point of the given street is
+    /// found. The current node is restored after the call.
+    ///
+    /// [`rollout`]: #method.rollout
+    /// [`sample_quiz_spot_with_rng`]: #method.sample_quiz_spot_with_rng
+    #[inline]
+    pub fn sample_quiz_spot(
+        &mut self,
+        seed: u64,
+        street: Option<BoardState>,
+    ) -> Result<QuizSpot, SolverError> {
+        self.sample_quiz_spot_with_rng(&mut Rng::with_seed(seed), street)
+    }
+
+    /// Same as [`sample_quiz_spot`], but makes all the random draws from `rng`.
+    ///
+    /// [`sample_quiz_spot`]: #method.sample_quiz_spot
+    pub fn sample_quiz_spot_with_rng<R: RandomSource + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        street: Option<BoardState>,
+    ) -> Result<QuizSpot, SolverError> {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+
+        let original_history = self.history().to_vec();
+        self.back_to_root();
+
+        let result = self.sample_quiz_spot_internal(rng, street);
+
+        self.apply_history(&original_history);
+        result
+    }
+
+    fn sample_quiz_spot_internal<R: RandomSource + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        street: Option<BoardState>,
+    ) -> Result<QuizSpot, SolverError> {
+        for _ in 0..MAX_QUIZ_TRIALS {
+            let rollout = self.rollout_with_rng(rng, None)?;
+
+            // collects the decision points of the rollout
+            let mut candidates = Vec::new();
+            for len in 0..rollout.history.len() {
+                self.apply_history(&rollout.history[..len]);
+                if self.is_chance_node() {
+                    continue;
+                }
+                let current_street = street_of_board(&self.current_board());
+                if street.is_none_or(|s| s == current_street) {
+                    candidates.push(len);
+                }
+            }
+            self.back_to_root();
+
+            if candidates.is_empty() {
+                continue;
+            }
+
+            let len = candidates[rng.next_below(candidates.len())];
+            let history = &rollout.history[..len];
+            let line = self.line_from_history(history)?;
+            self.cache_normalized_weights();
+
+            let player = self.current_player();
+            let hand_index = rollout.hands[player];
+            let num_hands = self.num_private_hands(player);
+            let actions = self.available_actions();
+            let strategy = self.strategy();
+            let ev_detail = self.expected_values_detail(player);
+            let column = |values: &[f32]| {
+                (0..actions.len())
+                    .map(|action| values[action * num_hands + hand_index])
+                    .collect::<Vec<_>>()
+            };
+
+            return Ok(QuizSpot {
+                history: history.to_vec(),
+                line,
+                board: self.current_board(),
+                player,
+                hand: self.private_cards[player][hand_index],
+                pot: self.current_pot(),
+                strategy: column(&strategy),
+                action_ev: column(&ev_detail),
+                actions,
+            });
+        }
+
+        Err(SolverError::InvalidArgument(
+            "No decision point found".to_owned(),
+        ))
+    }
+}
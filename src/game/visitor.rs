@@ -0,0 +1,192 @@
+use super::walk::*;
+use super::*;
+use crate::interface::*;
+use crate::utility::*;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Number of node contexts materialized before they are passed to the visitor in parallel.
+const VISIT_BATCH_SIZE: usize = 256;
+
+/// Context of a player node passed to [`TreeVisitor::visit`].
+#[derive(Debug, Clone)]
+pub struct NodeContext {
+    /// History from the root node (see [`PostFlopGame::apply_history`]).
+    pub history: Vec<usize>,
+
+    /// Line (list of actions and dealt cards) from the root node.
+    pub line: Vec<Action>,
+
+    /// Board cards at the node.
+    pub board: Vec<Card>,
+
+    /// Street of the node.
+    pub street: BoardState,
+
+    /// Player to act (0 = OOP, 1 = IP).
+    pub player: usize,
+
+    /// Pot size at the node.
+    pub pot: i32,
+
+    /// Available actions.
+    pub actions: Vec<Action>,
+
+    /// Probability that the node is reached from the root node.
+    pub reach: f32,
+
+    /// Normalized weights of the private hands of each player (OOP, IP) (see
+    /// [`PostFlopGame::normalized_weights`]).
+    pub weights: [Vec<f32>; 2],
+
+    /// Strategy of the acting player (see [`PostFlopGame::strategy`]).
+    pub strategy: Vec<f32>,
+
+    /// Expected values of each private hand of each player (OOP, IP). `None` if the game is not
+    /// solved.
+    pub expected_values: Option<[Vec<f32>; 2]>,
+
+    /// Expected values of each action of the acting player (see
+    /// [`PostFlopGame::expected_values_detail`]). `None` if the game is not solved.
+    pub action_ev: Option<Vec<f32>>,
+}
+
+impl NodeContext {
+    /// Returns the number of private hands of the acting player.
+    #[inline]
+    pub fn num_hands(&self) -> usize {
+        self.weights[self.player].len()
+    }
+
+    /// Returns the strategy of the given action for each private hand of the acting player.
+    #[inline]
+    pub fn action_strategy(&self, action: usize) -> &[f32] {
+        let num_hands = self.num_hands();
+        &self.strategy[action * num_hands..(action + 1) * num_hands]
+    }
+}
+
+/// A report computed by visiting the player nodes of a game tree.
+///
+/// The visitor receives an immutable [`NodeContext`] for each player node and may be called from
+/// multiple threads, so a report is usually built by returning a value per node and combining the
+/// results returned by [`PostFlopGame::visit_tree`].
+pub trait TreeVisitor: Sync {
+    /// Value computed for a node.
+    type Output: Send;
+
+    /// Visits a player node. Returning `None` skips the node in the results.
+    fn visit(&self, ctx: &NodeContext) -> Option<Self::Output>;
+}
+
+impl<T: Send, F: Fn(&NodeContext) -> Option<T> + Sync> TreeVisitor for F {
+    type Output = T;
+
+    #[inline]
+    fn visit(&self, ctx: &NodeContext) -> Option<T> {
+        self(ctx)
+    }
+}
+
+impl PostFlopGame {
+    /// Visits the player nodes in the subtree of the node reached by `history` and returns the
+    /// outputs of the visitor in depth-first order.
+    ///
+    /// Navigation and normalization of the weights are handled internally: every possible turn
+    /// and river card is visited, and the context of each node is materialized before calling the
+    /// visitor. If `street` is `Some`, only the nodes of the given street are visited. The tree is
+    /// walked sequentially, while the visitor is called in parallel for batches of nodes when the
+    /// `rayon` feature is enabled. The current node is restored after the call.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["AA,KK,QQ".parse().unwrap(), "JJ,AK".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("2c").unwrap(),
+    ///     river: card_from_str("3s").unwrap(),
+    ///     dead_cards: 0,
+    ///     short_deck: false,
+    ///     positions: [None; 2],
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::River,
+    ///     starting_pot: 100,
+    ///     effective_stack: 100,
+    ///     river_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// game.allocate_memory(false);
+    /// solve(&mut game, 100, 0.0, false);
+    ///
+    /// // reach probability of each node where OOP acts
+    /// let visitor = |ctx: &NodeContext| (ctx.player == 0).then(|| (ctx.history.clone(), ctx.reach));
+    /// let reaches = game.visit_tree(&visitor, &[], None).unwrap();
+    /// assert_eq!(reaches[0], (vec![], 1.0));
+    /// ```
+    pub fn visit_tree<V: TreeVisitor>(
+        &mut self,
+        visitor: &V,
+        history: &[usize],
+        street: Option<BoardState>,
+    ) -> Result<Vec<V::Output>, SolverError> {
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+
+        let root_reach = self.root_reach();
+        let mut batch = Vec::with_capacity(VISIT_BATCH_SIZE);
+        let mut outputs = Vec::new();
+
+        let flush = |batch: &mut Vec<NodeContext>, outputs: &mut Vec<V::Output>| {
+            let results = into_par_iter(0..batch.len())
+                .map(|i| visitor.visit(&batch[i]))
+                .collect::<Vec<_>>();
+            outputs.extend(results.into_iter().flatten());
+            batch.clear();
+        };
+
+        self.walk_player_nodes(history, street, &mut |game, history, line| {
+            batch.push(game.node_context(history, line, root_reach));
+            if batch.len() == VISIT_BATCH_SIZE {
+                flush(&mut batch, &mut outputs);
+            }
+            Ok(())
+        })?;
+
+        flush(&mut batch, &mut outputs);
+        Ok(outputs)
+    }
+
+    fn node_context(&mut self, history: &[usize], line: &[Action], root_reach: f64) -> NodeContext {
+        self.cache_normalized_weights();
+
+        let player = self.current_player();
+        let board = self.current_board();
+        let is_solved = self.is_solved();
+
+        NodeContext {
+            history: history.to_vec(),
+            line: line.to_vec(),
+            street: street_of_board(&board),
+            board,
+            player,
+            pot: self.current_pot(),
+            actions: self.available_actions(),
+            reach: self.current_reach(root_reach) as f32,
+            weights: [0, 1].map(|p| self.normalized_weights(p).to_vec()),
+            strategy: self.strategy(),
+            expected_values: is_solved.then(|| [0, 1].map(|p| self.expected_values(p))),
+            action_ev: is_solved.then(|| self.expected_values_detail(player)),
+        }
+    }
+}
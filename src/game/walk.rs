@@ -0,0 +1,164 @@
+use super::*;
+
+/// Returns the street of the given board.
+#[inline]
+pub(super) fn street_of_board(board: &[Card]) -> BoardState {
+    match board.len() {
+        3 => BoardState::Flop,
+        4 => BoardState::Turn,
+        _ => BoardState::River,
+    }
+}
+
+impl PostFlopGame {
+    /// Returns the line (list of actions and dealt cards) corresponding to the given history.
+    ///
+    /// The current node is moved to the node reached by the `history`. Returns
+    /// [`SolverError::InvalidLine`] with the valid prefix of the line if the history is invalid.
+    pub(super) fn line_from_history(
+        &mut self,
+        history: &[usize],
+    ) -> Result<Vec<Action>, SolverError> {
+        let mut line = Vec::with_capacity(history.len());
+
+        let invalid =
+            |line: Vec<Action>, message: String| SolverError::InvalidLine { line, message };
+
+        self.back_to_root();
+        for &action in history {
+            if self.is_terminal_node() {
+                let message = "Invalid history: terminal node reached".to_owned();
+                return Err(invalid(line, message));
+            }
+            if self.is_chance_node() {
+                if action >= 52 || self.possible_cards() & (1 << action) == 0 {
+                    return Err(invalid(line, format!("Invalid card: {action}")));
+                }
+                line.push(Action::Chance(action as Card));
+            } else {
+                let Some(&a) = self.available_actions().get(action) else {
+                    return Err(invalid(line, format!("Invalid action index: {action}")));
+                };
+                line.push(a);
+            }
+            self.play(action);
+        }
+
+        Ok(line)
+    }
+
+    /// Returns the sum of the normalized weights of OOP at the root node.
+    ///
+    /// The current node is preserved.
+    pub(super) fn root_reach(&mut self) -> f64 {
+        let original_history = self.history().to_vec();
+        self.back_to_root();
+        self.cache_normalized_weights();
+        let reach = self
+            .normalized_weights(0)
+            .iter()
+            .map(|&w| w as f64)
+            .sum::<f64>();
+        self.apply_history(&original_history);
+        reach
+    }
+
+    /// Returns the probability that the current player node is reached, given the value of
+    /// [`root_reach`].
+    ///
+    /// The normalized weights must be cached.
+    ///
+    /// [`root_reach`]: #method.root_reach
+    pub(super) fn current_reach(&self, root_reach: f64) -> f64 {
+        let reach = self
+            .normalized_weights(self.current_player())
+            .iter()
+            .map(|&w| w as f64)
+            .sum::<f64>();
+        reach / (root_reach * self.current_chance_factor() as f64)
+    }
+
+    /// Walks the player nodes in the subtree of the node reached by `history` in depth-first order.
+    ///
+    /// Every possible turn and river card is visited (not only the representative of isomorphic
+    /// cards). If `street` is `Some`, only the nodes of the given street are passed to `f`. The
+    /// callback receives the game moved to the visited node, its history, and its line. The current
+    /// node is restored after the walk.
+    pub(super) fn walk_player_nodes<F>(
+        &mut self,
+        history: &[usize],
+        street: Option<BoardState>,
+        f: &mut F,
+    ) -> Result<(), SolverError>
+    where
+        F: FnMut(&mut Self, &[usize], &[Action]) -> Result<(), SolverError>,
+    {
+        if self.state < State::MemoryAllocated {
+            return Err(SolverError::InvalidState(
+                "Memory is not allocated".to_owned(),
+            ));
+        }
+
+        let original_history = self.history().to_vec();
+
+        let result = self.line_from_history(history).and_then(|mut line| {
+            let mut history = history.to_vec();
+            self.walk_recursive(&mut history, &mut line, street, f)
+        });
+
+        self.apply_history(&original_history);
+        result
+    }
+
+    fn walk_recursive<F>(
+        &mut self,
+        history: &mut Vec<usize>,
+        line: &mut Vec<Action>,
+        street: Option<BoardState>,
+        f: &mut F,
+    ) -> Result<(), SolverError>
+    where
+        F: FnMut(&mut Self, &[usize], &[Action]) -> Result<(), SolverError>,
+    {
+        self.apply_history(history);
+
+        if self.is_terminal_node() {
+            return Ok(());
+        }
+
+        let current_street = street_of_board(&self.current_board());
+        if street.is_some_and(|s| current_street > s) {
+            return Ok(());
+        }
+
+        if self.is_chance_node() {
+            let possible_cards = self.possible_cards();
+            for card in 0..52 {
+                if possible_cards & (1 << card) != 0 {
+                    history.push(card);
+                    line.push(Action::Chance(card as Card));
+                    self.walk_recursive(history, line, street, f)?;
+                    line.pop();
+                    history.pop();
+                }
+            }
+            return Ok(());
+        }
+
+        let actions = self.available_actions();
+
+        if street.is_none_or(|s| current_street == s) {
+            f(self, history, line)?;
+        }
+
+        for (index, &action) in actions.iter().enumerate() {
+            history.push(index);
+            line.push(action);
+            self.walk_recursive(history, line, street, f)?;
+            line.pop();
+            history.pop();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+use super::*;
+use crate::interface::*;
+use crate::utility::*;
+
+/// Result of [`PostFlopGame::what_if`] and [`PostFlopGame::apply_best_response`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WhatIfReport {
+    /// Expected values of each player (OOP, IP) before applying the overrides.
+    pub baseline_ev: [f32; 2],
+
+    /// Expected values of each player (OOP, IP) after applying the overrides.
+    pub ev: [f32; 2],
+
+    /// Difference between `ev` and `baseline_ev`.
+    pub ev_shift: [f32; 2],
+}
+
+impl PostFlopGame {
+    /// Temporarily overrides the strategy of the current node of the solved game.
+    ///
+    /// The `strategy` argument has the same format as [`lock_current_strategy`]: a hand whose
+    /// frequencies are all non-positive keeps its current strategy. The expected values of the
+    /// whole tree are recomputed with the other strategies fixed (i.e., without re-solving), so
+    /// that [`expected_values`] and related methods reflect the override at any node. Overriding
+    /// the same node again merges the new strategy into the previous override.
+    ///
+    /// The overrides are kept until [`clear_strategy_overrides`] is called, and the game cannot be
+    /// saved while overrides are active. The current node is preserved.
+    ///
+    /// **Time complexity:** *O*(#(nodes) * #(private hands)), the same as [`finalize`].
+    ///
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    /// [`expected_values`]: #method.expected_values
+    /// [`clear_strategy_overrides`]: #method.clear_strategy_overrides
+    /// [`finalize`]: crate::finalize
+    pub fn override_current_strategy(&mut self, strategy: &[f32]) -> Result<(), SolverError> {
+        self.override_current_strategy_internal(strategy)?;
+        self.refresh_after_override();
+        Ok(())
+    }
+
+    /// Removes all strategy overrides and restores the original expected values.
+    ///
+    /// Does nothing if there are no overrides. The current node is preserved.
+    pub fn clear_strategy_overrides(&mut self) {
+        if self.strategy_overrides.is_empty() {
+            return;
+        }
+
+        for (index, original) in std::mem::take(&mut self.strategy_overrides) {
+            let mut node = self.node_arena[index].lock();
+            node.is_locked = original.is_some();
+            match original {
+                Some(locking) => self.locking_strategy.insert(index, locking),
+                None => self.locking_strategy.remove(&index),
+            };
+        }
+
+        self.refresh_after_override();
+    }
+
+    /// Returns whether any strategy overrides are active.
+    #[inline]
+    pub fn has_strategy_overrides(&self) -> bool {
+        !self.strategy_overrides.is_empty()
+    }
+
+    /// Applies the given strategy overrides and reports the resulting shift of the expected values.
+    ///
+    /// Each override is a pair of a history from the root node (see [`apply_history`]) and a
+    /// strategy (see [`override_current_strategy`]). The expected values are recomputed only once
+    /// after applying all the overrides. The overrides stay active after the call, so the effect on
+    /// downstream nodes can be inspected with the usual methods; call
+    /// [`clear_strategy_overrides`] to restore the baseline.
+    ///
+    /// The expected values follow the convention of [`compute_current_ev`], i.e., (starting pot) /
+    /// 2 is subtracted. If an error occurs, the overrides applied by this call are kept; call
+    /// [`clear_strategy_overrides`] to discard them.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["AA,KK,QQ".parse().unwrap(), "JJ,AK".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("2c").unwrap(),
+    ///     river: card_from_str("3s").unwrap(),
+    ///     dead_cards: 0,
+    ///     short_deck: false,
+    ///     positions: [None; 2],
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::River,
+    ///     starting_pot: 100,
+    ///     effective_stack: 100,
+    ///     river_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// game.allocate_memory(false);
+    /// solve(&mut game, 100, 0.0, false);
+    ///
+    /// // what if IP always calls the bet?
+    /// game.apply_history(&[1]);
+    /// let num_hands = game.private_cards(1).len();
+    /// let mut strategy = vec![0.0; 2 * num_hands];
+    /// strategy[num_hands..].fill(1.0);
+    ///
+    /// let report = game.what_if(&[(vec![1], strategy)]).unwrap();
+    /// assert!(report.ev_shift[0] >= 0.0);
+    ///
+    /// game.clear_strategy_overrides();
+    /// ```
+    ///
+    /// [`apply_history`]: #method.apply_history
+    /// [`override_current_strategy`]: #method.override_current_strategy
+    /// [`clear_strategy_overrides`]: #method.clear_strategy_overrides
+    /// [`compute_current_ev`]: crate::compute_current_ev
+    pub fn what_if(
+        &mut self,
+        overrides: &[(Vec<usize>, Vec<f32>)],
+    ) -> Result<WhatIfReport, SolverError> {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+
+        let history = self.history().to_vec();
+        let baseline_ev = compute_current_ev(self);
+
+        let mut result = Ok(());
+        for (override_history, strategy) in overrides {
+            self.apply_history(override_history);
+            result = self.override_current_strategy_internal(strategy);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.apply_history(&history);
+        self.refresh_after_override();
+        result?;
+
+        let ev = compute_current_ev(self);
+        Ok(WhatIfReport {
+            baseline_ev,
+            ev,
+            ev_shift: [ev[0] - baseline_ev[0], ev[1] - baseline_ev[1]],
+        })
+    }
+
+    fn override_current_strategy_internal(&mut self, strategy: &[f32]) -> Result<(), SolverError> {
+        if self.state != State::Solved {
+            return Err(SolverError::InvalidState("Game is not solved".to_owned()));
+        }
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(SolverError::InvalidState(
+                "Strategy can only be overridden at a player node".to_owned(),
+            ));
+        }
+
+        let player = self.current_player();
+        let num_actions = self.node().num_actions();
+        if strategy.len() != num_actions * self.num_private_hands(player) {
+            return Err(SolverError::InvalidArgument(
+                "Invalid strategy length".to_owned(),
+            ));
+        }
+
+        let locking = self.locking_from_strategy(strategy);
+        let index = self.node_index(&self.node());
+        self.insert_strategy_override(index, locking);
+
+        Ok(())
+    }
+
+    /// Overrides the strategy of the node at `index` with `locking` in the internal locking format,
+    /// remembering the original locking strategy to be restored by [`clear_strategy_overrides`].
+    ///
+    /// [`clear_strategy_overrides`]: #method.clear_strategy_overrides
+    pub(super) fn insert_strategy_override(&mut self, index: usize, mut locking: Vec<f32>) {
+        let original = self.locking_strategy.get(&index).cloned();
+
+        // hands not specified in the override keep their locked strategy (if any)
+        if let Some(original) = &original {
+            locking.iter_mut().zip(original).for_each(|(dst, &src)| {
+                if *dst < 0.0 {
+                    *dst = src;
+                }
+            });
+        }
+
+        if self.strategy_overrides.iter().all(|&(i, _)| i != index) {
+            self.strategy_overrides.push((index, original));
+        }
+
+        self.node_arena[index].lock().is_locked = true;
+        self.locking_strategy.insert(index, locking);
+    }
+
+    /// Recomputes the expected values and the cached values of the current node.
+    pub(super) fn refresh_after_override(&mut self) {
+        self.unshare_storage();
+        save_cfvalues(self);
+        let history = self.history().to_vec();
+        self.apply_history(&history);
+    }
+}
@@ -10,6 +10,9 @@ pub(crate) struct Hand {
 fn keep_n_msb(mut x: i32, n: i32) -> i32 {
     let mut ret = 0;
     for _ in 0..n {
+        if x == 0 {
+            break;
+        }
         let bit = 1 << (x.leading_zeros() ^ 31);
         x ^= bit;
         ret |= bit;
@@ -18,13 +21,26 @@ fn keep_n_msb(mut x: i32, n: i32) -> i32 {
 }
 
 #[inline]
-fn find_straight(rankset: i32) -> i32 {
+pub(crate) fn find_straight(rankset: i32) -> i32 {
+    find_straight_with_wheel::<false>(rankset)
+}
+
+/// Finds the highest straight. In short-deck mode, the wheel is A-6-7-8-9 instead of A-2-3-4-5;
+/// it is represented in the same way as the 9-high straight, which cannot exist without the 5.
+#[inline]
+fn find_straight_with_wheel<const SHORT_DECK: bool>(rankset: i32) -> i32 {
     const WHEEL: i32 = 0b1_0000_0000_1111;
+    const SHORT_DECK_WHEEL: i32 = 0b1_0000_1111_0000;
+    let (wheel, wheel_rank) = if SHORT_DECK {
+        (SHORT_DECK_WHEEL, 7)
+    } else {
+        (WHEEL, 3)
+    };
     let is_straight = rankset & (rankset << 1) & (rankset << 2) & (rankset << 3) & (rankset << 4);
     if is_straight != 0 {
         keep_n_msb(is_straight, 1)
-    } else if (rankset & WHEEL) == WHEEL {
-        1 << 3
+    } else if (rankset & wheel) == wheel {
+        1 << wheel_rank
     } else {
         0
     }
@@ -45,22 +61,46 @@ impl Hand {
     }
 
     #[inline]
-    pub fn contains(&self, card: usize) -> bool {
-        self.cards[0..self.num_cards].contains(&card)
+    pub fn evaluate(&self) -> u16 {
+        HAND_TABLE.binary_search(&self.evaluate_internal()).unwrap() as u16
     }
 
+    /// Evaluates the hand with the short-deck (6+) rankings, where a flush beats a full house and
+    /// A-6-7-8-9 is the lowest straight. The hand must not contain cards of ranks 2-5.
+    ///
+    /// The returned value is only comparable with other values returned by this method.
     #[inline]
-    pub fn evaluate(&self) -> u16 {
-        HAND_TABLE.binary_search(&self.evaluate_internal()).unwrap() as u16
+    pub fn evaluate_short_deck(&self) -> u16 {
+        let raw_value = self.evaluate_raw::<true>();
+        let index = HAND_TABLE.binary_search(&raw_value).unwrap();
+
+        // the flushes and the full houses are adjacent in `HAND_TABLE`: swap the two blocks
+        let flush_begin = HAND_TABLE.partition_point(|&v| v < 5 << 26);
+        let full_house_begin = HAND_TABLE.partition_point(|&v| v < 6 << 26);
+        let full_house_end = HAND_TABLE.partition_point(|&v| v < 7 << 26);
+        let index = match raw_value >> 26 {
+            5 => index + (full_house_end - full_house_begin),
+            6 => index - (full_house_begin - flush_begin),
+            _ => index,
+        };
+
+        index as u16
+    }
+
+    /// Returns the raw strength of the hand, which is also valid for hands of less than 7 cards.
+    /// The hand category (0 = high card, ..., 8 = straight flush) is stored in the bits 26-29.
+    #[inline]
+    pub(crate) fn evaluate_internal(&self) -> i32 {
+        self.evaluate_raw::<false>()
     }
 
-    fn evaluate_internal(&self) -> i32 {
+    fn evaluate_raw<const SHORT_DECK: bool>(&self) -> i32 {
         let mut rankset = 0i32;
         let mut rankset_suit = [0i32; 4];
         let mut rankset_of_count = [0i32; 5];
         let mut rank_count = [0i32; 13];
 
-        for &card in &self.cards {
+        for &card in &self.cards[..self.num_cards] {
             let rank = card / 4;
             let suit = card % 4;
             rankset |= 1 << rank;
@@ -79,10 +119,11 @@ impl Hand {
             }
         }
 
-        let is_straight = find_straight(rankset);
+        let is_straight = find_straight_with_wheel::<SHORT_DECK>(rankset);
 
         if flush_suit >= 0 {
-            let is_straight_flush = find_straight(rankset_suit[flush_suit as usize]);
+            let is_straight_flush =
+                find_straight_with_wheel::<SHORT_DECK>(rankset_suit[flush_suit as usize]);
             if is_straight_flush != 0 {
                 // straight flush
                 (8 << 26) | is_straight_flush
@@ -173,4 +214,30 @@ mod tests {
         assert_eq!(counter[1], 58627800); // one pair
         assert_eq!(counter[0], 23294460); // high card
     }
+
+    #[test]
+    fn short_deck_rankings() {
+        let hand = |s: &str| {
+            crate::cards_from_str(s)
+                .unwrap()
+                .into_iter()
+                .fold(Hand::new(), |hand, card| hand.add_card(card as usize))
+        };
+
+        let flush = hand("AhQhTh8h6hKsKd").evaluate_short_deck();
+        let full_house = hand("KhKsKdQcQd9s7c").evaluate_short_deck();
+        let quads = hand("6c6d6h6s7c8d9h").evaluate_short_deck();
+        let straight = hand("As6c7d8h9sKcKd").evaluate_short_deck();
+        let trips = hand("KhKsKdQcJd9s7c").evaluate_short_deck();
+        assert!(flush > full_house);
+        assert!(quads > flush);
+        assert!(straight > trips);
+        assert!(straight < hand("6c7d8h9sTcKcKd").evaluate_short_deck());
+        assert!(hand("Ah6h7h8h9hKcKd").evaluate_short_deck() > quads);
+
+        // within a category, the usual order is kept
+        assert!(full_house < hand("AhAsAdQcQd9s7c").evaluate_short_deck());
+        assert!(flush < hand("AhKhTh8h6hQsQd").evaluate_short_deck());
+        assert!(full_house > hand("AhAsKdKcQd9s7c").evaluate_short_deck());
+    }
 }
@@ -0,0 +1,332 @@
+use crate::card::*;
+use crate::hand::*;
+use crate::texture::*;
+use std::fmt;
+
+/// Minimum kicker rank of "top pair, good kicker" (T).
+const GOOD_KICKER_RANK: u8 = 8;
+
+/// An enum representing the class of a private hand on a given board.
+///
+/// The classes are ordered from the strongest to the weakest. Made hands take precedence over
+/// draws, and a hand is only classified by the part that uses at least one of its hole cards (e.g.,
+/// a hand playing the board is not a straight even if the board is a straight).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandClass {
+    /// Straight flush.
+    StraightFlush,
+
+    /// Four of a kind.
+    Quads,
+
+    /// Full house.
+    FullHouse,
+
+    /// Flush.
+    Flush,
+
+    /// Straight.
+    Straight,
+
+    /// Three of a kind with a pocket pair.
+    Set,
+
+    /// Three of a kind with one hole card (i.e., on a paired board).
+    Trips,
+
+    /// Two pair using both hole cards.
+    TwoPair,
+
+    /// Pocket pair higher than any board card.
+    Overpair,
+
+    /// Top pair with the best possible kicker.
+    TopPairTopKicker,
+
+    /// Top pair with a kicker of T or better.
+    TopPairGoodKicker,
+
+    /// Top pair with a kicker of 9 or worse.
+    TopPairWeakKicker,
+
+    /// Pocket pair lower than the highest board card.
+    Underpair,
+
+    /// Pair with the second highest rank on the board.
+    SecondPair,
+
+    /// Pair with the third highest rank on the board or lower.
+    WeakPair,
+
+    /// Flush draw with a straight draw.
+    ComboDraw,
+
+    /// Four cards to a flush.
+    FlushDraw,
+
+    /// Straight draw completed by two or more ranks (including double gutshots).
+    OpenEnder,
+
+    /// Straight draw completed by exactly one rank.
+    Gutshot,
+
+    /// Two hole cards higher than any board card.
+    Overcards,
+
+    /// None of the above.
+    Air,
+}
+
+impl HandClass {
+    /// List of all hand classes, from the strongest to the weakest.
+    pub const ALL: [Self; 21] = [
+        Self::StraightFlush,
+        Self::Quads,
+        Self::FullHouse,
+        Self::Flush,
+        Self::Straight,
+        Self::Set,
+        Self::Trips,
+        Self::TwoPair,
+        Self::Overpair,
+        Self::TopPairTopKicker,
+        Self::TopPairGoodKicker,
+        Self::TopPairWeakKicker,
+        Self::Underpair,
+        Self::SecondPair,
+        Self::WeakPair,
+        Self::ComboDraw,
+        Self::FlushDraw,
+        Self::OpenEnder,
+        Self::Gutshot,
+        Self::Overcards,
+        Self::Air,
+    ];
+
+    /// Returns whether the class is a made hand (one pair or better).
+    #[inline]
+    pub fn is_made_hand(self) -> bool {
+        self <= Self::WeakPair
+    }
+
+    /// Returns whether the class is a drawing hand.
+    #[inline]
+    pub fn is_draw(self) -> bool {
+        (Self::ComboDraw..=Self::Gutshot).contains(&self)
+    }
+}
+
+impl fmt::Display for HandClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::StraightFlush => "Straight flush",
+            Self::Quads => "Quads",
+            Self::FullHouse => "Full house",
+            Self::Flush => "Flush",
+            Self::Straight => "Straight",
+            Self::Set => "Set",
+            Self::Trips => "Trips",
+            Self::TwoPair => "Two pair",
+            Self::Overpair => "Overpair",
+            Self::TopPairTopKicker => "Top pair, top kicker",
+            Self::TopPairGoodKicker => "Top pair, good kicker",
+            Self::TopPairWeakKicker => "Top pair, weak kicker",
+            Self::Underpair => "Underpair",
+            Self::SecondPair => "Second pair",
+            Self::WeakPair => "Weak pair",
+            Self::ComboDraw => "Combo draw",
+            Self::FlushDraw => "Flush draw",
+            Self::OpenEnder => "Open-ended straight draw",
+            Self::Gutshot => "Gutshot",
+            Self::Overcards => "Overcards",
+            Self::Air => "Air",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Classifies the private hand on the given board.
+///
+/// The `board` must contain 3 to 5 cards. Draws and overcards are only considered when the board
+/// has cards to come; on the river, hands without a pair are classified as [`HandClass::Air`].
+///
+/// Panics if the board has an invalid length or the hand overlaps with the board.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let board = flop_from_str("Kh9h4c").unwrap();
+/// let hand = |s: &str| {
+///     let cards = cards_from_str(s).unwrap();
+///     classify_hand((cards[0], cards[1]), &board)
+/// };
+///
+/// assert_eq!(hand("9s9d"), HandClass::Set);
+/// assert_eq!(hand("AsKd"), HandClass::TopPairTopKicker);
+/// assert_eq!(hand("Kc5c"), HandClass::TopPairWeakKicker);
+/// assert_eq!(hand("AhQh"), HandClass::FlushDraw);
+/// assert_eq!(hand("JhTh"), HandClass::ComboDraw);
+/// assert_eq!(hand("6s2d"), HandClass::Air);
+/// ```
+pub fn classify_hand(hand: (Card, Card), board: &[Card]) -> HandClass {
+    assert!(
+        (3..=5).contains(&board.len()),
+        "Board must contain 3 to 5 cards"
+    );
+
+    let (c1, c2) = hand;
+    let board_mask = board.iter().fold(0u64, |acc, &c| acc | (1 << c));
+    assert!(
+        c1 != c2 && board_mask & ((1 << c1) | (1 << c2)) == 0,
+        "Hand overlaps with the board"
+    );
+
+    let board_hand = board
+        .iter()
+        .fold(Hand::new(), |hand, &card| hand.add_card(card as usize));
+    let full_hand = board_hand.add_card(c1 as usize).add_card(c2 as usize);
+    let board_value = board_hand.evaluate_internal();
+    let value = full_hand.evaluate_internal();
+    let board_category = board_value >> 26;
+    let category = value >> 26;
+
+    // straight or better
+    if category >= 4 && value > board_value && !(category == 7 && board_category == 7) {
+        return match category {
+            8 => HandClass::StraightFlush,
+            7 => HandClass::Quads,
+            6 => HandClass::FullHouse,
+            5 => HandClass::Flush,
+            _ => HandClass::Straight,
+        };
+    }
+
+    let texture = BoardTexture::new(board);
+    let (r1, r2) = ((c1 >> 2).max(c2 >> 2), (c1 >> 2).min(c2 >> 2));
+    let (on_board1, on_board2) = (texture.contains_rank(r1), texture.contains_rank(r2));
+
+    if category == 3 && board_category < 3 {
+        return if r1 == r2 {
+            HandClass::Set
+        } else {
+            HandClass::Trips
+        };
+    }
+
+    if category == 2 && board_category < 2 && r1 != r2 && on_board1 && on_board2 {
+        return HandClass::TwoPair;
+    }
+
+    let board_ranks = (0..13)
+        .rev()
+        .filter(|&rank| texture.contains_rank(rank))
+        .collect::<Vec<_>>();
+
+    // pocket pair
+    if r1 == r2 && !on_board1 {
+        return if r1 > board_ranks[0] {
+            HandClass::Overpair
+        } else {
+            HandClass::Underpair
+        };
+    }
+
+    // one pair
+    if on_board1 || on_board2 {
+        let (rank, kicker) = if on_board1 { (r1, r2) } else { (r2, r1) };
+        let index = board_ranks.iter().position(|&r| r == rank).unwrap();
+        return match index {
+            0 => {
+                let top_kicker = (0..13).rev().find(|&r| !texture.contains_rank(r));
+                if Some(kicker) == top_kicker {
+                    HandClass::TopPairTopKicker
+                } else if kicker >= GOOD_KICKER_RANK {
+                    HandClass::TopPairGoodKicker
+                } else {
+                    HandClass::TopPairWeakKicker
+                }
+            }
+            1 => HandClass::SecondPair,
+            _ => HandClass::WeakPair,
+        };
+    }
+
+    if board.len() == 5 {
+        return HandClass::Air;
+    }
+
+    // flush draw
+    let is_flush_draw = [c1, c2].iter().any(|&c| {
+        let suit = c & 3;
+        texture.suit_counts[suit as usize] + (c1 & 3 == suit) as u8 + (c2 & 3 == suit) as u8 == 4
+    });
+
+    // straight draw
+    let board_rankset = texture.rankset() as i32;
+    let rankset = board_rankset | (1 << r1) | (1 << r2);
+    let num_outs = (0..13)
+        .filter(|&rank| {
+            let bit = 1 << rank;
+            rankset & bit == 0
+                && find_straight(rankset | bit) != 0
+                && find_straight(board_rankset | bit) == 0
+        })
+        .count();
+
+    match (is_flush_draw, num_outs) {
+        (true, 1..) => HandClass::ComboDraw,
+        (true, _) => HandClass::FlushDraw,
+        (false, 2..) => HandClass::OpenEnder,
+        (false, 1) => HandClass::Gutshot,
+        _ if r2 > board_ranks[0] => HandClass::Overcards,
+        _ => HandClass::Air,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range::*;
+
+    #[test]
+    fn classify_hand_classes() {
+        let tests = [
+            ("7c8c9c", "TcJc", HandClass::StraightFlush),
+            ("7c7d9h", "7h7s", HandClass::Quads),
+            ("7c7d9h", "9c9s", HandClass::FullHouse),
+            ("2h5h9h", "Ah3h", HandClass::Flush),
+            ("5c6d7h", "8s9s", HandClass::Straight),
+            ("5c6d7h8s9s", "2c2d", HandClass::Underpair),
+            ("5c6d7h8s9s", "Tc2d", HandClass::Straight),
+            ("Kc7d2h", "7h7s", HandClass::Set),
+            ("Kc7d7h", "Ks7s", HandClass::FullHouse),
+            ("Kc7d7h", "As7s", HandClass::Trips),
+            ("Kc7d2h", "Ks7s", HandClass::TwoPair),
+            ("Kc7d2h", "AsAh", HandClass::Overpair),
+            ("Kc7d7h", "QsQh", HandClass::Underpair),
+            ("AcKd2h", "AsQh", HandClass::TopPairTopKicker),
+            ("AcKd2h", "AsTh", HandClass::TopPairGoodKicker),
+            ("AcKd2h", "As9h", HandClass::TopPairWeakKicker),
+            ("AcKd2h", "Ks9h", HandClass::SecondPair),
+            ("AcKd2h", "2s9h", HandClass::WeakPair),
+            ("AcKd2h5s", "9c9d", HandClass::Underpair),
+            ("Tc9c2h", "8c7c", HandClass::ComboDraw),
+            ("Tc9c2h", "Qc3c", HandClass::FlushDraw),
+            ("Tc9c2h", "8d7d", HandClass::OpenEnder),
+            ("Tc9c2h", "8d6d", HandClass::Gutshot),
+            ("Tc9d2h", "AsKs", HandClass::Overcards),
+            ("Tc9d2h", "As4s", HandClass::Air),
+            ("Tc9d2h5s", "AsKs", HandClass::Overcards),
+            ("Tc9d2h5s4c", "AsKs", HandClass::Air),
+            ("Tc9c8c7c", "2d3d", HandClass::Air),
+        ];
+
+        for (board, hand, expected) in tests {
+            let board = cards_from_str(board).unwrap();
+            let hand = cards_from_str(hand).unwrap();
+            let class = classify_hand((hand[0], hand[1]), &board);
+            assert_eq!(class, expected, "{hand:?} on {board:?}");
+        }
+    }
+}
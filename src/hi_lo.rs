@@ -0,0 +1,292 @@
+// [Hi-Lo Split]
+// In hi-lo split games, the pot is divided between the best high hand and the best qualifying low
+// hand. A low hand consists of five unpaired cards of eight or lower with aces counted low, and
+// straights and flushes do not count against it (ace-to-five). If no hand qualifies for low, the
+// high hand wins the whole pot. A tie in either half splits the half, so a player who wins one
+// half and ties the other is quartered (3/4 against 1/4 heads-up).
+//
+// The showdown is expressed by `SplitPotEvaluator`, which evaluates the two halves separately.
+// `PostFlopGame` deals two-card hands, so only hold'em hi-lo (`HoldemHiLoEvaluator`, set by
+// `PostFlopGame::set_split_pot_evaluator`) can be solved. Omaha 8 and Big O need the four- and
+// five-card hands, which `PostFlopGame` does not support.
+
+use crate::card::*;
+use crate::error::*;
+use crate::evaluator::*;
+use std::cmp::Ordering;
+
+/// A trait for evaluating hands at the showdown of split-pot games.
+///
+/// The pot is divided as described in [`hi_lo_share`]. As with [`HandEvaluator`], only the order
+/// of the returned values matters, and a larger value represents a stronger hand.
+///
+/// [`HandEvaluator`]: crate::HandEvaluator
+pub trait SplitPotEvaluator: Send + Sync {
+    /// Returns the high strength of `hand` on the complete `board`.
+    fn evaluate_high(&self, board: &[Card; 5], hand: &[Card]) -> u32;
+
+    /// Returns the low strength of `hand` on the complete `board`, or `None` if the hand does not
+    /// qualify for low.
+    fn evaluate_low(&self, board: &[Card; 5], hand: &[Card]) -> Option<u32>;
+}
+
+/// The evaluator of hold'em hi-lo with the eight-or-better qualifier.
+///
+/// Both halves are made of the best five of the seven cards (two hole cards and five board
+/// cards), and the high half is ranked as in [`HoldemEvaluator`]. This is the evaluator to use
+/// with [`PostFlopGame::set_split_pot_evaluator`], whose private hands have two cards.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let board = board_from_str("2c5d7hKsQd").unwrap().try_into().unwrap();
+/// let wheel = cards_from_str("As3d").unwrap();
+/// let eight_low = cards_from_str("8s3c").unwrap();
+/// let kings = cards_from_str("KhKd").unwrap();
+/// let low = |hand: &[Card]| HoldemHiLoEvaluator.evaluate_low(&board, hand);
+/// assert!(low(&wheel) > low(&eight_low));
+/// assert!(low(&kings).is_none());
+/// ```
+///
+/// [`HoldemEvaluator`]: crate::HoldemEvaluator
+/// [`PostFlopGame::set_split_pot_evaluator`]: crate::PostFlopGame::set_split_pot_evaluator
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HoldemHiLoEvaluator;
+
+impl SplitPotEvaluator for HoldemHiLoEvaluator {
+    #[inline]
+    fn evaluate_high(&self, board: &[Card; 5], hand: &[Card]) -> u32 {
+        HoldemEvaluator.evaluate(board, (hand[0], hand[1]))
+    }
+
+    fn evaluate_low(&self, board: &[Card; 5], hand: &[Card]) -> Option<u32> {
+        // the five lowest distinct ranks make the best low
+        let mut rank_set = 0u16;
+        for &card in board.iter().chain(hand) {
+            if let Some(rank) = low_rank(card) {
+                rank_set |= 1 << rank;
+            }
+        }
+        while rank_set.count_ones() > 5 {
+            rank_set &= !(1 << (15 - rank_set.leading_zeros()));
+        }
+        (rank_set.count_ones() == 5).then(|| low_strength(rank_set))
+    }
+}
+
+/// Returns the rank of `card` for the eight-or-better low (ace = 1), or `None` if it is higher
+/// than eight.
+#[inline]
+fn low_rank(card: Card) -> Option<u16> {
+    match card >> 2 {
+        12 => Some(1),
+        rank @ 0..=6 => Some(rank as u16 + 2),
+        _ => None,
+    }
+}
+
+/// Returns the low strength of the five distinct ranks in `rank_set` (bit `r` for rank `r`).
+///
+/// The ranks are compared from the highest, so the strength is the complement of the ranks
+/// packed in descending order: 5-4-3-2-A is the best and 8-7-6-5-4 is the worst.
+#[inline]
+fn low_strength(rank_set: u16) -> u32 {
+    let packed = (1..=8)
+        .rev()
+        .filter(|rank| rank_set & (1 << rank) != 0)
+        .fold(0u32, |acc, rank| (acc << 4) | rank);
+    (1 << 20) - packed
+}
+
+/// Returns the share of the pot won by `hands[0]` at the showdown of a hi-lo split game.
+///
+/// The high half and the qualifying low half are awarded separately, and a tie in a half splits
+/// it. If neither hand qualifies for low, the high hand wins the whole pot.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let board = board_from_str("2c5d7hKsQd").unwrap().try_into().unwrap();
+/// let kings = cards_from_str("KhKd").unwrap();
+/// let jacks = cards_from_str("JhJs").unwrap();
+/// let nut_low = cards_from_str("As3d").unwrap();
+/// let same_low = cards_from_str("Ac3h").unwrap();
+///
+/// // scoop without a qualifying low
+/// assert_eq!(hi_lo_share(&HoldemHiLoEvaluator, &board, [&kings, &jacks]), 1.0);
+///
+/// // split: the high is won by the set of kings, and the low by A-3
+/// assert_eq!(hi_lo_share(&HoldemHiLoEvaluator, &board, [&kings, &nut_low]), 0.5);
+///
+/// // both halves are tied
+/// assert_eq!(hi_lo_share(&HoldemHiLoEvaluator, &board, [&nut_low, &same_low]), 0.5);
+/// ```
+pub fn hi_lo_share(
+    evaluator: &dyn SplitPotEvaluator,
+    board: &[Card; 5],
+    hands: [&[Card]; 2],
+) -> f64 {
+    let share = |ordering: Ordering| match ordering {
+        Ordering::Greater => 1.0,
+        Ordering::Equal => 0.5,
+        Ordering::Less => 0.0,
+    };
+
+    let high = hands.map(|hand| evaluator.evaluate_high(board, hand));
+    let high_share = share(high[0].cmp(&high[1]));
+
+    let low = hands.map(|hand| evaluator.evaluate_low(board, hand));
+    if low == [None, None] {
+        high_share
+    } else {
+        // `None` is less than any qualifying low
+        0.5 * high_share + 0.5 * share(low[0].cmp(&low[1]))
+    }
+}
+
+/// Computes the hi-lo equity of `hands[0]` against `hands[1]` on the board by enumerating all
+/// runouts (see [`hi_lo_share`]).
+///
+/// Both hands must consist of two cards. Returns [`SolverError::InvalidArgument`] if the board
+/// does not consist of three to five cards, a hand does not consist of two cards, or the cards
+/// are not valid and distinct.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let board = board_from_str("2c5d7h").unwrap();
+/// let low_draw = cards_from_str("As3d").unwrap();
+/// let set = cards_from_str("7s7d").unwrap();
+/// let equity = hi_lo_equity(&HoldemHiLoEvaluator, [&low_draw, &set], &board).unwrap();
+/// assert!(0.5 < equity && equity < 0.7);
+/// ```
+pub fn hi_lo_equity(
+    evaluator: &dyn SplitPotEvaluator,
+    hands: [&[Card]; 2],
+    board: &[Card],
+) -> Result<f64, SolverError> {
+    if !(3..=5).contains(&board.len()) {
+        return Err(SolverError::InvalidArgument(
+            "Board must consist of three to five cards".to_owned(),
+        ));
+    }
+    if hands.iter().any(|hand| hand.len() != 2) {
+        return Err(SolverError::InvalidArgument(
+            "Hands must consist of two cards".to_owned(),
+        ));
+    }
+
+    let mut mask = 0u64;
+    for &card in hands[0].iter().chain(hands[1]).chain(board) {
+        if card >= 52 || mask & (1 << card) != 0 {
+            return Err(SolverError::InvalidArgument(
+                "Cards must be valid and distinct".to_owned(),
+            ));
+        }
+        mask |= 1 << card;
+    }
+
+    let deck = (0..52)
+        .filter(|&card| mask & (1 << card) == 0)
+        .collect::<Vec<Card>>();
+    let mut full_board = [NOT_DEALT; 5];
+    full_board[..board.len()].copy_from_slice(board);
+
+    let mut total = 0.0;
+    let mut count = 0;
+    match board.len() {
+        5 => {
+            total += hi_lo_share(evaluator, &full_board, hands);
+            count += 1;
+        }
+        4 => {
+            for &river in &deck {
+                full_board[4] = river;
+                total += hi_lo_share(evaluator, &full_board, hands);
+                count += 1;
+            }
+        }
+        _ => {
+            for (i, &turn) in deck.iter().enumerate() {
+                for &river in &deck[i + 1..] {
+                    full_board[3] = turn;
+                    full_board[4] = river;
+                    total += hi_lo_share(evaluator, &full_board, hands);
+                    count += 1;
+                }
+            }
+        }
+    }
+
+    Ok(total / count as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range::*;
+
+    #[test]
+    fn hi_lo_showdown() {
+        let board = |s: &str| -> [Card; 5] { board_from_str(s).unwrap().try_into().unwrap() };
+        let hand = |s: &str| cards_from_str(s).unwrap();
+
+        // any five of the seven cards, and straights and flushes are ignored
+        let low = |b: &str, h: &str| HoldemHiLoEvaluator.evaluate_low(&board(b), &hand(h));
+        assert!(low("2c3d4h6sKd", "AsKc") > low("2c3d4h5s8d", "KcQc"));
+        assert!(low("2c3d4h5s8d", "KcQc").is_some());
+        assert!(low("2c3c4cKsKd", "Ac5c") > low("2c3c4cKsKd", "Ac6c"));
+        assert!(low("2c3d4hKsKd", "As5c") > low("2c3d4hKsKd", "As6c"));
+        assert!(low("2c3c4cKsKd", "Ah9c").is_none());
+
+        // counterfeited: a duplicated rank cannot be used
+        assert!(low("2c3d4hKsKd", "Ad2s").is_none());
+        assert!(low("2c3d9hTsKd", "As4c").is_none());
+        assert!(
+            HoldemHiLoEvaluator.evaluate_high(&board("2c3d4hKsKd"), &hand("As5c"))
+                > HoldemHiLoEvaluator.evaluate_high(&board("2c3d4hKsKd"), &hand("AhAs"))
+        );
+
+        let b = board("2c5d7hKsQd");
+        let kings = hand("KhKd");
+        let nut_low = hand("As3d");
+        let nut_low2 = hand("Ac3h");
+        let worse_low = hand("8s6d");
+        let share = |h0: &[Card], h1: &[Card]| hi_lo_share(&HoldemHiLoEvaluator, &b, [h0, h1]);
+        assert_eq!(share(&kings, &nut_low), 0.5);
+        assert_eq!(share(&nut_low, &worse_low), 1.0);
+        assert_eq!(share(&nut_low, &nut_low2), 0.5);
+        assert_eq!(share(&worse_low, &nut_low), 0.0);
+
+        // quartered: the low is tied and the high is won by the pair of queens
+        let b2 = board("2c5d7h8sQd");
+        let queens = hand("AcQc");
+        let nine_high = hand("Ad9d");
+        let share2 = |h0: &[Card], h1: &[Card]| hi_lo_share(&HoldemHiLoEvaluator, &b2, [h0, h1]);
+        assert_eq!(share2(&queens, &nine_high), 0.75);
+        assert_eq!(share2(&nine_high, &queens), 0.25);
+
+        let flop = board_from_str("2c5d7h").unwrap();
+        let equity = hi_lo_equity(&HoldemHiLoEvaluator, [&nut_low, &kings], &flop).unwrap();
+        let reverse = hi_lo_equity(&HoldemHiLoEvaluator, [&kings, &nut_low], &flop).unwrap();
+        assert!((equity + reverse - 1.0).abs() < 1e-9);
+
+        // the river equity is the showdown share
+        assert_eq!(
+            hi_lo_equity(&HoldemHiLoEvaluator, [&kings, &nut_low], &b).unwrap(),
+            0.5
+        );
+        let invalid = |hands: [&[Card]; 2], board: &[Card]| {
+            matches!(
+                hi_lo_equity(&HoldemHiLoEvaluator, hands, board),
+                Err(SolverError::InvalidArgument(_))
+            )
+        };
+        assert!(invalid([&kings, &kings], &flop));
+        assert!(invalid([&kings, &hand("AsKcQc")], &flop));
+        assert!(invalid([&kings, &nut_low], &flop[..2]));
+    }
+}
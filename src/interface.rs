@@ -1,7 +1,116 @@
+use crate::action_tree::BoardState;
+use crate::error::SolverError;
 use crate::mutex_like::*;
 use std::mem::MaybeUninit;
 use std::ops::Range;
 
+#[cfg(feature = "rayon")]
+use std::sync::Arc;
+
+/// Scale-management policy of the 16-bit compressed storage.
+///
+/// In the compressed mode, the cumulative regrets and strategies of each node are stored as 16-bit
+/// integers with a floating-point scale per node. By default, the scale is recomputed from the
+/// maximum absolute value every time the node is updated, which gives the best precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionParams {
+    /// Number of the most significant bits left unused when the scale is recomputed (0 to 8).
+    ///
+    /// The reserved bits are headroom for the values to grow before the next recomputation of the
+    /// scale; each reserved bit halves the precision. Defaults to `0`.
+    pub reserved_bits: u8,
+
+    /// Number of iterations between the recomputations of the scale (at least 1).
+    ///
+    /// In the other iterations, the existing scale is reused, which skips a pass to find the
+    /// maximum value, and the values that do not fit in the 16-bit range are saturated. Defaults to
+    /// `1` (every iteration).
+    pub rescale_interval: u32,
+}
+
+impl Default for CompressionParams {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            reserved_bits: 0,
+            rescale_interval: 1,
+        }
+    }
+}
+
+impl CompressionParams {
+    /// Checks if the parameters are valid.
+    #[inline]
+    pub fn check(&self) -> Result<(), SolverError> {
+        if self.reserved_bits > 8 {
+            return Err(SolverError::InvalidArgument(format!(
+                "Reserved bits must be at most 8: {}",
+                self.reserved_bits
+            )));
+        }
+        if self.rescale_interval == 0 {
+            return Err(SolverError::InvalidArgument(
+                "Rescale interval must be positive".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Update schedule of the strategy of a player while solving.
+///
+/// By default, the strategies of both players are updated in every iteration. When only the
+/// strategy of one player is studied, the opponent can be updated less frequently, or its strategy
+/// can be fixed after some iterations, to spend most of the computation on the studied player. The
+/// iterations in which a player is not updated skip the traversal of the tree for that player.
+///
+/// Note that the strategy of a player updated less often is less accurate, and the exploitability
+/// reported by [`solve`] is that of both players combined.
+///
+/// [`solve`]: crate::solve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateSchedule {
+    /// Number of iterations between the updates of the strategy (at least 1). Defaults to `1`
+    /// (every iteration).
+    pub interval: u32,
+
+    /// Number of iterations after which the strategy is no longer updated and stays fixed at the
+    /// strategy of the earlier iterations. Defaults to `None` (never fixed).
+    pub freeze_after: Option<u32>,
+}
+
+impl Default for UpdateSchedule {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            interval: 1,
+            freeze_after: None,
+        }
+    }
+}
+
+impl UpdateSchedule {
+    /// Checks if the schedule is valid.
+    #[inline]
+    pub fn check(&self) -> Result<(), SolverError> {
+        if self.interval == 0 {
+            return Err(SolverError::InvalidArgument(
+                "Update interval must be positive".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns whether the strategy is updated in the iteration `current_iteration` (0-indexed).
+    #[inline]
+    pub fn is_update_iteration(&self, current_iteration: u32) -> bool {
+        current_iteration.is_multiple_of(self.interval)
+            && self
+                .freeze_after
+                .is_none_or(|freeze_after| current_iteration < freeze_after)
+    }
+}
+
 /// The trait representing a game.
 pub trait Game: Send + Sync {
     /// The type representing a node in game tree.
@@ -42,6 +151,10 @@ pub trait Game: Send + Sync {
     #[doc(hidden)]
     fn set_solved(&mut self);
 
+    /// Records the number of iterations and the exploitability of the finished solving.
+    #[doc(hidden)]
+    fn set_solve_result(&mut self, _num_iterations: u32, _exploitability: f32) {}
+
     /// Returns whether the instance is ready to be solved.
     #[doc(hidden)]
     fn is_ready(&self) -> bool {
@@ -78,10 +191,52 @@ pub trait Game: Send + Sync {
         false
     }
 
+    /// Returns whether the cumulative strategy is stored in 8-bit (only when the compression is
+    /// enabled).
+    #[doc(hidden)]
+    fn is_strategy_quantized(&self) -> bool {
+        false
+    }
+
+    /// Returns the scale-management policy of the compressed storage of the node.
+    #[doc(hidden)]
+    fn node_compression_params(&self, _node: &Self::Node) -> CompressionParams {
+        CompressionParams::default()
+    }
+
+    /// Returns the update schedule of the strategy of the given player.
+    #[doc(hidden)]
+    fn update_schedule(&self, _player: usize) -> UpdateSchedule {
+        UpdateSchedule::default()
+    }
+
+    /// Returns the street of the node, which is used for profiling.
+    #[doc(hidden)]
+    fn node_street(&self, _node: &Self::Node) -> BoardState {
+        BoardState::Flop
+    }
+
     /// Returns whether the game is zero-sum.
     fn is_zero_sum(&self) -> bool {
         true
     }
+
+    /// Returns the dedicated thread pool to run the computations on. If `None`, the global thread
+    /// pool is used.
+    #[cfg(feature = "rayon")]
+    #[doc(hidden)]
+    fn thread_pool(&self) -> Option<Arc<rayon::ThreadPool>> {
+        None
+    }
+
+    /// Returns the thread pool on which the subtree of the `action`-th child of the root is
+    /// solved. If `None`, the subtree is solved on the current thread pool.
+    #[cfg(feature = "rayon")]
+    #[doc(hidden)]
+    fn subtree_thread_pool(&self, action: usize) -> Option<Arc<rayon::ThreadPool>> {
+        let _ = action;
+        None
+    }
 }
 
 /// The trait representing a node in game tree.
@@ -184,6 +339,18 @@ pub trait GameNode: Send + Sync {
         unreachable!()
     }
 
+    /// Returns the 8-bit quantized strategy.
+    #[doc(hidden)]
+    fn strategy_quantized(&self) -> &[u8] {
+        unreachable!()
+    }
+
+    /// Returns the mutable reference to the 8-bit quantized strategy.
+    #[doc(hidden)]
+    fn strategy_quantized_mut(&mut self) -> &mut [u8] {
+        unreachable!()
+    }
+
     /// Returns the compressed cumulative regrets.
     #[doc(hidden)]
     fn regrets_compressed(&self) -> &[i16] {
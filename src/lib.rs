@@ -67,12 +67,11 @@
     clippy::string_add,
     clippy::string_add_assign,
     clippy::string_lit_as_bytes,
-    clippy::string_to_string,
     clippy::suspicious_to_owned,
     clippy::trait_duplication_in_bounds,
     clippy::trivially_copy_pass_by_ref,
     clippy::type_repetition_in_bounds,
-    clippy::unchecked_duration_subtraction,
+    clippy::unchecked_time_subtraction,
     clippy::unicode_not_nfc,
     clippy::uninlined_format_args,
     clippy::unnecessary_join,
@@ -123,55 +122,235 @@
 //! - `bincode`: Uses [bincode] crate (2.0.0-rc.3) to serialize and deserialize the `PostFlopGame` struct.
 //!   This feature is required to save and load the game tree.
 //!   Enabled by default.
+//! - `cbor`: Uses [ciborium] crate to serialize query results (`NodeQuery`) into CBOR.
+//!   Implies `serde`.
+//!   Disabled by default.
+//! - `cli`: Builds the `postflop-solver` binary with `solve` (executes a `SolveSpec` file),
+//!   `inspect`, and `export` (CSV, JSON, or PioSOLVER format) subcommands.
+//!   Implies `bincode` and `spec`.
+//!   Disabled by default.
 //! - `custom-alloc`: Uses custom memory allocator in solving process (only available in nightly Rust).
 //!   It significantly reduces the number of calls of the default allocator,
 //!   so it is recommended to use this feature when the default allocator is not so efficient.
 //!   Note that this feature assumes that, at most, only one instance of `PostFlopGame` is available
 //!   when solving in a program.
 //!   Disabled by default.
+//! - `encryption`: Uses [chacha20poly1305] crate to save and load the game tree with authenticated
+//!   encryption under a key supplied by the caller. Implies `bincode`.
+//!   Disabled by default.
+//! - `ffi`: Exports a C API (`pfs_*` functions with opaque handles, status codes, and flat
+//!   buffers), declared in `include/postflop_solver.h`. The crate is built as an `rlib` only, so
+//!   build the shared library with `cargo rustc --release --lib --crate-type cdylib --features ffi`.
+//!   Disabled by default.
+//! - `mmap`: Enables `PostFlopGame::allocate_memory_on_disk`, which stores the game tree in
+//!   memory-mapped files so that trees larger than the physical memory can be solved, and
+//!   `load_game_from_file_mmap`, which queries a save file without reading it into memory (only available on Unix).
+//!   Disabled by default.
+//! - `msgpack`: Uses [rmp-serde] crate to serialize query results (`NodeQuery`) into MessagePack.
+//!   Implies `serde`.
+//!   Disabled by default.
+//! - `numa`: Enables NUMA-aware thread pools pinned to the CPUs of each NUMA node, and the
+//!   partitioning of a game across the nodes by `PostFlopGame::set_numa_nodes` (only available on
+//!   Linux).
+//!   Implies `rayon`.
+//!   Disabled by default.
+//! - `parquet`: Uses [arrow] and [parquet] crates to export the strategy as Apache Arrow record
+//!   batches (`PostFlopGame::export_arrow`) and Parquet files (`PostFlopGame::export_parquet`).
+//!   Disabled by default.
+//! - `profiling`: Records the time spent in each phase of the computation (tree build, terminal
+//!   evaluation per street, regret update, strategy sum, and finalization), which can be obtained by
+//!   `profiling_report`.
+//!   Disabled by default.
+//! - `python`: Uses [PyO3] crate to build a Python extension module named `postflop_solver`
+//!   (`TreeConfig` and `PostFlopGame` classes; see `PyGame`), whose queries return numpy arrays.
+//!   Build it with `maturin`, or build the shared library with
+//!   `cargo rustc --release --lib --crate-type cdylib --features python` and copy it as
+//!   `postflop_solver.so`.
+//!   Disabled by default.
 //! - `rayon`: Uses [rayon] crate for parallelization.
 //!   Enabled by default.
+//! - `serde`: Implements `Serialize` and `Deserialize` of [serde] crate for `Action` and the query
+//!   results (`NodeQuery`).
+//!   Disabled by default.
+//! - `server`: Enables `SolverServer`, an HTTP server (using [tiny_http] crate) that solves submitted
+//!   jobs on a worker pool within a memory budget, streams their progress, and serves queries
+//!   against the finished solves (also in MessagePack and CBOR with the `msgpack` and `cbor` features).
+//!   Jobs can be prioritized, retried, and persisted with resumable checkpoints. Implies `bincode`.
+//!   Disabled by default.
+//! - `spec`: Enables `SolveSpec`, a declarative specification of a batch of solves (ranges, boards,
+//!   tree configuration, solver settings, and outputs) loaded from TOML or YAML files (using [toml]
+//!   and [serde_yaml] crates) and executed end to end by `SolveSpec::run`. Implies `serde`.
+//!   Disabled by default.
+//! - `tracing`: Uses [tracing] crate to emit spans and events around the tree building, the memory
+//!   allocation, the iterations of the solver, and the finalization, which can be collected by any
+//!   `tracing` subscriber. Per-iteration spans are emitted at the `TRACE` level.
+//!   Disabled by default.
+//! - `wasm`: Uses [wasm-bindgen] crate to export the game construction, the solving, and the
+//!   queries to JavaScript (`TreeConfig` and `PostFlopGame` classes; see `WasmGame`). See the
+//!   WebAssembly section below for the build.
+//!   Disabled by default.
 //! - `zstd`: Uses [zstd] crate to compress and decompress the game tree.
 //!   This feature is required to save and load the game tree with compression.
 //!   Disabled by default.
 //!
+//! [arrow]: https://github.com/apache/arrow-rs
 //! [bincode]: https://github.com/bincode-org/bincode
+//! [chacha20poly1305]: https://github.com/RustCrypto/AEADs
+//! [ciborium]: https://github.com/enarx/ciborium
+//! [parquet]: https://github.com/apache/arrow-rs/tree/main/parquet
+//! [PyO3]: https://github.com/PyO3/pyo3
 //! [rayon]: https://github.com/rayon-rs/rayon
+//! [rmp-serde]: https://github.com/3Hren/msgpack-rust
+//! [serde]: https://github.com/serde-rs/serde
+//! [serde_yaml]: https://github.com/dtolnay/serde-yaml
+//! [tiny_http]: https://github.com/tiny-http/tiny-http
+//! [toml]: https://github.com/toml-rs/toml
+//! [tracing]: https://github.com/tokio-rs/tracing
+//! [wasm-bindgen]: https://github.com/wasm-bindgen/wasm-bindgen
 //! [zstd]: https://github.com/gyscos/zstd-rs
+//!
+//! # WebAssembly
+//!
+//! The solver runs on `wasm32-unknown-unknown` with the `rayon` feature disabled, in which case the
+//! computation is performed on a single thread. Build with `-C target-feature=+simd128` to use the
+//! WebAssembly SIMD instructions. The `mmap`, `numa`, `profiling`, and `zstd` features are not
+//! available on this target, and the functions reading and writing files return an error in the
+//! browser.
+//!
+//! To use the solver from JavaScript, build a `cdylib` with the `wasm` feature and generate the
+//! bindings with `wasm-bindgen`:
+//!
+//! ```sh
+//! cargo rustc --release --lib --crate-type cdylib --target wasm32-unknown-unknown \
+//!     --no-default-features --features bincode,wasm
+//! wasm-bindgen --target web --out-dir pkg \
+//!     target/wasm32-unknown-unknown/release/postflop_solver.wasm
+//! ```
 
 #![cfg_attr(feature = "custom-alloc", feature(allocator_api))]
 
+// `std::time::Instant` panics on the web
+#[cfg(all(feature = "profiling", target_arch = "wasm32", target_os = "unknown"))]
+compile_error!("The `profiling` feature is not available on wasm32-unknown-unknown");
+
 #[cfg(feature = "custom-alloc")]
 mod alloc;
 
+#[cfg(feature = "bincode")]
+mod archive;
+
+#[cfg(feature = "encryption")]
+mod encryption;
+
+#[cfg(feature = "ffi")]
+mod ffi;
+
 #[cfg(feature = "bincode")]
 mod file;
 
+#[cfg(all(feature = "numa", target_os = "linux"))]
+mod numa;
+
+#[cfg(feature = "python")]
+mod python;
+
+#[cfg(feature = "server")]
+mod server;
+
+#[cfg(feature = "spec")]
+mod spec;
+
+#[cfg(feature = "wasm")]
+mod wasm;
+
 mod action_tree;
+mod aggregation;
 mod atomic_float;
 mod bet_size;
+mod board_generator;
+mod bomb_pot;
 mod bunching;
 mod card;
+mod convergence;
+mod csv_import;
+mod error;
+mod evaluator;
 mod game;
 mod hand;
+mod hand_class;
 mod hand_table;
+mod hi_lo;
 mod interface;
 mod mutex_like;
+mod position;
+mod preflop;
+mod profiling;
+mod protocol;
+mod random;
 mod range;
+mod sensitivity;
 mod sliceop;
 mod solver;
+mod stack_sweep;
+mod storage;
+mod texture;
+mod units;
 mod utility;
 
+#[cfg(feature = "bincode")]
+pub use archive::*;
+
+#[cfg(feature = "encryption")]
+pub use encryption::*;
+
+#[cfg(feature = "ffi")]
+pub use ffi::*;
+
 #[cfg(feature = "bincode")]
 pub use file::*;
 
+#[cfg(all(feature = "numa", target_os = "linux"))]
+pub use numa::*;
+
+#[cfg(feature = "python")]
+pub use python::*;
+
+#[cfg(feature = "server")]
+pub use server::*;
+
+#[cfg(feature = "spec")]
+pub use spec::*;
+
+#[cfg(feature = "wasm")]
+pub use wasm::*;
+
+#[cfg(feature = "profiling")]
+pub use profiling::*;
+
 pub use action_tree::*;
+pub use aggregation::*;
 pub use bet_size::*;
+pub use board_generator::*;
+pub use bomb_pot::*;
 pub use bunching::*;
 pub use card::*;
+pub use convergence::*;
+pub use csv_import::*;
+pub use error::*;
+pub use evaluator::*;
 pub use game::*;
+pub use hand_class::*;
+pub use hi_lo::*;
 pub use interface::*;
 pub use mutex_like::*;
+pub use position::*;
+pub use preflop::*;
+pub use protocol::*;
+pub use random::*;
 pub use range::*;
+pub use sensitivity::*;
 pub use solver::*;
+pub use stack_sweep::*;
+pub use texture::*;
+pub use units::*;
 pub use utility::*;
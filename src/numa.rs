@@ -0,0 +1,199 @@
+use crate::error::SolverError;
+use std::fs;
+use std::mem;
+use std::sync::Arc;
+
+/// A NUMA node of the machine, returned by [`numa_nodes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumaNode {
+    /// ID of the node.
+    pub id: usize,
+
+    /// CPUs belonging to the node.
+    pub cpus: Vec<usize>,
+}
+
+/// Returns the NUMA nodes of the machine.
+///
+/// The topology is read from `/sys/devices/system/node`. If it is not available, a single node
+/// containing all CPUs is returned.
+pub fn numa_nodes() -> Vec<NumaNode> {
+    let mut nodes = Vec::new();
+
+    if let Ok(entries) = fs::read_dir("/sys/devices/system/node") {
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let Some(id) = name
+                .to_str()
+                .and_then(|name| name.strip_prefix("node"))
+                .and_then(|id| id.parse().ok())
+            else {
+                continue;
+            };
+            let Ok(cpulist) = fs::read_to_string(entry.path().join("cpulist")) else {
+                continue;
+            };
+            if let Ok(cpus) = parse_cpu_list(&cpulist)
+                && !cpus.is_empty()
+            {
+                nodes.push(NumaNode { id, cpus });
+            }
+        }
+    }
+
+    if nodes.is_empty() {
+        let num_cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+        nodes.push(NumaNode {
+            id: 0,
+            cpus: (0..num_cpus).collect(),
+        });
+    }
+
+    nodes.sort_unstable_by_key(|node| node.id);
+    nodes
+}
+
+/// Creates a thread pool whose worker threads are pinned to the CPUs of the given NUMA node.
+///
+/// Set the pool to a game with [`PostFlopGame::set_thread_pool`] to solve it on the node. Since
+/// the operating system places a page of the storage on the node of the thread that first writes
+/// to it, building and solving a game entirely on one pool keeps its storage local to the node.
+///
+/// Returns [`SolverError::InvalidArgument`] if the node has no CPUs, and
+/// [`SolverError::Other`] if the thread pool cannot be created.
+///
+/// [`PostFlopGame::set_thread_pool`]: crate::PostFlopGame::set_thread_pool
+pub fn numa_thread_pool(node: &NumaNode) -> Result<Arc<rayon::ThreadPool>, SolverError> {
+    if node.cpus.is_empty() {
+        return Err(SolverError::InvalidArgument(format!(
+            "NUMA node {} has no CPUs",
+            node.id
+        )));
+    }
+
+    let cpus = node.cpus.clone();
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(cpus.len())
+        .thread_name(move |index| format!("postflop-solver-numa-{index}"))
+        .start_handler(move |_| pin_current_thread(&cpus))
+        .build()
+        .map_err(|e| SolverError::Other(format!("Failed to create thread pool: {e}")))?;
+
+    Ok(Arc::new(pool))
+}
+
+/// Creates a pinned thread pool for each NUMA node (see [`numa_thread_pool`]).
+///
+/// Solving many games (e.g., a flop subset) at once is partitioned across the nodes by assigning
+/// the games to the pools in a round-robin manner and solving each group on its own thread. This
+/// avoids cross-socket traffic that occurs when a single global pool works on every game. To
+/// partition a single large game instead, use [`PostFlopGame::set_numa_nodes`].
+///
+/// [`PostFlopGame::set_numa_nodes`]: crate::PostFlopGame::set_numa_nodes
+pub fn numa_thread_pools() -> Result<Vec<Arc<rayon::ThreadPool>>, SolverError> {
+    numa_nodes().iter().map(numa_thread_pool).collect()
+}
+
+/// `MPOL_PREFERRED` of `<linux/mempolicy.h>`.
+const MPOL_PREFERRED: libc::c_int = 1;
+
+/// `MPOL_MF_MOVE` of `<linux/mempolicy.h>`.
+const MPOL_MF_MOVE: libc::c_uint = 1 << 1;
+
+/// Makes the pages within the address range [`start`, `end`) prefer the memory of the NUMA node
+/// `node`, moving the pages already placed on the other nodes. Only the pages entirely within the
+/// range are affected. Failures are ignored because the placement is only a performance hint.
+pub(crate) fn bind_memory(start: usize, end: usize, node: usize) {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if page_size <= 0 {
+        return;
+    }
+
+    let page_size = page_size as usize;
+    let start = start.next_multiple_of(page_size);
+    let end = end / page_size * page_size;
+
+    let mut mask = [0 as libc::c_ulong; 16];
+    let bits = libc::c_ulong::BITS as usize;
+    if start >= end || node >= mask.len() * bits {
+        return;
+    }
+    mask[node / bits] |= 1 << (node % bits);
+
+    unsafe {
+        libc::syscall(
+            libc::SYS_mbind,
+            start,
+            end - start,
+            MPOL_PREFERRED,
+            mask.as_ptr(),
+            mask.len() * bits,
+            MPOL_MF_MOVE,
+        );
+    }
+}
+
+/// Pins the current thread to the given CPUs. Failures are ignored because the pinning is only a
+/// performance hint.
+fn pin_current_thread(cpus: &[usize]) {
+    unsafe {
+        let mut set: libc::cpu_set_t = mem::zeroed();
+        for &cpu in cpus {
+            if cpu < libc::CPU_SETSIZE as usize {
+                libc::CPU_SET(cpu, &mut set);
+            }
+        }
+        libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &raw const set);
+    }
+}
+
+/// Parses a CPU list such as `0-3,8-11`.
+fn parse_cpu_list(list: &str) -> Result<Vec<usize>, String> {
+    let mut cpus = Vec::new();
+    let err = || format!("Invalid CPU list: {list}");
+
+    for range in list.trim().split(',').filter(|s| !s.is_empty()) {
+        match range.split_once('-') {
+            Some((start, end)) => {
+                let start = start.parse::<usize>().map_err(|_| err())?;
+                let end = end.parse::<usize>().map_err(|_| err())?;
+                if start > end {
+                    return Err(err());
+                }
+                cpus.extend(start..=end);
+            }
+            None => cpus.push(range.parse().map_err(|_| err())?),
+        }
+    }
+
+    Ok(cpus)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_list() {
+        assert_eq!(
+            parse_cpu_list("0-3,8,10-11\n").unwrap(),
+            [0, 1, 2, 3, 8, 10, 11]
+        );
+        assert!(parse_cpu_list("").unwrap().is_empty());
+        parse_cpu_list("3-1").unwrap_err();
+        parse_cpu_list("a").unwrap_err();
+    }
+
+    #[test]
+    fn thread_pools() {
+        let nodes = numa_nodes();
+        assert!(!nodes.is_empty());
+
+        let pools = numa_thread_pools().unwrap();
+        assert_eq!(pools.len(), nodes.len());
+        for (pool, node) in pools.iter().zip(&nodes) {
+            assert_eq!(pool.current_num_threads(), node.cpus.len());
+            assert_eq!(pool.install(|| 1 + 1), 2);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use crate::error::*;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Seat of a player at a 9-handed or smaller table, set in [`CardConfig::positions`].
+///
+/// The variants are ordered by the postflop order of action, i.e., a position acts before every
+/// greater position, so the out-of-position player of a heads-up pot is the one with the smaller
+/// position. The positions are parsed from and displayed as their usual abbreviations (e.g.,
+/// `BTN`), which are also used by the `serde` feature.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let btn: Position = "BTN".parse().unwrap();
+/// let bb: Position = "bb".parse().unwrap();
+/// assert_eq!(btn, Position::Button);
+/// assert!(bb < btn); // the big blind acts first postflop
+/// assert_eq!(bb.to_string(), "BB");
+/// ```
+///
+/// [`CardConfig::positions`]: crate::CardConfig::positions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Position {
+    /// Small blind (`SB`).
+    #[cfg_attr(feature = "serde", serde(rename = "SB"))]
+    SmallBlind,
+
+    /// Big blind (`BB`).
+    #[cfg_attr(feature = "serde", serde(rename = "BB"))]
+    BigBlind,
+
+    /// Under the gun (`UTG`).
+    #[cfg_attr(feature = "serde", serde(rename = "UTG"))]
+    UnderTheGun,
+
+    /// Under the gun + 1 (`UTG+1`).
+    #[cfg_attr(feature = "serde", serde(rename = "UTG+1"))]
+    UnderTheGun1,
+
+    /// Under the gun + 2 (`UTG+2`).
+    #[cfg_attr(feature = "serde", serde(rename = "UTG+2"))]
+    UnderTheGun2,
+
+    /// Lojack (`LJ`).
+    #[cfg_attr(feature = "serde", serde(rename = "LJ"))]
+    Lojack,
+
+    /// Hijack (`HJ`).
+    #[cfg_attr(feature = "serde", serde(rename = "HJ"))]
+    Hijack,
+
+    /// Cutoff (`CO`).
+    #[cfg_attr(feature = "serde", serde(rename = "CO"))]
+    Cutoff,
+
+    /// Button (`BTN`).
+    #[cfg_attr(feature = "serde", serde(rename = "BTN"))]
+    Button,
+}
+
+impl Position {
+    /// All positions in the postflop order of action.
+    pub const ALL: [Self; 9] = [
+        Self::SmallBlind,
+        Self::BigBlind,
+        Self::UnderTheGun,
+        Self::UnderTheGun1,
+        Self::UnderTheGun2,
+        Self::Lojack,
+        Self::Hijack,
+        Self::Cutoff,
+        Self::Button,
+    ];
+
+    /// Returns the abbreviation of the position (e.g., `"BTN"`).
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SmallBlind => "SB",
+            Self::BigBlind => "BB",
+            Self::UnderTheGun => "UTG",
+            Self::UnderTheGun1 => "UTG+1",
+            Self::UnderTheGun2 => "UTG+2",
+            Self::Lojack => "LJ",
+            Self::Hijack => "HJ",
+            Self::Cutoff => "CO",
+            Self::Button => "BTN",
+        }
+    }
+
+    /// Returns whether the position is the small blind or the big blind.
+    #[inline]
+    pub fn is_blind(&self) -> bool {
+        matches!(self, Self::SmallBlind | Self::BigBlind)
+    }
+}
+
+impl fmt::Display for Position {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Position {
+    type Err = SolverError;
+
+    /// Parses the abbreviation of a position, case-insensitively. `BU` is accepted for the button
+    /// and `UTG1` / `UTG2` for `UTG+1` / `UTG+2`. Returns [`SolverError::InvalidArgument`] for an
+    /// unknown abbreviation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.trim().to_ascii_uppercase();
+        let alias = match upper.as_str() {
+            "BU" => "BTN",
+            "UTG1" => "UTG+1",
+            "UTG2" => "UTG+2",
+            other => other,
+        };
+        Self::ALL
+            .into_iter()
+            .find(|position| position.as_str() == alias)
+            .ok_or_else(|| SolverError::InvalidArgument(format!("Invalid position: {s}")))
+    }
+}
+
+/// Returns the label of `player` (0 = OOP, 1 = IP) for reports: the abbreviation of its position
+/// if known, and `"OOP"` or `"IP"` otherwise.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let positions = [Some(Position::BigBlind), None];
+/// assert_eq!(player_label(0, positions), "BB");
+/// assert_eq!(player_label(1, positions), "IP");
+/// ```
+#[inline]
+pub fn player_label(player: usize, positions: [Option<Position>; 2]) -> &'static str {
+    match positions[player] {
+        Some(position) => position.as_str(),
+        None => ["OOP", "IP"][player],
+    }
+}
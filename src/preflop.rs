@@ -0,0 +1,909 @@
+// [Preflop]
+// This module provides a heads-up preflop solver. The preflop game is solved by the same Discounted
+// CFR as the postflop game (it implements `Game`), and the postflop play is abstracted at the flop
+// boundary: when a hand reaches the flop, the pot is divided by the values of a `PreflopEquity`
+// table of the 169 starting hand classes. The table can be estimated by Monte Carlo simulation of
+// the all-in equities, or filled with precomputed postflop values (e.g., the realized equities of
+// postflop solutions). The ranges at each node (see `PreflopGame::range`) can be fed to the
+// postflop solver.
+
+use crate::action_tree::*;
+use crate::card::*;
+use crate::error::*;
+use crate::evaluator::*;
+use crate::interface::*;
+use crate::mutex_like::*;
+use crate::random::*;
+use crate::range::*;
+use crate::utility::*;
+use std::mem::MaybeUninit;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Number of the starting hand classes (e.g., `AKs`).
+pub const NUM_HAND_CLASSES: usize = 169;
+
+/// Returns the index of the starting hand class of `hand` in `0..NUM_HAND_CLASSES`.
+///
+/// The classes form a 13x13 grid indexed by `13 * row + column`: a pair of rank `r` is at
+/// (`r`, `r`), a suited hand at (higher rank, lower rank), and an offsuit hand at (lower rank,
+/// higher rank), where the ranks are numbered from `0` (deuce) to `12` (ace).
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let hand = (card_from_str("Ks").unwrap(), card_from_str("As").unwrap());
+/// assert_eq!(hand_class_name(hand_class_index(hand)), "AKs");
+/// ```
+#[inline]
+pub fn hand_class_index(hand: (Card, Card)) -> usize {
+    let (rank1, rank2) = ((hand.0 >> 2) as usize, (hand.1 >> 2) as usize);
+    let (high, low) = (rank1.max(rank2), rank1.min(rank2));
+    if high == low || hand.0 & 3 == hand.1 & 3 {
+        13 * high + low
+    } else {
+        13 * low + high
+    }
+}
+
+/// Returns the name of the starting hand class of `index` (e.g., `"AA"`, `"AKs"`, or `"AKo"`).
+///
+/// # Panics
+/// Panics if `index` is not less than [`NUM_HAND_CLASSES`].
+pub fn hand_class_name(index: usize) -> String {
+    assert!(index < NUM_HAND_CLASSES, "Invalid hand class: {index}");
+    let (row, column) = ((index / 13) as u8, (index % 13) as u8);
+    let rank = |rank| rank_to_char(rank).unwrap();
+    match row.cmp(&column) {
+        std::cmp::Ordering::Equal => format!("{}{}", rank(row), rank(row)),
+        std::cmp::Ordering::Greater => format!("{}{}s", rank(row), rank(column)),
+        std::cmp::Ordering::Less => format!("{}{}o", rank(column), rank(row)),
+    }
+}
+
+/// Returns the hands of each starting hand class.
+fn hands_by_class() -> Vec<Vec<(Card, Card)>> {
+    let mut ret = vec![Vec::new(); NUM_HAND_CLASSES];
+    for card1 in 0..52 {
+        for card2 in card1 + 1..52 {
+            ret[hand_class_index((card1, card2))].push((card1, card2));
+        }
+    }
+    ret
+}
+
+/// Returns the hands of each starting hand class, ordered from the strongest class to the weakest
+/// by the all-in equity against a random hand (estimated by [`PreflopEquity::monte_carlo`]).
+pub(crate) fn hand_classes_by_strength(num_samples: usize, seed: u64) -> Vec<Vec<(Card, Card)>> {
+    let hands = hands_by_class();
+    let equity = PreflopEquity::monte_carlo(num_samples, seed);
+
+    let strength = (0..NUM_HAND_CLASSES)
+        .map(|class| {
+            let row = &equity.data[class * NUM_HAND_CLASSES..(class + 1) * NUM_HAND_CLASSES];
+            let sum = row.iter().zip(&hands).fold(0.0, |sum, (&value, opponent)| {
+                sum + value as f64 * opponent.len() as f64
+            });
+            sum / 1326.0
+        })
+        .collect::<Vec<_>>();
+
+    let mut order = (0..NUM_HAND_CLASSES).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| strength[b].total_cmp(&strength[a]));
+    order
+        .into_iter()
+        .map(|class| hands[class].clone())
+        .collect()
+}
+
+/// Values of the hands reaching the flop in a [`PreflopGame`]: the share of the pot won by each
+/// starting hand class of the small blind against each class of the big blind.
+///
+/// The values are from the point of view of the small blind, so that the game stays zero-sum;
+/// the big blind receives the rest of the pot. The value of a class against itself need not be
+/// `0.5`, which can express the positional advantage postflop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreflopEquity {
+    data: Vec<f32>,
+}
+
+impl PreflopEquity {
+    /// Creates a table from the values returned by `f` for the names of the classes of the small
+    /// blind and the big blind (see [`hand_class_name`]).
+    ///
+    /// Returns [`SolverError::InvalidArgument`] if any value is not between `0.0` and `1.0`.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// // the pairs win against the other hands, and the pot is split otherwise
+    /// let is_pair = |name: &str| name.len() == 2;
+    /// let equity = PreflopEquity::from_fn(|sb, bb| match (is_pair(sb), is_pair(bb)) {
+    ///     (true, false) => 1.0,
+    ///     (false, true) => 0.0,
+    ///     _ => 0.5,
+    /// })
+    /// .unwrap();
+    /// assert_eq!(equity.get("AA", "AKs"), Some(1.0));
+    /// ```
+    pub fn from_fn(f: impl Fn(&str, &str) -> f32) -> Result<Self, SolverError> {
+        let names = (0..NUM_HAND_CLASSES)
+            .map(hand_class_name)
+            .collect::<Vec<_>>();
+        let mut data = Vec::with_capacity(NUM_HAND_CLASSES * NUM_HAND_CLASSES);
+        for sb in &names {
+            for bb in &names {
+                let value = f(sb, bb);
+                if !(0.0..=1.0).contains(&value) {
+                    return Err(SolverError::InvalidArgument(format!(
+                        "Invalid value for {sb} vs {bb}: {value}"
+                    )));
+                }
+                data.push(value);
+            }
+        }
+        Ok(Self { data })
+    }
+
+    /// Estimates the all-in equities by Monte Carlo simulation with `num_samples` runouts for each
+    /// pair of classes.
+    ///
+    /// Each sample deals a random hand of each class and a random board. The equity of a class
+    /// against itself is `0.5` by symmetry. The rows of the table are computed in parallel, each
+    /// with the random number generator seeded with `seed` plus the index of the row.
+    pub fn monte_carlo(num_samples: usize, seed: u64) -> Self {
+        let hands = hands_by_class();
+        let evaluator = LookupEvaluator::new();
+
+        let rows = into_par_iter(0..NUM_HAND_CLASSES)
+            .map(|sb| {
+                let mut rng = fastrand::Rng::with_seed(seed.wrapping_add(sb as u64));
+                let mut row = vec![0.5; NUM_HAND_CLASSES];
+                for (bb, value) in row.iter_mut().enumerate().skip(sb + 1) {
+                    let mut sum = 0.0;
+                    for _ in 0..num_samples {
+                        sum += sample_share(&mut rng, evaluator, [&hands[sb], &hands[bb]]);
+                    }
+                    *value = (sum / num_samples.max(1) as f64) as f32;
+                }
+                row
+            })
+            .collect::<Vec<_>>();
+
+        let mut data = vec![0.5; NUM_HAND_CLASSES * NUM_HAND_CLASSES];
+        for (sb, row) in rows.iter().enumerate() {
+            for bb in sb + 1..NUM_HAND_CLASSES {
+                data[sb * NUM_HAND_CLASSES + bb] = row[bb];
+                data[bb * NUM_HAND_CLASSES + sb] = 1.0 - row[bb];
+            }
+        }
+
+        Self { data }
+    }
+
+    /// Same as [`monte_carlo`], but draws the seed from `rng`.
+    ///
+    /// [`monte_carlo`]: PreflopEquity::monte_carlo
+    #[inline]
+    pub fn monte_carlo_with_rng<R: RandomSource + ?Sized>(num_samples: usize, rng: &mut R) -> Self {
+        Self::monte_carlo(num_samples, rng.next_u64())
+    }
+
+    /// Returns the value of the class `sb` of the small blind against the class `bb` of the big
+    /// blind, or `None` if the names are invalid.
+    pub fn get(&self, sb: &str, bb: &str) -> Option<f32> {
+        let index = |name: &str| (0..NUM_HAND_CLASSES).find(|&i| hand_class_name(i) == name);
+        Some(self.data[index(sb)? * NUM_HAND_CLASSES + index(bb)?])
+    }
+}
+
+/// Deals a random hand of each class and a random board, and returns the share of the pot won
+/// by the first hand.
+fn sample_share(
+    rng: &mut fastrand::Rng,
+    evaluator: LookupEvaluator,
+    hands: [&[(Card, Card)]; 2],
+) -> f64 {
+    let hand1 = hands[0][rng.usize(..hands[0].len())];
+    let mut mask: u64 = (1 << hand1.0) | (1 << hand1.1);
+    let hand2 = loop {
+        let hand = hands[1][rng.usize(..hands[1].len())];
+        if mask & ((1 << hand.0) | (1 << hand.1)) == 0 {
+            break hand;
+        }
+    };
+    mask |= (1 << hand2.0) | (1 << hand2.1);
+
+    let mut board = [0; 5];
+    for card in &mut board {
+        *card = loop {
+            let card = rng.u8(0..52);
+            if mask & (1 << card) == 0 {
+                break card;
+            }
+        };
+        mask |= 1 << *card;
+    }
+
+    let strength1 = evaluator.evaluate(&board, hand1);
+    let strength2 = evaluator.evaluate(&board, hand2);
+    match strength1.cmp(&strength2) {
+        std::cmp::Ordering::Greater => 1.0,
+        std::cmp::Ordering::Equal => 0.5,
+        std::cmp::Ordering::Less => 0.0,
+    }
+}
+
+/// Configuration of a [`PreflopGame`].
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// // 2.5bb open, 3x 3-bet, 2.2x 4-bet, and all-in afterward (100bb deep)
+/// let config = PreflopConfig {
+///     small_blind: 1,
+///     big_blind: 2,
+///     effective_stack: 200,
+///     raise_sizes: vec![vec![2.5], vec![3.0], vec![2.2]],
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PreflopConfig {
+    /// Small blind. Must be greater than `0`.
+    pub small_blind: i32,
+
+    /// Big blind. Must be greater than or equal to the small blind.
+    pub big_blind: i32,
+
+    /// Effective stack including the blinds. Must be greater than the big blind.
+    pub effective_stack: i32,
+
+    /// Raise sizes of each raise level, as multiples of the amount to call: `raise_sizes[0]` for
+    /// the first raise (of the big blind), `raise_sizes[1]` for the re-raise, and so on. Each must
+    /// be greater than `1.0`.
+    ///
+    /// All-in is always available, and it is the only raise after the last level. Sizes
+    /// exceeding the effective stack are replaced with all-in.
+    pub raise_sizes: Vec<Vec<f64>>,
+}
+
+impl PreflopConfig {
+    /// Checks the configuration.
+    fn check(&self) -> Result<(), String> {
+        if self.small_blind <= 0 {
+            return Err(format!(
+                "Small blind must be positive: {}",
+                self.small_blind
+            ));
+        }
+        if self.big_blind < self.small_blind {
+            return Err(format!(
+                "Big blind must be greater than or equal to small blind: {}",
+                self.big_blind
+            ));
+        }
+        if self.effective_stack <= self.big_blind {
+            return Err(format!(
+                "Effective stack must be greater than big blind: {}",
+                self.effective_stack
+            ));
+        }
+        for &size in self.raise_sizes.iter().flatten() {
+            if !(size.is_finite() && size > 1.0) {
+                return Err(format!("Raise size must be greater than 1.0: {size}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A struct representing a node in a preflop game tree.
+pub struct PreflopNode {
+    player: u8,
+    amounts: [i32; 2],
+    actions: Vec<Action>,
+    children: Vec<MutexLike<Self>>,
+    strategy: Vec<f32>,
+    storage: Vec<f32>,
+}
+
+/// A heads-up preflop game: the small blind (player `0`) acts first, and the hands reaching the
+/// flop are valued by a [`PreflopEquity`] table.
+///
+/// The game is solved by [`solve`] like a [`PostFlopGame`]. The nodes are specified by the lines
+/// of action indices from the root (see [`available_actions`]).
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let config = PreflopConfig {
+///     small_blind: 1,
+///     big_blind: 2,
+///     effective_stack: 20,
+///     raise_sizes: Vec::new(), // push or fold
+/// };
+/// let equity = PreflopEquity::monte_carlo(20, 0);
+/// let ranges = [Range::ones(), Range::ones()];
+/// let mut game = PreflopGame::new(config, ranges, equity).unwrap();
+/// assert_eq!(
+///     game.available_actions(&[]).unwrap(),
+///     vec![Action::Fold, Action::Call, Action::AllIn(20)]
+/// );
+///
+/// solve(&mut game, 10, 0.0, false);
+/// let shove_range = game.range(&[2], 0).unwrap();
+/// assert!(shove_range.get_weight_pair(12) > 0.0); // AA
+/// ```
+///
+/// [`solve`]: crate::solve
+/// [`PostFlopGame`]: crate::PostFlopGame
+/// [`available_actions`]: PreflopGame::available_actions
+pub struct PreflopGame {
+    config: PreflopConfig,
+    root: Box<MutexLike<PreflopNode>>,
+    private_cards: [Vec<(Card, Card)>; 2],
+    initial_weights: [Vec<f32>; 2],
+    hand_classes: [Vec<u8>; 2],
+    hands_by_card: [Vec<Vec<u16>>; 2],
+    same_hand_index: [Vec<u16>; 2],
+    equity: [Vec<f32>; 2],
+    num_combinations: f64,
+    is_solved: bool,
+}
+
+impl PreflopGame {
+    /// Creates a new preflop game with the initial ranges of the small blind and the big blind.
+    ///
+    /// Returns [`SolverError::InvalidTreeConfig`] if `config` is invalid, and
+    /// [`SolverError::InvalidCardConfig`] if no valid card assignment exists.
+    pub fn new(
+        config: PreflopConfig,
+        ranges: [Range; 2],
+        equity: PreflopEquity,
+    ) -> Result<Self, SolverError> {
+        config.check().map_err(SolverError::InvalidTreeConfig)?;
+
+        let private_cards = [0, 1].map(|player| ranges[player].get_hands_weights(0).0);
+        let initial_weights = [0, 1].map(|player| ranges[player].get_hands_weights(0).1);
+
+        let mut num_combinations = 0.0;
+        for (&(c1, c2), &w1) in private_cards[0].iter().zip(&initial_weights[0]) {
+            let sb_mask: u64 = (1 << c1) | (1 << c2);
+            for (&(c3, c4), &w2) in private_cards[1].iter().zip(&initial_weights[1]) {
+                if sb_mask & ((1 << c3) | (1 << c4)) == 0 {
+                    num_combinations += w1 as f64 * w2 as f64;
+                }
+            }
+        }
+        if num_combinations == 0.0 {
+            return Err(SolverError::InvalidCardConfig(
+                "Valid card assignment does not exist".to_owned(),
+            ));
+        }
+
+        let hand_classes = [0, 1].map(|player| {
+            let cards = &private_cards[player];
+            cards
+                .iter()
+                .map(|&hand| hand_class_index(hand) as u8)
+                .collect()
+        });
+
+        let hands_by_card = [0, 1].map(|player| {
+            let mut ret = vec![Vec::new(); 52];
+            for (i, &(c1, c2)) in private_cards[player].iter().enumerate() {
+                ret[c1 as usize].push(i as u16);
+                ret[c2 as usize].push(i as u16);
+            }
+            ret
+        });
+
+        let same_hand_index = [0, 1].map(|player| {
+            let opponent_cards = &private_cards[player ^ 1];
+            let cards = &private_cards[player];
+            cards
+                .iter()
+                .map(|hand| {
+                    opponent_cards
+                        .binary_search(hand)
+                        .map_or(u16::MAX, |i| i as u16)
+                })
+                .collect()
+        });
+
+        // values from the point of view of each player
+        let n = NUM_HAND_CLASSES;
+        let equity_bb = (0..n * n)
+            .map(|i| 1.0 - equity.data[(i % n) * n + i / n])
+            .collect();
+        let equity = [equity.data, equity_bb];
+
+        let mut root = PreflopNode {
+            player: PLAYER_OOP,
+            amounts: [config.small_blind, config.big_blind],
+            actions: Vec::new(),
+            children: Vec::new(),
+            strategy: Vec::new(),
+            storage: Vec::new(),
+        };
+        let num_hands = [private_cards[0].len(), private_cards[1].len()];
+        Self::build_tree_recursive(&mut root, &config, num_hands, 0, [false; 2]);
+
+        Ok(Self {
+            config,
+            root: Box::new(MutexLike::new(root)),
+            private_cards,
+            initial_weights,
+            hand_classes,
+            hands_by_card,
+            same_hand_index,
+            equity,
+            num_combinations,
+            is_solved: false,
+        })
+    }
+
+    /// Returns the configuration.
+    #[inline]
+    pub fn config(&self) -> &PreflopConfig {
+        &self.config
+    }
+
+    /// Returns the private hands of `player` (`0`: small blind, `1`: big blind).
+    #[inline]
+    pub fn private_cards(&self, player: usize) -> &[(Card, Card)] {
+        &self.private_cards[player]
+    }
+
+    /// Returns the available actions at the node of `line`.
+    ///
+    /// This method and the other methods taking `line` return [`SolverError::InvalidLine`] if the
+    /// line is invalid or, except for [`range`], reaches a terminal node.
+    ///
+    /// [`range`]: PreflopGame::range
+    pub fn available_actions(&self, line: &[usize]) -> Result<Vec<Action>, SolverError> {
+        self.with_node(line, |node| node.actions.clone())
+    }
+
+    /// Returns the player to act at the node of `line`.
+    pub fn current_player(&self, line: &[usize]) -> Result<usize, SolverError> {
+        self.with_node(line, |node| node.player as usize)
+    }
+
+    /// Returns the average strategy at the node of `line`.
+    ///
+    /// The return value is a vector of the length of `#(actions) * #(private hands)` of the
+    /// acting player, as in [`PostFlopGame::strategy`].
+    ///
+    /// [`PostFlopGame::strategy`]: crate::PostFlopGame::strategy
+    pub fn strategy(&self, line: &[usize]) -> Result<Vec<f32>, SolverError> {
+        self.with_node(line, |node| {
+            normalized_strategy(&node.strategy, node.num_actions())
+        })
+    }
+
+    /// Returns the range of `player` reaching the node of `line`: the initial weights multiplied
+    /// by the probabilities of the actions of `player` on the line.
+    ///
+    /// The node may be terminal, so the range of the flop can be obtained from the line ending
+    /// with a call. Returns [`SolverError::InvalidArgument`] if `player` is invalid.
+    pub fn range(&self, line: &[usize], player: usize) -> Result<Range, SolverError> {
+        if player > 1 {
+            return Err(SolverError::InvalidArgument(format!(
+                "Invalid player: {player}"
+            )));
+        }
+
+        let mut weights = self.initial_weights[player].clone();
+        let mut prefix = Vec::new();
+        let visit = &mut |node: &PreflopNode, action: usize| {
+            prefix.push(node.actions[action]);
+            if node.player as usize == player {
+                let strategy = normalized_strategy(&node.strategy, node.num_actions());
+                let num_hands = weights.len();
+                let row = &strategy[action * num_hands..(action + 1) * num_hands];
+                weights.iter_mut().zip(row).for_each(|(w, &s)| *w *= s);
+            }
+        };
+        if walk_line(&self.root.lock(), line, visit, |_| ()).is_none() {
+            return Err(invalid_line(prefix, line));
+        }
+
+        Range::from_hands_weights(&self.private_cards[player], &weights)
+    }
+
+    /// Calls `f` with the non-terminal node of `line`.
+    fn with_node<T>(
+        &self,
+        line: &[usize],
+        f: impl FnOnce(&PreflopNode) -> T,
+    ) -> Result<T, SolverError> {
+        let mut prefix = Vec::new();
+        let visit = &mut |node: &PreflopNode, action: usize| prefix.push(node.actions[action]);
+        let result = walk_line(&self.root.lock(), line, visit, |node| {
+            (!node.is_terminal()).then(|| f(node))
+        });
+        match result {
+            Some(Some(value)) => Ok(value),
+            Some(None) => Err(SolverError::InvalidLine {
+                line: prefix,
+                message: format!("Terminal node: {line:?}"),
+            }),
+            None => Err(invalid_line(prefix, line)),
+        }
+    }
+
+    fn build_tree_recursive(
+        node: &mut PreflopNode,
+        config: &PreflopConfig,
+        num_hands: [usize; 2],
+        num_raises: usize,
+        has_acted: [bool; 2],
+    ) {
+        let player = node.player as usize;
+        let amount_to_call = node.amounts[player ^ 1];
+        let stack = config.effective_stack;
+
+        let mut actions = Vec::new();
+        if node.amounts[player] < amount_to_call {
+            actions.push(Action::Fold);
+            actions.push(Action::Call);
+        } else {
+            actions.push(Action::Check);
+        }
+        if amount_to_call < stack {
+            if let Some(sizes) = config.raise_sizes.get(num_raises) {
+                let mut amounts = sizes
+                    .iter()
+                    .map(|&size| (amount_to_call as f64 * size).round() as i32)
+                    .filter(|&amount| amount_to_call < amount && amount < stack)
+                    .collect::<Vec<_>>();
+                amounts.sort_unstable();
+                amounts.dedup();
+                actions.extend(amounts.into_iter().map(Action::Raise));
+            }
+            actions.push(Action::AllIn(stack));
+        }
+
+        let mut has_acted = has_acted;
+        has_acted[player] = true;
+
+        for &action in &actions {
+            let mut amounts = node.amounts;
+            let mut raised = false;
+            let next_player = match action {
+                Action::Fold => PLAYER_FOLD_FLAG | player as u8,
+                Action::Check | Action::Call => {
+                    amounts[player] = amount_to_call;
+                    if has_acted[player ^ 1] {
+                        PLAYER_TERMINAL_FLAG
+                    } else {
+                        player as u8 ^ 1
+                    }
+                }
+                Action::Raise(amount) | Action::AllIn(amount) => {
+                    amounts[player] = amount;
+                    raised = true;
+                    player as u8 ^ 1
+                }
+                _ => unreachable!(),
+            };
+
+            let mut child = PreflopNode {
+                player: next_player,
+                amounts,
+                actions: Vec::new(),
+                children: Vec::new(),
+                strategy: Vec::new(),
+                storage: Vec::new(),
+            };
+            if !child.is_terminal() {
+                let num_raises = num_raises + raised as usize;
+                Self::build_tree_recursive(&mut child, config, num_hands, num_raises, has_acted);
+            }
+            node.children.push(MutexLike::new(child));
+        }
+
+        let num_elements = actions.len() * num_hands[player];
+        node.actions = actions;
+        node.strategy = vec![0.0; num_elements];
+        node.storage = vec![0.0; num_elements];
+    }
+}
+
+/// Returns the error for an invalid `line`, whose valid part consists of the actions of `prefix`.
+fn invalid_line(prefix: Vec<Action>, line: &[usize]) -> SolverError {
+    SolverError::InvalidLine {
+        line: prefix,
+        message: format!("Invalid line: {line:?}"),
+    }
+}
+
+/// Follows `line` from `node`, calling `visit` with each node on the way and the action taken,
+/// and returns the result of `f` for the reached node, or `None` if the line is invalid.
+fn walk_line<T>(
+    node: &PreflopNode,
+    line: &[usize],
+    visit: &mut impl FnMut(&PreflopNode, usize),
+    f: impl FnOnce(&PreflopNode) -> T,
+) -> Option<T> {
+    match line.split_first() {
+        None => Some(f(node)),
+        Some((&action, rest)) => {
+            if node.is_terminal() || action >= node.num_actions() {
+                return None;
+            }
+            visit(node, action);
+            walk_line(&node.children[action].lock(), rest, visit, f)
+        }
+    }
+}
+
+impl Game for PreflopGame {
+    type Node = PreflopNode;
+
+    #[inline]
+    fn root(&self) -> MutexGuardLike<'_, Self::Node> {
+        self.root.lock()
+    }
+
+    #[inline]
+    fn num_private_hands(&self, player: usize) -> usize {
+        self.private_cards[player].len()
+    }
+
+    #[inline]
+    fn initial_weights(&self, player: usize) -> &[f32] {
+        &self.initial_weights[player]
+    }
+
+    fn evaluate(
+        &self,
+        result: &mut [MaybeUninit<f32>],
+        node: &Self::Node,
+        player: usize,
+        cfreach: &[f32],
+    ) {
+        let opponent = player ^ 1;
+        let opponent_cards = &self.private_cards[opponent];
+        let hands_by_card = &self.hands_by_card[opponent];
+        let same_hand_index = &self.same_hand_index[player];
+
+        let mut cfreach_sum = 0.0;
+        let mut cfreach_by_card = [0.0; 52];
+        for (&(c1, c2), &w) in opponent_cards.iter().zip(cfreach) {
+            let w = w as f64;
+            cfreach_sum += w;
+            cfreach_by_card[c1 as usize] += w;
+            cfreach_by_card[c2 as usize] += w;
+        }
+
+        // fold
+        if node.player & PLAYER_FOLD_FLAG == PLAYER_FOLD_FLAG {
+            let folded_player = (node.player & PLAYER_MASK) as usize;
+            let amount = node.amounts[folded_player] as f64 / self.num_combinations;
+            let payoff = if folded_player == player {
+                -amount
+            } else {
+                amount
+            };
+
+            for (i, (r, &(c1, c2))) in result
+                .iter_mut()
+                .zip(&self.private_cards[player])
+                .enumerate()
+            {
+                let mut reach = cfreach_sum - cfreach_by_card[c1 as usize];
+                reach -= cfreach_by_card[c2 as usize];
+                if let Some(&w) = cfreach.get(same_hand_index[i] as usize) {
+                    reach += w as f64;
+                }
+                r.write((payoff * reach) as f32);
+            }
+        }
+        // flop
+        else {
+            let amount = node.amounts[player] as f64 / self.num_combinations;
+            let equity = &self.equity[player];
+            let opponent_classes = &self.hand_classes[opponent];
+
+            let mut cfreach_by_class = [0.0; NUM_HAND_CLASSES];
+            for (&class, &w) in opponent_classes.iter().zip(cfreach) {
+                cfreach_by_class[class as usize] += w as f64;
+            }
+
+            for (i, (r, &(c1, c2))) in result
+                .iter_mut()
+                .zip(&self.private_cards[player])
+                .enumerate()
+            {
+                let class = self.hand_classes[player][i] as usize;
+                let row = &equity[class * NUM_HAND_CLASSES..(class + 1) * NUM_HAND_CLASSES];
+                let mut share = row
+                    .iter()
+                    .zip(&cfreach_by_class)
+                    .map(|(&e, &w)| e as f64 * w)
+                    .sum::<f64>();
+                let mut reach = cfreach_sum - cfreach_by_card[c1 as usize];
+                reach -= cfreach_by_card[c2 as usize];
+
+                // excludes the hands conflicting with the hand (inclusion-exclusion principle)
+                for card in [c1, c2] {
+                    for &j in &hands_by_card[card as usize] {
+                        let e = row[opponent_classes[j as usize] as usize] as f64;
+                        share -= e * cfreach[j as usize] as f64;
+                    }
+                }
+                let j = same_hand_index[i] as usize;
+                if let Some(&w) = cfreach.get(j) {
+                    share += row[opponent_classes[j] as usize] as f64 * w as f64;
+                    reach += w as f64;
+                }
+
+                r.write((amount * (2.0 * share - reach)) as f32);
+            }
+        }
+    }
+
+    #[inline]
+    fn chance_factor(&self, _node: &Self::Node) -> usize {
+        unreachable!()
+    }
+
+    #[inline]
+    fn is_solved(&self) -> bool {
+        self.is_solved
+    }
+
+    #[inline]
+    fn set_solved(&mut self) {
+        self.is_solved = true;
+    }
+}
+
+impl GameNode for PreflopNode {
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        self.player & PLAYER_TERMINAL_FLAG != 0
+    }
+
+    #[inline]
+    fn is_chance(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn player(&self) -> usize {
+        self.player as usize
+    }
+
+    #[inline]
+    fn num_actions(&self) -> usize {
+        self.children.len()
+    }
+
+    #[inline]
+    fn play(&self, action: usize) -> MutexGuardLike<'_, Self> {
+        self.children[action].lock()
+    }
+
+    #[inline]
+    fn strategy(&self) -> &[f32] {
+        &self.strategy
+    }
+
+    #[inline]
+    fn strategy_mut(&mut self) -> &mut [f32] {
+        &mut self.strategy
+    }
+
+    #[inline]
+    fn regrets(&self) -> &[f32] {
+        &self.storage
+    }
+
+    #[inline]
+    fn regrets_mut(&mut self) -> &mut [f32] {
+        &mut self.storage
+    }
+
+    #[inline]
+    fn cfvalues(&self) -> &[f32] {
+        &self.storage
+    }
+
+    #[inline]
+    fn cfvalues_mut(&mut self) -> &mut [f32] {
+        &mut self.storage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::*;
+
+    #[test]
+    fn preflop_equity() {
+        for index in 0..NUM_HAND_CLASSES {
+            let name = hand_class_name(index);
+            let cards = cards_from_str(&format!(
+                "{}c{}{}",
+                &name[..1],
+                &name[1..2],
+                if name.ends_with('s') { 'c' } else { 'd' }
+            ))
+            .unwrap();
+            assert_eq!(hand_class_index((cards[0], cards[1])), index);
+        }
+
+        let equity = PreflopEquity::monte_carlo(1000, 0);
+        let aces_vs_seven_deuce = equity.get("AA", "72o").unwrap();
+        assert!(0.8 < aces_vs_seven_deuce && aces_vs_seven_deuce < 0.95);
+        assert_eq!(equity.get("KQs", "KQs"), Some(0.5));
+        let reverse = equity.get("72o", "AA").unwrap();
+        assert!((aces_vs_seven_deuce + reverse - 1.0).abs() < 1e-6);
+        assert_eq!(equity.get("AAs", "KK"), None);
+        assert!(matches!(
+            PreflopEquity::from_fn(|_, _| 1.5),
+            Err(SolverError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn preflop_game() {
+        let config = PreflopConfig {
+            small_blind: 1,
+            big_blind: 2,
+            effective_stack: 20,
+            raise_sizes: vec![vec![2.5]],
+        };
+        let equity = PreflopEquity::monte_carlo(200, 0);
+        let ranges = [Range::ones(), Range::ones()];
+        let mut game = PreflopGame::new(config, ranges, equity).unwrap();
+        assert_eq!(
+            game.available_actions(&[]).unwrap(),
+            vec![
+                Action::Fold,
+                Action::Call,
+                Action::Raise(5),
+                Action::AllIn(20)
+            ]
+        );
+        assert_eq!(
+            game.available_actions(&[1]).unwrap(),
+            vec![Action::Check, Action::Raise(5), Action::AllIn(20)]
+        );
+        assert_eq!(game.current_player(&[2]), Ok(1));
+        let error = game.available_actions(&[0]).unwrap_err();
+        match error {
+            SolverError::InvalidLine { line, .. } => assert_eq!(line, [Action::Fold]),
+            _ => panic!("unexpected error: {error:?}"),
+        }
+
+        let exploitability = solve(&mut game, 300, 0.01, false);
+        assert!(exploitability < 0.02);
+
+        // AA never folds
+        let strategy = game.strategy(&[]).unwrap();
+        let cards = game.private_cards(0);
+        let aces = cards.iter().position(|&hand| hand == (48, 49)).unwrap();
+        assert!(strategy[aces] < 0.01);
+
+        // the big blind does not act before the limp
+        let range = game.range(&[1, 0], 0).unwrap();
+        assert_eq!(range, game.range(&[1], 0).unwrap());
+        let error = game.range(&[1, 0, 0], 1).unwrap_err();
+        match error {
+            SolverError::InvalidLine { line, .. } => {
+                assert_eq!(line, [Action::Call, Action::Check]);
+            }
+            _ => panic!("unexpected error: {error:?}"),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use crate::action_tree::BoardState;
+
+#[cfg(feature = "profiling")]
+use std::fmt;
+
+#[cfg(feature = "profiling")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "profiling")]
+use std::time::{Duration, Instant};
+
+/// Phases of the computation measured by the profiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Phase {
+    TreeBuild,
+    Iteration,
+    FlopEvaluation,
+    TurnEvaluation,
+    RiverEvaluation,
+    RegretUpdate,
+    StrategySum,
+    Finalize,
+}
+
+impl Phase {
+    /// Returns the phase of evaluating the terminal nodes of `street`.
+    #[inline]
+    pub(crate) fn evaluation(street: BoardState) -> Self {
+        match street {
+            BoardState::Flop => Self::FlopEvaluation,
+            BoardState::Turn => Self::TurnEvaluation,
+            BoardState::River => Self::RiverEvaluation,
+        }
+    }
+}
+
+#[cfg(feature = "profiling")]
+const NUM_PHASES: usize = Phase::Finalize as usize + 1;
+
+#[cfg(feature = "profiling")]
+static PHASE_NANOS: [AtomicU64; NUM_PHASES] = [const { AtomicU64::new(0) }; NUM_PHASES];
+
+#[cfg(feature = "profiling")]
+static NUM_ITERATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// A guard that adds the elapsed time to the phase when dropped.
+///
+/// Without the `profiling` feature, the guard is zero-sized and does nothing.
+pub(crate) struct PhaseTimer {
+    #[cfg(feature = "profiling")]
+    phase: Phase,
+    #[cfg(feature = "profiling")]
+    start: Instant,
+}
+
+impl PhaseTimer {
+    #[cfg(feature = "profiling")]
+    #[inline]
+    pub(crate) fn start(phase: Phase) -> Self {
+        Self {
+            phase,
+            start: Instant::now(),
+        }
+    }
+
+    #[cfg(not(feature = "profiling"))]
+    #[inline]
+    pub(crate) fn start(_phase: Phase) -> Self {
+        Self {}
+    }
+
+    /// Stops the timer before the end of the scope.
+    #[inline]
+    pub(crate) fn stop(self) {}
+}
+
+#[cfg(feature = "profiling")]
+impl Drop for PhaseTimer {
+    #[inline]
+    fn drop(&mut self) {
+        let nanos = self.start.elapsed().as_nanos() as u64;
+        PHASE_NANOS[self.phase as usize].fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+/// Counts a completed iteration of the solver.
+#[inline]
+pub(crate) fn count_iteration() {
+    #[cfg(feature = "profiling")]
+    NUM_ITERATIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Time spent in each phase of the computation, returned by [`profiling_report`].
+///
+/// The counters are global to the process: they accumulate over all games since the start of the
+/// program or the last call of [`reset_profiling`]. Phases that run on multiple threads
+/// (`evaluation`, `regret_update`, and `strategy_sum`) are summed over the threads, so they can
+/// exceed the wall-clock time of `iterations` when multithreading is enabled.
+#[cfg(feature = "profiling")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfilingReport {
+    /// Wall-clock time spent building the action trees and the game trees.
+    pub tree_build: Duration,
+
+    /// Wall-clock time spent in the iterations of the solver.
+    pub iterations: Duration,
+
+    /// Number of iterations performed.
+    pub num_iterations: u64,
+
+    /// Time spent evaluating the terminal nodes of each street (flop, turn, river), which accounts
+    /// for most of the traversal.
+    pub evaluation: [Duration; 3],
+
+    /// Time spent updating the cumulative regrets.
+    pub regret_update: Duration,
+
+    /// Time spent updating the cumulative strategies.
+    pub strategy_sum: Duration,
+
+    /// Wall-clock time spent finalizing the games.
+    pub finalize: Duration,
+}
+
+#[cfg(feature = "profiling")]
+impl fmt::Display for ProfilingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        writeln!(f, "tree build:        {:10.2} ms", ms(self.tree_build))?;
+        write!(f, "iterations:        {:10.2} ms", ms(self.iterations))?;
+        writeln!(f, " ({} iterations)", self.num_iterations)?;
+        let streets = ["flop", "turn", "river"];
+        for (street, &duration) in streets.iter().zip(&self.evaluation) {
+            writeln!(f, "  {street:<5} evaluation: {:10.2} ms", ms(duration))?;
+        }
+        writeln!(f, "  regret update:   {:10.2} ms", ms(self.regret_update))?;
+        writeln!(f, "  strategy sum:    {:10.2} ms", ms(self.strategy_sum))?;
+        write!(f, "finalize:          {:10.2} ms", ms(self.finalize))
+    }
+}
+
+/// Returns the time spent in each phase of the computation so far.
+#[cfg(feature = "profiling")]
+pub fn profiling_report() -> ProfilingReport {
+    let get =
+        |phase: Phase| Duration::from_nanos(PHASE_NANOS[phase as usize].load(Ordering::Relaxed));
+    ProfilingReport {
+        tree_build: get(Phase::TreeBuild),
+        iterations: get(Phase::Iteration),
+        num_iterations: NUM_ITERATIONS.load(Ordering::Relaxed),
+        evaluation: [
+            get(Phase::FlopEvaluation),
+            get(Phase::TurnEvaluation),
+            get(Phase::RiverEvaluation),
+        ],
+        regret_update: get(Phase::RegretUpdate),
+        strategy_sum: get(Phase::StrategySum),
+        finalize: get(Phase::Finalize),
+    }
+}
+
+/// Resets the profiling counters.
+#[cfg(feature = "profiling")]
+pub fn reset_profiling() {
+    for nanos in &PHASE_NANOS {
+        nanos.store(0, Ordering::Relaxed);
+    }
+    NUM_ITERATIONS.store(0, Ordering::Relaxed);
+}
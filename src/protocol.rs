@@ -0,0 +1,447 @@
+use crate::action_tree::*;
+use crate::bet_size::*;
+use crate::card::*;
+use crate::error::*;
+use crate::game::*;
+use crate::interface::*;
+use crate::range::*;
+use crate::solver::*;
+use crate::utility::*;
+use std::fmt::Write as _;
+use std::io::{BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, ScopedJoinHandle};
+
+struct Session {
+    ranges: [String; 2],
+    board: String,
+    tree_config: TreeConfig,
+    game: Option<PostFlopGame>,
+}
+
+/// Writes a response line and flushes it.
+fn write_line<W: Write>(output: &Mutex<W>, line: &str) -> std::io::Result<()> {
+    let mut output = output.lock().unwrap_or_else(|e| e.into_inner());
+    writeln!(output, "{line}").and_then(|_| output.flush())
+}
+
+/// Same as [`write_line`], but returns the error message as a `String`.
+fn send<W: Write>(output: &Mutex<W>, line: &str) -> Result<(), String> {
+    write_line(output, line).map_err(|e| format!("Failed to write: {e}"))
+}
+
+#[inline]
+fn parse_player(player: &str) -> Result<usize, String> {
+    match player {
+        "oop" => Ok(0),
+        "ip" => Ok(1),
+        _ => Err(format!("Invalid player: {player}")),
+    }
+}
+
+#[inline]
+fn parse_number<T: std::str::FromStr>(token: Option<&str>, name: &str) -> Result<T, String> {
+    let token = token.ok_or_else(|| format!("Missing {name}"))?;
+    token
+        .parse()
+        .map_err(|_| format!("Invalid {name}: {token}"))
+}
+
+impl Session {
+    fn new() -> Self {
+        Self {
+            ranges: Default::default(),
+            board: String::new(),
+            tree_config: TreeConfig::default(),
+            game: None,
+        }
+    }
+
+    /// Handles a setup command. Returns `false` if `command` is not a setup command.
+    fn setup(&mut self, command: &str, args: &[&str]) -> Result<bool, String> {
+        let config = &mut self.tree_config;
+        match (command, args) {
+            ("range", [player, range @ ..]) if !range.is_empty() => {
+                let range = range.concat();
+                range.parse::<Range>().map_err(|e| e.to_string())?;
+                self.ranges[parse_player(player)?] = range;
+            }
+            ("board", [board]) => {
+                board_from_str(board).map_err(|e| e.to_string())?;
+                (*board).clone_into(&mut self.board);
+            }
+            ("pot", [pot]) => config.starting_pot = parse_number(Some(pot), "pot")?,
+            ("stack", [stack]) => config.effective_stack = parse_number(Some(stack), "stack")?,
+            ("rake", [rate, cap]) => {
+                config.rake_rate = parse_number(Some(rate), "rake rate")?;
+                config.rake_cap = parse_number(Some(cap), "rake cap")?;
+            }
+            ("bet", [street, player, bet, raise @ ..]) if raise.len() <= 1 => {
+                let player = parse_player(player)?;
+                let raise = raise.first().copied().unwrap_or_default();
+                let sizes = BetSizeOptions::try_from((*bet, raise)).map_err(|e| e.to_string())?;
+                match *street {
+                    "flop" => config.flop_bet_sizes[player] = sizes,
+                    "turn" => config.turn_bet_sizes[player] = sizes,
+                    "river" => config.river_bet_sizes[player] = sizes,
+                    _ => return Err(format!("Invalid street: {street}")),
+                }
+            }
+            ("donk", [street, donk]) => {
+                let sizes = Some(DonkSizeOptions::try_from(*donk).map_err(|e| e.to_string())?);
+                match *street {
+                    "turn" => config.turn_donk_sizes = sizes,
+                    "river" => config.river_donk_sizes = sizes,
+                    _ => return Err(format!("Invalid street: {street}")),
+                }
+            }
+            ("range" | "board" | "pot" | "stack" | "rake" | "bet" | "donk", _) => {
+                return Err(format!("Invalid arguments of {command}"));
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Builds the game from the current setup.
+    fn build(&self) -> Result<PostFlopGame, String> {
+        if self.ranges.iter().any(String::is_empty) || self.board.is_empty() {
+            return Err("Ranges and board must be set".to_owned());
+        }
+
+        let board = board_from_str(&self.board).map_err(|e| e.to_string())?;
+        let card_config = CardConfig {
+            range: [
+                self.ranges[0].parse::<Range>().map_err(|e| e.to_string())?,
+                self.ranges[1].parse::<Range>().map_err(|e| e.to_string())?,
+            ],
+            flop: [board[0], board[1], board[2]],
+            turn: board.get(3).copied().unwrap_or(NOT_DEALT),
+            river: board.get(4).copied().unwrap_or(NOT_DEALT),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: match board.len() {
+                3 => BoardState::Flop,
+                4 => BoardState::Turn,
+                _ => BoardState::River,
+            },
+            ..self.tree_config.clone()
+        };
+
+        let action_tree = ActionTree::new(tree_config).map_err(|e| e.to_string())?;
+        PostFlopGame::with_config(card_config, action_tree).map_err(|e| e.to_string())
+    }
+}
+
+/// Parses the arguments of `go` into the maximum number of iterations and the target
+/// exploitability (defaulting to 1000 iterations and 0.5% of the pot).
+fn parse_go(args: &[&str], starting_pot: i32) -> Result<(u32, f32), String> {
+    let mut max_num_iterations = 1000;
+    let mut target_exploitability = starting_pot as f32 * 0.005;
+    let mut args = args.iter();
+    while let Some(&key) = args.next() {
+        let value = args.next().copied();
+        match key {
+            "iterations" => max_num_iterations = parse_number(value, "iterations")?,
+            "exploitability" => target_exploitability = parse_number(value, "exploitability")?,
+            _ => return Err(format!("Invalid argument of go: {key}")),
+        }
+    }
+    Ok((max_num_iterations, target_exploitability))
+}
+
+/// Solves the game until the stopping condition is met or `stop` is set, reporting the progress.
+fn solve_with_progress<W: Write>(
+    mut game: PostFlopGame,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    stop: &AtomicBool,
+    output: &Mutex<W>,
+) -> PostFlopGame {
+    game.allocate_memory(false);
+
+    let mut exploitability = compute_exploitability(&game);
+    let mut num_iterations = 0;
+    while num_iterations < max_num_iterations
+        && exploitability > target_exploitability
+        && !stop.load(Ordering::Relaxed)
+    {
+        solve_step(&game, num_iterations);
+        num_iterations += 1;
+
+        if num_iterations % 10 == 0 {
+            exploitability = compute_exploitability(&game);
+            let line = format!("info iteration {num_iterations} exploitability {exploitability}");
+            send(output, &line).ok();
+        }
+    }
+
+    if num_iterations % 10 != 0 {
+        exploitability = compute_exploitability(&game);
+    }
+    game.set_solve_result(num_iterations, exploitability);
+    finalize(&mut game);
+
+    let line = format!("solved iteration {num_iterations} exploitability {exploitability}");
+    send(output, &line).ok();
+    game
+}
+
+/// Writes the node reached by `history` from the root node:
+///
+/// - `node player <0|1>`, `node chance`, or `node terminal`.
+/// - `actions <action>...` (player nodes): the actions in the format of [`line_to_string`].
+/// - `cards <card>...` (chance nodes): the cards that can be dealt.
+/// - `hand <hand> <weight> <equity> <ev> <strategy>...` (player nodes): one line per private hand
+///   of the player to act, with the probability of each action.
+fn write_node<W: Write>(
+    game: &mut PostFlopGame,
+    history: &[usize],
+    output: &Mutex<W>,
+) -> Result<(), String> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| game.apply_history(history)));
+    if result.is_err() {
+        return Err("Invalid history".to_owned());
+    }
+    game.cache_normalized_weights();
+
+    if game.is_terminal_node() {
+        return send(output, "node terminal");
+    }
+
+    if game.is_chance_node() {
+        let mask = game.possible_cards();
+        let cards = (0..52)
+            .filter(|&card| mask & (1 << card) != 0)
+            .map(|card| card_to_string(card).unwrap())
+            .collect::<Vec<_>>();
+        send(output, "node chance")?;
+        return send(output, &format!("cards {}", cards.join(" ")));
+    }
+
+    let player = game.current_player();
+    let actions = game
+        .available_actions()
+        .iter()
+        .map(|&action| line_to_string(&[action]))
+        .collect::<Vec<_>>();
+    send(output, &format!("node player {player}"))?;
+    send(output, &format!("actions {}", actions.join(" ")))?;
+
+    let hands = holes_to_strings(game.private_cards(player)).map_err(|e| e.to_string())?;
+    let weights = game.weights(player);
+    let equity = game.equity(player);
+    let ev = game.expected_values(player);
+    let strategy = game.strategy();
+    for (i, hand) in hands.iter().enumerate() {
+        let mut line = format!("hand {hand} {} {} {}", weights[i], equity[i], ev[i]);
+        for action in 0..actions.len() {
+            write!(line, " {}", strategy[action * hands.len() + i]).unwrap();
+        }
+        send(output, &line)?;
+    }
+
+    Ok(())
+}
+
+/// Runs a line-oriented text protocol that drives the solver, reading commands from `input` and
+/// writing responses to `output` until `quit` or the end of the input.
+///
+/// This allows GUIs to use the solver as a subprocess (e.g., on the standard input and output)
+/// without linking to it, in the manner of the UCI protocol of chess engines. The solving runs on a
+/// background thread, so `stop` and `isready` are handled while solving.
+///
+/// The commands are as follows; the tokens are separated by whitespace, and errors are replied as
+/// `error <message>`:
+///
+/// - `range <oop|ip> <range>`, `board <cards>`, `pot <amount>`, `stack <amount>`,
+///   `rake <rate> <cap>`, `bet <flop|turn|river> <oop|ip> <bet sizes> [<raise sizes>]`, and
+///   `donk <turn|river> <sizes>`: set up the game. The sizes are comma-separated as in
+///   [`BetSizeOptions`], and the initial street is determined by the board.
+/// - `go [iterations <n>] [exploitability <x>]`: builds and solves the game (by default, 1000
+///   iterations and 0.5% of the pot). Replies `info iteration <n> exploitability <x>` every 10
+///   iterations and `solved iteration <n> exploitability <x>` at the end.
+/// - `stop`: stops the solving early and finalizes the current strategy.
+/// - `wait`: waits until the solving finishes.
+/// - `isready`: replies `readyok`.
+/// - `query [<action index>...]`: replies the node reached by the actions from the root node as
+///   `node player <0|1>` (or `node chance` / `node terminal`), `actions <action>...` in the format
+///   of [`line_to_string`] (or `cards <card>...` at chance nodes), and
+///   `hand <hand> <weight> <equity> <ev> <strategy>...` for each private hand of the player to act,
+///   followed by `end`.
+/// - `quit`: stops the solving and exits.
+///
+/// Returns [`SolverError::Io`] only if reading or writing fails.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let input = "range oop 66+\nrange ip 22+\nboard Td9d6h4s2c\npot 100\nstack 100\n\
+///              bet river oop 50%\ngo iterations 10\nwait\nquery\nquit\n";
+/// let mut output = Vec::new();
+/// run_text_protocol(input.as_bytes(), &mut output).unwrap();
+///
+/// let output = String::from_utf8(output).unwrap();
+/// assert!(output.contains("solved iteration 10"));
+/// assert!(output.contains("actions X B50"));
+/// ```
+pub fn run_text_protocol<R: BufRead, W: Write + Send>(
+    input: R,
+    output: W,
+) -> Result<(), SolverError> {
+    let output = Mutex::new(output);
+    let stop = AtomicBool::new(false);
+    let mut session = Session::new();
+
+    thread::scope(|scope| {
+        let mut solving: Option<ScopedJoinHandle<'_, PostFlopGame>> = None;
+        let join = |solving: &mut Option<ScopedJoinHandle<'_, PostFlopGame>>| {
+            solving.take().map(|handle| handle.join().unwrap())
+        };
+
+        for line in input.lines() {
+            let line = line.map_err(|e| SolverError::io("Failed to read", &e))?;
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            let Some((&command, args)) = tokens.split_first() else {
+                continue;
+            };
+
+            if solving.as_ref().is_some_and(|handle| handle.is_finished()) {
+                session.game = join(&mut solving);
+            }
+
+            let result = match command {
+                "isready" => send(&output, "readyok"),
+                "stop" | "wait" | "quit" => {
+                    if command != "wait" {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                    if let Some(game) = join(&mut solving) {
+                        session.game = Some(game);
+                    }
+                    if command == "quit" {
+                        break;
+                    }
+                    Ok(())
+                }
+                _ if solving.is_some() => Err("Solving is in progress".to_owned()),
+                "go" => {
+                    let starting_pot = session.tree_config.starting_pot;
+                    parse_go(args, starting_pot).and_then(|(max, target)| {
+                        let game = session.build()?;
+                        session.game = None;
+                        stop.store(false, Ordering::Relaxed);
+                        let (stop, output) = (&stop, &output);
+                        solving =
+                            Some(scope.spawn(move || {
+                                solve_with_progress(game, max, target, stop, output)
+                            }));
+                        Ok(())
+                    })
+                }
+                "query" => match session.game.as_mut() {
+                    Some(game) => args
+                        .iter()
+                        .map(|arg| parse_number(Some(arg), "action index"))
+                        .collect::<Result<Vec<_>, _>>()
+                        .and_then(|history| write_node(game, &history, &output))
+                        .and_then(|_| send(&output, "end")),
+                    None => Err("Game is not solved".to_owned()),
+                },
+                _ => match session.setup(command, args) {
+                    Ok(true) => Ok(()),
+                    Ok(false) => Err(format!("Unknown command: {command}")),
+                    Err(message) => Err(message),
+                },
+            };
+
+            if let Err(message) = result {
+                write_line(&output, &format!("error {message}"))
+                    .map_err(|e| SolverError::io("Failed to write", &e))?;
+            }
+        }
+
+        stop.store(true, Ordering::Relaxed);
+        join(&mut solving);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_protocol() {
+        let input = "\
+            go\n\
+            range oop 66+,A8s+\n\
+            range ip 22+\n\
+            range btn 22+\n\
+            board Td9d6h4s2c\n\
+            pot 100\n\
+            stack 200\n\
+            bet river oop 50%\n\
+            bet river ip 50% 2x\n\
+            go iterations 20 exploitability 0\n\
+            isready\n\
+            wait\n\
+            query\n\
+            query 1\n\
+            query 9\n\
+            unknown\n\
+            quit\n\
+            query\n";
+
+        let mut output = Vec::new();
+        run_text_protocol(input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "error Ranges and board must be set");
+        assert_eq!(lines[1], "error Invalid player: btn");
+        assert!(lines.contains(&"readyok"));
+        assert!(
+            lines
+                .iter()
+                .any(|line| line.starts_with("info iteration 10 "))
+        );
+        assert!(
+            lines
+                .iter()
+                .any(|line| line.starts_with("solved iteration 20 "))
+        );
+        assert!(lines.contains(&"node player 0"));
+        assert!(lines.contains(&"actions X B50"));
+        assert!(lines.contains(&"node player 1"));
+        assert!(lines.contains(&"actions F C R100"));
+        assert!(lines.contains(&"error Invalid history"));
+        assert_eq!(lines.last(), Some(&"error Unknown command: unknown"));
+
+        let num_hands = lines
+            .iter()
+            .filter(|line| line.starts_with("hand "))
+            .count();
+        let game = Session {
+            ranges: ["66+,A8s+".to_owned(), "22+".to_owned()],
+            board: "Td9d6h4s2c".to_owned(),
+            tree_config: TreeConfig {
+                starting_pot: 100,
+                effective_stack: 200,
+                ..Default::default()
+            },
+            game: None,
+        }
+        .build()
+        .unwrap();
+        assert_eq!(
+            num_hands,
+            game.private_cards(0).len() + game.private_cards(1).len()
+        );
+    }
+}
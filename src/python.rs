@@ -0,0 +1,396 @@
+use crate::action_tree::*;
+use crate::bet_size::*;
+use crate::card::*;
+use crate::game::*;
+use crate::interface::*;
+use crate::range::*;
+use crate::solver::*;
+use crate::utility::*;
+use numpy::{PyArray1, PyArray2, PyArrayMethods, PyReadonlyArray1};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+#[inline]
+fn value_error(message: impl ToString) -> PyErr {
+    PyValueError::new_err(message.to_string())
+}
+
+#[inline]
+fn parse_street(street: &str) -> PyResult<usize> {
+    match street {
+        "flop" => Ok(0),
+        "turn" => Ok(1),
+        "river" => Ok(2),
+        _ => Err(value_error(format!("Invalid street: {street}"))),
+    }
+}
+
+#[inline]
+fn parse_player(player: usize) -> PyResult<usize> {
+    match player {
+        0 | 1 => Ok(player),
+        _ => Err(value_error(format!("Invalid player: {player}"))),
+    }
+}
+
+/// Tree configuration of [`PyGame`], exported to Python as `TreeConfig`.
+///
+/// The bet sizes are specified in the same format as [`BetSizeOptions`]. The initial street is
+/// determined by the board passed to the game.
+#[pyclass(name = "TreeConfig", module = "postflop_solver")]
+pub struct PyTreeConfig {
+    config: TreeConfig,
+}
+
+#[pymethods]
+impl PyTreeConfig {
+    /// Creates a tree configuration without bet sizes.
+    #[new]
+    #[pyo3(signature = (starting_pot, effective_stack, rake_rate = 0.0, rake_cap = 0.0))]
+    pub fn new(starting_pot: i32, effective_stack: i32, rake_rate: f64, rake_cap: f64) -> Self {
+        Self {
+            config: TreeConfig {
+                starting_pot,
+                effective_stack,
+                rake_rate,
+                rake_cap,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Sets the bet and raise sizes of `player` (`0` for OOP, `1` for IP) on `street` (`"flop"`,
+    /// `"turn"`, or `"river"`).
+    #[pyo3(signature = (street, player, bet, raise_ = ""))]
+    pub fn set_bet_sizes(
+        &mut self,
+        street: &str,
+        player: usize,
+        bet: &str,
+        raise_: &str,
+    ) -> PyResult<()> {
+        let street = parse_street(street)?;
+        let player = parse_player(player)?;
+        let sizes = BetSizeOptions::try_from((bet, raise_)).map_err(value_error)?;
+        match street {
+            0 => self.config.flop_bet_sizes[player] = sizes,
+            1 => self.config.turn_bet_sizes[player] = sizes,
+            _ => self.config.river_bet_sizes[player] = sizes,
+        }
+        Ok(())
+    }
+
+    /// Sets the donk bet sizes on `street` (`"turn"` or `"river"`). An empty string disables them.
+    pub fn set_donk_sizes(&mut self, street: &str, donk: &str) -> PyResult<()> {
+        let sizes = if donk.is_empty() {
+            None
+        } else {
+            Some(DonkSizeOptions::try_from(donk).map_err(value_error)?)
+        };
+        match parse_street(street)? {
+            1 => self.config.turn_donk_sizes = sizes,
+            2 => self.config.river_donk_sizes = sizes,
+            _ => {
+                return Err(value_error(
+                    "Donk sizes can only be set on the turn and river",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the thresholds of adding and forcing all-in actions and merging bet sizes (see
+    /// [`TreeConfig`]).
+    pub fn set_thresholds(&mut self, add_allin: f64, force_allin: f64, merging: f64) {
+        self.config.add_allin_threshold = add_allin;
+        self.config.force_allin_threshold = force_allin;
+        self.config.merging_threshold = merging;
+    }
+}
+
+/// A [`PostFlopGame`] exported to Python as `PostFlopGame`.
+///
+/// The methods mirror those of [`PostFlopGame`] with Python-friendly types: the players are `0`
+/// (OOP) and `1` (IP), the cards are integers as in [`Card`], and the strategies and values are
+/// returned as numpy arrays. Errors are raised as `ValueError`s.
+#[pyclass(name = "PostFlopGame", module = "postflop_solver")]
+pub struct PyGame {
+    game: PostFlopGame,
+}
+
+#[pymethods]
+impl PyGame {
+    /// Creates a game from the ranges, the board (e.g., `"Td9d6h"`), and the tree configuration.
+    #[new]
+    pub fn new(
+        oop_range: &str,
+        ip_range: &str,
+        board: &str,
+        tree_config: &PyTreeConfig,
+    ) -> PyResult<Self> {
+        let board = board_from_str(board).map_err(|e| value_error(e.to_string()))?;
+        let card_config = CardConfig {
+            range: [
+                oop_range.parse().map_err(value_error)?,
+                ip_range.parse().map_err(value_error)?,
+            ],
+            flop: [board[0], board[1], board[2]],
+            turn: board.get(3).copied().unwrap_or(NOT_DEALT),
+            river: board.get(4).copied().unwrap_or(NOT_DEALT),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: match board.len() {
+                3 => BoardState::Flop,
+                4 => BoardState::Turn,
+                _ => BoardState::River,
+            },
+            ..tree_config.config.clone()
+        };
+
+        let action_tree = ActionTree::new(tree_config).map_err(value_error)?;
+        let game = PostFlopGame::with_config(card_config, action_tree).map_err(value_error)?;
+        Ok(Self { game })
+    }
+
+    /// Returns the estimated memory usage in bytes without and with compression.
+    pub fn memory_usage(&self) -> (u64, u64) {
+        self.game.memory_usage()
+    }
+
+    /// Allocates the memory for solving.
+    #[pyo3(signature = (enable_compression = false))]
+    pub fn allocate_memory(&mut self, enable_compression: bool) {
+        self.game.allocate_memory(enable_compression);
+    }
+
+    /// Solves the game until `max_num_iterations` or `target_exploitability` is reached, and
+    /// returns the exploitability.
+    ///
+    /// If `progress` is given, it is called as `progress(iteration, exploitability)` after each
+    /// iteration; the exploitability is updated every 10 iterations. The solving is aborted
+    /// without finalizing the game if `progress` raises an exception or the process receives a
+    /// signal (e.g., `KeyboardInterrupt`). The GIL is released during each iteration.
+    #[pyo3(signature = (max_num_iterations, target_exploitability, progress = None))]
+    pub fn solve(
+        &mut self,
+        py: Python<'_>,
+        max_num_iterations: u32,
+        target_exploitability: f32,
+        progress: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<f32> {
+        if !self.game.is_ready() || self.game.is_solved() {
+            return Err(value_error("Game is not ready to be solved"));
+        }
+
+        let mut exploitability = compute_exploitability(&self.game);
+        let mut num_iterations = 0;
+
+        for t in 0..max_num_iterations {
+            if exploitability <= target_exploitability {
+                break;
+            }
+
+            let game = &self.game;
+            py.detach(|| {
+                solve_step(game, t);
+                if (t + 1) % 10 == 0 || t + 1 == max_num_iterations {
+                    exploitability = compute_exploitability(game);
+                }
+            });
+
+            if let Some(progress) = &progress {
+                progress.call1((t + 1, exploitability))?;
+            }
+            py.check_signals()?;
+
+            num_iterations = t + 1;
+        }
+
+        self.game.set_solve_result(num_iterations, exploitability);
+        finalize(&mut self.game);
+        Ok(exploitability)
+    }
+
+    /// Performs one iteration of the solving (see [`solve_step`]). Call `finalize` after the last
+    /// iteration.
+    pub fn solve_step(&self, current_iteration: u32) -> PyResult<()> {
+        if !self.game.is_ready() || self.game.is_solved() {
+            return Err(value_error("Game is not ready to be solved"));
+        }
+        solve_step(&self.game, current_iteration);
+        Ok(())
+    }
+
+    /// Computes the exploitability of the current strategy.
+    pub fn exploitability(&self) -> f32 {
+        compute_exploitability(&self.game)
+    }
+
+    /// Finalizes the solving (see [`finalize`]).
+    pub fn finalize(&mut self) -> PyResult<()> {
+        if !self.game.is_ready() || self.game.is_solved() {
+            return Err(value_error("Game is not ready to be finalized"));
+        }
+        finalize(&mut self.game);
+        Ok(())
+    }
+
+    /// Returns whether the game is solved.
+    pub fn is_solved(&self) -> bool {
+        self.game.is_solved()
+    }
+
+    /// Moves back to the root node.
+    pub fn back_to_root(&mut self) {
+        self.game.back_to_root();
+    }
+
+    /// Returns the history of the actions taken from the root node.
+    pub fn history(&self) -> Vec<usize> {
+        self.game.history().to_vec()
+    }
+
+    /// Moves to the node reached by `history` from the root node.
+    pub fn apply_history(&mut self, history: Vec<usize>) {
+        self.game.apply_history(&history);
+    }
+
+    /// Plays `action` (an index of `available_actions`, or a card at a chance node).
+    pub fn play(&mut self, action: usize) {
+        self.game.play(action);
+    }
+
+    /// Undoes the last action played, moving back to the parent node.
+    pub fn undo(&mut self) -> PyResult<()> {
+        self.game.undo().map_err(value_error)
+    }
+
+    /// Replays the next action undone by `undo`.
+    pub fn redo(&mut self) -> PyResult<()> {
+        self.game.redo().map_err(value_error)
+    }
+
+    /// Returns whether the current node is a terminal node.
+    pub fn is_terminal_node(&self) -> bool {
+        self.game.is_terminal_node()
+    }
+
+    /// Returns whether the current node is a chance node.
+    pub fn is_chance_node(&self) -> bool {
+        self.game.is_chance_node()
+    }
+
+    /// Returns the player to act at the current node.
+    pub fn current_player(&self) -> usize {
+        self.game.current_player()
+    }
+
+    /// Returns the actions available at the current node as strings (e.g., `"Bet(50)"`).
+    pub fn available_actions(&self) -> Vec<String> {
+        self.game
+            .available_actions()
+            .iter()
+            .map(|action| action.to_string())
+            .collect()
+    }
+
+    /// Returns the cards that can be dealt at the current chance node.
+    pub fn possible_cards(&self) -> Vec<Card> {
+        let mask = self.game.possible_cards();
+        (0..52).filter(|&card| mask & (1 << card) != 0).collect()
+    }
+
+    /// Returns the board cards at the current node.
+    pub fn current_board(&self) -> Vec<Card> {
+        self.game.current_board()
+    }
+
+    /// Returns the private hands of `player` as strings (e.g., `"AsKs"`).
+    pub fn private_cards(&self, player: usize) -> PyResult<Vec<String>> {
+        let player = parse_player(player)?;
+        holes_to_strings(self.game.private_cards(player)).map_err(value_error)
+    }
+
+    /// Computes the normalized weights at the current node, which must be called before the
+    /// queries below.
+    pub fn cache_normalized_weights(&mut self) {
+        self.game.cache_normalized_weights();
+    }
+
+    /// Returns the weights of the private hands of `player` at the current node.
+    pub fn weights<'py>(
+        &self,
+        py: Python<'py>,
+        player: usize,
+    ) -> PyResult<Bound<'py, PyArray1<f32>>> {
+        let weights = self.game.weights(parse_player(player)?);
+        Ok(PyArray1::from_slice(py, weights))
+    }
+
+    /// Returns the normalized weights of the private hands of `player` at the current node.
+    pub fn normalized_weights<'py>(
+        &self,
+        py: Python<'py>,
+        player: usize,
+    ) -> PyResult<Bound<'py, PyArray1<f32>>> {
+        let weights = self.game.normalized_weights(parse_player(player)?);
+        Ok(PyArray1::from_slice(py, weights))
+    }
+
+    /// Returns the equity of the private hands of `player` at the current node.
+    pub fn equity<'py>(
+        &self,
+        py: Python<'py>,
+        player: usize,
+    ) -> PyResult<Bound<'py, PyArray1<f32>>> {
+        let equity = self.game.equity(parse_player(player)?);
+        Ok(PyArray1::from_vec(py, equity))
+    }
+
+    /// Returns the expected values of the private hands of `player` at the current node.
+    pub fn expected_values<'py>(
+        &self,
+        py: Python<'py>,
+        player: usize,
+    ) -> PyResult<Bound<'py, PyArray1<f32>>> {
+        let ev = self.game.expected_values(parse_player(player)?);
+        Ok(PyArray1::from_vec(py, ev))
+    }
+
+    /// Returns the strategy at the current node as an array of shape `(#actions, #hands)`.
+    pub fn strategy<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        let num_actions = self.game.available_actions().len();
+        let num_hands = self.game.num_private_hands(self.game.current_player());
+        PyArray1::from_vec(py, self.game.strategy()).reshape([num_actions, num_hands])
+    }
+
+    /// Returns the total bet amounts of the players at the current node.
+    pub fn total_bet_amount(&self) -> [i32; 2] {
+        self.game.total_bet_amount()
+    }
+
+    /// Locks the strategy at the current node (see [`PostFlopGame::lock_current_strategy`]). The
+    /// strategy is a flat array laid out as in [`PostFlopGame::strategy`].
+    pub fn lock_current_strategy(&mut self, strategy: PyReadonlyArray1<'_, f32>) -> PyResult<()> {
+        let strategy = strategy
+            .as_slice()
+            .map_err(|e| value_error(e.to_string()))?;
+        self.game.lock_current_strategy(strategy);
+        Ok(())
+    }
+
+    /// Unlocks the strategy at the current node.
+    pub fn unlock_current_strategy(&mut self) {
+        self.game.unlock_current_strategy();
+    }
+}
+
+/// The `postflop_solver` Python module.
+#[pymodule]
+fn postflop_solver(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyTreeConfig>()?;
+    m.add_class::<PyGame>()?;
+    Ok(())
+}
@@ -0,0 +1,83 @@
+/// A source of random numbers for the stochastic methods of this crate.
+///
+/// The sampling methods accept a generator supplied by the caller (e.g.,
+/// [`PostFlopGame::rollout_with_rng`]), so that an application can drive all the random draws from
+/// one seeded generator of its choice. Each of them has a convenience counterpart taking a `u64`
+/// seed (e.g., [`PostFlopGame::rollout`]), which uses a [`fastrand::Rng`] seeded with it.
+///
+/// Only [`next_u64`] is required; the other methods are derived from it. The computations that
+/// run in parallel (e.g., [`estimate_exploitability_with_rng`]) draw a single seed from the
+/// generator for their per-thread generators, so their results do not depend on the number of
+/// threads.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// // xorshift64*
+/// struct XorShift(u64);
+///
+/// impl RandomSource for XorShift {
+///     fn next_u64(&mut self) -> u64 {
+///         self.0 ^= self.0 >> 12;
+///         self.0 ^= self.0 << 25;
+///         self.0 ^= self.0 >> 27;
+///         self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+///     }
+/// }
+///
+/// let mut rng = XorShift(42);
+/// let boards = sample_boards_with_rng(3, 10, 0, &mut rng, |_| true).unwrap();
+/// assert_eq!(boards.len(), 10);
+/// assert!(rng.next_f64() < 1.0);
+/// ```
+///
+/// [`next_u64`]: RandomSource::next_u64
+/// [`PostFlopGame::rollout_with_rng`]: crate::PostFlopGame::rollout_with_rng
+/// [`PostFlopGame::rollout`]: crate::PostFlopGame::rollout
+/// [`estimate_exploitability_with_rng`]: crate::estimate_exploitability_with_rng
+pub trait RandomSource {
+    /// Returns a uniformly distributed `u64`.
+    fn next_u64(&mut self) -> u64;
+
+    /// Returns a uniformly distributed `f64` in `[0, 1)`.
+    #[inline]
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Returns a uniformly distributed integer in `0..n`.
+    ///
+    /// Panics if `n` is zero.
+    #[inline]
+    fn next_below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "Empty range");
+
+        // widening multiplication with the rejection of the biased low part
+        let n = n as u64;
+        let threshold = n.wrapping_neg() % n;
+        loop {
+            let product = self.next_u64() as u128 * n as u128;
+            if product as u64 >= threshold {
+                return (product >> 64) as usize;
+            }
+        }
+    }
+}
+
+impl RandomSource for fastrand::Rng {
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.u64(..)
+    }
+
+    #[inline]
+    fn next_f64(&mut self) -> f64 {
+        self.f64()
+    }
+
+    #[inline]
+    fn next_below(&mut self, n: usize) -> usize {
+        self.usize(..n)
+    }
+}
@@ -1,4 +1,5 @@
 use crate::card::*;
+use crate::error::*;
 use std::fmt::{self, Write};
 use std::str::FromStr;
 use std::sync::LazyLock;
@@ -164,7 +165,7 @@ fn char_to_suit(c: char) -> Result<u8, String> {
 ///
 /// `12` => `'A'`, `11` => `'K'`, ..., `0` => `'2'`.
 #[inline]
-fn rank_to_char(rank: u8) -> Result<char, String> {
+pub(crate) fn rank_to_char(rank: u8) -> Result<char, String> {
     match rank {
         12 => Ok('A'),
         11 => Ok('K'),
@@ -203,11 +204,11 @@ fn suit_to_char(suit: u8) -> Result<char, String> {
 /// assert!(card_to_string(52).is_err());
 /// ```
 #[inline]
-pub fn card_to_string(card: Card) -> Result<String, String> {
+pub fn card_to_string(card: Card) -> Result<String, SolverError> {
     check_card(card)?;
-    let rank = card >> 2;
-    let suit = card & 3;
-    Ok(format!("{}{}", rank_to_char(rank)?, suit_to_char(suit)?))
+    let rank = rank_to_char(card >> 2).map_err(SolverError::InvalidCardConfig)?;
+    let suit = suit_to_char(card & 3).map_err(SolverError::InvalidCardConfig)?;
+    Ok(format!("{rank}{suit}"))
 }
 
 /// Attempts to convert hole cards into a string.
@@ -225,7 +226,7 @@ pub fn card_to_string(card: Card) -> Result<String, String> {
 /// assert!(hole_to_string((52, 53)).is_err());
 /// ```
 #[inline]
-pub fn hole_to_string(hole: (Card, Card)) -> Result<String, String> {
+pub fn hole_to_string(hole: (Card, Card)) -> Result<String, SolverError> {
     let max_card = Card::max(hole.0, hole.1);
     let min_card = Card::min(hole.0, hole.1);
     Ok(format!(
@@ -252,12 +253,15 @@ pub fn hole_to_string(hole: (Card, Card)) -> Result<String, String> {
 /// assert!(holes_to_strings(&[(52, 53)]).is_err());
 /// ```
 #[inline]
-pub fn holes_to_strings(holes: &[(Card, Card)]) -> Result<Vec<String>, String> {
+pub fn holes_to_strings(holes: &[(Card, Card)]) -> Result<Vec<String>, SolverError> {
     holes.iter().map(|&hole| hole_to_string(hole)).collect()
 }
 
 /// Attempts to read the next card from a char iterator.
 ///
+/// The `position` of the returned error is the offset of the offending character relative to the
+/// first character read by this call.
+///
 /// # Examples
 /// ```
 /// use postflop_solver::card_from_chars;
@@ -270,18 +274,214 @@ pub fn holes_to_strings(holes: &[(Card, Card)]) -> Result<Vec<String>, String> {
 /// assert!(card_from_chars(&mut chars).is_err());
 /// ```
 #[inline]
-pub fn card_from_chars<T: Iterator<Item = char>>(chars: &mut T) -> Result<Card, String> {
-    let rank_char = chars.next().ok_or_else(|| "Unexpected end".to_owned())?;
-    let suit_char = chars.next().ok_or_else(|| "Unexpected end".to_owned())?;
-
-    let rank = char_to_rank(rank_char)?;
-    let suit = char_to_suit(suit_char)?;
+pub fn card_from_chars<T: Iterator<Item = char>>(chars: &mut T) -> Result<Card, CardParseError> {
+    let error = |position, kind| CardParseError { position, kind };
+    let rank_char = chars
+        .next()
+        .ok_or_else(|| error(0, CardParseErrorKind::UnexpectedEnd))?;
+    let suit_char = chars
+        .next()
+        .ok_or_else(|| error(1, CardParseErrorKind::UnexpectedEnd))?;
+
+    let rank = char_to_rank(rank_char)
+        .map_err(|_| error(0, CardParseErrorKind::InvalidRank(rank_char)))?;
+    let suit = char_to_suit(suit_char)
+        .map_err(|_| error(1, CardParseErrorKind::InvalidSuit(suit_char)))?;
 
     Ok((rank << 2) | suit)
 }
 
+/// An error returned by the card parsers: [`card_from_str`], [`flop_from_str`],
+/// [`cards_from_str`], [`board_from_str`], and [`card_from_chars`].
+///
+/// `position` is the byte offset in the input string at which the error was detected. The error
+/// converts into [`SolverError::CardParse`] with `?`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CardParseError {
+    /// Byte offset in the input string.
+    pub position: usize,
+
+    /// Kind of the error.
+    pub kind: CardParseErrorKind,
+}
+
+/// Kind of a [`CardParseError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardParseErrorKind {
+    /// The input ended in the middle of a card.
+    UnexpectedEnd,
+
+    /// The character is not a valid rank.
+    InvalidRank(char),
+
+    /// The character is not a valid suit.
+    InvalidSuit(char),
+
+    /// The card appeared more than once.
+    DuplicateCard(Card),
+
+    /// The bracket is not matched.
+    UnmatchedBracket(char),
+
+    /// The number of cards is out of the expected range.
+    InvalidNumberOfCards(usize),
+}
+
+impl fmt::Display for CardParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let position = self.position;
+        match self.kind {
+            CardParseErrorKind::UnexpectedEnd => {
+                write!(f, "Unexpected end at position {position}")
+            }
+            CardParseErrorKind::InvalidRank(c) => {
+                write!(f, "Expected rank character at position {position}: {c}")
+            }
+            CardParseErrorKind::InvalidSuit(c) => {
+                write!(f, "Expected suit character at position {position}: {c}")
+            }
+            CardParseErrorKind::DuplicateCard(card) => {
+                let card = card_to_string(card).unwrap();
+                write!(f, "Duplicate card at position {position}: {card}")
+            }
+            CardParseErrorKind::UnmatchedBracket(c) => {
+                write!(f, "Unmatched bracket at position {position}: {c}")
+            }
+            CardParseErrorKind::InvalidNumberOfCards(n) => {
+                write!(f, "Invalid number of cards: {n}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CardParseError {}
+
+/// Attempts to convert a suit character to a suit index, accepting uppercase letters and unicode
+/// suit symbols.
+#[inline]
+fn flexible_char_to_suit(c: char) -> Option<u8> {
+    match c {
+        'c' | 'C' | '♣' | '♧' => Some(0),
+        'd' | 'D' | '♦' | '♢' => Some(1),
+        'h' | 'H' | '♥' | '♡' => Some(2),
+        's' | 'S' | '♠' | '♤' => Some(3),
+        _ => None,
+    }
+}
+
+/// Attempts to convert a free-text string into a list of unique cards.
+///
+/// The parser is more permissive than [`card_from_chars`]:
+///
+/// - Ranks may be written as `10` in addition to `T`, in either case (e.g., "10h", "td").
+/// - Suits may be written in either case or as unicode symbols (e.g., "A♠", "K♡", "QS").
+/// - Cards may be separated by whitespace, `,`, `;`, `-`, `/` or `|`, or not separated at all.
+/// - Cards may be enclosed in brackets: `()`, `[]` or `{}`. Brackets must be balanced.
+///
+/// The cards are returned in the order of appearance.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// assert_eq!(cards_from_str("[10h, J♠ 2D]"), Ok(vec![34, 39, 1]));
+/// assert_eq!(cards_from_str(""), Ok(vec![]));
+///
+/// let error = cards_from_str("Ah Kx").unwrap_err();
+/// assert_eq!(error.position, 4);
+/// assert_eq!(error.kind, CardParseErrorKind::InvalidSuit('x'));
+/// ```
+pub fn cards_from_str(s: &str) -> Result<Vec<Card>, CardParseError> {
+    let error = |position, kind| CardParseError { position, kind };
+    let unexpected_end = error(s.len(), CardParseErrorKind::UnexpectedEnd);
+
+    let mut result = Vec::new();
+    let mut brackets = Vec::new();
+    let mut chars = s.char_indices();
+
+    while let Some((position, c)) = chars.next() {
+        match c {
+            // separators (U+FE0F: emoji variation selector following suit symbols)
+            ',' | ';' | '-' | '/' | '|' | '\u{fe0f}' => continue,
+            _ if c.is_whitespace() => continue,
+            '(' | '[' | '{' => {
+                brackets.push((position, c));
+                continue;
+            }
+            ')' | ']' | '}' => {
+                let expected = match c {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                match brackets.pop() {
+                    Some((_, open)) if open == expected => continue,
+                    _ => return Err(error(position, CardParseErrorKind::UnmatchedBracket(c))),
+                }
+            }
+            _ => {}
+        }
+
+        let rank = if c == '1' {
+            match chars.next() {
+                Some((_, '0')) => 8,
+                Some((position, c)) => {
+                    return Err(error(position, CardParseErrorKind::InvalidRank(c)));
+                }
+                None => return Err(unexpected_end),
+            }
+        } else {
+            char_to_rank(c).map_err(|_| error(position, CardParseErrorKind::InvalidRank(c)))?
+        };
+
+        let (suit_position, suit_char) = chars.next().ok_or(unexpected_end)?;
+        let suit = flexible_char_to_suit(suit_char)
+            .ok_or_else(|| error(suit_position, CardParseErrorKind::InvalidSuit(suit_char)))?;
+
+        let card = (rank << 2) | suit;
+        if result.contains(&card) {
+            return Err(error(position, CardParseErrorKind::DuplicateCard(card)));
+        }
+
+        result.push(card);
+    }
+
+    if let Some((position, c)) = brackets.pop() {
+        return Err(error(position, CardParseErrorKind::UnmatchedBracket(c)));
+    }
+
+    Ok(result)
+}
+
+/// Attempts to convert a free-text string into a board of three to five unique cards.
+///
+/// See [`cards_from_str`] for the accepted format. The cards are returned in the order of
+/// appearance, i.e., the first three cards are the flop, followed by the turn and the river.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// assert_eq!(board_from_str("Td9d6h Qc"), Ok(vec![33, 29, 18, 40]));
+/// assert_eq!(
+///     board_from_str("Td9d").unwrap_err().kind,
+///     CardParseErrorKind::InvalidNumberOfCards(2)
+/// );
+/// ```
+pub fn board_from_str(s: &str) -> Result<Vec<Card>, CardParseError> {
+    let result = cards_from_str(s)?;
+
+    if !(3..=5).contains(&result.len()) {
+        return Err(invalid_number_of_cards(s, result.len()));
+    }
+
+    Ok(result)
+}
+
 /// Attempts to convert a string into a card.
 ///
+/// See [`cards_from_str`] for the accepted format.
+///
 /// # Examples
 /// ```
 /// use postflop_solver::card_from_str;
@@ -290,20 +490,23 @@ pub fn card_from_chars<T: Iterator<Item = char>>(chars: &mut T) -> Result<Card,
 /// assert_eq!(card_from_str("3d"), Ok(5));
 /// assert_eq!(card_from_str("4h"), Ok(10));
 /// assert_eq!(card_from_str("As"), Ok(51));
+/// assert_eq!(card_from_str("10♥"), Ok(34));
+/// assert!(card_from_str("AsKs").is_err());
 /// ```
 #[inline]
-pub fn card_from_str(s: &str) -> Result<Card, String> {
-    let mut chars = s.chars();
-    let result = card_from_chars(&mut chars)?;
+pub fn card_from_str(s: &str) -> Result<Card, CardParseError> {
+    let result = cards_from_str(s)?;
 
-    if chars.next().is_some() {
-        return Err("Expected exactly two characters".to_owned());
+    if result.len() != 1 {
+        return Err(invalid_number_of_cards(s, result.len()));
     }
 
-    Ok(result)
+    Ok(result[0])
 }
 
-/// Attempts to convert an optionally space-separated string into a sorted flop array.
+/// Attempts to convert an optionally separated string into a sorted flop array.
+///
+/// See [`cards_from_str`] for the accepted format.
 ///
 /// # Examples
 /// ```
@@ -311,28 +514,27 @@ pub fn card_from_str(s: &str) -> Result<Card, String> {
 ///
 /// assert_eq!(flop_from_str("2c3d4h"), Ok([0, 5, 10]));
 /// assert_eq!(flop_from_str("As Ah Ks"), Ok([47, 50, 51]));
+/// assert_eq!(flop_from_str("[A♠, A♥, K♠]"), Ok([47, 50, 51]));
 /// assert!(flop_from_str("2c3d4h5s").is_err());
 /// ```
 #[inline]
-pub fn flop_from_str(s: &str) -> Result<[Card; 3], String> {
-    let mut result = [0; 3];
-    let mut chars = s.chars();
+pub fn flop_from_str(s: &str) -> Result<[Card; 3], CardParseError> {
+    let cards = cards_from_str(s)?;
 
-    result[0] = card_from_chars(&mut chars)?;
-    result[1] = card_from_chars(&mut chars.by_ref().skip_while(|c| c.is_whitespace()))?;
-    result[2] = card_from_chars(&mut chars.by_ref().skip_while(|c| c.is_whitespace()))?;
-
-    if chars.next().is_some() {
-        return Err("Expected exactly three cards".to_owned());
-    }
+    let mut result: [Card; 3] = cards
+        .try_into()
+        .map_err(|cards: Vec<_>| invalid_number_of_cards(s, cards.len()))?;
 
     result.sort_unstable();
+    Ok(result)
+}
 
-    if result[0] == result[1] || result[1] == result[2] {
-        return Err("Cards must be unique".to_owned());
+#[inline]
+fn invalid_number_of_cards(s: &str, num_cards: usize) -> CardParseError {
+    CardParseError {
+        position: s.len(),
+        kind: CardParseErrorKind::InvalidNumberOfCards(num_cards),
     }
-
-    Ok(result)
 }
 
 #[inline]
@@ -383,12 +585,23 @@ fn parse_compound_singleton(combo: &str) -> Result<(u8, u8, Suitedness), String>
     Ok((rank1, rank2, suitedness))
 }
 
+/// Returns a function that attaches the offending element `token` of a range string to an error.
+#[inline]
+fn invalid_range(token: &str) -> impl FnOnce(String) -> SolverError + '_ {
+    move |message| SolverError::InvalidRange {
+        token: token.to_owned(),
+        message,
+    }
+}
+
 #[inline]
-fn check_card(card: Card) -> Result<(), String> {
+fn check_card(card: Card) -> Result<(), SolverError> {
     if card < 52 {
         Ok(())
     } else {
-        Err(format!("Invalid card: {card}"))
+        Err(SolverError::InvalidCardConfig(format!(
+            "Invalid card: {card}"
+        )))
     }
 }
 
@@ -426,14 +639,20 @@ impl Range {
     }
 
     /// Attempts to create a range from raw data.
+    ///
+    /// Returns [`SolverError::InvalidData`] if `data` does not have exactly 1326 elements, and
+    /// [`SolverError::InvalidRange`] if a weight is out of `[0, 1]`.
     #[inline]
-    pub fn from_raw_data(data: &[f32]) -> Result<Self, String> {
+    pub fn from_raw_data(data: &[f32]) -> Result<Self, SolverError> {
         if data.len() != 52 * 51 / 2 {
-            return Err(format!("Expected exactly {} elements", 52 * 51 / 2));
+            return Err(SolverError::InvalidData(format!(
+                "Expected exactly {} elements",
+                52 * 51 / 2
+            )));
         }
 
         for &weight in data {
-            check_weight(weight)?;
+            check_weight(weight).map_err(invalid_range(&weight.to_string()))?;
         }
 
         Ok(Self {
@@ -448,15 +667,23 @@ impl Range {
     }
 
     /// Attempts to create a range from a list of hands with their weights.
+    ///
+    /// Returns [`SolverError::InvalidCardConfig`] if a hand is invalid, and
+    /// [`SolverError::InvalidRange`] if a weight is out of `[0, 1]`.
     #[inline]
-    pub fn from_hands_weights(hands: &[(Card, Card)], weights: &[f32]) -> Result<Self, String> {
+    pub fn from_hands_weights(
+        hands: &[(Card, Card)],
+        weights: &[f32],
+    ) -> Result<Self, SolverError> {
         let mut range = Self::default();
         for (&(card1, card2), &weight) in hands.iter().zip(weights.iter()) {
             check_card(card1)?;
             check_card(card2)?;
-            check_weight(weight)?;
+            check_weight(weight).map_err(invalid_range(&weight.to_string()))?;
             if card1 == card2 {
-                return Err("Hand must consist of two different cards".to_owned());
+                return Err(SolverError::InvalidCardConfig(
+                    "Hand must consist of two different cards".to_owned(),
+                ));
             }
             range.set_weight_by_cards(card1, card2, weight);
         }
@@ -488,12 +715,48 @@ impl Range {
         (hands, weights)
     }
 
+    /// Attempts to create a range of short-deck (6+) hold'em from a range string.
+    ///
+    /// The syntax is the same as `parse::<Range>()`, but the range must not contain cards of ranks
+    /// 2-5.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::Range;
+    ///
+    /// let range = Range::from_short_deck_str("66+,A6s+,KQo").unwrap();
+    /// assert!(range.is_short_deck());
+    /// assert!(Range::from_short_deck_str("55+").is_err());
+    /// ```
+    pub fn from_short_deck_str(s: &str) -> Result<Self, SolverError> {
+        let range = s.parse::<Self>()?;
+        if !range.is_short_deck() {
+            return Err(invalid_range(s)(format!(
+                "Short-deck range must not contain cards of ranks 2-5: {s}"
+            )));
+        }
+        Ok(range)
+    }
+
+    /// Returns whether the range is valid in short-deck (6+) hold'em, i.e., no hand containing a
+    /// card of ranks 2-5 has a positive weight.
+    #[inline]
+    pub fn is_short_deck(&self) -> bool {
+        (0..52).all(|card1| {
+            (card1 + 1..52).all(|card2| {
+                let hand_mask: u64 = (1 << card1) | (1 << card2);
+                hand_mask & SHORT_DECK_DEAD_CARDS == 0
+                    || self.get_weight_by_cards(card1, card2) == 0.0
+            })
+        })
+    }
+
     /// Attempts to create a range from a sanitized range string.
     ///
     /// "Sanitized" means that the range string does not contain any invalid patterns and whitespace
     /// characters. Therefore, this method can bypass the regular expression processing. If you want
     /// to create a range from a regular string, use `parse::<Range>()` instead.
-    pub fn from_sanitized_str(ranges: &str) -> Result<Self, String> {
+    pub fn from_sanitized_str(ranges: &str) -> Result<Self, SolverError> {
         let mut ranges = ranges.split(',').collect::<Vec<_>>();
 
         // remove last empty element if any
@@ -503,29 +766,30 @@ impl Range {
 
         let mut result = Self::new();
 
-        for range in ranges.into_iter().rev() {
-            let mut split = range.split(':');
-            let range = split.next().unwrap();
+        for token in ranges.into_iter().rev() {
+            result
+                .update_with_sanitized_token(token)
+                .map_err(invalid_range(token))?;
+        }
 
-            let weight = split
-                .next()
-                .map_or(Ok(1.0), |s| s.parse::<f32>().map_err(|e| e.to_string()))?;
-            check_weight(weight)?;
+        Ok(result)
+    }
 
-            if split.next().is_some() {
-                return Err(format!("Invalid range: {range}"));
-            }
+    /// Updates the range with an element of a sanitized range string.
+    fn update_with_sanitized_token(&mut self, token: &str) -> Result<(), String> {
+        let mut split = token.split(':');
+        let range = split.next().unwrap();
 
-            if range.contains('-') {
-                result.update_with_dash_range(range, weight)?;
-            } else if range.contains('+') {
-                result.update_with_plus_range(range, weight)?;
-            } else {
-                result.update_with_singleton(range, weight)?;
-            }
+        let weight = split
+            .next()
+            .map_or(Ok(1.0), |s| s.parse::<f32>().map_err(|e| e.to_string()))?;
+        check_weight(weight)?;
+
+        if split.next().is_some() {
+            return Err(format!("Invalid range: {range}"));
         }
 
-        Ok(result)
+        self.update_with_range(range, weight)
     }
 
     /// Clears the range.
@@ -706,8 +970,20 @@ impl Range {
         }
     }
 
+    /// Updates the range with a singleton, a plus range, or a dash range.
     #[inline]
-    fn update_with_singleton(&mut self, combo: &str, weight: f32) -> Result<(), String> {
+    fn update_with_range(&mut self, range: &str, weight: f32) -> Result<(), String> {
+        if range.contains('-') {
+            self.update_with_dash_range(range, weight)
+        } else if range.contains('+') {
+            self.update_with_plus_range(range, weight)
+        } else {
+            self.update_with_singleton(range, weight)
+        }
+    }
+
+    #[inline]
+    pub(crate) fn update_with_singleton(&mut self, combo: &str, weight: f32) -> Result<(), String> {
         let (rank1, rank2, suitedness) = parse_singleton(combo)?;
         self.set_weight(&indices_with_suitedness(rank1, rank2, suitedness), weight);
         Ok(())
@@ -952,7 +1228,7 @@ impl Range {
 }
 
 impl FromStr for Range {
-    type Err = String;
+    type Err = SolverError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = TRIM_REGEX.replace_all(s, "$1").trim().to_owned();
@@ -965,24 +1241,19 @@ impl FromStr for Range {
 
         let mut result = Self::new();
 
-        for range in ranges.into_iter().rev() {
+        for token in ranges.into_iter().rev() {
             let caps = RANGE_REGEX
-                .captures(range)
-                .ok_or_else(|| format!("Failed to parse range: {range}"))?;
+                .captures(token)
+                .ok_or_else(|| format!("Failed to parse range: {token}"))
+                .map_err(invalid_range(token))?;
 
             let range = caps.name("range").unwrap().as_str();
             let weight = caps
                 .name("weight")
                 .map_or(1.0, |s| s.as_str().parse().unwrap());
-            check_weight(weight)?;
-
-            if range.contains('-') {
-                result.update_with_dash_range(range, weight)?;
-            } else if range.contains('+') {
-                result.update_with_plus_range(range, weight)?;
-            } else {
-                result.update_with_singleton(range, weight)?;
-            }
+            check_weight(weight)
+                .and_then(|_| result.update_with_range(range, weight))
+                .map_err(invalid_range(token))?;
         }
 
         Ok(result)
@@ -1141,6 +1412,81 @@ mod tests {
         assert_eq!(data.get_weight_offsuit(6, 3), 0.0);
     }
 
+    #[test]
+    fn cards_from_str_flexible() {
+        let tests = [
+            ("", vec![]),
+            ("2c", vec![0]),
+            ("10h", vec![34]),
+            ("TdTS", vec![33, 35]),
+            ("a♠ K♡, q♦️", vec![51, 46, 41]),
+            ("[Ah][Kh]", vec![50, 46]),
+            ("(Qc-Jc/9c | 2d; 3d)", vec![40, 36, 28, 1, 5]),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(cards_from_str(input), Ok(expected));
+        }
+
+        let error_tests = [
+            ("Ah Ax", 4, CardParseErrorKind::InvalidSuit('x')),
+            ("Ah 1h", 4, CardParseErrorKind::InvalidRank('h')),
+            ("Ah Xh", 3, CardParseErrorKind::InvalidRank('X')),
+            ("Ah K", 4, CardParseErrorKind::UnexpectedEnd),
+            ("Ah ah", 3, CardParseErrorKind::DuplicateCard(50)),
+            ("[Ah Kh", 0, CardParseErrorKind::UnmatchedBracket('[')),
+            ("(Ah Kh]", 6, CardParseErrorKind::UnmatchedBracket(']')),
+        ];
+
+        for (input, position, kind) in error_tests {
+            assert_eq!(
+                cards_from_str(input),
+                Err(CardParseError { position, kind })
+            );
+        }
+
+        assert_eq!(board_from_str("AhKhQh"), Ok(vec![50, 46, 42]));
+        assert_eq!(board_from_str("AhKhQhJhTh"), Ok(vec![50, 46, 42, 38, 34]));
+        for (input, n) in [("AhKh", 2), ("AhKhQhJhTh9h", 6)] {
+            let kind = CardParseErrorKind::InvalidNumberOfCards(n);
+            let position = input.len();
+            assert_eq!(
+                board_from_str(input),
+                Err(CardParseError { position, kind })
+            );
+        }
+    }
+
+    #[test]
+    fn card_and_range_errors() {
+        let kind = CardParseErrorKind::InvalidNumberOfCards(2);
+        let error = CardParseError { position: 4, kind };
+        assert_eq!(card_from_str("AsKs"), Err(error));
+        assert_eq!(flop_from_str("AsKs"), Err(error));
+        assert_eq!(SolverError::from(error), SolverError::CardParse(error));
+
+        let kind = CardParseErrorKind::InvalidSuit('x');
+        let error = CardParseError { position: 1, kind };
+        assert_eq!(card_from_chars(&mut "Ax".chars()), Err(error));
+
+        assert!(matches!(
+            card_to_string(52),
+            Err(SolverError::InvalidCardConfig(_))
+        ));
+        assert!(matches!(
+            Range::from_hands_weights(&[(0, 0)], &[1.0]),
+            Err(SolverError::InvalidCardConfig(_))
+        ));
+        assert!(matches!(
+            Range::from_hands_weights(&[(0, 1)], &[1.5]),
+            Err(SolverError::InvalidRange { token, .. }) if token == "1.5"
+        ));
+        assert!(matches!(
+            Range::from_raw_data(&[1.0; 10]),
+            Err(SolverError::InvalidData(_))
+        ));
+    }
+
     #[test]
     fn range_to_string() {
         let tests = [
@@ -0,0 +1,599 @@
+use crate::action_tree::*;
+use crate::bet_size::*;
+use crate::card::*;
+use crate::error::SolverError;
+use crate::game::*;
+use crate::preflop::*;
+use crate::range::*;
+use crate::solver::*;
+use crate::utility::*;
+use std::fmt;
+
+/// Number of Monte Carlo samples for each pair of hand classes when ordering the classes by
+/// strength for [`Perturbation::RangeWidth`].
+const STRENGTH_NUM_SAMPLES: usize = 200;
+
+/// Seed of the Monte Carlo simulation for [`Perturbation::RangeWidth`].
+const STRENGTH_SEED: u64 = 0;
+
+/// A change of the baseline configuration, an entry of [`SensitivitySweep::perturbations`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Perturbation {
+    /// Removes the rake: clears [`TreeConfig::rake_rate`], [`TreeConfig::rake_cap`], and
+    /// [`TreeConfig::rake_config`].
+    RakeOff,
+
+    /// Sets the rake to the configuration (overriding `rake_rate` and `rake_cap`).
+    Rake(RakeConfig),
+
+    /// Widens (if `fraction` is positive) or narrows (if negative) the range of `player` by
+    /// `fraction` of its width, e.g., `0.05` for +5%.
+    ///
+    /// The width is the total weight of the combos, not counting the board. The starting hand
+    /// classes are ordered by the all-in equity against a random hand: widening adds the weight
+    /// to the strongest classes not yet fully in the range, and narrowing removes the weight from
+    /// the weakest classes in the range. The class at the boundary is scaled uniformly, so the
+    /// suit-specific weights within a class keep their proportions.
+    RangeWidth { player: usize, fraction: f64 },
+
+    /// Replaces the bet size options of `player` on `street`.
+    BetSizes {
+        street: BoardState,
+        player: usize,
+        sizes: BetSizeOptions,
+    },
+}
+
+impl Perturbation {
+    /// Applies the perturbation to the configurations.
+    ///
+    /// Returns [`SolverError::InvalidArgument`] if `player` is not 0 or 1, the fraction of
+    /// [`RangeWidth`](Perturbation::RangeWidth) is not greater than `-1.0`, or the range to widen
+    /// is empty.
+    pub fn apply(
+        &self,
+        card_config: &mut CardConfig,
+        tree_config: &mut TreeConfig,
+    ) -> Result<(), SolverError> {
+        match self {
+            Self::RakeOff => {
+                tree_config.rake_rate = 0.0;
+                tree_config.rake_cap = 0.0;
+                tree_config.rake_config = None;
+            }
+            Self::Rake(rake_config) => {
+                tree_config.rake_config = Some(*rake_config);
+            }
+            &Self::RangeWidth { player, fraction } => {
+                if player > 1 {
+                    return Err(SolverError::InvalidArgument(format!(
+                        "Invalid player: {player}"
+                    )));
+                }
+                if !(fraction > -1.0 && fraction.is_finite()) {
+                    return Err(SolverError::InvalidArgument(format!(
+                        "Invalid range width fraction: {fraction}"
+                    )));
+                }
+                let classes = hand_classes_by_strength(STRENGTH_NUM_SAMPLES, STRENGTH_SEED);
+                let dead_mask = card_config.dead_mask();
+                change_range_width(
+                    &mut card_config.range[player],
+                    &classes,
+                    dead_mask,
+                    fraction,
+                )?;
+            }
+            Self::BetSizes {
+                street,
+                player,
+                sizes,
+            } => {
+                if *player > 1 {
+                    return Err(SolverError::InvalidArgument(format!(
+                        "Invalid player: {player}"
+                    )));
+                }
+                let bet_sizes = match street {
+                    BoardState::Flop => &mut tree_config.flop_bet_sizes,
+                    BoardState::Turn => &mut tree_config.turn_bet_sizes,
+                    BoardState::River => &mut tree_config.river_bet_sizes,
+                };
+                bet_sizes[*player] = sizes.clone();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Perturbation {
+    /// Formats a short label of the perturbation for reports (e.g., `OOP range +5%`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let player_name = |player: usize| ["OOP", "IP"].get(player).copied().unwrap_or("?");
+        match self {
+            Self::RakeOff => write!(f, "rake off"),
+            Self::Rake(rake_config) => write!(
+                f,
+                "rake {:?} capped at {:?}",
+                rake_config.rates, rake_config.caps
+            ),
+            Self::RangeWidth { player, fraction } => {
+                write!(f, "{} range {:+}%", player_name(*player), fraction * 100.0)
+            }
+            Self::BetSizes {
+                street,
+                player,
+                sizes,
+            } => {
+                let join = |sizes: &[BetSize]| {
+                    sizes
+                        .iter()
+                        .map(|size| size.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                write!(
+                    f,
+                    "{} {street:?} sizes {} / {}",
+                    player_name(*player),
+                    join(&sizes.bet),
+                    join(&sizes.raise)
+                )
+            }
+        }
+    }
+}
+
+/// Changes the width of `range` by `fraction` along `classes` (ordered from the strongest).
+fn change_range_width(
+    range: &mut Range,
+    classes: &[Vec<(Card, Card)>],
+    dead_mask: u64,
+    fraction: f64,
+) -> Result<(), SolverError> {
+    let live_hands = |hands: &[(Card, Card)]| {
+        hands
+            .iter()
+            .copied()
+            .filter(|&(c1, c2)| ((1u64 << c1) | (1u64 << c2)) & dead_mask == 0)
+            .collect::<Vec<_>>()
+    };
+    let classes = classes
+        .iter()
+        .map(|hands| live_hands(hands))
+        .collect::<Vec<_>>();
+
+    let weight = |range: &Range, &(c1, c2): &(Card, Card)| range.get_weight_by_cards(c1, c2) as f64;
+    let width = classes
+        .iter()
+        .flatten()
+        .map(|hand| weight(range, hand))
+        .sum::<f64>();
+    if width == 0.0 {
+        return Err(SolverError::InvalidArgument("Range is empty".to_owned()));
+    }
+
+    let mut remaining = width * fraction.abs();
+    if fraction > 0.0 {
+        for hands in &classes {
+            let room = hands
+                .iter()
+                .map(|hand| 1.0 - weight(range, hand))
+                .sum::<f64>();
+            if room <= 0.0 {
+                continue;
+            }
+            let ratio = (remaining / room).min(1.0);
+            for &(c1, c2) in hands {
+                let w = range.get_weight_by_cards(c1, c2) as f64;
+                range.set_weight_by_cards(c1, c2, (w + (1.0 - w) * ratio) as f32);
+            }
+            remaining -= room * ratio;
+            if remaining <= 0.0 {
+                break;
+            }
+        }
+    } else {
+        for hands in classes.iter().rev() {
+            let total = hands.iter().map(|hand| weight(range, hand)).sum::<f64>();
+            if total <= 0.0 {
+                continue;
+            }
+            let ratio = (remaining / total).min(1.0);
+            for &(c1, c2) in hands {
+                let w = range.get_weight_by_cards(c1, c2) as f64;
+                range.set_weight_by_cards(c1, c2, (w * (1.0 - ratio)) as f32);
+            }
+            remaining -= total * ratio;
+            if remaining <= 0.0 {
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Headline results of a game solved by [`SensitivitySweep::run`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensitivityEntry {
+    /// Perturbation of the entry, or `None` for the baseline.
+    pub perturbation: Option<Perturbation>,
+
+    /// Number of iterations performed.
+    pub num_iterations: u32,
+
+    /// Exploitability of the obtained strategy.
+    pub exploitability: f32,
+
+    /// Average expected value of the range of each player at the root, in the same convention as
+    /// [`PostFlopGame::expected_values`].
+    pub ev: [f32; 2],
+
+    /// Available actions at the root.
+    pub actions: Vec<Action>,
+
+    /// Overall frequency of each action at the root (see [`PostFlopGame::action_frequencies`]).
+    pub action_frequencies: Vec<f32>,
+
+    /// Total frequency of the bets, raises, and all-ins at the root, which is comparable between
+    /// entries with different bet sizes.
+    pub aggression_frequency: f32,
+}
+
+/// Result of [`SensitivitySweep::run`]: the baseline and each perturbation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensitivityReport {
+    /// Results of the baseline configuration.
+    pub baseline: SensitivityEntry,
+
+    /// Results of each perturbation, in the order of [`SensitivitySweep::perturbations`].
+    pub entries: Vec<SensitivityEntry>,
+}
+
+impl SensitivityReport {
+    /// Returns the change of the average expected value of each player from the baseline to the
+    /// `index`-th entry.
+    #[inline]
+    pub fn ev_shift(&self, index: usize) -> [f32; 2] {
+        let entry = &self.entries[index];
+        [0, 1].map(|player| entry.ev[player] - self.baseline.ev[player])
+    }
+
+    /// Returns the change of the aggression frequency at the root from the baseline to the
+    /// `index`-th entry.
+    #[inline]
+    pub fn aggression_shift(&self, index: usize) -> f32 {
+        self.entries[index].aggression_frequency - self.baseline.aggression_frequency
+    }
+
+    /// Returns the change of the frequency of each action at the root from the baseline to the
+    /// `index`-th entry, or `None` if the available actions differ (e.g., the bet sizes of the
+    /// root player are replaced).
+    pub fn frequency_shift(&self, index: usize) -> Option<Vec<f32>> {
+        let entry = &self.entries[index];
+        if entry.actions != self.baseline.actions {
+            return None;
+        }
+        let iter = entry.action_frequencies.iter();
+        let shift = iter.zip(&self.baseline.action_frequencies);
+        Some(shift.map(|(a, b)| a - b).collect())
+    }
+}
+
+impl fmt::Display for SensitivityReport {
+    /// Formats the report as a table with one row per configuration.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<40} {:>9} {:>9} {:>8} {:>9} {:>9} {:>8}",
+            "configuration", "EV OOP", "EV IP", "aggr", "dEV OOP", "dEV IP", "daggr"
+        )?;
+        let row = |f: &mut fmt::Formatter<'_>, label: &str, entry: &SensitivityEntry, shift| {
+            let [shift_oop, shift_ip, shift_aggression]: [f32; 3] = shift;
+            writeln!(
+                f,
+                "{label:<40} {:>9.3} {:>9.3} {:>7.1}% {shift_oop:>+9.3} {shift_ip:>+9.3} \
+                 {:>+7.1}%",
+                entry.ev[0],
+                entry.ev[1],
+                entry.aggression_frequency * 100.0,
+                shift_aggression * 100.0
+            )
+        };
+        row(f, "baseline", &self.baseline, [0.0; 3])?;
+        for (index, entry) in self.entries.iter().enumerate() {
+            let label = entry
+                .perturbation
+                .as_ref()
+                .map_or_else(String::new, |p| p.to_string());
+            let [shift_oop, shift_ip] = self.ev_shift(index);
+            let shift = [shift_oop, shift_ip, self.aggression_shift(index)];
+            row(f, &label, entry, shift)?;
+        }
+        Ok(())
+    }
+}
+
+/// Solves a baseline configuration and its perturbations, and reports how the headline results
+/// move.
+///
+/// The baseline is solved first, then each entry of `perturbations` is applied to the baseline
+/// (not cumulatively) and solved. Each entry reports the average expected values of the players
+/// and the action frequencies at the root, and [`SensitivityReport`] gives their shifts from the
+/// baseline. The games share a [`TableCache`] as in [`StackSweep`], and are solved one at a time.
+///
+/// Note that the expected values of a perturbed range are those of a different range, and that
+/// the bet sizes replaced by [`Perturbation::BetSizes`] are overridden by any matching
+/// [`TreeConfig::sizing_rules`].
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let sweep = SensitivitySweep {
+///     card_config: CardConfig {
+///         range: ["AA,KK,87s,65s,A5s".parse().unwrap(), "QQ-99,AK,KQs,T9s".parse().unwrap()],
+///         flop: flop_from_str("Td9d6h").unwrap(),
+///         turn: card_from_str("2c").unwrap(),
+///         river: card_from_str("3s").unwrap(),
+///         ..Default::default()
+///     },
+///     tree_config: TreeConfig {
+///         initial_state: BoardState::River,
+///         starting_pot: 100,
+///         effective_stack: 100,
+///         river_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+///         ..Default::default()
+///     },
+///     perturbations: vec![
+///         Perturbation::Rake(RakeConfig { rates: [0.05; 3], caps: [3.0; 3] }),
+///         Perturbation::RangeWidth { player: 0, fraction: 0.05 },
+///         Perturbation::RangeWidth { player: 0, fraction: -0.05 },
+///         Perturbation::BetSizes {
+///             street: BoardState::River,
+///             player: 0,
+///             sizes: ("75%", "").try_into().unwrap(),
+///         },
+///     ],
+///     max_num_iterations: 200,
+///     target_exploitability: 0.5,
+///     enable_compression: false,
+/// };
+///
+/// let report = sweep.run().unwrap();
+/// println!("{report}");
+/// assert!(report.ev_shift(0)[0] < 0.0); // the rake costs both players
+/// ```
+///
+/// [`StackSweep`]: crate::StackSweep
+#[derive(Debug, Clone, Default)]
+pub struct SensitivitySweep {
+    /// Card configuration of the baseline.
+    pub card_config: CardConfig,
+
+    /// Tree configuration of the baseline.
+    pub tree_config: TreeConfig,
+
+    /// Perturbations of the baseline to solve, in order.
+    pub perturbations: Vec<Perturbation>,
+
+    /// Maximum number of iterations of each solve.
+    pub max_num_iterations: u32,
+
+    /// Target exploitability of each solve.
+    pub target_exploitability: f32,
+
+    /// Whether to enable the compression of the storage.
+    pub enable_compression: bool,
+}
+
+impl SensitivitySweep {
+    /// Solves the baseline and every perturbation in order and returns the report.
+    ///
+    /// Stops at the first error (e.g., a perturbation that produces an invalid configuration).
+    /// The progress is reported as `tracing` events when the `tracing` feature is enabled.
+    #[inline]
+    pub fn run(&self) -> Result<SensitivityReport, SolverError> {
+        self.run_with(|_, _| Ok(()))
+    }
+
+    /// Same as [`run`], but calls `inspect` with each solved game (at the root node) and its
+    /// entry, e.g., to query the strategy deeper in the tree, to save the game, or to report the
+    /// progress. An error returned by `inspect` stops the sweep.
+    ///
+    /// [`run`]: #method.run
+    pub fn run_with<F>(&self, mut inspect: F) -> Result<SensitivityReport, SolverError>
+    where
+        F: FnMut(&mut PostFlopGame, &SensitivityEntry) -> Result<(), SolverError>,
+    {
+        let table_cache = TableCache::new(1)?;
+        let mut entries = Vec::with_capacity(self.perturbations.len() + 1);
+
+        let baseline = std::iter::once(None);
+        for perturbation in baseline.chain(self.perturbations.iter().map(Some)) {
+            let mut card_config = self.card_config.clone();
+            let mut tree_config = self.tree_config.clone();
+            if let Some(perturbation) = perturbation {
+                perturbation.apply(&mut card_config, &mut tree_config)?;
+            }
+
+            #[cfg(feature = "tracing")]
+            match perturbation {
+                Some(perturbation) => tracing::info!(%perturbation, "solving perturbation"),
+                None => tracing::info!("solving baseline"),
+            }
+
+            let action_tree = ActionTree::new(tree_config)?;
+            let mut game = PostFlopGame::new();
+            game.set_table_cache(Some(table_cache.clone()));
+            game.update_config(card_config, action_tree)?;
+
+            game.allocate_memory(self.enable_compression);
+            let exploitability = solve(
+                &mut game,
+                self.max_num_iterations,
+                self.target_exploitability,
+                false,
+            );
+            let num_iterations = game.solve_summary().map_or(0, |s| s.num_iterations);
+
+            game.cache_normalized_weights();
+            let ev = [0, 1].map(|player| {
+                compute_average(
+                    &game.expected_values(player),
+                    game.normalized_weights(player),
+                )
+            });
+
+            let actions = game.available_actions();
+            let action_frequencies = game.action_frequencies();
+            let aggression_frequency = actions
+                .iter()
+                .zip(&action_frequencies)
+                .filter(|(action, _)| {
+                    matches!(action, Action::Bet(_) | Action::Raise(_) | Action::AllIn(_))
+                })
+                .map(|(_, frequency)| frequency)
+                .sum();
+
+            let entry = SensitivityEntry {
+                perturbation: perturbation.cloned(),
+                num_iterations,
+                exploitability,
+                ev,
+                actions,
+                action_frequencies,
+                aggression_frequency,
+            };
+
+            inspect(&mut game, &entry)?;
+            entries.push(entry);
+        }
+
+        let baseline = entries.remove(0);
+        Ok(SensitivityReport { baseline, entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_width() {
+        let classes = hand_classes_by_strength(STRENGTH_NUM_SAMPLES, STRENGTH_SEED);
+        assert_eq!(classes[0].len(), 6); // AA
+        assert_eq!(classes[0][0], (48, 49));
+
+        let width = |range: &Range| {
+            let mut sum = 0.0;
+            for c1 in 0..52 {
+                for c2 in c1 + 1..52 {
+                    sum += range.get_weight_by_cards(c1, c2) as f64;
+                }
+            }
+            sum
+        };
+
+        let base: Range = "JJ+,AK".parse().unwrap();
+        let base_width = width(&base);
+
+        let mut wider = base;
+        change_range_width(&mut wider, &classes, 0, 0.05).unwrap();
+        assert!((width(&wider) - base_width * 1.05).abs() < 1e-3);
+        for c1 in 0..52 {
+            for c2 in c1 + 1..52 {
+                assert!(wider.get_weight_by_cards(c1, c2) >= base.get_weight_by_cards(c1, c2));
+            }
+        }
+
+        let mut narrower = base;
+        change_range_width(&mut narrower, &classes, 0, -0.05).unwrap();
+        assert!((width(&narrower) - base_width * 0.95).abs() < 1e-3);
+        assert_eq!(narrower.get_weight_by_cards(48, 49), 1.0); // AA is kept
+
+        change_range_width(&mut Range::new(), &classes, 0, 0.05).unwrap_err();
+    }
+
+    #[test]
+    fn sensitivity_sweep() {
+        let card_config = CardConfig {
+            range: [
+                "AA,KK,87s,65s,A5s".parse().unwrap(),
+                "QQ-99,AK,KQs,T9s".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("2c").unwrap(),
+            river: card_from_str("3s").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 100,
+            effective_stack: 100,
+            rake_rate: 0.05,
+            rake_cap: 3.0,
+            river_bet_sizes: [
+                ("50%", "").try_into().unwrap(),
+                ("50%", "").try_into().unwrap(),
+            ],
+            ..Default::default()
+        };
+
+        let mut sweep = SensitivitySweep {
+            card_config,
+            tree_config,
+            perturbations: vec![
+                Perturbation::RakeOff,
+                Perturbation::RangeWidth {
+                    player: 1,
+                    fraction: -0.05,
+                },
+                Perturbation::BetSizes {
+                    street: BoardState::River,
+                    player: 0,
+                    sizes: ("75%", "").try_into().unwrap(),
+                },
+            ],
+            max_num_iterations: 100,
+            target_exploitability: 0.0,
+            enable_compression: false,
+        };
+
+        let mut num_inspected = 0;
+        let report = sweep
+            .run_with(|game, entry| {
+                num_inspected += 1;
+                match &entry.perturbation {
+                    Some(Perturbation::RakeOff) => assert!(!game.tree_config().is_raked()),
+                    _ => assert!(game.tree_config().is_raked()),
+                }
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(num_inspected, 4);
+        assert_eq!(report.baseline.perturbation, None);
+        assert_eq!(report.entries.len(), 3);
+
+        // the rake is returned to the players
+        let [shift_oop, shift_ip] = report.ev_shift(0);
+        assert!((shift_oop + shift_ip - 3.0).abs() < 1e-2);
+        assert!(report.frequency_shift(0).is_some());
+
+        // the root actions change with the bet sizes of OOP
+        assert_eq!(report.entries[2].actions, [Action::Check, Action::Bet(75)]);
+        assert!(report.frequency_shift(2).is_none());
+        let frequencies = &report.entries[2].action_frequencies;
+        assert!((report.entries[2].aggression_frequency - frequencies[1]).abs() < 1e-6);
+
+        assert_eq!(report.to_string().lines().count(), 5);
+
+        sweep.perturbations.push(Perturbation::RangeWidth {
+            player: 2,
+            fraction: 0.05,
+        });
+        assert!(matches!(sweep.run(), Err(SolverError::InvalidArgument(_))));
+    }
+}
@@ -0,0 +1,1692 @@
+// [HTTP API]
+//  - `POST /jobs`: submits a solve job (see `parse_job` for the request body) and returns its
+//    `id`. Fails with 413 if the job alone exceeds the memory budget. Queued jobs are started in
+//    the descending order of `priority`, and in the order of submission for the same priority.
+//  - `GET /jobs`: returns the status of every job.
+//  - `GET /jobs/{id}`: returns the status of the job (see `status_json`).
+//  - `GET /jobs/{id}/progress`: streams the status as newline-delimited JSON until the job ends.
+//  - `POST /jobs/{id}/query`: returns the node reached by `history` (see `query_json`) of a
+//    finished job. With the `msgpack` or `cbor` feature, `Accept: application/msgpack` or
+//    `Accept: application/cbor` returns the `NodeQuery` of the node in the binary format instead.
+//  - `DELETE /jobs/{id}`: cancels the job if it is queued or running, and discards it.
+//  - `GET /metrics`: returns the metrics of the server in the Prometheus text format (see
+//    `metrics_text`).
+//
+// Errors are returned as `{"error": message}` with a 4xx status code, or with 503 if the queue of
+// pending requests is full (see `ServerConfig::max_pending_requests`). Request bodies larger than
+// `ServerConfig::max_body_size` are rejected with 413.
+//
+// [Persistence]
+// With `ServerConfig::state_dir`, each job is stored in the directory (see `JobStore`) so that the
+// server can be restarted without losing the jobs: finished jobs are restored with their solved
+// games, and unfinished ones are queued again and resumed from their last checkpoints.
+
+use crate::action_tree::*;
+use crate::bet_size::*;
+use crate::card::*;
+use crate::error::*;
+use crate::file::*;
+use crate::game::*;
+use crate::interface::*;
+use crate::range::*;
+use crate::solver::*;
+use crate::utility::*;
+use serde_json::{Value, json};
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, TrySendError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+use tiny_http::{Header, Method, Request, Response};
+
+/// Configuration of [`SolverServer`].
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Number of jobs solved concurrently. Each job is parallelized on its own as well.
+    pub num_workers: usize,
+
+    /// Upper bound of the total memory usage in bytes of the running and finished jobs. Finished
+    /// jobs keep their memory until they are deleted, and queued jobs wait until enough memory is
+    /// released.
+    pub memory_budget: u64,
+
+    /// Directory to persist the jobs in (default: `None`). The server restores the jobs stored in
+    /// the directory on startup, so it can be restarted without losing the queue and the solves.
+    pub state_dir: Option<PathBuf>,
+
+    /// Number of times a failed job is queued again before it is reported as failed (default: `0`).
+    pub max_retries: u32,
+
+    /// Number of iterations between the checkpoints of a running job (default: `100`). A retried or
+    /// restored job resumes from its last checkpoint. Checkpoints are saved only with `state_dir`,
+    /// and `0` disables them.
+    pub checkpoint_interval: u32,
+
+    /// Number of threads handling the HTTP requests (default: `8`). A progress stream occupies a
+    /// thread until its job ends.
+    pub num_handlers: usize,
+
+    /// Number of requests waiting for a free handler thread (default: `64`). Further requests are
+    /// rejected with 503 until the queue has room.
+    pub max_pending_requests: usize,
+
+    /// Maximum size in bytes of a request body (default: `1 << 20`). Larger requests are rejected
+    /// with 413 without reading the rest of the body.
+    pub max_body_size: usize,
+}
+
+impl Default for ServerConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            num_workers: 1,
+            memory_budget: u64::MAX,
+            state_dir: None,
+            max_retries: 0,
+            checkpoint_interval: 100,
+            num_handlers: 8,
+            max_pending_requests: 64,
+            max_body_size: 1 << 20,
+        }
+    }
+}
+
+/// An HTTP server that solves submitted games on a worker pool and serves queries against the
+/// finished solves.
+///
+/// The server is started by [`run`], which blocks the calling thread until [`shutdown`] is called
+/// (e.g., from another thread). The endpoints are listed in the source of this module.
+///
+/// [`run`]: SolverServer::run
+/// [`shutdown`]: SolverServer::shutdown
+///
+/// # Examples
+/// ```no_run
+/// use postflop_solver::*;
+///
+/// let config = ServerConfig {
+///     num_workers: 2,
+///     memory_budget: 16 << 30,
+///     state_dir: Some("solver-jobs".into()),
+///     ..Default::default()
+/// };
+/// let server = SolverServer::bind("0.0.0.0:8080", config).unwrap();
+/// server.run();
+/// ```
+pub struct SolverServer {
+    http: tiny_http::Server,
+    shared: Arc<Shared>,
+    workers: Mutex<Vec<JoinHandle<()>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum JobState {
+    Queued,
+    Running,
+    Finished,
+    Failed(String),
+    Cancelled,
+}
+
+impl JobState {
+    #[inline]
+    fn is_done(&self) -> bool {
+        !matches!(self, Self::Queued | Self::Running)
+    }
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Finished => "finished",
+            Self::Failed(_) => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct JobStatus {
+    state: JobState,
+    iteration: u32,
+    exploitability: Option<f32>,
+    attempts: u32,
+    started_at: Option<Instant>,
+    version: u64,
+}
+
+struct Job {
+    id: u64,
+    priority: i32,
+    spec: Value,
+    memory_usage: u64,
+    max_iterations: u32,
+    target_exploitability: f32,
+    enable_compression: bool,
+    cancelled: AtomicBool,
+    status: Mutex<JobStatus>,
+    status_changed: Condvar,
+    game: Mutex<Option<PostFlopGame>>,
+}
+
+#[derive(Default)]
+struct Scheduler {
+    jobs: HashMap<u64, Arc<Job>>,
+    queue: VecDeque<Arc<Job>>,
+    memory_used: u64,
+    is_shutdown: bool,
+}
+
+struct Shared {
+    config: ServerConfig,
+    store: Option<JobStore>,
+    scheduler: Mutex<Scheduler>,
+    job_available: Condvar,
+    next_id: AtomicU64,
+    iterations_total: AtomicU64,
+}
+
+/// Locks the mutex, recovering from poisoning (the protected data is updated atomically).
+#[inline]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[inline]
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_owned()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Unknown panic".to_owned()
+    }
+}
+
+impl Job {
+    fn update_status(&self, f: impl FnOnce(&mut JobStatus)) {
+        let mut status = lock(&self.status);
+        f(&mut status);
+        status.version += 1;
+        self.status_changed.notify_all();
+    }
+
+    fn status_json(&self) -> Value {
+        let status = lock(&self.status).clone();
+        status_json(self, &status)
+    }
+}
+
+/// Formats the status of a job: `id`, `state` (`queued`, `running`, `finished`, `failed`, or
+/// `cancelled`), `priority`, `iteration`, `exploitability` (`null` before the first evaluation),
+/// `memory_usage`, `attempts` (number of failed attempts), and `error` (`null` unless failed).
+fn status_json(job: &Job, status: &JobStatus) -> Value {
+    let error = match &status.state {
+        JobState::Failed(message) => Some(message.as_str()),
+        _ => None,
+    };
+    json!({
+        "id": job.id,
+        "state": status.state.name(),
+        "priority": job.priority,
+        "iteration": status.iteration,
+        "exploitability": status.exploitability,
+        "memory_usage": job.memory_usage,
+        "attempts": status.attempts,
+        "error": error,
+    })
+}
+
+/// Files of the jobs persisted in [`ServerConfig::state_dir`]:
+///
+/// - `job-{id}.json`: the status of the job (see [`status_json`]) with the submitted body as `job`.
+///   Written atomically whenever the job is submitted, checkpointed, retried, or done.
+/// - `job-{id}.ckpt`: the last checkpoint of an unfinished job (see [`save_resumable_checkpoint`];
+///   the memo is the number of completed iterations).
+/// - `job-{id}.bin`: the solved game of a finished job.
+struct JobStore {
+    dir: PathBuf,
+}
+
+impl JobStore {
+    fn new(dir: PathBuf) -> Result<Self, SolverError> {
+        fs::create_dir_all(&dir).map_err(|e| SolverError::io("Failed to create directory", &e))?;
+        Ok(Self { dir })
+    }
+
+    #[inline]
+    fn path(&self, id: u64, extension: &str) -> PathBuf {
+        self.dir.join(format!("job-{id}.{extension}"))
+    }
+
+    fn save_record(&self, job: &Job) -> Result<(), String> {
+        let mut record = job.status_json();
+        record["job"] = job.spec.clone();
+        let path = self.path(job.id, "json");
+        let temp_path = self.path(job.id, "json.tmp");
+        fs::write(&temp_path, record.to_string())
+            .map_err(|e| format!("Failed to write file: {e}"))?;
+        fs::rename(&temp_path, &path).map_err(|e| format!("Failed to rename file: {e}"))
+    }
+
+    /// Returns the records of the stored jobs in the order of `id`.
+    fn load_records(&self) -> Result<Vec<Value>, SolverError> {
+        let read_dir_err = |e: std::io::Error| SolverError::io("Failed to read directory", &e);
+        let entries = fs::read_dir(&self.dir).map_err(read_dir_err)?;
+        let mut records = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(read_dir_err)?.path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if !(name.starts_with("job-") && name.ends_with(".json")) {
+                continue;
+            }
+            let text = fs::read_to_string(&path)
+                .map_err(|e| SolverError::io("Failed to read file", &e))?;
+            let record = serde_json::from_str::<Value>(&text)
+                .map_err(|e| SolverError::InvalidData(format!("Invalid job record {name}: {e}")))?;
+            records.push(record);
+        }
+        records.sort_unstable_by_key(|record| record["id"].as_u64());
+        Ok(records)
+    }
+
+    fn remove(&self, id: u64) {
+        for extension in ["json", "ckpt", "bin"] {
+            fs::remove_file(self.path(id, extension)).ok();
+        }
+    }
+}
+
+fn field<'a>(value: &'a Value, key: &str) -> Result<&'a Value, String> {
+    value
+        .get(key)
+        .ok_or_else(|| format!("Missing field: {key}"))
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Result<&'a str, String> {
+    field(value, key)?
+        .as_str()
+        .ok_or_else(|| format!("Field must be a string: {key}"))
+}
+
+fn f64_field(value: &Value, key: &str, default: Option<f64>) -> Result<f64, String> {
+    match (value.get(key), default) {
+        (None | Some(Value::Null), Some(default)) => Ok(default),
+        (Some(x), _) => x
+            .as_f64()
+            .ok_or_else(|| format!("Field must be a number: {key}")),
+        (None, None) => Err(format!("Missing field: {key}")),
+    }
+}
+
+fn i32_field(value: &Value, key: &str) -> Result<i32, String> {
+    field(value, key)?
+        .as_i64()
+        .and_then(|x| i32::try_from(x).ok())
+        .ok_or_else(|| format!("Field must be a 32-bit integer: {key}"))
+}
+
+/// Joins an array of size strings (e.g., `["50%", "100%"]`) with commas.
+fn sizes_field(value: &Value, key: &str) -> Result<String, String> {
+    let err = || format!("Field must be an array of strings: {key}");
+    match value.get(key) {
+        None | Some(Value::Null) => Ok(String::new()),
+        Some(Value::Array(sizes)) => {
+            let sizes = sizes
+                .iter()
+                .map(|size| size.as_str().ok_or_else(err))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(sizes.join(","))
+        }
+        Some(_) => Err(err()),
+    }
+}
+
+fn bet_sizes_field(value: &Value, key: &str) -> Result<[BetSizeOptions; 2], String> {
+    let mut ret = [BetSizeOptions::default(), BetSizeOptions::default()];
+    match value.get(key) {
+        None | Some(Value::Null) => {}
+        Some(Value::Array(players)) if players.len() == 2 => {
+            for (options, player) in ret.iter_mut().zip(players) {
+                let bet = sizes_field(player, "bet")?;
+                let raise = sizes_field(player, "raise")?;
+                *options = BetSizeOptions::try_from((bet.as_str(), raise.as_str()))
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        Some(_) => return Err(format!("Field must be an array of two objects: {key}")),
+    }
+    Ok(ret)
+}
+
+fn donk_sizes_field(value: &Value, key: &str) -> Result<Option<DonkSizeOptions>, String> {
+    match value.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        _ => DonkSizeOptions::try_from(sizes_field(value, key)?.as_str())
+            .map(Some)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Parses the body of a job submission:
+///
+/// - `ranges`: range strings of OOP and IP.
+/// - `board`: board cards (e.g., `"Td9d6h"`), which determine the initial street.
+/// - `tree_config`: `starting_pot`, `effective_stack`, and optionally `rake_rate`, `rake_cap`,
+///   `{flop,turn,river}_bet_sizes` (`[{"bet": [..], "raise": [..]}, ..]` for OOP and IP),
+///   `{turn,river}_donk_sizes`, `add_allin_threshold`, `force_allin_threshold`, and
+///   `merging_threshold`, in the same format as [`PostFlopGame::export_json`].
+/// - `max_iterations` and `target_exploitability` (in chips): stopping conditions.
+/// - `enable_compression` (optional): whether to compress the storage.
+/// - `priority` (optional): 32-bit integer; jobs with higher priorities are started first
+///   (default: `0`; parsed by [`priority_field`]).
+fn parse_job(body: &Value) -> Result<(PostFlopGame, u32, f32, bool), String> {
+    let ranges = field(body, "ranges")?
+        .as_array()
+        .filter(|ranges| ranges.len() == 2)
+        .ok_or("Field must be an array of two strings: ranges")?;
+    let parse_range = |range: &Value| {
+        range
+            .as_str()
+            .ok_or_else(|| "Field must be an array of two strings: ranges".to_owned())?
+            .parse::<Range>()
+            .map_err(|e| e.to_string())
+    };
+
+    let board = board_from_str(str_field(body, "board")?).map_err(|e| e.to_string())?;
+    let card_config = CardConfig {
+        range: [parse_range(&ranges[0])?, parse_range(&ranges[1])?],
+        flop: [board[0], board[1], board[2]],
+        turn: board.get(3).copied().unwrap_or(NOT_DEALT),
+        river: board.get(4).copied().unwrap_or(NOT_DEALT),
+        ..Default::default()
+    };
+
+    let config = field(body, "tree_config")?;
+    let tree_config = TreeConfig {
+        initial_state: match board.len() {
+            3 => BoardState::Flop,
+            4 => BoardState::Turn,
+            _ => BoardState::River,
+        },
+        starting_pot: i32_field(config, "starting_pot")?,
+        effective_stack: i32_field(config, "effective_stack")?,
+        rake_rate: f64_field(config, "rake_rate", Some(0.0))?,
+        rake_cap: f64_field(config, "rake_cap", Some(0.0))?,
+        flop_bet_sizes: bet_sizes_field(config, "flop_bet_sizes")?,
+        turn_bet_sizes: bet_sizes_field(config, "turn_bet_sizes")?,
+        river_bet_sizes: bet_sizes_field(config, "river_bet_sizes")?,
+        turn_donk_sizes: donk_sizes_field(config, "turn_donk_sizes")?,
+        river_donk_sizes: donk_sizes_field(config, "river_donk_sizes")?,
+        add_allin_threshold: f64_field(config, "add_allin_threshold", Some(0.0))?,
+        force_allin_threshold: f64_field(config, "force_allin_threshold", Some(0.0))?,
+        merging_threshold: f64_field(config, "merging_threshold", Some(0.0))?,
+        ..Default::default()
+    };
+
+    let max_iterations = field(body, "max_iterations")?
+        .as_u64()
+        .and_then(|x| u32::try_from(x).ok())
+        .ok_or("Field must be a 32-bit unsigned integer: max_iterations")?;
+    let target_exploitability = f64_field(body, "target_exploitability", None)? as f32;
+    let enable_compression = match body.get("enable_compression") {
+        None | Some(Value::Null) => false,
+        Some(x) => x
+            .as_bool()
+            .ok_or("Field must be a boolean: enable_compression")?,
+    };
+
+    let action_tree = ActionTree::new(tree_config).map_err(|e| e.to_string())?;
+    let game = PostFlopGame::with_config(card_config, action_tree).map_err(|e| e.to_string())?;
+    Ok((
+        game,
+        max_iterations,
+        target_exploitability,
+        enable_compression,
+    ))
+}
+
+fn priority_field(body: &Value) -> Result<i32, String> {
+    match body.get("priority") {
+        None | Some(Value::Null) => Ok(0),
+        Some(_) => i32_field(body, "priority"),
+    }
+}
+
+/// Formats the node reached by `history` from the root node: `history`, `type` (`player`,
+/// `chance`, or `terminal`), `board`, `pot`, `hands` (private hands of OOP and IP), `equity` and
+/// `ev` (per hand of OOP and IP), and additionally `player`, `actions`, and `strategy` (indexed by
+/// action and then by hand) at player nodes and `possible_cards` at chance nodes.
+fn query_json(game: &mut PostFlopGame, history: &[usize]) -> Result<Value, String> {
+    game.apply_history(history);
+    game.cache_normalized_weights();
+
+    let cards = |cards: &[Card]| {
+        cards
+            .iter()
+            .map(|&card| card_to_string(card).unwrap_or_default())
+            .collect::<Vec<_>>()
+    };
+    let hands = [0, 1].map(|player| holes_to_strings(game.private_cards(player)));
+    let bet = game.total_bet_amount();
+
+    let mut ret = json!({
+        "history": history,
+        "board": cards(&game.current_board()),
+        "pot": game.tree_config().starting_pot + bet[0] + bet[1],
+        "hands": [hands[0].clone().map_err(|e| e.to_string())?, hands[1].clone().map_err(|e| e.to_string())?],
+        "equity": [game.equity(0), game.equity(1)],
+        "ev": [game.expected_values(0), game.expected_values(1)],
+    });
+
+    if game.is_terminal_node() {
+        ret["type"] = json!("terminal");
+    } else if game.is_chance_node() {
+        let mask = game.possible_cards();
+        let possible = (0..52)
+            .filter(|&card| mask & (1 << card) != 0)
+            .collect::<Vec<_>>();
+        ret["type"] = json!("chance");
+        ret["possible_cards"] = json!(cards(&possible));
+    } else {
+        let player = game.current_player();
+        let num_hands = game.private_cards(player).len();
+        let actions = game.available_actions();
+        let strategy = game.strategy();
+        ret["type"] = json!("player");
+        ret["player"] = json!(player);
+        ret["actions"] = json!(actions.iter().map(Action::to_string).collect::<Vec<_>>());
+        ret["strategy"] = json!(strategy.chunks(num_hands.max(1)).collect::<Vec<_>>());
+    }
+
+    Ok(ret)
+}
+
+/// Formats the metrics of the server in the Prometheus text exposition format:
+///
+/// - `postflop_solver_jobs{state}`: number of jobs in each state (deleted jobs are not counted).
+/// - `postflop_solver_queue_depth`: number of queued jobs.
+/// - `postflop_solver_memory_used_bytes` and `postflop_solver_memory_budget_bytes`: memory
+///   reserved by the running and finished jobs, and its upper bound.
+/// - `postflop_solver_iterations_total`: number of iterations performed by all jobs.
+/// - `postflop_solver_job_iteration{job}`, `postflop_solver_job_iterations_per_second{job}`, and
+///   `postflop_solver_job_exploitability{job}`: progress of each running job.
+fn metrics_text(shared: &Shared) -> String {
+    let (jobs, queue_depth, memory_used) = {
+        let scheduler = lock(&shared.scheduler);
+        let mut jobs = scheduler.jobs.values().cloned().collect::<Vec<_>>();
+        jobs.sort_unstable_by_key(|job| job.id);
+        (jobs, scheduler.queue.len(), scheduler.memory_used)
+    };
+    let statuses = jobs
+        .iter()
+        .map(|job| lock(&job.status).clone())
+        .collect::<Vec<_>>();
+
+    let mut text = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, samples: &[(String, String)]| {
+        writeln!(text, "# HELP postflop_solver_{name} {help}").unwrap();
+        writeln!(text, "# TYPE postflop_solver_{name} {kind}").unwrap();
+        for (labels, value) in samples {
+            writeln!(text, "postflop_solver_{name}{labels} {value}").unwrap();
+        }
+    };
+
+    let states = ["queued", "running", "finished", "failed", "cancelled"];
+    let counts = states.map(|state| {
+        let count = statuses.iter().filter(|s| s.state.name() == state).count();
+        (format!("{{state=\"{state}\"}}"), count.to_string())
+    });
+    metric("jobs", "gauge", "Number of jobs by state.", &counts);
+
+    let single = |value: String| [(String::new(), value)];
+    let budget = shared.config.memory_budget;
+    let iterations_total = shared.iterations_total.load(Ordering::Relaxed);
+    metric(
+        "queue_depth",
+        "gauge",
+        "Number of queued jobs.",
+        &single(queue_depth.to_string()),
+    );
+    metric(
+        "memory_used_bytes",
+        "gauge",
+        "Memory reserved by the running and finished jobs.",
+        &single(memory_used.to_string()),
+    );
+    metric(
+        "memory_budget_bytes",
+        "gauge",
+        "Upper bound of the memory reserved by the jobs.",
+        &single(budget.to_string()),
+    );
+    metric(
+        "iterations_total",
+        "counter",
+        "Number of iterations performed by all jobs.",
+        &single(iterations_total.to_string()),
+    );
+
+    let running = jobs
+        .iter()
+        .zip(&statuses)
+        .filter(|(_, status)| status.state == JobState::Running)
+        .collect::<Vec<_>>();
+    let per_job = |f: &dyn Fn(&JobStatus) -> Option<String>| {
+        running
+            .iter()
+            .filter_map(|(job, status)| f(status).map(|v| (format!("{{job=\"{}\"}}", job.id), v)))
+            .collect::<Vec<_>>()
+    };
+
+    let iterations = per_job(&|status| Some(status.iteration.to_string()));
+    let speeds = per_job(&|status| {
+        let elapsed = status.started_at?.elapsed().as_secs_f64();
+        (elapsed > 0.0).then(|| (status.iteration as f64 / elapsed).to_string())
+    });
+    let exploitabilities = per_job(&|status| status.exploitability.map(|x| x.to_string()));
+    metric(
+        "job_iteration",
+        "gauge",
+        "Number of iterations performed by each running job.",
+        &iterations,
+    );
+    metric(
+        "job_iterations_per_second",
+        "gauge",
+        "Average number of iterations per second of each running job.",
+        &speeds,
+    );
+    metric(
+        "job_exploitability",
+        "gauge",
+        "Last evaluated exploitability of each running job.",
+        &exploitabilities,
+    );
+
+    text
+}
+
+impl Shared {
+    fn submit(&self, body: &Value) -> Result<Arc<Job>, (u16, String)> {
+        let priority = priority_field(body).map_err(|e| (400, e))?;
+        let (game, max_iterations, target_exploitability, enable_compression) =
+            parse_job(body).map_err(|e| (400, e))?;
+
+        let (uncompressed, compressed) = game.memory_usage();
+        let memory_usage = if enable_compression {
+            compressed
+        } else {
+            uncompressed
+        };
+        if memory_usage > self.config.memory_budget {
+            return Err((
+                413,
+                format!(
+                    "Memory usage exceeds the budget: {memory_usage} > {}",
+                    self.config.memory_budget
+                ),
+            ));
+        }
+
+        let job = Arc::new(Job {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            priority,
+            spec: body.clone(),
+            memory_usage,
+            max_iterations,
+            target_exploitability,
+            enable_compression,
+            cancelled: AtomicBool::new(false),
+            status: Mutex::new(JobStatus {
+                state: JobState::Queued,
+                iteration: 0,
+                exploitability: None,
+                attempts: 0,
+                started_at: None,
+                version: 0,
+            }),
+            status_changed: Condvar::new(),
+            game: Mutex::new(Some(game)),
+        });
+
+        let mut scheduler = lock(&self.scheduler);
+        if scheduler.is_shutdown {
+            return Err((503, "Server is shutting down".to_owned()));
+        }
+        if let Some(store) = &self.store {
+            store.save_record(&job).map_err(|e| (500, e))?;
+        }
+        scheduler.jobs.insert(job.id, Arc::clone(&job));
+        scheduler.queue.push_back(Arc::clone(&job));
+        self.job_available.notify_all();
+        Ok(job)
+    }
+
+    fn job(&self, id: u64) -> Option<Arc<Job>> {
+        lock(&self.scheduler).jobs.get(&id).cloned()
+    }
+
+    /// Restores the jobs stored in [`ServerConfig::state_dir`]. Finished jobs reserve their memory
+    /// regardless of the budget, and unfinished ones are queued again.
+    fn restore(&self) -> Result<(), SolverError> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+
+        let mut scheduler = lock(&self.scheduler);
+        for record in store.load_records()? {
+            let invalid = SolverError::InvalidData;
+            let id = record["id"]
+                .as_u64()
+                .ok_or_else(|| invalid("Invalid job record".to_owned()))?;
+            let spec = field(&record, "job").map_err(invalid)?;
+            let (game, max_iterations, target_exploitability, enable_compression) =
+                parse_job(spec).map_err(invalid)?;
+
+            let (state, game) = match record["state"].as_str() {
+                Some("finished") => match load_data_from_file(store.path(id, "bin"), None) {
+                    Ok((game, _)) => (JobState::Finished, Some(game)),
+                    Err(error) => (JobState::Failed(error.to_string()), None),
+                },
+                Some("failed") => {
+                    let message = record["error"].as_str().unwrap_or_default().to_owned();
+                    (JobState::Failed(message), None)
+                }
+                _ => (JobState::Queued, Some(game)),
+            };
+
+            let job = Arc::new(Job {
+                id,
+                priority: priority_field(spec).map_err(invalid)?,
+                spec: spec.clone(),
+                memory_usage: record["memory_usage"].as_u64().unwrap_or_default(),
+                max_iterations,
+                target_exploitability,
+                enable_compression,
+                cancelled: AtomicBool::new(false),
+                status: Mutex::new(JobStatus {
+                    state,
+                    iteration: record["iteration"].as_u64().unwrap_or_default() as u32,
+                    exploitability: record["exploitability"].as_f64().map(|x| x as f32),
+                    attempts: record["attempts"].as_u64().unwrap_or_default() as u32,
+                    started_at: None,
+                    version: 0,
+                }),
+                status_changed: Condvar::new(),
+                game: Mutex::new(game),
+            });
+
+            match lock(&job.status).state {
+                JobState::Queued => scheduler.queue.push_back(Arc::clone(&job)),
+                JobState::Finished => scheduler.memory_used += job.memory_usage,
+                _ => {}
+            }
+            scheduler.jobs.insert(id, job);
+            self.next_id.fetch_max(id + 1, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+
+    /// Cancels and discards the job, releasing its memory if it is finished.
+    fn delete(&self, id: u64) -> bool {
+        let mut scheduler = lock(&self.scheduler);
+        let Some(job) = scheduler.jobs.remove(&id) else {
+            return false;
+        };
+        job.cancelled.store(true, Ordering::Relaxed);
+        scheduler.queue.retain(|queued| queued.id != id);
+        if let Some(store) = &self.store {
+            store.remove(id);
+        }
+
+        // a running job releases its memory by itself
+        let state = lock(&job.status).state.clone();
+        match state {
+            JobState::Queued => job.update_status(|status| status.state = JobState::Cancelled),
+            JobState::Finished => {
+                scheduler.memory_used -= job.memory_usage;
+                lock(&job.game).take();
+                self.job_available.notify_all();
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Waits for a queued job that fits in the memory budget and reserves its memory. The job with
+    /// the highest priority is taken first. Returns `None` on shutdown.
+    fn next_job(&self) -> Option<Arc<Job>> {
+        let mut scheduler = lock(&self.scheduler);
+        loop {
+            if scheduler.is_shutdown {
+                return None;
+            }
+            let available = self
+                .config
+                .memory_budget
+                .saturating_sub(scheduler.memory_used);
+            let index = scheduler
+                .queue
+                .iter()
+                .enumerate()
+                .filter(|(_, job)| job.memory_usage <= available)
+                .max_by_key(|&(index, job)| (job.priority, Reverse(index)))
+                .map(|(index, _)| index);
+            if let Some(job) = index.and_then(|index| scheduler.queue.remove(index)) {
+                scheduler.memory_used += job.memory_usage;
+                job.update_status(|status| {
+                    status.state = JobState::Running;
+                    status.started_at = Some(Instant::now());
+                });
+                return Some(job);
+            }
+            scheduler = self
+                .job_available
+                .wait(scheduler)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// Takes the game of the job, resuming from its checkpoint if any. Returns the game and the
+    /// number of completed iterations.
+    fn prepare_game(&self, job: &Job) -> Result<(PostFlopGame, u32), String> {
+        let game = lock(&job.game).take();
+        if let Some(store) = &self.store {
+            let path = store.path(job.id, "ckpt");
+            if path.exists() {
+                let result = load_resumable_checkpoint(&path, None)
+                    .map_err(|e| e.to_string())
+                    .and_then(|(game, memo)| {
+                        let iteration =
+                            memo.parse().map_err(|_| "Invalid checkpoint".to_owned())?;
+                        Ok((game, iteration))
+                    });
+                if result.is_err() {
+                    // starts over on the retry
+                    fs::remove_file(&path).ok();
+                    *lock(&job.game) = game;
+                }
+                return result;
+            }
+        }
+
+        match game {
+            Some(game) => Ok((game, 0)),
+            None => parse_job(&job.spec).map(|(game, ..)| (game, 0)),
+        }
+    }
+
+    /// Solves the job and saves the solved game. Returns `None` if the job is cancelled.
+    fn run_job(&self, job: &Job) -> Result<Option<PostFlopGame>, String> {
+        let (mut game, start_iteration) = self.prepare_game(job)?;
+        solve_job(job, &mut game, start_iteration, self)?;
+        if job.cancelled.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        if let Some(store) = &self.store {
+            save_data_to_file(&game, "", store.path(job.id, "bin"), None)
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(Some(game))
+    }
+
+    fn worker(&self) {
+        while let Some(job) = self.next_job() {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| self.run_job(&job)))
+                .unwrap_or_else(|payload| Err(panic_message(&*payload)));
+
+            let mut scheduler = lock(&self.scheduler);
+            let is_cancelled = job.cancelled.load(Ordering::Relaxed);
+            let attempts = lock(&job.status).attempts;
+            let state = match result {
+                Ok(Some(game)) => {
+                    *lock(&job.game) = Some(game);
+                    JobState::Finished
+                }
+                Ok(None) => JobState::Cancelled,
+                Err(_) if !is_cancelled && attempts < self.config.max_retries => JobState::Queued,
+                Err(message) => JobState::Failed(message),
+            };
+            if state != JobState::Finished {
+                scheduler.memory_used -= job.memory_usage;
+                self.job_available.notify_all();
+            }
+            if state == JobState::Queued {
+                scheduler.queue.push_back(Arc::clone(&job));
+            }
+            job.update_status(|status| {
+                status.attempts += matches!(state, JobState::Queued | JobState::Failed(_)) as u32;
+                status.state = state.clone();
+            });
+
+            // cancelled jobs are kept in the store to be resumed on restart unless deleted
+            if let Some(store) = &self.store {
+                if !scheduler.jobs.contains_key(&job.id) {
+                    store.remove(job.id);
+                } else if state != JobState::Cancelled {
+                    store.save_record(&job).ok();
+                    if state.is_done() {
+                        fs::remove_file(store.path(job.id, "ckpt")).ok();
+                    }
+                }
+            }
+        }
+    }
+
+    fn shutdown(&self) {
+        let mut scheduler = lock(&self.scheduler);
+        scheduler.is_shutdown = true;
+        for job in scheduler.jobs.values() {
+            job.cancelled.store(true, Ordering::Relaxed);
+        }
+        for job in scheduler.queue.drain(..) {
+            job.update_status(|status| status.state = JobState::Cancelled);
+        }
+        self.job_available.notify_all();
+    }
+}
+
+/// Solves the game of the job from `start_iteration`, reporting the progress every 10 iterations
+/// and saving a checkpoint every [`ServerConfig::checkpoint_interval`] iterations. Returns early
+/// without finalizing the game if the job is cancelled.
+fn solve_job(
+    job: &Job,
+    game: &mut PostFlopGame,
+    start_iteration: u32,
+    shared: &Shared,
+) -> Result<(), String> {
+    if !game.is_ready() {
+        game.allocate_memory(job.enable_compression);
+    }
+
+    let mut exploitability = compute_exploitability(game);
+    job.update_status(|status| {
+        status.iteration = start_iteration;
+        status.exploitability = Some(exploitability);
+    });
+
+    let interval = shared.config.checkpoint_interval;
+    let mut num_iterations = start_iteration;
+    while num_iterations < job.max_iterations && exploitability > job.target_exploitability {
+        if job.cancelled.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        solve_step(game, num_iterations);
+        num_iterations += 1;
+        shared.iterations_total.fetch_add(1, Ordering::Relaxed);
+
+        if num_iterations.is_multiple_of(10) || num_iterations == job.max_iterations {
+            exploitability = compute_exploitability(game);
+            job.update_status(|status| {
+                status.iteration = num_iterations;
+                status.exploitability = Some(exploitability);
+            });
+        }
+
+        if let Some(store) = &shared.store
+            && interval > 0
+            && num_iterations.is_multiple_of(interval)
+        {
+            let memo = num_iterations.to_string();
+            save_resumable_checkpoint(game, &memo, store.path(job.id, "ckpt"))
+                .map_err(|e| e.to_string())?;
+            store.save_record(job)?;
+        }
+    }
+
+    game.set_solve_result(num_iterations, exploitability);
+    finalize(game);
+    job.update_status(|status| {
+        status.iteration = num_iterations;
+        status.exploitability = Some(exploitability);
+    });
+    Ok(())
+}
+
+/// Encoding of a query response, negotiated by the `Accept` header of the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QueryEncoding {
+    Json,
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+    #[cfg(feature = "cbor")]
+    Cbor,
+}
+
+impl QueryEncoding {
+    /// Returns the encoding requested by the `Accept` header of the request (JSON by default).
+    fn from_request(request: &Request) -> Self {
+        let accept = request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv("Accept"))
+            .map(|header| header.value.as_str())
+            .unwrap_or_default();
+
+        let encodings: &[(Self, &str)] = &[
+            #[cfg(feature = "msgpack")]
+            (Self::MessagePack, "application/msgpack"),
+            #[cfg(feature = "cbor")]
+            (Self::Cbor, "application/cbor"),
+        ];
+        encodings
+            .iter()
+            .find(|(_, mime)| accept.contains(mime))
+            .map_or(Self::Json, |&(encoding, _)| encoding)
+    }
+}
+
+fn query_response(
+    game: &mut PostFlopGame,
+    history: &[usize],
+    encoding: QueryEncoding,
+) -> Result<Response<std::io::Cursor<Vec<u8>>>, String> {
+    match encoding {
+        QueryEncoding::Json => Ok(json_response(200, &query_json(game, history)?)),
+        #[cfg(feature = "msgpack")]
+        QueryEncoding::MessagePack => {
+            game.apply_history(history);
+            let bytes = game
+                .query_node()
+                .and_then(|node| node.to_msgpack())
+                .map_err(|e| e.to_string())?;
+            Ok(binary_response("application/msgpack", bytes))
+        }
+        #[cfg(feature = "cbor")]
+        QueryEncoding::Cbor => {
+            game.apply_history(history);
+            let bytes = game
+                .query_node()
+                .and_then(|node| node.to_cbor())
+                .map_err(|e| e.to_string())?;
+            Ok(binary_response("application/cbor", bytes))
+        }
+    }
+}
+
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+fn binary_response(content_type: &str, bytes: Vec<u8>) -> Response<std::io::Cursor<Vec<u8>>> {
+    let header = Header::from_bytes("Content-Type", content_type).unwrap();
+    Response::from_data(bytes).with_header(header)
+}
+
+fn json_response(code: u16, value: &Value) -> Response<std::io::Cursor<Vec<u8>>> {
+    let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+    Response::from_string(value.to_string())
+        .with_status_code(code)
+        .with_header(header)
+}
+
+#[inline]
+fn error_response(code: u16, message: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+    json_response(code, &json!({ "error": message }))
+}
+
+/// Reads the body of `request` as JSON. Returns the status code and the message on failure.
+fn read_json(request: &mut Request, max_body_size: usize) -> Result<Value, (u16, String)> {
+    let too_large = || (413, format!("Request body exceeds {max_body_size} bytes"));
+    if request
+        .body_length()
+        .is_some_and(|length| length > max_body_size)
+    {
+        return Err(too_large());
+    }
+
+    // the body length is unknown for chunked requests, so at most one byte more is read
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(max_body_size as u64 + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| (400, format!("Failed to read request: {e}")))?;
+    if body.len() > max_body_size {
+        return Err(too_large());
+    }
+
+    serde_json::from_str(&body).map_err(|e| (400, format!("Invalid JSON: {e}")))
+}
+
+/// Writes the status of the job as newline-delimited JSON whenever it changes, until the job ends.
+/// Each line is sent as a chunk so that clients receive it immediately.
+fn stream_progress(request: Request, job: &Job) {
+    let mut writer = request.into_writer();
+    let header = "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\n\
+                  Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n";
+    if writer.write_all(header.as_bytes()).is_err() {
+        return;
+    }
+
+    let mut version = None;
+    loop {
+        let status = {
+            let mut status = lock(&job.status);
+            while version == Some(status.version) {
+                status = job
+                    .status_changed
+                    .wait(status)
+                    .unwrap_or_else(|e| e.into_inner());
+            }
+            status.clone()
+        };
+        version = Some(status.version);
+
+        let line = format!("{}\n", status_json(job, &status));
+        let mut chunk = format!("{:x}\r\n{line}\r\n", line.len());
+        if status.state.is_done() {
+            chunk.push_str("0\r\n\r\n");
+        }
+        if writer.write_all(chunk.as_bytes()).is_err() || writer.flush().is_err() {
+            return;
+        }
+        if status.state.is_done() {
+            return;
+        }
+    }
+}
+
+fn handle(shared: &Shared, mut request: Request) {
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_owned();
+    let segments = path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+    let method = request.method().clone();
+
+    let job = match segments.as_slice() {
+        ["jobs", id, ..] => match id.parse().ok().and_then(|id| shared.job(id)) {
+            Some(job) => Some(job),
+            None => {
+                request.respond(error_response(404, "Job not found")).ok();
+                return;
+            }
+        },
+        _ => None,
+    };
+
+    let response = match (&method, segments.as_slice(), job) {
+        (Method::Post, ["jobs"], _) => {
+            match read_json(&mut request, shared.config.max_body_size)
+                .and_then(|body| shared.submit(&body))
+            {
+                Ok(job) => json_response(201, &json!({ "id": job.id })),
+                Err((code, message)) => error_response(code, &message),
+            }
+        }
+        (Method::Get, ["jobs"], _) => {
+            let mut jobs = lock(&shared.scheduler)
+                .jobs
+                .values()
+                .cloned()
+                .collect::<Vec<_>>();
+            jobs.sort_unstable_by_key(|job| job.id);
+            let statuses = jobs.iter().map(|job| job.status_json()).collect();
+            json_response(200, &Value::Array(statuses))
+        }
+        (Method::Get, ["jobs", _], Some(job)) => json_response(200, &job.status_json()),
+        (Method::Get, ["jobs", _, "progress"], Some(job)) => {
+            stream_progress(request, &job);
+            return;
+        }
+        (Method::Post, ["jobs", _, "query"], Some(job)) => {
+            let encoding = QueryEncoding::from_request(&request);
+            let history = read_json(&mut request, shared.config.max_body_size).and_then(|body| {
+                let history = field(&body, "history").map_err(|message| (400, message))?;
+                serde_json::from_value::<Vec<usize>>(history.clone()).map_err(|e| {
+                    let message = format!("Field must be an array of integers: history ({e})");
+                    (400, message)
+                })
+            });
+            // queries run on a copy sharing the storage, so the job is not locked while querying
+            let game = lock(&job.game).as_mut().map(PostFlopGame::shared_clone);
+            match (history, game) {
+                (Err((code, message)), _) => error_response(code, &message),
+                (_, None) => error_response(409, "Job is not finished"),
+                (_, Some(Err(error))) => error_response(409, &error.to_string()),
+                (Ok(history), Some(Ok(mut game))) => {
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                        query_response(&mut game, &history, encoding)
+                    }));
+                    match result {
+                        Ok(Ok(response)) => response,
+                        Ok(Err(message)) => error_response(400, &message),
+                        Err(payload) => error_response(400, &panic_message(&*payload)),
+                    }
+                }
+            }
+        }
+        (Method::Get, ["metrics"], _) => {
+            let header = Header::from_bytes("Content-Type", "text/plain; version=0.0.4").unwrap();
+            Response::from_string(metrics_text(shared)).with_header(header)
+        }
+        (Method::Delete, ["jobs", _], Some(job)) => {
+            shared.delete(job.id);
+            json_response(200, &json!({ "id": job.id }))
+        }
+        (_, ["jobs" | "metrics"] | ["jobs", _] | ["jobs", _, "progress" | "query"], _) => {
+            error_response(405, "Method not allowed")
+        }
+        _ => error_response(404, "Not found"),
+    };
+
+    request.respond(response).ok();
+}
+
+impl SolverServer {
+    /// Binds the server to `addr` (e.g., `"127.0.0.1:8080"`), restores the jobs stored in
+    /// [`ServerConfig::state_dir`], and starts the worker threads.
+    ///
+    /// Returns [`SolverError::InvalidArgument`] if `config` is invalid, [`SolverError::Io`] if the
+    /// state directory cannot be accessed, and [`SolverError::InvalidData`] if a stored job is
+    /// corrupted.
+    pub fn bind(addr: &str, config: ServerConfig) -> Result<Self, SolverError> {
+        if config.num_workers == 0 {
+            return Err(SolverError::InvalidArgument(
+                "Number of workers must be positive".to_owned(),
+            ));
+        }
+        if config.num_handlers == 0 {
+            return Err(SolverError::InvalidArgument(
+                "Number of handlers must be positive".to_owned(),
+            ));
+        }
+
+        let store = config.state_dir.clone().map(JobStore::new).transpose()?;
+        let http = tiny_http::Server::http(addr).map_err(|e| match e.downcast_ref() {
+            Some(e) => SolverError::io("Failed to bind", e),
+            None => SolverError::Other(format!("Failed to bind: {e}")),
+        })?;
+        let shared = Arc::new(Shared {
+            config: config.clone(),
+            store,
+            scheduler: Mutex::new(Scheduler::default()),
+            job_available: Condvar::new(),
+            next_id: AtomicU64::new(1),
+            iterations_total: AtomicU64::new(0),
+        });
+        shared.restore()?;
+
+        let workers = (0..config.num_workers)
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || shared.worker())
+            })
+            .collect();
+
+        Ok(Self {
+            http,
+            shared,
+            workers: Mutex::new(workers),
+        })
+    }
+
+    /// Returns the address the server is listening on.
+    #[inline]
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.http.server_addr().to_ip()
+    }
+
+    /// Serves requests until [`shutdown`] is called.
+    ///
+    /// The requests are handled by [`ServerConfig::num_handlers`] threads. Requests arriving while
+    /// all the handlers are busy wait in a queue of [`ServerConfig::max_pending_requests`], and are
+    /// rejected with 503 if the queue is full. The pending requests are still handled after
+    /// [`shutdown`] is called, and this method returns when all of them are done.
+    ///
+    /// [`shutdown`]: SolverServer::shutdown
+    pub fn run(&self) {
+        let config = &self.shared.config;
+        let (sender, receiver) = mpsc::sync_channel::<Request>(config.max_pending_requests);
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let handlers = (0..config.num_handlers)
+            .map(|_| {
+                let shared = Arc::clone(&self.shared);
+                let receiver = Arc::clone(&receiver);
+                thread::spawn(move || {
+                    loop {
+                        let request = lock(&receiver).recv();
+                        match request {
+                            Ok(request) => handle(&shared, request),
+                            Err(_) => break,
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        while let Ok(request) = self.http.recv() {
+            if let Err(TrySendError::Full(request)) = sender.try_send(request) {
+                request.respond(error_response(503, "Server is busy")).ok();
+            }
+        }
+
+        drop(sender);
+        for handler in handlers {
+            handler.join().ok();
+        }
+    }
+
+    /// Stops serving requests, cancels all queued and running jobs, and waits for the worker
+    /// threads to exit.
+    pub fn shutdown(&self) {
+        self.shared.shutdown();
+        self.http.unblock();
+        for worker in lock(&self.workers).drain(..) {
+            worker.join().ok();
+        }
+    }
+}
+
+impl Drop for SolverServer {
+    #[inline]
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::net::TcpStream;
+
+    fn http(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+             Content-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let code = response[9..12].parse().unwrap();
+        let body = response.split("\r\n\r\n").nth(1).unwrap_or_default();
+        (code, body.to_owned())
+    }
+
+    fn start(config: ServerConfig) -> (Arc<SolverServer>, SocketAddr, JoinHandle<()>) {
+        let server = Arc::new(SolverServer::bind("127.0.0.1:0", config).unwrap());
+        let addr = server.local_addr().unwrap();
+        let handle = thread::spawn({
+            let server = Arc::clone(&server);
+            move || server.run()
+        });
+        (server, addr, handle)
+    }
+
+    fn submit(addr: SocketAddr, job: &Value) -> u64 {
+        let (code, body) = http(addr, "POST", "/jobs", &job.to_string());
+        assert_eq!(code, 201);
+        serde_json::from_str::<Value>(&body).unwrap()["id"]
+            .as_u64()
+            .unwrap()
+    }
+
+    fn status(addr: SocketAddr, id: u64) -> Value {
+        let (code, body) = http(addr, "GET", &format!("/jobs/{id}"), "");
+        assert_eq!(code, 200);
+        serde_json::from_str(&body).unwrap()
+    }
+
+    /// Reads the progress stream until the job ends and returns the last status.
+    fn wait(addr: SocketAddr, id: u64) -> Value {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "GET /jobs/{id}/progress HTTP/1.1\r\nHost: localhost\r\n\r\n"
+        )
+        .unwrap();
+        let lines = std::io::BufReader::new(stream)
+            .lines()
+            .map(Result::unwrap)
+            .skip_while(|line| !line.is_empty())
+            .take_while(|line| line != "0")
+            .filter(|line| line.starts_with('{'))
+            .collect::<Vec<_>>();
+        serde_json::from_str(lines.last().unwrap()).unwrap()
+    }
+
+    fn test_job() -> Value {
+        json!({
+            "ranges": ["66+,A8s+", "22+,A2s+"],
+            "board": "Td9d6h4s",
+            "tree_config": {
+                "starting_pot": 100,
+                "effective_stack": 100,
+                "turn_bet_sizes": [{"bet": ["50%"]}, {"bet": ["50%"]}],
+                "river_bet_sizes": [{"bet": ["50%"]}, {"bet": ["50%"]}],
+            },
+            "max_iterations": 30,
+            "target_exploitability": 0.0,
+        })
+    }
+
+    #[test]
+    fn solver_server() {
+        let (server, addr, handle) = start(ServerConfig {
+            num_workers: 2,
+            memory_budget: 64 << 20,
+            ..Default::default()
+        });
+
+        let job = test_job();
+        let id = submit(addr, &job);
+
+        // progress stream ends when the job finishes
+        let last = wait(addr, id);
+        assert_eq!(last["state"], "finished");
+        assert_eq!(last["iteration"], 30);
+
+        let (code, body) = http(
+            addr,
+            "POST",
+            &format!("/jobs/{id}/query"),
+            r#"{"history":[]}"#,
+        );
+        assert_eq!(code, 200);
+        let node = serde_json::from_str::<Value>(&body).unwrap();
+        assert_eq!(node["type"], "player");
+        assert_eq!(node["actions"].as_array().unwrap().len(), 2);
+        assert_eq!(node["strategy"].as_array().unwrap().len(), 2);
+
+        let (code, _) = http(
+            addr,
+            "POST",
+            &format!("/jobs/{id}/query"),
+            r#"{"history":[9]}"#,
+        );
+        assert_eq!(code, 400);
+
+        // invalid submissions
+        let (code, _) = http(addr, "POST", "/jobs", r#"{"ranges":["66+"]}"#);
+        assert_eq!(code, 400);
+        let mut huge = job.clone();
+        huge["ranges"] = json!(["22+,A2+,K2+,Q2+,J2+,T2+", "22+,A2+,K2+,Q2+,J2+,T2+"]);
+        huge["board"] = json!("Td9d6h");
+        huge["tree_config"]["flop_bet_sizes"] = json!([{"bet": ["50%"]}, {"bet": ["50%"]}]);
+        let (code, _) = http(addr, "POST", "/jobs", &huge.to_string());
+        assert_eq!(code, 413);
+
+        let (code, metrics) = http(addr, "GET", "/metrics", "");
+        assert_eq!(code, 200);
+        assert!(metrics.contains("# TYPE postflop_solver_jobs gauge\n"));
+        assert!(metrics.contains("\npostflop_solver_jobs{state=\"finished\"} 1\n"));
+        assert!(metrics.contains("\npostflop_solver_queue_depth 0\n"));
+        assert!(metrics.contains("\npostflop_solver_iterations_total 30\n"));
+        assert!(!metrics.contains("\npostflop_solver_job_iteration{"));
+        let used = lock(&server.shared.scheduler).memory_used;
+        assert!(metrics.contains(&format!("\npostflop_solver_memory_used_bytes {used}\n")));
+
+        // only the `jobs` collection is routed
+        let (code, _) = http(addr, "GET", &format!("/anything/{id}"), "");
+        assert_eq!(code, 404);
+        let (code, _) = http(addr, "DELETE", &format!("/metrics/{id}"), "");
+        assert_eq!(code, 404);
+
+        let (code, _) = http(addr, "DELETE", &format!("/jobs/{id}"), "");
+        assert_eq!(code, 200);
+        let (code, _) = http(addr, "GET", &format!("/jobs/{id}"), "");
+        assert_eq!(code, 404);
+        assert_eq!(lock(&server.shared.scheduler).memory_used, 0);
+
+        server.shutdown();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn metrics() {
+        let (server, addr, handle) = start(ServerConfig {
+            num_workers: 1,
+            memory_budget: 64 << 20,
+            ..Default::default()
+        });
+
+        let metrics = || {
+            let (code, metrics) = http(addr, "GET", "/metrics", "");
+            assert_eq!(code, 200);
+            metrics
+        };
+        let sample = |metrics: &str, name: &str| {
+            let prefix = format!("\npostflop_solver_{name} ");
+            let start = metrics.find(&prefix).unwrap() + prefix.len();
+            let end = start + metrics[start..].find('\n').unwrap();
+            metrics[start..end].parse::<f64>().unwrap()
+        };
+
+        let empty = metrics();
+        for name in [
+            "jobs",
+            "queue_depth",
+            "memory_used_bytes",
+            "iterations_total",
+        ] {
+            assert!(empty.contains(&format!("# HELP postflop_solver_{name} ")));
+        }
+        assert_eq!(sample(&empty, "memory_budget_bytes"), (64 << 20) as f64);
+        assert_eq!(sample(&empty, "queue_depth"), 0.0);
+        assert_eq!(sample(&empty, "iterations_total"), 0.0);
+
+        // the only worker runs the first job, and the second one waits in the queue
+        let mut job = test_job();
+        job["max_iterations"] = json!(1_000_000);
+        let running = submit(addr, &job);
+        let queued = submit(addr, &job);
+        let label = format!("{{job=\"{running}\"}}");
+        let busy = loop {
+            let metrics = metrics();
+            if metrics.contains(&format!("\npostflop_solver_job_exploitability{label} ")) {
+                break metrics;
+            }
+            thread::sleep(std::time::Duration::from_millis(10));
+        };
+        assert_eq!(sample(&busy, "jobs{state=\"running\"}"), 1.0);
+        assert_eq!(sample(&busy, "jobs{state=\"queued\"}"), 1.0);
+        assert_eq!(sample(&busy, "queue_depth"), 1.0);
+        assert!(sample(&busy, &format!("job_iteration{label}")) >= 0.0);
+        assert!(sample(&busy, &format!("job_exploitability{label}")) > 0.0);
+        assert!(!busy.contains(&format!("{{job=\"{queued}\"}}")));
+        let used = sample(&busy, "memory_used_bytes");
+        assert!(used > 0.0 && used <= (64 << 20) as f64);
+
+        // the deleted jobs are not counted and release their memory
+        for id in [queued, running] {
+            let (code, _) = http(addr, "DELETE", &format!("/jobs/{id}"), "");
+            assert_eq!(code, 200);
+        }
+        let idle = metrics();
+        assert_eq!(sample(&idle, "jobs{state=\"running\"}"), 0.0);
+        assert_eq!(sample(&idle, "jobs{state=\"queued\"}"), 0.0);
+        assert_eq!(sample(&idle, "queue_depth"), 0.0);
+        assert!(!idle.contains("\npostflop_solver_job_iteration{"));
+        assert!(sample(&idle, "iterations_total") >= sample(&busy, "iterations_total"));
+
+        server.shutdown();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn persistent_jobs() {
+        let dir = std::env::temp_dir().join(format!(
+            "postflop-solver-test-server-{}",
+            std::process::id()
+        ));
+        fs::remove_dir_all(&dir).ok();
+
+        let job = test_job();
+        let (game, ..) = parse_job(&job).unwrap();
+        let config = ServerConfig {
+            num_workers: 1,
+            memory_budget: game.memory_usage().0,
+            state_dir: Some(dir.clone()),
+            max_retries: 1,
+            checkpoint_interval: 10,
+            ..Default::default()
+        };
+
+        // the finished job occupies the whole memory budget
+        let (server, addr, handle) = start(config.clone());
+        let first = submit(addr, &job);
+        assert_eq!(wait(addr, first)["state"], "finished");
+        assert!(dir.join(format!("job-{first}.bin")).exists());
+
+        let low = submit(addr, &job);
+        let mut high_job = job.clone();
+        high_job["priority"] = json!(1);
+        let high = submit(addr, &high_job);
+        let retried = submit(addr, &job);
+
+        // the job with the higher priority is started first
+        let (code, _) = http(addr, "DELETE", &format!("/jobs/{first}"), "");
+        assert_eq!(code, 200);
+        assert!(!dir.join(format!("job-{first}.json")).exists());
+        assert_eq!(wait(addr, high)["state"], "finished");
+        assert_eq!(status(addr, low)["state"], "queued");
+
+        server.shutdown();
+        handle.join().unwrap();
+
+        // checkpoints of the queued jobs: valid for `low` and corrupted for `retried`
+        let (mut game, ..) = parse_job(&job).unwrap();
+        game.allocate_memory(false);
+        for t in 0..20 {
+            solve_step(&game, t);
+        }
+        let path = dir.join(format!("job-{low}.ckpt"));
+        save_resumable_checkpoint(&mut game, "20", path).unwrap();
+        fs::write(dir.join(format!("job-{retried}.ckpt")), "broken").unwrap();
+
+        let (server, addr, handle) = start(ServerConfig {
+            memory_budget: u64::MAX,
+            ..config
+        });
+
+        let restored = status(addr, high);
+        assert_eq!(restored["state"], "finished");
+        assert_eq!(restored["priority"], 1);
+        let query = format!("/jobs/{high}/query");
+        let (code, _) = http(addr, "POST", &query, r#"{"history":[]}"#);
+        assert_eq!(code, 200);
+
+        let resumed = wait(addr, low);
+        assert_eq!(resumed["state"], "finished");
+        assert_eq!(resumed["iteration"], 30);
+        assert_eq!(resumed["attempts"], 0);
+
+        let retried = wait(addr, retried);
+        assert_eq!(retried["state"], "finished");
+        assert_eq!(retried["iteration"], 30);
+        assert_eq!(retried["attempts"], 1);
+
+        // 10 iterations of the resumed job and 30 of the retried one
+        let (_, metrics) = http(addr, "GET", "/metrics", "");
+        assert!(metrics.contains("\npostflop_solver_iterations_total 40\n"));
+        assert!(!dir.join(format!("job-{low}.ckpt")).exists());
+
+        server.shutdown();
+        handle.join().unwrap();
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn request_body_limit() {
+        let (server, addr, handle) = start(ServerConfig {
+            max_body_size: 1024,
+            ..Default::default()
+        });
+
+        // rejected by the content length
+        let mut large = test_job();
+        large["padding"] = json!("x".repeat(1024));
+        let (code, body) = http(addr, "POST", "/jobs", &large.to_string());
+        assert_eq!(code, 413);
+        assert!(body.contains("exceeds 1024 bytes"));
+
+        // rejected while reading a chunked body
+        let body = large.to_string();
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "POST /jobs HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+             Transfer-Encoding: chunked\r\n\r\n{:x}\r\n{body}\r\n0\r\n\r\n",
+            body.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 413"));
+
+        // the limit applies to the queries as well
+        let history = format!(r#"{{"history":[{}]}}"#, vec!["0"; 600].join(","));
+        let id = submit(addr, &test_job());
+        let (code, _) = http(addr, "POST", &format!("/jobs/{id}/query"), &history);
+        assert_eq!(code, 413);
+
+        server.shutdown();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn busy_server() {
+        let (server, addr, handle) = start(ServerConfig {
+            num_handlers: 1,
+            max_pending_requests: 1,
+            ..Default::default()
+        });
+
+        let mut job = test_job();
+        job["max_iterations"] = json!(1_000_000);
+        let id = submit(addr, &job);
+
+        // the only handler is occupied by the progress stream
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "GET /jobs/{id}/progress HTTP/1.1\r\nHost: localhost\r\n\r\n"
+        )
+        .unwrap();
+        let mut reader = std::io::BufReader::new(stream);
+        let mut line = String::new();
+        while !line.starts_with('{') {
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+        }
+
+        // the first request waits in the queue, and the second one is rejected
+        let mut pending = TcpStream::connect(addr).unwrap();
+        write!(
+            pending,
+            "GET /jobs HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+        )
+        .unwrap();
+        thread::sleep(std::time::Duration::from_millis(200));
+        let (code, _) = http(addr, "GET", "/jobs", "");
+        assert_eq!(code, 503);
+
+        // shutting down cancels the job, which ends the stream and releases the handler
+        server.shutdown();
+        handle.join().unwrap();
+        let mut response = String::new();
+        pending.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+    }
+}
@@ -1,17 +1,28 @@
+use crate::convergence::*;
 use crate::interface::*;
 use crate::mutex_like::*;
+use crate::profiling::*;
 use crate::sliceop::*;
 use crate::utility::*;
+use std::convert::Infallible;
 use std::io::{self, Write};
 use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 #[cfg(feature = "custom-alloc")]
 use crate::alloc::*;
 
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+#[cfg(feature = "rayon")]
+use std::{sync::Arc, thread};
+
 struct DiscountParams {
     alpha_t: f32,
     beta_t: f32,
     gamma_t: f32,
+    current_iteration: u32,
 }
 
 impl DiscountParams {
@@ -32,6 +43,7 @@ impl DiscountParams {
             alpha_t: (pow_alpha / (pow_alpha + 1.0)) as f32,
             beta_t: 0.5,
             gamma_t: pow_gamma as f32,
+            current_iteration,
         }
     }
 }
@@ -46,11 +58,170 @@ pub fn solve<T: Game>(
     target_exploitability: f32,
     print_progress: bool,
 ) -> f32 {
+    let result = solve_with_callback(
+        game,
+        max_num_iterations,
+        &mut TargetExploitability(target_exploitability),
+        print_progress,
+        None,
+        &mut |_, _| Ok::<_, Infallible>(()),
+    );
+    let Ok(exploitability) = result;
+    exploitability
+}
+
+/// Same as [`solve`], but stops when `criterion` is converged instead of when the exploitability
+/// reaches a target (see [`ConvergenceCriterion`]).
+///
+/// This method returns the exploitability of the obtained strategy.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let card_config = CardConfig {
+///     range: ["QQ+,AK".parse().unwrap(), "JJ-99,AQs".parse().unwrap()],
+///     flop: flop_from_str("Td9d6h").unwrap(),
+///     turn: card_from_str("Qc").unwrap(),
+///     river: card_from_str("2s").unwrap(),
+///     ..Default::default()
+/// };
+/// let tree_config = TreeConfig {
+///     initial_state: BoardState::River,
+///     starting_pot: 100,
+///     effective_stack: 100,
+///     river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+///     ..Default::default()
+/// };
+/// let action_tree = ActionTree::new(tree_config).unwrap();
+/// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+/// game.allocate_memory(false);
+///
+/// // stops when the target is reached or the exploitability stalls for 30 iterations
+/// let mut criterion = AnyOf(vec![
+///     Box::new(TargetExploitability(0.1)),
+///     Box::new(ImprovementStall::new(3, 0.01)),
+/// ]);
+/// solve_until(&mut game, 1000, &mut criterion, false);
+/// ```
+pub fn solve_until<T: Game>(
+    game: &mut T,
+    max_num_iterations: u32,
+    criterion: &mut dyn ConvergenceCriterion<T>,
+    print_progress: bool,
+) -> f32 {
+    let result = solve_with_callback(
+        game,
+        max_num_iterations,
+        criterion,
+        print_progress,
+        None,
+        &mut |_, _| Ok::<_, Infallible>(()),
+    );
+    let Ok(exploitability) = result;
+    exploitability
+}
+
+/// Same as [`solve`], but stops early when `stop` is set.
+///
+/// `stop` is checked between iterations, so it can be set from another thread or a signal handler
+/// (e.g., on Ctrl-C) while solving. Once it is set, the current iteration is completed and the game
+/// is finalized with the average strategy obtained so far, so that the game can be queried and
+/// saved as usual. The number of performed iterations is available from the solve summary (see
+/// [`PostFlopGame::solve_summary`]).
+///
+/// This method returns the exploitability of the obtained strategy.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+/// use std::sync::atomic::{AtomicBool, Ordering};
+///
+/// let card_config = CardConfig {
+///     range: ["QQ+,AK".parse().unwrap(), "JJ-99,AQs".parse().unwrap()],
+///     flop: flop_from_str("Td9d6h").unwrap(),
+///     turn: card_from_str("Qc").unwrap(),
+///     river: card_from_str("2s").unwrap(),
+///     ..Default::default()
+/// };
+/// let tree_config = TreeConfig {
+///     initial_state: BoardState::River,
+///     starting_pot: 100,
+///     effective_stack: 100,
+///     river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+///     ..Default::default()
+/// };
+/// let action_tree = ActionTree::new(tree_config).unwrap();
+/// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+/// game.allocate_memory(false);
+///
+/// // e.g., set by a Ctrl-C handler
+/// let stop = AtomicBool::new(true);
+/// let exploitability = solve_with_stop(&mut game, 1000, 0.0, false, &stop);
+/// assert!(game.is_solved());
+/// assert_eq!(game.solve_summary().unwrap().exploitability, exploitability);
+/// ```
+///
+/// [`PostFlopGame::solve_summary`]: crate::PostFlopGame::solve_summary
+pub fn solve_with_stop<T: Game>(
+    game: &mut T,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    print_progress: bool,
+    stop: &AtomicBool,
+) -> f32 {
+    let result = solve_with_callback(
+        game,
+        max_num_iterations,
+        &mut TargetExploitability(target_exploitability),
+        print_progress,
+        Some(stop),
+        &mut |_, _| Ok::<_, Infallible>(()),
+    );
+    let Ok(exploitability) = result;
+    exploitability
+}
+
+/// Same as [`solve`], but calls `callback` with the game and the number of completed iterations
+/// after each iteration, and stops early when `stop` is set (see [`solve_with_stop`]).
+///
+/// The solving is aborted without finalizing the game if `callback` returns an error.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "info",
+        skip(game, criterion, print_progress, stop, callback),
+        name = "solve"
+    )
+)]
+pub(crate) fn solve_with_callback<T: Game, E: Send>(
+    game: &mut T,
+    max_num_iterations: u32,
+    criterion: &mut dyn ConvergenceCriterion<T>,
+    print_progress: bool,
+    stop: Option<&AtomicBool>,
+    callback: &mut (dyn FnMut(&mut T, u32) -> Result<(), E> + Send),
+) -> Result<f32, E> {
     assert!(!game.is_solved(), "Game is already solved");
     assert!(game.is_ready(), "Game is not ready");
 
-    let mut root = game.root();
+    #[cfg(feature = "rayon")]
+    if let Some(pool) = outer_thread_pool(game) {
+        return pool.install(|| {
+            solve_with_callback(
+                game,
+                max_num_iterations,
+                criterion,
+                print_progress,
+                stop,
+                callback,
+            )
+        });
+    }
+
     let mut exploitability = compute_exploitability(game);
+    let mut is_exploitability_updated = true;
+    let mut num_iterations = 0;
 
     if print_progress {
         print!("iteration: 0 / {max_num_iterations} ");
@@ -58,28 +229,36 @@ pub fn solve<T: Game>(
         io::stdout().flush().unwrap();
     }
 
+    let is_stopped = || stop.is_some_and(|stop| stop.load(Ordering::Relaxed));
+    let mut stopped = false;
+
     for t in 0..max_num_iterations {
-        if exploitability <= target_exploitability {
+        let progress = SolveProgress {
+            num_iterations: t,
+            exploitability,
+            is_exploitability_updated,
+        };
+        if criterion.is_converged(game, &progress) {
             break;
         }
 
-        let params = DiscountParams::new(t);
-
-        // alternating updates
-        for player in 0..2 {
-            let mut result = Vec::with_capacity(game.num_private_hands(player));
-            solve_recursive(
-                result.spare_capacity_mut(),
-                game,
-                &mut root,
-                player,
-                game.initial_weights(player ^ 1),
-                &params,
-            );
+        if is_stopped() {
+            stopped = true;
+            break;
         }
 
-        if (t + 1) % 10 == 0 || t + 1 == max_num_iterations {
+        solve_step(game, t);
+
+        is_exploitability_updated = (t + 1) % 10 == 0 || t + 1 == max_num_iterations;
+        if is_exploitability_updated {
             exploitability = compute_exploitability(game);
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                iteration = t + 1,
+                exploitability,
+                "iteration batch completed"
+            );
         }
 
         if print_progress {
@@ -87,29 +266,67 @@ pub fn solve<T: Game>(
             print!("(exploitability = {exploitability:.4e})");
             io::stdout().flush().unwrap();
         }
+
+        callback(game, t + 1)?;
+        num_iterations = t + 1;
+    }
+
+    // the exploitability is not up to date unless the last iteration was evaluated
+    if stopped && num_iterations % 10 != 0 {
+        exploitability = compute_exploitability(game);
     }
 
     if print_progress {
+        if stopped {
+            print!("\rstopped at iteration {num_iterations} ");
+            print!("(exploitability = {exploitability:.4e})");
+        }
         println!();
         io::stdout().flush().unwrap();
     }
 
+    #[cfg(feature = "tracing")]
+    if stopped {
+        tracing::info!(num_iterations, "solving stopped");
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::info!(num_iterations, exploitability, "solving completed");
+
+    game.set_solve_result(num_iterations, exploitability);
     finalize(game);
 
-    exploitability
+    Ok(exploitability)
 }
 
 /// Proceeds Discounted CFR algorithm for one iteration.
+///
+/// The strategy of a player is not updated if the iteration is skipped by the update schedule of
+/// the player (see [`UpdateSchedule`]).
 #[inline]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(game)))]
 pub fn solve_step<T: Game>(game: &T, current_iteration: u32) {
     assert!(!game.is_solved(), "Game is already solved");
     assert!(game.is_ready(), "Game is not ready");
 
+    #[cfg(feature = "rayon")]
+    if let Some(pool) = outer_thread_pool(game) {
+        return pool.install(|| solve_step(game, current_iteration));
+    }
+
+    let _timer = PhaseTimer::start(Phase::Iteration);
     let mut root = game.root();
     let params = DiscountParams::new(current_iteration);
 
     // alternating updates
     for player in 0..2 {
+        if !game
+            .update_schedule(player)
+            .is_update_iteration(current_iteration)
+        {
+            continue;
+        }
+
         let mut result = Vec::with_capacity(game.num_private_hands(player));
         solve_recursive(
             result.spare_capacity_mut(),
@@ -118,11 +335,51 @@ pub fn solve_step<T: Game>(game: &T, current_iteration: u32) {
             player,
             game.initial_weights(player ^ 1),
             &params,
+            true,
         );
     }
+
+    count_iteration();
+}
+
+/// Performs Discounted CFR algorithm only on the subtree rooted at `node` for the given number of
+/// iterations, with the reach probabilities of the players at `node` fixed to `reach`.
+///
+/// The cumulative regrets and strategies of the subtree must be reset in advance.
+pub(crate) fn solve_subtree<T: Game>(
+    game: &T,
+    node: &mut T::Node,
+    reach: &[Vec<f32>; 2],
+    num_iterations: u32,
+) {
+    #[cfg(feature = "rayon")]
+    if let Some(pool) = outer_thread_pool(game) {
+        return pool.install(|| solve_subtree(game, node, reach, num_iterations));
+    }
+
+    for t in 0..num_iterations {
+        let params = DiscountParams::new(t);
+
+        // alternating updates
+        for player in 0..2 {
+            let mut result = Vec::with_capacity(game.num_private_hands(player));
+            solve_recursive(
+                result.spare_capacity_mut(),
+                game,
+                node,
+                player,
+                &reach[player ^ 1],
+                &params,
+                false,
+            );
+        }
+    }
 }
 
 /// Recursively solves the counterfactual values.
+///
+/// If `is_root` is `true`, the subtrees of the children are solved on the thread pools assigned to
+/// them (see [`for_each_root_child`]).
 fn solve_recursive<T: Game>(
     result: &mut [MaybeUninit<f32>],
     game: &T,
@@ -130,9 +387,11 @@ fn solve_recursive<T: Game>(
     player: usize,
     cfreach: &[f32],
     params: &DiscountParams,
+    is_root: bool,
 ) {
     // return the counterfactual values when the `node` is terminal
     if node.is_terminal() {
+        let _timer = PhaseTimer::start(Phase::evaluation(game.node_street(node)));
         game.evaluate(result, node, player, cfreach);
         return;
     }
@@ -143,7 +402,7 @@ fn solve_recursive<T: Game>(
     // simply recurse when the number of actions is one
     if num_actions == 1 && !node.is_chance() {
         let child = &mut node.play(0);
-        solve_recursive(result, game, child, player, cfreach, params);
+        solve_recursive(result, game, child, player, cfreach, params, false);
         return;
     }
 
@@ -168,7 +427,7 @@ fn solve_recursive<T: Game>(
         unsafe { cfreach_updated.set_len(cfreach.len()) };
 
         // compute the counterfactual values of each action
-        for_each_child(node, |action| {
+        for_each_root_child(game, node, is_root, |action| {
             solve_recursive(
                 row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands),
                 game,
@@ -176,6 +435,7 @@ fn solve_recursive<T: Game>(
                 player,
                 &cfreach_updated,
                 params,
+                false,
             );
         });
 
@@ -215,7 +475,7 @@ fn solve_recursive<T: Game>(
     // if the current player is `player`
     else if node.player() == player {
         // compute the counterfactual values of each action
-        for_each_child(node, |action| {
+        for_each_root_child(game, node, is_root, |action| {
             solve_recursive(
                 row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands),
                 game,
@@ -223,6 +483,7 @@ fn solve_recursive<T: Game>(
                 player,
                 cfreach,
                 params,
+                false,
             );
         });
 
@@ -243,14 +504,33 @@ fn solve_recursive<T: Game>(
         let result = fma_slices_uninit(result, &strategy, &cfv_actions);
 
         if game.is_compression_enabled() {
+            let policy = game.node_compression_params(node);
+            let rescale = params
+                .current_iteration
+                .is_multiple_of(policy.rescale_interval);
+
             // update the cumulative strategy
+            let timer = PhaseTimer::start(Phase::StrategySum);
             let scale = node.strategy_scale();
-            let decoder = params.gamma_t * scale / u16::MAX as f32;
-            let cum_strategy = node.strategy_compressed_mut();
-
-            strategy.iter_mut().zip(&*cum_strategy).for_each(|(x, y)| {
-                *x += (*y as f32) * decoder;
-            });
+            let quantized = game.is_strategy_quantized();
+
+            if quantized {
+                let decoder = params.gamma_t * scale / u8::MAX as f32;
+                strategy
+                    .iter_mut()
+                    .zip(node.strategy_quantized())
+                    .for_each(|(x, y)| {
+                        *x += (*y as f32) * decoder;
+                    });
+            } else {
+                let decoder = params.gamma_t * scale / u16::MAX as f32;
+                strategy
+                    .iter_mut()
+                    .zip(node.strategy_compressed())
+                    .for_each(|(x, y)| {
+                        *x += (*y as f32) * decoder;
+                    });
+            }
 
             if !locking.is_empty() {
                 strategy.iter_mut().zip(locking).for_each(|(d, s)| {
@@ -260,10 +540,22 @@ fn solve_recursive<T: Game>(
                 });
             }
 
-            let new_scale = encode_unsigned_slice(cum_strategy, &strategy);
+            let new_scale = if quantized {
+                encode_unsigned_slice_u8(node.strategy_quantized_mut(), &strategy)
+            } else {
+                encode_unsigned_slice_with(
+                    node.strategy_compressed_mut(),
+                    &strategy,
+                    scale,
+                    policy.reserved_bits,
+                    rescale,
+                )
+            };
             node.set_strategy_scale(new_scale);
+            timer.stop();
 
             // update the cumulative regret
+            let _timer = PhaseTimer::start(Phase::RegretUpdate);
             let scale = node.regret_scale();
             let alpha_decoder = params.alpha_t * scale / i16::MAX as f32;
             let beta_decoder = params.beta_t * scale / i16::MAX as f32;
@@ -285,17 +577,26 @@ fn solve_recursive<T: Game>(
                 });
             }
 
-            let new_scale = encode_signed_slice(cum_regret, &cfv_actions);
+            let new_scale = encode_signed_slice_with(
+                cum_regret,
+                &cfv_actions,
+                scale,
+                policy.reserved_bits,
+                rescale,
+            );
             node.set_regret_scale(new_scale);
         } else {
             // update the cumulative strategy
+            let timer = PhaseTimer::start(Phase::StrategySum);
             let gamma = params.gamma_t;
             let cum_strategy = node.strategy_mut();
             cum_strategy.iter_mut().zip(&strategy).for_each(|(x, y)| {
                 *x = *x * gamma + *y;
             });
+            timer.stop();
 
             // update the cumulative regret
+            let _timer = PhaseTimer::start(Phase::RegretUpdate);
             let (alpha, beta) = (params.alpha_t, params.beta_t);
             let cum_regret = node.regrets_mut();
             cum_regret.iter_mut().zip(&*cfv_actions).for_each(|(x, y)| {
@@ -327,7 +628,7 @@ fn solve_recursive<T: Game>(
         });
 
         // compute the counterfactual values of each action
-        for_each_child(node, |action| {
+        for_each_root_child(game, node, is_root, |action| {
             solve_recursive(
                 row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands),
                 game,
@@ -335,6 +636,7 @@ fn solve_recursive<T: Game>(
                 player,
                 row(&cfreach_actions, action, row_size),
                 params,
+                false,
             );
         });
 
@@ -345,6 +647,58 @@ fn solve_recursive<T: Game>(
     }
 }
 
+/// Executes `op` for each child of `node` as [`for_each_child`] does.
+///
+/// If `is_root` is `true` and the subtrees of the children are assigned to thread pools (see
+/// [`Game::subtree_thread_pool`]), the children are grouped by their pools, and each group is run
+/// on its pool concurrently with the other groups. The children without a pool are run on the
+/// current thread pool.
+#[inline]
+fn for_each_root_child<T: Game, OP: Fn(usize) + Sync + Send>(
+    game: &T,
+    node: &T::Node,
+    is_root: bool,
+    op: OP,
+) {
+    #[cfg(feature = "rayon")]
+    if is_root {
+        let mut groups: Vec<(Option<Arc<rayon::ThreadPool>>, Vec<usize>)> = Vec::new();
+        for action in node.action_indices() {
+            let pool = game.subtree_thread_pool(action);
+            let group = groups.iter_mut().find(|(other, _)| match (other, &pool) {
+                (Some(other), Some(pool)) => Arc::ptr_eq(other, pool),
+                (other, pool) => other.is_none() && pool.is_none(),
+            });
+            match group {
+                Some((_, actions)) => actions.push(action),
+                None => groups.push((pool, vec![action])),
+            }
+        }
+
+        if groups.iter().any(|(pool, _)| pool.is_some()) {
+            let run = |actions: &[usize]| actions.par_iter().for_each(|&action| op(action));
+            thread::scope(|scope| {
+                for (pool, actions) in &groups {
+                    if let Some(pool) = pool {
+                        scope.spawn(|| pool.install(|| run(actions)));
+                    }
+                }
+                for (pool, actions) in &groups {
+                    if pool.is_none() {
+                        run(actions);
+                    }
+                }
+            });
+            return;
+        }
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    let _ = (game, is_root);
+
+    for_each_child(node, op);
+}
+
 /// Computes the strategy by regret-matching algorithm.
 #[cfg(feature = "custom-alloc")]
 #[inline]
@@ -0,0 +1,586 @@
+use crate::action_tree::*;
+use crate::bet_size::*;
+use crate::board_generator::*;
+use crate::card::*;
+use crate::error::SolverError;
+use crate::game::*;
+use crate::position::*;
+use crate::range::*;
+use crate::solver::*;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// A declarative specification of a batch of solves, loaded from TOML or YAML.
+///
+/// One game is solved for each board of `boards` with the same ranges, tree configuration, and
+/// solver settings, and the results of each game are written to `outputs`. The field names
+/// follow [`PostFlopGame::export_json`].
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let spec = SolveSpec::from_toml(r#"
+/// ranges = ["AA,KK,QQ,AK", "QQ-99,AQ,KQs"]
+/// positions = ["BB", "BTN"]
+/// boards = ["Td9d6h 2c", "Ks8d3c 4s"]
+///
+/// [tree_config]
+/// starting_pot = 100
+/// effective_stack = 200
+/// turn_bet_sizes = [{ bet = ["50%"] }, { bet = ["50%"] }]
+/// river_bet_sizes = [{ bet = ["50%", "a"] }, { bet = ["50%"], raise = ["2.5x"] }]
+///
+/// [solver]
+/// max_iterations = 100
+/// target_exploitability = 0.5
+///
+/// [[outputs]]
+/// format = "csv"
+/// path = "strategy-{board}.csv"
+/// street = "turn"
+/// "#).unwrap();
+///
+/// assert_eq!(spec.boards.len(), 2);
+/// assert_eq!(spec.positions, [Some(Position::BigBlind), Some(Position::Button)]);
+/// assert_eq!(spec.output_path(&spec.outputs[0], "Td9d6h2c"), "strategy-Td9d6h2c.csv");
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SolveSpec {
+    /// Range strings of OOP and IP.
+    pub ranges: [String; 2],
+
+    /// Positions of OOP and IP (e.g., `["BB", "BTN"]`; see [`CardConfig::positions`]).
+    #[serde(default)]
+    pub positions: [Option<Position>; 2],
+
+    /// Boards to solve (e.g., `"Td9d6h"`), each of which determines the initial street.
+    pub boards: Vec<String>,
+
+    /// Tree configuration shared by all boards.
+    pub tree_config: TreeSpec,
+
+    /// Solver settings.
+    #[serde(default)]
+    pub solver: SolverSpec,
+
+    /// Files written for each board.
+    #[serde(default)]
+    pub outputs: Vec<OutputSpec>,
+}
+
+/// Tree configuration of a [`SolveSpec`] (see [`TreeConfig`]).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TreeSpec {
+    /// Starting pot size.
+    pub starting_pot: i32,
+
+    /// Initial effective stack.
+    pub effective_stack: i32,
+
+    /// Rake rate.
+    #[serde(default)]
+    pub rake_rate: f64,
+
+    /// Rake cap.
+    #[serde(default)]
+    pub rake_cap: f64,
+
+    /// Bet sizes of the flop (OOP, IP).
+    #[serde(default)]
+    pub flop_bet_sizes: [BetSizeSpec; 2],
+
+    /// Bet sizes of the turn (OOP, IP).
+    #[serde(default)]
+    pub turn_bet_sizes: [BetSizeSpec; 2],
+
+    /// Bet sizes of the river (OOP, IP).
+    #[serde(default)]
+    pub river_bet_sizes: [BetSizeSpec; 2],
+
+    /// Donk sizes of the turn.
+    #[serde(default)]
+    pub turn_donk_sizes: Option<Vec<String>>,
+
+    /// Donk sizes of the river.
+    #[serde(default)]
+    pub river_donk_sizes: Option<Vec<String>>,
+
+    /// See [`TreeConfig::add_allin_threshold`].
+    #[serde(default)]
+    pub add_allin_threshold: f64,
+
+    /// See [`TreeConfig::force_allin_threshold`].
+    #[serde(default)]
+    pub force_allin_threshold: f64,
+
+    /// See [`TreeConfig::merging_threshold`].
+    #[serde(default)]
+    pub merging_threshold: f64,
+}
+
+/// Bet and raise sizes of a player in a [`TreeSpec`] (e.g., `["50%", "a"]`; see
+/// [`BetSizeOptions`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BetSizeSpec {
+    /// Bet sizes.
+    #[serde(default)]
+    pub bet: Vec<String>,
+
+    /// Raise sizes.
+    #[serde(default)]
+    pub raise: Vec<String>,
+}
+
+/// Solver settings of a [`SolveSpec`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SolverSpec {
+    /// Maximum number of iterations (default: 1000).
+    pub max_iterations: u32,
+
+    /// Target exploitability in chips (default: 0.5% of the starting pot).
+    pub target_exploitability: Option<f32>,
+
+    /// Whether to compress the storage (default: `false`).
+    pub enable_compression: bool,
+
+    /// Whether to skip the boards that are suit-isomorphic to an earlier board (default: `false`;
+    /// see [`group_isomorphic_boards`]).
+    pub skip_isomorphic_boards: bool,
+}
+
+impl Default for SolverSpec {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_iterations: 1000,
+            target_exploitability: None,
+            enable_compression: false,
+            skip_isomorphic_boards: false,
+        }
+    }
+}
+
+/// Format of an [`OutputSpec`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// CSV strategy table (see [`PostFlopGame::export_strategy`]).
+    Csv,
+
+    /// JSON strategy (see [`PostFlopGame::export_strategy`]).
+    Json,
+
+    /// PioSOLVER-compatible strategy (see [`PostFlopGame::export_strategy`]).
+    Pio,
+
+    /// Save file of the solved game (requires the `bincode` feature).
+    Save,
+}
+
+/// A file written for each board of a [`SolveSpec`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutputSpec {
+    /// Output format.
+    pub format: OutputFormat,
+
+    /// Output path, where `{board}` is replaced by the board (e.g., `"out/{board}.csv"`).
+    /// Relative paths are resolved against the current directory.
+    pub path: String,
+
+    /// If `Some`, only the nodes of the given street are exported (ignored by `save`).
+    #[serde(default)]
+    pub street: Option<BoardState>,
+
+    /// History of the node to start the export from (ignored by `save`).
+    #[serde(default)]
+    pub history: Vec<usize>,
+}
+
+/// Result of a board solved by [`SolveSpec::run`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpecResult {
+    /// Board in the canonical format (e.g., `"Td9d6h"`).
+    pub board: String,
+
+    /// Number of iterations performed.
+    pub num_iterations: u32,
+
+    /// Exploitability of the obtained strategy.
+    pub exploitability: f32,
+
+    /// Paths of the written files.
+    pub outputs: Vec<PathBuf>,
+
+    /// If the board was skipped as a suit-isomorphic duplicate, the board whose solution is shared
+    /// (the number of iterations and the exploitability are those of that board).
+    pub isomorphic_to: Option<String>,
+
+    /// Permutation that maps the board to `isomorphic_to` (the identity if the board was solved).
+    pub permutation: SuitPermutation,
+}
+
+/// Loads a [`SolveSpec`] from a TOML (`.toml`) or YAML (`.yaml` or `.yml`) file.
+///
+/// Returns [`SolverError::Io`] if the file cannot be read, and [`SolverError::InvalidArgument`] if
+/// the extension is unknown.
+pub fn load_solve_spec<P: AsRef<Path>>(path: P) -> Result<SolveSpec, SolverError> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| SolverError::io("Failed to read spec file", &e))?;
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => SolveSpec::from_toml(&text),
+        Some("yaml" | "yml") => SolveSpec::from_yaml(&text),
+        _ => Err(SolverError::InvalidArgument(format!(
+            "Unknown spec file extension: {}",
+            path.display()
+        ))),
+    }
+}
+
+/// Parses a board of the specification.
+#[inline]
+fn parse_board(board: &str) -> Result<Vec<Card>, SolverError> {
+    board_from_str(board)
+        .map_err(|e| SolverError::InvalidCardConfig(format!("Invalid board {board}: {e}")))
+}
+
+#[inline]
+fn join_sizes(sizes: &[String]) -> String {
+    sizes.join(",")
+}
+
+impl BetSizeSpec {
+    fn to_options(&self) -> Result<BetSizeOptions, SolverError> {
+        BetSizeOptions::try_from((
+            join_sizes(&self.bet).as_str(),
+            join_sizes(&self.raise).as_str(),
+        ))
+    }
+}
+
+impl TreeSpec {
+    /// Converts the specification into a [`TreeConfig`] for the given initial street.
+    ///
+    /// Returns [`SolverError::InvalidTreeConfig`] if a bet size is invalid.
+    pub fn to_tree_config(&self, initial_state: BoardState) -> Result<TreeConfig, SolverError> {
+        let bet_sizes = |sizes: &[BetSizeSpec; 2]| -> Result<[BetSizeOptions; 2], SolverError> {
+            Ok([sizes[0].to_options()?, sizes[1].to_options()?])
+        };
+        let donk_sizes = |sizes: &Option<Vec<String>>| {
+            sizes
+                .as_deref()
+                .map(|sizes| DonkSizeOptions::try_from(join_sizes(sizes).as_str()))
+                .transpose()
+        };
+
+        Ok(TreeConfig {
+            initial_state,
+            starting_pot: self.starting_pot,
+            effective_stack: self.effective_stack,
+            rake_rate: self.rake_rate,
+            rake_cap: self.rake_cap,
+            flop_bet_sizes: bet_sizes(&self.flop_bet_sizes)?,
+            turn_bet_sizes: bet_sizes(&self.turn_bet_sizes)?,
+            river_bet_sizes: bet_sizes(&self.river_bet_sizes)?,
+            turn_donk_sizes: donk_sizes(&self.turn_donk_sizes)?,
+            river_donk_sizes: donk_sizes(&self.river_donk_sizes)?,
+            add_allin_threshold: self.add_allin_threshold,
+            force_allin_threshold: self.force_allin_threshold,
+            merging_threshold: self.merging_threshold,
+            ..Default::default()
+        })
+    }
+}
+
+impl SolveSpec {
+    /// Parses a specification written in TOML.
+    ///
+    /// Returns [`SolverError::InvalidData`] if the text is not a valid specification, and the
+    /// error of the offending board or range if one is invalid.
+    pub fn from_toml(text: &str) -> Result<Self, SolverError> {
+        let spec: Self = toml::from_str(text)
+            .map_err(|e| SolverError::InvalidData(format!("Invalid spec: {e}")))?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Parses a specification written in YAML.
+    ///
+    /// The errors are the same as [`from_toml`](Self::from_toml).
+    pub fn from_yaml(text: &str) -> Result<Self, SolverError> {
+        let spec: Self = serde_yaml::from_str(text)
+            .map_err(|e| SolverError::InvalidData(format!("Invalid spec: {e}")))?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    fn validate(&self) -> Result<(), SolverError> {
+        if self.boards.is_empty() {
+            return Err(SolverError::InvalidData(
+                "Invalid spec: no boards".to_owned(),
+            ));
+        }
+        for board in &self.boards {
+            parse_board(board)?;
+        }
+        for range in &self.ranges {
+            range.parse::<Range>()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the path of `output` for the given board.
+    #[inline]
+    pub fn output_path(&self, output: &OutputSpec, board: &str) -> String {
+        output.path.replace("{board}", board)
+    }
+
+    /// Builds the game of the given board (not allocated yet).
+    pub fn build_game(&self, board: &str) -> Result<PostFlopGame, SolverError> {
+        let cards = parse_board(board)?;
+        let card_config = CardConfig {
+            range: [self.ranges[0].parse()?, self.ranges[1].parse()?],
+            flop: [cards[0], cards[1], cards[2]],
+            turn: cards.get(3).copied().unwrap_or(NOT_DEALT),
+            river: cards.get(4).copied().unwrap_or(NOT_DEALT),
+            positions: self.positions,
+            ..Default::default()
+        };
+
+        let initial_state = match cards.len() {
+            3 => BoardState::Flop,
+            4 => BoardState::Turn,
+            _ => BoardState::River,
+        };
+
+        let action_tree = ActionTree::new(self.tree_config.to_tree_config(initial_state)?)?;
+        PostFlopGame::with_config(card_config, action_tree)
+    }
+
+    /// Groups the boards that are suit-isomorphic to each other with the ranges of the
+    /// specification (see [`group_isomorphic_boards`]).
+    pub fn isomorphic_boards(&self) -> Result<IsomorphicBoards, SolverError> {
+        let ranges = [self.ranges[0].parse()?, self.ranges[1].parse()?];
+        let boards = self
+            .boards
+            .iter()
+            .map(|board| parse_board(board))
+            .collect::<Result<Vec<_>, _>>()?;
+        group_isomorphic_boards(&boards, &ranges, 0)
+    }
+
+    /// Solves every board in order and writes the outputs, executing the specification end to end.
+    ///
+    /// The boards are solved one at a time, so the peak memory usage is that of the largest game.
+    /// If `solver.skip_isomorphic_boards` is set, a board that is suit-isomorphic to an earlier
+    /// board is neither solved nor written, and its result refers to the earlier board. Stops at
+    /// the first error.
+    pub fn run(&self, print_progress: bool) -> Result<Vec<SpecResult>, SolverError> {
+        let mut results = Vec::<SpecResult>::with_capacity(self.boards.len());
+        let groups = if self.solver.skip_isomorphic_boards {
+            Some(self.isomorphic_boards()?)
+        } else {
+            None
+        };
+
+        for (index, board) in self.boards.iter().enumerate() {
+            if let Some(groups) = &groups
+                && groups.representatives[index] != index
+            {
+                let representative = &results[groups.representatives[index]];
+                let cards = parse_board(board)?;
+                results.push(SpecResult {
+                    board: cards_to_string(&cards),
+                    num_iterations: representative.num_iterations,
+                    exploitability: representative.exploitability,
+                    outputs: Vec::new(),
+                    isomorphic_to: Some(representative.board.clone()),
+                    permutation: groups.permutations[index],
+                });
+                continue;
+            }
+
+            let mut game = self.build_game(board)?;
+            let board = board_to_string(&game);
+            if print_progress {
+                println!("board: {board}");
+            }
+
+            game.allocate_memory(self.solver.enable_compression);
+            let target = self
+                .solver
+                .target_exploitability
+                .unwrap_or(self.tree_config.starting_pot as f32 * 0.005);
+            let exploitability = solve(
+                &mut game,
+                self.solver.max_iterations,
+                target,
+                print_progress,
+            );
+            let num_iterations = game.solve_summary().map_or(0, |s| s.num_iterations);
+
+            let mut outputs = Vec::with_capacity(self.outputs.len());
+            for output in &self.outputs {
+                let path = PathBuf::from(self.output_path(output, &board));
+                write_output(&mut game, output, &path)?;
+                outputs.push(path);
+            }
+
+            results.push(SpecResult {
+                board,
+                num_iterations,
+                exploitability,
+                outputs,
+                isomorphic_to: None,
+                permutation: SuitPermutation::IDENTITY,
+            });
+        }
+
+        Ok(results)
+    }
+}
+
+/// Returns the board of the root node of the game without separators.
+fn board_to_string(game: &PostFlopGame) -> String {
+    let config = game.card_config();
+    let cards = config
+        .flop
+        .iter()
+        .chain([&config.turn, &config.river])
+        .copied()
+        .filter(|&card| card != NOT_DEALT)
+        .collect::<Vec<_>>();
+    cards_to_string(&cards)
+}
+
+/// Returns the cards without separators.
+fn cards_to_string(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(|&card| card_to_string(card).unwrap())
+        .collect()
+}
+
+fn write_output(
+    game: &mut PostFlopGame,
+    output: &OutputSpec,
+    path: &Path,
+) -> Result<(), SolverError> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| SolverError::io("Failed to create directory", &e))?;
+    }
+
+    let format = match output.format {
+        OutputFormat::Csv => ExportFormat::Csv,
+        OutputFormat::Json => ExportFormat::Json,
+        OutputFormat::Pio => ExportFormat::Pio,
+        #[cfg(feature = "bincode")]
+        OutputFormat::Save => {
+            return crate::save_data_to_file(game, "", path, None);
+        }
+        #[cfg(not(feature = "bincode"))]
+        OutputFormat::Save => {
+            return Err(SolverError::InvalidArgument(
+                "The `bincode` feature is required to save".to_owned(),
+            ));
+        }
+    };
+
+    let options = ExportOptions {
+        format,
+        street: output.street,
+        history: output.history.clone(),
+    };
+    let file = File::create(path).map_err(|e| SolverError::io("Failed to create file", &e))?;
+    game.export_strategy(&mut BufWriter::new(file), &options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_spec() {
+        let dir =
+            std::env::temp_dir().join(format!("postflop-solver-test-spec-{}", std::process::id()));
+        let yaml = format!(
+            r#"
+ranges: ["AA,KK,QQ", "QQ-JJ,AQs"]
+boards: ["Td9d6h 2c 3s", "Kh9h4c2s"]
+tree_config:
+  starting_pot: 100
+  effective_stack: 100
+  turn_bet_sizes: [{{ bet: ["50%"] }}, {{ bet: ["50%"] }}]
+  river_bet_sizes: [{{ bet: ["50%"] }}, {{ bet: ["50%"], raise: ["a"] }}]
+solver:
+  max_iterations: 50
+outputs:
+  - format: csv
+    path: "{}/{{board}}.csv"
+  - format: json
+    path: "{}/{{board}}.json"
+    street: river
+"#,
+            dir.display(),
+            dir.display()
+        );
+
+        let spec = SolveSpec::from_yaml(&yaml).unwrap();
+        assert_eq!(spec.solver.max_iterations, 50);
+        assert_eq!(spec.solver.target_exploitability, None);
+        assert_eq!(spec.outputs[1].street, Some(BoardState::River));
+
+        let results = spec.run(false).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].board, "Td9d6h2c3s");
+        assert_eq!(results[1].board, "Kh9h4c2s");
+        for result in &results {
+            assert!(result.num_iterations <= 50);
+            assert_eq!(result.outputs.len(), 2);
+            let csv = std::fs::read_to_string(&result.outputs[0]).unwrap();
+            assert!(csv.starts_with("line,street,board,player,hand,"));
+            let json = std::fs::read_to_string(&result.outputs[1]).unwrap();
+            assert!(json.starts_with("{\"nodes\":["));
+        }
+
+        // suit-isomorphic boards
+        let mut spec = spec;
+        spec.boards.push("Th9h6d 2c 3s".to_owned());
+        spec.solver.skip_isomorphic_boards = true;
+        spec.outputs.truncate(1);
+        let results = spec.run(false).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].isomorphic_to, None);
+        assert_eq!(results[1].isomorphic_to, None);
+        assert_eq!(results[2].board, "Th9h6d2c3s");
+        assert_eq!(results[2].isomorphic_to.as_deref(), Some("Td9d6h2c3s"));
+        assert_eq!(results[2].num_iterations, results[0].num_iterations);
+        assert!(results[2].outputs.is_empty());
+        let permutation = results[2].permutation;
+        let board = board_from_str("Th9h6d2c3s").unwrap();
+        assert_eq!(
+            permutation.board(&board),
+            board_from_str("Td9d6h2c3s").unwrap()
+        );
+        std::fs::remove_dir_all(&dir).ok();
+
+        // errors
+        SolveSpec::from_toml("ranges = [\"AA\", \"KK\"]\nboards = []\n").unwrap_err();
+        let unknown = "ranges = [\"AA\", \"KK\"]\nboards = [\"Td9d6h\"]\nfoo = 1\n\
+                       [tree_config]\nstarting_pot = 100\neffective_stack = 100\n";
+        SolveSpec::from_toml(unknown).unwrap_err();
+        let invalid = "ranges = [\"AA\", \"KK\"]\nboards = [\"Td9d\"]\n\
+                       [tree_config]\nstarting_pot = 100\neffective_stack = 100\n";
+        SolveSpec::from_toml(invalid).unwrap_err();
+        load_solve_spec("spec.txt").unwrap_err();
+    }
+}
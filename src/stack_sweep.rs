@@ -0,0 +1,279 @@
+use crate::action_tree::*;
+use crate::card::*;
+use crate::error::SolverError;
+use crate::game::*;
+use crate::solver::*;
+use crate::utility::*;
+
+/// Stack depth of an entry of [`StackSweep::depths`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StackDepth {
+    /// Effective stack in chips.
+    EffectiveStack(i32),
+
+    /// Stack-to-pot ratio at the root, i.e., the effective stack divided by the starting pot.
+    Spr(f64),
+}
+
+impl StackDepth {
+    /// Returns the effective stack for `starting_pot` (the SPR is rounded to the nearest chip).
+    #[inline]
+    pub fn effective_stack(&self, starting_pot: i32) -> i32 {
+        match *self {
+            Self::EffectiveStack(stack) => stack,
+            Self::Spr(spr) => (spr * starting_pot as f64).round() as i32,
+        }
+    }
+}
+
+/// Summary of the game solved at a stack depth, returned by [`StackSweep::run`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthSummary {
+    /// Stack depth of the entry.
+    pub depth: StackDepth,
+
+    /// Effective stack of the game.
+    pub effective_stack: i32,
+
+    /// Stack-to-pot ratio at the root.
+    pub spr: f32,
+
+    /// Number of iterations performed.
+    pub num_iterations: u32,
+
+    /// Exploitability of the obtained strategy.
+    pub exploitability: f32,
+
+    /// Average expected value of the range of each player at the root, in the same convention as
+    /// [`PostFlopGame::expected_values`].
+    pub ev: [f32; 2],
+
+    /// Available actions at the root.
+    pub actions: Vec<Action>,
+
+    /// Overall frequency of each action at the root (see [`PostFlopGame::action_frequencies`]).
+    pub action_frequencies: Vec<f32>,
+}
+
+/// Solves the same configuration across a list of stack depths.
+///
+/// Each entry of `depths` is solved with `tree_config`, whose effective stack is replaced by that
+/// of the entry (in a heads-up pot, only the effective stack matters for both seats). The games
+/// share a [`TableCache`], so the card tables (e.g., the hand strengths and the suit isomorphism)
+/// are computed only once for the whole sweep. The games are solved one at a time, so the peak
+/// memory usage is that of the deepest game.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let sweep = StackSweep {
+///     card_config: CardConfig {
+///         range: ["AA,KK,87s,65s,A5s".parse().unwrap(), "QQ-99,AK,KQs,T9s".parse().unwrap()],
+///         flop: flop_from_str("Td9d6h").unwrap(),
+///         turn: card_from_str("2c").unwrap(),
+///         river: card_from_str("3s").unwrap(),
+///         ..Default::default()
+///     },
+///     tree_config: TreeConfig {
+///         initial_state: BoardState::River,
+///         starting_pot: 100,
+///         river_bet_sizes: [("50%, a", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+///         ..Default::default()
+///     },
+///     depths: vec![StackDepth::Spr(0.5), StackDepth::Spr(1.0), StackDepth::EffectiveStack(300)],
+///     max_num_iterations: 200,
+///     target_exploitability: 0.5,
+///     enable_compression: false,
+/// };
+///
+/// for summary in sweep.run().unwrap() {
+///     println!(
+///         "SPR {:.1}: EV {:.2}, {:?} -> {:?}",
+///         summary.spr, summary.ev[0], summary.actions, summary.action_frequencies
+///     );
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StackSweep {
+    /// Card configuration of the games.
+    pub card_config: CardConfig,
+
+    /// Tree configuration of the games. The effective stack is replaced by each depth.
+    pub tree_config: TreeConfig,
+
+    /// Stack depths to solve, in order.
+    pub depths: Vec<StackDepth>,
+
+    /// Maximum number of iterations of each solve.
+    pub max_num_iterations: u32,
+
+    /// Target exploitability of each solve.
+    pub target_exploitability: f32,
+
+    /// Whether to enable the compression of the storage.
+    pub enable_compression: bool,
+}
+
+impl StackSweep {
+    /// Solves every depth in order and returns the summaries.
+    ///
+    /// Stops at the first error (e.g., a depth with a non-positive effective stack). The progress
+    /// is reported as `tracing` events when the `tracing` feature is enabled.
+    #[inline]
+    pub fn run(&self) -> Result<Vec<DepthSummary>, SolverError> {
+        self.run_with(|_, _| Ok(()))
+    }
+
+    /// Same as [`run`], but calls `inspect` with each solved game (at the root node) and its
+    /// summary, e.g., to query the strategy deeper in the tree, to save the game, or to report
+    /// the progress. An error returned by `inspect` stops the sweep.
+    ///
+    /// [`run`]: #method.run
+    pub fn run_with<F>(&self, mut inspect: F) -> Result<Vec<DepthSummary>, SolverError>
+    where
+        F: FnMut(&mut PostFlopGame, &DepthSummary) -> Result<(), SolverError>,
+    {
+        let table_cache = TableCache::new(1)?;
+        let starting_pot = self.tree_config.starting_pot;
+        let mut summaries = Vec::with_capacity(self.depths.len());
+
+        for &depth in &self.depths {
+            let effective_stack = depth.effective_stack(starting_pot);
+
+            #[cfg(feature = "tracing")]
+            tracing::info!(?depth, effective_stack, "solving stack depth");
+
+            let tree_config = TreeConfig {
+                effective_stack,
+                ..self.tree_config.clone()
+            };
+            let action_tree = ActionTree::new(tree_config)?;
+            let mut game = PostFlopGame::new();
+            game.set_table_cache(Some(table_cache.clone()));
+            game.update_config(self.card_config.clone(), action_tree)?;
+
+            game.allocate_memory(self.enable_compression);
+            let exploitability = solve(
+                &mut game,
+                self.max_num_iterations,
+                self.target_exploitability,
+                false,
+            );
+            let num_iterations = game.solve_summary().map_or(0, |s| s.num_iterations);
+
+            game.cache_normalized_weights();
+            let ev = [0, 1].map(|player| {
+                compute_average(
+                    &game.expected_values(player),
+                    game.normalized_weights(player),
+                )
+            });
+
+            let summary = DepthSummary {
+                depth,
+                effective_stack,
+                spr: effective_stack as f32 / starting_pot as f32,
+                num_iterations,
+                exploitability,
+                ev,
+                actions: game.available_actions(),
+                action_frequencies: game.action_frequencies(),
+            };
+
+            inspect(&mut game, &summary)?;
+            summaries.push(summary);
+        }
+
+        Ok(summaries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range::*;
+
+    #[test]
+    fn stack_sweep() {
+        let card_config = CardConfig {
+            range: [
+                "AA,KK,87s,65s,A5s".parse().unwrap(),
+                "QQ-99,AK,KQs,T9s".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("2c").unwrap(),
+            river: card_from_str("3s").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 100,
+            river_bet_sizes: [
+                ("50%", "").try_into().unwrap(),
+                ("50%", "").try_into().unwrap(),
+            ],
+            ..Default::default()
+        };
+
+        let mut sweep = StackSweep {
+            card_config: card_config.clone(),
+            tree_config: tree_config.clone(),
+            depths: vec![StackDepth::Spr(0.25), StackDepth::EffectiveStack(200)],
+            max_num_iterations: 100,
+            target_exploitability: 0.0,
+            enable_compression: false,
+        };
+
+        let mut num_inspected = 0;
+        let summaries = sweep
+            .run_with(|game, summary| {
+                num_inspected += 1;
+                assert_eq!(game.tree_config().effective_stack, summary.effective_stack);
+                let (hits, misses) = game.table_cache().unwrap().stats();
+                assert_eq!((hits, misses), (num_inspected - 1, 1));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(num_inspected, 2);
+
+        assert_eq!(summaries[0].effective_stack, 25);
+        assert_eq!(summaries[0].spr, 0.25);
+        assert_eq!(summaries[1].effective_stack, 200);
+        assert_eq!(summaries[1].actions, [Action::Check, Action::Bet(50)]);
+
+        // the same results as the independent solves
+        for summary in &summaries {
+            let tree_config = TreeConfig {
+                effective_stack: summary.effective_stack,
+                ..tree_config.clone()
+            };
+            let action_tree = ActionTree::new(tree_config).unwrap();
+            let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+            game.allocate_memory(false);
+            let exploitability = solve(&mut game, 100, 0.0, false);
+            game.cache_normalized_weights();
+
+            assert_eq!(summary.num_iterations, 100);
+            assert!((summary.exploitability - exploitability).abs() < 1e-3);
+            let ev = compute_average(&game.expected_values(0), game.normalized_weights(0));
+            assert!((summary.ev[0] - ev).abs() < 1e-3);
+            let frequencies = game.action_frequencies();
+            for (a, b) in summary.action_frequencies.iter().zip(&frequencies) {
+                assert!((a - b).abs() < 1e-3);
+            }
+        }
+
+        // the total EV is the pot without rake
+        for summary in &summaries {
+            assert!((summary.ev[0] + summary.ev[1] - 100.0).abs() < 1e-2);
+        }
+
+        sweep.depths.push(StackDepth::Spr(0.0));
+        assert!(matches!(
+            sweep.run(),
+            Err(SolverError::InvalidTreeConfig(_))
+        ));
+    }
+}
@@ -0,0 +1,369 @@
+use crate::error::SolverError;
+use std::alloc::{self, Layout};
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+#[cfg(all(feature = "mmap", unix))]
+use std::path::Path;
+#[cfg(all(feature = "mmap", unix))]
+use std::slice;
+
+#[cfg(all(feature = "mmap", unix))]
+pub(crate) use mmap::FileMapping;
+
+/// Alignment in bytes of the storage buffers in a save file, so that a mapped file can be used in
+/// place by [`Storage::mapped`].
+pub(crate) const STORAGE_ALIGNMENT: usize = 64;
+
+/// A byte buffer used as the global storage of the game tree.
+///
+/// The buffer is either allocated on the heap or, with the `mmap` feature on Unix, backed by a
+/// memory-mapped file. In the latter case, the operating system keeps only the recently used pages
+/// in RAM and writes the others back to the file, so that trees larger than the physical memory
+/// can be solved (slowly). A buffer can also be a view into a mapped save file, whose pages are
+/// read from the file on demand.
+///
+/// A buffer can be shared between games by [`Storage::share`]; a shared buffer is read-only, and
+/// must be copied by the owner before writing to it.
+#[derive(Default)]
+pub(crate) struct Storage {
+    inner: StorageInner,
+}
+
+#[derive(Default)]
+enum StorageInner {
+    #[default]
+    Empty,
+    Heap(Vec<u8>),
+    Shared(Arc<Storage>),
+    #[cfg(all(feature = "mmap", unix))]
+    Mmap(mmap::MmapBuffer),
+    #[cfg(all(feature = "mmap", unix))]
+    Mapped {
+        mapping: Arc<FileMapping>,
+        offset: usize,
+        len: usize,
+    },
+}
+
+impl Storage {
+    /// Allocates a zero-initialized buffer of `len` bytes on the heap.
+    #[inline]
+    pub(crate) fn zeroed(len: usize) -> Self {
+        Self::from(vec![0; len])
+    }
+
+    /// Same as [`Storage::zeroed`], but returns [`SolverError::AllocationFailed`] instead of
+    /// aborting if the allocation fails.
+    pub(crate) fn try_zeroed(len: usize) -> Result<Self, SolverError> {
+        if len == 0 {
+            return Ok(Self::default());
+        }
+        let error = |message: String| SolverError::AllocationFailed {
+            required: len as u64,
+            context: "a storage buffer".to_owned(),
+            message,
+        };
+        let layout = Layout::array::<u8>(len).map_err(|e| error(e.to_string()))?;
+        let ptr = unsafe { alloc::alloc_zeroed(layout) };
+        if ptr.is_null() {
+            return Err(error("Failed to allocate memory".to_owned()));
+        }
+        // the allocation has the layout of `[u8; len]` as required by `Vec`
+        Ok(Self::from(unsafe { Vec::from_raw_parts(ptr, len, len) }))
+    }
+
+    /// Creates a zero-initialized buffer of `len` bytes backed by a file in `dir`.
+    ///
+    /// The file is removed as soon as it is mapped, so no file is left behind even if the process
+    /// is killed.
+    #[cfg(all(feature = "mmap", unix))]
+    pub(crate) fn zeroed_on_disk(len: usize, dir: &Path) -> Result<Self, SolverError> {
+        if len == 0 {
+            return Ok(Self::default());
+        }
+        Ok(Self {
+            inner: StorageInner::Mmap(mmap::MmapBuffer::new(len, dir)?),
+        })
+    }
+
+    /// Creates a view of `len` bytes at `offset` of the mapped file.
+    ///
+    /// The view is writable, but the changes are private to the process and never written back to
+    /// the file.
+    #[cfg(all(feature = "mmap", unix))]
+    pub(crate) fn mapped(mapping: &Arc<FileMapping>, offset: usize, len: usize) -> Self {
+        assert!(offset + len <= mapping.len(), "View is out of the mapping");
+        if len == 0 {
+            return Self::default();
+        }
+        Self {
+            inner: StorageInner::Mapped {
+                mapping: Arc::clone(mapping),
+                offset,
+                len,
+            },
+        }
+    }
+
+    /// Converts the buffer into a reference-counted one (if not yet) and returns another handle
+    /// to it, without copying the contents.
+    pub(crate) fn share(&mut self) -> Self {
+        let inner = match &self.inner {
+            StorageInner::Empty => StorageInner::Empty,
+            StorageInner::Shared(storage) => StorageInner::Shared(Arc::clone(storage)),
+            _ => {
+                let storage = Arc::new(Self {
+                    inner: mem::take(&mut self.inner),
+                });
+                self.inner = StorageInner::Shared(Arc::clone(&storage));
+                StorageInner::Shared(storage)
+            }
+        };
+        Self { inner }
+    }
+
+    /// Returns whether the buffer is shared with another handle, i.e., it cannot be written to.
+    #[inline]
+    pub(crate) fn is_shared(&self) -> bool {
+        match &self.inner {
+            StorageInner::Shared(storage) => Arc::strong_count(storage) > 1,
+            _ => false,
+        }
+    }
+
+    /// Returns whether `self` and `other` are handles to the same buffer.
+    #[inline]
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.inner, &other.inner) {
+            (StorageInner::Shared(a), StorageInner::Shared(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// Returns whether the buffer is backed by a file.
+    #[inline]
+    pub(crate) fn is_on_disk(&self) -> bool {
+        match &self.inner {
+            StorageInner::Shared(storage) => storage.is_on_disk(),
+            #[cfg(all(feature = "mmap", unix))]
+            StorageInner::Mmap(_) | StorageInner::Mapped { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+impl From<Vec<u8>> for Storage {
+    #[inline]
+    fn from(vec: Vec<u8>) -> Self {
+        Self {
+            inner: StorageInner::Heap(vec),
+        }
+    }
+}
+
+impl Deref for Storage {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        match &self.inner {
+            StorageInner::Empty => &[],
+            StorageInner::Heap(vec) => vec,
+            StorageInner::Shared(storage) => storage,
+            #[cfg(all(feature = "mmap", unix))]
+            StorageInner::Mmap(buffer) => buffer.as_slice(),
+            #[cfg(all(feature = "mmap", unix))]
+            StorageInner::Mapped {
+                mapping,
+                offset,
+                len,
+            } => unsafe { slice::from_raw_parts(mapping.as_ptr().add(*offset), *len) },
+        }
+    }
+}
+
+impl DerefMut for Storage {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match &mut self.inner {
+            StorageInner::Empty => &mut [],
+            StorageInner::Heap(vec) => vec,
+            StorageInner::Shared(storage) => {
+                Arc::get_mut(storage).expect("Storage is shared with another game")
+            }
+            #[cfg(all(feature = "mmap", unix))]
+            StorageInner::Mmap(buffer) => buffer.as_mut_slice(),
+            // the views of a mapping never overlap, and the mapping is copy-on-write
+            #[cfg(all(feature = "mmap", unix))]
+            StorageInner::Mapped {
+                mapping,
+                offset,
+                len,
+            } => unsafe { slice::from_raw_parts_mut(mapping.as_ptr().add(*offset), *len) },
+        }
+    }
+}
+
+#[cfg(all(feature = "mmap", unix))]
+mod mmap {
+    use crate::error::SolverError;
+    use std::fs::{self, OpenOptions};
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+    use std::ptr;
+    use std::slice;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    /// A shared memory mapping of an unlinked file.
+    pub(super) struct MmapBuffer {
+        ptr: *mut u8,
+        len: usize,
+    }
+
+    // the mapping is owned exclusively by the buffer
+    unsafe impl Send for MmapBuffer {}
+    unsafe impl Sync for MmapBuffer {}
+
+    impl MmapBuffer {
+        pub(super) fn new(len: usize, dir: &Path) -> Result<Self, SolverError> {
+            let counter = FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+            let path = dir.join(format!(
+                "postflop-solver-{}-{counter}.bin",
+                std::process::id()
+            ));
+
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&path)
+                .map_err(|e| {
+                    SolverError::io(&format!("Failed to create {}", path.display()), &e)
+                })?;
+
+            let result = file
+                .set_len(len as u64)
+                .map_err(|e| SolverError::io(&format!("Failed to extend {}", path.display()), &e))
+                .and_then(|_| {
+                    let ptr = unsafe {
+                        libc::mmap(
+                            ptr::null_mut(),
+                            len,
+                            libc::PROT_READ | libc::PROT_WRITE,
+                            libc::MAP_SHARED,
+                            file.as_raw_fd(),
+                            0,
+                        )
+                    };
+                    if ptr == libc::MAP_FAILED {
+                        Err(SolverError::io(
+                            &format!("Failed to map {}", path.display()),
+                            &std::io::Error::last_os_error(),
+                        ))
+                    } else {
+                        Ok(Self {
+                            ptr: ptr as *mut u8,
+                            len,
+                        })
+                    }
+                });
+
+            // the mapping remains valid after the file is removed
+            fs::remove_file(&path).ok();
+            result
+        }
+
+        #[inline]
+        pub(super) fn as_slice(&self) -> &[u8] {
+            unsafe { slice::from_raw_parts(self.ptr, self.len) }
+        }
+
+        #[inline]
+        pub(super) fn as_mut_slice(&mut self) -> &mut [u8] {
+            unsafe { slice::from_raw_parts_mut(self.ptr, self.len) }
+        }
+    }
+
+    impl Drop for MmapBuffer {
+        fn drop(&mut self) {
+            unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.len) };
+        }
+    }
+
+    /// A private (copy-on-write) memory mapping of an existing file.
+    pub(crate) struct FileMapping {
+        ptr: *mut u8,
+        len: usize,
+    }
+
+    // the pages are only modified through non-overlapping views
+    unsafe impl Send for FileMapping {}
+    unsafe impl Sync for FileMapping {}
+
+    impl FileMapping {
+        pub(crate) fn open(path: &Path) -> Result<Self, SolverError> {
+            let file = fs::File::open(path)
+                .map_err(|e| SolverError::io(&format!("Failed to open {}", path.display()), &e))?;
+            let len = file
+                .metadata()
+                .map_err(|e| {
+                    let context = format!("Failed to read metadata of {}", path.display());
+                    SolverError::io(&context, &e)
+                })?
+                .len() as usize;
+            if len == 0 {
+                return Err(SolverError::InvalidData(format!(
+                    "File is empty: {}",
+                    path.display()
+                )));
+            }
+
+            let ptr = unsafe {
+                libc::mmap(
+                    ptr::null_mut(),
+                    len,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                    libc::MAP_PRIVATE,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+            if ptr == libc::MAP_FAILED {
+                return Err(SolverError::io(
+                    &format!("Failed to map {}", path.display()),
+                    &std::io::Error::last_os_error(),
+                ));
+            }
+
+            Ok(Self {
+                ptr: ptr as *mut u8,
+                len,
+            })
+        }
+
+        #[inline]
+        pub(crate) fn len(&self) -> usize {
+            self.len
+        }
+
+        #[inline]
+        pub(crate) fn as_ptr(&self) -> *mut u8 {
+            self.ptr
+        }
+
+        #[inline]
+        pub(crate) fn as_slice(&self) -> &[u8] {
+            unsafe { slice::from_raw_parts(self.ptr, self.len) }
+        }
+    }
+
+    impl Drop for FileMapping {
+        fn drop(&mut self) {
+            unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.len) };
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use crate::card::*;
+use crate::range::*;
+
+/// Pairing category of a board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Pairing {
+    /// No two cards share a rank.
+    Unpaired,
+
+    /// Exactly one rank appears twice.
+    Paired,
+
+    /// Two ranks appear twice each.
+    TwoPaired,
+
+    /// One rank appears three times.
+    Trips,
+
+    /// One rank appears three times and another rank appears at least twice.
+    FullHouse,
+
+    /// One rank appears four times.
+    Quads,
+}
+
+/// Class of a turn or river card relative to the previous board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RunoutClass {
+    /// The card brings the third card of a suit (or more), so a flush becomes possible.
+    FlushCompleting,
+
+    /// The card pairs the board.
+    BoardPairing,
+
+    /// The card increases the number of straights that can be made with two hole cards.
+    StraightCompleting,
+
+    /// The card is higher than any card on the previous board.
+    Overcard,
+
+    /// None of the above.
+    Brick,
+}
+
+impl RunoutClass {
+    /// List of all runout classes.
+    pub const ALL: [Self; 5] = [
+        Self::FlushCompleting,
+        Self::BoardPairing,
+        Self::StraightCompleting,
+        Self::Overcard,
+        Self::Brick,
+    ];
+}
+
+/// Classifies the `card` dealt on the `board`.
+///
+/// If the card falls into several classes, the first one in the order of [`RunoutClass`] is
+/// returned (e.g., a card that pairs the board and completes a flush is
+/// [`RunoutClass::FlushCompleting`]).
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let board = flop_from_str("Kh9h4c").unwrap();
+/// let class = |s: &str| classify_runout(&board, card_from_str(s).unwrap());
+///
+/// assert_eq!(class("2h"), RunoutClass::FlushCompleting);
+/// assert_eq!(class("9d"), RunoutClass::BoardPairing);
+/// assert_eq!(class("Jd"), RunoutClass::StraightCompleting);
+/// assert_eq!(class("As"), RunoutClass::Overcard);
+/// assert_eq!(class("2s"), RunoutClass::Brick);
+/// ```
+pub fn classify_runout(board: &[Card], card: Card) -> RunoutClass {
+    let before = BoardTexture::new(board);
+    let mut after = before;
+    after.num_cards += 1;
+    after.suit_counts[card as usize & 3] += 1;
+    after.rank_counts[card as usize >> 2] += 1;
+
+    let rank = card >> 2;
+
+    if after.is_flush_possible() && !before.is_flush_possible() {
+        RunoutClass::FlushCompleting
+    } else if before.contains_rank(rank) {
+        RunoutClass::BoardPairing
+    } else if after.num_straight_windows() > before.num_straight_windows() {
+        RunoutClass::StraightCompleting
+    } else if before.high_rank().is_some_and(|high| rank > high) {
+        RunoutClass::Overcard
+    } else {
+        RunoutClass::Brick
+    }
+}
+
+/// A struct representing the texture of a (partial) board.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let texture = BoardTexture::new(&flop_from_str("AhKh2h").unwrap());
+/// assert!(texture.is_monotone());
+/// assert!(texture.contains_rank(12));
+/// assert_eq!(texture.pairing(), Pairing::Unpaired);
+/// assert!(!texture.is_straight_possible());
+///
+/// let texture = BoardTexture::new(&board_from_str("9c8d7d 9h").unwrap());
+/// assert!(texture.is_two_tone());
+/// assert_eq!(texture.pairing(), Pairing::Paired);
+/// assert!(texture.is_straight_possible());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardTexture {
+    /// Number of cards on the board.
+    pub num_cards: usize,
+
+    /// Number of cards of each suit (club, diamond, heart, spade).
+    pub suit_counts: [u8; 4],
+
+    /// Number of cards of each rank (2, 3, ..., A).
+    pub rank_counts: [u8; 13],
+}
+
+impl BoardTexture {
+    /// Creates a new [`BoardTexture`] of the given board.
+    ///
+    /// Panics if the board contains an invalid card.
+    #[inline]
+    pub fn new(board: &[Card]) -> Self {
+        let mut suit_counts = [0; 4];
+        let mut rank_counts = [0; 13];
+
+        for &card in board {
+            assert!(card < 52, "Invalid card: {card}");
+            suit_counts[card as usize & 3] += 1;
+            rank_counts[card as usize >> 2] += 1;
+        }
+
+        Self {
+            num_cards: board.len(),
+            suit_counts,
+            rank_counts,
+        }
+    }
+
+    /// Returns the maximum number of cards of the same suit.
+    #[inline]
+    pub fn max_suit_count(&self) -> u8 {
+        *self.suit_counts.iter().max().unwrap()
+    }
+
+    /// Returns the number of distinct suits on the board.
+    #[inline]
+    pub fn num_suits(&self) -> usize {
+        self.suit_counts.iter().filter(|&&n| n > 0).count()
+    }
+
+    /// Returns whether no two cards share a suit.
+    #[inline]
+    pub fn is_rainbow(&self) -> bool {
+        self.max_suit_count() <= 1
+    }
+
+    /// Returns whether the board has exactly two cards of the most frequent suit.
+    #[inline]
+    pub fn is_two_tone(&self) -> bool {
+        self.max_suit_count() == 2
+    }
+
+    /// Returns whether all cards share the same suit.
+    #[inline]
+    pub fn is_monotone(&self) -> bool {
+        self.num_cards > 0 && self.num_suits() == 1
+    }
+
+    /// Returns whether a flush can be made with two hole cards.
+    #[inline]
+    pub fn is_flush_possible(&self) -> bool {
+        self.max_suit_count() >= 3
+    }
+
+    /// Returns the pairing category of the board.
+    #[inline]
+    pub fn pairing(&self) -> Pairing {
+        let mut counts = self.rank_counts;
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        match (counts[0], counts[1]) {
+            (4.., _) => Pairing::Quads,
+            (3, 2..) => Pairing::FullHouse,
+            (3, _) => Pairing::Trips,
+            (2, 2) => Pairing::TwoPaired,
+            (2, _) => Pairing::Paired,
+            _ => Pairing::Unpaired,
+        }
+    }
+
+    /// Returns whether at least two cards share a rank.
+    #[inline]
+    pub fn is_paired(&self) -> bool {
+        self.pairing() != Pairing::Unpaired
+    }
+
+    /// Returns whether the board contains the given rank (2 => `0`, ..., A => `12`).
+    #[inline]
+    pub fn contains_rank(&self, rank: u8) -> bool {
+        self.rank_counts[rank as usize] > 0
+    }
+
+    /// Returns the highest rank on the board, or `None` if the board is empty.
+    #[inline]
+    pub fn high_rank(&self) -> Option<u8> {
+        (0..13).rev().find(|&rank| self.contains_rank(rank))
+    }
+
+    /// Returns the bitset of the ranks on the board (bit `i` represents rank `i`).
+    #[inline]
+    pub fn rankset(&self) -> u16 {
+        (0..13).fold(0, |acc, rank| {
+            acc | ((self.contains_rank(rank) as u16) << rank)
+        })
+    }
+
+    /// Returns a short summary of the suits, the pairing, and the highest rank of the board.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let texture = BoardTexture::new(&flop_from_str("AhKh2h").unwrap());
+    /// assert_eq!(texture.summary(), "Monotone, Unpaired, A-high");
+    ///
+    /// let texture = BoardTexture::new(&flop_from_str("7c7d2h").unwrap());
+    /// assert_eq!(texture.summary(), "Rainbow, Paired, 7-high");
+    /// ```
+    pub fn summary(&self) -> String {
+        let suits = if self.is_monotone() {
+            "Monotone"
+        } else if self.is_rainbow() {
+            "Rainbow"
+        } else if self.is_two_tone() {
+            "Two-tone"
+        } else {
+            "Flush-possible"
+        };
+
+        let high = match self.high_rank() {
+            Some(rank) => rank_to_char(rank).unwrap(),
+            None => '-',
+        };
+
+        format!("{suits}, {:?}, {high}-high", self.pairing())
+    }
+
+    /// Returns whether a straight can be made with two hole cards.
+    #[inline]
+    pub fn is_straight_possible(&self) -> bool {
+        self.num_straight_windows() > 0
+    }
+
+    /// Returns the number of straights (from 5-high to A-high) that can be made with two hole
+    /// cards.
+    #[inline]
+    pub fn num_straight_windows(&self) -> usize {
+        // the ace also plays as the lowest card
+        let rankset = self.rankset() as u32;
+        let rankset = (rankset << 1) | (rankset >> 12);
+        (0..10)
+            .filter(|i| ((rankset >> i) & 0b11111).count_ones() >= 3)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn board_texture() {
+        let tests = [
+            ("2c7d9h", false, false, Pairing::Unpaired, false),
+            ("AsKs4s", true, true, Pairing::Unpaired, false),
+            ("Ac2d5h", false, false, Pairing::Unpaired, true),
+            ("TcTdTh", false, false, Pairing::Trips, false),
+            ("9c9d4c4h", false, false, Pairing::TwoPaired, false),
+            ("KcKdKh4c4h", false, false, Pairing::FullHouse, false),
+            ("QcJcTc9c", true, true, Pairing::Unpaired, true),
+        ];
+
+        for (board, is_monotone, is_flush_possible, pairing, is_straight_possible) in tests {
+            let texture = BoardTexture::new(&cards_from_str(board).unwrap());
+            assert_eq!(texture.is_monotone(), is_monotone, "{board}");
+            assert_eq!(texture.is_flush_possible(), is_flush_possible, "{board}");
+            assert_eq!(texture.pairing(), pairing, "{board}");
+            assert_eq!(
+                texture.is_straight_possible(),
+                is_straight_possible,
+                "{board}"
+            );
+        }
+    }
+}
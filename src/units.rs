@@ -0,0 +1,119 @@
+use crate::error::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Unit of the amounts reported by the solver, such as expected values and pot shares.
+///
+/// The solver works in chips (the unit of [`TreeConfig`]), and the other units are derived from
+/// the size of the big blind in chips.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let unit = ChipUnit::BigBlinds { big_blind: 20.0 };
+/// assert_eq!(unit.convert(150.0), 7.5);
+///
+/// // $1/$2 stakes played with 20 chips per big blind
+/// let unit = ChipUnit::Currency { big_blind: 20.0, big_blind_value: 2.0 };
+/// assert_eq!(unit.convert(150.0), 15.0);
+/// assert_eq!(unit.to_chips(15.0), 150.0);
+/// ```
+///
+/// [`TreeConfig`]: crate::TreeConfig
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ChipUnit {
+    /// Chips (default).
+    #[default]
+    Chips,
+
+    /// Big blinds of `big_blind` chips.
+    BigBlinds {
+        /// Size of the big blind in chips.
+        big_blind: f64,
+    },
+
+    /// Currency, where the big blind of `big_blind` chips is worth `big_blind_value`.
+    Currency {
+        /// Size of the big blind in chips.
+        big_blind: f64,
+
+        /// Value of the big blind in the currency.
+        big_blind_value: f64,
+    },
+}
+
+impl ChipUnit {
+    /// Checks that the sizes are positive and finite.
+    ///
+    /// Returns [`SolverError::InvalidArgument`] otherwise.
+    pub fn check(&self) -> Result<(), SolverError> {
+        let (big_blind, big_blind_value) = match *self {
+            Self::Chips => return Ok(()),
+            Self::BigBlinds { big_blind } => (big_blind, 1.0),
+            Self::Currency {
+                big_blind,
+                big_blind_value,
+            } => (big_blind, big_blind_value),
+        };
+
+        if !big_blind.is_finite() || big_blind <= 0.0 {
+            return Err(SolverError::InvalidArgument(format!(
+                "Big blind must be positive and finite: {big_blind}"
+            )));
+        }
+        if !big_blind_value.is_finite() || big_blind_value <= 0.0 {
+            return Err(SolverError::InvalidArgument(format!(
+                "Value of big blind must be positive and finite: {big_blind_value}"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the value of a chip in this unit.
+    #[inline]
+    pub fn per_chip(&self) -> f64 {
+        match *self {
+            Self::Chips => 1.0,
+            Self::BigBlinds { big_blind } => 1.0 / big_blind,
+            Self::Currency {
+                big_blind,
+                big_blind_value,
+            } => big_blind_value / big_blind,
+        }
+    }
+
+    /// Converts an amount in chips into this unit.
+    #[inline]
+    pub fn convert(&self, chips: f64) -> f64 {
+        chips * self.per_chip()
+    }
+
+    /// Converts an amount in this unit into chips.
+    #[inline]
+    pub fn to_chips(&self, amount: f64) -> f64 {
+        amount / self.per_chip()
+    }
+
+    /// Converts the amounts in chips (e.g., the expected values of the private hands) into this
+    /// unit.
+    #[inline]
+    pub fn convert_all(&self, chips: &[f32]) -> Vec<f32> {
+        let per_chip = self.per_chip();
+        chips
+            .iter()
+            .map(|&x| (x as f64 * per_chip) as f32)
+            .collect()
+    }
+
+    /// Returns the share `equity` of the pot of `pot` chips after the rake of `rake` chips in this
+    /// unit.
+    #[inline]
+    pub fn pot_share(&self, pot: f64, rake: f64, equity: f64) -> f64 {
+        self.convert((pot - rake) * equity)
+    }
+}
@@ -1,8 +1,12 @@
+use crate::error::SolverError;
 use crate::interface::*;
 use crate::mutex_like::*;
+use crate::profiling::*;
+use crate::random::*;
 use crate::sliceop::*;
 use std::mem::{self, MaybeUninit};
 use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[cfg(feature = "custom-alloc")]
 use crate::alloc::*;
@@ -10,6 +14,9 @@ use crate::alloc::*;
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 
+#[cfg(feature = "rayon")]
+use std::sync::Arc;
+
 /// Executes `op` for each child potentially in parallel.
 #[cfg(feature = "rayon")]
 #[inline]
@@ -38,6 +45,15 @@ pub(crate) fn into_par_iter(range: std::ops::Range<usize>) -> std::ops::Range<us
     range
 }
 
+/// Returns the dedicated thread pool of `game` if the current thread is not its worker, i.e., if
+/// the computation needs to be moved into the pool.
+#[cfg(feature = "rayon")]
+#[inline]
+pub(crate) fn outer_thread_pool<T: Game>(game: &T) -> Option<Arc<rayon::ThreadPool>> {
+    game.thread_pool()
+        .filter(|pool| pool.current_thread_index().is_none())
+}
+
 #[inline]
 pub(crate) fn max(x: f32, y: f32) -> f32 {
     if x > y { x } else { y }
@@ -53,6 +69,88 @@ pub(crate) fn vec_memory_usage<T>(vec: &Vec<T>) -> u64 {
     vec.capacity() as u64 * mem::size_of::<T>() as u64
 }
 
+/// Returns the current time in seconds since the Unix epoch, or `0` if the time is unavailable.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Returns the current time in seconds since the Unix epoch, or `0` if the time is unavailable.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub(crate) fn unix_time() -> u64 {
+    // `std::time::SystemTime` panics on the web
+    0
+}
+
+/// Escapes a field of a CSV record if necessary.
+#[inline]
+pub(crate) fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Splits a CSV text into records, unquoting the quoted fields.
+///
+/// The delimiter is a tab if the first line contains one (as copied from a spreadsheet), and a
+/// comma otherwise. A leading byte order mark and blank records are skipped.
+pub(crate) fn csv_records(text: &str) -> Result<Vec<Vec<String>>, SolverError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let first_line = text.lines().next().unwrap_or_default();
+    let delimiter = if first_line.contains('\t') { '\t' } else { ',' };
+
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    let mut push_record = |record: Vec<String>| {
+        if record.iter().any(|field| !field.trim().is_empty()) {
+            records.push(record);
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c != '"' {
+                field.push(c);
+            } else if chars.next_if_eq(&'"').is_some() {
+                field.push('"');
+            } else {
+                in_quotes = false;
+            }
+        } else if c == '"' && field.trim().is_empty() {
+            field.clear();
+            in_quotes = true;
+        } else if c == delimiter {
+            record.push(std::mem::take(&mut field));
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' {
+                chars.next_if_eq(&'\n');
+            }
+            record.push(std::mem::take(&mut field));
+            push_record(std::mem::take(&mut record));
+        } else {
+            field.push(c);
+        }
+    }
+
+    if in_quotes {
+        return Err(SolverError::InvalidData(
+            "Unterminated quoted field".to_owned(),
+        ));
+    }
+    record.push(field);
+    push_record(record);
+
+    Ok(records)
+}
+
 /// Computes the average with given weights.
 #[inline]
 pub fn compute_average(slice: &[f32], weights: &[f32]) -> f32 {
@@ -65,6 +163,80 @@ pub fn compute_average(slice: &[f32], weights: &[f32]) -> f32 {
     (value_sum / weight_sum) as f32
 }
 
+/// Adjusts the temperature of a strategy in place, softening or sharpening it without re-solving.
+///
+/// The `strategy` has the layout of [`PostFlopGame::strategy`], i.e., the probability of the
+/// `i`-th action with the `j`-th hand is the `i * num_hands + j`-th element. The frequencies of
+/// each hand are raised to the power of `1 / temperature` and normalized again, so that:
+///
+/// - `temperature == 1.0` keeps the strategy;
+/// - `temperature > 1.0` softens the strategy toward the uniform distribution over the actions
+///   taken with a positive frequency (over all the actions if `temperature` is infinite);
+/// - `temperature < 1.0` sharpens the strategy toward the most frequent action; and
+/// - `temperature == 0.0` plays the most frequent action (ties are split evenly).
+///
+/// Hands whose frequencies are all zero are left as is.
+///
+/// # Panics
+///
+/// Panics if `temperature` is negative or NaN, or the length of `strategy` is not a multiple of
+/// `num_hands`.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// // two actions and one hand
+/// let mut strategy = vec![0.8, 0.2];
+/// apply_temperature(&mut strategy, 1, 2.0);
+/// assert!((strategy[0] - 2.0 / 3.0).abs() < 1e-6);
+///
+/// apply_temperature(&mut strategy, 1, 0.0);
+/// assert_eq!(strategy, [1.0, 0.0]);
+/// ```
+///
+/// [`PostFlopGame::strategy`]: crate::PostFlopGame::strategy
+pub fn apply_temperature(strategy: &mut [f32], num_hands: usize, temperature: f32) {
+    assert!(temperature >= 0.0, "Invalid temperature: {temperature}");
+    assert!(
+        num_hands > 0 && strategy.len().is_multiple_of(num_hands),
+        "Invalid strategy length"
+    );
+
+    if temperature == 1.0 {
+        return;
+    }
+
+    let num_actions = strategy.len() / num_hands;
+    let exponent = 1.0 / temperature as f64;
+
+    for hand in 0..num_hands {
+        let indices = (0..num_actions).map(|action| action * num_hands + hand);
+        let max = indices.clone().fold(0.0f32, |acc, i| acc.max(strategy[i]));
+        if max <= 0.0 {
+            continue;
+        }
+
+        // divides by the maximum to avoid underflows when sharpening
+        let powered = indices
+            .clone()
+            .map(|i| {
+                let ratio = (strategy[i].max(0.0) / max) as f64;
+                if temperature == 0.0 {
+                    (ratio == 1.0) as u8 as f64
+                } else {
+                    ratio.powf(exponent)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let sum = powered.iter().sum::<f64>();
+        for (i, p) in indices.zip(powered) {
+            strategy[i] = (p / sum) as f32;
+        }
+    }
+}
+
 #[inline]
 fn weighted_sum(values: &[f32], weights: &[f32]) -> f32 {
     let f = |sum: f64, (&v, &w): (&f32, &f32)| sum + v as f64 * w as f64;
@@ -123,7 +295,9 @@ fn slice_absolute_max(slice: &[f32]) -> f32 {
         slice.iter().fold(0.0, |a, x| max(a, x.abs()))
     } else {
         let mut tmp: [f32; 8] = slice[..8].try_into().unwrap();
-        tmp.iter_mut().for_each(|x| *x = x.abs());
+        for x in &mut tmp {
+            *x = x.abs();
+        }
         let mut iter = slice[8..].chunks_exact(8);
         for chunk in iter.by_ref() {
             for i in 0..8 {
@@ -223,6 +397,88 @@ pub(crate) fn encode_unsigned_slice(dst: &mut [u16], slice: &[f32]) -> f32 {
     scale
 }
 
+/// Encodes the `f32` slice to the `i16` slice under the scale-management policy, and returns the
+/// scale.
+///
+/// If `rescale` is `false` and `scale` is nonzero, `scale` is reused and the values out of range are
+/// saturated. Otherwise, the scale is recomputed leaving `reserved_bits` bits unused.
+#[inline]
+pub(crate) fn encode_signed_slice_with(
+    dst: &mut [i16],
+    slice: &[f32],
+    scale: f32,
+    reserved_bits: u8,
+    rescale: bool,
+) -> f32 {
+    let rescale = rescale || scale == 0.0;
+    if rescale && reserved_bits == 0 {
+        return encode_signed_slice(dst, slice);
+    }
+
+    let scale = if rescale {
+        slice_absolute_max(slice) * (1 << reserved_bits) as f32
+    } else {
+        scale
+    };
+    let scale_nonzero = if scale == 0.0 { 1.0 } else { scale };
+    let encoder = i16::MAX as f32 / scale_nonzero;
+    let max = i16::MAX as f32;
+    dst.iter_mut().zip(slice).for_each(|(d, s)| {
+        *d = unsafe {
+            (s * encoder)
+                .round()
+                .clamp(-max, max)
+                .to_int_unchecked::<i32>() as i16
+        }
+    });
+    scale
+}
+
+/// Encodes the `f32` slice to the `u16` slice under the scale-management policy, and returns the
+/// scale (see [`encode_signed_slice_with`]).
+#[inline]
+pub(crate) fn encode_unsigned_slice_with(
+    dst: &mut [u16],
+    slice: &[f32],
+    scale: f32,
+    reserved_bits: u8,
+    rescale: bool,
+) -> f32 {
+    let rescale = rescale || scale == 0.0;
+    if rescale && reserved_bits == 0 {
+        return encode_unsigned_slice(dst, slice);
+    }
+
+    let scale = if rescale {
+        slice_nonnegative_max(slice) * (1 << reserved_bits) as f32
+    } else {
+        scale
+    };
+    let scale_nonzero = if scale == 0.0 { 1.0 } else { scale };
+    let encoder = u16::MAX as f32 / scale_nonzero;
+    let max = u16::MAX as f32;
+    dst.iter_mut().zip(slice).for_each(|(d, s)| {
+        *d = unsafe {
+            (s * encoder + 0.49999997)
+                .min(max)
+                .to_int_unchecked::<i32>() as u16
+        }
+    });
+    scale
+}
+
+/// Encodes the `f32` slice to the `u8` slice, and returns the scale.
+#[inline]
+pub(crate) fn encode_unsigned_slice_u8(dst: &mut [u8], slice: &[f32]) -> f32 {
+    let scale = slice_nonnegative_max(slice);
+    let scale_nonzero = if scale == 0.0 { 1.0 } else { scale };
+    let encoder = u8::MAX as f32 / scale_nonzero;
+    dst.iter_mut().zip(slice).for_each(|(d, s)| {
+        *d = unsafe { (s * encoder + 0.49999997).to_int_unchecked::<i32>() as u8 }
+    });
+    scale
+}
+
 /// Applies the given swap to the given slice.
 #[inline]
 pub(crate) fn apply_swap<T>(slice: &mut [T], swap_list: &[(u16, u16)]) {
@@ -239,11 +495,56 @@ pub(crate) fn apply_swap<T>(slice: &mut [T], swap_list: &[(u16, u16)]) {
 /// Finalizes the solving process.
 #[inline]
 pub fn finalize<T: Game>(game: &mut T) {
+    finalize_internal(game, None);
+}
+
+/// Finalizes the solving process while reporting the progress.
+///
+/// `progress` is called with the number of processed nodes and the total number of nodes to
+/// process, at most about 1000 times. Because the counterfactual values are computed in parallel,
+/// `progress` may be called concurrently from the worker threads.
+#[inline]
+pub fn finalize_with_progress<T: Game>(game: &mut T, progress: impl Fn(usize, usize) + Sync) {
+    finalize_internal(game, Some(&progress));
+}
+
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "info", skip_all, name = "finalize")
+)]
+fn finalize_internal<T: Game>(game: &mut T, progress: Option<&(dyn Fn(usize, usize) + Sync)>) {
     assert!(!game.is_solved(), "Game is already solved");
     assert!(game.is_ready(), "Game is not ready");
 
+    #[cfg(feature = "rayon")]
+    if let Some(pool) = outer_thread_pool(game) {
+        return pool.install(|| finalize_internal(game, progress));
+    }
+
+    let _timer = PhaseTimer::start(Phase::Finalize);
+
     // compute the expected values and save them
-    for player in 0..2 {
+    let progress = progress.map(|callback| Progress::new(game, callback));
+    save_cfvalues_internal(game, progress.as_ref());
+
+    // set the game solved
+    game.set_solved();
+
+    // free buffer
+    #[cfg(all(feature = "custom-alloc", feature = "rayon"))]
+    rayon::broadcast(|_| free_custom_alloc_buffer());
+    #[cfg(all(feature = "custom-alloc", not(feature = "rayon")))]
+    free_custom_alloc_buffer();
+}
+
+/// Computes the counterfactual values of the current strategy and saves them into the nodes.
+pub(crate) fn save_cfvalues<T: Game>(game: &T) {
+    save_cfvalues_internal(game, None);
+}
+
+fn save_cfvalues_internal<T: Game>(game: &T, progress: Option<&Progress<'_>>) {
+    // the two players write to disjoint buffers, so they can be processed in parallel
+    let save = |player: usize| {
         let mut cfvalues = Vec::with_capacity(game.num_private_hands(player));
         compute_cfvalue_recursive(
             cfvalues.spare_capacity_mut(),
@@ -252,17 +553,55 @@ pub fn finalize<T: Game>(game: &mut T) {
             player,
             game.initial_weights(player ^ 1),
             true,
+            progress,
         );
+    };
+
+    #[cfg(feature = "rayon")]
+    rayon::join(|| save(0), || save(1));
+
+    #[cfg(not(feature = "rayon"))]
+    (0..2).for_each(save);
+}
+
+/// Progress reporter of the finalization.
+struct Progress<'a> {
+    callback: &'a (dyn Fn(usize, usize) + Sync),
+    done: AtomicUsize,
+    total: usize,
+    step: usize,
+}
+
+impl<'a> Progress<'a> {
+    fn new<T: Game>(game: &T, callback: &'a (dyn Fn(usize, usize) + Sync)) -> Self {
+        // each non-terminal node is processed once for each player
+        let total = 2 * count_nonterminal_nodes(&*game.root());
+        Self {
+            callback,
+            done: AtomicUsize::new(0),
+            total,
+            step: (total / 1000).max(1),
+        }
     }
 
-    // set the game solved
-    game.set_solved();
+    #[inline]
+    fn increment(&self) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        if done.is_multiple_of(self.step) || done == self.total {
+            (self.callback)(done, self.total);
+        }
+    }
+}
 
-    // free buffer
-    #[cfg(all(feature = "custom-alloc", feature = "rayon"))]
-    rayon::broadcast(|_| free_custom_alloc_buffer());
-    #[cfg(all(feature = "custom-alloc", not(feature = "rayon")))]
-    free_custom_alloc_buffer();
+/// Counts the non-terminal nodes in the subtree.
+fn count_nonterminal_nodes<T: GameNode>(node: &T) -> usize {
+    if node.is_terminal() {
+        return 0;
+    }
+    1 + node
+        .action_indices()
+        .map(|action| count_nonterminal_nodes(&*node.play(action)))
+        .sum::<usize>()
 }
 
 /// Computes the exploitability of the current strategy.
@@ -270,6 +609,11 @@ pub fn finalize<T: Game>(game: &mut T) {
 pub fn compute_exploitability<T: Game>(game: &T) -> f32 {
     assert!(game.is_ready() || game.is_solved(), "Game is not ready");
 
+    #[cfg(feature = "rayon")]
+    if let Some(pool) = outer_thread_pool(game) {
+        return pool.install(|| compute_exploitability(game));
+    }
+
     let mes_ev = compute_mes_ev(game);
     if game.is_zero_sum() && !game.is_raked() {
         (mes_ev[0] + mes_ev[1]) * 0.5
@@ -287,6 +631,11 @@ pub fn compute_exploitability<T: Game>(game: &T) -> f32 {
 pub fn compute_current_ev<T: Game>(game: &T) -> [f32; 2] {
     assert!(game.is_ready() || game.is_solved(), "Game is not ready");
 
+    #[cfg(feature = "rayon")]
+    if let Some(pool) = outer_thread_pool(game) {
+        return pool.install(|| compute_current_ev(game));
+    }
+
     let mut cfvalues = [
         Vec::with_capacity(game.num_private_hands(0)),
         Vec::with_capacity(game.num_private_hands(1)),
@@ -302,6 +651,7 @@ pub fn compute_current_ev<T: Game>(game: &T) -> [f32; 2] {
             player,
             reach[player ^ 1],
             false,
+            None,
         );
         unsafe { cfvalues[player].set_len(game.num_private_hands(player)) };
     }
@@ -318,6 +668,11 @@ pub fn compute_current_ev<T: Game>(game: &T) -> [f32; 2] {
 pub fn compute_mes_ev<T: Game>(game: &T) -> [f32; 2] {
     assert!(game.is_ready() || game.is_solved(), "Game is not ready");
 
+    #[cfg(feature = "rayon")]
+    if let Some(pool) = outer_thread_pool(game) {
+        return pool.install(|| compute_mes_ev(game));
+    }
+
     let mut cfvalues = [
         Vec::with_capacity(game.num_private_hands(0)),
         Vec::with_capacity(game.num_private_hands(1)),
@@ -332,6 +687,7 @@ pub fn compute_mes_ev<T: Game>(game: &T) -> [f32; 2] {
             &game.root(),
             player,
             reach[player ^ 1],
+            None,
         );
         unsafe { cfvalues[player].set_len(game.num_private_hands(player)) };
     }
@@ -340,6 +696,243 @@ pub fn compute_mes_ev<T: Game>(game: &T) -> [f32; 2] {
     [get_sum(0), get_sum(1)]
 }
 
+/// Estimate of the exploitability returned by [`estimate_exploitability`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExploitabilityEstimate {
+    /// Estimate of a lower bound of the exploitability (the mean of the samples).
+    pub lower_bound: f32,
+
+    /// Estimate of an upper bound of the exploitability (the mean of the samples).
+    pub upper_bound: f32,
+
+    /// Standard errors of `lower_bound` and `upper_bound` (infinity if there is only one sample).
+    pub standard_error: [f32; 2],
+
+    /// Number of samples.
+    pub num_samples: usize,
+}
+
+impl ExploitabilityEstimate {
+    /// Returns the midpoint of `lower_bound` and `upper_bound` as a point estimate.
+    #[inline]
+    pub fn exploitability(&self) -> f32 {
+        (self.lower_bound + self.upper_bound) * 0.5
+    }
+
+    /// Returns the interval that contains the exploitability with the confidence of the given
+    /// z-score (e.g., `1.96` for 95%), i.e., the bounds widened by their standard errors.
+    #[inline]
+    pub fn confidence_interval(&self, z: f32) -> (f32, f32) {
+        (
+            self.lower_bound - z * self.standard_error[0],
+            self.upper_bound + z * self.standard_error[1],
+        )
+    }
+}
+
+/// Estimates the exploitability of the current strategy by sampling the runouts.
+///
+/// Instead of all the cards like [`compute_exploitability`], each sample computes the best
+/// response on `cards_per_chance` cards chosen uniformly at random (with replacement) at every
+/// chance node, so the cost of a sample is a small fraction of the exact computation when the
+/// turn or the river is to be dealt. Because the best response would choose its actions before a
+/// chance node from the noisy values of the sampled cards, each sample evaluates two estimates:
+///
+/// - an upper bound, where the actions are chosen from the same cards that evaluate them (as if
+///   the best response could peek at the sampled cards); and
+/// - a lower bound, where the actions are chosen from the values of independently sampled cards,
+///   which evaluates a valid (but suboptimal) strategy without bias.
+///
+/// The exploitability is between the expectations of the two, and the gap shrinks as
+/// `cards_per_chance` increases. Games without chance nodes are computed exactly. The samples are
+/// computed in parallel, each with the random number generator seeded with `seed` plus its index,
+/// so the same `seed` always produces the same estimate regardless of the number of threads (see
+/// [`estimate_exploitability_with_rng`] to draw the seed from a generator).
+///
+/// **Time complexity:** *O*(`num_samples` * #(nodes of a sampled tree) * #(private hands)), where
+/// the sampled tree has `2 * cards_per_chance` children at each chance node.
+///
+/// # Panics
+///
+/// Panics if `num_samples` or `cards_per_chance` is zero, or the game is not ready.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let card_config = CardConfig {
+///     range: ["QQ+,AK".parse().unwrap(), "JJ-99,AQs".parse().unwrap()],
+///     flop: flop_from_str("Td9d6h").unwrap(),
+///     ..Default::default()
+/// };
+/// let tree_config = TreeConfig {
+///     initial_state: BoardState::Flop,
+///     starting_pot: 100,
+///     effective_stack: 100,
+///     flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+///     turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+///     river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+///     ..Default::default()
+/// };
+/// let action_tree = ActionTree::new(tree_config).unwrap();
+/// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+/// game.allocate_memory(false);
+///
+/// for t in 0..20 {
+///     solve_step(&game, t);
+///     if (t + 1) % 10 == 0 {
+///         let estimate = estimate_exploitability(&game, 20, 4, t as u64);
+///         let (_lower, _upper) = estimate.confidence_interval(1.96);
+///     }
+/// }
+/// ```
+pub fn estimate_exploitability<T: Game>(
+    game: &T,
+    num_samples: usize,
+    cards_per_chance: usize,
+    seed: u64,
+) -> ExploitabilityEstimate {
+    assert!(num_samples > 0, "Number of samples must be positive");
+    assert!(
+        cards_per_chance > 0,
+        "Number of cards per chance must be positive"
+    );
+    assert!(game.is_ready() || game.is_solved(), "Game is not ready");
+
+    #[cfg(feature = "rayon")]
+    if let Some(pool) = outer_thread_pool(game) {
+        return pool.install(|| estimate_exploitability(game, num_samples, cards_per_chance, seed));
+    }
+
+    let is_zero_sum = game.is_zero_sum() && !game.is_raked();
+
+    // (lower bound, upper bound)
+    let samples = into_par_iter(0..num_samples)
+        .map(|i| {
+            let mut sampler = ChanceSampler {
+                rng: fastrand::Rng::with_seed(seed.wrapping_add(i as u64)),
+                cards_per_chance,
+            };
+            let mut ev = |player: usize, best: bool| {
+                let [lower, upper] = compute_sampled_cfv_recursive(
+                    game,
+                    &game.root(),
+                    player,
+                    game.initial_weights(player ^ 1),
+                    best,
+                    &mut sampler,
+                );
+                let weights = game.initial_weights(player);
+                [weighted_sum(&lower, weights), weighted_sum(&upper, weights)]
+            };
+
+            let mes_ev = [ev(0, true), ev(1, true)];
+            let current_ev = if is_zero_sum {
+                [0.0; 2]
+            } else {
+                [ev(0, false)[0], ev(1, false)[0]]
+            };
+            [0, 1].map(|bound| {
+                let gain = (mes_ev[0][bound] - current_ev[0]) + (mes_ev[1][bound] - current_ev[1]);
+                gain as f64 * 0.5
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let n = num_samples as f64;
+    let stats = |bound: usize| {
+        let mean = samples.iter().map(|x| x[bound]).sum::<f64>() / n;
+        let standard_error = if num_samples == 1 {
+            f64::INFINITY
+        } else {
+            let sum_sq = samples.iter().map(|x| (x[bound] - mean).powi(2));
+            (sum_sq.sum::<f64>() / (n - 1.0) / n).sqrt()
+        };
+        (mean as f32, standard_error as f32)
+    };
+
+    let (lower_bound, lower_error) = stats(0);
+    let (upper_bound, upper_error) = stats(1);
+    ExploitabilityEstimate {
+        lower_bound,
+        upper_bound,
+        standard_error: [lower_error, upper_error],
+        num_samples,
+    }
+}
+
+/// Same as [`estimate_exploitability`], but seeds the samples from `rng`.
+///
+/// Only the seed is drawn from `rng` (one [`RandomSource::next_u64`] call), so that the estimate
+/// does not depend on the number of threads.
+#[inline]
+pub fn estimate_exploitability_with_rng<T: Game, R: RandomSource + ?Sized>(
+    game: &T,
+    num_samples: usize,
+    cards_per_chance: usize,
+    rng: &mut R,
+) -> ExploitabilityEstimate {
+    estimate_exploitability(game, num_samples, cards_per_chance, rng.next_u64())
+}
+
+/// Random sampler of the chances used by [`estimate_exploitability`].
+struct ChanceSampler {
+    rng: fastrand::Rng,
+    cards_per_chance: usize,
+}
+
+/// Callback of [`compute_best_cfvalues`] called with a node and the counterfactual values of its
+/// actions.
+pub(crate) type RecordFn<'a, T> = dyn Fn(&<T as Game>::Node, &[f32]) + Sync + 'a;
+
+/// Computes the counterfactual values of the current strategy of `player` at the root node.
+pub(crate) fn compute_current_cfvalues<T: Game>(game: &T, player: usize) -> Vec<f32> {
+    #[cfg(feature = "rayon")]
+    if let Some(pool) = outer_thread_pool(game) {
+        return pool.install(|| compute_current_cfvalues(game, player));
+    }
+
+    let mut cfvalues = Vec::with_capacity(game.num_private_hands(player));
+    compute_cfvalue_recursive(
+        cfvalues.spare_capacity_mut(),
+        game,
+        &mut game.root(),
+        player,
+        game.initial_weights(player ^ 1),
+        false,
+        None,
+    );
+    unsafe { cfvalues.set_len(game.num_private_hands(player)) };
+    cfvalues
+}
+
+/// Computes the counterfactual values of the best response of `player` at the root node.
+///
+/// If given, `record` is called with each node of `player` that has two or more actions and the
+/// counterfactual values of its actions. It may be called concurrently from the worker threads.
+pub(crate) fn compute_best_cfvalues<T: Game>(
+    game: &T,
+    player: usize,
+    record: Option<&RecordFn<'_, T>>,
+) -> Vec<f32> {
+    #[cfg(feature = "rayon")]
+    if let Some(pool) = outer_thread_pool(game) {
+        return pool.install(|| compute_best_cfvalues(game, player, record));
+    }
+
+    let mut cfvalues = Vec::with_capacity(game.num_private_hands(player));
+    compute_best_cfv_recursive(
+        cfvalues.spare_capacity_mut(),
+        game,
+        &game.root(),
+        player,
+        game.initial_weights(player ^ 1),
+        record,
+    );
+    unsafe { cfvalues.set_len(game.num_private_hands(player)) };
+    cfvalues
+}
+
 /// The recursive helper function for computing the counterfactual values of the given strategy.
 fn compute_cfvalue_recursive<T: Game>(
     result: &mut [MaybeUninit<f32>],
@@ -348,6 +941,7 @@ fn compute_cfvalue_recursive<T: Game>(
     player: usize,
     cfreach: &[f32],
     save_cfvalues: bool,
+    progress: Option<&Progress<'_>>,
 ) {
     // terminal node
     if node.is_terminal() {
@@ -387,6 +981,7 @@ fn compute_cfvalue_recursive<T: Game>(
                 player,
                 &cfreach_updated,
                 save_cfvalues,
+                progress,
             );
         });
 
@@ -445,18 +1040,23 @@ fn compute_cfvalue_recursive<T: Game>(
                 player,
                 cfreach,
                 save_cfvalues,
+                progress,
             );
         });
 
         // obtain the strategy
         #[cfg(feature = "custom-alloc")]
-        let mut strategy = if game.is_compression_enabled() {
+        let mut strategy = if game.is_strategy_quantized() {
+            normalized_strategy_compressed_custom_alloc(node.strategy_quantized(), num_actions)
+        } else if game.is_compression_enabled() {
             normalized_strategy_compressed_custom_alloc(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy_custom_alloc(node.strategy(), num_actions)
         };
         #[cfg(not(feature = "custom-alloc"))]
-        let mut strategy = if game.is_compression_enabled() {
+        let mut strategy = if game.is_strategy_quantized() {
+            normalized_strategy_compressed(node.strategy_quantized(), num_actions)
+        } else if game.is_compression_enabled() {
             normalized_strategy_compressed(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy(node.strategy(), num_actions)
@@ -491,17 +1091,22 @@ fn compute_cfvalue_recursive<T: Game>(
             player,
             cfreach,
             save_cfvalues,
+            progress,
         );
     } else {
         // obtain the strategy
         #[cfg(feature = "custom-alloc")]
-        let mut cfreach_actions = if game.is_compression_enabled() {
+        let mut cfreach_actions = if game.is_strategy_quantized() {
+            normalized_strategy_compressed_custom_alloc(node.strategy_quantized(), num_actions)
+        } else if game.is_compression_enabled() {
             normalized_strategy_compressed_custom_alloc(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy_custom_alloc(node.strategy(), num_actions)
         };
         #[cfg(not(feature = "custom-alloc"))]
-        let mut cfreach_actions = if game.is_compression_enabled() {
+        let mut cfreach_actions = if game.is_strategy_quantized() {
+            normalized_strategy_compressed(node.strategy_quantized(), num_actions)
+        } else if game.is_compression_enabled() {
             normalized_strategy_compressed(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy(node.strategy(), num_actions)
@@ -526,6 +1131,7 @@ fn compute_cfvalue_recursive<T: Game>(
                 player,
                 row(&cfreach_actions, action, row_size),
                 save_cfvalues,
+                progress,
             );
         });
 
@@ -545,6 +1151,10 @@ fn compute_cfvalue_recursive<T: Game>(
             node.cfvalues_ip_mut().copy_from_slice(result);
         }
     }
+
+    if let Some(progress) = progress {
+        progress.increment();
+    }
 }
 
 /// The recursive helper function for computing the counterfactual values of best response.
@@ -554,6 +1164,7 @@ fn compute_best_cfv_recursive<T: Game>(
     node: &T::Node,
     player: usize,
     cfreach: &[f32],
+    record: Option<&RecordFn<'_, T>>,
 ) {
     // terminal node
     if node.is_terminal() {
@@ -567,7 +1178,7 @@ fn compute_best_cfv_recursive<T: Game>(
     // simply recurse when the number of actions is one
     if num_actions == 1 && !node.is_chance() {
         let child = &node.play(0);
-        compute_best_cfv_recursive(result, game, child, player, cfreach);
+        compute_best_cfv_recursive(result, game, child, player, cfreach, record);
         return;
     }
 
@@ -599,6 +1210,7 @@ fn compute_best_cfv_recursive<T: Game>(
                 &node.play(action),
                 player,
                 &cfreach_updated,
+                record,
             );
         });
 
@@ -645,6 +1257,7 @@ fn compute_best_cfv_recursive<T: Game>(
                 &node.play(action),
                 player,
                 cfreach,
+                record,
             );
         });
 
@@ -652,6 +1265,10 @@ fn compute_best_cfv_recursive<T: Game>(
         let mut cfv_actions = cfv_actions.lock();
         unsafe { cfv_actions.set_len(num_actions * num_hands) };
 
+        if let Some(record) = record {
+            record(node, &cfv_actions);
+        }
+
         if locking.is_empty() {
             // compute element-wise maximum (take the best response)
             max_slices_uninit(result, &cfv_actions);
@@ -664,13 +1281,17 @@ fn compute_best_cfv_recursive<T: Game>(
     else {
         // obtain the strategy
         #[cfg(feature = "custom-alloc")]
-        let mut cfreach_actions = if game.is_compression_enabled() {
+        let mut cfreach_actions = if game.is_strategy_quantized() {
+            normalized_strategy_compressed_custom_alloc(node.strategy_quantized(), num_actions)
+        } else if game.is_compression_enabled() {
             normalized_strategy_compressed_custom_alloc(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy_custom_alloc(node.strategy(), num_actions)
         };
         #[cfg(not(feature = "custom-alloc"))]
-        let mut cfreach_actions = if game.is_compression_enabled() {
+        let mut cfreach_actions = if game.is_strategy_quantized() {
+            normalized_strategy_compressed(node.strategy_quantized(), num_actions)
+        } else if game.is_compression_enabled() {
             normalized_strategy_compressed(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy(node.strategy(), num_actions)
@@ -694,6 +1315,7 @@ fn compute_best_cfv_recursive<T: Game>(
                 &node.play(action),
                 player,
                 row(&cfreach_actions, action, row_size),
+                record,
             );
         });
 
@@ -704,6 +1326,162 @@ fn compute_best_cfv_recursive<T: Game>(
     }
 }
 
+/// The recursive helper function for [`estimate_exploitability`], which computes the
+/// counterfactual values of the best response (if `best`) or the current strategy on the sampled
+/// cards.
+///
+/// Returns the values for the lower bound and the upper bound. The values for the upper bound
+/// choose the actions of the best response by themselves, while those for the lower bound follow
+/// the actions chosen by the values for the upper bound, which are computed on independently
+/// sampled cards. Without `best`, both are unbiased estimates of the current strategy.
+fn compute_sampled_cfv_recursive<T: Game>(
+    game: &T,
+    node: &T::Node,
+    player: usize,
+    cfreach: &[f32],
+    best: bool,
+    sampler: &mut ChanceSampler,
+) -> [Vec<f32>; 2] {
+    let num_hands = game.num_private_hands(player);
+
+    // terminal node
+    if node.is_terminal() {
+        let mut result = Vec::with_capacity(num_hands);
+        game.evaluate(result.spare_capacity_mut(), node, player, cfreach);
+        unsafe { result.set_len(num_hands) };
+        return [result.clone(), result];
+    }
+
+    let num_actions = node.num_actions();
+
+    // chance node: the two bounds use independent cards among the explicit and isomorphic chances
+    if node.is_chance() {
+        let isomorphic_chances = game.isomorphic_chances(node);
+        let num_chances = num_actions + isomorphic_chances.len();
+        let scale = 1.0 / game.chance_factor(node) as f32;
+        let cfreach_updated = cfreach.iter().map(|&r| r * scale).collect::<Vec<_>>();
+        let weight = num_chances as f32 / sampler.cards_per_chance as f32;
+
+        return [0, 1].map(|bound| {
+            let mut result = vec![0.0; num_hands];
+            for _ in 0..sampler.cards_per_chance {
+                let index = sampler.rng.usize(..num_chances);
+                let action = match index.checked_sub(num_actions) {
+                    Some(i) => isomorphic_chances[i] as usize,
+                    None => index,
+                };
+
+                let child = node.play(action);
+                let mut values = mem::take(
+                    &mut compute_sampled_cfv_recursive(
+                        game,
+                        &child,
+                        player,
+                        &cfreach_updated,
+                        best,
+                        sampler,
+                    )[bound],
+                );
+
+                if let Some(i) = index.checked_sub(num_actions) {
+                    apply_swap(&mut values, &game.isomorphic_swap(node, i)[player]);
+                }
+
+                result
+                    .iter_mut()
+                    .zip(&values)
+                    .for_each(|(r, &v)| *r += v * weight);
+            }
+            result
+        });
+    }
+
+    // simply recurse when the number of actions is one
+    if num_actions == 1 {
+        let child = node.play(0);
+        return compute_sampled_cfv_recursive(game, &child, player, cfreach, best, sampler);
+    }
+
+    let mut strategy = if game.is_strategy_quantized() {
+        normalized_strategy_compressed(node.strategy_quantized(), num_actions)
+    } else if game.is_compression_enabled() {
+        normalized_strategy_compressed(node.strategy_compressed(), num_actions)
+    } else {
+        normalized_strategy(node.strategy(), num_actions)
+    };
+    let locking = game.locking_strategy(node);
+
+    // player node
+    if node.player() == player {
+        let mut cfv_actions = [0, 1].map(|_| Vec::with_capacity(num_actions * num_hands));
+        for action in node.action_indices() {
+            let child = node.play(action);
+            let values =
+                compute_sampled_cfv_recursive(game, &child, player, cfreach, best, sampler);
+            for (dst, src) in cfv_actions.iter_mut().zip(values) {
+                dst.extend(src);
+            }
+        }
+
+        if !best {
+            apply_locking_strategy(&mut strategy, locking);
+            return cfv_actions.map(|cfv_actions| {
+                let mut result = Vec::with_capacity(num_hands);
+                fma_slices_uninit(result.spare_capacity_mut(), &strategy, &cfv_actions);
+                unsafe { result.set_len(num_hands) };
+                result
+            });
+        }
+
+        // the actions are chosen by the values for the upper bound
+        let [lower, upper] = &cfv_actions;
+        let mut result = [vec![0.0; num_hands], vec![0.0; num_hands]];
+        for hand in 0..num_hands {
+            let is_locked = !locking.is_empty() && locking[hand] >= 0.0;
+            if is_locked {
+                for action in 0..num_actions {
+                    let index = action * num_hands + hand;
+                    result[0][hand] += locking[index] * lower[index];
+                    result[1][hand] += locking[index] * upper[index];
+                }
+            } else {
+                let best_action = (1..num_actions).fold(0, |best, action| {
+                    if upper[action * num_hands + hand] > upper[best * num_hands + hand] {
+                        action
+                    } else {
+                        best
+                    }
+                });
+                result[0][hand] = lower[best_action * num_hands + hand];
+                result[1][hand] = upper[best_action * num_hands + hand];
+            }
+        }
+        result
+    }
+    // opponent node
+    else {
+        apply_locking_strategy(&mut strategy, locking);
+
+        // update the reach probabilities
+        let row_size = cfreach.len();
+        strategy.chunks_exact_mut(row_size).for_each(|row| {
+            mul_slice(row, cfreach);
+        });
+
+        let mut result = [vec![0.0; num_hands], vec![0.0; num_hands]];
+        for action in node.action_indices() {
+            let child = node.play(action);
+            let cfreach_action = row(&strategy, action, row_size);
+            let values =
+                compute_sampled_cfv_recursive(game, &child, player, cfreach_action, best, sampler);
+            for (dst, src) in result.iter_mut().zip(&values) {
+                dst.iter_mut().zip(src).for_each(|(d, &s)| *d += s);
+            }
+        }
+        result
+    }
+}
+
 #[cfg(feature = "custom-alloc")]
 #[inline]
 pub(crate) fn normalized_strategy_custom_alloc(
@@ -754,15 +1532,15 @@ pub(crate) fn normalized_strategy(strategy: &[f32], num_actions: usize) -> Vec<f
 
 #[cfg(feature = "custom-alloc")]
 #[inline]
-pub(crate) fn normalized_strategy_compressed_custom_alloc(
-    strategy: &[u16],
+pub(crate) fn normalized_strategy_compressed_custom_alloc<T: Copy + Into<f32>>(
+    strategy: &[T],
     num_actions: usize,
 ) -> Vec<f32, StackAlloc> {
     let mut normalized = Vec::with_capacity_in(strategy.len(), StackAlloc);
     let uninit = normalized.spare_capacity_mut();
 
     uninit.iter_mut().zip(strategy).for_each(|(n, s)| {
-        n.write(*s as f32);
+        n.write((*s).into());
     });
     unsafe { normalized.set_len(strategy.len()) };
 
@@ -780,12 +1558,15 @@ pub(crate) fn normalized_strategy_compressed_custom_alloc(
 }
 
 #[inline]
-pub(crate) fn normalized_strategy_compressed(strategy: &[u16], num_actions: usize) -> Vec<f32> {
+pub(crate) fn normalized_strategy_compressed<T: Copy + Into<f32>>(
+    strategy: &[T],
+    num_actions: usize,
+) -> Vec<f32> {
     let mut normalized = Vec::with_capacity(strategy.len());
     let uninit = normalized.spare_capacity_mut();
 
     uninit.iter_mut().zip(strategy).for_each(|(n, s)| {
-        n.write(*s as f32);
+        n.write((*s).into());
     });
     unsafe { normalized.set_len(strategy.len()) };
 
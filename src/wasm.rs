@@ -0,0 +1,378 @@
+use crate::action_tree::*;
+use crate::bet_size::*;
+use crate::card::*;
+use crate::game::*;
+use crate::interface::*;
+use crate::range::*;
+use crate::solver::*;
+use crate::utility::*;
+use js_sys::Function;
+use wasm_bindgen::prelude::*;
+
+#[inline]
+fn js_error(message: impl std::fmt::Display) -> JsError {
+    JsError::new(&message.to_string())
+}
+
+#[inline]
+fn parse_street(street: &str) -> Result<usize, JsError> {
+    match street {
+        "flop" => Ok(0),
+        "turn" => Ok(1),
+        "river" => Ok(2),
+        _ => Err(js_error(format!("Invalid street: {street}"))),
+    }
+}
+
+#[inline]
+fn parse_player(player: u32) -> Result<usize, JsError> {
+    match player {
+        0 | 1 => Ok(player as usize),
+        _ => Err(js_error(format!("Invalid player: {player}"))),
+    }
+}
+
+/// Tree configuration of [`WasmGame`], exported to JavaScript as `TreeConfig`.
+///
+/// The bet sizes are specified in the same format as [`BetSizeOptions`]. The initial street is
+/// determined by the board passed to the game.
+#[wasm_bindgen(js_name = TreeConfig)]
+pub struct WasmTreeConfig {
+    config: TreeConfig,
+}
+
+#[wasm_bindgen(js_class = TreeConfig)]
+impl WasmTreeConfig {
+    /// Creates a tree configuration without bet sizes.
+    #[wasm_bindgen(constructor)]
+    pub fn new(starting_pot: i32, effective_stack: i32) -> Self {
+        Self {
+            config: TreeConfig {
+                starting_pot,
+                effective_stack,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Sets the rake rate and the rake cap.
+    #[wasm_bindgen(js_name = setRake)]
+    pub fn set_rake(&mut self, rate: f64, cap: f64) {
+        self.config.rake_rate = rate;
+        self.config.rake_cap = cap;
+    }
+
+    /// Sets the bet and raise sizes of `player` (`0` for OOP, `1` for IP) on `street` (`"flop"`,
+    /// `"turn"`, or `"river"`).
+    #[wasm_bindgen(js_name = setBetSizes)]
+    pub fn set_bet_sizes(
+        &mut self,
+        street: &str,
+        player: u32,
+        bet: &str,
+        raise: &str,
+    ) -> Result<(), JsError> {
+        let street = parse_street(street)?;
+        let player = parse_player(player)?;
+        let sizes = BetSizeOptions::try_from((bet, raise)).map_err(js_error)?;
+        match street {
+            0 => self.config.flop_bet_sizes[player] = sizes,
+            1 => self.config.turn_bet_sizes[player] = sizes,
+            _ => self.config.river_bet_sizes[player] = sizes,
+        }
+        Ok(())
+    }
+
+    /// Sets the donk bet sizes on `street` (`"turn"` or `"river"`). An empty string disables them.
+    #[wasm_bindgen(js_name = setDonkSizes)]
+    pub fn set_donk_sizes(&mut self, street: &str, donk: &str) -> Result<(), JsError> {
+        let sizes = if donk.is_empty() {
+            None
+        } else {
+            Some(DonkSizeOptions::try_from(donk).map_err(js_error)?)
+        };
+        match parse_street(street)? {
+            1 => self.config.turn_donk_sizes = sizes,
+            2 => self.config.river_donk_sizes = sizes,
+            _ => return Err(js_error("Donk sizes can only be set on the turn and river")),
+        }
+        Ok(())
+    }
+
+    /// Sets the thresholds of adding and forcing all-in actions and merging bet sizes (see
+    /// [`TreeConfig`]).
+    #[wasm_bindgen(js_name = setThresholds)]
+    pub fn set_thresholds(&mut self, add_allin: f64, force_allin: f64, merging: f64) {
+        self.config.add_allin_threshold = add_allin;
+        self.config.force_allin_threshold = force_allin;
+        self.config.merging_threshold = merging;
+    }
+}
+
+/// A [`PostFlopGame`] exported to JavaScript as `PostFlopGame`.
+///
+/// The methods mirror those of [`PostFlopGame`] with JavaScript-friendly types: the players are
+/// `0` (OOP) and `1` (IP), the cards are numbers as in [`Card`], and the strategies and values are
+/// returned as `Float32Array`s. Errors are thrown as JavaScript `Error`s.
+#[wasm_bindgen(js_name = PostFlopGame)]
+pub struct WasmGame {
+    game: PostFlopGame,
+}
+
+#[wasm_bindgen(js_class = PostFlopGame)]
+impl WasmGame {
+    /// Creates a game from the ranges, the board (e.g., `"Td9d6h"`), and the tree configuration.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        oop_range: &str,
+        ip_range: &str,
+        board: &str,
+        tree_config: &WasmTreeConfig,
+    ) -> Result<Self, JsError> {
+        let board = board_from_str(board).map_err(|e| js_error(e.to_string()))?;
+        let card_config = CardConfig {
+            range: [
+                oop_range.parse().map_err(js_error)?,
+                ip_range.parse().map_err(js_error)?,
+            ],
+            flop: [board[0], board[1], board[2]],
+            turn: board.get(3).copied().unwrap_or(NOT_DEALT),
+            river: board.get(4).copied().unwrap_or(NOT_DEALT),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: match board.len() {
+                3 => BoardState::Flop,
+                4 => BoardState::Turn,
+                _ => BoardState::River,
+            },
+            ..tree_config.config.clone()
+        };
+
+        let action_tree = ActionTree::new(tree_config).map_err(js_error)?;
+        let game = PostFlopGame::with_config(card_config, action_tree).map_err(js_error)?;
+        Ok(Self { game })
+    }
+
+    /// Returns the estimated memory usage in bytes without and with compression.
+    #[wasm_bindgen(js_name = memoryUsage)]
+    pub fn memory_usage(&self) -> Vec<f64> {
+        let (uncompressed, compressed) = self.game.memory_usage();
+        vec![uncompressed as f64, compressed as f64]
+    }
+
+    /// Allocates the memory for solving.
+    #[wasm_bindgen(js_name = allocateMemory)]
+    pub fn allocate_memory(&mut self, enable_compression: bool) {
+        self.game.allocate_memory(enable_compression);
+    }
+
+    /// Solves the game until `max_num_iterations` or `target_exploitability` is reached, and
+    /// returns the exploitability.
+    ///
+    /// If `progress` is given, it is called as `progress(iteration, exploitability)` after each
+    /// iteration; the exploitability is updated every 10 iterations. The solving is aborted
+    /// without finalizing the game if `progress` throws.
+    pub fn solve(
+        &mut self,
+        max_num_iterations: u32,
+        target_exploitability: f32,
+        progress: Option<Function>,
+    ) -> Result<f32, JsError> {
+        if !self.game.is_ready() || self.game.is_solved() {
+            return Err(js_error("Game is not ready to be solved"));
+        }
+
+        let mut exploitability = compute_exploitability(&self.game);
+        let mut num_iterations = 0;
+
+        for t in 0..max_num_iterations {
+            if exploitability <= target_exploitability {
+                break;
+            }
+
+            solve_step(&self.game, t);
+            if (t + 1) % 10 == 0 || t + 1 == max_num_iterations {
+                exploitability = compute_exploitability(&self.game);
+            }
+
+            if let Some(progress) = &progress {
+                let iteration = JsValue::from(t + 1);
+                progress
+                    .call2(&JsValue::NULL, &iteration, &JsValue::from(exploitability))
+                    .map_err(|e| js_error(format!("Progress callback failed: {e:?}")))?;
+            }
+
+            num_iterations = t + 1;
+        }
+
+        self.game.set_solve_result(num_iterations, exploitability);
+        finalize(&mut self.game);
+        Ok(exploitability)
+    }
+
+    /// Performs one iteration of the solving (see [`solve_step`]). Call `finalize` after the last
+    /// iteration.
+    #[wasm_bindgen(js_name = solveStep)]
+    pub fn solve_step(&self, current_iteration: u32) -> Result<(), JsError> {
+        if !self.game.is_ready() || self.game.is_solved() {
+            return Err(js_error("Game is not ready to be solved"));
+        }
+        solve_step(&self.game, current_iteration);
+        Ok(())
+    }
+
+    /// Computes the exploitability of the current strategy.
+    pub fn exploitability(&self) -> f32 {
+        compute_exploitability(&self.game)
+    }
+
+    /// Finalizes the solving (see [`finalize`]).
+    pub fn finalize(&mut self) -> Result<(), JsError> {
+        if !self.game.is_ready() || self.game.is_solved() {
+            return Err(js_error("Game is not ready to be finalized"));
+        }
+        finalize(&mut self.game);
+        Ok(())
+    }
+
+    /// Returns whether the game is solved.
+    #[wasm_bindgen(js_name = isSolved)]
+    pub fn is_solved(&self) -> bool {
+        self.game.is_solved()
+    }
+
+    /// Moves back to the root node.
+    #[wasm_bindgen(js_name = backToRoot)]
+    pub fn back_to_root(&mut self) {
+        self.game.back_to_root();
+    }
+
+    /// Returns the history of the actions taken from the root node.
+    pub fn history(&self) -> Vec<u32> {
+        self.game.history().iter().map(|&a| a as u32).collect()
+    }
+
+    /// Moves to the node reached by `history` from the root node.
+    #[wasm_bindgen(js_name = applyHistory)]
+    pub fn apply_history(&mut self, history: &[u32]) {
+        let history = history.iter().map(|&a| a as usize).collect::<Vec<_>>();
+        self.game.apply_history(&history);
+    }
+
+    /// Plays `action` (an index of `availableActions`, or a card at a chance node).
+    pub fn play(&mut self, action: u32) {
+        self.game.play(action as usize);
+    }
+
+    /// Undoes the last action played, moving back to the parent node.
+    pub fn undo(&mut self) -> Result<(), JsError> {
+        self.game.undo().map_err(js_error)
+    }
+
+    /// Replays the next action undone by `undo`.
+    pub fn redo(&mut self) -> Result<(), JsError> {
+        self.game.redo().map_err(js_error)
+    }
+
+    /// Returns whether the current node is a terminal node.
+    #[wasm_bindgen(js_name = isTerminalNode)]
+    pub fn is_terminal_node(&self) -> bool {
+        self.game.is_terminal_node()
+    }
+
+    /// Returns whether the current node is a chance node.
+    #[wasm_bindgen(js_name = isChanceNode)]
+    pub fn is_chance_node(&self) -> bool {
+        self.game.is_chance_node()
+    }
+
+    /// Returns the player to act at the current node.
+    #[wasm_bindgen(js_name = currentPlayer)]
+    pub fn current_player(&self) -> u32 {
+        self.game.current_player() as u32
+    }
+
+    /// Returns the actions available at the current node as strings (e.g., `"Bet(50)"`).
+    #[wasm_bindgen(js_name = availableActions)]
+    pub fn available_actions(&self) -> Vec<String> {
+        self.game
+            .available_actions()
+            .iter()
+            .map(|action| action.to_string())
+            .collect()
+    }
+
+    /// Returns the cards that can be dealt at the current chance node.
+    #[wasm_bindgen(js_name = possibleCards)]
+    pub fn possible_cards(&self) -> Vec<u8> {
+        let mask = self.game.possible_cards();
+        (0..52).filter(|&card| mask & (1 << card) != 0).collect()
+    }
+
+    /// Returns the board cards at the current node.
+    #[wasm_bindgen(js_name = currentBoard)]
+    pub fn current_board(&self) -> Vec<u8> {
+        self.game.current_board()
+    }
+
+    /// Returns the private hands of `player` as strings (e.g., `"AsKs"`).
+    #[wasm_bindgen(js_name = privateCards)]
+    pub fn private_cards(&self, player: u32) -> Result<Vec<String>, JsError> {
+        let player = parse_player(player)?;
+        holes_to_strings(self.game.private_cards(player)).map_err(js_error)
+    }
+
+    /// Computes the normalized weights at the current node, which must be called before the
+    /// queries below.
+    #[wasm_bindgen(js_name = cacheNormalizedWeights)]
+    pub fn cache_normalized_weights(&mut self) {
+        self.game.cache_normalized_weights();
+    }
+
+    /// Returns the weights of the private hands of `player` at the current node.
+    pub fn weights(&self, player: u32) -> Result<Vec<f32>, JsError> {
+        Ok(self.game.weights(parse_player(player)?).to_vec())
+    }
+
+    /// Returns the normalized weights of the private hands of `player` at the current node.
+    #[wasm_bindgen(js_name = normalizedWeights)]
+    pub fn normalized_weights(&self, player: u32) -> Result<Vec<f32>, JsError> {
+        Ok(self.game.normalized_weights(parse_player(player)?).to_vec())
+    }
+
+    /// Returns the equity of the private hands of `player` at the current node.
+    pub fn equity(&self, player: u32) -> Result<Vec<f32>, JsError> {
+        Ok(self.game.equity(parse_player(player)?))
+    }
+
+    /// Returns the expected values of the private hands of `player` at the current node.
+    #[wasm_bindgen(js_name = expectedValues)]
+    pub fn expected_values(&self, player: u32) -> Result<Vec<f32>, JsError> {
+        Ok(self.game.expected_values(parse_player(player)?))
+    }
+
+    /// Returns the strategy at the current node, laid out as in [`PostFlopGame::strategy`].
+    pub fn strategy(&self) -> Vec<f32> {
+        self.game.strategy()
+    }
+
+    /// Returns the total bet amounts of the players at the current node.
+    #[wasm_bindgen(js_name = totalBetAmount)]
+    pub fn total_bet_amount(&self) -> Vec<i32> {
+        self.game.total_bet_amount().to_vec()
+    }
+
+    /// Locks the strategy at the current node (see [`PostFlopGame::lock_current_strategy`]).
+    #[wasm_bindgen(js_name = lockCurrentStrategy)]
+    pub fn lock_current_strategy(&mut self, strategy: &[f32]) {
+        self.game.lock_current_strategy(strategy);
+    }
+
+    /// Unlocks the strategy at the current node.
+    #[wasm_bindgen(js_name = unlockCurrentStrategy)]
+    pub fn unlock_current_strategy(&mut self) {
+        self.game.unlock_current_strategy();
+    }
+}